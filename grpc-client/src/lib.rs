@@ -1,13 +1,21 @@
-use std::{sync::Arc, time::Duration};
+mod connect;
+
+use std::{fmt, sync::Arc, time::Duration};
 
 use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
 use solana_transaction_status::TransactionStatusMeta;
 use tokio::sync::Mutex;
-use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient, Interceptor};
+use tonic_health::pb::health_client::HealthClient;
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient, Interceptor, InterceptorXToken};
+pub use yellowstone_grpc_client::{GeyserGrpcBuilderError, GeyserGrpcClientError};
 use yellowstone_grpc_proto::{
-    convert_from::create_tx_with_meta, geyser::SubscribeUpdateTransaction,
+    convert_from::create_tx_with_meta,
+    geyser::{geyser_client::GeyserClient, SubscribeUpdateTransaction},
+    tonic::transport::Certificate,
 };
 
+pub use connect::{Connector, IpVersion, ProxyUrl};
+
 use anyhow::Error;
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -51,27 +59,134 @@ impl From<SubscribeUpdateTransaction> for TransactionFormat {
     }
 }
 
+/// connection failure by the step that failed
+#[derive(Debug)]
+pub enum ConnectError {
+    /// the endpoint is not a valid uri
+    Endpoint(GeyserGrpcBuilderError),
+    /// the x-token is not a valid header value, the token itself is left out
+    InvalidXToken,
+    /// the tls settings were rejected, e.g. an unparsable ca certificate
+    Tls(GeyserGrpcBuilderError),
+    /// connecting failed: refused, timed out, the proxy refused the tunnel
+    /// or the tls handshake failed. `route` is the proxy and address family
+    /// that were tried, see `Connector::route`
+    Connect { route: String, source: GeyserGrpcBuilderError },
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Endpoint(e) => write!(f, "invalid endpoint: {}", e),
+            Self::InvalidXToken => write!(f, "x-token is not a valid header value"),
+            Self::Tls(e) => write!(f, "invalid tls configuration: {}", e),
+            Self::Connect { route, source } => write!(f, "connection failed ({}): {}", route, source),
+        }
+    }
+}
+
+impl std::error::Error for ConnectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Endpoint(e) | Self::Tls(e) | Self::Connect { source: e, .. } => Some(e),
+            Self::InvalidXToken => None,
+        }
+    }
+}
+
 pub struct YellowstoneGrpc {
     endpoint: String,
     x_token: Option<String>,
+    ca_cert_pem: Option<Vec<u8>>,
+    connect_timeout: Duration,
+    max_message_size: Option<usize>,
+    connector: Connector,
 }
 
 impl YellowstoneGrpc {
     pub fn new(endpoint: String, x_token: Option<String>) -> Self {
-        Self { endpoint, x_token }
+        Self {
+            endpoint,
+            x_token,
+            ca_cert_pem: None,
+            connect_timeout: Duration::from_secs(10),
+            max_message_size: None,
+            connector: Connector::default(),
+        }
+    }
+
+    /// trust this pem encoded ca certificate instead of the native roots
+    pub fn with_ca_cert(mut self, pem: Vec<u8>) -> Self {
+        self.ca_cert_pem = Some(pem);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// largest update the client decodes, tonic's 4 MiB default otherwise
+    pub fn with_max_message_size(mut self, limit: usize) -> Self {
+        self.max_message_size = Some(limit);
+        self
+    }
+
+    /// tunnel through this http proxy with CONNECT, tls to the endpoint
+    /// still runs end to end
+    pub fn with_proxy(mut self, proxy: ProxyUrl) -> Self {
+        self.connector = Connector::new(Some(proxy), self.connector.ip_version());
+        self
+    }
+
+    /// only connect over this address family, to the proxy when one is set
+    pub fn with_ip_version(mut self, ip_version: IpVersion) -> Self {
+        self.connector = self.connector.with_ip_version(ip_version);
+        self
+    }
+
+    /// the proxy and address family connections go through
+    pub fn route(&self) -> String {
+        self.connector.route()
     }
 
     pub async fn build_client(
         self,
-    ) -> Result<Arc<Mutex<GeyserGrpcClient<impl Interceptor>>>, AppError> {
-        let client = GeyserGrpcClient::build_from_shared(self.endpoint)?
-            .x_token(self.x_token)?
-            .tls_config(ClientTlsConfig::new().with_native_roots())?
-            .connect_timeout(Duration::from_secs(10))
+    ) -> Result<Arc<Mutex<GeyserGrpcClient<impl Interceptor>>>, ConnectError> {
+        let tls_config = match self.ca_cert_pem {
+            Some(pem) => ClientTlsConfig::new().ca_certificate(Certificate::from_pem(pem)),
+            None => ClientTlsConfig::new().with_native_roots(),
+        };
+        let mut builder = GeyserGrpcClient::build_from_shared(self.endpoint)
+            .map_err(ConnectError::Endpoint)?
+            .x_token(self.x_token)
+            .map_err(|_| ConnectError::InvalidXToken)?
+            .tls_config(tls_config)
+            .map_err(ConnectError::Tls)?
+            .connect_timeout(self.connect_timeout)
             .keep_alive_while_idle(true)
-            .timeout(Duration::from_secs(60))
-            .connect()
-            .await?;
+            .timeout(Duration::from_secs(60));
+        if let Some(limit) = self.max_message_size {
+            builder = builder.max_decoding_message_size(limit);
+        }
+
+        // the builder has no connector hook, the channel is opened here with
+        // its endpoint and the clients are assembled the way it would
+        let route = self.connector.route();
+        let channel = builder
+            .endpoint
+            .connect_with_connector(self.connector)
+            .await
+            .map_err(|e| ConnectError::Connect { route, source: GeyserGrpcBuilderError::TonicError(e) })?;
+        let interceptor = InterceptorXToken {
+            x_token: builder.x_token,
+            x_request_snapshot: builder.x_request_snapshot,
+        };
+        let mut geyser = GeyserClient::with_interceptor(channel.clone(), interceptor.clone());
+        if let Some(limit) = builder.max_decoding_message_size {
+            geyser = geyser.max_decoding_message_size(limit);
+        }
+        let client = GeyserGrpcClient::new(HealthClient::with_interceptor(channel, interceptor), geyser);
         Ok(Arc::new(Mutex::new(client)))
     }
 }
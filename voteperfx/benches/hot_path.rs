@@ -0,0 +1,218 @@
+//! benchmarks for the vote parsing and confirmation path
+//!
+//! save a baseline before a change and compare against it afterwards:
+//!
+//!     cargo bench --bench hot_path -- --save-baseline main
+//!     cargo bench --bench hot_path -- --baseline main
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use voteperfx::dashboard::DashboardRenderer;
+use voteperfx::events::EventBus;
+use voteperfx::memory::MemoryUsage;
+use voteperfx::performance::{PerformanceStats, Slot};
+use voteperfx::test_support::{make_confirmed_vote, make_pending_vote, signature, signature_base58, tower_sync_data};
+use voteperfx::theme::{DashboardTheme, Theme};
+use voteperfx::vote_tracker::{parse_vote_instruction_data, SignatureCache, VoteTracker};
+use voteperfx::vote_tracker_actor::VoteTrackerHandle;
+
+const VOTE_ACCOUNT: &str = "Vote111111111111111111111111111111111111111";
+
+/// pending votes in the tracker while confirming
+const PENDING_VOTES: u64 = 10_000;
+
+/// a full tower: 31 lockouts from the oldest, the newest slot a new vote
+fn full_tower(newest: Slot) -> Vec<(Slot, u32)> {
+    (0..31u32).rev().map(|depth| (newest - depth as Slot, depth + 1)).collect()
+}
+
+/// latency of the n-th synthetic vote, mostly optimal with a tail
+fn latency(n: u64) -> u64 {
+    match n % 20 {
+        0 => 6,
+        1 | 2 => 3,
+        _ => 1 + n % 2,
+    }
+}
+
+fn parse_tower_sync(c: &mut Criterion) {
+    let data = tower_sync_data(&full_tower(300_000_031));
+    c.bench_function("parse_vote_instruction_data/tower_sync", |b| {
+        b.iter(|| parse_vote_instruction_data(black_box(&data)))
+    });
+}
+
+fn signature_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("signature_cache");
+    let hit = signature(1);
+    let mut cache = SignatureCache::new(2048);
+    cache.get_or_insert(&hit);
+    group.bench_function("hit", |b| b.iter(|| cache.get_or_insert(black_box(&hit))));
+
+    // distinct signatures, the cache stays full and evicts on every insert
+    let misses: Vec<Vec<u8>> = (0..4096).map(|seed| signature(seed + 2)).collect();
+    let mut cache = SignatureCache::new(2048);
+    let mut next = misses.iter().cycle();
+    group.bench_function("miss", |b| {
+        b.iter(|| cache.get_or_insert(black_box(next.next().unwrap_or(&hit))))
+    });
+    group.finish();
+}
+
+fn tracker_with_pending(count: u64) -> VoteTracker {
+    let mut tracker = VoteTracker::new();
+    for n in 0..count {
+        let slot = 300_000_000 + n;
+        tracker.add_pending_vote(make_pending_vote(n, slot + 1, &[slot]));
+    }
+    tracker
+}
+
+fn confirm_vote(c: &mut Criterion) {
+    let signatures: Vec<Arc<String>> = (0..PENDING_VOTES).map(|n| Arc::new(signature_base58(n))).collect();
+    let mut group = c.benchmark_group("vote_tracker");
+    group.throughput(Throughput::Elements(PENDING_VOTES));
+    // confirms every pending vote in turn, from 10k pending down to none
+    group.bench_function("confirm_vote/10k_pending", |b| {
+        b.iter_batched(
+            || tracker_with_pending(PENDING_VOTES),
+            |mut tracker| {
+                for (n, signature) in signatures.iter().enumerate() {
+                    let slot = 300_000_000 + n as Slot;
+                    black_box(tracker.confirm_vote(signature.clone(), slot, slot + latency(n as u64), None));
+                }
+                tracker
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// votes added and confirmed through the actor per iteration
+const ACTOR_VOTES: u64 = 10_000;
+
+/// adds and confirms `ACTOR_VOTES` votes through the actor, `senders`
+/// transaction tasks and as many block tasks sending at the same time
+async fn add_and_confirm(tracker: VoteTrackerHandle, signatures: Arc<Vec<Arc<String>>>, senders: u64) {
+    let mut tasks = Vec::new();
+    for share in 0..senders {
+        let adder = tracker.clone();
+        tasks.push(tokio::spawn(async move {
+            for n in (share..ACTOR_VOTES).step_by(senders as usize) {
+                let slot = 300_000_000 + n;
+                black_box(adder.add_pending(make_pending_vote(n, slot + 1, &[slot])).await.unwrap());
+            }
+        }));
+        let confirmer = tracker.clone();
+        let signatures = signatures.clone();
+        tasks.push(tokio::spawn(async move {
+            for n in (share..ACTOR_VOTES).step_by(senders as usize) {
+                let slot = 300_000_000 + n;
+                let signature = signatures[n as usize].clone();
+                black_box(confirmer.confirm_vote(signature, slot, slot + latency(n), None).await.unwrap());
+            }
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+fn tracker_actor(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap();
+    let signatures: Arc<Vec<Arc<String>>> = Arc::new((0..ACTOR_VOTES).map(|n| Arc::new(signature_base58(n))).collect());
+    let mut group = c.benchmark_group("vote_tracker_actor");
+    group.throughput(Throughput::Elements(ACTOR_VOTES));
+    // one sender pair against several: the actor serializes the commands,
+    // more senders should not lower the throughput. compare with
+    // vote_tracker/confirm_vote/10k_pending for the cost of the messages
+    for senders in [1, 4] {
+        group.bench_function(format!("add_and_confirm/10k/{}_senders", senders), |b| {
+            b.iter_batched(
+                || {
+                    let _runtime = runtime.enter();
+                    VoteTrackerHandle::spawn(VoteTracker::new(), EventBus::default())
+                },
+                |(tracker, task)| {
+                    runtime.block_on(async {
+                        add_and_confirm(tracker, signatures.clone(), senders).await;
+                        task.await.unwrap();
+                    })
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn add_confirmed_vote(c: &mut Criterion) {
+    let mut stats = PerformanceStats::new();
+    let mut n = 0u64;
+    c.bench_function("performance_stats/add_confirmed_vote", |b| {
+        b.iter(|| {
+            n += 1;
+            stats.add_confirmed_vote(make_confirmed_vote(n, 300_000_000 + n, latency(n)))
+        })
+    });
+}
+
+/// confirmed votes in a burst
+const BURST_VOTES: u64 = 10_000;
+
+fn add_confirmed_vote_burst(c: &mut Criterion) {
+    let votes: Vec<_> = (0..BURST_VOTES).map(|n| make_confirmed_vote(n, 300_000_000 + n, latency(n))).collect();
+    let mut group = c.benchmark_group("performance_stats");
+    group.throughput(Throughput::Elements(BURST_VOTES));
+    // the recent and poor vote windows and the last vote share each
+    // signature with the tracker instead of copying it
+    group.bench_function("add_confirmed_vote/10k_burst", |b| {
+        b.iter_batched(
+            || (PerformanceStats::new(), votes.clone()),
+            |(mut stats, votes)| {
+                for vote in votes {
+                    black_box(stats.add_confirmed_vote(vote));
+                }
+                stats
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn dashboard_content(c: &mut Criterion) {
+    let mut stats = PerformanceStats::new();
+    for n in 0..2_000 {
+        stats.add_confirmed_vote(make_confirmed_vote(n, 300_000_000 + n, latency(n)));
+    }
+    let memory = MemoryUsage::new();
+    let theme = Theme::new(DashboardTheme::Emoji);
+    c.bench_function("dashboard/build_dashboard_content/120x50", |b| {
+        b.iter(|| DashboardRenderer::build_dashboard_content(&stats, VOTE_ACCOUNT, &memory, &theme, (120, 50)))
+    });
+}
+
+fn config() -> Criterion {
+    // fixed settings so runs on different commits compare against a saved baseline
+    Criterion::default()
+        .warm_up_time(Duration::from_secs(2))
+        .measurement_time(Duration::from_secs(5))
+        .sample_size(100)
+        .noise_threshold(0.03)
+}
+
+criterion_group! {
+    name = hot_path;
+    config = config();
+    targets = parse_tower_sync, signature_cache, confirm_vote, tracker_actor, add_confirmed_vote, add_confirmed_vote_burst, dashboard_content
+}
+criterion_main!(hot_path);
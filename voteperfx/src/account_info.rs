@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::error::{Result, VoteMonitorError};
+use crate::events::EventBus;
+use crate::message::SystemEvent;
+use crate::performance::PerformanceStats;
+use crate::rpc::{PollBackoff, RpcClient};
+
+/// identity, commission, stake and delinquency of the monitored vote account (rpc_url)
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountInfo {
+    pub identity: String,
+    pub commission: u8,
+    /// lamports
+    pub activated_stake: u64,
+    pub delinquent: bool,
+    pub fetched_at: DateTime<Local>,
+}
+
+impl AccountInfo {
+    pub fn activated_stake_sol(&self) -> f64 {
+        self.activated_stake as f64 / LAMPORTS_PER_SOL as f64
+    }
+}
+
+#[derive(Deserialize)]
+struct VoteAccounts {
+    current: Vec<VoteAccountEntry>,
+    delinquent: Vec<VoteAccountEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VoteAccountEntry {
+    node_pubkey: String,
+    commission: u8,
+    activated_stake: u64,
+}
+
+/// the vote account's getVoteAccounts entry over json-rpc
+#[derive(Debug, Clone)]
+pub struct AccountInfoClient {
+    rpc: RpcClient,
+    vote_account: String,
+}
+
+impl AccountInfoClient {
+    pub fn new(url: impl Into<String>, vote_account: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            rpc: RpcClient::new(url)?,
+            vote_account: vote_account.into(),
+        })
+    }
+
+    pub async fn fetch(&self) -> Result<AccountInfo> {
+        let accounts: VoteAccounts = self.rpc
+            .call("getVoteAccounts", json!([{
+                "votePubkey": self.vote_account,
+                "commitment": "finalized",
+                "keepUnstakedDelinquents": true,
+            }]))
+            .await?;
+
+        let current = accounts.current.into_iter().map(|entry| (entry, false));
+        let delinquent = accounts.delinquent.into_iter().map(|entry| (entry, true));
+        current
+            .chain(delinquent)
+            .next()
+            .map(|(entry, delinquent)| AccountInfo {
+                identity: entry.node_pubkey,
+                commission: entry.commission,
+                activated_stake: entry.activated_stake,
+                delinquent,
+                fetched_at: Local::now(),
+            })
+            .ok_or_else(|| VoteMonitorError::Rpc(format!("vote account {} not found", self.vote_account)))
+    }
+}
+
+/// fetch the account info at startup and every `poll_interval`
+///
+/// a commission change publishes `CommissionChanged`. a failed refresh keeps
+/// the last info and backs off like the credits check.
+pub fn spawn_account_info_monitor(
+    client: AccountInfoClient,
+    poll_interval: Duration,
+    stats: Arc<RwLock<PerformanceStats>>,
+    events: EventBus,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut backoff = PollBackoff::new(poll_interval);
+
+        loop {
+            backoff.wait().await;
+
+            match client.fetch().await {
+                Ok(info) => {
+                    backoff.succeeded();
+                    let mut stats = stats.write().await;
+                    if let Some(previous) = stats.account_info.as_ref() {
+                        if previous.commission != info.commission {
+                            log::warn!("commission changed from {}% to {}%", previous.commission, info.commission);
+                            events.publish(SystemEvent::CommissionChanged {
+                                previous: previous.commission,
+                                current: info.commission,
+                            });
+                        }
+                        if previous.delinquent != info.delinquent {
+                            if info.delinquent {
+                                log::warn!("vote account is delinquent");
+                            } else {
+                                log::info!("vote account is no longer delinquent");
+                            }
+                        }
+                    }
+                    stats.account_info = Some(info);
+                    stats.mark_changed();
+                }
+                Err(e) => log::warn!("account info refresh failed ({} in a row), keeping last info: {}", backoff.failed(), e),
+            }
+        }
+    })
+}
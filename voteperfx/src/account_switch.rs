@@ -0,0 +1,157 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::watch;
+
+use crate::error::{Result, VoteMonitorError};
+use crate::status_api::StatsSnapshot;
+use crate::vote_tracker::MissedVote;
+
+/// the session of a vote account switched away from, one json line of
+/// stats.account_archive_file
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountArchive {
+    pub archived_at: DateTime<Local>,
+    pub switched_to: String,
+    #[serde(flatten)]
+    pub stats: StatsSnapshot,
+    /// votes still waiting for a finalized block, expired without counting
+    /// as missed, oldest first
+    pub in_flight_votes: Vec<MissedVote>,
+}
+
+/// append the archive as one json line, creating the file and its directory
+pub async fn append_account_archive(archive: &AccountArchive, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let mut line = serde_json::to_vec(archive)?;
+    line.push(b'\n');
+    let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+    file.write_all(&line).await?;
+    file.flush().await?;
+
+    Ok(())
+}
+
+/// why a switch request was turned down
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwitchRefused {
+    InvalidAccount(String),
+    /// the account already monitored
+    SameAccount,
+    /// another switch is still in progress, to this account
+    Pending(String),
+    /// the monitor was not started or already stopped
+    NotRunning,
+}
+
+impl fmt::Display for SwitchRefused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwitchRefused::InvalidAccount(e) => write!(f, "vote_account is not a valid pubkey: {}", e),
+            SwitchRefused::SameAccount => write!(f, "already monitoring this vote account"),
+            SwitchRefused::Pending(next) => write!(f, "a switch to {} is already in progress", next),
+            SwitchRefused::NotRunning => write!(f, "the monitor is not running"),
+        }
+    }
+}
+
+/// asks a running monitor to move to another vote account
+///
+/// a request records the new account and stops the pipeline; the monitor
+/// then closes, archives the old session on shutdown and `Monitor::switched_to`
+/// tells the caller what to restart with.
+#[derive(Debug, Clone)]
+pub struct AccountSwitch {
+    current: Arc<str>,
+    target: watch::Sender<Option<String>>,
+    stop: watch::Sender<bool>,
+}
+
+impl AccountSwitch {
+    pub fn new(current: &str, target: watch::Sender<Option<String>>, stop: watch::Sender<bool>) -> Self {
+        Self { current: Arc::from(current), target, stop }
+    }
+
+    /// record `vote_account` as the next account and stop the pipeline
+    pub fn request(&self, vote_account: &str) -> std::result::Result<(), SwitchRefused> {
+        Pubkey::from_str(vote_account).map_err(|e| SwitchRefused::InvalidAccount(e.to_string()))?;
+        if vote_account == &*self.current {
+            return Err(SwitchRefused::SameAccount);
+        }
+
+        // only the first of two concurrent requests gets through
+        let mut refused = None;
+        self.target.send_if_modified(|target| match target {
+            Some(next) => {
+                refused = Some(SwitchRefused::Pending(next.clone()));
+                false
+            }
+            None => {
+                *target = Some(vote_account.to_string());
+                true
+            }
+        });
+        if let Some(refused) = refused {
+            return Err(refused);
+        }
+
+        log::warn!("switching from vote account {} to {}", self.current, vote_account);
+        self.stop.send_replace(true);
+        Ok(())
+    }
+}
+
+/// POST /switch-account to the status api on `http_listen`, returns its reply
+pub async fn request_account_switch(http_listen: SocketAddr, vote_account: &str) -> Result<String> {
+    let url = format!("http://{}/switch-account", http_listen);
+    let response = reqwest::Client::new()
+        .post(&url)
+        .query(&[("vote_account", vote_account)])
+        .send()
+        .await
+        .map_err(|e| VoteMonitorError::Rpc(format!("{}: {}", url, e)))?;
+
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    match status {
+        status if status.is_success() => Ok(body.trim_end().to_string()),
+        reqwest::StatusCode::NOT_FOUND => Err(VoteMonitorError::Config(
+            "the monitor does not serve POST /switch-account, set stats.switch_account_over_http = true".to_string(),
+        )),
+        status => Err(VoteMonitorError::Rpc(format!("{}: {} {}", url, status, body.trim_end()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURRENT: &str = "Vote111111111111111111111111111111111111111";
+
+    #[test]
+    fn one_switch_at_a_time() {
+        let (target, target_rx) = watch::channel(None);
+        let (stop, stop_rx) = watch::channel(false);
+        let switch = AccountSwitch::new(CURRENT, target, stop);
+        let next = Pubkey::new_unique().to_string();
+
+        assert!(matches!(switch.request("not a pubkey"), Err(SwitchRefused::InvalidAccount(_))));
+        assert_eq!(switch.request(CURRENT), Err(SwitchRefused::SameAccount));
+        assert!(!*stop_rx.borrow());
+
+        switch.request(&next).unwrap();
+        assert_eq!(target_rx.borrow().as_deref(), Some(next.as_str()));
+        assert!(*stop_rx.borrow());
+        assert_eq!(switch.request(&Pubkey::new_unique().to_string()), Err(SwitchRefused::Pending(next)));
+    }
+}
@@ -0,0 +1,351 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use flate2::read::MultiGzDecoder;
+use rustc_hash::FxHashSet;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::epoch::EpochStats;
+use crate::error::{Result, VoteMonitorError};
+use crate::loss_cause::LostCredits;
+use crate::performance::{ConfirmedVote, LatencyDistribution, PerformanceStats, PoorPerformanceEvent, TvcHistogram, sorted_percentile};
+use crate::storage::{is_sqlite_file, read_database};
+use crate::vote_log::{VoteLogRecord, CSV_HEADER_PREFIX};
+
+/// votes listed in the worst votes section
+pub const WORST_VOTES: usize = 20;
+
+/// malformed lines echoed in the report
+const MALFORMED_EXAMPLES: usize = 10;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnalysisInputs {
+    pub files: Vec<PathBuf>,
+    pub lines: u64,
+    /// vote and poor performance event rows read from sqlite databases
+    pub database_rows: u64,
+    pub vote_log_records: u64,
+    pub performance_events: u64,
+    /// the same vote seen in more than one file, counted once
+    pub duplicates: u64,
+    pub malformed_lines: u64,
+    /// `file:line` of the first malformed lines
+    pub malformed_examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisLatency {
+    pub avg: f64,
+    pub p50: Option<u64>,
+    pub p90: Option<u64>,
+    pub p99: Option<u64>,
+    pub max: Option<u64>,
+    pub low_latency_pct: f64,
+    /// votes at latency 0, 1, 2, 3 and 4+ slots
+    pub distribution: LatencyDistribution,
+    /// votes at exactly the grace limit
+    pub grace_dependent_pct: f64,
+}
+
+/// offline recomputation of the session stats from recorded logs
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisReport {
+    pub inputs: AnalysisInputs,
+    pub votes: u64,
+    pub first_slot: Option<u64>,
+    pub last_slot: Option<u64>,
+    pub tvc_earned: u64,
+    pub tvc_possible: u64,
+    pub efficiency: f64,
+    pub latency: AnalysisLatency,
+    pub optimal_votes: u64,
+    pub good_votes: u64,
+    pub poor_votes: u64,
+    /// votes by earned credits, 0 to 16 tvc
+    pub tvc_histogram: TvcHistogram,
+    /// recorded causes, votes logged without one are classified again
+    /// without the leader schedule
+    pub lost_credits: LostCredits,
+    /// oldest first
+    pub epochs: Vec<EpochStats>,
+    /// lowest credits first
+    pub worst_votes: Vec<ConfirmedVote>,
+}
+
+/// expand directories, globs and plain paths into a sorted list of log files
+pub fn resolve_inputs(patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for pattern in patterns {
+        let path = Path::new(pattern);
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path)? {
+                let entry_path = entry?.path();
+                if is_log_file(&entry_path) {
+                    files.push(entry_path);
+                }
+            }
+            continue;
+        }
+
+        let matches = glob::glob(pattern)
+            .map_err(|e| VoteMonitorError::Config(format!("invalid path pattern '{}': {}", pattern, e)))?;
+        let before = files.len();
+        files.extend(matches.filter_map(|entry| entry.ok()).filter(|entry| entry.is_file()));
+        if files.len() == before {
+            return Err(VoteMonitorError::Config(format!("no log files match '{}'", pattern)));
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// vote records only, the epoch summaries that share the directory are skipped
+fn is_log_file(path: &Path) -> bool {
+    path.is_file()
+        && path.file_stem().is_some_and(|stem| stem != "epoch_summaries")
+        && matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("json" | "jsonl" | "csv" | "gz" | "db" | "sqlite" | "sqlite3")
+        )
+}
+
+/// file contents, gzip compressed performance logs are decompressed
+async fn read_log_file(path: &Path) -> Result<String> {
+    if !path.extension().is_some_and(|ext| ext == "gz") {
+        return Ok(tokio::fs::read_to_string(path).await?);
+    }
+
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut content = String::new();
+        MultiGzDecoder::new(std::fs::File::open(path)?).read_to_string(&mut content)?;
+        Ok(content)
+    })
+    .await
+    .map_err(|e| VoteMonitorError::Channel(format!("decompression task failed: {}", e)))?
+}
+
+/// parse one json line, either a vote log record or a poor performance event
+fn parse_json_line(line: &str, inputs: &mut AnalysisInputs) -> Option<ConfirmedVote> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    if value.get("transaction_signature").is_some() {
+        let event: PoorPerformanceEvent = serde_json::from_value(value).ok()?;
+        inputs.performance_events += 1;
+        Some(event.into())
+    } else {
+        let record: VoteLogRecord = serde_json::from_value(value).ok()?;
+        inputs.vote_log_records += 1;
+        Some(record.into())
+    }
+}
+
+/// keep the first copy of each vote across files
+fn push_unique(
+    vote: ConfirmedVote,
+    votes: &mut Vec<ConfirmedVote>,
+    seen: &mut FxHashSet<(Arc<String>, u64)>,
+    inputs: &mut AnalysisInputs,
+) {
+    if !seen.insert((vote.signature.clone(), vote.voted_slot)) {
+        inputs.duplicates += 1;
+        return;
+    }
+    votes.push(vote);
+}
+
+/// read every file, counting malformed lines instead of aborting
+///
+/// sqlite databases written by the storage backend are recognized by their
+/// header and read alongside json and csv logs.
+pub async fn read_votes(files: &[PathBuf]) -> Result<(Vec<ConfirmedVote>, AnalysisInputs)> {
+    let mut inputs = AnalysisInputs {
+        files: files.to_vec(),
+        ..Default::default()
+    };
+    let mut votes = Vec::new();
+    let mut seen: FxHashSet<(Arc<String>, u64)> = FxHashSet::default();
+
+    for file in files {
+        if is_sqlite_file(file).await {
+            let stored = read_database(file).await?;
+            inputs.database_rows += (stored.votes.len() + stored.events.len()) as u64 + stored.malformed;
+            inputs.vote_log_records += stored.votes.len() as u64;
+            inputs.performance_events += stored.events.len() as u64;
+            inputs.malformed_lines += stored.malformed;
+            for vote in stored.votes.into_iter().chain(stored.events.into_iter().map(ConfirmedVote::from)) {
+                push_unique(vote, &mut votes, &mut seen, &mut inputs);
+            }
+            continue;
+        }
+
+        let content = read_log_file(file).await?;
+        let is_csv = file.extension().is_some_and(|ext| ext == "csv");
+
+        for (index, line) in content.lines().enumerate() {
+            // older csv headers lack the landed_slot column
+            if line.trim().is_empty() || (is_csv && line.starts_with(CSV_HEADER_PREFIX)) {
+                continue;
+            }
+            inputs.lines += 1;
+
+            let vote = if is_csv {
+                VoteLogRecord::from_csv_line(line).map(|record| {
+                    inputs.vote_log_records += 1;
+                    ConfirmedVote::from(record)
+                })
+            } else {
+                parse_json_line(line, &mut inputs)
+            };
+
+            let Some(vote) = vote else {
+                inputs.malformed_lines += 1;
+                if inputs.malformed_examples.len() < MALFORMED_EXAMPLES {
+                    inputs.malformed_examples.push(format!("{}:{}", file.display(), index + 1));
+                }
+                continue;
+            };
+
+            push_unique(vote, &mut votes, &mut seen, &mut inputs);
+        }
+    }
+
+    Ok((votes, inputs))
+}
+
+/// recompute the stats through the same aggregation as the live monitor
+pub fn analyze_votes(mut votes: Vec<ConfirmedVote>, inputs: AnalysisInputs, config: &Config) -> AnalysisReport {
+    votes.sort_by_key(|vote| (vote.finalized_slot, vote.voted_slot));
+
+    let include_estimates = config.stats.percentiles_include_estimates;
+    let mut latencies: Vec<u64> = votes
+        .iter()
+        .filter(|vote| vote.kind.is_counted() && (include_estimates || !vote.kind.is_estimated()))
+        .map(|vote| vote.latency)
+        .collect();
+    latencies.sort_unstable();
+
+    let mut worst_votes: Vec<ConfirmedVote> = votes.clone();
+    worst_votes.sort_by(|a, b| a.tvc_credits.cmp(&b.tvc_credits).then(b.latency.cmp(&a.latency)));
+    worst_votes.truncate(WORST_VOTES);
+
+    let first_slot = votes.iter().map(|vote| vote.voted_slot).min();
+    let last_slot = votes.iter().map(|vote| vote.finalized_slot).max();
+
+    let mut stats = PerformanceStats::new()
+        .with_slots_per_epoch(config.network_profile().slots_per_epoch)
+        .with_tvc_config(config.tvc)
+        .with_thresholds(config.thresholds)
+        .with_loss_causes(config.loss_causes);
+    let mut epochs = stats.replay(votes);
+    epochs.extend(stats.epochs.current().cloned());
+
+    AnalysisReport {
+        inputs,
+        votes: stats.total_transactions(),
+        first_slot,
+        last_slot,
+        tvc_earned: stats.total_tvc_earned(),
+        tvc_possible: stats.total_tvc_possible(),
+        efficiency: stats.calculate_efficiency(),
+        latency: AnalysisLatency {
+            avg: stats.calculate_session_avg_latency(),
+            p50: sorted_percentile(&latencies, 50.0),
+            p90: sorted_percentile(&latencies, 90.0),
+            p99: sorted_percentile(&latencies, 99.0),
+            max: latencies.last().copied(),
+            low_latency_pct: stats.calculate_low_latency_percentage(),
+            distribution: stats.latency_distribution(),
+            grace_dependent_pct: stats.latency_distribution().grace_dependent_pct(),
+        },
+        optimal_votes: stats.optimal_votes(),
+        good_votes: stats.good_votes(),
+        poor_votes: stats.poor_votes(),
+        tvc_histogram: stats.tvc_histogram(),
+        lost_credits: stats.lost_credits(),
+        epochs,
+        worst_votes,
+    }
+}
+
+fn format_optional(value: Option<u64>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+pub fn print_report(report: &AnalysisReport) {
+    let inputs = &report.inputs;
+    println!("voteperfx analysis");
+    if inputs.database_rows > 0 {
+        println!("files: {} ({} lines, {} database rows)", inputs.files.len(), inputs.lines, inputs.database_rows);
+    } else {
+        println!("files: {} ({} lines)", inputs.files.len(), inputs.lines);
+    }
+    println!(
+        "records: {} vote log, {} performance events, {} duplicates, {} malformed",
+        inputs.vote_log_records, inputs.performance_events, inputs.duplicates, inputs.malformed_lines
+    );
+    for example in &inputs.malformed_examples {
+        println!("  malformed: {}", example);
+    }
+    if inputs.performance_events > 0 && inputs.vote_log_records == 0 {
+        println!("note: performance events only contain votes that matched the logging filters, totals are not session-wide");
+    }
+    println!();
+
+    if report.votes == 0 {
+        println!("no votes found");
+        return;
+    }
+
+    println!(
+        "votes: {} (slots {} - {})",
+        report.votes, format_optional(report.first_slot), format_optional(report.last_slot)
+    );
+    println!(
+        "tvc efficiency: {:.1}% ({}/{} credits)",
+        report.efficiency, report.tvc_earned, report.tvc_possible
+    );
+    let latency = &report.latency;
+    println!(
+        "latency: avg {:.2} | p50 {} | p90 {} | p99 {} | max {} | low latency {:.1}%",
+        latency.avg, format_optional(latency.p50), format_optional(latency.p90),
+        format_optional(latency.p99), format_optional(latency.max), latency.low_latency_pct
+    );
+    if let Some(summary) = latency.distribution.summary() {
+        println!("latency distribution: {} | grace-dependent {:.1}%", summary, latency.grace_dependent_pct);
+    }
+    let pct = |count: u64| count as f64 / report.votes as f64 * 100.0;
+    println!(
+        "breakdown: optimal {} ({:.1}%) | good {} ({:.1}%) | poor {} ({:.1}%)",
+        report.optimal_votes, pct(report.optimal_votes),
+        report.good_votes, pct(report.good_votes),
+        report.poor_votes, pct(report.poor_votes)
+    );
+    if let Some(summary) = report.tvc_histogram.summary() {
+        println!("tvc distribution: {}", summary);
+    }
+    if let Some(summary) = report.lost_credits.summary() {
+        println!("credits lost by cause: {}", summary);
+    }
+
+    println!();
+    println!("{:>8} {:>8} {:>11} {:>12}", "epoch", "votes", "efficiency", "avg latency");
+    for epoch in &report.epochs {
+        println!(
+            "{:>8} {:>8} {:>10.1}% {:>12.2}",
+            epoch.epoch, epoch.votes, epoch.efficiency(), epoch.avg_latency()
+        );
+    }
+
+    println!();
+    println!("worst {} votes:", report.worst_votes.len());
+    println!("{:>11} {:>8} {:>4}  signature", "slot", "latency", "tvc");
+    for vote in &report.worst_votes {
+        println!("{:>11} {:>8} {:>4}  {}", vote.voted_slot, vote.latency, vote.tvc_credits, vote.signature);
+    }
+}
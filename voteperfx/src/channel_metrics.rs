@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+/// fill level and backpressure of one update channel
+///
+/// written by the stream task only, read lock-free by the dashboard and the
+/// status api.
+#[derive(Debug)]
+pub struct ChannelMetrics {
+    capacity: usize,
+    depth: AtomicUsize,
+    high_water: AtomicUsize,
+    blocked_sends: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl ChannelMetrics {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            depth: AtomicUsize::new(0),
+            high_water: AtomicUsize::new(0),
+            blocked_sends: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// sample the queued updates of `sender`, the channel this tracks
+    #[inline]
+    pub fn sample<T>(&self, sender: &mpsc::Sender<T>) -> usize {
+        let depth = sender.max_capacity().saturating_sub(sender.capacity());
+        self.depth.store(depth, Ordering::Relaxed);
+        self.high_water.fetch_max(depth, Ordering::Relaxed);
+        depth
+    }
+
+    /// a send that found the channel full and had to wait
+    #[inline]
+    pub fn record_blocked(&self) {
+        self.blocked_sends.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// an update discarded instead of queued
+    #[inline]
+    pub fn record_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn snapshot(&self) -> ChannelSnapshot {
+        ChannelSnapshot {
+            depth: self.depth.load(Ordering::Relaxed),
+            capacity: self.capacity,
+            high_water: self.high_water.load(Ordering::Relaxed),
+            blocked_sends: self.blocked_sends.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ChannelSnapshot {
+    /// updates queued at the last sample
+    pub depth: usize,
+    pub capacity: usize,
+    /// deepest the queue has been since startup
+    pub high_water: usize,
+    /// sends that found the channel full and waited for the consumer
+    pub blocked_sends: u64,
+    /// updates discarded by the drop policy
+    pub dropped: u64,
+}
+
+/// backpressure on the transaction and block channels
+#[derive(Debug)]
+pub struct PipelineMetrics {
+    pub transactions: ChannelMetrics,
+    pub blocks: ChannelMetrics,
+}
+
+impl PipelineMetrics {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            transactions: ChannelMetrics::new(capacity),
+            blocks: ChannelMetrics::new(capacity),
+        }
+    }
+
+    pub fn snapshot(&self) -> PipelineSnapshot {
+        PipelineSnapshot {
+            transactions: self.transactions.snapshot(),
+            blocks: self.blocks.snapshot(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PipelineSnapshot {
+    pub transactions: ChannelSnapshot,
+    pub blocks: ChannelSnapshot,
+}
@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::config::{Config, LogFormat};
+
+const CONFIGURATION_HELP: &str = "\
+configuration:
+    config.toml    all configuration including:
+                   - grpc_url: yellowstone grpc endpoint, or grpc_urls for failover
+                   - grpc_x_token / grpc_tls_ca_cert_path: endpoint authentication and private ca
+                   - vote_account: vote account to monitor
+                   - performance_logging: logging filters
+                   - rpc_url: optional credits cross-check and leader attribution
+                   without it a terminal is asked for grpc_url and vote_account,
+                   otherwise a sample is printed and the exit code is 78
+
+environment:
+    VOTEPERFX_GRPC_URL      grpc endpoint, comma-separated for a grpc_urls failover list
+    VOTEPERFX_VOTE_ACCOUNT  vote account to monitor
+    VOTEPERFX_X_TOKEN       grpc_x_token, keeps the token out of config.toml
+
+    precedence: command line flags > VOTEPERFX_* environment > config.toml,
+    the environment is read again on config reload
+
+for more information, see: https://github.com/1000xsh/voteperfx";
+
+/// solana vote performance monitor
+#[derive(Debug, Clone, Parser)]
+#[command(name = "voteperfx", version = crate::LONG_VERSION, after_help = CONFIGURATION_HELP)]
+pub struct Cli {
+    /// path to the config file
+    #[arg(long, value_name = "PATH", default_value = "config.toml")]
+    pub config: PathBuf,
+
+    /// vote account to monitor, overrides VOTEPERFX_VOTE_ACCOUNT and config.toml
+    #[arg(long, value_name = "PUBKEY")]
+    pub vote_account: Option<String>,
+
+    /// yellowstone grpc endpoint, overrides VOTEPERFX_GRPC_URL and grpc_url / grpc_urls in config.toml
+    #[arg(long, value_name = "URL")]
+    pub grpc_url: Option<String>,
+
+    /// json-rpc endpoint for the credits cross-check, leader attribution and cluster comparison, overrides config.toml
+    #[arg(long, value_name = "URL")]
+    pub rpc_url: Option<String>,
+
+    /// simple cli logging mode
+    #[arg(long, conflicts_with = "dashboard")]
+    pub simple: bool,
+
+    /// no terminal ui or escape codes, for systemd and containers; implies --simple
+    #[arg(long, conflicts_with = "dashboard")]
+    pub headless: bool,
+
+    /// interactive dashboard with real-time metrics (default)
+    #[arg(long)]
+    pub dashboard: bool,
+
+    /// log filter, overrides log_level in config.toml, defaults to info in simple mode and warn in dashboard mode
+    #[arg(long, value_name = "LEVEL")]
+    pub log_level: Option<String>,
+
+    /// json log lines for log aggregation, overrides log_format in config.toml
+    #[arg(long)]
+    pub log_json: bool,
+
+    /// write influxdb line protocol points to stdout instead of influx.url,
+    /// logs stay on stderr; implies --headless
+    #[arg(long, conflicts_with = "dashboard")]
+    pub metrics_stdout: bool,
+
+    /// write votes, missed votes, poor votes and periodic stats to stdout as
+    /// ndjson, logs stay on stderr; implies --headless
+    #[arg(long, conflicts_with_all = ["dashboard", "metrics_stdout"])]
+    pub json_stream: bool,
+
+    /// validate the config file, print warnings and exit
+    #[arg(long)]
+    pub check_config: bool,
+
+    /// write a commented config file with every setting and its default to
+    /// PATH and exit, an existing file is not overwritten
+    #[arg(long, value_name = "PATH")]
+    pub write_default_config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// recompute stats from recorded performance and vote logs or a sqlite database
+    Analyze(AnalyzeArgs),
+    /// ask the running monitor to move to another vote account, archiving the
+    /// current session; sent to http_listen, needs stats.switch_account_over_http
+    SwitchAccount(SwitchAccountArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SwitchAccountArgs {
+    /// vote account to monitor from now on
+    #[arg(value_name = "PUBKEY")]
+    pub vote_account: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct AnalyzeArgs {
+    /// log files, sqlite databases, directories or glob patterns, defaults to
+    /// storage.path with the sqlite backend and performance_log_dir otherwise
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// print the report as json
+    #[arg(long)]
+    pub json: bool,
+
+    /// compare two ranges side by side, each a time span `START..END` of
+    /// dates or local times with either end open, filtering PATH, or a
+    /// comma-separated list of paths
+    #[arg(long, num_args = 2, value_names = ["RANGE_A", "RANGE_B"])]
+    pub compare: Vec<String>,
+}
+
+impl Cli {
+    pub fn simple_mode(&self) -> bool {
+        self.simple || self.headless || self.metrics_stdout || self.json_stream
+    }
+
+    /// apply command line overrides on top of the loaded config
+    pub fn apply_overrides(&self, config: &mut Config) {
+        if let Some(ref vote_account) = self.vote_account {
+            config.vote_account = vote_account.clone();
+        }
+        if let Some(ref grpc_url) = self.grpc_url {
+            config.grpc_url = grpc_url.clone();
+            config.grpc_urls.clear();
+        }
+        if let Some(ref rpc_url) = self.rpc_url {
+            config.rpc_url = Some(rpc_url.clone());
+        }
+        if let Some(ref log_level) = self.log_level {
+            config.log_level = Some(log_level.clone());
+        }
+        if self.log_json {
+            config.log_format = LogFormat::Json;
+        }
+        if self.metrics_stdout {
+            config.influx.stdout = true;
+        }
+        if self.json_stream {
+            config.json_stream.enabled = true;
+        }
+    }
+
+    /// whether any flag overrides a config value
+    pub fn has_overrides(&self) -> bool {
+        self.vote_account.is_some() || self.grpc_url.is_some() || self.rpc_url.is_some()
+    }
+}
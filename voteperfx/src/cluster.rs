@@ -0,0 +1,342 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::error::{Result, VoteMonitorError};
+use crate::performance::PerformanceStats;
+use crate::rpc::{PollBackoff, RpcClient};
+
+/// slots into an epoch before epoch credits say anything about efficiency
+pub const MIN_EPOCH_SLOTS: u64 = 1000;
+
+/// one current vote account from getVoteAccounts
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteAccountEntry {
+    pub vote_pubkey: String,
+    pub activated_stake: u64,
+    /// (epoch, credits, previous credits), credits are cumulative
+    pub epoch_credits: Vec<(u64, u64, u64)>,
+}
+
+impl VoteAccountEntry {
+    /// credits earned in `epoch` so far, none without an entry for it
+    pub fn credits_in_epoch(&self, epoch: u64) -> Option<u64> {
+        self.epoch_credits
+            .iter()
+            .rev()
+            .find(|&&(credits_epoch, _, _)| credits_epoch == epoch)
+            .map(|&(_, credits, previous)| credits.saturating_sub(previous))
+    }
+}
+
+#[derive(Deserialize)]
+struct VoteAccounts {
+    current: Vec<VoteAccountEntry>,
+}
+
+/// epoch-to-date efficiency of the staked, non-delinquent validators
+///
+/// efficiency is credits earned this epoch over slots elapsed times max
+/// credits per slot. percentiles are stake-weighted.
+#[derive(Debug, Clone)]
+pub struct ClusterSnapshot {
+    pub fetched_at: DateTime<Local>,
+    fetched: Instant,
+    pub epoch: u64,
+    pub slots_elapsed: u64,
+    pub validators: usize,
+    pub total_stake: u64,
+    /// (efficiency percent, stake), lowest efficiency first
+    efficiencies: Vec<(f64, u64)>,
+}
+
+impl ClusterSnapshot {
+    /// none when no staked validator has credits for `epoch`
+    pub fn from_vote_accounts(
+        accounts: &[VoteAccountEntry],
+        epoch: u64,
+        slots_elapsed: u64,
+        max_credits_per_slot: u64,
+    ) -> Option<Self> {
+        let possible = slots_elapsed.saturating_mul(max_credits_per_slot);
+        if possible == 0 {
+            return None;
+        }
+
+        let mut efficiencies: Vec<(f64, u64)> = accounts
+            .iter()
+            .filter(|account| account.activated_stake > 0)
+            .filter_map(|account| {
+                let credits = account.credits_in_epoch(epoch)?;
+                Some(((credits as f64 / possible as f64 * 100.0).min(100.0), account.activated_stake))
+            })
+            .collect();
+        if efficiencies.is_empty() {
+            return None;
+        }
+        efficiencies.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        Some(Self {
+            fetched_at: Local::now(),
+            fetched: Instant::now(),
+            epoch,
+            slots_elapsed,
+            validators: efficiencies.len(),
+            total_stake: efficiencies.iter().map(|&(_, stake)| stake).sum(),
+            efficiencies,
+        })
+    }
+
+    pub fn age(&self) -> Duration {
+        self.fetched.elapsed()
+    }
+
+    /// lowest efficiency with at least `percentile` of the stake at or below it
+    pub fn stake_weighted_percentile(&self, percentile: f64) -> f64 {
+        let target = self.total_stake as f64 * percentile.clamp(0.0, 100.0) / 100.0;
+        let mut cumulative = 0u64;
+        for &(efficiency, stake) in &self.efficiencies {
+            cumulative += stake;
+            if cumulative as f64 >= target {
+                return efficiency;
+            }
+        }
+        self.efficiencies.last().map_or(0.0, |&(efficiency, _)| efficiency)
+    }
+
+    pub fn median(&self) -> f64 {
+        self.stake_weighted_percentile(50.0)
+    }
+
+    /// efficiency needed to be in the top 10% of stake
+    pub fn top_decile(&self) -> f64 {
+        self.stake_weighted_percentile(90.0)
+    }
+
+    /// percent of stake with a lower efficiency, ties count half
+    pub fn rank_of(&self, efficiency_pct: f64) -> f64 {
+        let (below, equal) = self.efficiencies.iter().fold((0u64, 0u64), |(below, equal), &(efficiency, stake)| {
+            if efficiency < efficiency_pct {
+                (below + stake, equal)
+            } else if efficiency == efficiency_pct {
+                (below, equal + stake)
+            } else {
+                (below, equal)
+            }
+        });
+        (below as f64 + equal as f64 / 2.0) / self.total_stake as f64 * 100.0
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.efficiencies.capacity() * std::mem::size_of::<(f64, u64)>()
+    }
+}
+
+/// current vote accounts and epoch progress over json-rpc
+#[derive(Debug, Clone)]
+pub struct ClusterClient {
+    rpc: RpcClient,
+}
+
+impl ClusterClient {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        Ok(Self { rpc: RpcClient::new(url)? })
+    }
+
+    /// none during the first `MIN_EPOCH_SLOTS` slots of an epoch
+    pub async fn fetch_snapshot(&self, max_credits_per_slot: u64) -> Result<Option<ClusterSnapshot>> {
+        let epoch = self.rpc.epoch_info().await?;
+        if epoch.slot_index < MIN_EPOCH_SLOTS {
+            log::debug!("epoch {} is only {} slots old, keeping the last cluster snapshot", epoch.epoch, epoch.slot_index);
+            return Ok(None);
+        }
+
+        let accounts: VoteAccounts = self.rpc
+            .call("getVoteAccounts", json!([{ "commitment": "finalized" }]))
+            .await?;
+        ClusterSnapshot::from_vote_accounts(&accounts.current, epoch.epoch, epoch.slot_index, max_credits_per_slot)
+            .map(Some)
+            .ok_or_else(|| VoteMonitorError::Rpc(format!("no epoch {} credits in getVoteAccounts", epoch.epoch)))
+    }
+}
+
+/// refresh the cluster snapshot every `poll_interval`
+///
+/// a failed refresh keeps the last good snapshot, the dashboard shows its
+/// age. failures back off like the credits check.
+pub fn spawn_cluster_comparison(
+    client: ClusterClient,
+    poll_interval: Duration,
+    stats: Arc<RwLock<PerformanceStats>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut backoff = PollBackoff::new(poll_interval);
+
+        loop {
+            backoff.wait().await;
+
+            let max_credits_per_slot = stats.read().await.tvc.max_credits_per_slot;
+            match client.fetch_snapshot(max_credits_per_slot).await {
+                Ok(None) => backoff.succeeded(),
+                Ok(Some(snapshot)) => {
+                    backoff.succeeded();
+                    log::debug!(
+                        "cluster snapshot for epoch {}: {} validators, median {:.2}%, top decile {:.2}%",
+                        snapshot.epoch, snapshot.validators, snapshot.median(), snapshot.top_decile()
+                    );
+                    let mut stats = stats.write().await;
+                    stats.cluster = Some(snapshot);
+                    stats.mark_changed();
+                }
+                Err(e) => {
+                    let failures = backoff.failed();
+                    log::warn!("cluster comparison refresh failed ({} in a row), keeping last snapshot: {}", failures, e);
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use serde_json::Value;
+
+    /// getVoteAccounts result in epoch 500, 10_000 slots in. 16 credits per
+    /// slot make 160_000 possible: 98%, 95% and 99% for the staked ones
+    const VOTE_ACCOUNTS: &str = r#"{
+        "current": [
+            {
+                "activatedStake": 100, "commission": 5, "epochVoteAccount": true, "lastVote": 216010000,
+                "nodePubkey": "Node1", "rootSlot": 216009969, "votePubkey": "VoteA",
+                "epochCredits": [[498, 900, 0], [499, 1000, 900], [500, 157800, 1000]]
+            },
+            {
+                "activatedStake": 300, "commission": 0, "epochVoteAccount": true, "lastVote": 216010000,
+                "nodePubkey": "Node2", "rootSlot": 216009969, "votePubkey": "VoteB",
+                "epochCredits": [[500, 152000, 0]]
+            },
+            {
+                "activatedStake": 600, "commission": 10, "epochVoteAccount": true, "lastVote": 216010000,
+                "nodePubkey": "Node3", "rootSlot": 216009969, "votePubkey": "VoteC",
+                "epochCredits": [[499, 5000, 0], [500, 163400, 5000]]
+            },
+            {
+                "activatedStake": 0, "commission": 100, "epochVoteAccount": false, "lastVote": 216010000,
+                "nodePubkey": "Node4", "rootSlot": 216009969, "votePubkey": "Unstaked",
+                "epochCredits": [[500, 1000, 0]]
+            },
+            {
+                "activatedStake": 50, "commission": 5, "epochVoteAccount": true, "lastVote": 215000000,
+                "nodePubkey": "Node5", "rootSlot": 214999969, "votePubkey": "NoCreditsThisEpoch",
+                "epochCredits": [[499, 400000, 0]]
+            }
+        ],
+        "delinquent": []
+    }"#;
+
+    fn accounts() -> Vec<VoteAccountEntry> {
+        serde_json::from_str::<VoteAccounts>(VOTE_ACCOUNTS).unwrap().current
+    }
+
+    fn snapshot() -> ClusterSnapshot {
+        ClusterSnapshot::from_vote_accounts(&accounts(), 500, 10_000, 16).unwrap()
+    }
+
+    #[test]
+    fn credits_of_the_requested_epoch() {
+        let accounts = accounts();
+        assert_eq!(accounts[0].credits_in_epoch(500), Some(156_800));
+        assert_eq!(accounts[0].credits_in_epoch(499), Some(100));
+        assert_eq!(accounts[4].credits_in_epoch(500), None);
+    }
+
+    #[test]
+    fn snapshot_skips_unstaked_and_silent_accounts() {
+        let snapshot = snapshot();
+        assert_eq!((snapshot.epoch, snapshot.slots_elapsed), (500, 10_000));
+        assert_eq!(snapshot.validators, 3);
+        assert_eq!(snapshot.total_stake, 1000);
+        let efficiencies: Vec<_> = snapshot.efficiencies.iter().map(|&(efficiency, _)| efficiency).collect();
+        assert_eq!(efficiencies, [95.0, 98.0, 99.0]);
+    }
+
+    #[test]
+    fn percentiles_are_stake_weighted() {
+        let snapshot = snapshot();
+        // 300 of the stake at 95%, 100 at 98% and 600 at 99%
+        assert_eq!(snapshot.stake_weighted_percentile(0.0), 95.0);
+        assert_eq!(snapshot.stake_weighted_percentile(30.0), 95.0);
+        assert_eq!(snapshot.stake_weighted_percentile(35.0), 98.0);
+        assert_eq!(snapshot.median(), 99.0);
+        assert_eq!(snapshot.top_decile(), 99.0);
+        assert_eq!(snapshot.stake_weighted_percentile(150.0), 99.0);
+    }
+
+    #[test]
+    fn rank_among_the_stake() {
+        let snapshot = snapshot();
+        assert_eq!(snapshot.rank_of(90.0), 0.0);
+        assert_eq!(snapshot.rank_of(96.0), 30.0);
+        // ties count half
+        assert_eq!(snapshot.rank_of(98.0), 35.0);
+        assert_eq!(snapshot.rank_of(99.5), 100.0);
+    }
+
+    #[test]
+    fn no_snapshot_without_data() {
+        assert!(ClusterSnapshot::from_vote_accounts(&accounts(), 500, 0, 16).is_none());
+        assert!(ClusterSnapshot::from_vote_accounts(&accounts(), 501, 10_000, 16).is_none());
+        assert!(ClusterSnapshot::from_vote_accounts(&[], 500, 10_000, 16).is_none());
+    }
+
+    #[test]
+    fn efficiency_is_capped_at_100() {
+        // credits over the possible ones, e.g. slots_elapsed from a lagging node
+        let snapshot = ClusterSnapshot::from_vote_accounts(&accounts(), 500, 9_000, 16).unwrap();
+        assert_eq!(snapshot.stake_weighted_percentile(100.0), 100.0);
+    }
+
+    /// json-rpc endpoint answering getEpochInfo with `slot_index` and
+    /// getVoteAccounts with `VOTE_ACCOUNTS`
+    async fn rpc(slot_index: u64) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(request): Json<Value>| async move {
+                let result = match request["method"].as_str() {
+                    Some("getEpochInfo") => json!({
+                        "epoch": 500, "absoluteSlot": 216_000_000 + slot_index, "slotIndex": slot_index,
+                        "slotsInEpoch": 432_000, "blockHeight": 190_000_000, "transactionCount": 1,
+                    }),
+                    Some("getVoteAccounts") => serde_json::from_str(VOTE_ACCOUNTS).unwrap(),
+                    _ => Value::Null,
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn fetch_snapshot_over_rpc() {
+        let client = ClusterClient::new(rpc(10_000).await).unwrap();
+        let snapshot = client.fetch_snapshot(16).await.unwrap().unwrap();
+        assert_eq!((snapshot.epoch, snapshot.validators), (500, 3));
+        assert_eq!(snapshot.median(), 99.0);
+
+        // too early in the epoch to say anything
+        let client = ClusterClient::new(rpc(MIN_EPOCH_SLOTS - 1).await).unwrap();
+        assert!(client.fetch_snapshot(16).await.unwrap().is_none());
+    }
+}
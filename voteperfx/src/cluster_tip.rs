@@ -0,0 +1,77 @@
+use serde::Serialize;
+
+use crate::performance::Slot;
+
+/// commitment level of a slot status update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotCommitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+/// change of the finalized lag alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LagAlert {
+    Raised(u64),
+    Cleared(u64),
+}
+
+/// newest slot per commitment level on the slot status stream
+///
+/// the finalized stream normally trails the processed tip by a few dozen
+/// slots. a much larger lag points at an unhealthy geyser endpoint rather
+/// than the validator, and vote latencies measured against it are suspect.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ClusterTip {
+    pub processed: Option<Slot>,
+    pub confirmed: Option<Slot>,
+    pub finalized: Option<Slot>,
+    /// finalized lag that raises an alert, 0 disables it
+    #[serde(skip)]
+    lag_alert_slots: u64,
+    pub lagging: bool,
+}
+
+impl ClusterTip {
+    pub fn new(lag_alert_slots: u64) -> Self {
+        Self {
+            lag_alert_slots,
+            ..Default::default()
+        }
+    }
+
+    /// record a status update, returning a change of the lag alert
+    pub fn record(&mut self, slot: Slot, commitment: SlotCommitment) -> Option<LagAlert> {
+        let newest = match commitment {
+            SlotCommitment::Processed => &mut self.processed,
+            SlotCommitment::Confirmed => &mut self.confirmed,
+            SlotCommitment::Finalized => &mut self.finalized,
+        };
+        if newest.is_some_and(|newest| newest >= slot) {
+            return None;
+        }
+        *newest = Some(slot);
+
+        let lag = self.finalized_lag()?;
+        if self.lag_alert_slots == 0 {
+            return None;
+        }
+        match (self.lagging, lag > self.lag_alert_slots) {
+            (false, true) => {
+                self.lagging = true;
+                Some(LagAlert::Raised(lag))
+            }
+            (true, false) => {
+                self.lagging = false;
+                Some(LagAlert::Cleared(lag))
+            }
+            _ => None,
+        }
+    }
+
+    /// slots the finalized stream trails the processed tip, none until both are seen
+    pub fn finalized_lag(&self) -> Option<u64> {
+        Some(self.processed?.saturating_sub(self.finalized?))
+    }
+}
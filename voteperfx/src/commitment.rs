@@ -0,0 +1,362 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem::size_of;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeUpdateBlock};
+
+use crate::performance::{ConfirmedVote, Slot};
+
+/// voted slots behind the newest one a confirmed sighting is kept for
+pub const CONFIRMED_OBSERVATION_WINDOW_SLOTS: u64 = 512;
+
+/// slots behind the newest finalized slot a held confirmed block waits before
+/// it is dropped as part of an abandoned fork
+pub const HELD_BLOCK_WINDOW_SLOTS: u64 = 150;
+
+/// commitment the vote blocks are read at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    /// finalized blocks confirm votes
+    #[default]
+    Finalized,
+    /// confirmed blocks, each held until the slot status stream reports its
+    /// slot finalized
+    Confirmed,
+    /// finalized blocks confirm votes, a second subscription to confirmed
+    /// blocks adds the confirmed figures
+    Both,
+}
+
+impl Commitment {
+    /// commitment of the main subscription
+    pub fn subscription_level(self) -> CommitmentLevel {
+        match self {
+            Self::Confirmed => CommitmentLevel::Confirmed,
+            Self::Finalized | Self::Both => CommitmentLevel::Finalized,
+        }
+    }
+
+    /// whether votes carry a confirmed latency and finalization delay
+    pub fn observes_confirmed(self) -> bool {
+        self != Self::Finalized
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Finalized => "finalized",
+            Self::Confirmed => "confirmed",
+            Self::Both => "both",
+        }
+    }
+}
+
+/// first sighting of a voted slot in a confirmed block
+#[derive(Debug, Clone, Copy)]
+struct Sighting {
+    confirmed_slot: Slot,
+    seen: Instant,
+}
+
+/// voted slots seen in confirmed blocks, waiting for their finalized vote
+///
+/// the confirmed and the finalized stream carry the same votes, usually the
+/// confirmed one first. like the tracker, which credits a voted slot once
+/// whatever signature carried it, only the first sighting of a voted slot
+/// counts: a block delivered again after a resubscription or a vote landing
+/// on a second fork is a duplicate. finalized voted slots are remembered
+/// so a sighting after finalization, e.g. a confirmed copy of the block
+/// arriving late, is not kept either.
+/// everything more than `window_slots` behind the newest voted slot is
+/// forgotten, those sightings expire.
+#[derive(Debug, Clone)]
+pub struct ConfirmedObservations {
+    sightings: BTreeMap<Slot, Sighting>,
+    // voted slots finalized within the window
+    finalized: BTreeSet<Slot>,
+    newest_voted: Slot,
+    window_slots: u64,
+    duplicates: u64,
+    late: u64,
+    expired: u64,
+}
+
+impl Default for ConfirmedObservations {
+    fn default() -> Self {
+        Self::new(CONFIRMED_OBSERVATION_WINDOW_SLOTS)
+    }
+}
+
+impl ConfirmedObservations {
+    pub fn new(window_slots: u64) -> Self {
+        Self {
+            sightings: BTreeMap::new(),
+            finalized: BTreeSet::new(),
+            newest_voted: 0,
+            window_slots,
+            duplicates: 0,
+            late: 0,
+            expired: 0,
+        }
+    }
+
+    /// record `voted_slot` in the confirmed block at `confirmed_slot`, false
+    /// when it was sighted or finalized already
+    pub fn observe(&mut self, voted_slot: Slot, confirmed_slot: Slot, seen: Instant) -> bool {
+        if self.finalized.contains(&voted_slot) {
+            self.late += 1;
+            return false;
+        }
+        if self.sightings.contains_key(&voted_slot) {
+            self.duplicates += 1;
+            return false;
+        }
+        self.advance(voted_slot);
+        if voted_slot < self.floor() {
+            self.expired += 1;
+            return false;
+        }
+        self.sightings.insert(voted_slot, Sighting { confirmed_slot, seen });
+        true
+    }
+
+    /// fill in the confirmed latency and finalization delay of a finalized
+    /// vote, left unset when its slot was never sighted
+    pub fn attach(&mut self, vote: &mut ConfirmedVote) {
+        self.advance(vote.voted_slot);
+        if let Some(sighting) = self.sightings.remove(&vote.voted_slot) {
+            vote.confirmed_latency = Some(sighting.confirmed_slot.saturating_sub(vote.voted_slot));
+            vote.finalization_delay_ms = Some(sighting.seen.elapsed().as_millis() as u64);
+        }
+        if vote.voted_slot >= self.floor() {
+            self.finalized.insert(vote.voted_slot);
+        }
+    }
+
+    /// sightings waiting for their finalized vote
+    pub fn len(&self) -> usize {
+        self.sightings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sightings.is_empty()
+    }
+
+    /// second sightings of a voted slot, not kept
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// sightings after the vote was finalized
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+
+    /// sightings forgotten without a finalized vote
+    pub fn expired(&self) -> u64 {
+        self.expired
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.sightings.len() * (size_of::<Slot>() + size_of::<Sighting>()) + self.finalized.len() * size_of::<Slot>()
+    }
+
+    fn floor(&self) -> Slot {
+        self.newest_voted.saturating_sub(self.window_slots)
+    }
+
+    fn advance(&mut self, voted_slot: Slot) {
+        if voted_slot <= self.newest_voted {
+            return;
+        }
+        self.newest_voted = voted_slot;
+        let floor = self.floor();
+        if self.sightings.first_key_value().is_some_and(|(&oldest, _)| oldest < floor) {
+            let kept = self.sightings.split_off(&floor);
+            self.expired += self.sightings.len() as u64;
+            self.sightings = kept;
+        }
+        if self.finalized.first().is_some_and(|&oldest| oldest < floor) {
+            self.finalized = self.finalized.split_off(&floor);
+        }
+    }
+}
+
+/// confirmed blocks waiting for their slot to be finalized, `commitment = "confirmed"`
+///
+/// a block whose slot was reported finalized before it arrived is released
+/// right away. blocks more than `HELD_BLOCK_WINDOW_SLOTS` behind the newest
+/// finalized slot were on a fork that did not make it and are dropped.
+#[derive(Debug, Default)]
+pub struct HeldBlocks {
+    blocks: BTreeMap<Slot, SubscribeUpdateBlock>,
+    // finalized slots within the window
+    finalized: BTreeSet<Slot>,
+    dropped: u64,
+}
+
+impl HeldBlocks {
+    /// hold `block` until its slot is finalized, returns it when it already is
+    ///
+    /// a second block for a held slot, e.g. after a resubscription, is ignored.
+    pub fn hold(&mut self, block: SubscribeUpdateBlock) -> Option<SubscribeUpdateBlock> {
+        if self.finalized.contains(&block.slot) {
+            return Some(block);
+        }
+        self.blocks.entry(block.slot).or_insert(block);
+        None
+    }
+
+    /// the held block of a newly finalized slot, if any
+    pub fn finalize(&mut self, slot: Slot) -> Option<SubscribeUpdateBlock> {
+        self.finalized.insert(slot);
+        let newest = self.finalized.last().copied().unwrap_or(slot);
+        let floor = newest.saturating_sub(HELD_BLOCK_WINDOW_SLOTS);
+        self.finalized = self.finalized.split_off(&floor);
+        let kept = self.blocks.split_off(&floor);
+        self.dropped += self.blocks.len() as u64;
+        self.blocks = kept;
+        self.blocks.remove(&slot)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// blocks never finalized, dropped as abandoned forks
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_block, make_confirmed_vote, make_vote_tx, signature};
+
+    fn finalized(voted_slot: Slot, latency: u64, observations: &mut ConfirmedObservations) -> ConfirmedVote {
+        let mut vote = make_confirmed_vote(voted_slot, voted_slot, latency);
+        observations.attach(&mut vote);
+        vote
+    }
+
+    #[test]
+    fn first_sighting_of_a_voted_slot_counts() {
+        let mut observations = ConfirmedObservations::default();
+        let now = Instant::now();
+        assert!(observations.observe(100, 101, now));
+        // the same block again after a resubscription, then the vote on a second fork
+        assert!(!observations.observe(100, 101, now));
+        assert!(!observations.observe(100, 103, now));
+        assert_eq!((observations.len(), observations.duplicates()), (1, 2));
+
+        let vote = finalized(100, 2, &mut observations);
+        assert_eq!(vote.confirmed_latency, Some(1));
+        assert!(vote.finalization_delay_ms.is_some());
+        // the finalized figures are left alone
+        assert_eq!(vote.latency, 2);
+        assert!(observations.is_empty());
+    }
+
+    #[test]
+    fn sighting_after_finalization_is_late() {
+        let mut observations = ConfirmedObservations::default();
+        let vote = finalized(100, 1, &mut observations);
+        assert_eq!((vote.confirmed_latency, vote.finalization_delay_ms), (None, None));
+
+        assert!(!observations.observe(100, 101, Instant::now()));
+        assert_eq!((observations.late(), observations.duplicates()), (1, 0));
+        assert!(observations.is_empty());
+    }
+
+    #[test]
+    fn both_streams_delivering_the_same_block() {
+        let mut observations = ConfirmedObservations::default();
+        let now = Instant::now();
+        let block = make_block(103, &[
+            make_vote_tx(signature(1), 103, &[(100, 1), (101, 1)]),
+            make_vote_tx(signature(2), 103, &[(101, 1), (102, 1)]),
+        ]);
+        let key = crate::test_support::vote_account_key();
+        let mut voted_slots = crate::vote_tracker::block_votes(&block, &key);
+        voted_slots.sort_unstable();
+        voted_slots.dedup();
+        assert_eq!(voted_slots, [100, 101, 102]);
+
+        // the confirmed subscription of `both`, then a redelivery of the same block
+        for _ in 0..2 {
+            for &voted_slot in &voted_slots {
+                observations.observe(voted_slot, block.slot, now);
+            }
+        }
+        assert_eq!((observations.len(), observations.duplicates()), (3, 3));
+
+        // the finalized stream confirms each voted slot once
+        let latencies: Vec<_> = voted_slots
+            .iter()
+            .map(|&voted_slot| finalized(voted_slot, 103 - voted_slot, &mut observations).confirmed_latency)
+            .collect();
+        assert_eq!(latencies, [Some(3), Some(2), Some(1)]);
+        assert!(observations.is_empty());
+
+        // and a late confirmed copy of the block adds nothing
+        for &voted_slot in &voted_slots {
+            assert!(!observations.observe(voted_slot, block.slot, now));
+        }
+        assert_eq!((observations.late(), observations.len()), (3, 0));
+    }
+
+    #[test]
+    fn sightings_behind_the_window_expire() {
+        let mut observations = ConfirmedObservations::new(10);
+        let now = Instant::now();
+        assert!(observations.observe(100, 101, now));
+        assert!(observations.observe(105, 106, now));
+        // 111 moves the floor past 100
+        assert!(observations.observe(111, 112, now));
+        assert_eq!((observations.len(), observations.expired()), (2, 1));
+        // too old to be kept at all
+        assert!(!observations.observe(99, 100, now));
+        assert_eq!(observations.expired(), 2);
+
+        let vote = finalized(100, 1, &mut observations);
+        assert_eq!(vote.confirmed_latency, None);
+        assert_eq!(finalized(105, 1, &mut observations).confirmed_latency, Some(1));
+    }
+
+    #[test]
+    fn held_block_is_released_once_finalized() {
+        let mut held = HeldBlocks::default();
+        assert!(held.hold(make_block(101, &[])).is_none());
+        // a second copy of the held slot is ignored
+        assert!(held.hold(make_block(101, &[])).is_none());
+        assert_eq!(held.len(), 1);
+
+        assert!(held.finalize(100).is_none());
+        assert_eq!(held.finalize(101).map(|block| block.slot), Some(101));
+        assert!(held.is_empty());
+
+        // finalized before the confirmed block arrived
+        assert!(held.finalize(102).is_none());
+        assert_eq!(held.hold(make_block(102, &[])).map(|block| block.slot), Some(102));
+        assert!(held.is_empty());
+    }
+
+    #[test]
+    fn abandoned_fork_blocks_are_dropped() {
+        let mut held = HeldBlocks::default();
+        held.hold(make_block(100, &[]));
+        held.hold(make_block(200, &[]));
+        assert!(held.finalize(100 + HELD_BLOCK_WINDOW_SLOTS).is_none());
+        assert_eq!((held.len(), held.dropped()), (2, 0));
+
+        assert!(held.finalize(101 + HELD_BLOCK_WINDOW_SLOTS).is_none());
+        assert_eq!((held.len(), held.dropped()), (1, 1));
+        assert_eq!(held.finalize(200).map(|block| block.slot), Some(200));
+    }
+}
@@ -0,0 +1,471 @@
+use std::fmt;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::Serialize;
+
+use crate::analyze::{analyze_votes, read_votes, resolve_inputs, AnalysisInputs, AnalysisReport};
+use crate::config::Config;
+use crate::error::{Result, VoteMonitorError};
+use crate::performance::ConfirmedVote;
+
+/// votes per range below which the latency change is not judged
+pub const MIN_COMPARE_VOTES: usize = 30;
+
+/// welch t statistic beyond which a latency change is unlikely to be noise
+const SIGNIFICANT_T: f64 = 2.0;
+
+/// one side of a comparison, parsed from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompareRange {
+    /// votes confirmed in `[start, end)` of the shared inputs, an open end is
+    /// unbounded
+    Time {
+        start: Option<DateTime<Local>>,
+        end: Option<DateTime<Local>>,
+    },
+    /// votes of these files, directories or glob patterns
+    Files(Vec<String>),
+}
+
+impl CompareRange {
+    /// `START..END` with dates or local times and either end optional, any
+    /// other argument is a comma-separated list of paths
+    pub fn parse(arg: &str) -> Result<Self> {
+        if let Some((start, end)) = arg.split_once("..") {
+            match (parse_bound(start), parse_bound(end)) {
+                (Some(start), Some(end)) => {
+                    if let (Some(start), Some(end)) = (start, end) {
+                        if start >= end {
+                            return Err(VoteMonitorError::Config(format!("empty time range '{}'", arg)));
+                        }
+                    }
+                    return Ok(Self::Time { start, end });
+                }
+                // `../logs` is a path, not a range
+                _ if start.is_empty() || start.contains(['/', '*']) => {}
+                _ => return Err(VoteMonitorError::Config(format!("invalid time range '{}'", arg))),
+            }
+        }
+
+        let paths: Vec<String> = arg.split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect();
+        if paths.is_empty() {
+            return Err(VoteMonitorError::Config(format!("invalid compare range '{}'", arg)));
+        }
+        Ok(Self::Files(paths))
+    }
+
+    /// whether the range keeps a vote confirmed at `timestamp`
+    pub fn contains(&self, timestamp: &DateTime<Local>) -> bool {
+        match self {
+            Self::Time { start, end } => {
+                start.map_or(true, |start| *timestamp >= start) && end.map_or(true, |end| *timestamp < end)
+            }
+            Self::Files(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for CompareRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |bound: &Option<DateTime<Local>>| {
+            bound.map(|time| time.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default()
+        };
+        match self {
+            Self::Time { start, end } => write!(f, "{}..{}", bound(start), bound(end)),
+            Self::Files(paths) => write!(f, "{}", paths.join(",")),
+        }
+    }
+}
+
+/// a date is its local midnight, times are local unless they carry an offset
+fn parse_bound(text: &str) -> Option<Option<DateTime<Local>>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(None);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(Some(time.with_timezone(&Local)));
+    }
+    let naive = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))?;
+    Local.from_local_datetime(&naive).earliest().map(Some)
+}
+
+/// mean and sample standard deviation of the vote latencies
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LatencySample {
+    pub votes: usize,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl LatencySample {
+    pub fn of(votes: &[ConfirmedVote]) -> Self {
+        let count = votes.len();
+        if count == 0 {
+            return Self::default();
+        }
+        let mean = votes.iter().map(|vote| vote.latency as f64).sum::<f64>() / count as f64;
+        let variance = if count > 1 {
+            votes.iter().map(|vote| (vote.latency as f64 - mean).powi(2)).sum::<f64>() / (count - 1) as f64
+        } else {
+            0.0
+        };
+        Self { votes: count, mean, stddev: variance.sqrt() }
+    }
+}
+
+/// whether the mean latency moved by more than the spread of the votes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Significance {
+    /// fewer than `MIN_COMPARE_VOTES` votes on a side
+    TooFewVotes,
+    NotSignificant,
+    Likely,
+}
+
+impl Significance {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TooFewVotes => "too few votes to tell",
+            Self::NotSignificant => "within noise",
+            Self::Likely => "likely a real change",
+        }
+    }
+}
+
+/// change in mean latency from a to b, against the spread of both ranges
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LatencyChange {
+    pub a: LatencySample,
+    pub b: LatencySample,
+    /// b minus a, in slots
+    pub delta: f64,
+    /// delta over the pooled standard deviation, none when neither range varies
+    pub effect_size: Option<f64>,
+    /// welch's t statistic, none when neither range varies
+    pub t: Option<f64>,
+    pub significance: Significance,
+}
+
+impl LatencyChange {
+    pub fn between(a: LatencySample, b: LatencySample) -> Self {
+        let delta = b.mean - a.mean;
+        let pooled = if a.votes + b.votes > 2 {
+            let a_weight = a.votes.saturating_sub(1) as f64;
+            let b_weight = b.votes.saturating_sub(1) as f64;
+            ((a_weight * a.stddev.powi(2) + b_weight * b.stddev.powi(2)) / (a_weight + b_weight)).sqrt()
+        } else {
+            0.0
+        };
+        let standard_error = if a.votes > 0 && b.votes > 0 {
+            (a.stddev.powi(2) / a.votes as f64 + b.stddev.powi(2) / b.votes as f64).sqrt()
+        } else {
+            0.0
+        };
+        let effect_size = (pooled > 0.0).then(|| delta / pooled);
+        let t = (standard_error > 0.0).then(|| delta / standard_error);
+
+        let significance = if a.votes < MIN_COMPARE_VOTES || b.votes < MIN_COMPARE_VOTES {
+            Significance::TooFewVotes
+        } else {
+            match t {
+                Some(t) if t.abs() >= SIGNIFICANT_T => Significance::Likely,
+                // identical constant latencies differ only if the means do
+                None if delta != 0.0 => Significance::Likely,
+                _ => Significance::NotSignificant,
+            }
+        };
+
+        Self { a, b, delta, effect_size, t, significance }
+    }
+
+    /// cohen's usual labels for the effect size
+    pub fn effect_label(&self) -> &'static str {
+        match self.effect_size.map(f64::abs) {
+            None => "n/a",
+            Some(d) if d < 0.2 => "negligible",
+            Some(d) if d < 0.5 => "small",
+            Some(d) if d < 0.8 => "medium",
+            Some(_) => "large",
+        }
+    }
+}
+
+/// one row of the side by side table
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricDelta {
+    pub metric: &'static str,
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+    /// b minus a
+    pub delta: Option<f64>,
+    /// whether b is better than a, none for neutral metrics or no change
+    pub improved: Option<bool>,
+}
+
+impl MetricDelta {
+    /// `higher_is_better` none for metrics without a direction, such as the vote count
+    fn new(metric: &'static str, a: Option<f64>, b: Option<f64>, higher_is_better: Option<bool>) -> Self {
+        let delta = a.zip(b).map(|(a, b)| b - a);
+        let improved = delta
+            .filter(|delta| *delta != 0.0)
+            .zip(higher_is_better)
+            .map(|(delta, higher)| (delta > 0.0) == higher);
+        Self { metric, a, b, delta, improved }
+    }
+}
+
+/// the full report of one range
+#[derive(Debug, Clone, Serialize)]
+pub struct RangeReport {
+    pub range: String,
+    pub report: AnalysisReport,
+}
+
+/// two ranges of stored votes side by side
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonReport {
+    pub a: RangeReport,
+    pub b: RangeReport,
+    pub metrics: Vec<MetricDelta>,
+    pub latency_change: LatencyChange,
+}
+
+/// stored votes of one range
+#[derive(Debug, Clone)]
+pub struct RangeVotes {
+    pub range: CompareRange,
+    pub votes: Vec<ConfirmedVote>,
+    pub inputs: AnalysisInputs,
+}
+
+impl RangeVotes {
+    /// read the votes of `range`, time ranges filter `default_paths`
+    pub async fn load(range: CompareRange, default_paths: &[String]) -> Result<Self> {
+        let paths = match &range {
+            CompareRange::Files(paths) => paths.as_slice(),
+            CompareRange::Time { .. } => default_paths,
+        };
+        let files = resolve_inputs(paths)?;
+        let (mut votes, inputs) = read_votes(&files).await?;
+        votes.retain(|vote| range.contains(&vote.timestamp));
+        Ok(Self { range, votes, inputs })
+    }
+}
+
+fn share(count: u64, votes: u64) -> Option<f64> {
+    (votes > 0).then(|| count as f64 / votes as f64 * 100.0)
+}
+
+/// full stats of both vote sets and the change from a to b
+pub fn compare_votes(a: RangeVotes, b: RangeVotes, config: &Config) -> ComparisonReport {
+    let latency_change = LatencyChange::between(LatencySample::of(&a.votes), LatencySample::of(&b.votes));
+    let a = RangeReport { range: a.range.to_string(), report: analyze_votes(a.votes, a.inputs, config) };
+    let b = RangeReport { range: b.range.to_string(), report: analyze_votes(b.votes, b.inputs, config) };
+
+    let (ra, rb) = (&a.report, &b.report);
+    let has_votes = |report: &AnalysisReport| report.votes > 0;
+    let metric = |report: &AnalysisReport, value: fn(&AnalysisReport) -> Option<f64>| {
+        if has_votes(report) { value(report) } else { None }
+    };
+    let row = |name: &'static str, higher_is_better: Option<bool>, value: fn(&AnalysisReport) -> Option<f64>| {
+        MetricDelta::new(name, metric(ra, value), metric(rb, value), higher_is_better)
+    };
+
+    let metrics = vec![
+        MetricDelta::new("votes", Some(ra.votes as f64), Some(rb.votes as f64), None),
+        row("tvc efficiency %", Some(true), |r| Some(r.efficiency)),
+        row("tvc per vote", Some(true), |r| Some(r.tvc_earned as f64 / r.votes as f64)),
+        row("avg latency", Some(false), |r| Some(r.latency.avg)),
+        row("p50 latency", Some(false), |r| r.latency.p50.map(|v| v as f64)),
+        row("p90 latency", Some(false), |r| r.latency.p90.map(|v| v as f64)),
+        row("p99 latency", Some(false), |r| r.latency.p99.map(|v| v as f64)),
+        row("max latency", Some(false), |r| r.latency.max.map(|v| v as f64)),
+        row("low latency %", Some(true), |r| Some(r.latency.low_latency_pct)),
+        row("grace-dependent %", Some(false), |r| Some(r.latency.grace_dependent_pct)),
+        row("optimal %", Some(true), |r| share(r.optimal_votes, r.votes)),
+        row("good %", None, |r| share(r.good_votes, r.votes)),
+        row("poor %", Some(false), |r| share(r.poor_votes, r.votes)),
+    ];
+
+    ComparisonReport { a, b, metrics, latency_change }
+}
+
+fn format_value(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.fract() == 0.0 => format!("{}", value),
+        Some(value) => format!("{:.2}", value),
+        None => "-".to_string(),
+    }
+}
+
+pub fn print_comparison(report: &ComparisonReport) {
+    println!("voteperfx comparison");
+    for (name, side) in [("a", &report.a), ("b", &report.b)] {
+        println!(
+            "{}: {} ({} votes from {} files, {} malformed)",
+            name, side.range, side.report.votes, side.report.inputs.files.len(), side.report.inputs.malformed_lines
+        );
+    }
+    println!();
+
+    println!("{:<18} {:>12} {:>12} {:>12}", "metric", "a", "b", "delta");
+    for metric in &report.metrics {
+        let delta = match metric.delta {
+            Some(delta) if delta.fract() == 0.0 => format!("{:+}", delta),
+            Some(delta) => format!("{:+.2}", delta),
+            None => "-".to_string(),
+        };
+        let verdict = match metric.improved {
+            Some(true) => "  better",
+            Some(false) => "  worse",
+            None => "",
+        };
+        println!(
+            "{:<18} {:>12} {:>12} {:>12}{}",
+            metric.metric, format_value(metric.a), format_value(metric.b), delta, verdict
+        );
+    }
+
+    let change = &report.latency_change;
+    println!();
+    println!("latency stddev: a {:.2} | b {:.2} slots", change.a.stddev, change.b.stddev);
+    match (change.effect_size, change.t) {
+        (Some(effect_size), Some(t)) => println!(
+            "mean latency change: {:+.2} slots, {:+.2} pooled stddev ({}), welch t {:+.1}: {}",
+            change.delta, effect_size, change.effect_label(), t, change.significance.as_str()
+        ),
+        _ => println!("mean latency change: {:+.2} slots: {}", change.delta, change.significance.as_str()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::make_confirmed_vote;
+
+    /// a range of votes with these latencies, one slot apart
+    fn range(latencies: impl IntoIterator<Item = u64>) -> RangeVotes {
+        let votes = latencies
+            .into_iter()
+            .enumerate()
+            .map(|(i, latency)| make_confirmed_vote(i as u64, 1_000 + i as u64, latency))
+            .collect();
+        RangeVotes { range: CompareRange::Files(vec!["synthetic".to_string()]), votes, inputs: AnalysisInputs::default() }
+    }
+
+    fn alternating(first: u64, second: u64, count: usize) -> impl Iterator<Item = u64> {
+        [first, second].into_iter().cycle().take(count)
+    }
+
+    fn row<'a>(report: &'a ComparisonReport, metric: &str) -> &'a MetricDelta {
+        report.metrics.iter().find(|row| row.metric == metric).unwrap()
+    }
+
+    #[test]
+    fn latency_sample_of_a_known_set() {
+        let sample = LatencySample::of(&range([1, 2, 3, 4]).votes);
+        assert_eq!((sample.votes, sample.mean), (4, 2.5));
+        assert!((sample.stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-9);
+
+        let single = LatencySample::of(&range([7]).votes);
+        assert_eq!((single.mean, single.stddev), (7.0, 0.0));
+        assert_eq!(LatencySample::of(&[]).votes, 0);
+    }
+
+    #[test]
+    fn clear_improvement_is_likely() {
+        let report = compare_votes(range(alternating(3, 5, 100)), range(alternating(1, 2, 100)), &Config::default());
+        let change = report.latency_change;
+        assert_eq!(change.delta, -2.5);
+        assert_eq!(change.significance, Significance::Likely);
+        assert!(change.t.unwrap() < -SIGNIFICANT_T);
+        assert_eq!(change.effect_label(), "large");
+
+        let latency = row(&report, "avg latency");
+        assert_eq!((latency.a, latency.b, latency.improved), (Some(4.0), Some(1.5), Some(true)));
+        assert_eq!(row(&report, "tvc efficiency %").improved, Some(true));
+        assert_eq!(row(&report, "max latency").delta, Some(-3.0));
+        // the count has no direction
+        assert_eq!(row(&report, "votes").improved, None);
+    }
+
+    #[test]
+    fn same_distribution_is_within_noise() {
+        let report = compare_votes(range(alternating(1, 4, 100)), range(alternating(4, 1, 100)), &Config::default());
+        let change = report.latency_change;
+        assert_eq!(change.delta, 0.0);
+        assert_eq!(change.significance, Significance::NotSignificant);
+        assert_eq!(change.effect_label(), "negligible");
+        assert_eq!(row(&report, "avg latency").improved, None);
+
+        // a small shift against a wide spread
+        let wide = compare_votes(range(alternating(1, 9, 60)), range(alternating(1, 10, 60)), &Config::default());
+        assert_eq!(wide.latency_change.significance, Significance::NotSignificant);
+        assert_eq!(row(&wide, "avg latency").improved, Some(false));
+    }
+
+    #[test]
+    fn small_ranges_are_not_judged() {
+        let few = MIN_COMPARE_VOTES - 1;
+        let report = compare_votes(range(alternating(5, 6, few)), range(alternating(1, 1, 200)), &Config::default());
+        assert_eq!(report.latency_change.significance, Significance::TooFewVotes);
+        // the table still has the figures
+        assert_eq!(row(&report, "avg latency").improved, Some(true));
+    }
+
+    #[test]
+    fn constant_latencies() {
+        let same = compare_votes(range(alternating(1, 1, 50)), range(alternating(1, 1, 50)), &Config::default());
+        assert_eq!((same.latency_change.t, same.latency_change.effect_size), (None, None));
+        assert_eq!(same.latency_change.significance, Significance::NotSignificant);
+        assert_eq!(same.latency_change.effect_label(), "n/a");
+
+        let shifted = compare_votes(range(alternating(1, 1, 50)), range(alternating(2, 2, 50)), &Config::default());
+        assert_eq!(shifted.latency_change.significance, Significance::Likely);
+        assert_eq!(row(&shifted, "avg latency").improved, Some(false));
+    }
+
+    #[test]
+    fn empty_range_has_no_figures() {
+        let report = compare_votes(range(alternating(1, 2, 40)), range([]), &Config::default());
+        let votes = row(&report, "votes");
+        assert_eq!((votes.a, votes.b, votes.delta), (Some(40.0), Some(0.0), Some(-40.0)));
+        for row in report.metrics.iter().filter(|row| row.metric != "votes") {
+            assert_eq!((row.b, row.delta, row.improved), (None, None, None), "{}", row.metric);
+        }
+        assert_eq!(report.latency_change.significance, Significance::TooFewVotes);
+    }
+
+    #[test]
+    fn ranges_from_the_command_line() {
+        let day = |d: u32| Local.from_local_datetime(&NaiveDate::from_ymd_opt(2026, 10, d).unwrap().and_hms_opt(0, 0, 0).unwrap()).unwrap();
+
+        let range = CompareRange::parse("2026-10-01..2026-10-03").unwrap();
+        assert_eq!(range, CompareRange::Time { start: Some(day(1)), end: Some(day(3)) });
+        assert!(range.contains(&day(1)) && range.contains(&day(2)));
+        // the end is exclusive
+        assert!(!range.contains(&day(3)));
+
+        let open = CompareRange::parse("2026-10-02 12:00..").unwrap();
+        assert!(open.contains(&day(9)) && !open.contains(&day(2)));
+        assert_eq!(open.to_string(), "2026-10-02 12:00..");
+
+        assert!(CompareRange::parse("2026-10-03..2026-10-01").is_err());
+        assert!(CompareRange::parse("2026-10-01..yesterday").is_err());
+        assert_eq!(CompareRange::parse("../logs").unwrap(), CompareRange::Files(vec!["../logs".to_string()]));
+        assert_eq!(
+            CompareRange::parse("a.csv, b.csv,").unwrap(),
+            CompareRange::Files(vec!["a.csv".to_string(), "b.csv".to_string()])
+        );
+        assert!(CompareRange::parse(" , ").is_err());
+    }
+}
@@ -1,7 +1,34 @@
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use crate::performance::TvcPerformanceLevel;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use crate::performance::{
+    TvcPerformanceLevel, calculate_tvc_credits_from_latency, categorize_tvc_performance,
+    VOTE_CREDITS_GRACE_SLOTS, VOTE_CREDITS_MAXIMUM_PER_SLOT,
+};
+use crate::performance::{ConfirmationSource, HistogramScale, DEFAULT_LATENCY_WINDOW_SECS, DEFAULT_POOR_VOTES_WINDOW, DEFAULT_RECENT_VOTES_WINDOW};
+use crate::status_api::{DEFAULT_EVENT_REPLAY_MINUTES, DEFAULT_EVENT_REPLAY_VOTES};
+use crate::streaks::DEFAULT_STREAK_ALERT_VOTES;
+use crate::commitment::Commitment;
+use crate::efficiency_trend::{DEFAULT_TREND_BUCKETS, DEFAULT_TREND_BUCKET_SECS};
+use crate::epoch::{DEFAULT_SLOTS_PER_EPOCH, default_epoch_report_dir, default_epoch_summary_path};
 use crate::error::{Result, VoteMonitorError};
+use crate::finalized_slots::DEFAULT_BLOCK_HORIZON_SLOTS;
+use crate::network::{Explorer, Network, NetworkProfile, DEFAULT_SLOT_DURATION_MS, SIGNATURE_PLACEHOLDER};
+use crate::pipeline::UPDATE_CHANNEL_CAPACITY;
+use crate::signature_display::SignatureDisplay;
+use crate::stream_health::{UpdateKind, STALE_MIN_SLOTS};
+use crate::theme::DashboardTheme;
+use crate::tower_root::DEFAULT_ROOT_LAG_ALERT_SLOTS;
+use crate::vote_tracker::{
+    DEFAULT_CONFIRMED_CAPACITY, DEFAULT_DUPLICATE_WINDOW_SLOTS, DEFAULT_MAX_PENDING_VOTES, DEFAULT_MISSED_VOTE_CUTOFF_SLOTS,
+    DEFAULT_PENDING_CLEANUP_INTERVAL_SLOTS, DEFAULT_PENDING_MAX_AGE_SLOTS,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceFilterConfig {
@@ -11,6 +38,35 @@ pub struct PerformanceFilterConfig {
     pub min_tvc_threshold: Option<u64>,
     pub max_tvc_threshold: Option<u64>,
     pub performance_levels: Vec<String>,
+    /// directory the daily performance_issues files are written to
+    #[serde(default = "default_performance_log_dir")]
+    pub performance_log_dir: PathBuf,
+    /// delete daily files older than this many days, keep everything when unset
+    #[serde(default)]
+    pub retention_days: Option<u64>,
+    /// gzip daily files once the day is over
+    #[serde(default)]
+    pub compress: bool,
+    /// events of failed writes kept for a retry, the oldest are dropped
+    /// beyond it (0 drops a failed batch)
+    #[serde(default = "default_retry_queue_events")]
+    pub retry_queue_events: usize,
+    /// events waiting for a retry longer than this are dropped, 0 keeps them
+    /// until the queue overflows
+    #[serde(default = "default_retry_max_age_secs")]
+    pub retry_max_age_secs: u64,
+}
+
+fn default_performance_log_dir() -> PathBuf {
+    PathBuf::from("./performance_issues")
+}
+
+fn default_retry_queue_events() -> usize {
+    10_000
+}
+
+fn default_retry_max_age_secs() -> u64 {
+    3600
 }
 
 impl Default for PerformanceFilterConfig {
@@ -22,43 +78,1013 @@ impl Default for PerformanceFilterConfig {
             min_tvc_threshold: None,
             max_tvc_threshold: Some(15),
             performance_levels: vec!["poor".to_string(), "critical".to_string()],
+            performance_log_dir: default_performance_log_dir(),
+            retention_days: None,
+            compress: false,
+            retry_queue_events: default_retry_queue_events(),
+            retry_max_age_secs: default_retry_max_age_secs(),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub grpc_url: String,
-    pub vote_account: String,
-    pub performance_logging: PerformanceFilterConfig,
+#[serde(default)]
+pub struct TrackerConfig {
+    /// slots behind the latest finalized slot before an unconfirmed vote is declared missed
+    pub missed_vote_cutoff_slots: u64,
+    /// seconds of vote transactions without any block before switching to the
+    /// slot-status fallback, the first block after it switches back. 0
+    /// disables the fallback
+    pub block_timeout_secs: u64,
+    /// confirmed votes retained by the tracker
+    pub confirmed_capacity: usize,
+    /// slots behind the newest finalized block before an older block is
+    /// rejected as stale, duplicates within it are always skipped
+    pub block_horizon_slots: u64,
+    /// slots the finalized stream may trail the processed tip before a
+    /// warning, 0 disables it
+    pub finalized_lag_alert_slots: u64,
+    /// slots behind the newest confirmed voted slot within which a second
+    /// confirmation of the same slot is counted as a duplicate
+    pub duplicate_window_slots: u64,
+    /// slots the tower root may trail the newest voted slot before a
+    /// warning, 0 disables it
+    pub root_lag_alert_slots: u64,
+    /// finalized slots between two sweeps of the pending votes
+    pub pending_cleanup_interval_slots: u64,
+    /// slots behind the newest finalized slot before the sweep drops a
+    /// pending vote, at least `missed_vote_cutoff_slots`
+    pub pending_max_age_slots: u64,
+    /// pending votes held before the oldest are evicted, 0 disables the cap
+    pub max_pending_votes: usize,
+}
+
+/// a set, non-empty environment variable, trimmed
+fn env_override(name: &str) -> Option<String> {
+    std::env::var(name).ok().map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+/// names of the VOTEPERFX_* variables that override the file, for the startup log
+pub fn env_overrides() -> Vec<&'static str> {
+    [ENV_GRPC_URL, ENV_VOTE_ACCOUNT, ENV_X_TOKEN]
+        .into_iter()
+        .filter(|name| env_override(name).is_some())
+        .collect()
+}
+
+/// a grpc endpoint with an http or https scheme and a host
+pub(crate) fn parse_grpc_url(endpoint: &str) -> Result<Url> {
+    let url = Url::parse(endpoint).map_err(|e| {
+        VoteMonitorError::Config(format!("grpc endpoint {} is not a url ({}), expected e.g. https://host:443", endpoint, e))
+    })?;
+    // `host:10000` parses with `host` as the scheme
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(VoteMonitorError::Config(format!(
+            "grpc endpoint {} must be an http:// or https:// url with a host, e.g. https://host:443",
+            endpoint
+        )));
+    }
+    Ok(url)
+}
+
+fn is_loopback(url: &Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    host.eq_ignore_ascii_case("localhost")
+        || host.trim_start_matches('[').trim_end_matches(']').parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            missed_vote_cutoff_slots: DEFAULT_MISSED_VOTE_CUTOFF_SLOTS,
+            block_timeout_secs: 60,
+            confirmed_capacity: DEFAULT_CONFIRMED_CAPACITY,
+            block_horizon_slots: DEFAULT_BLOCK_HORIZON_SLOTS,
+            finalized_lag_alert_slots: 150,
+            duplicate_window_slots: DEFAULT_DUPLICATE_WINDOW_SLOTS,
+            root_lag_alert_slots: DEFAULT_ROOT_LAG_ALERT_SLOTS,
+            pending_cleanup_interval_slots: DEFAULT_PENDING_CLEANUP_INTERVAL_SLOTS,
+            pending_max_age_slots: DEFAULT_PENDING_MAX_AGE_SLOTS,
+            max_pending_votes: DEFAULT_MAX_PENDING_VOTES,
+        }
+    }
+}
+
+/// channels between the grpc stream and the processing tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    /// updates buffered per kind (transactions, blocks, slots)
+    pub channel_capacity: usize,
+    /// discard blocks older than the newest finalized slot instead of queueing
+    /// them while the block channel is backed up, their votes are confirmed
+    /// from the transaction slot
+    pub drop_stale_blocks: bool,
+    /// block channel fill, in percent of its capacity, above which stale
+    /// blocks are dropped
+    pub drop_threshold_pct: u8,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            channel_capacity: UPDATE_CHANNEL_CAPACITY,
+            drop_stale_blocks: false,
+            drop_threshold_pct: 80,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// seconds of confirmed votes in the rolling average latency
+    pub recent_window_secs: u64,
+    /// length of one efficiency trend bucket
+    pub trend_bucket_secs: u64,
+    /// efficiency trend buckets kept
+    pub trend_buckets: usize,
+    /// votes below max credits kept for the poor performance events panel
+    /// and /status, the per-severity counts cover the whole session
+    pub poor_votes_window: usize,
+    /// leave votes for our own leader slots out of the session and window
+    /// latency averages, they are reported separately either way (rpc_url)
+    pub exclude_leader_slots: bool,
+    /// a stats reset (`r`, SIGUSR2, POST /reset) keeps the per-epoch breakdown
+    pub reset_keeps_epochs: bool,
+    /// serve POST /reset on http_listen, the api has no authentication
+    pub reset_over_http: bool,
+    /// serve POST /switch-account on http_listen, the api has no authentication
+    pub switch_account_over_http: bool,
+    /// session stats of an account switched away from are appended here, one
+    /// json line per switch
+    pub account_archive_file: PathBuf,
+    /// confirmed votes GET /events replays to a client when it connects,
+    /// from the dashboard.recent_votes_window kept, 0 replays nothing
+    pub event_replay_votes: usize,
+    /// minutes back the replay reaches, older votes are left out
+    pub event_replay_minutes: u64,
+    /// publish the end of an optimal vote streak at least this long, 0 never
+    pub streak_alert_votes: u64,
+    /// count estimated latencies (direct_block_only, estimated_tx_slot,
+    /// tower_latency) in the latency percentiles, see `ConfirmationKind`
+    pub percentiles_include_estimates: bool,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            recent_window_secs: DEFAULT_LATENCY_WINDOW_SECS,
+            trend_bucket_secs: DEFAULT_TREND_BUCKET_SECS,
+            trend_buckets: DEFAULT_TREND_BUCKETS,
+            poor_votes_window: DEFAULT_POOR_VOTES_WINDOW,
+            exclude_leader_slots: false,
+            reset_keeps_epochs: true,
+            reset_over_http: false,
+            switch_account_over_http: false,
+            account_archive_file: PathBuf::from("./account_sessions.jsonl"),
+            event_replay_votes: DEFAULT_EVENT_REPLAY_VOTES,
+            event_replay_minutes: DEFAULT_EVENT_REPLAY_MINUTES,
+            streak_alert_votes: DEFAULT_STREAK_ALERT_VOTES,
+            percentiles_include_estimates: false,
+        }
+    }
+}
+
+/// vote credit parameters, governed by feature gates and different on testnet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TvcConfig {
+    /// slots of latency that still earn the maximum credits
+    pub grace_slots: u64,
+    pub max_credits_per_slot: u64,
+}
+
+impl Default for TvcConfig {
+    fn default() -> Self {
+        Self {
+            grace_slots: VOTE_CREDITS_GRACE_SLOTS as u64,
+            max_credits_per_slot: VOTE_CREDITS_MAXIMUM_PER_SLOT as u64,
+        }
+    }
+}
+
+impl TvcConfig {
+    /// lowest credits still counted as a good vote (12 of 16)
+    #[inline]
+    pub fn good_threshold(&self) -> u64 {
+        self.max_credits_per_slot * 3 / 4
+    }
+
+    /// latency at which credits bottom out at 1
+    #[inline]
+    pub fn saturation_latency(&self) -> u64 {
+        self.grace_slots + self.max_credits_per_slot
+    }
+}
+
+/// efficiency boundaries of the performance status and the low latency cutoff
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThresholdsConfig {
+    /// efficiency at or above this is optimal
+    pub optimal_efficiency_pct: f64,
+    /// efficiency at or above this is good, below it poor
+    pub good_efficiency_pct: f64,
+    /// votes at or below this latency count as low latency
+    pub low_latency_slots: u64,
+}
+
+impl Default for ThresholdsConfig {
+    fn default() -> Self {
+        Self {
+            optimal_efficiency_pct: 95.0,
+            good_efficiency_pct: 85.0,
+            low_latency_slots: VOTE_CREDITS_GRACE_SLOTS as u64,
+        }
+    }
+}
+
+/// thresholds of the causes a vote below max credits is blamed on, see `classify_loss`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LossCausesConfig {
+    /// landed slot minus voted slot above this is a high landing delay, 0 disables
+    pub landing_delay_slots: u64,
+    /// confirming block minus landed slot above this is a finalization stall, 0 disables
+    pub finalization_stall_slots: u64,
+    /// voted slots this close to one of our own leader slots blame the leader
+    /// window, needs rpc_url and the validator identity. 0 disables
+    pub leader_adjacency_slots: u64,
+}
+
+impl Default for LossCausesConfig {
+    fn default() -> Self {
+        Self {
+            landing_delay_slots: VOTE_CREDITS_GRACE_SLOTS as u64,
+            finalization_stall_slots: 2,
+            leader_adjacency_slots: 4,
+        }
+    }
+}
+
+/// on-chain epoch credits cross-check, only active when rpc_url is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CreditsDriftConfig {
+    pub poll_interval_secs: u64,
+    /// percent difference between local and on-chain credits that counts as a mismatch
+    pub threshold_pct: f64,
+}
+
+impl Default for CreditsDriftConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 120,
+            threshold_pct: 5.0,
+        }
+    }
+}
+
+/// stake-weighted comparison with the cluster's epoch-to-date efficiency (rpc_url)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterConfig {
+    /// seconds between getVoteAccounts snapshots
+    pub poll_interval_secs: u64,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self { poll_interval_secs: 300 }
+    }
+}
+
+/// identity, commission, stake and delinquency of the vote account (rpc_url)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountInfoConfig {
+    /// seconds between getVoteAccounts refreshes
+    pub poll_interval_secs: u64,
+}
+
+impl Default for AccountInfoConfig {
+    fn default() -> Self {
+        Self { poll_interval_secs: 300 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DashboardConfig {
+    /// confirmed votes retained for the recent votes table and its summary
+    pub recent_votes_window: usize,
+    /// milliseconds between frames, frames without stat changes are skipped
+    pub refresh_ms: u64,
+    /// glyphs and colors, "auto" picks one from NO_COLOR and TERM
+    pub theme: DashboardTheme,
+    /// rss, cpu, tokio tasks and allocator usage of the monitor in the footer
+    pub show_process: bool,
+    /// "full", "short" (first 8 characters) or "url" (an explorer link cut
+    /// to the column, and in simple mode to the terminal width)
+    pub signature_display: SignatureDisplay,
+    /// bar lengths of the session tvc distribution panel, "linear" or "log"
+    pub histogram_scale: HistogramScale,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            recent_votes_window: DEFAULT_RECENT_VOTES_WINDOW,
+            refresh_ms: 500,
+            theme: DashboardTheme::Auto,
+            show_process: true,
+            signature_display: SignatureDisplay::Full,
+            histogram_scale: HistogramScale::Linear,
+        }
+    }
+}
+
+/// vote coverage of finalized slots, catches a validator that stopped voting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoverageConfig {
+    /// settled finalized slots the coverage percentage is computed over
+    pub window_slots: usize,
+    /// slots behind the newest finalized slot before a slot's vote is expected
+    pub settle_slots: u64,
+    /// consecutive finalized slots without a vote that raise a warning
+    pub gap_alert_slots: u64,
+}
+
+impl Default for CoverageConfig {
+    fn default() -> Self {
+        Self {
+            window_slots: 1000,
+            settle_slots: 32,
+            gap_alert_slots: 64,
+        }
+    }
+}
+
+/// watchdog for a grpc stream that stops delivering without closing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StalenessConfig {
+    /// seconds without a transaction or block update before the dashboard
+    /// footer turns red and a warning is logged
+    pub warn_secs: u64,
+    /// seconds without one before the subscription is re-established, 0 disables
+    pub reconnect_secs: u64,
+}
+
+impl Default for StalenessConfig {
+    fn default() -> Self {
+        Self {
+            warn_secs: 30,
+            reconnect_secs: 120,
+        }
+    }
+}
+
+/// a private cluster, `network = "custom"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomNetworkConfig {
+    /// transaction link with `{signature}` in place of the signature,
+    /// solscan's when unset
+    pub explorer_tx_url: Option<String>,
+    pub slots_per_epoch: u64,
+    pub slot_duration_ms: u64,
+}
+
+impl Default for CustomNetworkConfig {
+    fn default() -> Self {
+        Self {
+            explorer_tx_url: None,
+            slots_per_epoch: DEFAULT_SLOTS_PER_EPOCH,
+            slot_duration_ms: DEFAULT_SLOT_DURATION_MS,
+        }
+    }
+}
+
+/// what happens when no vote is confirmed while the stream is connected,
+/// see `spawn_liveness_watchdog`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LivenessConfig {
+    /// seconds without a confirmed vote before the subscription is
+    /// re-established, again after each further period, 0 disables
+    pub resubscribe_secs: u64,
+    /// seconds without one before the monitor exits with code 3 so a
+    /// supervisor restarts it, 0 never exits
+    pub exit_secs: u64,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            resubscribe_secs: 300,
+            exit_secs: 0,
+        }
+    }
+}
+
+/// see `spawn_filter_drift_watchdog`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterDriftConfig {
+    /// how many times below or above its baseline a per-minute rate may get,
+    /// 5 flags a drop of more than 80%, 0 disables
+    pub factor: f64,
+    /// minutes the baseline averages over
+    pub baseline_minutes: u64,
+    /// minutes sampled before the baseline is trusted
+    pub warmup_minutes: u64,
+}
+
+impl Default for FilterDriftConfig {
+    fn default() -> Self {
+        Self {
+            factor: 5.0,
+            baseline_minutes: 30,
+            warmup_minutes: 10,
+        }
+    }
+}
+
+/// readiness reported by the status api's /healthz
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthzConfig {
+    /// seconds without any grpc update before /healthz reports unhealthy
+    pub max_stale_secs: u64,
+}
+
+impl Default for HealthzConfig {
+    fn default() -> Self {
+        Self { max_stale_secs: 30 }
+    }
+}
+
+/// config.toml hot reload, see `spawn_config_watcher`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReloadConfig {
+    pub enabled: bool,
+    /// seconds between modification time checks of the config file
+    pub poll_secs: u64,
+}
+
+impl Default for ReloadConfig {
+    fn default() -> Self {
+        Self { enabled: true, poll_secs: 5 }
+    }
+}
+
+/// telegram bot used for notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    /// numeric chat id or @channel name, quoted
+    pub chat_id: String,
+}
+
+/// influxdb line protocol metrics, only active with a url or stdout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InfluxConfig {
+    /// http(s) base url of the influxdb v2 api, or udp://host:port for a
+    /// line protocol socket
+    pub url: Option<String>,
+    /// api token, sent as the authorization header, never logged
+    pub token: Option<String>,
+    pub org: Option<String>,
+    pub bucket: Option<String>,
+    /// write the points to stdout instead of the url, see --metrics-stdout
+    pub stdout: bool,
+    /// points per write
+    pub batch_size: usize,
+    /// seconds before a partial batch is written
+    pub flush_interval_secs: u64,
+    /// points waiting to be written, the oldest are dropped beyond this
+    pub queue_capacity: usize,
+    /// seconds between session points
+    pub session_interval_secs: u64,
+}
+
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            token: None,
+            org: None,
+            bucket: None,
+            stdout: false,
+            batch_size: 500,
+            flush_interval_secs: 10,
+            queue_capacity: 10_000,
+            session_interval_secs: 60,
+        }
+    }
+}
+
+impl InfluxConfig {
+    pub fn enabled(&self) -> bool {
+        self.url.is_some() || self.stdout
+    }
+}
+
+/// prometheus metrics, scraped from /metrics on http_listen, pushed to a
+/// pushgateway, or both
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrometheusConfig {
+    /// serve /metrics on the status api, needs http_listen
+    pub scrape: bool,
+    /// http(s) base url of a pushgateway, for hosts that cannot be scraped
+    pub pushgateway_url: Option<String>,
+    /// seconds between pushes
+    pub push_interval_secs: u64,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self { scrape: false, pushgateway_url: None, push_interval_secs: 15 }
+    }
 }
 
-impl Default for Config {
+impl PrometheusConfig {
+    pub fn enabled(&self) -> bool {
+        self.scrape || self.pushgateway_url.is_some()
+    }
+}
+
+/// ndjson records on stdout, only active with --json-stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonStreamConfig {
+    /// set by --json-stream, stdout belongs to the dashboard otherwise
+    #[serde(skip)]
+    pub enabled: bool,
+    /// seconds between stats records
+    pub snapshot_interval_secs: u64,
+}
+
+impl Default for JsonStreamConfig {
+    fn default() -> Self {
+        Self { enabled: false, snapshot_interval_secs: 10 }
+    }
+}
+
+/// discord and telegram alerts, only active when a destination is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub discord_webhook_url: Option<String>,
+    pub telegram: Option<TelegramConfig>,
+    /// consecutive poor votes (below 3/4 of max credits) that trigger an alert
+    pub poor_vote_streak: u64,
+    /// consecutive missed vote transactions that trigger an alert
+    pub missed_vote_streak: u64,
+    /// alert when efficiency over the last `efficiency_window_votes` drops below this
+    pub efficiency_threshold_pct: f64,
+    pub efficiency_window_votes: usize,
+    /// minimum seconds between two alerts of the same kind
+    pub rate_limit_secs: u64,
+}
+
+impl Default for NotificationsConfig {
     fn default() -> Self {
         Self {
-            grpc_url: String::new(),
-            vote_account: String::new(),
-            performance_logging: PerformanceFilterConfig::default(),
+            discord_webhook_url: None,
+            telegram: None,
+            poor_vote_streak: 10,
+            missed_vote_streak: 3,
+            efficiency_threshold_pct: 90.0,
+            efficiency_window_votes: 200,
+            rate_limit_secs: 600,
         }
     }
 }
 
+impl NotificationsConfig {
+    pub fn enabled(&self) -> bool {
+        self.discord_webhook_url.is_some() || self.telegram.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EpochConfig {
+    /// the network's epoch length when unset, see `Config::network_profile`
+    pub slots_per_epoch: Option<u64>,
+    /// completed epoch summaries are appended here as json lines
+    pub summary_file: PathBuf,
+    /// write epoch_<N>_summary.json to report_dir for every completed epoch
+    pub reports: bool,
+    pub report_dir: PathBuf,
+}
+
+impl Default for EpochConfig {
+    fn default() -> Self {
+        Self {
+            slots_per_epoch: None,
+            summary_file: default_epoch_summary_path(),
+            reports: true,
+            report_dir: default_epoch_report_dir(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateConfig {
+    /// session state is saved here and reloaded on startup, disabled when unset
+    pub file: Option<PathBuf>,
+    /// state files older than this start a new session
+    pub max_age_secs: u64,
+    pub save_interval_secs: u64,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_age_secs: 24 * 60 * 60,
+            save_interval_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// one json object per line for log aggregation
+    Json,
+}
+
+/// address family of the grpc connection, to the proxy when one is set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GrpcIpVersion {
+    #[default]
+    Auto,
+    V4,
+    V6,
+}
+
+impl From<GrpcIpVersion> for grpc_client::IpVersion {
+    fn from(version: GrpcIpVersion) -> Self {
+        match version {
+            GrpcIpVersion::Auto => Self::Auto,
+            GrpcIpVersion::V4 => Self::V4,
+            GrpcIpVersion::V6 => Self::V6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteLogFormat {
+    Csv,
+    Jsonl,
+}
+
+impl VoteLogFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VoteLogFormat::Csv => "csv",
+            VoteLogFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteLogRotation {
+    Daily,
+}
+
+/// export of every confirmed vote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoteLogConfig {
+    pub enabled: bool,
+    pub format: VoteLogFormat,
+    /// directory the daily vote files are written to
+    pub path: PathBuf,
+    pub rotate: VoteLogRotation,
+    pub batch_size: usize,
+    pub flush_interval_secs: u64,
+}
+
+impl Default for VoteLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: VoteLogFormat::Csv,
+            path: PathBuf::from("./vote_logs"),
+            rotate: VoteLogRotation::Daily,
+            batch_size: 100,
+            flush_interval_secs: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// json lines in performance_log_dir and epoch.summary_file
+    #[default]
+    Files,
+    /// one sqlite database with every confirmed vote
+    Sqlite,
+}
+
+/// where vote history, poor performance events and epoch summaries are kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    #[serde(rename = "type")]
+    pub backend: StorageBackend,
+    /// database file of the sqlite backend
+    pub path: PathBuf,
+    pub batch_size: usize,
+    pub flush_interval_secs: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::Files,
+            path: PathBuf::from("./voteperfx.db"),
+            batch_size: 100,
+            flush_interval_secs: 5,
+        }
+    }
+}
+
+/// what confirms votes, see `ConfirmationSource`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfirmationMode {
+    /// vote transactions matched in finalized blocks, exact latency
+    #[default]
+    Blocks,
+    /// no block subscription, the transaction slot reaching finalized status
+    /// confirms its votes. latency is estimated from the landing slot, for
+    /// plans that bill block subscriptions heavily
+    TxStatus,
+}
+
+impl ConfirmationMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Blocks => "blocks",
+            Self::TxStatus => "tx-status",
+        }
+    }
+
+    pub fn subscribes_blocks(self) -> bool {
+        self == Self::Blocks
+    }
+
+    /// source votes are confirmed from at startup, the block mode may still
+    /// fall back to slot statuses (tracker.block_timeout_secs)
+    pub fn initial_source(self) -> ConfirmationSource {
+        match self {
+            Self::Blocks => ConfirmationSource::Block,
+            Self::TxStatus => ConfirmationSource::SlotStatus,
+        }
+    }
+
+    /// update kinds the stream watchdog expects to keep arriving
+    pub fn watched_updates(self) -> &'static [UpdateKind] {
+        match self {
+            Self::Blocks => &[UpdateKind::Transaction, UpdateKind::Block],
+            Self::TxStatus => &[UpdateKind::Transaction],
+        }
+    }
+}
+
+/// where votes are read from, see `VoteSource`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VoteSourceKind {
+    /// the geyser grpc stream, exact latencies
+    #[default]
+    Grpc,
+    /// polls the vote account over json-rpc (rpc_url), for machines without
+    /// geyser access. approximate, see `RpcVoteSource`
+    Rpc,
+}
+
+impl VoteSourceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Grpc => "grpc",
+            Self::Rpc => "rpc",
+        }
+    }
+}
+
+/// polling of the vote account with `source = "rpc"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcSourceConfig {
+    /// seconds between two reads of the vote account
+    pub poll_secs: u64,
+}
+
+impl Default for RpcSourceConfig {
+    fn default() -> Self {
+        Self { poll_secs: 2 }
+    }
+}
+
+/// grpc endpoint over the file, comma-separated for the grpc_urls failover list
+pub const ENV_GRPC_URL: &str = "VOTEPERFX_GRPC_URL";
+/// vote account over the file
+pub const ENV_VOTE_ACCOUNT: &str = "VOTEPERFX_VOTE_ACCOUNT";
+/// grpc_x_token over the file, keeps the credential out of config.toml
+pub const ENV_X_TOKEN: &str = "VOTEPERFX_X_TOKEN";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// "grpc" or "rpc", which polls the vote account over rpc_url instead
+    #[serde(default)]
+    pub source: VoteSourceKind,
+    #[serde(default)]
+    pub rpc_source: RpcSourceConfig,
+    #[serde(default)]
+    pub grpc_url: String,
+    /// endpoints tried in order instead of grpc_url, see `EndpointPool`
+    #[serde(default)]
+    pub grpc_urls: Vec<String>,
+    /// sent as the x-token header, never logged
+    #[serde(default)]
+    pub grpc_x_token: Option<String>,
+    /// pem ca certificate trusted instead of the native roots
+    #[serde(default)]
+    pub grpc_tls_ca_cert_path: Option<PathBuf>,
+    /// seconds to wait for the grpc connection, 10 when unset
+    #[serde(default)]
+    pub grpc_connect_timeout_secs: Option<u64>,
+    /// largest grpc update accepted in bytes, tonic's 4 MiB when unset
+    #[serde(default)]
+    pub grpc_max_message_size: Option<usize>,
+    /// http proxy the grpc connection is tunneled through with CONNECT,
+    /// `http://[user:password@]host:port`
+    #[serde(default)]
+    pub grpc_proxy_url: Option<String>,
+    /// "auto", "v4" or "v6", resolves the endpoint (or the proxy) to that family only
+    #[serde(default)]
+    pub grpc_ip_version: GrpcIpVersion,
+    pub vote_account: String,
+    /// validator identity of the vote account, marks votes for its own leader
+    /// slots (rpc_url); read from the vote account over rpc when unset
+    #[serde(default)]
+    pub identity: Option<String>,
+    pub performance_logging: PerformanceFilterConfig,
+    /// warn when the approximate memory usage exceeds this many MiB
+    #[serde(default)]
+    pub memory_soft_budget_mb: Option<u64>,
+    #[serde(default)]
+    pub tracker: TrackerConfig,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+    #[serde(default)]
+    pub stats: StatsConfig,
+    /// subscribe to the vote account and follow authorized voter changes
+    #[serde(default)]
+    pub follow_authorized_voter: bool,
+    /// commitment the vote blocks are read at, "both" opens a second
+    /// subscription for the confirmed latency
+    #[serde(default)]
+    pub commitment: Commitment,
+    /// "blocks" or "tx-status", which skips the block subscription and
+    /// estimates latency from the transaction slot
+    #[serde(default)]
+    pub confirmation_source: ConfirmationMode,
+    /// "mainnet", "testnet", "devnet" or "custom": explorer links, epoch
+    /// length and slot time
+    #[serde(default)]
+    pub network: Network,
+    #[serde(default)]
+    pub custom_network: CustomNetworkConfig,
+    #[serde(default)]
+    pub epoch: EpochConfig,
+    #[serde(default)]
+    pub vote_log: VoteLogConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub state: StateConfig,
+    #[serde(default)]
+    pub tvc: TvcConfig,
+    #[serde(default)]
+    pub thresholds: ThresholdsConfig,
+    #[serde(default)]
+    pub loss_causes: LossCausesConfig,
+    #[serde(default)]
+    pub dashboard: DashboardConfig,
+    #[serde(default)]
+    pub coverage: CoverageConfig,
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// json-rpc endpoint for the credits cross-check, leader attribution and cluster comparison, disabled when unset
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    #[serde(default)]
+    pub credits_drift: CreditsDriftConfig,
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    #[serde(default)]
+    pub account_info: AccountInfoConfig,
+    /// serve the json status api (/status, /healthz) here, disabled when unset
+    #[serde(default)]
+    pub http_listen: Option<SocketAddr>,
+    #[serde(default)]
+    pub healthz: HealthzConfig,
+    #[serde(default)]
+    pub staleness: StalenessConfig,
+    #[serde(default)]
+    pub liveness: LivenessConfig,
+    #[serde(default)]
+    pub filter_drift: FilterDriftConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub influx: InfluxConfig,
+    #[serde(default)]
+    pub prometheus: PrometheusConfig,
+    #[serde(default)]
+    pub json_stream: JsonStreamConfig,
+    /// seconds the shutdown gets to drain the pipeline and flush the writers,
+    /// 5 when unset
+    #[serde(default)]
+    pub shutdown_timeout_secs: Option<u64>,
+    /// stats snapshots written on SIGUSR1 go here, ./snapshots when unset
+    #[serde(default)]
+    pub snapshot_dir: Option<PathBuf>,
+    /// log filter in RUST_LOG syntax, --log-level takes precedence
+    #[serde(default)]
+    pub log_level: Option<String>,
+    #[serde(default)]
+    pub reload: ReloadConfig,
+}
+
 impl Config {
+    /// parse the file, apply the environment overrides and validate
     pub async fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = tokio::fs::read_to_string(path).await?;
-        let config: Config = toml::from_str(&content)?;
+        let mut config: Config = toml::from_str(&content)?;
+        config.apply_env_overrides();
         config.validate()?;
         Ok(config)
     }
 
+    /// take the grpc endpoints, vote account and x-token from VOTEPERFX_*
+    /// variables that are set, over the file and under the command line flags
+    ///
+    /// a comma-separated VOTEPERFX_GRPC_URL replaces grpc_urls, a single url
+    /// grpc_url. surrounding whitespace of the urls is trimmed either way.
+    pub fn apply_env_overrides(&mut self) {
+        if let Some(urls) = env_override(ENV_GRPC_URL) {
+            let mut urls: Vec<String> = urls.split(',').map(str::trim).filter(|url| !url.is_empty()).map(str::to_string).collect();
+            if urls.len() > 1 {
+                self.grpc_url.clear();
+                self.grpc_urls = urls;
+            } else {
+                self.grpc_url = urls.pop().unwrap_or_default();
+                self.grpc_urls.clear();
+            }
+        }
+        if let Some(vote_account) = env_override(ENV_VOTE_ACCOUNT) {
+            self.vote_account = vote_account;
+        }
+        if let Some(token) = env_override(ENV_X_TOKEN) {
+            self.grpc_x_token = Some(token);
+        }
+
+        self.grpc_url = self.grpc_url.trim().to_string();
+        for url in &mut self.grpc_urls {
+            *url = url.trim().to_string();
+        }
+    }
+
     pub async fn load_or_default<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
         match Self::load_from_file(path).await {
             Ok(config) => {
-                log::info!("configuration loaded from config.toml");
+                log::info!("configuration loaded from {}", path.display());
                 config
             }
             Err(e) => {
-                log::warn!("failed to load config.toml ({}), using defaults", e);
+                log::warn!("failed to load {} ({}), using defaults", path.display(), e);
                 Self::default()
             }
         }
@@ -71,10 +1097,99 @@ impl Config {
         Ok(())
     }
 
-    fn validate(&self) -> Result<()> {
-        // validate grpc_url
-        if self.grpc_url.is_empty() {
-            return Err(VoteMonitorError::Config("grpc_url cannot be empty".to_string()));
+    /// grpc endpoints in failover order, grpc_urls or else grpc_url alone
+    pub fn grpc_endpoints(&self) -> Vec<String> {
+        if self.grpc_urls.is_empty() {
+            vec![self.grpc_url.clone()]
+        } else {
+            self.grpc_urls.clone()
+        }
+    }
+
+    /// explorer, epoch length and slot time of `network`, with
+    /// `[custom_network]` for a custom one and `epoch.slots_per_epoch` on top
+    pub fn network_profile(&self) -> NetworkProfile {
+        let mut profile = NetworkProfile::preset(self.network);
+        if self.network == Network::Custom {
+            let custom = &self.custom_network;
+            if let Some(ref url) = custom.explorer_tx_url {
+                profile.explorer = Explorer::new(url.clone());
+            }
+            profile.slots_per_epoch = custom.slots_per_epoch;
+            profile.slot_duration = Duration::from_millis(custom.slot_duration_ms);
+        }
+        if let Some(slots_per_epoch) = self.epoch.slots_per_epoch {
+            profile.slots_per_epoch = slots_per_epoch;
+        }
+        profile
+    }
+
+    /// the settings `source` needs besides vote_account are there
+    pub fn has_source(&self) -> bool {
+        match self.source {
+            VoteSourceKind::Grpc => !self.grpc_url.is_empty() || !self.grpc_urls.is_empty(),
+            VoteSourceKind::Rpc => self.rpc_url.is_some(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        // validate grpc_url, or the grpc_urls failover list
+        if !self.grpc_urls.is_empty() && !self.grpc_url.is_empty() {
+            return Err(VoteMonitorError::Config("set either grpc_url or grpc_urls, not both".to_string()));
+        }
+        match self.source {
+            VoteSourceKind::Grpc => {
+                let endpoints = self.grpc_endpoints();
+                if endpoints.iter().any(String::is_empty) {
+                    return Err(VoteMonitorError::Config("grpc_url cannot be empty".to_string()));
+                }
+                if let Some(duplicate) = endpoints.iter().enumerate().find_map(|(i, url)| endpoints[..i].contains(url).then_some(url)) {
+                    return Err(VoteMonitorError::Config(format!("grpc_urls lists {} more than once", duplicate)));
+                }
+                for endpoint in &endpoints {
+                    parse_grpc_url(endpoint)?;
+                }
+            }
+            VoteSourceKind::Rpc => {
+                if self.rpc_url.is_none() {
+                    return Err(VoteMonitorError::Config("source = \"rpc\" needs rpc_url".to_string()));
+                }
+                if self.rpc_source.poll_secs == 0 {
+                    return Err(VoteMonitorError::Config("rpc_source.poll_secs cannot be 0".to_string()));
+                }
+            }
+        }
+        
+        if let Some(ref token) = self.grpc_x_token {
+            // the value stays out of the message, it is a credential
+            if token.is_empty() || !token.bytes().all(|b| b.is_ascii_graphic() || b == b' ') {
+                return Err(VoteMonitorError::Config(
+                    "grpc_x_token must be non-empty printable ascii".to_string()
+                ));
+            }
+        }
+        
+        if self.grpc_tls_ca_cert_path.as_ref().is_some_and(|path| path.as_os_str().is_empty()) {
+            return Err(VoteMonitorError::Config("grpc_tls_ca_cert_path cannot be empty".to_string()));
+        }
+        
+        if self.grpc_connect_timeout_secs == Some(0) || self.grpc_max_message_size == Some(0) {
+            return Err(VoteMonitorError::Config(
+                "grpc_connect_timeout_secs and grpc_max_message_size cannot be 0".to_string()
+            ));
+        }
+
+        if let Some(ref url) = self.grpc_proxy_url {
+            grpc_client::ProxyUrl::parse(url)
+                .map_err(|e| VoteMonitorError::Config(format!("grpc_proxy_url: {}", e)))?;
+        }
+        
+        // confirmed blocks are what the other commitments read
+        if self.confirmation_source == ConfirmationMode::TxStatus && self.commitment != Commitment::Finalized {
+            return Err(VoteMonitorError::Config(format!(
+                "commitment = \"{}\" reads blocks, confirmation_source = \"tx-status\" needs commitment = \"finalized\"",
+                self.commitment.as_str()
+            )));
         }
         
         // validate vote_account
@@ -82,10 +1197,19 @@ impl Config {
             return Err(VoteMonitorError::Config("vote_account cannot be empty".to_string()));
         }
         
-        if self.vote_account.len() < 32 || self.vote_account.len() > 44 {
-            return Err(VoteMonitorError::Config(
-                "vote_account appears to be invalid (should be 32-44 characters)".to_string()
-            ));
+        // a typo would subscribe to an account that never votes
+        if let Err(e) = Pubkey::from_str(&self.vote_account) {
+            return Err(VoteMonitorError::Config(format!(
+                "vote_account ({}) is not a valid base58 pubkey: {}", self.vote_account, e
+            )));
+        }
+        
+        if let Some(ref identity) = self.identity {
+            if let Err(e) = Pubkey::from_str(identity) {
+                return Err(VoteMonitorError::Config(format!(
+                    "identity ({}) is not a valid base58 pubkey: {}", identity, e
+                )));
+            }
         }
         
         // validate performance logging settings
@@ -107,11 +1231,43 @@ impl Config {
             }
         }
         
+        if perf.retention_days == Some(0) {
+            return Err(VoteMonitorError::Config(
+                "performance_logging.retention_days must be at least 1".to_string()
+            ));
+        }
+        
+        if self.tvc.grace_slots >= self.tvc.max_credits_per_slot {
+            return Err(VoteMonitorError::Config(format!(
+                "tvc.grace_slots ({}) must be less than tvc.max_credits_per_slot ({})",
+                self.tvc.grace_slots, self.tvc.max_credits_per_slot
+            )));
+        }
+        
+        let thresholds = &self.thresholds;
+        if !(thresholds.good_efficiency_pct > 0.0 && thresholds.optimal_efficiency_pct <= 100.0) {
+            return Err(VoteMonitorError::Config(
+                "thresholds.good_efficiency_pct and thresholds.optimal_efficiency_pct must be within 0-100".to_string()
+            ));
+        }
+        if thresholds.good_efficiency_pct >= thresholds.optimal_efficiency_pct {
+            return Err(VoteMonitorError::Config(format!(
+                "thresholds.good_efficiency_pct ({}) must be less than thresholds.optimal_efficiency_pct ({})",
+                thresholds.good_efficiency_pct, thresholds.optimal_efficiency_pct
+            )));
+        }
+        if thresholds.low_latency_slots == 0 {
+            return Err(VoteMonitorError::Config(
+                "thresholds.low_latency_slots must be at least 1".to_string()
+            ));
+        }
+        
         if let Some(tvc) = perf.max_tvc_threshold {
-            if tvc > 16 {
-                return Err(VoteMonitorError::Config(
-                    format!("max_tvc_threshold ({}) cannot exceed 16", tvc)
-                ));
+            if tvc > self.tvc.max_credits_per_slot {
+                return Err(VoteMonitorError::Config(format!(
+                    "max_tvc_threshold ({}) cannot exceed {}",
+                    tvc, self.tvc.max_credits_per_slot
+                )));
             }
         }
         
@@ -123,6 +1279,301 @@ impl Config {
             }
         }
         
+        if self.tracker.missed_vote_cutoff_slots == 0 {
+            return Err(VoteMonitorError::Config(
+                "tracker.missed_vote_cutoff_slots cannot be 0".to_string()
+            ));
+        }
+        
+        if self.tracker.confirmed_capacity == 0 || self.tracker.block_horizon_slots == 0 {
+            return Err(VoteMonitorError::Config(
+                "tracker.confirmed_capacity and tracker.block_horizon_slots cannot be 0".to_string()
+            ));
+        }
+        
+        if self.tracker.duplicate_window_slots == 0 {
+            return Err(VoteMonitorError::Config(
+                "tracker.duplicate_window_slots cannot be 0".to_string()
+            ));
+        }
+        
+        if self.tracker.pending_cleanup_interval_slots == 0 || self.tracker.pending_max_age_slots == 0 {
+            return Err(VoteMonitorError::Config(
+                "tracker.pending_cleanup_interval_slots and tracker.pending_max_age_slots cannot be 0".to_string()
+            ));
+        }
+        
+        if self.pipeline.channel_capacity == 0 {
+            return Err(VoteMonitorError::Config(
+                "pipeline.channel_capacity cannot be 0".to_string()
+            ));
+        }
+        
+        if self.pipeline.drop_threshold_pct == 0 || self.pipeline.drop_threshold_pct > 100 {
+            return Err(VoteMonitorError::Config(
+                "pipeline.drop_threshold_pct must be between 1 and 100".to_string()
+            ));
+        }
+        
+        if self.stats.poor_votes_window == 0 {
+            return Err(VoteMonitorError::Config(
+                "stats.poor_votes_window cannot be 0".to_string()
+            ));
+        }
+        if self.stats.recent_window_secs == 0 {
+            return Err(VoteMonitorError::Config(
+                "stats.recent_window_secs cannot be 0".to_string()
+            ));
+        }
+        
+        if self.stats.trend_bucket_secs < 10 || self.stats.trend_buckets == 0 {
+            return Err(VoteMonitorError::Config(
+                "stats.trend_bucket_secs must be at least 10 and stats.trend_buckets cannot be 0".to_string()
+            ));
+        }
+        
+        if self.state.file.is_some() && self.state.save_interval_secs == 0 {
+            return Err(VoteMonitorError::Config(
+                "state.save_interval_secs cannot be 0".to_string()
+            ));
+        }
+        
+        if self.vote_log.enabled && self.vote_log.batch_size == 0 {
+            return Err(VoteMonitorError::Config(
+                "vote_log.batch_size cannot be 0".to_string()
+            ));
+        }
+
+        if self.storage.backend == StorageBackend::Sqlite {
+            if self.storage.path.as_os_str().is_empty() {
+                return Err(VoteMonitorError::Config(
+                    "storage.path cannot be empty".to_string()
+                ));
+            }
+            if self.storage.batch_size == 0 || self.storage.flush_interval_secs == 0 {
+                return Err(VoteMonitorError::Config(
+                    "storage.batch_size and storage.flush_interval_secs cannot be 0".to_string()
+                ));
+            }
+        }
+
+        if let Some(ref rpc_url) = self.rpc_url {
+            if !rpc_url.starts_with("http://") && !rpc_url.starts_with("https://") {
+                return Err(VoteMonitorError::Config(
+                    format!("rpc_url ({}) must be an http:// or https:// url", rpc_url)
+                ));
+            }
+            
+            if self.credits_drift.poll_interval_secs == 0 {
+                return Err(VoteMonitorError::Config(
+                    "credits_drift.poll_interval_secs cannot be 0".to_string()
+                ));
+            }
+            
+            if self.credits_drift.threshold_pct <= 0.0 {
+                return Err(VoteMonitorError::Config(
+                    "credits_drift.threshold_pct must be positive".to_string()
+                ));
+            }
+            
+            if self.account_info.poll_interval_secs == 0 {
+                return Err(VoteMonitorError::Config(
+                    "account_info.poll_interval_secs cannot be 0".to_string()
+                ));
+            }
+            
+            // a full getVoteAccounts response is a few MB
+            if self.cluster.poll_interval_secs < 60 {
+                return Err(VoteMonitorError::Config(
+                    "cluster.poll_interval_secs must be at least 60".to_string()
+                ));
+            }
+        }
+        
+        if self.coverage.window_slots == 0 || self.coverage.gap_alert_slots == 0 {
+            return Err(VoteMonitorError::Config(
+                "coverage.window_slots and coverage.gap_alert_slots cannot be 0".to_string()
+            ));
+        }
+        
+        if self.dashboard.recent_votes_window == 0 {
+            return Err(VoteMonitorError::Config(
+                "dashboard.recent_votes_window cannot be 0".to_string()
+            ));
+        }
+        
+        if !(100..=5000).contains(&self.dashboard.refresh_ms) {
+            return Err(VoteMonitorError::Config(format!(
+                "dashboard.refresh_ms ({}) must be between 100 and 5000",
+                self.dashboard.refresh_ms
+            )));
+        }
+        
+        let notifications = &self.notifications;
+        if let Some(ref url) = notifications.discord_webhook_url {
+            if !url.starts_with("https://") {
+                return Err(VoteMonitorError::Config(
+                    format!("notifications.discord_webhook_url ({}) must be an https:// url", url)
+                ));
+            }
+        }
+        if let Some(ref telegram) = notifications.telegram {
+            if telegram.bot_token.is_empty() || telegram.chat_id.is_empty() {
+                return Err(VoteMonitorError::Config(
+                    "notifications.telegram needs both bot_token and chat_id".to_string()
+                ));
+            }
+        }
+        if notifications.enabled() {
+            if notifications.poor_vote_streak == 0 || notifications.missed_vote_streak == 0 {
+                return Err(VoteMonitorError::Config(
+                    "notifications.poor_vote_streak and missed_vote_streak cannot be 0".to_string()
+                ));
+            }
+            if notifications.efficiency_window_votes == 0 {
+                return Err(VoteMonitorError::Config(
+                    "notifications.efficiency_window_votes cannot be 0".to_string()
+                ));
+            }
+            if !(0.0..=100.0).contains(&notifications.efficiency_threshold_pct) {
+                return Err(VoteMonitorError::Config(format!(
+                    "notifications.efficiency_threshold_pct ({}) must be between 0 and 100",
+                    notifications.efficiency_threshold_pct
+                )));
+            }
+        }
+        
+        let influx = &self.influx;
+        if let Some(ref url) = influx.url {
+            if url.starts_with("http://") || url.starts_with("https://") {
+                if influx.org.as_deref().unwrap_or_default().is_empty()
+                    || influx.bucket.as_deref().unwrap_or_default().is_empty()
+                {
+                    return Err(VoteMonitorError::Config(
+                        "influx.url needs influx.org and influx.bucket".to_string()
+                    ));
+                }
+            } else if !url.starts_with("udp://") {
+                return Err(VoteMonitorError::Config(
+                    format!("influx.url ({}) must be an http://, https:// or udp:// url", url)
+                ));
+            }
+        }
+        if influx.enabled() {
+            if influx.batch_size == 0 || influx.flush_interval_secs == 0 || influx.session_interval_secs == 0 {
+                return Err(VoteMonitorError::Config(
+                    "influx.batch_size, flush_interval_secs and session_interval_secs cannot be 0".to_string()
+                ));
+            }
+            if influx.queue_capacity < influx.batch_size {
+                return Err(VoteMonitorError::Config(format!(
+                    "influx.queue_capacity ({}) must be at least influx.batch_size ({})",
+                    influx.queue_capacity, influx.batch_size
+                )));
+            }
+        }
+        
+        let prometheus = &self.prometheus;
+        if prometheus.scrape && self.http_listen.is_none() {
+            return Err(VoteMonitorError::Config(
+                "prometheus.scrape serves /metrics on the status api, set http_listen".to_string()
+            ));
+        }
+        if let Some(ref url) = prometheus.pushgateway_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(VoteMonitorError::Config(
+                    format!("prometheus.pushgateway_url ({}) must be an http:// or https:// url", url)
+                ));
+            }
+            if prometheus.push_interval_secs == 0 {
+                return Err(VoteMonitorError::Config("prometheus.push_interval_secs cannot be 0".to_string()));
+            }
+        }
+        
+        if self.shutdown_timeout_secs == Some(0) {
+            return Err(VoteMonitorError::Config("shutdown_timeout_secs cannot be 0".to_string()));
+        }
+        
+        if self.json_stream.enabled && self.json_stream.snapshot_interval_secs == 0 {
+            return Err(VoteMonitorError::Config(
+                "json_stream.snapshot_interval_secs cannot be 0".to_string()
+            ));
+        }
+        
+        if self.http_listen.is_some() && self.healthz.max_stale_secs == 0 {
+            return Err(VoteMonitorError::Config(
+                "healthz.max_stale_secs cannot be 0".to_string()
+            ));
+        }
+        
+        if self.staleness.warn_secs == 0 {
+            return Err(VoteMonitorError::Config(
+                "staleness.warn_secs cannot be 0".to_string()
+            ));
+        }
+        if self.staleness.reconnect_secs != 0 && self.staleness.reconnect_secs <= self.staleness.warn_secs {
+            return Err(VoteMonitorError::Config(format!(
+                "staleness.reconnect_secs ({}) must be above staleness.warn_secs ({}), or 0 to disable",
+                self.staleness.reconnect_secs, self.staleness.warn_secs
+            )));
+        }
+        let liveness = &self.liveness;
+        if liveness.exit_secs != 0 && liveness.resubscribe_secs != 0 && liveness.exit_secs <= liveness.resubscribe_secs {
+            return Err(VoteMonitorError::Config(format!(
+                "liveness.exit_secs ({}) must be above liveness.resubscribe_secs ({}), or 0 to never exit",
+                liveness.exit_secs, liveness.resubscribe_secs
+            )));
+        }
+        let drift = &self.filter_drift;
+        if drift.factor != 0.0 && !(drift.factor > 1.0 && drift.factor.is_finite()) {
+            return Err(VoteMonitorError::Config(format!(
+                "filter_drift.factor ({}) must be above 1, or 0 to disable", drift.factor
+            )));
+        }
+        if drift.baseline_minutes == 0 || drift.warmup_minutes == 0 {
+            return Err(VoteMonitorError::Config(
+                "filter_drift.baseline_minutes and warmup_minutes cannot be 0".to_string()
+            ));
+        }
+        
+        if self.reload.enabled && self.reload.poll_secs == 0 {
+            return Err(VoteMonitorError::Config(
+                "reload.poll_secs cannot be 0".to_string()
+            ));
+        }
+        
+        if self.log_level.as_deref().is_some_and(|level| level.trim().is_empty()) {
+            return Err(VoteMonitorError::Config(
+                "log_level cannot be empty, remove it to use the default".to_string()
+            ));
+        }
+        
+        if self.epoch.slots_per_epoch == Some(0) {
+            return Err(VoteMonitorError::Config(
+                "epoch.slots_per_epoch cannot be 0".to_string()
+            ));
+        }
+        
+        if self.network == Network::Custom {
+            let custom = &self.custom_network;
+            if custom.slots_per_epoch == 0 || custom.slot_duration_ms == 0 {
+                return Err(VoteMonitorError::Config(
+                    "custom_network.slots_per_epoch and custom_network.slot_duration_ms cannot be 0".to_string()
+                ));
+            }
+            if custom.explorer_tx_url.as_deref().is_some_and(|url| !url.contains(SIGNATURE_PLACEHOLDER)) {
+                return Err(VoteMonitorError::Config(format!(
+                    "custom_network.explorer_tx_url must contain {}", SIGNATURE_PLACEHOLDER
+                )));
+            }
+        }
+        
+        if self.memory_soft_budget_mb == Some(0) {
+            return Err(VoteMonitorError::Config(
+                "memory_soft_budget_mb cannot be 0".to_string()
+            ));
+        }
+        
         for level in &perf.performance_levels {
             match level.to_lowercase().as_str() {
                 "optimal" | "good" | "fair" | "poor" | "critical" => {},
@@ -132,11 +1583,147 @@ impl Config {
             }
         }
         
+        // cross-field checks
+        if perf.enabled && !perf.can_match_any_vote(&self.tvc) {
+            return Err(VoteMonitorError::Config(format!(
+                "performance_logging filters can never match a vote ({}); \
+                 widen the latency/tvc thresholds or performance_levels, or set enabled = false",
+                perf.describe_filters()
+            )));
+        }
+        
         Ok(())
     }
+    
+    /// copy the settings that can change without a restart from `other`:
+    /// performance_logging filters, dashboard.refresh_ms, notification
+    /// thresholds and log_level
+    pub fn apply_live_settings(&mut self, other: &Config) {
+        let perf = &mut self.performance_logging;
+        let new_perf = &other.performance_logging;
+        perf.enabled = new_perf.enabled;
+        perf.min_latency_threshold = new_perf.min_latency_threshold;
+        perf.max_latency_threshold = new_perf.max_latency_threshold;
+        perf.min_tvc_threshold = new_perf.min_tvc_threshold;
+        perf.max_tvc_threshold = new_perf.max_tvc_threshold;
+        perf.performance_levels = new_perf.performance_levels.clone();
+
+        self.dashboard.refresh_ms = other.dashboard.refresh_ms;
+
+        let notifications = &mut self.notifications;
+        let new_notifications = &other.notifications;
+        notifications.poor_vote_streak = new_notifications.poor_vote_streak;
+        notifications.missed_vote_streak = new_notifications.missed_vote_streak;
+        notifications.efficiency_threshold_pct = new_notifications.efficiency_threshold_pct;
+        notifications.efficiency_window_votes = new_notifications.efficiency_window_votes;
+
+        self.log_level = other.log_level.clone();
+    }
+    
+    /// dotted paths of the settings that differ from `other`, e.g. "tracker.block_timeout_secs"
+    pub fn changed_settings(&self, other: &Config) -> Vec<String> {
+        let mut changed = Vec::new();
+        if let (Ok(current), Ok(other)) = (serde_json::to_value(self), serde_json::to_value(other)) {
+            collect_changed_settings("", &current, &other, &mut changed);
+        }
+        changed
+    }
+    
+    /// questionable but valid combinations, printed at startup and by --check-config
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let perf = &self.performance_logging;
+        
+        if self.source == VoteSourceKind::Rpc {
+            warnings.push(
+                "source = \"rpc\" polls the vote account instead of the grpc stream: latencies are approximate, \
+                 votes carry no signature and missed votes go undetected".to_string()
+            );
+            if self.commitment != Commitment::Finalized || self.confirmation_source != ConfirmationMode::Blocks {
+                warnings.push("commitment and confirmation_source only apply to source = \"grpc\"".to_string());
+            }
+        }
+        let endpoints = match self.source {
+            VoteSourceKind::Grpc => self.grpc_endpoints(),
+            VoteSourceKind::Rpc => Vec::new(),
+        };
+        for endpoint in endpoints {
+            let Ok(url) = parse_grpc_url(&endpoint) else { continue };
+            if url.scheme() == "http" && !is_loopback(&url) {
+                warnings.push(format!(
+                    "grpc endpoint {} uses plain http to a remote host, a tls-only endpoint rejects it with a connection error, use https://",
+                    endpoint
+                ));
+            }
+        }
+        
+        let network = self.network_profile();
+        let stale_floor = network.slots_duration(STALE_MIN_SLOTS);
+        if self.staleness.warn_secs < stale_floor.as_secs() {
+            warnings.push(format!(
+                "staleness.warn_secs ({}) is under {} slots on {} ({}s), quiet moments will look stale",
+                self.staleness.warn_secs, STALE_MIN_SLOTS, network.network.as_str(), stale_floor.as_secs()
+            ));
+        }
+        
+        if self.tracker.pending_max_age_slots < self.tracker.missed_vote_cutoff_slots {
+            warnings.push(format!(
+                "tracker.pending_max_age_slots ({}) is under missed_vote_cutoff_slots ({}), pending votes are kept until they are declared missed",
+                self.tracker.pending_max_age_slots, self.tracker.missed_vote_cutoff_slots
+            ));
+        }
+        
+        if !perf.enabled {
+            return warnings;
+        }
+        
+        if perf.min_latency_threshold.is_none()
+            && perf.max_latency_threshold.is_none()
+            && perf.min_tvc_threshold.is_none()
+            && perf.max_tvc_threshold.is_none()
+            && perf.performance_levels.is_empty()
+        {
+            warnings.push(format!(
+                "performance_logging has no filters: every confirmed vote will be written to {}",
+                perf.performance_log_dir.display()
+            ));
+        }
+        
+        let max_credits = self.tvc.max_credits_per_slot;
+        let optimal_listed = perf.performance_levels.iter().any(|level| level.eq_ignore_ascii_case("optimal"));
+        if optimal_listed && perf.max_tvc_threshold.is_some_and(|max| max < max_credits) {
+            warnings.push(format!(
+                "performance_levels includes \"optimal\" but max_tvc_threshold ({}) excludes {} tvc votes",
+                perf.max_tvc_threshold.unwrap_or_default(), max_credits
+            ));
+        }
+        
+        let grace = self.tvc.grace_slots;
+        if optimal_listed && perf.min_latency_threshold.is_some_and(|min| min > grace) {
+            warnings.push(format!(
+                "performance_levels includes \"optimal\" but min_latency_threshold ({}) is above the {} slot grace period",
+                perf.min_latency_threshold.unwrap_or_default(), grace
+            ));
+        }
+        
+        warnings
+    }
 }
 
 impl PerformanceFilterConfig {
+    /// check whether any latency can pass every filter at once
+    pub fn can_match_any_vote(&self, tvc: &TvcConfig) -> bool {
+        // credits bottom out at 1 once latency exceeds grace + max credits
+        let saturation = tvc.saturation_latency();
+        let lowest = self.min_latency_threshold.unwrap_or(0);
+        let highest = self.max_latency_threshold.unwrap_or(u64::MAX).min(lowest.max(saturation));
+        
+        (lowest..=highest).any(|latency| {
+            let tvc_credits = calculate_tvc_credits_from_latency(latency, tvc);
+            self.should_save_vote(latency, tvc_credits, categorize_tvc_performance(tvc_credits, tvc))
+        })
+    }
+    
     // criteria for logging
     pub fn should_save_vote(&self, latency: u64, tvc_credits: u64, performance_level: TvcPerformanceLevel) -> bool {
         if !self.enabled {
@@ -206,4 +1793,267 @@ impl PerformanceFilterConfig {
             filters.join(", ")
         }
     }
-}
\ No newline at end of file
+}
+
+fn collect_changed_settings(prefix: &str, current: &Value, other: &Value, changed: &mut Vec<String>) {
+    match (current, other) {
+        (Value::Object(current), Value::Object(other)) => {
+            let keys: BTreeSet<&String> = current.keys().chain(other.keys()).collect();
+            for key in keys {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                let null = Value::Null;
+                collect_changed_settings(
+                    &path,
+                    current.get(key).unwrap_or(&null),
+                    other.get(key).unwrap_or(&null),
+                    changed,
+                );
+            }
+        }
+        _ if current != other => changed.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// name, change to a valid config, expected message or none
+    type Rule = (&'static str, fn(&mut Config), Option<&'static str>);
+
+    fn base() -> Config {
+        Config {
+            vote_account: "Vote111111111111111111111111111111111111111".to_string(),
+            grpc_url: "https://grpc.example.com:443".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_rules() {
+        // the expected message is the start of the error
+        let cases: &[Rule] = &[
+            ("defaults", |_| {}, None),
+            ("grpc_url and grpc_urls", |c| c.grpc_urls = vec!["https://other:443".to_string()], Some("set either grpc_url or grpc_urls")),
+            ("duplicate grpc_urls", |c| {
+                c.grpc_url.clear();
+                c.grpc_urls = vec!["https://a:443".to_string(), "https://a:443".to_string()];
+            }, Some("grpc_urls lists https://a:443 more than once")),
+            ("distinct grpc_urls", |c| {
+                c.grpc_url.clear();
+                c.grpc_urls = vec!["https://a:443".to_string(), "https://b:443".to_string()];
+            }, None),
+            ("empty x token", |c| c.grpc_x_token = Some(String::new()), Some("grpc_x_token must be non-empty")),
+            ("zero connect timeout", |c| c.grpc_connect_timeout_secs = Some(0), Some("grpc_connect_timeout_secs")),
+            ("proxy without http", |c| c.grpc_proxy_url = Some("socks5://proxy:1080".to_string()), Some("grpc_proxy_url")),
+            ("proxy", |c| c.grpc_proxy_url = Some("http://proxy:3128".to_string()), None),
+            ("tx-status on confirmed", |c| {
+                c.confirmation_source = ConfirmationMode::TxStatus;
+                c.commitment = Commitment::Confirmed;
+            }, Some("commitment = \"confirmed\" reads blocks")),
+            ("tx-status on finalized", |c| c.confirmation_source = ConfirmationMode::TxStatus, None),
+            ("empty vote account", |c| c.vote_account.clear(), Some("vote_account cannot be empty")),
+            ("vote account typo", |c| c.vote_account = "Vote111O".to_string(), Some("vote_account (Vote111O) is not a valid base58 pubkey")),
+            ("latency thresholds reversed", |c| {
+                c.performance_logging.min_latency_threshold = Some(5);
+                c.performance_logging.max_latency_threshold = Some(3);
+            }, Some("min_latency_threshold (5) > max_latency_threshold (3)")),
+            ("equal latency thresholds", |c| {
+                // 12 slots earns 6 credits, a poor vote
+                c.performance_logging.min_latency_threshold = Some(12);
+                c.performance_logging.max_latency_threshold = Some(12);
+            }, None),
+            ("tvc thresholds reversed", |c| {
+                c.performance_logging.min_tvc_threshold = Some(10);
+                c.performance_logging.max_tvc_threshold = Some(5);
+            }, Some("min_tvc_threshold (10) > max_tvc_threshold (5)")),
+            ("zero retention", |c| c.performance_logging.retention_days = Some(0), Some("performance_logging.retention_days must be at least 1")),
+            ("one day retention", |c| c.performance_logging.retention_days = Some(1), None),
+            ("grace as long as the credits", |c| c.tvc.grace_slots = 16, Some("tvc.grace_slots (16) must be less than tvc.max_credits_per_slot (16)")),
+            ("grace one under the credits", |c| c.tvc.grace_slots = 15, None),
+            ("good above optimal", |c| c.thresholds.good_efficiency_pct = 96.0, Some("thresholds.good_efficiency_pct (96) must be less than")),
+            ("optimal over 100", |c| c.thresholds.optimal_efficiency_pct = 101.0, Some("thresholds.good_efficiency_pct and thresholds.optimal_efficiency_pct must be within 0-100")),
+            ("zero low latency", |c| c.thresholds.low_latency_slots = 0, Some("thresholds.low_latency_slots must be at least 1")),
+            ("max tvc over the credits", |c| c.performance_logging.max_tvc_threshold = Some(17), Some("max_tvc_threshold (17) cannot exceed 16")),
+            ("max tvc at the credits", |c| c.performance_logging.max_tvc_threshold = Some(16), None),
+            ("zero min tvc", |c| c.performance_logging.min_tvc_threshold = Some(0), Some("min_tvc_threshold cannot be 0")),
+            ("filters matching no vote", |c| {
+                // latencies 1-2 earn the full 16 credits, above max_tvc_threshold
+                c.performance_logging.max_latency_threshold = Some(2);
+            }, Some("performance_logging filters can never match a vote")),
+            ("filters matching no vote while disabled", |c| {
+                c.performance_logging.enabled = false;
+                c.performance_logging.max_latency_threshold = Some(2);
+            }, None),
+            ("zero missed vote cutoff", |c| c.tracker.missed_vote_cutoff_slots = 0, Some("tracker.missed_vote_cutoff_slots cannot be 0")),
+            ("zero duplicate window", |c| c.tracker.duplicate_window_slots = 0, Some("tracker.duplicate_window_slots cannot be 0")),
+            ("zero channel capacity", |c| c.pipeline.channel_capacity = 0, Some("pipeline.channel_capacity cannot be 0")),
+            ("drop threshold over 100", |c| c.pipeline.drop_threshold_pct = 101, Some("pipeline.drop_threshold_pct must be between 1 and 100")),
+            ("drop threshold at 100", |c| c.pipeline.drop_threshold_pct = 100, None),
+            ("short trend buckets", |c| c.stats.trend_bucket_secs = 9, Some("stats.trend_bucket_secs must be at least 10")),
+        ];
+        for (rule, change, error) in cases {
+            let mut config = base();
+            change(&mut config);
+            match (config.validate(), error) {
+                (Ok(()), None) => {}
+                (Err(VoteMonitorError::Config(message)), Some(expected)) => {
+                    assert!(message.starts_with(expected), "{}: {:?} does not start with {:?}", rule, message, expected)
+                }
+                (result, expected) => panic!("{}: expected {:?}, got {:?}", rule, expected, result),
+            }
+        }
+    }
+
+    #[test]
+    fn warning_rules() {
+        // the expected message is part of a warning
+        let cases: &[Rule] = &[
+            ("defaults", |_| {}, None),
+            ("plain http to a remote host", |c| c.grpc_url = "http://grpc.example.com:10000".to_string(), Some("uses plain http to a remote host")),
+            ("plain http to loopback", |c| c.grpc_url = "http://127.0.0.1:10000".to_string(), None),
+            ("pending dropped before missed", |c| c.tracker.pending_max_age_slots = 100, Some("pending votes are kept until they are declared missed")),
+            ("logging without filters", |c| {
+                c.performance_logging = PerformanceFilterConfig {
+                    min_latency_threshold: None,
+                    max_tvc_threshold: None,
+                    performance_levels: Vec::new(),
+                    ..Default::default()
+                };
+            }, Some("performance_logging has no filters")),
+            ("optimal level cut by max tvc", |c| {
+                c.performance_logging.performance_levels = vec!["optimal".to_string(), "poor".to_string()];
+                c.performance_logging.max_tvc_threshold = Some(15);
+            }, Some("performance_levels includes \"optimal\" but max_tvc_threshold (15)")),
+        ];
+        for (rule, change, warning) in cases {
+            let mut config = base();
+            change(&mut config);
+            assert!(config.validate().is_ok(), "{}: {:?}", rule, config.validate());
+            let warnings = config.warnings();
+            match warning {
+                Some(expected) => assert!(warnings.iter().any(|w| w.contains(expected)), "{}: {:?}", rule, warnings),
+                None => assert!(warnings.is_empty(), "{}: {:?}", rule, warnings),
+            }
+        }
+    }
+
+    #[test]
+    fn thresholds_from_toml() {
+        let parse = |section: &str| -> Result<()> {
+            let mut config = base();
+            config.thresholds = toml::from_str(section).map_err(|e| VoteMonitorError::Config(e.to_string()))?;
+            config.validate()
+        };
+        assert!(parse("optimal_efficiency_pct = 98.0\ngood_efficiency_pct = 92.0").is_ok());
+        assert!(parse("optimal_efficiency_pct = 100.0\ngood_efficiency_pct = 99.99").is_ok());
+        // left out fields keep their defaults, 85% is below 90%
+        assert!(parse("optimal_efficiency_pct = 90.0").is_ok());
+        assert!(parse("good_efficiency_pct = 95.0").is_err());
+        assert!(parse("optimal_efficiency_pct = 92.0\ngood_efficiency_pct = 92.0").is_err());
+        assert!(parse("good_efficiency_pct = 0.0\noptimal_efficiency_pct = 50.0").is_err());
+        assert!(parse("low_latency_slots = 1").is_ok());
+        assert!(parse("low_latency_slots = 0").is_err());
+    }
+
+    /// every test that sets VOTEPERFX_* variables holds this, the
+    /// environment is shared by the test threads
+    static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    const FILE_ACCOUNT: &str = "Vote111111111111111111111111111111111111111";
+    const ENV_ACCOUNT: &str = "Stake11111111111111111111111111111111111111";
+    const CLI_ACCOUNT: &str = "Config1111111111111111111111111111111111111";
+
+    /// the example config.toml with a usable endpoint and vote account
+    fn config_file(name: &str) -> PathBuf {
+        let content = include_str!("../../config.toml")
+            .replace("\"https://your-grpc-endpoint:443\"", "\"https://file.example.com:443\"")
+            .replace("\"vote_pubkey\"", &format!("\"{}\"", FILE_ACCOUNT));
+        let path = crate::test_support::temp_dir(name).join("config.toml");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// set exactly these VOTEPERFX_* variables
+    fn set_env(vars: &[(&str, &str)]) {
+        for name in [ENV_GRPC_URL, ENV_VOTE_ACCOUNT, ENV_X_TOKEN] {
+            std::env::remove_var(name);
+        }
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+    }
+
+    #[tokio::test]
+    async fn file_values_without_the_environment() {
+        let _env = ENV_LOCK.lock().await;
+        set_env(&[]);
+        let config = Config::load_from_file(config_file("env-none")).await.unwrap();
+        assert_eq!(config.grpc_endpoints(), ["https://file.example.com:443"]);
+        assert_eq!(config.vote_account, FILE_ACCOUNT);
+        assert!(env_overrides().is_empty());
+    }
+
+    #[tokio::test]
+    async fn environment_overrides_the_file() {
+        let _env = ENV_LOCK.lock().await;
+        set_env(&[
+            (ENV_GRPC_URL, " https://env.example.com:443 "),
+            (ENV_VOTE_ACCOUNT, ENV_ACCOUNT),
+            (ENV_X_TOKEN, "secret"),
+        ]);
+        let config = Config::load_from_file(config_file("env-all")).await.unwrap();
+        set_env(&[]);
+        assert_eq!(config.grpc_url, "https://env.example.com:443");
+        assert!(config.grpc_urls.is_empty());
+        assert_eq!(config.vote_account, ENV_ACCOUNT);
+        assert_eq!(config.grpc_x_token.as_deref(), Some("secret"));
+    }
+
+    #[tokio::test]
+    async fn comma_separated_endpoints_become_the_failover_list() {
+        let _env = ENV_LOCK.lock().await;
+        set_env(&[(ENV_GRPC_URL, "https://a.example.com:443, https://b.example.com:443,")]);
+        assert_eq!(env_overrides(), [ENV_GRPC_URL]);
+        let config = Config::load_from_file(config_file("env-list")).await.unwrap();
+        set_env(&[]);
+        assert!(config.grpc_url.is_empty());
+        assert_eq!(config.grpc_endpoints(), ["https://a.example.com:443", "https://b.example.com:443"]);
+        // untouched by the variable
+        assert_eq!(config.vote_account, FILE_ACCOUNT);
+    }
+
+    #[tokio::test]
+    async fn empty_variables_are_ignored_and_bad_ones_rejected() {
+        let _env = ENV_LOCK.lock().await;
+        set_env(&[(ENV_GRPC_URL, "  "), (ENV_VOTE_ACCOUNT, "")]);
+        let config = Config::load_from_file(config_file("env-empty")).await.unwrap();
+        assert_eq!((config.grpc_url.as_str(), config.vote_account.as_str()), ("https://file.example.com:443", FILE_ACCOUNT));
+
+        // the environment is validated like the file
+        set_env(&[(ENV_GRPC_URL, "file.example.com:443")]);
+        assert!(Config::load_from_file(config_file("env-bad-url")).await.is_err());
+        set_env(&[(ENV_VOTE_ACCOUNT, "not-a-pubkey")]);
+        assert!(Config::load_from_file(config_file("env-bad-account")).await.is_err());
+        set_env(&[]);
+    }
+
+    #[tokio::test]
+    async fn command_line_wins_over_the_environment() {
+        use clap::Parser;
+
+        let _env = ENV_LOCK.lock().await;
+        set_env(&[(ENV_GRPC_URL, "https://a.example.com:443,https://b.example.com:443"), (ENV_VOTE_ACCOUNT, ENV_ACCOUNT)]);
+        let mut config = Config::load_from_file(config_file("env-cli")).await.unwrap();
+        set_env(&[]);
+        let cli = crate::cli::Cli::try_parse_from([
+            "voteperfx", "--grpc-url", "https://cli.example.com:443", "--vote-account", CLI_ACCOUNT,
+        ]).unwrap();
+        cli.apply_overrides(&mut config);
+        assert_eq!(config.grpc_endpoints(), ["https://cli.example.com:443"]);
+        assert_eq!(config.vote_account, CLI_ACCOUNT);
+        config.validate().unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use tokio::task::JoinHandle;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::events::EventBus;
+use crate::logging::set_log_filters;
+use crate::message::SystemEvent;
+
+/// settings applied on top of every reloaded file, e.g. command line flags
+pub type ConfigOverrides = Arc<dyn Fn(&mut Config) + Send + Sync>;
+
+/// config file to reload and the overrides applied on top of it
+#[derive(Clone)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    pub overrides: ConfigOverrides,
+}
+
+impl ConfigSource {
+    pub fn new(path: impl Into<PathBuf>, overrides: impl Fn(&mut Config) + Send + Sync + 'static) -> Self {
+        Self { path: path.into(), overrides: Arc::new(overrides) }
+    }
+}
+
+impl fmt::Debug for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigSource").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+/// the running config, swapped as a whole on reload
+///
+/// readers take a snapshot with `current` and keep a consistent view
+/// while a reload replaces it.
+#[derive(Debug, Clone)]
+pub struct SharedConfig {
+    inner: Arc<RwLock<Arc<Config>>>,
+}
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        Self { inner: Arc::new(RwLock::new(Arc::new(config))) }
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    pub fn replace(&self, config: Arc<Config>) {
+        *self.inner.write().unwrap_or_else(PoisonError::into_inner) = config;
+    }
+}
+
+/// load and validate the source file and apply its live settings to `shared`
+///
+/// settings that need a restart are logged and left as they are. returns the
+/// new config, none when no live setting changed. an invalid file keeps the
+/// running config.
+pub async fn reload_config(source: &ConfigSource, shared: &SharedConfig) -> Result<Option<Arc<Config>>> {
+    let path = source.path.as_path();
+    let content = tokio::fs::read_to_string(path).await?;
+    let mut loaded: Config = toml::from_str(&content)?;
+    loaded.apply_env_overrides();
+    (source.overrides)(&mut loaded);
+    loaded.validate()?;
+
+    let current = shared.current();
+    let mut next = (*current).clone();
+    next.apply_live_settings(&loaded);
+
+    let restart_required = next.changed_settings(&loaded);
+    if !restart_required.is_empty() {
+        log::warn!(
+            "{} changed {}, restart required to apply, keeping the running values",
+            path.display(), restart_required.join(", ")
+        );
+    }
+
+    let changed = current.changed_settings(&next);
+    if changed.is_empty() {
+        return Ok(None);
+    }
+    log::info!("config reloaded from {}: {}", path.display(), changed.join(", "));
+
+    if current.log_level != next.log_level {
+        set_log_filters(next.log_level.as_deref());
+    }
+    let next = Arc::new(next);
+    shared.replace(next.clone());
+    Ok(Some(next))
+}
+
+/// reload the source file whenever its modification time changes
+///
+/// a successful reload that changed live settings publishes
+/// `SystemEvent::ConfigReloaded`.
+pub fn spawn_config_watcher(
+    source: ConfigSource,
+    shared: SharedConfig,
+    events: EventBus,
+    poll_interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let path = source.path.as_path();
+        let mut modified = modified_time(path).await;
+        let mut interval = tokio::time::interval(poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            let current = modified_time(path).await;
+            if current.is_none() || current == modified {
+                continue;
+            }
+            modified = current;
+
+            match reload_config(&source, &shared).await {
+                Ok(Some(config)) => events.publish(SystemEvent::ConfigReloaded(config)),
+                Ok(None) => log::debug!("{} changed, no live settings differ", path.display()),
+                Err(e) => log::error!("failed to reload {}, keeping the running config: {}", path.display(), e),
+            }
+        }
+    })
+}
+
+async fn modified_time(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,172 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use serde::Serialize;
+
+use crate::config::CoverageConfig;
+use crate::performance::Slot;
+
+/// run of finalized slots without a confirmed vote from the monitored account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VoteGap {
+    pub first_slot: Slot,
+    pub last_slot: Slot,
+    /// finalized blocks in the gap, skipped slots are not counted
+    pub slots: u64,
+}
+
+/// voted share of the settled finalized slots in the window
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CoverageSummary {
+    pub finalized_slots: u64,
+    pub voted_slots: u64,
+}
+
+impl CoverageSummary {
+    pub fn percentage(&self) -> f64 {
+        if self.finalized_slots == 0 { return 100.0; }
+        self.voted_slots as f64 / self.finalized_slots as f64 * 100.0
+    }
+}
+
+/// sliding window of finalized slots and whether the monitored account voted on them
+///
+/// efficiency only covers slots that were voted on, a validator that stops
+/// voting keeps its efficiency. coverage counts the finalized blocks instead.
+/// a slot is only judged once it is `settle_slots` behind the newest finalized
+/// slot, giving its vote time to land and be confirmed, and the first
+/// `settle_slots` after startup are ignored since their votes may have landed
+/// before the stream connected.
+#[derive(Debug, Clone)]
+pub struct VoteCoverage {
+    window_slots: usize,
+    settle_slots: u64,
+    gap_alert_slots: u64,
+    // finalized block slots, oldest first
+    finalized: VecDeque<Slot>,
+    // voted slots at or after the oldest finalized slot in the window
+    voted: BTreeSet<Slot>,
+    first_slot: Option<Slot>,
+    gap_alerted: bool,
+}
+
+impl Default for VoteCoverage {
+    fn default() -> Self {
+        Self::new(&CoverageConfig::default())
+    }
+}
+
+impl VoteCoverage {
+    pub fn new(config: &CoverageConfig) -> Self {
+        let window_slots = config.window_slots.max(1);
+        Self {
+            window_slots,
+            settle_slots: config.settle_slots,
+            gap_alert_slots: config.gap_alert_slots.max(1),
+            finalized: VecDeque::with_capacity(window_slots + config.settle_slots as usize),
+            voted: BTreeSet::new(),
+            first_slot: None,
+            gap_alerted: false,
+        }
+    }
+
+    /// same window configuration, no slots
+    pub fn fresh(&self) -> Self {
+        Self {
+            window_slots: self.window_slots,
+            settle_slots: self.settle_slots,
+            gap_alert_slots: self.gap_alert_slots,
+            finalized: VecDeque::with_capacity(self.finalized.capacity()),
+            voted: BTreeSet::new(),
+            first_slot: None,
+            gap_alerted: false,
+        }
+    }
+
+    pub fn record_vote(&mut self, voted_slot: Slot) {
+        if self.finalized.front().is_some_and(|&oldest| voted_slot < oldest) {
+            return;
+        }
+        self.voted.insert(voted_slot);
+    }
+
+    /// add a finalized block, returns the gap when it first crosses the alert threshold
+    pub fn record_finalized_slot(&mut self, slot: Slot) -> Option<VoteGap> {
+        // duplicates and late out-of-order blocks are ignored
+        if self.finalized.back().is_some_and(|&newest| slot <= newest) {
+            return None;
+        }
+
+        self.first_slot.get_or_insert(slot);
+        self.finalized.push_back(slot);
+        while self.finalized.len() > self.window_slots + self.settle_slots as usize {
+            self.finalized.pop_front();
+        }
+        if let Some(&oldest) = self.finalized.front() {
+            self.voted = self.voted.split_off(&oldest);
+        }
+
+        match self.current_gap() {
+            Some(gap) if gap.slots >= self.gap_alert_slots => {
+                if self.gap_alerted {
+                    return None;
+                }
+                self.gap_alerted = true;
+                Some(gap)
+            }
+            _ => {
+                if self.gap_alerted {
+                    log::info!("vote coverage recovered, votes are landing again");
+                }
+                self.gap_alerted = false;
+                None
+            }
+        }
+    }
+
+    /// newest slot old enough to be judged, none during warmup
+    fn settled_cutoff(&self) -> Option<Slot> {
+        let newest = *self.finalized.back()?;
+        let cutoff = newest.checked_sub(self.settle_slots)?;
+        let warmup_end = self.first_slot? + self.settle_slots;
+        (cutoff >= warmup_end).then_some(cutoff)
+    }
+
+    fn settled_slots(&self) -> impl DoubleEndedIterator<Item = Slot> + '_ {
+        let cutoff = self.settled_cutoff();
+        let warmup_end = self.first_slot.unwrap_or_default() + self.settle_slots;
+        self.finalized
+            .iter()
+            .copied()
+            .filter(move |&slot| cutoff.is_some_and(|cutoff| slot <= cutoff && slot >= warmup_end))
+    }
+
+    pub fn summary(&self) -> CoverageSummary {
+        let mut summary = CoverageSummary::default();
+        for slot in self.settled_slots() {
+            summary.finalized_slots += 1;
+            if self.voted.contains(&slot) {
+                summary.voted_slots += 1;
+            }
+        }
+        summary
+    }
+
+    /// trailing run of settled slots without a vote
+    pub fn current_gap(&self) -> Option<VoteGap> {
+        let mut gap: Option<VoteGap> = None;
+        for slot in self.settled_slots().rev() {
+            if self.voted.contains(&slot) {
+                break;
+            }
+            let gap = gap.get_or_insert(VoteGap { first_slot: slot, last_slot: slot, slots: 0 });
+            gap.first_slot = slot;
+            gap.slots += 1;
+        }
+        gap
+    }
+
+    /// whether the current gap has crossed the alert threshold
+    pub fn gap_alerted(&self) -> bool {
+        self.gap_alerted
+    }
+}
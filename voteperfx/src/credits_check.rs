@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::config::CreditsDriftConfig;
+use crate::error::{Result, VoteMonitorError};
+use crate::events::EventBus;
+use crate::message::SystemEvent;
+use crate::performance::PerformanceStats;
+use crate::rpc::{PollBackoff, RpcClient};
+
+/// locally computed vs on-chain credits over one sampling window
+#[derive(Debug, Clone, Serialize)]
+pub struct CreditsDrift {
+    pub checked_at: DateTime<Local>,
+    pub window_secs: u64,
+    pub local_credits: u64,
+    pub on_chain_credits: u64,
+    /// positive when voteperfx counted more credits than the chain awarded
+    pub drift_pct: f64,
+    pub exceeded: bool,
+}
+
+#[derive(Deserialize)]
+struct VoteAccounts {
+    current: Vec<VoteAccountInfo>,
+    delinquent: Vec<VoteAccountInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VoteAccountInfo {
+    /// (epoch, credits, previous credits), credits are cumulative
+    epoch_credits: Vec<(u64, u64, u64)>,
+}
+
+/// epoch credits of the vote account over json-rpc
+#[derive(Debug, Clone)]
+pub struct RpcCreditsClient {
+    rpc: RpcClient,
+    vote_account: String,
+}
+
+impl RpcCreditsClient {
+    pub fn new(url: impl Into<String>, vote_account: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            rpc: RpcClient::new(url)?,
+            vote_account: vote_account.into(),
+        })
+    }
+
+    /// lifetime credits of the vote account at finalized commitment
+    pub async fn fetch_credits(&self) -> Result<u64> {
+        self.fetch_epoch_credits_history()
+            .await?
+            .last()
+            .map(|&(_, credits, _)| credits)
+            .ok_or_else(|| VoteMonitorError::Rpc(format!("no epoch credits for vote account {}", self.vote_account)))
+    }
+
+    /// credits earned during `epoch`, none once it dropped out of the
+    /// account's recent epoch credits
+    pub async fn fetch_epoch_credits(&self, epoch: u64) -> Result<Option<u64>> {
+        Ok(self.fetch_epoch_credits_history()
+            .await?
+            .into_iter()
+            .find(|&(credits_epoch, _, _)| credits_epoch == epoch)
+            .map(|(_, credits, previous)| credits.saturating_sub(previous)))
+    }
+
+    async fn fetch_epoch_credits_history(&self) -> Result<Vec<(u64, u64, u64)>> {
+        let accounts: VoteAccounts = self.rpc
+            .call("getVoteAccounts", json!([{
+                "votePubkey": self.vote_account,
+                "commitment": "finalized",
+                "keepUnstakedDelinquents": true,
+            }]))
+            .await?;
+
+        accounts.current
+            .into_iter()
+            .chain(accounts.delinquent)
+            .map(|account| account.epoch_credits)
+            .next()
+            .ok_or_else(|| VoteMonitorError::Rpc(format!("vote account {} not found", self.vote_account)))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CreditsSample {
+    taken_at: Instant,
+    local: u64,
+    on_chain: u64,
+}
+
+/// compare credit deltas between two samples, none when there is nothing to compare
+fn compare(previous: &CreditsSample, current: &CreditsSample, threshold_pct: f64) -> Option<CreditsDrift> {
+    // a session reset moves the local counter backwards, start a new window
+    let local = current.local.checked_sub(previous.local)?;
+    let on_chain = current.on_chain.checked_sub(previous.on_chain)?;
+    if local == 0 && on_chain == 0 {
+        return None;
+    }
+
+    let drift_pct = if on_chain == 0 {
+        100.0
+    } else {
+        (local as f64 - on_chain as f64) / on_chain as f64 * 100.0
+    };
+
+    Some(CreditsDrift {
+        checked_at: Local::now(),
+        window_secs: current.taken_at.duration_since(previous.taken_at).as_secs(),
+        local_credits: local,
+        on_chain_credits: on_chain,
+        drift_pct,
+        exceeded: drift_pct.abs() > threshold_pct,
+    })
+}
+
+/// poll on-chain epoch credits and compare them to the session's credits
+///
+/// both counters are sampled together, so the comparison covers the same
+/// window. failed or rate limited polls skip the cycle and back off.
+pub fn spawn_credits_monitor(
+    client: RpcCreditsClient,
+    config: CreditsDriftConfig,
+    stats: Arc<RwLock<PerformanceStats>>,
+    events: EventBus,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let poll_interval = Duration::from_secs(config.poll_interval_secs.max(1));
+        let mut previous: Option<CreditsSample> = None;
+        let mut backoff = PollBackoff::new(poll_interval);
+
+        loop {
+            backoff.wait().await;
+
+            match client.fetch_credits().await {
+                Ok(on_chain) => {
+                    backoff.succeeded();
+                    let current = CreditsSample {
+                        taken_at: Instant::now(),
+                        local: stats.read().await.total_tvc_earned(),
+                        on_chain,
+                    };
+
+                    if let Some(drift) = previous.and_then(|previous| compare(&previous, &current, config.threshold_pct)) {
+                        record_drift(drift, &config, &stats, &events).await;
+                    }
+                    previous = Some(current);
+                }
+                Err(e) => log::warn!("credits check failed ({} in a row), skipping cycle: {}", backoff.failed(), e),
+            }
+        }
+    })
+}
+
+async fn record_drift(
+    drift: CreditsDrift,
+    config: &CreditsDriftConfig,
+    stats: &RwLock<PerformanceStats>,
+    events: &EventBus,
+) {
+    if drift.exceeded {
+        match serde_json::to_string(&drift) {
+            Ok(json) => log::warn!("credits drift above {}%: {}", config.threshold_pct, json),
+            Err(_) => log::warn!("credits drift above {}%: {:+.1}%", config.threshold_pct, drift.drift_pct),
+        }
+        events.publish(SystemEvent::CreditsMismatch(Arc::new(drift.clone())));
+    } else {
+        log::debug!(
+            "credits check: local {} vs on-chain {} over {}s ({:+.1}%)",
+            drift.local_credits, drift.on_chain_credits, drift.window_secs, drift.drift_pct
+        );
+    }
+
+    let mut stats = stats.write().await;
+    stats.credits_drift = Some(drift);
+    stats.mark_changed();
+}
@@ -1,369 +1,695 @@
-use std::collections::VecDeque;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once};
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Local};
 use crossterm::{
     cursor::{self, Hide, Show},
+    event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     execute,
     style::{ResetColor, SetForegroundColor},
-    terminal::{Clear, ClearType, size},
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use ratatui::{
+    backend::{CrosstermBackend, TestBackend},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Bar, BarChart, BarGroup, Gauge, Paragraph,
+    },
+    Frame, Terminal,
+};
+use serde::Serialize;
 
-use crate::performance::{PerformanceStats, ConfirmedVote, format_duration, format_number};
+use crate::channel_metrics::{ChannelSnapshot, PipelineSnapshot};
+use crate::cluster::ClusterSnapshot;
+use crate::memory::{MemoryUsage, format_bytes};
+use crate::network::{Explorer, Network};
+use crate::config::TvcConfig;
+use crate::leader_schedule::{BlockProduction, LeaderPoorVotes, LeaderSlotCounts};
+use crate::rolling_windows::ROLLING_WINDOWS;
+use crate::signature_display::{osc52_copy, truncate_visible, SignatureDisplay};
+use crate::performance::{
+    ConfirmationKind, ConfirmationSource, HistogramScale, PerformanceStats, ConfirmedVote, TvcPerformanceLevel, categorize_tvc_performance,
+    format_duration, format_number, performance_status_for,
+};
 use crate::error::{Result, VoteMonitorError};
+use crate::endpoints::EndpointStatus;
+use crate::stream_health::StreamStaleness;
+use crate::theme::{DashboardTheme, Theme, MARKER_WIDTH};
+use crate::vote_tracker::VoteTrackerStats;
+use crate::{GIT_HASH, VERSION};
+
+/// smallest terminal the layout is drawn in, smaller ones get a message instead
+const MIN_WIDTH: u16 = 60;
+const MIN_HEIGHT: u16 = 20;
+/// terminal rows below which the efficiency trend and tvc chart are hidden
+const MIN_CHART_HEIGHT: u16 = 18;
+/// terminal rows below which the side panels and epoch history are hidden
+const MIN_DETAIL_HEIGHT: u16 = 30;
+const MAX_POOR_EVENT_ROWS: usize = 8;
+const WORST_LEADER_ROWS: usize = 5;
+/// minutes in the votes sent sparkline of the header
+const VOTES_SENT_MINUTES: usize = 30;
+const SCROLL_PAGE: usize = 10;
+/// how long a notice stays in the footer
+const NOTICE_DURATION: Duration = Duration::from_secs(5);
+
+static PANIC_HOOK: Once = Once::new();
+
+// raw mode and the alternate screen are set up, nothing is written to stdout
+// on cleanup or panic otherwise
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// what the caller should do after a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardAction {
+    None,
+    Redraw,
+    Quit,
+    ResetStats,
+    /// hand the terminal to simple mode logging, see `DashboardRenderer::suspend`
+    SimpleMode,
+}
+
+/// which poor performance events the dashboard lists, cycled with `f`
+///
+/// only the panel is filtered, the performance event files are not.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PoorEventFilter {
+    /// every vote below max credits
+    #[default]
+    All,
+    /// below the good threshold, 12 of 16
+    BelowGood,
+    /// below half of max credits, 8 of 16
+    BelowHalf,
+    /// critical votes only, below a quarter of max credits
+    Critical,
+}
+
+impl PoorEventFilter {
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::BelowGood,
+            Self::BelowGood => Self::BelowHalf,
+            Self::BelowHalf => Self::Critical,
+            Self::Critical => Self::All,
+        }
+    }
+
+    /// credits a vote must stay below to be listed
+    pub fn limit(self, tvc: &TvcConfig) -> u64 {
+        let max = tvc.max_credits_per_slot;
+        match self {
+            Self::All => max,
+            Self::BelowGood => tvc.good_threshold(),
+            Self::BelowHalf => max / 2,
+            Self::Critical => max / 4,
+        }
+    }
+
+    /// mildest severity listed
+    pub fn level(self) -> TvcPerformanceLevel {
+        match self {
+            Self::All => TvcPerformanceLevel::Good,
+            Self::BelowGood => TvcPerformanceLevel::Fair,
+            Self::BelowHalf => TvcPerformanceLevel::Poor,
+            Self::Critical => TvcPerformanceLevel::Critical,
+        }
+    }
+
+    pub fn matches(self, vote: &ConfirmedVote, tvc: &TvcConfig) -> bool {
+        vote.tvc_credits < self.limit(tvc)
+    }
 
-/// pre-allocated buffers
+    fn label(self, tvc: &TvcConfig) -> String {
+        match self {
+            Self::Critical => format!("critical only, < {} tvc", self.limit(tvc)),
+            _ => format!("< {} tvc", self.limit(tvc)),
+        }
+    }
+}
+
+/// ratatui dashboard on the alternate screen
 pub struct DashboardRenderer {
-    output_buffer: String,
-    previous_lines: Vec<String>,
-    terminal_width: u16,
-    terminal_height: u16,
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    paused: bool,
+    // stats copied when the view paused, drawn instead of the live ones
+    frozen: Option<Frozen>,
+    needs_redraw: bool,
+    scroll: usize,
+    active: bool,
+    // stats generation of the last frame, see PerformanceStats::mark_changed
+    drawn_generation: Option<u64>,
+    // footer notice and when it was shown, see `notice`
+    notice: Option<(String, Instant)>,
+    theme: Theme,
+    poor_filter: PoorEventFilter,
+    show_process: bool,
+    // vote tracker internals in the footer, toggled with `d`
+    show_debug: bool,
+    histogram_scale: HistogramScale,
+    signature_display: SignatureDisplay,
+    explorer: Explorer,
+    // the last frame was the too small message, see MIN_WIDTH
+    too_small: bool,
+    // row of the recent votes table, counted from the newest vote
+    selected: Option<usize>,
+    // signature of the selected row in the last frame, what `c` copies
+    selected_signature: Option<Arc<String>>,
 }
 
 impl DashboardRenderer {
-    pub fn new() -> Self {
-        let (width, height) = size().unwrap_or((80, 24));
-        Self {
-            output_buffer: String::with_capacity(8192), // pre-allocate
-            previous_lines: Vec::with_capacity(50),
-            terminal_width: width,
-            terminal_height: height,
+    /// take over the terminal, fails without touching it when stdout is not a tty
+    pub fn new() -> Result<Self> {
+        if !io::stdout().is_terminal() {
+            return Err(VoteMonitorError::Dashboard(
+                "stdout is not a terminal, run with --simple or --headless".to_string()
+            ));
         }
+        install_panic_hook();
+        enter_terminal()?;
+
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to create terminal: {}", e)))?;
+
+        Ok(Self {
+            terminal,
+            paused: false,
+            frozen: None,
+            needs_redraw: true,
+            scroll: 0,
+            active: true,
+            drawn_generation: None,
+            notice: None,
+            theme: Theme::default(),
+            poor_filter: PoorEventFilter::default(),
+            show_process: true,
+            show_debug: false,
+            histogram_scale: HistogramScale::default(),
+            signature_display: SignatureDisplay::default(),
+            explorer: Explorer::default(),
+            too_small: false,
+            selected: None,
+            selected_signature: None,
+        })
     }
 
-    pub async fn render(&mut self, stats: &PerformanceStats, vote_account: &str) -> Result<()> {
-        let mut stdout = io::stdout();
-        
-        // hide cursor during rendering
-        execute!(stdout, Hide)
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to hide cursor: {}", e)))?;
-        
-        // check terminal size changes
-        if let Ok((new_width, new_height)) = size() {
-            if new_width != self.terminal_width || new_height != self.terminal_height {
-                self.terminal_width = new_width;
-                self.terminal_height = new_height;
-                self.previous_lines.clear(); // force full redraw on resize
-            }
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// the process line in the footer, see `DashboardConfig::show_process`
+    pub fn with_process_panel(mut self, show: bool) -> Self {
+        self.show_process = show;
+        self
+    }
+
+    /// how the signature columns are shown, see `DashboardConfig::signature_display`
+    /// bar lengths of the tvc distribution panel, see `DashboardConfig::histogram_scale`
+    pub fn with_histogram_scale(mut self, scale: HistogramScale) -> Self {
+        self.histogram_scale = scale;
+        self
+    }
+
+    pub fn with_signature_display(mut self, display: SignatureDisplay) -> Self {
+        self.signature_display = display;
+        self
+    }
+
+    /// where signature links point, see `Config::network_profile`
+    pub fn with_explorer(mut self, explorer: Explorer) -> Self {
+        self.explorer = explorer;
+        self
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn poor_filter(&self) -> PoorEventFilter {
+        self.poor_filter
+    }
+
+    /// whether the footer shows the vote tracker line, see `Monitor::sample_tracker`
+    pub fn shows_debug(&self) -> bool {
+        self.show_debug
+    }
+
+    /// draw the whole frame on the next render, e.g. after a terminal resize
+    pub fn request_redraw(&mut self) {
+        self.needs_redraw = true;
+    }
+
+    /// show a short message in the footer for a few seconds
+    pub fn notice(&mut self, message: impl Into<String>) {
+        self.notice = Some((message.into(), Instant::now()));
+        self.needs_redraw = true;
+    }
+
+    /// apply a key press to the view state
+    pub fn handle_key(&mut self, key: KeyEvent) -> DashboardAction {
+        if key.kind != KeyEventKind::Press {
+            return DashboardAction::None;
         }
-        
-        self.build_dashboard_content(stats, vote_account);
-        
-        // split output into lines
-        let new_lines: Vec<String> = self.output_buffer.lines().map(String::from).collect();
-        
-        if self.previous_lines.is_empty() {
-            // first render or after resize - clear and draw everything
-            execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))
-                .map_err(|e| VoteMonitorError::Dashboard(format!("failed to clear screen: {}", e)))?;
-            
-            write!(stdout, "{}", self.output_buffer)
-                .map_err(|e| VoteMonitorError::Dashboard(format!("failed to write output: {}", e)))?;
-        } else {
-            // only redraw changed lines
-            for (i, (old_line, new_line)) in self.previous_lines.iter()
-                .zip(new_lines.iter())
-                .enumerate()
-            {
-                if old_line != new_line {
-                    execute!(stdout, cursor::MoveTo(0, i as u16))
-                        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to move cursor: {}", e)))?;
-                    
-                    // clear to end of line to handle shorter new content
-                    write!(stdout, "{}\x1b[K", new_line)
-                        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to write line: {}", e)))?;
+
+        let action = match key.code {
+            KeyCode::Char('q') => DashboardAction::Quit,
+            // raw mode swallows SIGINT, so ctrl+c arrives as a key
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => DashboardAction::Quit,
+            KeyCode::Char(' ') | KeyCode::Char('p') => {
+                self.paused = !self.paused;
+                DashboardAction::Redraw
+            }
+            KeyCode::Char('r') => {
+                self.scroll = 0;
+                self.selected = None;
+                DashboardAction::ResetStats
+            }
+            KeyCode::Char('f') => {
+                self.poor_filter = self.poor_filter.next();
+                DashboardAction::Redraw
+            }
+            KeyCode::Char('d') => {
+                self.show_debug = !self.show_debug;
+                DashboardAction::Redraw
+            }
+            KeyCode::Char('l') => DashboardAction::SimpleMode,
+            KeyCode::Char('c') => {
+                self.copy_selected();
+                DashboardAction::Redraw
+            }
+            // down selects the first visible vote, the table scrolls to keep the selection in view
+            KeyCode::Up => {
+                match self.selected {
+                    Some(row) => self.selected = Some(row.saturating_sub(1)),
+                    None => self.scroll = self.scroll.saturating_sub(1),
                 }
+                DashboardAction::Redraw
+            }
+            KeyCode::Down => {
+                self.selected = Some(self.selected.map_or(self.scroll, |row| row + 1));
+                DashboardAction::Redraw
             }
-            
-            // handle case where new content has more lines
-            if new_lines.len() > self.previous_lines.len() {
-                for (i, new_line) in new_lines[self.previous_lines.len()..].iter().enumerate() {
-                    let row = self.previous_lines.len() + i;
-                    execute!(stdout, cursor::MoveTo(0, row as u16))
-                        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to move cursor: {}", e)))?;
-                    
-                    write!(stdout, "{}", new_line)
-                        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to write line: {}", e)))?;
+            KeyCode::PageUp => {
+                match self.selected {
+                    Some(row) => self.selected = Some(row.saturating_sub(SCROLL_PAGE)),
+                    None => self.scroll = self.scroll.saturating_sub(SCROLL_PAGE),
                 }
+                DashboardAction::Redraw
             }
-            
-            // clear any remaining lines if new content is shorter
-            if new_lines.len() < self.previous_lines.len() {
-                for i in new_lines.len()..self.previous_lines.len() {
-                    execute!(stdout, cursor::MoveTo(0, i as u16))
-                        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to move cursor: {}", e)))?;
-                    
-                    write!(stdout, "\x1b[K") // clear line
-                        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to clear line: {}", e)))?;
+            KeyCode::PageDown => {
+                match self.selected {
+                    Some(row) => self.selected = Some(row + SCROLL_PAGE),
+                    None => self.scroll += SCROLL_PAGE,
                 }
+                DashboardAction::Redraw
+            }
+            KeyCode::Home => {
+                self.scroll = 0;
+                self.selected = None;
+                DashboardAction::Redraw
             }
+            KeyCode::Esc => {
+                self.selected = None;
+                DashboardAction::Redraw
+            }
+            _ => DashboardAction::None,
+        };
+
+        if action != DashboardAction::None {
+            self.needs_redraw = true;
         }
-        
-        stdout.flush()
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to flush output: {}", e)))?;
-        
-        self.previous_lines = new_lines;
-        
-        Ok(())
+        action
     }
-    
-    /// cleanup terminal state - before exiting
-    pub fn cleanup(&self) -> Result<()> {
+
+    /// put the selected vote on the clipboard with OSC 52, the terminal does the copying
+    fn copy_selected(&mut self) {
+        let Some(signature) = self.selected_signature.as_deref() else {
+            self.notice(format!("select a vote with {} first", self.theme.text("↑/↓", "up/down")));
+            return;
+        };
+        let text = self.signature_display.copy_text(signature, &self.explorer);
         let mut stdout = io::stdout();
-        
-        execute!(stdout, ResetColor)
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to reset color: {}", e)))?;
-        
-        // clear screen
-        execute!(stdout, Clear(ClearType::All))
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to clear screen: {}", e)))?;
-        
-        execute!(stdout, cursor::MoveTo(0, 0))
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to move cursor: {}", e)))?;
-        
-        // ensure cursor is visible
-        execute!(stdout, Show)
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to show cursor: {}", e)))?;
-        
-        // write a reset sequence to ensure terminal is in a good state
-        write!(stdout, "\x1b[0m")?; // reset all attributes
-        write!(stdout, "\x1b[?25h")?; // show cursor (backup)
-        
-        // flush to ensure all changes are applied
-        stdout.flush()
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to flush output: {}", e)))?;
-        
+        let copied = stdout.write_all(osc52_copy(&text).as_bytes()).and_then(|()| stdout.flush());
+        match copied {
+            Ok(()) => self.notice(format!("copied {}", text)),
+            Err(e) => self.notice(format!("copy failed: {}", e)),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn render(&mut self, stats: &PerformanceStats, vote_account: &str, memory: &MemoryUsage) -> Result<()> {
+        // an expired notice needs one more frame to disappear
+        if self.notice.as_ref().is_some_and(|(_, shown)| shown.elapsed() >= NOTICE_DURATION) {
+            self.notice = None;
+            self.needs_redraw = true;
+        }
+        // pausing copies the stats, scrolling keeps working over the copy;
+        // resuming starts from a blank screen with the live stats
+        let live_votes = stats.confirmed_votes();
+        match (self.paused, self.frozen.is_some()) {
+            (true, false) => {
+                self.frozen = Some(Frozen {
+                    stats: Box::new(stats.frozen_copy()),
+                    mark: PauseMark { at: Local::now(), behind: 0 },
+                    votes: live_votes,
+                });
+                self.needs_redraw = true;
+            }
+            (false, true) => {
+                self.frozen = None;
+                self.terminal
+                    .clear()
+                    .map_err(|e| VoteMonitorError::Dashboard(format!("failed to clear terminal: {}", e)))?;
+                self.needs_redraw = true;
+            }
+            _ => {}
+        }
+        // paused views only redraw in response to keys and to move the
+        // count of votes behind
+        if let Some(frozen) = self.frozen.as_mut() {
+            let behind = live_votes.saturating_sub(frozen.votes);
+            if behind != frozen.mark.behind {
+                frozen.mark.behind = behind;
+                self.needs_redraw = true;
+            }
+            if !self.needs_redraw {
+                return Ok(());
+            }
+        }
+        // nothing changed since the last frame, an idle monitor writes nothing
+        let generation = stats.generation();
+        if !self.needs_redraw && self.drawn_generation == Some(generation) {
+            return Ok(());
+        }
+
+        // growing back past the minimum starts from a blank screen, no
+        // leftovers of the message survive the diff
+        let size = self.terminal
+            .size()
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to read terminal size: {}", e)))?;
+        let too_small = is_too_small(size);
+        if self.too_small && !too_small {
+            self.terminal
+                .clear()
+                .map_err(|e| VoteMonitorError::Dashboard(format!("failed to clear terminal: {}", e)))?;
+        }
+        self.too_small = too_small;
+
+        let mut scroll = self.scroll;
+        let mut selected = self.selected;
+        let mut selected_signature = None;
+        let mut view = View {
+            paused: self.frozen.as_ref().map(|frozen| frozen.mark),
+            notice: self.notice.as_ref().map(|(message, _)| message.as_str()),
+            scroll: &mut scroll,
+            selected: &mut selected,
+            selected_signature: &mut selected_signature,
+            theme: &self.theme,
+            poor_filter: self.poor_filter,
+            show_process: self.show_process,
+            show_debug: self.show_debug,
+            histogram_scale: self.histogram_scale,
+            signature_display: self.signature_display,
+            explorer: &self.explorer,
+        };
+
+        let stats = self.frozen.as_ref().map_or(stats, |frozen| &*frozen.stats);
+        self.terminal
+            .draw(|frame| {
+                draw_dashboard(frame, stats, vote_account, memory, &mut view);
+            })
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to draw dashboard: {}", e)))?;
+
+        self.scroll = scroll;
+        self.selected = selected;
+        self.selected_signature = selected_signature;
+        self.needs_redraw = false;
+        self.drawn_generation = Some(generation);
+
         Ok(())
     }
-    
-    /// cleanup terminal without clearing screen - preserves final output
-    pub fn cleanup_without_clear(&self) -> Result<()> {
-        let mut stdout = io::stdout();
-        
-        execute!(stdout, ResetColor)
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to reset color: {}", e)))?;
-        
-        // ensure cursor is visible
-        execute!(stdout, Show)
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to show cursor: {}", e)))?;
-        
-        // write reset sequences to ensure terminal is in a good state
-        write!(stdout, "\x1b[0m")?; // reset all attributes
-        write!(stdout, "\x1b[?25h")?; // show cursor (backup)
-        write!(stdout, "\n")?; // add newline for clean output
-        
-        // flush to ensure all changes are applied
-        stdout.flush()
-            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to flush output: {}", e)))?;
-        
+
+    /// draw one frame of `width` x `height` cells into a buffer instead of
+    /// the terminal, for benchmarks and checking the layout without a tty
+    pub fn build_dashboard_content(
+        stats: &PerformanceStats,
+        vote_account: &str,
+        memory: &MemoryUsage,
+        theme: &Theme,
+        (width, height): (u16, u16),
+    ) -> Result<Buffer> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height))
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to create terminal: {}", e)))?;
+        let (explorer, mut scroll, mut selected, mut selected_signature) = (Explorer::default(), 0, None, None);
+        let mut view = View::offscreen(theme, &explorer, &mut scroll, &mut selected, &mut selected_signature);
+        terminal
+            .draw(|frame| draw_dashboard(frame, stats, vote_account, memory, &mut view))
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to draw dashboard: {}", e)))?;
+        Ok(terminal.backend().buffer().clone())
+    }
+
+    /// the text of each panel of a `width` x `height` frame, in drawing order
+    ///
+    /// the same panels the terminal draws, with styles dropped. see
+    /// `compose_panels` for a plain text dashboard.
+    pub fn build_dashboard_panels(
+        stats: &PerformanceStats,
+        vote_account: &str,
+        memory: &MemoryUsage,
+        theme: &Theme,
+        (width, height): (u16, u16),
+    ) -> Vec<Panel> {
+        let (explorer, mut scroll, mut selected, mut selected_signature) = (Explorer::default(), 0, None, None);
+        let mut view = View::offscreen(theme, &explorer, &mut scroll, &mut selected, &mut selected_signature);
+        let area = Rect::new(0, 0, width, height);
+        dashboard_panels(stats, vote_account, memory, area, &mut view)
+            .iter()
+            .map(PanelContent::text)
+            .collect()
+    }
+
+    /// give the terminal back for plain log lines without ending the session,
+    /// `resume` takes it over again
+    pub fn suspend(&mut self) -> Result<()> {
+        restore_terminal()
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to restore terminal: {}", e)))
+    }
+
+    /// take the terminal over again after `suspend`, the next render draws
+    /// the full frame on a cleared screen
+    pub fn resume(&mut self) -> Result<()> {
+        enter_terminal()?;
+        self.terminal
+            .clear()
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to clear terminal: {}", e)))?;
+        self.notice = None;
+        self.needs_redraw = true;
         Ok(())
     }
 
-    /// dashboard in memory
-    fn build_dashboard_content(&mut self, stats: &PerformanceStats, vote_account: &str) {
-        self.output_buffer.clear();
-        
-        self.add_header(vote_account);
-        
-        self.add_session_overview(stats);
-        
-        self.add_tvc_performance_chart(&stats.recent_confirmed_votes);
-        
-        self.add_efficiency_metrics(stats);
-        
-        self.add_latency_metrics(stats);
-        
-        self.add_performance_breakdown(stats);
-        
-        self.add_recent_performance(stats);
-        
-        self.add_poor_performance_tracking(stats);
-        
-        self.add_footer(stats);
-    }
-
-    fn add_header(&mut self, vote_account: &str) {
-        self.output_buffer.push_str("═══════════════════════════════════════════════════════════════\n");
-        self.output_buffer.push_str("performance monitor\n");
-        self.output_buffer.push_str(&format!("vote account: {}\n", vote_account));
-        self.output_buffer.push_str("═══════════════════════════════════════════════════════════════\n\n");
-    }
-
-    fn add_session_overview(&mut self, stats: &PerformanceStats) {
-        let uptime = format_duration(stats.session_start.elapsed());
-        let vote_rate = stats.calculate_vote_rate();
-        
-        self.output_buffer.push_str(&format!(
-            "current slot: {:>12}      session Uptime: {:>15}\n",
-            stats.current_finalized_slot(), uptime
-        ));
-        self.output_buffer.push_str(&format!(
-            "total votes: {:>13}      vote rate: {:>8.3} votes/sec\n\n",
-            stats.total_transactions(), vote_rate
-        ));
+    /// restore the terminal - before exiting
+    pub fn cleanup(&mut self) -> Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+
+        restore_terminal()
+            .map_err(|e| VoteMonitorError::Dashboard(format!("failed to restore terminal: {}", e)))
     }
+}
 
-    fn add_tvc_performance_chart(&mut self, recent_votes: &VecDeque<ConfirmedVote>) {
-        self.output_buffer.push_str("tvc performance (last 20 votes)\n");
-        
-        let chart_lines = create_tvc_chart(recent_votes);
-        for line in chart_lines {
-            self.output_buffer.push_str(&line);
-            self.output_buffer.push('\n');
+/// the text of one dashboard panel, see `DashboardRenderer::build_dashboard_panels`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Panel {
+    /// stable name, e.g. `recent_votes`
+    pub name: &'static str,
+    /// title on the top border, empty for the footer
+    pub title: String,
+    /// lines inside the border without blank lines around them or trailing
+    /// spaces, not cut to the panel width, then the bottom border title if
+    /// there is one
+    pub lines: Vec<String>,
+}
+
+/// the panels as plain text, each a title line followed by its indented lines
+pub fn compose_panels(panels: &[Panel]) -> String {
+    let mut text = String::new();
+    for panel in panels {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(if panel.title.is_empty() { panel.name } else { &panel.title });
+        text.push('\n');
+        for line in &panel.lines {
+            text.push_str("  ");
+            text.push_str(line);
+            text.push('\n');
         }
-        self.output_buffer.push('\n');
     }
+    text
+}
 
-    fn add_efficiency_metrics(&mut self, stats: &PerformanceStats) {
-        let efficiency = stats.calculate_efficiency();
-        let missed_credits = stats.calculate_missed_credits();
-        
-        self.output_buffer.push_str("tvc efficiency\n");
-        self.output_buffer.push_str(&format!(
-            "   earned:  {:>8} credits   possible: {:>8} credits\n",
-            stats.total_tvc_earned(), 
-            stats.total_tvc_possible()
-        ));
-        self.output_buffer.push_str(&format!(
-            "   missed:  {:>8} credits   efficiency: {:>6.1}%\n\n",
-            missed_credits, 
-            efficiency
-        ));
+/// one panel of a frame, built from the stats before anything is drawn.
+/// the terminal draws it, `text` gives the plain `Panel` of the http api
+struct PanelContent {
+    name: &'static str,
+    area: Rect,
+    /// top border title, no border at all without one
+    title: Option<String>,
+    bottom_title: Option<String>,
+    body: PanelBody,
+}
+
+enum PanelBody {
+    Lines(Vec<Line<'static>>),
+    /// `label` on a bar filled to `ratio`
+    Gauge { ratio: f64, style: Style, label: String },
+    /// one bar per value, oldest first, scaled to `max`
+    Bars { bars: Vec<(u64, Style)>, max: u64 },
+    /// a single line in the middle of the area
+    Centered(Line<'static>),
+}
+
+impl PanelContent {
+    fn bordered(name: &'static str, area: Rect, title: impl Into<String>, body: PanelBody) -> Self {
+        Self { name, area, title: Some(title.into()), bottom_title: None, body }
     }
 
-    fn add_latency_metrics(&mut self, stats: &PerformanceStats) {
-        let session_avg_latency = stats.calculate_session_avg_latency();
-        let low_latency_percentage = stats.calculate_low_latency_percentage();
-        
-        self.output_buffer.push_str("vote latency metrics\n");
-        self.output_buffer.push_str(&format!(
-            "   session avg latency: {:>6.1} slots   low latency rate: {:>6.1}%\n",
-            session_avg_latency, low_latency_percentage
-        ));
-        self.output_buffer.push_str(&format!(
-            "   low latency votes:   {:>6} of {}   (≤2 slots)\n\n",
-            stats.low_latency_votes(), stats.total_transactions()
-        ));
+    fn plain(name: &'static str, area: Rect, body: PanelBody) -> Self {
+        Self { name, area, title: None, bottom_title: None, body }
     }
 
-    fn add_performance_breakdown(&mut self, stats: &PerformanceStats) {
-        let total_votes = stats.optimal_votes() + stats.good_votes() + stats.poor_votes();
-        
-        self.output_buffer.push_str("performance breakdown\n");
-        
-        if total_votes > 0 {
-            let optimal_pct = (stats.optimal_votes() as f64 / total_votes as f64) * 100.0;
-            let good_pct = (stats.good_votes() as f64 / total_votes as f64) * 100.0;
-            let poor_pct = (stats.poor_votes() as f64 / total_votes as f64) * 100.0;
-            
-            self.output_buffer.push_str(&format!(
-                "   🟩 optimal (16 TVC):    {:>4} votes ({:>4.1}%)\n",
-                stats.optimal_votes(), optimal_pct
-            ));
-            self.output_buffer.push_str(&format!(
-                "   🟨 good (12-15 TVC):    {:>4} votes ({:>4.1}%)\n",
-                stats.good_votes(), good_pct
-            ));
-            self.output_buffer.push_str(&format!(
-                "   🟥 poor (<12 TVC):      {:>4} votes ({:>4.1}%)\n",
-                stats.poor_votes(), poor_pct
-            ));
-        } else {
-            self.output_buffer.push_str("   waiting for votes...\n");
-        }
-        self.output_buffer.push('\n');
+    fn with_bottom_title(mut self, title: String) -> Self {
+        self.bottom_title = Some(title).filter(|title| !title.is_empty());
+        self
     }
 
-    fn add_recent_performance(&mut self, stats: &PerformanceStats) {
-        self.output_buffer.push_str("recent performance (last 30 votes)\n");
-        
-        let recent_votes: Vec<_> = stats.recent_confirmed_votes
-            .iter()
-            .rev()
-            .take(30)
-            .collect();
-        
-        if recent_votes.is_empty() {
-            self.output_buffer.push_str("   waiting for confirmed votes...\n");
-        } else {
-            for vote in recent_votes.iter().take(10) { // show top 10 for space
-                let performance_icon = match vote.tvc_credits {
-                    16 => "🟩",
-                    12..=15 => "🟨", 
-                    _ => "🟥",
-                };
-                
-                let tvc_lost = 16u64.saturating_sub(vote.tvc_credits);
-                let loss_text = if tvc_lost > 0 {
-                    format!("(-{})", tvc_lost)
-                } else {
-                    "✅".to_string()
-                };
-                
-                self.output_buffer.push_str(&format!(
-                    "   {} slot {:>9} -> lat:{:>2} -> {:>2} tvc {} | tx: https://solscan.io/tx/{} \n",
-                    performance_icon,
-                    vote.voted_slot,
-                    vote.latency,
-                    vote.tvc_credits,
-                    loss_text,
-                    vote.signature
-                ));
+    fn draw(self, frame: &mut Frame, theme: &Theme) {
+        let block = self.title.map(|title| {
+            let block = theme.block().title(title);
+            match self.bottom_title {
+                Some(bottom) => block.title(Title::from(bottom).position(Position::Bottom)),
+                None => block,
+            }
+        });
+        match self.body {
+            PanelBody::Lines(lines) => {
+                let mut paragraph = Paragraph::new(lines);
+                if let Some(block) = block {
+                    paragraph = paragraph.block(block);
+                }
+                frame.render_widget(paragraph, self.area);
+            }
+            PanelBody::Gauge { ratio, style, label } => {
+                let mut gauge = Gauge::default().gauge_style(style).ratio(ratio).label(label);
+                if let Some(block) = block {
+                    gauge = gauge.block(block);
+                }
+                frame.render_widget(gauge, self.area);
+            }
+            PanelBody::Bars { bars, max } => {
+                let bars: Vec<Bar> = bars
+                    .into_iter()
+                    .map(|(value, style)| Bar::default().value(value).text_value(String::new()).style(style))
+                    .collect();
+                let mut chart = BarChart::default()
+                    .bar_set(theme.bar_set())
+                    .data(BarGroup::default().bars(&bars))
+                    .bar_width(1)
+                    .bar_gap(1)
+                    .max(max);
+                if let Some(block) = block {
+                    chart = chart.block(block);
+                }
+                frame.render_widget(chart, self.area);
+            }
+            PanelBody::Centered(line) => {
+                let area = self.area;
+                let row = Rect { y: area.y + area.height / 2, height: area.height.min(1), ..area };
+                frame.render_widget(Paragraph::new(line).alignment(Alignment::Center), row);
             }
-            
-            let total_recent = recent_votes.len() as f64;
-            let avg_recent_latency = recent_votes.iter().map(|v| v.latency).sum::<u64>() as f64 / total_recent;
-            let total_tvc_lost: u64 = recent_votes.iter().map(|v| 16u64.saturating_sub(v.tvc_credits)).sum();
-            let optimal_count = recent_votes.iter().filter(|v| v.tvc_credits == 16).count();
-            let optimal_percentage = (optimal_count as f64 / total_recent) * 100.0;
-            
-            self.output_buffer.push_str(&format!(
-                "\n   recent summary: avg latency {:.1}, {} tvc lost, {:.1}% optimal ({}/{})\n",
-                avg_recent_latency, total_tvc_lost, optimal_percentage, optimal_count, recent_votes.len()
-            ));
         }
-        self.output_buffer.push('\n');
     }
 
-    fn add_poor_performance_tracking(&mut self, stats: &PerformanceStats) {
-        self.output_buffer.push_str("poor performance events (< 16 tvc)\n");
-        
-        let poor_votes: Vec<_> = stats.session_poor_votes
-            .iter()
-            .rev()
-            .take(15)
-            .collect();
-        
-        if poor_votes.is_empty() {
-            self.output_buffer.push_str("   no poor performance votes in session\n");
-        } else {
-            for vote in poor_votes {
-                let severity = match vote.tvc_credits {
-                    12..=15 => "🟨",
-                    8..=11 => "🟧", 
-                    4..=7 => "🟥",
-                    _ => "💀",
-                };
-                
-                self.output_buffer.push_str(&format!(
-                    "   {} slot {:>9} -> lat:{:>2} -> {:>2} tvc | tx: https://solscan.io/tx/{} \n",
-                    severity,
-                    vote.voted_slot,
-                    vote.latency,
-                    vote.tvc_credits,
-                    vote.signature
-                ));
+    /// the panel without styles, lines are not cut to the area
+    fn text(&self) -> Panel {
+        let text = |line: &Line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>().trim_end().to_string();
+        let mut lines: Vec<String> = match &self.body {
+            PanelBody::Lines(lines) => lines.iter().map(text).collect(),
+            PanelBody::Gauge { label, .. } => vec![label.clone()],
+            PanelBody::Bars { bars, .. } if bars.is_empty() => Vec::new(),
+            PanelBody::Bars { bars, .. } => {
+                vec![bars.iter().map(|(value, _)| value.to_string()).collect::<Vec<_>>().join(" ")]
             }
+            PanelBody::Centered(line) => vec![text(line)],
+        };
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
         }
-        self.output_buffer.push('\n');
+        let blank = lines.iter().take_while(|line| line.is_empty()).count();
+        lines.drain(..blank);
+        if let Some(bottom) = &self.bottom_title {
+            lines.push(bottom.trim().to_string());
+        }
+        Panel { name: self.name, title: self.title.clone().unwrap_or_default(), lines }
     }
+}
+
+/// table rows as lines, the way `Table` lays them out: cells cut or padded
+/// to their `Length` column and one space between columns, the `Min`
+/// column takes what is left of `width`
+fn table_lines(
+    header: impl IntoIterator<Item = &'static str>,
+    rows: Vec<(Vec<String>, Style)>,
+    columns: &[Constraint],
+    width: u16,
+) -> Vec<Line<'static>> {
+    let rest = signature_width(Rect::new(0, 0, width, 0), columns);
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|column| match column {
+            Constraint::Length(width) => usize::from(*width),
+            _ => rest,
+        })
+        .collect();
+    // a row style covers the whole row, as with `Table`
+    let inner = usize::from(width.saturating_sub(2));
+    let line = |cells: Vec<String>, style: Style| {
+        let cells: Vec<String> = cells.iter().zip(&widths).map(|(cell, &width)| fit_cell(cell, width)).collect();
+        let mut text = cells.join(" ");
+        let used = Span::raw(text.as_str()).width();
+        text.push_str(&" ".repeat(inner.saturating_sub(used)));
+        Line::styled(text, style)
+    };
 
-    fn add_footer(&mut self, stats: &PerformanceStats) {
-        let (status_text, _status_color) = stats.get_performance_status();
-        
-        self.output_buffer.push_str(&format!("status: {} performance\n", status_text));
-        self.output_buffer.push_str("═══════════════════════════════════════════════════════════════\n");
-        self.output_buffer.push_str("press ctrl+c to quit\n");
+    let mut lines = vec![line(header.into_iter().map(str::to_string).collect(), Style::default().add_modifier(Modifier::BOLD))];
+    lines.extend(rows.into_iter().map(|(cells, style)| line(cells, style)));
+    lines
+}
+
+/// `text` cut or padded with spaces to `width` cells
+fn fit_cell(text: &str, width: usize) -> String {
+    let mut cell = String::with_capacity(width);
+    let mut used = 0;
+    for c in text.chars() {
+        let cells = Span::raw(c.to_string()).width();
+        if used + cells > width {
+            break;
+        }
+        used += cells;
+        cell.push(c);
     }
+    cell.push_str(&" ".repeat(width - used));
+    cell
 }
 
 impl Drop for DashboardRenderer {
@@ -373,113 +699,1688 @@ impl Drop for DashboardRenderer {
     }
 }
 
-fn create_tvc_chart(recent_votes: &VecDeque<ConfirmedVote>) -> Vec<String> {
-    const BAR_HEIGHT: usize = 4;
-    const BAR_WIDTH: usize = 20;
-    
-    // tvc with padding
-    let mut tvc_values = Vec::with_capacity(BAR_WIDTH);
-    let last_votes: Vec<_> = recent_votes.iter().rev().take(BAR_WIDTH).collect();
-    
-    for vote in last_votes.iter().rev() {
-        tvc_values.push(vote.tvc_credits);
-    }
-    
-    // pad with zeros if we have fewer votes
-    while tvc_values.len() < BAR_WIDTH {
-        tvc_values.insert(0, 0);
-    }
-    
-    let mut chart_lines = Vec::with_capacity(BAR_HEIGHT + 2);
-    
-    // build chart from top to bottom - static strings
-    for level in (1..=BAR_HEIGHT).rev() {
-        let mut line = String::with_capacity(64);
-        line.push_str(&format!("{:2} |", level * 4));
-        
-        for &tvc in &tvc_values {
-            let bar_height = match tvc {
-                0 => 0,
-                1..=4 => 1,
-                5..=8 => 2,
-                9..=12 => 3,
-                13..=16 => 4,
-                _ => 4,
-            };
-            
-            if bar_height >= level {
-                let bar_char = match tvc {
-                    16 => "\x1b[32m▓\x1b[0m",      // full performance - green
-                    12..=15 => "\x1b[38;5;208m▓\x1b[0m", // good performance - orange
-                    _ => "\x1b[31m▓\x1b[0m",       // poor performance - red
-                };
-                line.push(' ');
-                line.push_str(bar_char);
+/// no-op unless `DashboardRenderer::new` set the terminal up
+/// raw mode and the alternate screen, undone by `restore_terminal`
+fn enter_terminal() -> Result<()> {
+    TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
+    enable_raw_mode()
+        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to enable raw mode: {}", e)))?;
+    execute!(io::stdout(), EnterAlternateScreen, Hide)
+        .map_err(|e| VoteMonitorError::Dashboard(format!("failed to enter alternate screen: {}", e)))
+}
+
+fn restore_terminal() -> io::Result<()> {
+    if !TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, ResetColor, Show)?;
+    io::stdout().flush()
+}
+
+/// restore the terminal before the default hook prints the panic message
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal();
+            previous(info);
+        }));
+    });
+}
+
+/// what a paused view draws, see `DashboardRenderer::render`
+struct Frozen {
+    stats: Box<PerformanceStats>,
+    mark: PauseMark,
+    // confirmed votes of the live stats when the view paused
+    votes: u64,
+}
+
+/// `PAUSED at 14:32:07 (live: +43 votes behind)` in the header
+#[derive(Debug, Clone, Copy)]
+struct PauseMark {
+    at: DateTime<Local>,
+    // votes confirmed since the view paused
+    behind: u64,
+}
+
+/// renderer state a frame is drawn with
+struct View<'a> {
+    paused: Option<PauseMark>,
+    notice: Option<&'a str>,
+    scroll: &'a mut usize,
+    selected: &'a mut Option<usize>,
+    selected_signature: &'a mut Option<Arc<String>>,
+    theme: &'a Theme,
+    poor_filter: PoorEventFilter,
+    show_process: bool,
+    show_debug: bool,
+    histogram_scale: HistogramScale,
+    signature_display: SignatureDisplay,
+    explorer: &'a Explorer,
+}
+
+impl<'a> View<'a> {
+    /// the view of a fresh dashboard, for frames drawn without a terminal
+    fn offscreen(
+        theme: &'a Theme,
+        explorer: &'a Explorer,
+        scroll: &'a mut usize,
+        selected: &'a mut Option<usize>,
+        selected_signature: &'a mut Option<Arc<String>>,
+    ) -> Self {
+        Self {
+            paused: None,
+            notice: None,
+            scroll,
+            selected,
+            selected_signature,
+            theme,
+            poor_filter: PoorEventFilter::default(),
+            show_process: true,
+            show_debug: false,
+            histogram_scale: HistogramScale::default(),
+            signature_display: SignatureDisplay::default(),
+            explorer,
+        }
+    }
+}
+
+fn draw_dashboard(
+    frame: &mut Frame,
+    stats: &PerformanceStats,
+    vote_account: &str,
+    memory: &MemoryUsage,
+    view: &mut View,
+) {
+    for panel in dashboard_panels(stats, vote_account, memory, frame.size(), view) {
+        panel.draw(frame, view.theme);
+    }
+}
+
+/// the panels of an `area` sized frame, laid out top to bottom
+fn dashboard_panels(
+    stats: &PerformanceStats,
+    vote_account: &str,
+    memory: &MemoryUsage,
+    area: Rect,
+    view: &mut View,
+) -> Vec<PanelContent> {
+    let theme = view.theme;
+    if is_too_small(area) {
+        return vec![too_small_panel(area, theme)];
+    }
+    let mut header = header_lines(stats, vote_account, theme);
+    if let Some(mark) = view.paused {
+        header.insert(0, pause_line(mark, theme));
+    }
+    let show_chart = area.height >= MIN_CHART_HEIGHT;
+    let show_details = area.height >= MIN_DETAIL_HEIGHT;
+    // one row per credit value that occurred this session
+    let histogram_rows = Some(stats.tvc_histogram().nonzero().count()).filter(|&rows| show_details && rows > 0);
+    // one row per cause that lost credits this session
+    let lost_credit_rows = Some(stats.lost_credits().ranked().len()).filter(|&rows| show_details && rows > 0);
+    let show_epochs = show_details && stats.epochs.epochs().nth(1).is_some();
+    // only with rpc_url, see LeaderAttribution
+    let worst_leaders = match stats.leader_attribution {
+        Some(ref attribution) if show_details => Some(attribution.worst_leaders(WORST_LEADER_ROWS)),
+        _ => None,
+    };
+    // only with rpc_url, once the first snapshot is in
+    let cluster = stats.cluster.as_ref().filter(|_| show_details);
+    // only with rpc_url and a known identity
+    let block_production = stats.block_production().filter(|_| show_details);
+    let poor_rows = stats.session_poor_votes
+        .iter()
+        .filter(|vote| view.poor_filter.matches(vote, &stats.tvc))
+        .count()
+        .clamp(1, MAX_POOR_EVENT_ROWS);
+
+    let mut constraints = vec![
+        Constraint::Length(header.len() as u16 + 2),
+        Constraint::Length(3),
+    ];
+    if show_chart {
+        constraints.push(Constraint::Length(3));
+        constraints.push(Constraint::Length(8));
+    }
+    if let Some(rows) = histogram_rows {
+        constraints.push(Constraint::Length(rows as u16 + 2));
+    }
+    if let Some(rows) = lost_credit_rows {
+        constraints.push(Constraint::Length(rows as u16 + 3));
+    }
+    if show_details {
+        constraints.push(Constraint::Length(detail_height(memory)));
+    }
+    if show_epochs {
+        constraints.push(Constraint::Length(stats.epochs.epochs().count() as u16 + 3));
+    }
+    if cluster.is_some() {
+        constraints.push(Constraint::Length(4));
+    }
+    if block_production.is_some() {
+        constraints.push(Constraint::Length(4));
+    }
+    if let Some(ref leaders) = worst_leaders {
+        constraints.push(Constraint::Length(leaders.len().max(1) as u16 + 3));
+    }
+    constraints.push(Constraint::Min(5));
+    constraints.push(Constraint::Length(poor_rows as u16 + 3));
+    let stream_line = stats.pipeline.is_some() || stats.stream_health.is_some();
+    let process_line = view.show_process && stats.process.is_some();
+    let debug_line = view.show_debug && stats.tracker.is_some();
+    constraints.push(Constraint::Length(2 + stream_line as u16 + process_line as u16 + debug_line as u16));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+    let mut chunks = chunks.iter().copied();
+    let mut next = || chunks.next().unwrap_or_default();
+    let mut panels = Vec::with_capacity(16);
+
+    panels.push(PanelContent::bordered("header", next(), "performance monitor", PanelBody::Lines(header)));
+    panels.push(efficiency_panel(next(), stats, theme));
+    if show_chart {
+        panels.push(efficiency_trend_panel(next(), stats, theme));
+        panels.push(tvc_chart_panel(next(), stats, theme));
+    }
+    if histogram_rows.is_some() {
+        panels.push(tvc_histogram_panel(next(), stats, view.histogram_scale, theme));
+    }
+    if lost_credit_rows.is_some() {
+        panels.push(lost_credits_panel(next(), stats));
+    }
+    if show_details {
+        panels.extend(detail_panels(next(), stats, memory, theme));
+    }
+    if show_epochs {
+        panels.push(epoch_history_panel(next(), stats));
+    }
+    if let Some(cluster) = cluster {
+        panels.push(cluster_comparison_panel(next(), stats, cluster, theme));
+    }
+    if let Some(production) = block_production {
+        panels.push(block_production_panel(next(), production, theme));
+    }
+    if let Some(leaders) = worst_leaders {
+        panels.push(worst_leaders_panel(next(), stats, leaders));
+    }
+    panels.push(recent_votes_panel(next(), stats, view));
+    panels.push(poor_events_panel(next(), stats, view));
+    panels.push(footer_panel(next(), stats, view));
+    panels
+}
+
+fn is_too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
+
+/// a single centered line in place of a layout that would not fit
+fn too_small_panel(area: Rect, theme: &Theme) -> PanelContent {
+    let message = format!("terminal too small: need {}x{}, have {}x{}", MIN_WIDTH, MIN_HEIGHT, area.width, area.height);
+    let line = Line::styled(
+        truncate_visible(&message, area.width as usize, theme.text("…", "...")),
+        theme.fg(Color::Yellow),
+    );
+    PanelContent::plain("too_small", area, PanelBody::Centered(line))
+}
+
+fn header_lines(stats: &PerformanceStats, vote_account: &str, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = Vec::with_capacity(8);
+    match (&stats.account_info, &stats.vote_account_info) {
+        (Some(account), _) => {
+            lines.push(Line::from(format!("vote account: {}   identity: {}", vote_account, account.identity)));
+            let status = if account.delinquent {
+                Span::styled("delinquent", theme.fg(Color::Red).add_modifier(Modifier::BOLD))
             } else {
-                line.push_str("  ");
+                Span::styled("active", theme.fg(Color::Green))
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!(
+                    "commission: {}%   activated stake: {} SOL   status: ",
+                    account.commission,
+                    format_number(account.activated_stake_sol().round() as u64)
+                )),
+                status,
+            ]));
+        }
+        (None, Some(info)) => lines.push(Line::from(format!(
+            "vote account: {}   identity: {}   commission: {}%",
+            vote_account, info.identity, info.commission
+        ))),
+        (None, None) => lines.push(Line::from(format!("vote account: {}", vote_account))),
+    }
+
+    let voter = &stats.authorized_voter;
+    if let Some(current) = voter.current {
+        lines.push(Line::from(match voter.changed_at_slot {
+            Some(changed_at) => format!(
+                "authorized voter: {} (changed at slot {}, {} pre-change votes)",
+                current, changed_at, stats.pre_voter_change_votes.load(Ordering::Relaxed)
+            ),
+            None => format!("authorized voter: {}", current),
+        }));
+    }
+
+    lines.push(Line::from(format!(
+        "current slot: {}   uptime: {}   votes: {} ({:.3}/sec of {:.3})   missed: {}",
+        stats.current_finalized_slot(),
+        session_uptime(stats),
+        format_number(stats.total_transactions()),
+        stats.calculate_vote_rate(),
+        stats.expected_vote_rate(),
+        stats.missed_votes()
+    )));
+
+    let source_style = match stats.confirmation_source {
+        ConfirmationSource::Block => Style::default(),
+        ConfirmationSource::SlotStatus => theme.fg(Color::Yellow),
+        ConfirmationSource::VoteState => theme.fg(Color::Red).add_modifier(Modifier::BOLD),
+    };
+    let mut source = Line::from(vec![
+        Span::raw("confirmation source: "),
+        Span::styled(stats.confirmation_source.as_str(), source_style),
+    ]);
+    match stats.confirmation_source {
+        ConfirmationSource::Block => {}
+        ConfirmationSource::SlotStatus => source.spans.push(Span::styled(
+            " - latency from the transaction slot, a vote that landed on a dropped fork still counts. not exact tvc accounting",
+            source_style,
+        )),
+        ConfirmationSource::VoteState => source.spans.push(Span::styled(
+            " - degraded: no grpc stream, votes read from the vote account every few seconds. no signatures, missed votes undetected",
+            source_style,
+        )),
+    }
+    if stats.network != Network::Mainnet {
+        source.spans.push(Span::raw(format!("   network: {}", stats.network.as_str())));
+    }
+    lines.push(source);
+
+    let mut tip = cluster_tip_line(stats, theme);
+    tip.spans.extend(tower_root_spans(stats, theme));
+    lines.push(tip);
+    lines.push(coverage_line(stats, theme));
+    // rpc polling sees no vote transactions
+    if stats.confirmation_source != ConfirmationSource::VoteState {
+        lines.push(votes_sent_line(stats, theme));
+    }
+    lines.push(failed_votes_line(stats, theme));
+
+    if let Some(ref drift) = stats.credits_drift {
+        let drift_style = if drift.exceeded {
+            theme.fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::raw("credits drift: "),
+            Span::styled(
+                format!(
+                    "local {} vs on-chain {} over {}s ({:+.1}%)",
+                    drift.local_credits, drift.on_chain_credits, drift.window_secs, drift.drift_pct
+                ),
+                drift_style,
+            ),
+        ]));
+    }
+
+    if let Some(epoch) = stats.epochs.current() {
+        lines.push(Line::from(format!(
+            "epoch: {}   progress: {:.1}%   epoch efficiency: {:.1}% (session {:.1}%)",
+            epoch.epoch,
+            stats.epochs.progress(stats.current_finalized_slot()),
+            epoch.efficiency(),
+            stats.calculate_efficiency()
+        )));
+    }
+
+    lines
+}
+
+fn cluster_tip_line(stats: &PerformanceStats, theme: &Theme) -> Line<'static> {
+    let tip = &stats.cluster_tip;
+    let (Some(processed), Some(finalized), Some(lag)) = (tip.processed, tip.finalized, tip.finalized_lag()) else {
+        return Line::from("tip: waiting for slot updates");
+    };
+    let lag_style = if tip.lagging {
+        theme.fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    Line::from(vec![
+        Span::raw(format!("tip: {} / finalized: {} ", processed, finalized)),
+        Span::styled(format!("(lag: {} slots)", lag), lag_style),
+    ])
+}
+
+/// `root: <slot> (lagging voted slot by N)`, nothing before the first tower update
+fn tower_root_spans(stats: &PerformanceStats, theme: &Theme) -> Vec<Span<'static>> {
+    let root = &stats.tower_root;
+    let (Some(slot), Some(lag)) = (root.root(), root.lag()) else {
+        return Vec::new();
+    };
+    let lag_style = if root.is_lagging() {
+        theme.fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    vec![
+        Span::raw(format!("   root: {} ", slot)),
+        Span::styled(format!("(lagging voted slot by {})", lag), lag_style),
+    ]
+}
+
+fn coverage_line(stats: &PerformanceStats, theme: &Theme) -> Line<'static> {
+    let summary = stats.coverage.summary();
+    if summary.finalized_slots == 0 {
+        return Line::from("vote coverage: waiting for finalized blocks");
+    }
+
+    let coverage_style = if stats.coverage.gap_alerted() {
+        theme.fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    let mut spans = vec![
+        Span::raw("vote coverage: "),
+        Span::styled(
+            format!("{:.1}% ({}/{} finalized slots)", summary.percentage(), summary.voted_slots, summary.finalized_slots),
+            coverage_style,
+        ),
+    ];
+    if stats.coverage.gap_alerted() {
+        if let Some(gap) = stats.coverage.current_gap() {
+            spans.push(Span::styled(
+                format!("   no votes for {} slots since slot {}", gap.slots, gap.first_slot),
+                coverage_style,
+            ));
+        }
+    }
+    Line::from(spans)
+}
+
+/// when the view paused and how far the live stats moved on since
+fn pause_line(mark: PauseMark, theme: &Theme) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!("PAUSED at {}", mark.at.format("%H:%M:%S")),
+            theme.fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(" (live: +{} votes behind)   space resumes", format_number(mark.behind)),
+            theme.fg(Color::Yellow),
+        ),
+    ])
+}
+
+/// vote transactions sent per minute, one level character per minute
+/// scaled to the busiest one, with a warning while the validator is silent
+fn votes_sent_line(stats: &PerformanceStats, theme: &Theme) -> Line<'static> {
+    let levels = theme.levels();
+    let sent = &stats.votes_sent;
+    let minutes: Vec<u64> = sent.per_minute().rev().take(VOTES_SENT_MINUTES).rev().collect();
+    let busiest = minutes.iter().copied().max().unwrap_or_default().max(1);
+    let sparkline: String = minutes
+        .iter()
+        .map(|&count| match count {
+            0 => theme.empty_level().to_string(),
+            count => levels[(count * (levels.len() as u64 - 1)).div_ceil(busiest) as usize].to_string(),
+        })
+        .collect();
+
+    let mut spans = vec![
+        Span::raw("votes sent/min: "),
+        Span::styled(sparkline, theme.fg(Color::Cyan)),
+        Span::raw(format!(" now {} (last {}m)", sent.current_minute(), minutes.len())),
+    ];
+    if let Some(quiet) = sent.silence() {
+        spans.push(Span::styled(
+            format!("   no vote sent for {}s while blocks arrive", quiet.as_secs()),
+            theme.fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    Line::from(spans)
+}
+
+fn failed_votes_line(stats: &PerformanceStats, theme: &Theme) -> Line<'static> {
+    let failed_votes = stats.failed_votes();
+    if failed_votes == 0 {
+        return Line::from("failed vote txs: 0");
+    }
+
+    let failed_style = theme.fg(Color::Red).add_modifier(Modifier::BOLD);
+    let mut spans = vec![
+        Span::raw("failed vote txs: "),
+        Span::styled(failed_votes.to_string(), failed_style),
+    ];
+    if let Some(last) = stats.recent_failed_votes.back() {
+        spans.push(Span::styled(
+            format!("   last at slot {}: {}", last.transaction_slot, last.error),
+            theme.fg(Color::Red),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// session uptime, plus the process uptime when the session was restored
+fn session_uptime(stats: &PerformanceStats) -> String {
+    let session = stats.session_start.elapsed();
+    let process = stats.process_start.elapsed();
+    if session.saturating_sub(process).as_secs() < 1 {
+        format_duration(session)
+    } else {
+        format!("{} (process {})", format_duration(session), format_duration(process))
+    }
+}
+
+fn efficiency_panel(area: Rect, stats: &PerformanceStats, theme: &Theme) -> PanelContent {
+    let efficiency = stats.calculate_efficiency();
+    let (_, status_color) = stats.get_performance_status();
+
+    let ratio = (efficiency / 100.0).clamp(0.0, 1.0);
+    let label = format!(
+        "{:.1}%   earned {} / possible {}   missed {} credits   {}",
+        efficiency,
+        stats.total_tvc_earned(),
+        stats.total_tvc_possible(),
+        stats.calculate_missed_credits(),
+        format_rolling_efficiency(stats)
+    );
+    let title = "tvc efficiency";
+
+    // ratatui's gauge always fills with block characters
+    if theme.is_ascii() {
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let mut spans = Vec::with_capacity(3);
+        let bar_width = inner_width.saturating_sub(label.len() + 3);
+        if bar_width >= 10 {
+            let filled = (ratio * bar_width as f64).round() as usize;
+            spans.push(Span::styled(format!("[{}", "#".repeat(filled)), theme.fg(Color::from(status_color))));
+            spans.push(Span::raw(format!("{}] ", "-".repeat(bar_width - filled))));
+        }
+        spans.push(Span::raw(label));
+        return PanelContent::bordered("efficiency", area, title, PanelBody::Lines(vec![Line::from(spans)]));
+    }
+
+    let style = theme.fg(Color::from(status_color));
+    PanelContent::bordered("efficiency", area, title, PanelBody::Gauge { ratio, style, label })
+}
+
+/// "1m / 15m / 1h: 98.2% / 97.5% / 97.9%", a dash for a window without votes
+fn format_rolling_efficiency(stats: &PerformanceStats) -> String {
+    let names: Vec<&str> = ROLLING_WINDOWS.iter().map(|&(name, _)| name).collect();
+    let values: Vec<String> = ROLLING_WINDOWS
+        .iter()
+        .map(|&(_, window)| match stats.efficiency_over(window) {
+            Some(efficiency) => format!("{:.1}%", efficiency),
+            None => "-".to_string(),
+        })
+        .collect();
+    format!("{}: {}", names.join(" / "), values.join(" / "))
+}
+
+/// one level character per trend bucket, scaled between the lowest bucket and 100%
+fn efficiency_trend_panel(area: Rect, stats: &PerformanceStats, theme: &Theme) -> PanelContent {
+    let levels = theme.levels();
+
+    let trend = &stats.efficiency_trend;
+    let series: Vec<Option<f64>> = trend.buckets().map(|bucket| bucket.efficiency()).collect();
+    let current = series.last().copied().flatten();
+    let lowest = series.iter().flatten().copied().reduce(f64::min);
+
+    let summary = match (current, lowest) {
+        (Some(current), Some(lowest)) => format!("  now {:.1}%  low {:.1}%", current, lowest),
+        (None, Some(lowest)) => format!("  now -  low {:.1}%", lowest),
+        _ => "  no votes yet".to_string(),
+    };
+    let width = (area.width.saturating_sub(2) as usize).saturating_sub(summary.chars().count());
+
+    // floor to a multiple of 5 so a steady 100% still draws full bars
+    let floor = lowest.map_or(0.0, |lowest| ((lowest / 5.0).floor() * 5.0).min(95.0));
+    let mut spans: Vec<Span> = series[series.len().saturating_sub(width)..]
+        .iter()
+        .map(|efficiency| match *efficiency {
+            Some(efficiency) => {
+                let level = ((efficiency - floor) / (100.0 - floor) * (levels.len() - 1) as f64).round();
+                let (_, color) = performance_status_for(efficiency, &stats.thresholds);
+                Span::styled(
+                    levels[(level.max(0.0) as usize).min(levels.len() - 1)].to_string(),
+                    theme.fg(Color::from(color)),
+                )
+            }
+            None => Span::styled(theme.empty_level(), theme.fg(Color::DarkGray)),
+        })
+        .collect();
+    spans.push(Span::raw(summary));
+
+    let title = format!(
+        "efficiency trend ({} buckets, last {})",
+        format_duration(trend.bucket_duration()),
+        format_duration(trend.bucket_duration() * series.len() as u32)
+    );
+    PanelContent::bordered("efficiency_trend", area, title, PanelBody::Lines(vec![Line::from(spans)]))
+}
+
+fn tvc_chart_panel(area: Rect, stats: &PerformanceStats, theme: &Theme) -> PanelContent {
+    // one column per bar plus a one column gap
+    let capacity = (area.width.saturating_sub(2) / 2) as usize;
+    let votes: Vec<&ConfirmedVote> = stats.recent_confirmed_votes
+        .latest(capacity)
+        .collect();
+
+    let bars = votes
+        .iter()
+        .rev()
+        .map(|vote| (vote.tvc_credits, theme.fg(tvc_color(vote.tvc_credits, &stats.tvc))))
+        .collect();
+
+    let title = format!("tvc performance (last {} votes)", votes.len());
+    PanelContent::bordered("tvc_chart", area, title, PanelBody::Bars { bars, max: stats.tvc.max_credits_per_slot })
+}
+
+/// every vote this session by earned credits, one row per credit value with votes
+fn tvc_histogram_panel(area: Rect, stats: &PerformanceStats, scale: HistogramScale, theme: &Theme) -> PanelContent {
+    let histogram = stats.tvc_histogram();
+    let total = histogram.total();
+    let max = histogram.counts.iter().copied().max().unwrap_or_default();
+    let counts: Vec<String> = histogram.nonzero().map(|(_, count)| format_number(count)).collect();
+    let count_width = counts.iter().map(String::len).max().unwrap_or_default();
+    // "16 tvc " before the bar, " 96.1% 12,345" after it
+    let bar_width = usize::from(area.width.saturating_sub(2)).saturating_sub(7 + 8 + count_width);
+    let bar = theme.bar_set().full;
+
+    let lines: Vec<Line<'static>> = histogram
+        .nonzero()
+        .zip(counts)
+        .map(|((credits, count), label)| {
+            let len = scale.bar_len(count, max, bar_width);
+            Line::from(vec![
+                Span::raw(format!("{:>2} tvc ", credits)),
+                Span::styled(bar.repeat(len), theme.fg(tvc_color(credits, &stats.tvc))),
+                Span::raw(format!(
+                    "{} {:>5.1}% {:>width$}",
+                    " ".repeat(bar_width - len), count as f64 / total as f64 * 100.0, label, width = count_width
+                )),
+            ])
+        })
+        .collect();
+
+    let scale_label = match scale {
+        HistogramScale::Linear => "",
+        HistogramScale::Log => ", log scale",
+    };
+    let title = format!("tvc distribution (session, {} votes{})", format_number(total), scale_label);
+    PanelContent::bordered("tvc_histogram", area, title, PanelBody::Lines(lines))
+}
+
+/// credits lost below max per cause, see `classify_loss`
+fn lost_credits_panel(area: Rect, stats: &PerformanceStats) -> PanelContent {
+    let lost = stats.lost_credits();
+    let total = lost.total_credits();
+    let rows = lost
+        .ranked()
+        .into_iter()
+        .map(|(cause, tally)| {
+            let share = if total == 0 { 0.0 } else { tally.credits as f64 / total as f64 * 100.0 };
+            let cells = vec![
+                cause.label().to_string(),
+                format_number(tally.votes),
+                format_number(tally.credits),
+                format!("{:.1}%", share),
+            ];
+            (cells, Style::default())
+        })
+        .collect();
+
+    let title = format!("credits lost by cause (session, {} credits)", format_number(total));
+    let lines = table_lines(
+        ["cause", "votes", "credits", "share"],
+        rows,
+        &[Constraint::Length(22), Constraint::Length(10), Constraint::Length(10), Constraint::Length(7)],
+        area.width,
+    );
+    PanelContent::bordered("lost_credits", area, title, PanelBody::Lines(lines))
+}
+
+fn detail_height(memory: &MemoryUsage) -> u16 {
+    // latency panel uses 9 lines, breakdown up to 8, diagnostics one per component
+    memory.components.len().max(9) as u16 + 2
+}
+
+/// the three columns of the details row
+fn detail_panels(area: Rect, stats: &PerformanceStats, memory: &MemoryUsage, theme: &Theme) -> [PanelContent; 3] {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+            Constraint::Percentage(40),
+        ])
+        .split(area);
+
+    let latency = vec![
+        Line::from(format!("session avg: {:.1} slots", stats.overall_session_avg_latency())),
+        Line::from(match stats.avg_latency_window.mean() {
+            Some(avg) => format!("avg latency ({}s): {:.2} slots", stats.latency_window().as_secs(), avg),
+            None => format!("avg latency ({}s): n/a", stats.latency_window().as_secs()),
+        }),
+        Line::from(match stats.non_leader_slot_avg_latency() {
+            Some(avg) => format!("outside leader slots: {:.1} slots", avg),
+            None => "outside leader slots: n/a".to_string(),
+        }),
+        Line::from(match stats.leader_slot_votes() {
+            Some(leader_slots) => match leader_slots.avg_latency() {
+                Some(avg) => format!("leader slots: {:.1} slots ({} votes)", avg, leader_slots.votes),
+                None => "leader slots: no votes".to_string(),
+            },
+            None => "leader slots: n/a".to_string(),
+        }),
+        Line::from(format!("low latency rate: {:.1}%", stats.calculate_low_latency_percentage())),
+        Line::from(format!(
+            "low latency votes: {} of {} ({}{} slots)",
+            stats.low_latency_votes(), stats.total_transactions(), theme.text("≤", "<="), stats.thresholds.low_latency_slots
+        )),
+        Line::from(match stats.latency_distribution().summary() {
+            Some(summary) => format!("exact: {}", summary),
+            None => "exact: n/a".to_string(),
+        }),
+        Line::from(format!(
+            "grace-dependent: {:.1}% (latency {})",
+            stats.latency_distribution().grace_dependent_pct(), stats.tvc.grace_slots
+        )),
+        Line::from(match (stats.recent_confirmation_ms_avg(), stats.recent_confirmation_ms_percentile(95.0)) {
+            (Some(avg), Some(p95)) => format!("confirmation: avg {:.0} ms / p95 {} ms", avg, p95),
+            _ => "confirmation: n/a".to_string(),
+        }),
+        Line::from(match stats.landing_offsets.distribution() {
+            Some([(_, plus_1), (_, plus_2), (_, more)]) => {
+                format!("landed +1: {:.0}% | +2: {:.0}% | +3+: {:.0}%", plus_1, plus_2, more)
             }
+            None => "landed: n/a".to_string(),
+        }),
+    ];
+    let total_votes = stats.confirmed_votes();
+    let breakdown = if total_votes > 0 {
+        let pct = |count: u64| (count as f64 / total_votes as f64) * 100.0;
+        let max = stats.tvc.max_credits_per_slot;
+        let good = stats.tvc.good_threshold();
+        let line = |level: TvcPerformanceLevel, label: String, count: u64, color: Color| {
+            Line::styled(
+                format!("{} {:<18} {:>5} ({:>4.1}%)", theme.marker_cell(level), label, count, pct(count)),
+                theme.fg(color),
+            )
+        };
+        let mut lines = vec![
+            line(TvcPerformanceLevel::Optimal, format!("optimal ({} TVC):", max), stats.optimal_votes(), Color::Green),
+            line(TvcPerformanceLevel::Good, format!("good ({}-{} TVC):", good, max - 1), stats.good_votes(), Color::Yellow),
+            line(TvcPerformanceLevel::Poor, format!("poor (<{} TVC):", good), stats.poor_votes(), Color::Red),
+            Line::from(""),
+            Line::from(format!("optimal streak: {}   best: {}", stats.streaks.current(), stats.streaks.best())),
+        ];
+        if let Some(broken) = stats.streaks.last_break() {
+            lines.push(Line::styled(
+                format!(
+                    "last broken: slot {} at {} ({} TVC)",
+                    broken.voted_slot, broken.timestamp.format("%H:%M:%S"), broken.tvc_credits
+                ),
+                theme.fg(Color::DarkGray),
+            ));
+            lines.push(Line::styled(
+                format!("  by {}", truncate_visible(&broken.signature, 20, theme.text("…", "..."))),
+                theme.fg(Color::DarkGray),
+            ));
+        }
+        lines
+    } else {
+        vec![Line::from("waiting for votes...")]
+    };
+    let diagnostics = memory.components
+        .iter()
+        .map(|component| Line::from(format!(
+            "{:<24} {:>7} {:>10}",
+            component.name, component.entries, format_bytes(component.bytes)
+        )))
+        .collect();
+    let diagnostics_title = format!("diagnostics (approx memory: {})", format_bytes(memory.total_bytes()));
+
+    [
+        PanelContent::bordered("latency", columns[0], "vote latency", PanelBody::Lines(latency)),
+        PanelContent::bordered("breakdown", columns[1], "performance breakdown", PanelBody::Lines(breakdown)),
+        PanelContent::bordered("diagnostics", columns[2], diagnostics_title, PanelBody::Lines(diagnostics)),
+    ]
+}
+
+fn epoch_history_panel(area: Rect, stats: &PerformanceStats) -> PanelContent {
+    let rows = stats.epochs
+        .epochs()
+        .map(|epoch| (
+            vec![
+                epoch.epoch.to_string(),
+                epoch.votes.to_string(),
+                format!("{:.1}%", epoch.efficiency()),
+                format!("{:.1}", epoch.avg_latency()),
+                format!("{} / {} / {}", epoch.optimal_votes, epoch.good_votes, epoch.poor_votes),
+            ],
+            Style::default(),
+        ))
+        .collect();
+
+    let lines = table_lines(
+        ["epoch", "votes", "efficiency", "avg latency", "optimal / good / poor"],
+        rows,
+        &[
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(12),
+            Constraint::Min(20),
+        ],
+        area.width,
+    );
+    PanelContent::bordered("epochs", area, "epoch breakdown", PanelBody::Lines(lines))
+}
+
+fn cluster_comparison_panel(area: Rect, stats: &PerformanceStats, cluster: &ClusterSnapshot, theme: &Theme) -> PanelContent {
+    let efficiency = stats.calculate_efficiency();
+    let median = cluster.median();
+    let top_decile = cluster.top_decile();
+    let color = if efficiency >= top_decile {
+        Color::Green
+    } else if efficiency >= median {
+        Color::Yellow
+    } else {
+        Color::Red
+    };
+
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("you: "),
+            Span::styled(
+                format!("{:.2}% (p{:.0} by stake)", efficiency, cluster.rank_of(efficiency)),
+                theme.fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("   cluster median: {:.2}%   top 10%: {}{:.2}%", median, theme.text("≥", ">="), top_decile)),
+        ]),
+        Line::styled(
+            format!(
+                "{} staked validators, epoch {} after {} slots, updated {} ago",
+                cluster.validators, cluster.epoch,
+                format_number(cluster.slots_elapsed), format_duration(cluster.age())
+            ),
+            theme.fg(Color::DarkGray),
+        ),
+    ];
+    PanelContent::bordered("cluster", area, "cluster comparison (epoch to date)", PanelBody::Lines(lines))
+}
+
+fn block_production_panel(area: Rect, production: BlockProduction, theme: &Theme) -> PanelContent {
+    let color = match production.session.skipped {
+        0 => Color::Green,
+        _ => Color::Yellow,
+    };
+    let session = if production.session.scheduled() == 0 {
+        Line::from("leader slots: none settled yet")
+    } else {
+        Line::from(vec![
+            Span::raw("leader slots: "),
+            Span::styled(leader_slot_counts(&production.session), theme.fg(color).add_modifier(Modifier::BOLD)),
+        ])
+    };
+    let epoch = match production.epoch {
+        Some(epoch) => format!("epoch {}: {}", epoch, leader_slot_counts(&production.epoch_slots)),
+        None => "epoch: n/a".to_string(),
+    };
+    let last_skipped = production.last_skipped
+        .map_or_else(|| "none".to_string(), |slot| format!("slot {}", slot));
+    let lines = vec![
+        session,
+        Line::styled(format!("{}   last skipped: {}", epoch, last_skipped), theme.fg(Color::DarkGray)),
+    ];
+    PanelContent::bordered("block_production", area, "block production this session", PanelBody::Lines(lines))
+}
+
+/// "14 produced / 2 skipped (12.5% skip)"
+fn leader_slot_counts(counts: &LeaderSlotCounts) -> String {
+    format!(
+        "{} produced / {} skipped ({:.1}% skip)",
+        format_number(counts.produced), format_number(counts.skipped), counts.skip_rate().unwrap_or_default()
+    )
+}
+
+fn worst_leaders_panel(area: Rect, stats: &PerformanceStats, leaders: Vec<LeaderPoorVotes>) -> PanelContent {
+    let unattributed = stats.leader_attribution.as_ref().map_or(0, |attribution| attribution.unattributed);
+    let title = if unattributed > 0 {
+        format!("worst leaders this session ({} poor votes before the schedule loaded)", unattributed)
+    } else {
+        "worst leaders this session".to_string()
+    };
+
+    let rows = leaders
+        .into_iter()
+        .map(|leader| (vec![leader.poor_votes.to_string(), leader.leader], Style::default()))
+        .collect();
+
+    let lines = table_lines(["poor votes", "leader"], rows, &[Constraint::Length(11), Constraint::Min(44)], area.width);
+    PanelContent::bordered("worst_leaders", area, title, PanelBody::Lines(lines))
+}
+
+fn recent_votes_panel(area: Rect, stats: &PerformanceStats, view: &mut View) -> PanelContent {
+    let theme = view.theme;
+    let total = stats.recent_confirmed_votes.len();
+    let capacity = area.height.saturating_sub(3) as usize;
+    let scroll = &mut *view.scroll;
+
+    // scroll so the selection stays clear of the older votes indicator
+    *view.selected = view.selected.filter(|_| total > 0).map(|row| row.min(total - 1));
+    if let Some(row) = *view.selected {
+        let visible = capacity.saturating_sub(1).max(1);
+        if row >= *scroll + visible {
+            *scroll = row + 1 - visible;
+        }
+        *scroll = (*scroll).min(row);
+    }
+    *scroll = (*scroll).min(total.saturating_sub(capacity));
+
+    // the last row becomes an indicator when older votes do not fit
+    let remaining = total - *scroll;
+    let vote_rows = if remaining > capacity { capacity.saturating_sub(1) } else { remaining };
+    let older = remaining - vote_rows;
+
+    // the confirmed latency goes next to the finalized one
+    let dual_latency = stats.commitment.observes_confirmed();
+    let mut columns = vote_columns(true);
+    if dual_latency {
+        columns.insert(1, Constraint::Length(8));
+    }
+    // the finalized slot before the signature, with the block height when
+    // the stream sends it
+    let block_heights = stats.recent_confirmed_votes.iter().any(|vote| vote.block_height.is_some());
+    let block_column = columns.len() - 1;
+    columns.insert(block_column, Constraint::Length(11));
+    if block_heights {
+        columns.insert(block_column + 1, Constraint::Length(11));
+    }
+    let signature_width = signature_width(area, &columns);
+    let mut rows: Vec<(Vec<String>, Style)> = stats.recent_confirmed_votes
+        .iter()
+        .rev()
+        .enumerate()
+        .skip(*scroll)
+        .take(vote_rows)
+        .map(|(row, vote)| {
+            let tvc_lost = stats.tvc.max_credits_per_slot.saturating_sub(vote.tvc_credits);
+            let mut style = theme.fg(tvc_color(vote.tvc_credits, &stats.tvc));
+            if *view.selected == Some(row) {
+                style = style.add_modifier(Modifier::REVERSED);
+                *view.selected_signature = Some(vote.signature.clone());
+            }
+            let mut cells = vec![
+                vote.voted_slot.to_string(),
+                estimated_latency(vote),
+                vote.tvc_credits.to_string(),
+                if tvc_lost > 0 { format!("-{}", tvc_lost) } else { String::new() },
+                view.signature_display.format(&vote.signature, view.explorer, Some(signature_width), theme.text("…", "...")),
+            ];
+            if dual_latency {
+                cells.insert(1, vote.confirmed_latency.map(|latency| latency.to_string()).unwrap_or_else(|| "-".to_string()));
+            }
+            cells.insert(block_column, vote.finalized_slot.to_string());
+            if block_heights {
+                cells.insert(block_column + 1, vote.block_height.map(|height| height.to_string()).unwrap_or_else(|| "-".to_string()));
+            }
+            (cells, style)
+        })
+        .collect();
+    if older > 0 {
+        rows.push((vec![format!("{} {} older", theme.text("…", "..."), older)], theme.fg(Color::DarkGray)));
+    }
+
+    let mut title = if vote_rows < total {
+        format!(
+            "recent votes ({}-{} of {}, {} to select",
+            *scroll + 1, *scroll + vote_rows, total, theme.text("↑/↓", "up/down")
+        )
+    } else {
+        format!("recent votes (last {}", total)
+    };
+    let estimated: Vec<String> = [
+        ConfirmationKind::DirectBlockOnly,
+        ConfirmationKind::EstimatedTxSlot,
+        ConfirmationKind::TowerLatency,
+    ]
+    .into_iter()
+    .filter(|kind| stats.recent_confirmed_votes.iter().any(|vote| vote.kind == *kind))
+    .map(|kind| format!("{} {}", kind.marker(), kind.name()))
+    .collect();
+    if !estimated.is_empty() {
+        title.push_str(&format!(", estimated: {}", estimated.join(" ")));
+    }
+    title.push(')');
+
+    let summary = stats.recent_window_summary();
+    let summary = if summary.votes > 0 {
+        let finalization = summary.avg_finalization_delay_ms
+            .map(|delay| format!(" | finalized after {:.0}ms", delay))
+            .unwrap_or_default();
+        format!(
+            " last {}: avg latency {:.2} | tvc lost {} | {:.1}% optimal{} ",
+            summary.votes, summary.avg_latency, summary.tvc_lost, summary.optimal_pct, finalization
+        )
+    } else {
+        String::new()
+    };
+
+    let mut titles = if dual_latency {
+        vec!["slot", "conf lat", "fin lat", "tvc", "lost", "signature"]
+    } else {
+        vec!["slot", "latency", "tvc", "lost", "signature"]
+    };
+    titles.insert(block_column, "finalized");
+    if block_heights {
+        titles.insert(block_column + 1, "height");
+    }
+    let lines = table_lines(titles, rows, &columns, area.width);
+    PanelContent::bordered("recent_votes", area, title, PanelBody::Lines(lines)).with_bottom_title(summary)
+}
+
+fn poor_events_panel(area: Rect, stats: &PerformanceStats, view: &View) -> PanelContent {
+    let (filter, theme) = (view.poor_filter, view.theme);
+    let mut columns = vote_columns(false);
+    columns.insert(0, Constraint::Length(MARKER_WIDTH));
+    let signature_width = signature_width(area, &columns);
+    let matching: Vec<&ConfirmedVote> = stats.session_poor_votes
+        .iter()
+        .filter(|vote| filter.matches(vote, &stats.tvc))
+        .collect();
+    let rows: Vec<(Vec<String>, Style)> = matching
+        .iter()
+        .rev()
+        .take(MAX_POOR_EVENT_ROWS)
+        .map(|vote| {
+            let level = categorize_tvc_performance(vote.tvc_credits, &stats.tvc);
+            let cells = vec![
+                theme.marker(level).to_string(),
+                vote.voted_slot.to_string(),
+                estimated_latency(vote),
+                vote.tvc_credits.to_string(),
+                view.signature_display.format(&vote.signature, view.explorer, Some(signature_width), theme.text("…", "...")),
+            ];
+            (cells, theme.fg(severity_color(level)))
+        })
+        .collect();
+
+    // the panel lists the newest votes only, the histogram counts all of them
+    let severity = stats.severity_histogram();
+    let title = if rows.is_empty() {
+        format!("poor performance events ({}: none this session, f to filter)", filter.label(&stats.tvc))
+    } else {
+        format!(
+            "poor performance events ({}: showing last {} of {} this session ({} critical), f to filter)",
+            filter.label(&stats.tvc),
+            rows.len(),
+            format_number(severity.at_or_below(filter.level())),
+            format_number(severity.critical)
+        )
+    };
+
+    let lines = table_lines(["", "slot", "latency", "tvc", "signature"], rows, &columns, area.width);
+    PanelContent::bordered("poor_events", area, title, PanelBody::Lines(lines))
+}
+
+/// the latency with the marker of its kind, see `ConfirmationKind::marker`
+fn estimated_latency(vote: &ConfirmedVote) -> String {
+    format!("{}{}", vote.latency, vote.kind.marker())
+}
+
+fn footer_panel(area: Rect, stats: &PerformanceStats, view: &View) -> PanelContent {
+    let theme = view.theme;
+    let (status_text, status_color) = stats.get_performance_status();
+
+    let mut status = vec![
+        Span::styled(
+            format!("status: {} performance", status_text),
+            theme.fg(Color::from(status_color)).add_modifier(Modifier::BOLD),
+        ),
+    ];
+    let timeline = &stats.status_timeline;
+    if let Some((current_status, streak)) = timeline.current_streak() {
+        status.push(Span::raw(format!(
+            "   time in status: optimal {} | good {} | poor {} (current: {} for {})",
+            format_duration(timeline.time_in("optimal")),
+            format_duration(timeline.time_in("good")),
+            format_duration(timeline.time_in("poor")),
+            current_status,
+            format_duration(streak)
+        )));
+    }
+
+    for drift in &stats.filter_drift {
+        status.push(Span::styled(
+            format!("   degraded, grpc filter drift: {}", drift.summary()),
+            theme.fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if let Some(writer) = stats.event_writer.as_ref().map(|metrics| metrics.snapshot()).filter(|writer| writer.is_degraded()) {
+        let style = if writer.retrying { theme.fg(Color::Red) } else { theme.fg(Color::Yellow) };
+        status.push(Span::styled(
+            format!(
+                "   event log: {} awaiting retry, {} dropped",
+                format_number(writer.queued_events), format_number(writer.dropped_events)
+            ),
+            style,
+        ));
+    }
+
+    let mut lines = vec![Line::from(status)];
+    let mut stream = Vec::with_capacity(4);
+    if let Some(endpoints) = &stats.endpoints {
+        let status = endpoints.status();
+        let style = if status.failovers > 0 { theme.fg(Color::Yellow) } else { theme.fg(Color::DarkGray) };
+        stream.push(Span::styled(format_endpoint(&status), style));
+    }
+    if let Some(health) = &stats.stream_health {
+        let separator = if stream.is_empty() { "" } else { "   " };
+        let staleness = health.staleness();
+        let style = if staleness.stale {
+            theme.fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            theme.fg(Color::DarkGray)
+        };
+        stream.push(Span::styled(format!("{}{}", separator, format_staleness(&staleness)), style));
+    }
+    if let Some(pipeline) = &stats.pipeline {
+        let separator = if stream.is_empty() { "" } else { "   " };
+        stream.push(Span::styled(
+            format!("{}{}", separator, format_pipeline(&pipeline.snapshot())),
+            theme.fg(Color::DarkGray),
+        ));
+    }
+    if !stream.is_empty() {
+        lines.push(Line::from(stream));
+    }
+    if let Some(process) = stats.process.as_ref().filter(|_| view.show_process) {
+        lines.push(Line::from(Span::styled(format!("process: {}", process.summary()), theme.fg(Color::DarkGray))));
+    }
+    if let Some(tracker) = stats.tracker.as_ref().filter(|_| view.show_debug) {
+        let style = if tracker.evicted_pending_votes > 0 { theme.fg(Color::Yellow) } else { theme.fg(Color::DarkGray) };
+        lines.push(Line::from(Span::styled(format_tracker(tracker), style)));
+    }
+
+    let mut keys = vec![Span::styled(
+        format!(
+            "q quit   space pause   {} pgup/pgdn select   c copy   esc clear   f filter poor events   d debug   l logs   r reset stats",
+            theme.text("↑/↓", "up/down")
+        ),
+        theme.fg(Color::DarkGray),
+    )];
+    keys.push(Span::styled(format!("   v{} ({})", VERSION, GIT_HASH), theme.fg(Color::DarkGray)));
+    if let Some(notice) = view.notice {
+        keys.push(Span::styled(format!("   {}", notice), theme.fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+    }
+
+    lines.push(Line::from(keys));
+    PanelContent::plain("footer", area, PanelBody::Lines(lines))
+}
+
+/// `tracker: 14 pending, 0 evicted, 2 unmatched, 3 blocks without vote | cleanup horizon 300 slots, last 3s ago at slot 1200`
+fn format_tracker(tracker: &VoteTrackerStats) -> String {
+    let cleanup = match (tracker.last_cleanup_slot, tracker.last_cleanup_secs) {
+        (Some(slot), Some(secs)) => format!("last {} ago at slot {}", format_duration(Duration::from_secs(secs)), slot),
+        _ => "no sweep yet".to_string(),
+    };
+    format!(
+        "tracker: {} pending, {} evicted, {} unmatched, {} blocks without vote | cleanup horizon {} slots, {}",
+        format_number(tracker.pending_votes as u64), format_number(tracker.evicted_pending_votes),
+        format_number(tracker.unmatched_confirmations as u64), format_number(tracker.blocks_without_vote),
+        tracker.pending_max_age_slots, cleanup
+    )
+}
+
+/// `grpc: https://backup:10000 (2 failovers)`
+fn format_endpoint(status: &EndpointStatus) -> String {
+    if status.endpoints > 1 {
+        format!("grpc: {} ({} failovers)", status.active, status.failovers)
+    } else {
+        format!("grpc: {}", status.active)
+    }
+}
+
+/// `last tx update: 4s ago / last block: 2s ago / last ping: 10s ago`
+fn format_staleness(staleness: &StreamStaleness) -> String {
+    let age = |secs: Option<u64>| secs.map_or_else(|| "never".to_string(), |secs| format!("{}s ago", secs));
+    let mut text = format!(
+        "last tx update: {} / last block: {} / last ping: {}",
+        age(staleness.last_transaction_secs), age(staleness.last_block_secs), age(staleness.last_ping_secs)
+    );
+    if staleness.stale_reconnects > 0 {
+        text.push_str(&format!(" ({} stale reconnects)", staleness.stale_reconnects));
+    }
+    text
+}
+
+/// `pipeline: tx q 12/1000, block q 3/1000`, with peaks, waits and drops once there are any
+fn format_pipeline(pipeline: &PipelineSnapshot) -> String {
+    let channel = |name: &str, channel: &ChannelSnapshot| {
+        let mut text = format!("{} q {}/{}", name, channel.depth, channel.capacity);
+        let mut details = Vec::new();
+        if channel.high_water > channel.depth {
+            details.push(format!("peak {}", channel.high_water));
+        }
+        if channel.blocked_sends > 0 {
+            details.push(format!("{} waits", format_number(channel.blocked_sends)));
         }
-        chart_lines.push(line);
+        if channel.dropped > 0 {
+            details.push(format!("{} dropped", format_number(channel.dropped)));
+        }
+        if !details.is_empty() {
+            text.push_str(&format!(" ({})", details.join(", ")));
+        }
+        text
+    };
+    format!(
+        "pipeline: {}, {}",
+        channel("tx", &pipeline.transactions),
+        channel("block", &pipeline.blocks)
+    )
+}
+
+fn vote_columns(with_loss: bool) -> Vec<Constraint> {
+    let mut columns = vec![
+        Constraint::Length(11),
+        Constraint::Length(8),
+        Constraint::Length(4),
+    ];
+    if with_loss {
+        columns.push(Constraint::Length(5));
     }
-    
-    let mut baseline = String::with_capacity(64);
-    baseline.push_str(" 0 |");
-    for _ in 0..BAR_WIDTH {
-        baseline.push_str("──");
+    columns.push(Constraint::Min(20));
+    columns
+}
+
+/// cells left for the last, signature, column inside the block borders
+/// once the fixed columns and the spacing between columns are taken
+fn signature_width(area: Rect, columns: &[Constraint]) -> usize {
+    let fixed: u16 = columns
+        .iter()
+        .map(|column| match column {
+            Constraint::Length(width) => *width,
+            _ => 0,
+        })
+        .sum();
+    let spacing = columns.len().saturating_sub(1) as u16;
+    area.width.saturating_sub(2 + fixed + spacing) as usize
+}
+
+fn tvc_color(tvc_credits: u64, tvc: &TvcConfig) -> Color {
+    match categorize_tvc_performance(tvc_credits, tvc) {
+        TvcPerformanceLevel::Optimal => Color::Green,
+        TvcPerformanceLevel::Good => Color::Indexed(208), // orange
+        _ => Color::Red,
     }
-    chart_lines.push(baseline);
-    
-    chart_lines
 }
 
-pub async fn render_dashboard_with_colors(stats: &PerformanceStats, vote_account: &str) -> Result<()> {
+fn severity_color(level: TvcPerformanceLevel) -> Color {
+    match level {
+        TvcPerformanceLevel::Optimal | TvcPerformanceLevel::Good => Color::Yellow,
+        TvcPerformanceLevel::Fair => Color::Indexed(208),
+        TvcPerformanceLevel::Poor => Color::Red,
+        TvcPerformanceLevel::Critical => Color::Magenta,
+    }
+}
+
+pub async fn render_dashboard_with_colors(stats: &PerformanceStats, vote_account: &str, theme: &Theme) -> Result<()> {
     let mut stdout = io::stdout();
-    
+
     execute!(stdout, Hide, Clear(ClearType::All), cursor::MoveTo(0, 0))
         .map_err(|e| VoteMonitorError::Dashboard(format!("terminal error: {}", e)))?;
-    
-    let efficiency = stats.calculate_efficiency();
-    let (status_text, status_color) = stats.get_performance_status();
-    
-    println!("═══════════════════════════════════════════════════════════════");
-    println!("solana vote monitor");
-    println!("vote account: {}", vote_account);
-    println!("═══════════════════════════════════════════════════════════════\n");
-    
-    execute!(stdout, SetForegroundColor(status_color))?;
-    println!("status: {} performance ({:.1}% efficiency)", status_text, efficiency);
-    execute!(stdout, ResetColor)?;
-    
-    println!("total votes: {} | uptime: {}", 
-             format_number(stats.total_transactions()),
-             format_duration(stats.session_start.elapsed()));
-    
+
+    print_fitted(&colored_dashboard_lines(stats, vote_account, theme), theme);
+
     stdout.flush()
         .map_err(|e| VoteMonitorError::Dashboard(format!("flush error: {}", e)))?;
-    
+
     Ok(())
 }
 
-pub async fn render_simple_dashboard(stats: &PerformanceStats, vote_account: &str) -> Result<()> {
+pub async fn render_simple_dashboard(stats: &PerformanceStats, vote_account: &str, theme: &Theme) -> Result<()> {
+    print_fitted(&simple_dashboard_lines(stats, vote_account, theme), theme);
+
+    Ok(())
+}
+
+/// lines of `render_dashboard_with_colors`, the status colored unless the theme has no color
+fn colored_dashboard_lines(stats: &PerformanceStats, vote_account: &str, theme: &Theme) -> Vec<String> {
+    let efficiency = stats.calculate_efficiency();
+    let (status_text, status_color) = stats.get_performance_status();
+
+    let separator = theme.text("═", "=").repeat(63);
+    let status = format!("status: {} performance ({:.1}% efficiency)", status_text, efficiency);
+    let status = if theme.kind() == DashboardTheme::NoColor {
+        status
+    } else {
+        format!("{}{}{}", SetForegroundColor(status_color), status, ResetColor)
+    };
+    vec![
+        separator.clone(),
+        "solana vote monitor".to_string(),
+        format!("vote account: {}", vote_account),
+        separator,
+        String::new(),
+        status,
+        format!(
+            "total votes: {} | uptime: {}",
+            format_number(stats.total_transactions()),
+            format_duration(stats.session_start.elapsed())
+        ),
+    ]
+}
+
+/// lines of `render_simple_dashboard`
+fn simple_dashboard_lines(stats: &PerformanceStats, vote_account: &str, theme: &Theme) -> Vec<String> {
     let efficiency = stats.calculate_efficiency();
     let uptime = format_duration(stats.session_start.elapsed());
     let vote_rate = stats.calculate_vote_rate();
-    
-    println!("=== solana vote monitor ===");
-    println!("vote account: {}", vote_account);
-    println!("session uptime: {} | total votes: {} | rate: {:.2}/sec", 
-             uptime, stats.total_transactions(), vote_rate);
-    println!("tvc efficiency: {:.1}% ({}/{} credits)", 
-             efficiency, stats.total_tvc_earned(), stats.total_tvc_possible());
-    println!("performance: {} optimal, {} good, {} poor votes",
-             stats.optimal_votes(), stats.good_votes(), stats.poor_votes());
-    
+
+    let mut lines = vec![
+        "=== solana vote monitor ===".to_string(),
+        format!("vote account: {}", vote_account),
+        format!("session uptime: {} | total votes: {} | rate: {:.2}/sec of {:.2}",
+                uptime, stats.total_transactions(), vote_rate, stats.expected_vote_rate()),
+        format!("tvc efficiency: {:.1}% ({}/{} credits)",
+                efficiency, stats.total_tvc_earned(), stats.total_tvc_possible()),
+        format_rolling_efficiency(stats),
+        format!("performance: {} optimal, {} good, {} poor votes",
+                stats.optimal_votes(), stats.good_votes(), stats.poor_votes()),
+    ];
+
     if let Some(last_vote) = &stats.last_confirmed_vote {
-        println!("last vote: slot {} → {} tvc (latency: {})", 
-                 last_vote.voted_slot, last_vote.tvc_credits, last_vote.latency);
+        lines.push(format!("last vote: slot {} {} {} tvc (latency: {})",
+                           last_vote.voted_slot, theme.text("→", "->"), last_vote.tvc_credits, last_vote.latency));
     }
-    
-    println!("=====================================\n");
-    
-    Ok(())
-}
\ No newline at end of file
+
+    lines.push("=====================================".to_string());
+    lines.push(String::new());
+    lines
+}
+
+/// print `lines` cut to the terminal width so none of them wraps, color
+/// escapes don't count towards it. lines are printed whole without a terminal.
+fn print_fitted(lines: &[String], theme: &Theme) {
+    let width = crossterm::terminal::size().ok().map(|(columns, _)| usize::from(columns));
+    for line in fit_lines(lines, width, theme) {
+        println!("{}", line);
+    }
+}
+
+/// `lines` cut to `width` visible cells, untouched without a width
+fn fit_lines(lines: &[String], width: Option<usize>, theme: &Theme) -> Vec<String> {
+    match width {
+        Some(width) => lines.iter().map(|line| truncate_visible(line, width, theme.text("…", "..."))).collect(),
+        None => lines.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::signature_display::visible_width;
+    use crate::status_api::STATUS_DASHBOARD_SIZE;
+    use crate::test_support::{make_confirmed_vote, signature_base58};
+
+    const ACCOUNT: &str = "Vote111111111111111111111111111111111111111";
+
+    fn stats() -> PerformanceStats {
+        let mut stats = PerformanceStats::new();
+        for (seed, latency) in (1..=20).zip([1, 1, 2, 5, 9].into_iter().cycle()) {
+            stats.add_confirmed_vote(make_confirmed_vote(seed, 100 + seed, latency));
+        }
+        stats
+    }
+
+    fn rows(buffer: &Buffer) -> Vec<String> {
+        let area = buffer.area;
+        (area.top()..area.bottom())
+            .map(|y| (area.left()..area.right()).map(|x| buffer.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn printed_lines_fit_the_width() {
+        let stats = stats();
+        for kind in [DashboardTheme::Emoji, DashboardTheme::Ascii, DashboardTheme::NoColor] {
+            let theme = Theme::new(kind);
+            let mut lines = colored_dashboard_lines(&stats, ACCOUNT, &theme);
+            lines.extend(simple_dashboard_lines(&stats, ACCOUNT, &theme));
+            for width in [0, 1, 10, 40, 59, 60, 80] {
+                for line in fit_lines(&lines, Some(width), &theme) {
+                    assert!(visible_width(&line) <= width, "{:?} wider than {} with {:?}", line, width, kind);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fitted_lines_keep_their_color_escapes() {
+        let theme = Theme::new(DashboardTheme::Ascii);
+        let lines = colored_dashboard_lines(&stats(), ACCOUNT, &theme);
+        let status = fit_lines(&lines, Some(20), &theme).remove(5);
+        assert!(status.starts_with("\x1b[") && status.ends_with("\x1b[0m"));
+        assert_eq!(visible_width(&status), 20);
+        assert!(status.contains("..."));
+
+        // no terminal, nothing is cut
+        assert_eq!(fit_lines(&lines, None, &theme), lines);
+    }
+
+    #[test]
+    fn small_terminals_get_the_message() {
+        let theme = Theme::new(DashboardTheme::Ascii);
+        let stats = stats();
+        for size in [(59, 40), (120, 19), (50, 15), (20, 3)] {
+            let buffer = DashboardRenderer::build_dashboard_content(&stats, ACCOUNT, &MemoryUsage::new(), &theme, size).unwrap();
+            let rows = rows(&buffer);
+            assert_eq!(rows.len(), usize::from(size.1));
+            let message: Vec<_> = rows.iter().filter(|row| !row.trim().is_empty()).collect();
+            assert_eq!(message.len(), 1, "{:?}", size);
+            assert_eq!(rows.iter().position(|row| !row.trim().is_empty()), Some(usize::from(size.1 / 2)));
+            if size.0 >= 50 {
+                let expected = format!("terminal too small: need 60x20, have {}x{}", size.0, size.1);
+                assert_eq!(message[0].trim(), expected);
+            } else {
+                assert!(message[0].trim().ends_with("..."));
+            }
+        }
+    }
+
+    #[test]
+    fn minimum_size_draws_the_layout() {
+        let theme = Theme::new(DashboardTheme::Ascii);
+        let stats = stats();
+        for size in [(60, 20), (80, 24), (200, 60)] {
+            let buffer = DashboardRenderer::build_dashboard_content(&stats, ACCOUNT, &MemoryUsage::new(), &theme, size).unwrap();
+            let rows = rows(&buffer);
+            assert!(rows.iter().all(|row| visible_width(row) == usize::from(size.0)));
+            assert!(!rows.iter().any(|row| row.contains("terminal too small")), "{:?}", size);
+            assert!(rows.iter().filter(|row| !row.trim().is_empty()).count() > 10);
+        }
+    }
+
+    /// a session restored 100s ago with one vote per latency, slots from 1001
+    fn session(latencies: &[u64]) -> PerformanceStats {
+        let mut stats = PerformanceStats::new();
+        stats.session_start = Instant::now() - Duration::from_secs(100);
+        stats.process_start = stats.session_start;
+        for (seed, &latency) in (1..).zip(latencies) {
+            let mut vote = make_confirmed_vote(seed, 1_000 + seed, latency);
+            vote.timestamp = Local.with_ymd_and_hms(2026, 10, 16, 12, 0, seed as u32).unwrap();
+            stats.add_confirmed_vote(vote);
+        }
+        stats
+    }
+
+    /// the panels served by the status api
+    fn api_panels(stats: &PerformanceStats) -> Vec<Panel> {
+        let theme = Theme::new(DashboardTheme::NoColor);
+        DashboardRenderer::build_dashboard_panels(stats, ACCOUNT, &MemoryUsage::new(), &theme, STATUS_DASHBOARD_SIZE)
+    }
+
+    fn assert_panel(panels: &[Panel], name: &str, title: &str, lines: &[&str]) {
+        let panel = panels.iter().find(|panel| panel.name == name).unwrap_or_else(|| panic!("no {} panel", name));
+        assert_eq!(panel.title, title, "{}", name);
+        assert_eq!(panel.lines, lines, "{}", name);
+    }
+
+    fn assert_names(panels: &[Panel], names: &[&str]) {
+        assert_eq!(panels.iter().map(|panel| panel.name).collect::<Vec<_>>(), names);
+    }
+
+    fn sig(seed: u64) -> String {
+        signature_base58(seed)
+    }
+
+    /// `truncate_visible` of the signature to 20 cells
+    fn short_sig(seed: u64) -> String {
+        signature_base58(seed)[..17].to_string()
+    }
+
+    #[test]
+    fn empty_session_panels() {
+        let panels = api_panels(&session(&[]));
+        assert_names(&panels, &[
+            "header", "efficiency", "efficiency_trend", "tvc_chart", "latency", "breakdown",
+            "diagnostics", "recent_votes", "poor_events", "footer",
+        ]);
+        assert_panel(&panels, "header", "performance monitor", &[
+            "vote account: Vote111111111111111111111111111111111111111",
+            "current slot: 0   uptime: 1m 40s   votes: 0 (0.000/sec of 2.500)   missed: 0",
+            "confirmation source: blocks",
+            "tip: waiting for slot updates",
+            "vote coverage: waiting for finalized blocks",
+            "votes sent/min:   now 0 (last 1m)",
+            "failed vote txs: 0",
+        ]);
+        assert_panel(&panels, "efficiency", "tvc efficiency", &[
+            &format!("[{}] 100.0%   earned 0 / possible 0   missed 0 credits   1m / 15m / 1h: - / - / -", "#".repeat(79)),
+        ]);
+        assert_panel(&panels, "efficiency_trend", "efficiency trend (5m 0s buckets, last 5m 0s)", &[
+            "   no votes yet",
+        ]);
+        assert_panel(&panels, "tvc_chart", "tvc performance (last 0 votes)", &[]);
+        assert_panel(&panels, "latency", "vote latency", &[
+            "session avg: 0.0 slots",
+            "avg latency (60s): n/a",
+            "outside leader slots: n/a",
+            "leader slots: n/a",
+            "low latency rate: 0.0%",
+            "low latency votes: 0 of 0 (<=2 slots)",
+            "exact: n/a",
+            "grace-dependent: 0.0% (latency 2)",
+            "confirmation: n/a",
+            "landed: n/a",
+        ]);
+        assert_panel(&panels, "breakdown", "performance breakdown", &[
+            "waiting for votes...",
+        ]);
+        assert_panel(&panels, "diagnostics", "diagnostics (approx memory: 0 B)", &[]);
+        assert_panel(&panels, "recent_votes", "recent votes (last 0)", &[
+            "slot        latency  tvc  lost  finalized   signature",
+        ]);
+        assert_panel(&panels, "poor_events", "poor performance events (< 16 tvc: none this session, f to filter)", &[
+            "     slot        latency  tvc  signature",
+        ]);
+        assert_panel(&panels, "footer", "", &[
+            "status: optimal performance",
+            &format!("q quit   space pause   up/down pgup/pgdn select   c copy   esc clear   f filter poor events   d debug   l logs   r reset stats   v{} ({})", VERSION, GIT_HASH),
+        ]);
+    }
+
+    #[test]
+    fn mixed_session_panels() {
+        let panels = api_panels(&session(&[1, 1, 2, 1, 3, 1, 1, 2, 5, 1, 1, 1, 2, 1, 4, 1]));
+        assert_names(&panels, &[
+            "header", "efficiency", "efficiency_trend", "tvc_chart", "tvc_histogram", "lost_credits",
+            "latency", "breakdown", "diagnostics", "recent_votes", "poor_events", "footer",
+        ]);
+        assert_panel(&panels, "header", "performance monitor", &[
+            "vote account: Vote111111111111111111111111111111111111111",
+            "current slot: 1017   uptime: 1m 40s   votes: 16 (0.160/sec of 2.500)   missed: 0",
+            "confirmation source: blocks",
+            "tip: waiting for slot updates",
+            "vote coverage: waiting for finalized blocks",
+            "votes sent/min:   now 0 (last 1m)",
+            "failed vote txs: 0",
+            "epoch: 0   progress: 0.2%   epoch efficiency: 97.7% (session 97.7%)",
+        ]);
+        assert_panel(&panels, "efficiency", "tvc efficiency", &[
+            &format!("[{}-] 97.7%   earned 250 / possible 256   missed 6 credits   1m / 15m / 1h: 97.7% / 97.7% / 97.7%", "#".repeat(63)),
+        ]);
+        assert_panel(&panels, "efficiency_trend", "efficiency trend (5m 0s buckets, last 5m 0s)", &[
+            "~  now 97.7%  low 97.7%",
+        ]);
+        assert_panel(&panels, "tvc_chart", "tvc performance (last 16 votes)", &[
+            "16 16 16 16 15 16 16 16 13 16 16 16 16 16 14 16",
+        ]);
+        assert_panel(&panels, "tvc_histogram", "tvc distribution (session, 16 votes)", &[
+            &format!("16 tvc {:<141}  81.2% 13", "#".repeat(141)),
+            &format!("15 tvc {:<141}   6.2%  1", "#".repeat(11)),
+            &format!("14 tvc {:<141}   6.2%  1", "#".repeat(11)),
+            &format!("13 tvc {:<141}   6.2%  1", "#".repeat(11)),
+        ]);
+        assert_panel(&panels, "lost_credits", "credits lost by cause (session, 6 credits)", &[
+            "cause                  votes      credits    share",
+            "finalization stall     2          5          83.3%",
+            "unknown                1          1          16.7%",
+        ]);
+        assert_panel(&panels, "latency", "vote latency", &[
+            "session avg: 1.8 slots",
+            "avg latency (60s): 1.75 slots",
+            "outside leader slots: n/a",
+            "leader slots: n/a",
+            "low latency rate: 81.2%",
+            "low latency votes: 13 of 16 (<=2 slots)",
+            "exact: 0: 0% | 1: 62% | 2: 19% | 3: 6% | 4+: 12%",
+            "grace-dependent: 18.8% (latency 2)",
+            "confirmation: avg 700 ms / p95 1600 ms",
+            "landed +1: 100% | +2: 0% | +3+: 0%",
+        ]);
+        assert_panel(&panels, "breakdown", "performance breakdown", &[
+            "[OK] optimal (16 TVC):     13 (81.2%)",
+            "[!]  good (12-15 TVC):      3 (18.8%)",
+            "[X]  poor (<12 TVC):        0 ( 0.0%)",
+            "",
+            "optimal streak: 1   best: 5",
+            "last broken: slot 1015 at 12:00:15 (14 TVC)",
+            &format!("  by {}...", short_sig(15)),
+        ]);
+        assert_panel(&panels, "diagnostics", "diagnostics (approx memory: 0 B)", &[]);
+        assert_panel(&panels, "recent_votes", "recent votes (last 16)", &[
+            "slot        latency  tvc  lost  finalized   signature",
+            &format!("1016        1        16         1017        {}", sig(16)),
+            &format!("1015        4        14   -2    1019        {}", sig(15)),
+            &format!("1014        1        16         1015        {}", sig(14)),
+            &format!("1013        2        16         1015        {}", sig(13)),
+            &format!("1012        1        16         1013        {}", sig(12)),
+            &format!("1011        1        16         1012        {}", sig(11)),
+            &format!("1010        1        16         1011        {}", sig(10)),
+            &format!("1009        5        13   -3    1014        {}", sig(9)),
+            &format!("1008        2        16         1010        {}", sig(8)),
+            &format!("1007        1        16         1008        {}", sig(7)),
+            &format!("1006        1        16         1007        {}", sig(6)),
+            &format!("1005        3        15   -1    1008        {}", sig(5)),
+            &format!("1004        1        16         1005        {}", sig(4)),
+            &format!("1003        2        16         1005        {}", sig(3)),
+            &format!("1002        1        16         1003        {}", sig(2)),
+            &format!("1001        1        16         1002        {}", sig(1)),
+            "last 16: avg latency 1.75 | tvc lost 6 | 81.2% optimal",
+        ]);
+        assert_panel(&panels, "poor_events", "poor performance events (< 16 tvc: showing last 3 of 3 this session (0 critical), f to filter)", &[
+            "     slot        latency  tvc  signature",
+            &format!("[!]  1015        4        14   {}", sig(15)),
+            &format!("[!]  1009        5        13   {}", sig(9)),
+            &format!("[!]  1005        3        15   {}", sig(5)),
+        ]);
+        assert_panel(&panels, "footer", "", &[
+            "status: optimal performance   time in status: optimal 0s | good 0s | poor 0s (current: optimal for 0s)",
+            &format!("q quit   space pause   up/down pgup/pgdn select   c copy   esc clear   f filter poor events   d debug   l logs   r reset stats   v{} ({})", VERSION, GIT_HASH),
+        ]);
+    }
+
+    #[test]
+    fn poor_events_session_panels() {
+        let panels = api_panels(&session(&[1, 9, 1, 20, 2, 40, 1, 7, 1, 12, 3, 60]));
+        assert_names(&panels, &[
+            "header", "efficiency", "efficiency_trend", "tvc_chart", "tvc_histogram", "lost_credits",
+            "latency", "breakdown", "diagnostics", "recent_votes", "poor_events", "footer",
+        ]);
+        assert_panel(&panels, "header", "performance monitor", &[
+            "vote account: Vote111111111111111111111111111111111111111",
+            "current slot: 1072   uptime: 1m 40s   votes: 12 (0.120/sec of 2.500)   missed: 0",
+            "confirmation source: blocks",
+            "tip: waiting for slot updates",
+            "vote coverage: waiting for finalized blocks",
+            "votes sent/min:   now 0 (last 1m)",
+            "failed vote txs: 0",
+            "epoch: 0   progress: 0.2%   epoch efficiency: 64.6% (session 64.6%)",
+        ]);
+        assert_panel(&panels, "efficiency", "tvc efficiency", &[
+            &format!("[{}{}] 64.6%   earned 124 / possible 192   missed 68 credits   1m / 15m / 1h: 64.6% / 64.6% / 64.6%", "#".repeat(41), "-".repeat(22)),
+        ]);
+        assert_panel(&panels, "efficiency_trend", "efficiency trend (5m 0s buckets, last 5m 0s)", &[
+            ".  now 64.6%  low 64.6%",
+        ]);
+        assert_panel(&panels, "tvc_chart", "tvc performance (last 12 votes)", &[
+            "16 9 16 1 16 1 16 11 16 6 15 1",
+        ]);
+        assert_panel(&panels, "tvc_histogram", "tvc distribution (session, 12 votes)", &[
+            &format!("16 tvc {:<142}  41.7% 5", "#".repeat(142)),
+            &format!("15 tvc {:<142}   8.3% 1", "#".repeat(28)),
+            &format!("11 tvc {:<142}   8.3% 1", "#".repeat(28)),
+            &format!(" 9 tvc {:<142}   8.3% 1", "#".repeat(28)),
+            &format!(" 6 tvc {:<142}   8.3% 1", "#".repeat(28)),
+            &format!(" 1 tvc {:<142}  25.0% 3", "#".repeat(85)),
+        ]);
+        assert_panel(&panels, "lost_credits", "credits lost by cause (session, 68 credits)", &[
+            "cause                  votes      credits    share",
+            "finalization stall     6          67         98.5%",
+            "unknown                1          1          1.5%",
+        ]);
+        assert_panel(&panels, "latency", "vote latency", &[
+            "session avg: 13.1 slots",
+            "avg latency (60s): 13.08 slots",
+            "outside leader slots: n/a",
+            "leader slots: n/a",
+            "low latency rate: 41.7%",
+            "low latency votes: 5 of 12 (<=2 slots)",
+            "exact: 0: 0% | 1: 33% | 2: 8% | 3: 8% | 4+: 50%",
+            "grace-dependent: 8.3% (latency 2)",
+            "confirmation: avg 5233 ms / p95 16000 ms",
+            "landed +1: 100% | +2: 0% | +3+: 0%",
+        ]);
+        assert_panel(&panels, "breakdown", "performance breakdown", &[
+            "[OK] optimal (16 TVC):      5 (41.7%)",
+            "[!]  good (12-15 TVC):      1 ( 8.3%)",
+            "[X]  poor (<12 TVC):        6 (50.0%)",
+            "",
+            "optimal streak: 0   best: 1",
+            "last broken: slot 1010 at 12:00:10 (6 TVC)",
+            &format!("  by {}...", short_sig(10)),
+        ]);
+        assert_panel(&panels, "diagnostics", "diagnostics (approx memory: 0 B)", &[]);
+        assert_panel(&panels, "recent_votes", "recent votes (last 12)", &[
+            "slot        latency  tvc  lost  finalized   signature",
+            &format!("1012        60       1    -15   1072        {}", sig(12)),
+            &format!("1011        3        15   -1    1014        {}", sig(11)),
+            &format!("1010        12       6    -10   1022        {}", sig(10)),
+            &format!("1009        1        16         1010        {}", sig(9)),
+            &format!("1008        7        11   -5    1015        {}", sig(8)),
+            &format!("1007        1        16         1008        {}", sig(7)),
+            &format!("1006        40       1    -15   1046        {}", sig(6)),
+            &format!("1005        2        16         1007        {}", sig(5)),
+            &format!("1004        20       1    -15   1024        {}", sig(4)),
+            &format!("1003        1        16         1004        {}", sig(3)),
+            &format!("1002        9        9    -7    1011        {}", sig(2)),
+            &format!("1001        1        16         1002        {}", sig(1)),
+            "last 12: avg latency 13.08 | tvc lost 68 | 41.7% optimal",
+        ]);
+        assert_panel(&panels, "poor_events", "poor performance events (< 16 tvc: showing last 7 of 7 this session (3 critical), f to filter)", &[
+            "     slot        latency  tvc  signature",
+            &format!("[X]  1012        60       1    {}", sig(12)),
+            &format!("[!]  1011        3        15   {}", sig(11)),
+            &format!("[X]  1010        12       6    {}", sig(10)),
+            &format!("[!]  1008        7        11   {}", sig(8)),
+            &format!("[X]  1006        40       1    {}", sig(6)),
+            &format!("[X]  1004        20       1    {}", sig(4)),
+            &format!("[!]  1002        9        9    {}", sig(2)),
+        ]);
+        assert_panel(&panels, "footer", "", &[
+            "status: poor performance   time in status: optimal 0s | good 0s | poor 0s (current: poor for 0s)",
+            &format!("q quit   space pause   up/down pgup/pgdn select   c copy   esc clear   f filter poor events   d debug   l logs   r reset stats   v{} ({})", VERSION, GIT_HASH),
+        ]);
+    }
+}
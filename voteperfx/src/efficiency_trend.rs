@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::ring_buffer::RingBuffer;
+
+/// bucket length unless configured
+pub const DEFAULT_TREND_BUCKET_SECS: u64 = 300;
+
+/// buckets kept unless configured, two hours at the default length
+pub const DEFAULT_TREND_BUCKETS: usize = 24;
+
+/// credits earned and possible over one fixed-duration bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct EfficiencyBucket {
+    pub start: DateTime<Local>,
+    pub votes: u64,
+    pub tvc_earned: u64,
+    pub tvc_possible: u64,
+}
+
+impl EfficiencyBucket {
+    fn empty(start: DateTime<Local>) -> Self {
+        Self { start, votes: 0, tvc_earned: 0, tvc_possible: 0 }
+    }
+
+    /// none for a bucket without votes
+    pub fn efficiency(&self) -> Option<f64> {
+        (self.tvc_possible > 0).then(|| self.tvc_earned as f64 / self.tvc_possible as f64 * 100.0)
+    }
+}
+
+/// efficiency per fixed-duration bucket, oldest first
+///
+/// buckets roll over on `roll`, which runs on a timer, so a quiet period
+/// shows up as empty buckets instead of stretching the last one.
+#[derive(Debug, Clone)]
+pub struct EfficiencyTrend {
+    buckets: RingBuffer<EfficiencyBucket>,
+    bucket_duration: Duration,
+    current_started: Instant,
+}
+
+impl Default for EfficiencyTrend {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_TREND_BUCKET_SECS), DEFAULT_TREND_BUCKETS)
+    }
+}
+
+impl EfficiencyTrend {
+    pub fn new(bucket_duration: Duration, buckets: usize) -> Self {
+        let mut trend = Self {
+            buckets: RingBuffer::new(buckets.max(1)),
+            bucket_duration: bucket_duration.max(Duration::from_secs(1)),
+            current_started: Instant::now(),
+        };
+        trend.buckets.push(EfficiencyBucket::empty(Local::now()));
+        trend
+    }
+
+    pub fn bucket_duration(&self) -> Duration {
+        self.bucket_duration
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buckets.capacity()
+    }
+
+    /// add a confirmed vote to the current bucket
+    pub fn record(&mut self, tvc_earned: u64, tvc_possible: u64) {
+        self.roll();
+        if let Some(current) = self.buckets.last_mut() {
+            current.votes += 1;
+            current.tvc_earned += tvc_earned;
+            current.tvc_possible += tvc_possible;
+        }
+    }
+
+    /// start new buckets for every bucket duration elapsed, returns whether any started
+    pub fn roll(&mut self) -> bool {
+        let elapsed = self.current_started.elapsed();
+        if elapsed < self.bucket_duration {
+            return false;
+        }
+
+        let elapsed_buckets = (elapsed.as_nanos() / self.bucket_duration.as_nanos()) as u32;
+        // a gap longer than the whole window only needs the newest buckets
+        let skipped = elapsed_buckets.saturating_sub(self.buckets.capacity() as u32);
+        let mut start = self.buckets.last().map_or_else(Local::now, |bucket| bucket.start);
+        let step = chrono::Duration::from_std(self.bucket_duration).unwrap_or_default();
+        start += step * skipped as i32;
+        for _ in skipped..elapsed_buckets {
+            start += step;
+            self.buckets.push(EfficiencyBucket::empty(start));
+        }
+        self.current_started += self.bucket_duration * elapsed_buckets;
+        true
+    }
+
+    /// all buckets, oldest first, the last one still filling
+    pub fn buckets(&self) -> impl DoubleEndedIterator<Item = &EfficiencyBucket> + ExactSizeIterator {
+        self.buckets.iter()
+    }
+
+    /// (bucket start, efficiency) oldest first, none for buckets without votes
+    pub fn series(&self) -> Vec<(DateTime<Local>, Option<f64>)> {
+        self.buckets.iter().map(|bucket| (bucket.start, bucket.efficiency())).collect()
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.buckets.allocated_bytes()
+    }
+}
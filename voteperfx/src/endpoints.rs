@@ -0,0 +1,148 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use serde::Serialize;
+
+/// penalty after the first failure of an endpoint, doubled per failure
+pub const FAILOVER_PENALTY: Duration = Duration::from_secs(30);
+
+/// longest penalty of a flapping endpoint
+pub const MAX_FAILOVER_PENALTY: Duration = Duration::from_secs(600);
+
+/// an endpoint connected for this long is trusted again, its failures reset
+pub const STABLE_CONNECTION: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    // failures since the last stable connection
+    failures: u32,
+    penalized_until: Option<Instant>,
+    connected_at: Option<Instant>,
+}
+
+impl Endpoint {
+    fn penalized(&self, now: Instant) -> bool {
+        self.penalized_until.is_some_and(|until| until > now)
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    endpoints: Vec<Endpoint>,
+    active: usize,
+    failovers: u64,
+}
+
+/// the grpc endpoints in failover order, see `Config::grpc_endpoints`
+///
+/// the first endpoint is active at startup. a failed or stale subscription
+/// penalizes the active endpoint and moves round-robin to the next one that
+/// is not penalized, so a flapping endpoint is not retried right away.
+#[derive(Debug)]
+pub struct EndpointPool {
+    state: Mutex<PoolState>,
+}
+
+/// active endpoint and failovers, for the dashboard and /status
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    pub active: String,
+    pub endpoints: usize,
+    /// switches to another endpoint this session
+    pub failovers: u64,
+}
+
+impl EndpointPool {
+    pub fn new(urls: Vec<String>) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint { url, failures: 0, penalized_until: None, connected_at: None })
+            .collect();
+        Self {
+            state: Mutex::new(PoolState { endpoints, active: 0, failovers: 0 }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn len(&self) -> usize {
+        self.state().endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state().endpoints.is_empty()
+    }
+
+    /// url of the endpoint in use
+    pub fn active(&self) -> String {
+        let state = self.state();
+        state.endpoints[state.active].url.clone()
+    }
+
+    /// the subscription to the active endpoint was established
+    pub fn mark_connected(&self) {
+        let mut state = self.state();
+        let active = state.active;
+        state.endpoints[active].connected_at = Some(Instant::now());
+    }
+
+    /// the active endpoint failed: penalize it and switch to the next one
+    ///
+    /// returns the new active endpoint and how long it is still penalized,
+    /// zero unless every endpoint is. a single endpoint is never penalized.
+    pub fn fail_over(&self) -> (String, Duration) {
+        let now = Instant::now();
+        let mut state = self.state();
+        let count = state.endpoints.len();
+        let current = state.active;
+        if count == 1 {
+            return (state.endpoints[0].url.clone(), Duration::ZERO);
+        }
+
+        let endpoint = &mut state.endpoints[current];
+        if endpoint.connected_at.take().is_some_and(|at| now.duration_since(at) >= STABLE_CONNECTION) {
+            endpoint.failures = 0;
+        }
+        endpoint.failures = endpoint.failures.saturating_add(1);
+        let penalty = FAILOVER_PENALTY
+            .saturating_mul(1 << (endpoint.failures - 1).min(16))
+            .min(MAX_FAILOVER_PENALTY);
+        endpoint.penalized_until = Some(now + penalty);
+        info!("grpc endpoint {} penalized for {}s after {} failures", endpoint.url, penalty.as_secs(), endpoint.failures);
+
+        // round-robin from the next endpoint, or the one whose penalty ends first
+        let candidates = (1..=count).map(|offset| (current + offset) % count);
+        let next = candidates
+            .clone()
+            .find(|&index| !state.endpoints[index].penalized(now))
+            .or_else(|| candidates.min_by_key(|&index| state.endpoints[index].penalized_until))
+            .unwrap_or(current);
+        let wait = state.endpoints[next]
+            .penalized_until
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+
+        if next != current {
+            state.active = next;
+            state.failovers += 1;
+            warn!("failing over from grpc endpoint {} to {}", state.endpoints[current].url, state.endpoints[next].url);
+        }
+        (state.endpoints[next].url.clone(), wait)
+    }
+
+    pub fn failovers(&self) -> u64 {
+        self.state().failovers
+    }
+
+    pub fn status(&self) -> EndpointStatus {
+        let state = self.state();
+        EndpointStatus {
+            active: state.endpoints[state.active].url.clone(),
+            endpoints: state.endpoints.len(),
+            failovers: state.failovers,
+        }
+    }
+}
@@ -0,0 +1,422 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::credits_check::RpcCreditsClient;
+use crate::error::Result;
+use crate::config::TvcConfig;
+use crate::performance::{ConfirmedVote, Slot, TvcHistogram};
+
+pub const DEFAULT_SLOTS_PER_EPOCH: u64 = 432_000;
+
+/// epochs kept in memory for the dashboard
+const RETAINED_EPOCHS: usize = 5;
+
+/// lowest credit votes listed in an epoch report
+pub const EPOCH_REPORT_WORST_VOTES: usize = 20;
+
+/// performance summary for a single epoch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EpochStats {
+    pub epoch: u64,
+    pub first_slot: Slot,
+    pub last_slot: Slot,
+    pub votes: u64,
+    pub tvc_earned: u64,
+    pub tvc_possible: u64,
+    pub latency_sum: u64,
+    pub optimal_votes: u64,
+    pub good_votes: u64,
+    pub poor_votes: u64,
+    /// votes by earned credits, empty in summaries written before it existed
+    #[serde(default)]
+    pub tvc_histogram: TvcHistogram,
+}
+
+impl EpochStats {
+    pub fn new(epoch: u64, slot: Slot) -> Self {
+        Self {
+            epoch,
+            first_slot: slot,
+            last_slot: slot,
+            ..Default::default()
+        }
+    }
+
+    pub fn efficiency(&self) -> f64 {
+        if self.tvc_possible == 0 { return 100.0; }
+        (self.tvc_earned as f64 / self.tvc_possible as f64) * 100.0
+    }
+
+    pub fn avg_latency(&self) -> f64 {
+        if self.votes == 0 { return 0.0; }
+        self.latency_sum as f64 / self.votes as f64
+    }
+
+    fn record(&mut self, vote: &ConfirmedVote, tvc: &TvcConfig) {
+        self.votes += 1;
+        self.tvc_earned += vote.tvc_credits;
+        self.tvc_possible += tvc.max_credits_per_slot;
+        self.latency_sum += vote.latency;
+        self.last_slot = self.last_slot.max(vote.finalized_slot);
+
+        if vote.tvc_credits >= tvc.max_credits_per_slot {
+            self.optimal_votes += 1;
+        } else if vote.tvc_credits >= tvc.good_threshold() {
+            self.good_votes += 1;
+        } else {
+            self.poor_votes += 1;
+        }
+        self.tvc_histogram.record(vote.tvc_credits);
+    }
+}
+
+/// latency distribution, missed votes and worst votes of one epoch, kept
+/// for the epoch report only
+#[derive(Debug, Clone)]
+struct EpochDetail {
+    epoch: u64,
+    // latency in slots -> votes
+    latencies: BTreeMap<u64, u64>,
+    missed_votes: u64,
+    // votes below max credits, lowest credits first, then highest latency
+    worst_votes: Vec<ConfirmedVote>,
+}
+
+impl EpochDetail {
+    fn new(epoch: u64) -> Self {
+        Self {
+            epoch,
+            latencies: BTreeMap::new(),
+            missed_votes: 0,
+            worst_votes: Vec::with_capacity(EPOCH_REPORT_WORST_VOTES + 1),
+        }
+    }
+
+    /// `sample_latency` false leaves the vote out of the latency percentiles
+    fn record(&mut self, vote: &ConfirmedVote, tvc: &TvcConfig, sample_latency: bool) {
+        if sample_latency {
+            *self.latencies.entry(vote.latency).or_default() += 1;
+        }
+        if vote.tvc_credits >= tvc.max_credits_per_slot {
+            return;
+        }
+
+        let rank = |vote: &ConfirmedVote| (vote.tvc_credits, Reverse(vote.latency));
+        let position = self.worst_votes.partition_point(|worse| rank(worse) <= rank(vote));
+        if position < EPOCH_REPORT_WORST_VOTES {
+            self.worst_votes.insert(position, vote.clone());
+            self.worst_votes.truncate(EPOCH_REPORT_WORST_VOTES);
+        }
+    }
+
+    fn latency(&self) -> LatencyPercentiles {
+        let votes: u64 = self.latencies.values().sum();
+        let percentile = |pct: u64| {
+            // nearest rank
+            let rank = (votes * pct).div_ceil(100).max(1);
+            let mut seen = 0;
+            self.latencies
+                .iter()
+                .find(|&(_, &count)| {
+                    seen += count;
+                    seen >= rank
+                })
+                .map_or(0, |(&latency, _)| latency)
+        };
+        LatencyPercentiles {
+            sampled_votes: votes,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: self.latencies.keys().next_back().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// vote latency percentiles in slots
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LatencyPercentiles {
+    /// votes the percentiles cover, fewer than the epoch's votes when the
+    /// monitor started or restored its state partway through
+    pub sampled_votes: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+/// credits the chain awarded for the epoch
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OnChainCredits {
+    pub credits: u64,
+    /// positive when voteperfx counted more credits than the chain awarded
+    pub local_minus_on_chain: i64,
+}
+
+/// everything known about a completed epoch, written as epoch_<N>_summary.json
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochReport {
+    pub vote_account: String,
+    pub epoch: u64,
+    pub first_slot: Slot,
+    pub last_slot: Slot,
+    pub votes: u64,
+    pub tvc_earned: u64,
+    pub tvc_possible: u64,
+    pub efficiency: f64,
+    /// voted slots that never landed, as far as detected before the rollover
+    pub missed_votes: u64,
+    pub optimal_votes: u64,
+    pub good_votes: u64,
+    pub poor_votes: u64,
+    /// votes by earned credits, 0 to 16 tvc
+    pub tvc_histogram: TvcHistogram,
+    pub avg_latency: f64,
+    pub latency: LatencyPercentiles,
+    /// up to `EPOCH_REPORT_WORST_VOTES` votes below max credits, lowest
+    /// credits first, then highest latency
+    pub worst_votes: Vec<ConfirmedVote>,
+    /// with rpc_url, none when the lookup failed
+    pub on_chain: Option<OnChainCredits>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl EpochReport {
+    /// one line for the log
+    pub fn summary_line(&self) -> String {
+        format!(
+            "epoch {} complete: {} votes, {} missed, {:.1}% efficiency ({}/{} tvc), latency avg {:.1} p50 {} p90 {} p99 {} slots",
+            self.epoch, self.votes, self.missed_votes, self.efficiency, self.tvc_earned, self.tvc_possible,
+            self.avg_latency, self.latency.p50, self.latency.p90, self.latency.p99
+        )
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("epoch_{}_summary.json", self.epoch)
+    }
+}
+
+/// per-epoch breakdown derived from finalized slots
+#[derive(Debug, Clone)]
+pub struct EpochTracker {
+    slots_per_epoch: u64,
+    // oldest first, the back entry is the current epoch
+    epochs: VecDeque<EpochStats>,
+    // report details of the current and the last completed epoch
+    detail: Option<EpochDetail>,
+    completed_detail: Option<EpochDetail>,
+    // estimated latencies count in the report percentiles too
+    estimates_in_percentiles: bool,
+}
+
+impl EpochTracker {
+    pub fn new(slots_per_epoch: u64) -> Self {
+        Self {
+            slots_per_epoch: slots_per_epoch.max(1),
+            epochs: VecDeque::with_capacity(RETAINED_EPOCHS + 1),
+            detail: None,
+            completed_detail: None,
+            estimates_in_percentiles: false,
+        }
+    }
+
+    /// count estimated latencies in the report percentiles, see `ConfirmationKind::is_estimated`
+    pub fn with_estimates_in_percentiles(mut self, include: bool) -> Self {
+        self.estimates_in_percentiles = include;
+        self
+    }
+
+    #[inline]
+    pub fn slots_per_epoch(&self) -> u64 {
+        self.slots_per_epoch
+    }
+
+    #[inline]
+    pub fn epoch_of(&self, slot: Slot) -> u64 {
+        slot / self.slots_per_epoch
+    }
+
+    /// percent progress through the epoch containing `slot`
+    pub fn progress(&self, slot: Slot) -> f64 {
+        (slot % self.slots_per_epoch) as f64 / self.slots_per_epoch as f64 * 100.0
+    }
+
+    /// add a confirmed vote, returning the previous epoch's summary on rollover
+    pub fn record(&mut self, vote: &ConfirmedVote, tvc: &TvcConfig) -> Option<EpochStats> {
+        let epoch = self.epoch_of(vote.finalized_slot);
+        let sample_latency = self.estimates_in_percentiles || !vote.kind.is_estimated();
+        let mut completed = None;
+
+        match self.epochs.back().map(|current| current.epoch) {
+            Some(current) if current == epoch => {}
+            Some(current) if epoch < current => {
+                // late vote for an older epoch still in memory
+                if let Some(older) = self.epochs.iter_mut().find(|e| e.epoch == epoch) {
+                    older.record(vote, tvc);
+                }
+                if let Some(detail) = self.completed_detail.as_mut().filter(|detail| detail.epoch == epoch) {
+                    detail.record(vote, tvc, sample_latency);
+                }
+                return None;
+            }
+            previous => {
+                if previous.is_some() {
+                    completed = self.epochs.back().cloned();
+                    self.completed_detail = self.detail.take();
+                }
+                self.epochs.push_back(EpochStats::new(epoch, vote.finalized_slot));
+                if self.epochs.len() > RETAINED_EPOCHS {
+                    self.epochs.pop_front();
+                }
+            }
+        }
+
+        if let Some(current) = self.epochs.back_mut() {
+            current.record(vote, tvc);
+        }
+        // restored epochs start without details
+        self.detail
+            .get_or_insert_with(|| EpochDetail::new(epoch))
+            .record(vote, tvc, sample_latency);
+
+        completed
+    }
+
+    /// count voted slots that never landed against their epoch
+    pub fn record_missed(&mut self, voted_slots: &[Slot]) {
+        for &slot in voted_slots {
+            let epoch = self.epoch_of(slot);
+            let detail = [self.detail.as_mut(), self.completed_detail.as_mut()]
+                .into_iter()
+                .flatten()
+                .find(|detail| detail.epoch == epoch);
+            if let Some(detail) = detail {
+                detail.missed_votes += 1;
+            }
+        }
+    }
+
+    /// full report of an epoch `record` returned as completed
+    pub fn report(&self, summary: &EpochStats, vote_account: &str) -> EpochReport {
+        let detail = [self.completed_detail.as_ref(), self.detail.as_ref()]
+            .into_iter()
+            .flatten()
+            .find(|detail| detail.epoch == summary.epoch);
+
+        EpochReport {
+            vote_account: vote_account.to_string(),
+            epoch: summary.epoch,
+            first_slot: summary.first_slot,
+            last_slot: summary.last_slot,
+            votes: summary.votes,
+            tvc_earned: summary.tvc_earned,
+            tvc_possible: summary.tvc_possible,
+            efficiency: summary.efficiency(),
+            missed_votes: detail.map_or(0, |detail| detail.missed_votes),
+            optimal_votes: summary.optimal_votes,
+            good_votes: summary.good_votes,
+            poor_votes: summary.poor_votes,
+            tvc_histogram: summary.tvc_histogram,
+            avg_latency: summary.avg_latency(),
+            latency: detail.map(EpochDetail::latency).unwrap_or_default(),
+            worst_votes: detail.map(|detail| detail.worst_votes.clone()).unwrap_or_default(),
+            on_chain: None,
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// rebuild from persisted epochs, oldest first
+    pub fn restore(&mut self, epochs: Vec<EpochStats>) {
+        self.epochs = epochs.into_iter().collect();
+        while self.epochs.len() > RETAINED_EPOCHS {
+            self.epochs.pop_front();
+        }
+    }
+
+    pub fn current(&self) -> Option<&EpochStats> {
+        self.epochs.back()
+    }
+
+    /// retained epochs, newest first
+    pub fn epochs(&self) -> impl Iterator<Item = &EpochStats> {
+        self.epochs.iter().rev()
+    }
+}
+
+/// append a completed epoch summary as one json line
+pub async fn append_epoch_summary(summary: &EpochStats, path: &Path) -> Result<()> {
+    use tokio::fs::OpenOptions;
+    use tokio::io::AsyncWriteExt;
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let json_line = format!("{}\n", serde_json::to_string(summary)?);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+
+    file.write_all(json_line.as_bytes()).await?;
+    file.flush().await?;
+
+    Ok(())
+}
+
+pub fn default_epoch_summary_path() -> PathBuf {
+    PathBuf::from("./performance_issues/epoch_summaries.jsonl")
+}
+
+pub fn default_epoch_report_dir() -> PathBuf {
+    PathBuf::from("./performance_issues")
+}
+
+/// write `report` to `dir` atomically through a temporary file, returning its path
+pub async fn write_epoch_report(report: &EpochReport, dir: &Path) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let path = dir.join(report.file_name());
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, serde_json::to_vec_pretty(report)?).await?;
+    tokio::fs::rename(&tmp_path, &path).await?;
+
+    Ok(path)
+}
+
+/// add the on-chain credits with `rpc` and write the report in the background
+pub fn spawn_epoch_report(mut report: EpochReport, dir: PathBuf, rpc: Option<RpcCreditsClient>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Some(rpc) = rpc {
+            match rpc.fetch_epoch_credits(report.epoch).await {
+                Ok(Some(credits)) => {
+                    report.on_chain = Some(OnChainCredits {
+                        credits,
+                        local_minus_on_chain: report.tvc_earned as i64 - credits as i64,
+                    });
+                }
+                Ok(None) => warn!("no on-chain credits for epoch {}, writing the report without them", report.epoch),
+                Err(e) => warn!("failed to fetch on-chain credits for epoch {}: {}", report.epoch, e),
+            }
+        }
+
+        match write_epoch_report(&report, &dir).await {
+            Ok(path) => match report.on_chain {
+                Some(on_chain) => info!(
+                    "epoch {} report written to {} (on-chain {} credits, local {:+})",
+                    report.epoch, path.display(), on_chain.credits, on_chain.local_minus_on_chain
+                ),
+                None => info!("epoch {} report written to {}", report.epoch, path.display()),
+            },
+            Err(e) => error!("error writing epoch {} report: {}", report.epoch, e),
+        }
+    })
+}
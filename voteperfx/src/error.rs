@@ -1,9 +1,26 @@
 use thiserror::Error;
+use yellowstone_grpc_proto::tonic::{Code, Status};
 
 #[derive(Error, Debug)]
 pub enum VoteMonitorError {
-    #[error("grpc connection failed: {0}")]
-    GrpcConnection(String),
+    /// unreachable endpoint, dropped connection or an unavailable server, retryable
+    #[error("grpc transport failed: {0}")]
+    GrpcTransport(String),
+    
+    /// rejected or missing x-token, retrying won't help
+    #[error("grpc authentication failed: {0}")]
+    GrpcAuth(String),
+    
+    /// the endpoint is throttling us, retryable after a longer backoff
+    #[error("grpc rate limited: {0}")]
+    GrpcRateLimited(String),
+    
+    /// the endpoint rejected the request itself, e.g. an unsupported filter
+    #[error("grpc request rejected: {0}")]
+    GrpcProtocol(String),
+    
+    #[error("grpc tls failed: {0}")]
+    GrpcTls(String),
     
     #[error("configuration error: {0}")]
     Config(String),
@@ -25,12 +42,182 @@ pub enum VoteMonitorError {
     
     #[error("dashboard rendering error: {0}")]
     Dashboard(String),
+    
+    #[error("channel closed: {0}")]
+    Channel(String),
+    
+    #[error("rpc request failed: {0}")]
+    Rpc(String),
+    
+    #[error("metrics write failed: {0}")]
+    Metrics(String),
+    
+    #[error("database error: {0}")]
+    Database(#[from] rusqlite::Error),
+}
+
+impl VoteMonitorError {
+    /// a grpc status by its code, `context` says what was rejected
+    pub fn from_grpc_status(status: &Status, context: &str) -> Self {
+        let detail = format!("{} ({:?}: {})", context, status.code(), status.message());
+        match status.code() {
+            Code::Unauthenticated | Code::PermissionDenied => {
+                VoteMonitorError::GrpcAuth(format!("{}, check grpc_x_token", detail))
+            }
+            Code::ResourceExhausted => VoteMonitorError::GrpcRateLimited(detail),
+            Code::Unavailable | Code::Cancelled | Code::Aborted | Code::DeadlineExceeded | Code::Internal | Code::Unknown => {
+                VoteMonitorError::GrpcTransport(detail)
+            }
+            Code::Ok
+            | Code::InvalidArgument
+            | Code::NotFound
+            | Code::AlreadyExists
+            | Code::FailedPrecondition
+            | Code::OutOfRange
+            | Code::Unimplemented
+            | Code::DataLoss => VoteMonitorError::GrpcProtocol(detail),
+        }
+    }
+
+    /// whether trying the same request again can succeed
+    ///
+    /// transport failures and rate limits clear up on their own, a rejected
+    /// token, bad tls settings or an invalid config don't.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, VoteMonitorError::GrpcTransport(_) | VoteMonitorError::GrpcRateLimited(_))
+    }
+
+    /// whether a failed file write can succeed later without a restart
+    ///
+    /// a full disk or quota, a read-only or permission error from a remount
+    /// and a removed directory clear up once fixed, a path that is not a
+    /// directory or data that does not serialize don't.
+    pub fn is_transient_io(&self) -> bool {
+        match self {
+            VoteMonitorError::Io(e) => is_transient_io(e),
+            _ => false,
+        }
+    }
+}
+
+fn is_transient_io(error: &std::io::Error) -> bool {
+    #[cfg(target_os = "linux")]
+    if let Some(code) = error.raw_os_error() {
+        return matches!(
+            code,
+            libc::ENOSPC | libc::EDQUOT | libc::EROFS | libc::EACCES | libc::EPERM | libc::ENOENT
+                | libc::EIO | libc::EINTR | libc::EAGAIN | libc::EMFILE | libc::ENFILE
+        );
+    }
+    !matches!(
+        error.kind(),
+        std::io::ErrorKind::InvalidInput | std::io::ErrorKind::InvalidData | std::io::ErrorKind::Unsupported
+    )
+}
+
+impl From<Status> for VoteMonitorError {
+    fn from(status: Status) -> Self {
+        VoteMonitorError::from_grpc_status(&status, "grpc request failed")
+    }
 }
 
 impl From<grpc_client::AppError> for VoteMonitorError {
     fn from(err: grpc_client::AppError) -> Self {
-        VoteMonitorError::GrpcConnection(format!("{:?}", err))
+        VoteMonitorError::GrpcTransport(format!("{:?}", err))
+    }
+}
+
+impl From<reqwest::Error> for VoteMonitorError {
+    fn from(err: reqwest::Error) -> Self {
+        VoteMonitorError::Rpc(err.to_string())
     }
 }
 
 pub type Result<T> = std::result::Result<T, VoteMonitorError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Variant {
+        Auth,
+        RateLimited,
+        Transport,
+        Protocol,
+    }
+
+    fn variant(error: &VoteMonitorError) -> Variant {
+        match error {
+            VoteMonitorError::GrpcAuth(_) => Variant::Auth,
+            VoteMonitorError::GrpcRateLimited(_) => Variant::RateLimited,
+            VoteMonitorError::GrpcTransport(_) => Variant::Transport,
+            VoteMonitorError::GrpcProtocol(_) => Variant::Protocol,
+            other => panic!("not a grpc error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn grpc_status_codes_map_to_variants() {
+        let cases = [
+            (Code::Unauthenticated, Variant::Auth),
+            (Code::PermissionDenied, Variant::Auth),
+            (Code::ResourceExhausted, Variant::RateLimited),
+            (Code::Unavailable, Variant::Transport),
+            (Code::Cancelled, Variant::Transport),
+            (Code::Aborted, Variant::Transport),
+            (Code::DeadlineExceeded, Variant::Transport),
+            (Code::Internal, Variant::Transport),
+            (Code::Unknown, Variant::Transport),
+            (Code::Ok, Variant::Protocol),
+            (Code::InvalidArgument, Variant::Protocol),
+            (Code::NotFound, Variant::Protocol),
+            (Code::AlreadyExists, Variant::Protocol),
+            (Code::FailedPrecondition, Variant::Protocol),
+            (Code::OutOfRange, Variant::Protocol),
+            (Code::Unimplemented, Variant::Protocol),
+            (Code::DataLoss, Variant::Protocol),
+        ];
+        for (code, expected) in cases {
+            let error = VoteMonitorError::from(Status::new(code, "from the endpoint"));
+            let retryable = matches!(expected, Variant::RateLimited | Variant::Transport);
+            assert_eq!(variant(&error), expected, "{:?}", code);
+            assert_eq!(error.is_retryable(), retryable, "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn grpc_errors_keep_the_context_and_message() {
+        let error = VoteMonitorError::from_grpc_status(&Status::unauthenticated("invalid x-token"), "subscribe");
+        let message = error.to_string();
+        assert!(message.starts_with("grpc authentication failed: subscribe (Unauthenticated: invalid x-token)"), "{}", message);
+        assert!(message.ends_with("check grpc_x_token"), "{}", message);
+
+        let error = VoteMonitorError::from(Status::resource_exhausted("slow down"));
+        assert_eq!(error.to_string(), "grpc rate limited: grpc request failed (ResourceExhausted: slow down)");
+    }
+
+    #[test]
+    fn only_transport_and_rate_limits_are_retryable() {
+        assert!(!VoteMonitorError::GrpcTls("bad certificate".to_string()).is_retryable());
+        assert!(!VoteMonitorError::Config("vote_account".to_string()).is_retryable());
+        assert!(!VoteMonitorError::Channel("closed".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn transient_io_errors_are_told_apart() {
+        use std::io::{Error, ErrorKind};
+
+        let io = |error: Error| VoteMonitorError::Io(error);
+        for code in [libc::ENOSPC, libc::EDQUOT, libc::EROFS, libc::EACCES, libc::EPERM, libc::ENOENT] {
+            assert!(io(Error::from_raw_os_error(code)).is_transient_io(), "errno {}", code);
+        }
+        // a file where the directory should be stays that way
+        for code in [libc::ENOTDIR, libc::EISDIR, libc::EEXIST] {
+            assert!(!io(Error::from_raw_os_error(code)).is_transient_io(), "errno {}", code);
+        }
+        assert!(!io(Error::new(ErrorKind::InvalidData, "bad json")).is_transient_io());
+        assert!(io(Error::new(ErrorKind::TimedOut, "slow disk")).is_transient_io());
+        assert!(!VoteMonitorError::Config("performance_log_dir".to_string()).is_transient_io());
+    }
+}
@@ -0,0 +1,232 @@
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::message::SystemEvent;
+use serde_json::json;
+
+use crate::config::{LogFormat, TvcConfig};
+use crate::logging::VOTE_RECORD_TARGET;
+use crate::network::Explorer;
+use crate::signature_display::SignatureDisplay;
+
+/// events buffered per subscriber before the slowest one starts losing events
+pub const DEFAULT_EVENT_BUFFER: usize = 1024;
+
+/// broadcast of pipeline events to any number of subscribers
+///
+/// publishing never waits on subscribers. each subscriber has its own view of a
+/// bounded ring of `capacity` events; one that falls further behind gets
+/// `RecvError::Lagged(n)` on its next receive, meaning the `n` oldest events were
+/// dropped for it, and continues from the oldest event still buffered.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<SystemEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUFFER)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<SystemEvent> {
+        self.sender.subscribe()
+    }
+
+    /// publish to current subscribers, a no-op when there are none
+    #[inline]
+    pub fn publish(&self, event: SystemEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+/// simple cli logger built as an event bus subscriber
+#[derive(Debug, Default)]
+pub struct SimpleLogger {
+    votes: u64,
+    tvc_earned: u64,
+    tvc_possible: u64,
+    tvc: TvcConfig,
+    format: LogFormat,
+    signature_display: SignatureDisplay,
+    explorer: Explorer,
+    // prints only while true, always when unset, see `with_active`
+    active: Option<watch::Receiver<bool>>,
+}
+
+impl SimpleLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tvc_config(mut self, tvc: TvcConfig) -> Self {
+        self.tvc = tvc;
+        self
+    }
+
+    /// json emits one structured record per vote instead of the text block
+    pub fn with_log_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// short drops the explorer link from the text lines, see `SignatureDisplay`
+    pub fn with_signature_display(mut self, display: SignatureDisplay) -> Self {
+        self.signature_display = display;
+        self
+    }
+
+    /// where transaction links point, solscan when unset
+    pub fn with_explorer(mut self, explorer: Explorer) -> Self {
+        self.explorer = explorer;
+        self
+    }
+
+    /// log only while `active` holds true, e.g. while the dashboard is
+    /// switched to simple mode. votes are counted either way, so the session
+    /// totals stay right across switches
+    pub fn with_active(mut self, active: watch::Receiver<bool>) -> Self {
+        self.active = Some(active);
+        self
+    }
+
+    pub fn handle(&mut self, event: &SystemEvent) {
+        if let SystemEvent::VoteConfirmed(vote) = event {
+            self.votes += 1;
+            self.tvc_earned += vote.tvc_credits;
+            self.tvc_possible += self.tvc.max_credits_per_slot;
+        }
+        if self.active.as_ref().is_some_and(|active| !*active.borrow()) {
+            return;
+        }
+
+        match event {
+            SystemEvent::VoteConfirmed(vote) => {
+                if self.format == LogFormat::Json {
+                    let record = json!({
+                        "event": "vote_confirmed",
+                        "voted_slot": vote.voted_slot,
+                        "finalized_slot": vote.finalized_slot,
+                        "landed_slot": vote.landed_slot,
+                        "latency": vote.latency,
+                        "tvc_credits": vote.tvc_credits,
+                        "signature": vote.signature,
+                        "estimated": vote.kind.is_estimated(),
+                        "kind": vote.kind,
+                        "confirmation_ms": vote.confirmation_ms,
+                        "confirmed_latency": vote.confirmed_latency,
+                        "finalization_delay_ms": vote.finalization_delay_ms,
+                        "efficiency": self.efficiency(),
+                        "session_votes": self.votes,
+                        "session_tvc_earned": self.tvc_earned,
+                    });
+                    log::info!(target: VOTE_RECORD_TARGET, "{}", record);
+                    return;
+                }
+
+                log::info!(
+                    "vote confirmed: slot {} → latency {} → {} TVC{} | TX: {}",
+                    vote.voted_slot,
+                    vote.latency,
+                    vote.tvc_credits,
+                    if vote.kind.is_estimated() { format!(" ({})", vote.kind.name()) } else { String::new() },
+                    self.transaction(&vote.signature)
+                );
+                log::info!(
+                    "session stats: {} votes, {:.1}% efficiency, {} total tvc earned",
+                    self.votes,
+                    self.efficiency(),
+                    self.tvc_earned
+                );
+                log::info!("---");
+            }
+            SystemEvent::VoteMissed { signature, reason } => {
+                if self.format == LogFormat::Json {
+                    let record = json!({
+                        "event": "vote_missed",
+                        "signature": signature.as_str(),
+                        "reason": reason,
+                    });
+                    log::info!(target: VOTE_RECORD_TARGET, "{}", record);
+                    return;
+                }
+                log::info!("vote missed: {} | TX: {}", reason, self.transaction(signature));
+            }
+            SystemEvent::VoteFailed(failed) => {
+                if self.format == LogFormat::Json {
+                    let record = json!({
+                        "event": "vote_failed",
+                        "transaction_slot": failed.transaction_slot,
+                        "voted_slots": failed.voted_slots,
+                        "signature": failed.signature,
+                        "error": failed.error,
+                    });
+                    log::warn!(target: VOTE_RECORD_TARGET, "{}", record);
+                    return;
+                }
+                log::warn!(
+                    "vote transaction failed at slot {}: {} | TX: {}",
+                    failed.transaction_slot, failed.error, self.transaction(&failed.signature)
+                );
+            }
+            SystemEvent::OptimalStreakBroken(broken) => {
+                if self.format == LogFormat::Json {
+                    let record = json!({
+                        "event": "optimal_streak_broken",
+                        "length": broken.length,
+                        "voted_slot": broken.voted_slot,
+                        "tvc_credits": broken.tvc_credits,
+                        "signature": broken.signature,
+                    });
+                    log::warn!(target: VOTE_RECORD_TARGET, "{}", record);
+                    return;
+                }
+                log::warn!(
+                    "optimal streak of {} votes ended at slot {} with {} TVC | TX: {}",
+                    broken.length, broken.voted_slot, broken.tvc_credits, self.transaction(&broken.signature)
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// a link unless short, url lines are cut to the terminal by the logger
+    fn transaction(&self, signature: &str) -> String {
+        match self.signature_display {
+            SignatureDisplay::Full | SignatureDisplay::Url if !signature.is_empty() => self.explorer.tx_url(signature),
+            display => display.format(signature, &self.explorer, None, ""),
+        }
+    }
+
+    fn efficiency(&self) -> f64 {
+        if self.tvc_possible == 0 { return 100.0; }
+        (self.tvc_earned as f64 / self.tvc_possible as f64) * 100.0
+    }
+
+    /// consume events until the bus is dropped
+    pub fn spawn(mut self, mut events: broadcast::Receiver<SystemEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => self.handle(&event),
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("simple logger fell behind, skipped {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,360 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::config::FilterDriftConfig;
+use crate::events::EventBus;
+use crate::message::SystemEvent;
+use crate::performance::PerformanceStats;
+use crate::stream_health::StreamHealth;
+
+/// how often the update counters are sampled, the rates are per minute
+const DRIFT_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// exponentially weighted moving average of a series, with a deviation check
+///
+/// samples that deviate are not folded in, a lasting drop stays flagged
+/// instead of becoming the new baseline.
+#[derive(Debug, Clone)]
+pub struct EwmaBaseline {
+    alpha: f64,
+    warmup: u64,
+    samples: u64,
+    mean: f64,
+}
+
+/// where a sample lies against the baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deviation {
+    /// fewer samples than the warmup so far
+    Warmup,
+    Within,
+    Below { baseline: f64 },
+    Above { baseline: f64 },
+}
+
+impl EwmaBaseline {
+    /// average over about `span` samples, trusted after `warmup` of them
+    pub fn new(span: u64, warmup: u64) -> Self {
+        Self {
+            alpha: 2.0 / (span.max(1) as f64 + 1.0),
+            warmup: warmup.max(1),
+            samples: 0,
+            mean: 0.0,
+        }
+    }
+
+    /// none during the warmup
+    pub fn baseline(&self) -> Option<f64> {
+        (self.samples >= self.warmup).then_some(self.mean)
+    }
+
+    /// compare `value` with the baseline, more than `factor` times below or
+    /// above it deviates. a zero baseline has nothing to be above of.
+    pub fn observe(&mut self, value: f64, factor: f64) -> Deviation {
+        let deviation = match self.baseline() {
+            None => Deviation::Warmup,
+            Some(baseline) if value * factor < baseline => Deviation::Below { baseline },
+            Some(baseline) if baseline > 0.0 && value > baseline * factor => Deviation::Above { baseline },
+            Some(_) => Deviation::Within,
+        };
+        if matches!(deviation, Deviation::Warmup | Deviation::Within) {
+            self.mean = if self.samples == 0 { value } else { self.mean + self.alpha * (value - self.mean) };
+            self.samples += 1;
+        }
+        deviation
+    }
+}
+
+/// per-minute rates the watchdog follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftMetric {
+    /// vote transaction updates per minute
+    TransactionRate,
+    /// vote transaction updates per block update, blocks only carry our votes
+    TransactionsPerBlock,
+}
+
+impl DriftMetric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TransactionRate => "transaction rate",
+            Self::TransactionsPerBlock => "transactions per block",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Self::TransactionRate => "/min",
+            Self::TransactionsPerBlock => "",
+        }
+    }
+}
+
+/// a rate that left its baseline, see `FilterDriftDetector`
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterDrift {
+    pub metric: DriftMetric,
+    /// the newest sample
+    pub current: f64,
+    pub baseline: f64,
+    pub since: DateTime<Local>,
+}
+
+impl FilterDrift {
+    /// `transaction rate 12.0/min vs baseline 150.0/min`
+    pub fn summary(&self) -> String {
+        let unit = self.metric.unit();
+        format!("{} {:.1}{} vs baseline {:.1}{}", self.metric.as_str(), self.current, unit, self.baseline, unit)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DriftChange {
+    Started(FilterDrift),
+    Ended(FilterDrift),
+}
+
+/// follows the transaction rate and the transactions per block and reports
+/// when one leaves or returns to its baseline
+#[derive(Debug, Clone)]
+pub struct FilterDriftDetector {
+    factor: f64,
+    rate: EwmaBaseline,
+    per_block: EwmaBaseline,
+    drifts: Vec<FilterDrift>,
+}
+
+impl FilterDriftDetector {
+    pub fn new(config: &FilterDriftConfig) -> Self {
+        Self {
+            factor: config.factor,
+            rate: EwmaBaseline::new(config.baseline_minutes, config.warmup_minutes),
+            per_block: EwmaBaseline::new(config.baseline_minutes, config.warmup_minutes),
+            drifts: Vec::new(),
+        }
+    }
+
+    /// one minute of update counts, the drifts it started or ended
+    ///
+    /// a minute without blocks has no ratio, blocks are not subscribed in
+    /// the tx-status mode and a validator that stopped voting has none.
+    pub fn sample(&mut self, transactions: u64, blocks: u64) -> Vec<DriftChange> {
+        let mut changes = Vec::new();
+        self.observe(DriftMetric::TransactionRate, transactions as f64, &mut changes);
+        if blocks > 0 {
+            self.observe(DriftMetric::TransactionsPerBlock, transactions as f64 / blocks as f64, &mut changes);
+        }
+        changes
+    }
+
+    fn observe(&mut self, metric: DriftMetric, value: f64, changes: &mut Vec<DriftChange>) {
+        let baseline = match metric {
+            DriftMetric::TransactionRate => &mut self.rate,
+            DriftMetric::TransactionsPerBlock => &mut self.per_block,
+        };
+        let deviation = baseline.observe(value, self.factor);
+        let drifting = self.drifts.iter().position(|drift| drift.metric == metric);
+        match (deviation, drifting) {
+            (Deviation::Below { baseline } | Deviation::Above { baseline }, None) => {
+                let drift = FilterDrift { metric, current: value, baseline, since: Local::now() };
+                self.drifts.push(drift.clone());
+                changes.push(DriftChange::Started(drift));
+            }
+            (Deviation::Below { .. } | Deviation::Above { .. }, Some(i)) => self.drifts[i].current = value,
+            (Deviation::Within, Some(i)) => changes.push(DriftChange::Ended(self.drifts.remove(i))),
+            _ => {}
+        }
+    }
+
+    /// rates currently off their baseline
+    pub fn drifts(&self) -> &[FilterDrift] {
+        &self.drifts
+    }
+}
+
+/// sample the grpc update counters every minute and flag rates that leave
+/// their baseline
+///
+/// a provider that narrows the subscription filters after maintenance keeps
+/// the stream alive, only fewer of our vote transactions arrive. a drift is
+/// logged, published as `FilterDrift` and shown in the dashboard status line
+/// until the rate is back. minutes with a reconnect in them are skipped.
+pub fn spawn_filter_drift_watchdog(
+    config: FilterDriftConfig,
+    health: Arc<StreamHealth>,
+    stats: Arc<RwLock<PerformanceStats>>,
+    events: EventBus,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut detector = FilterDriftDetector::new(&config);
+        let mut interval = tokio::time::interval(DRIFT_SAMPLE_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval.tick().await;
+        let mut last = health.update_counts();
+
+        loop {
+            interval.tick().await;
+            let counts = health.update_counts();
+            let (transactions, blocks) = (counts.0 - last.0, counts.1 - last.1);
+            last = counts;
+            if !health.connected_for().is_some_and(|connected| connected >= DRIFT_SAMPLE_INTERVAL) {
+                continue;
+            }
+
+            let changes = detector.sample(transactions, blocks);
+            if changes.is_empty() {
+                continue;
+            }
+            for change in changes {
+                match change {
+                    DriftChange::Started(drift) => {
+                        warn!(
+                            "grpc filter drift: {} ({} transactions, {} blocks in the last minute, factor {}), the provider may have narrowed the subscription filters",
+                            drift.summary(), transactions, blocks, config.factor
+                        );
+                        events.publish(SystemEvent::FilterDrift(Arc::new(drift)));
+                    }
+                    DriftChange::Ended(drift) => {
+                        info!("grpc filter drift over: {} back within its baseline", drift.metric.as_str());
+                        events.publish(SystemEvent::FilterDriftEnded { metric: drift.metric });
+                    }
+                }
+            }
+            let mut stats = stats.write().await;
+            stats.filter_drift = detector.drifts().to_vec();
+            stats.mark_changed();
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a steady rate around `mean` with a little jitter
+    fn steady(mean: f64, minutes: usize) -> impl Iterator<Item = f64> {
+        [-3.0, 1.0, 4.0, -2.0, 0.0].into_iter().cycle().take(minutes).map(move |jitter| mean + jitter)
+    }
+
+    fn config() -> FilterDriftConfig {
+        FilterDriftConfig { factor: 5.0, baseline_minutes: 10, warmup_minutes: 3 }
+    }
+
+    #[test]
+    fn baseline_after_the_warmup() {
+        let mut ewma = EwmaBaseline::new(10, 3);
+        assert_eq!(ewma.baseline(), None);
+        for value in [150.0, 0.0, 1_000.0] {
+            assert_eq!(ewma.observe(value, 5.0), Deviation::Warmup);
+        }
+        // the warmup is folded in whatever it was
+        assert!(ewma.baseline().is_some());
+
+        let mut ewma = EwmaBaseline::new(10, 3);
+        for value in steady(150.0, 60) {
+            assert!(matches!(ewma.observe(value, 5.0), Deviation::Warmup | Deviation::Within));
+        }
+        assert!((ewma.baseline().unwrap() - 150.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn lasting_drop_stays_flagged() {
+        let mut ewma = EwmaBaseline::new(10, 3);
+        steady(150.0, 30).for_each(|value| {
+            ewma.observe(value, 5.0);
+        });
+        let before = ewma.baseline().unwrap();
+
+        // an 85% drop for an hour never becomes the new normal
+        for _ in 0..60 {
+            assert!(matches!(ewma.observe(20.0, 5.0), Deviation::Below { baseline } if baseline == before));
+        }
+        assert_eq!(ewma.baseline(), Some(before));
+        assert_eq!(ewma.observe(150.0, 5.0), Deviation::Within);
+
+        assert!(matches!(ewma.observe(1_000.0, 5.0), Deviation::Above { .. }));
+        // a factor off is still within
+        assert_eq!(ewma.observe(before / 5.0, 5.0), Deviation::Within);
+    }
+
+    #[test]
+    fn slow_decline_moves_the_baseline() {
+        let mut ewma = EwmaBaseline::new(10, 3);
+        let mut rate = 150.0;
+        for _ in 0..120 {
+            assert!(matches!(ewma.observe(rate, 5.0), Deviation::Warmup | Deviation::Within), "at {}", rate);
+            rate *= 0.97;
+        }
+        assert!(ewma.baseline().unwrap() < 10.0);
+    }
+
+    #[test]
+    fn zero_baseline_has_nothing_to_be_above_of() {
+        let mut ewma = EwmaBaseline::new(10, 3);
+        for _ in 0..5 {
+            ewma.observe(0.0, 5.0);
+        }
+        assert_eq!(ewma.baseline(), Some(0.0));
+        assert_eq!(ewma.observe(0.0, 5.0), Deviation::Within);
+        assert_eq!(ewma.observe(40.0, 5.0), Deviation::Within);
+    }
+
+    #[test]
+    fn narrowed_filter_while_blocks_keep_flowing() {
+        let mut detector = FilterDriftDetector::new(&config());
+        // 150 vote transactions and 150 blocks a minute
+        for transactions in steady(150.0, 20) {
+            assert!(detector.sample(transactions as u64, 150).is_empty());
+        }
+
+        let changes = detector.sample(12, 150);
+        let started: Vec<_> = changes
+            .iter()
+            .map(|change| match change {
+                DriftChange::Started(drift) => drift.metric,
+                DriftChange::Ended(drift) => panic!("{:?} ended", drift.metric),
+            })
+            .collect();
+        assert_eq!(started, [DriftMetric::TransactionRate, DriftMetric::TransactionsPerBlock]);
+        assert_eq!(detector.drifts()[0].current, 12.0);
+        assert!(detector.drifts()[0].summary().starts_with("transaction rate 12.0/min vs baseline 1"));
+
+        // still down, the drift is updated but not started again
+        assert!(detector.sample(10, 150).is_empty());
+        assert_eq!(detector.drifts()[0].current, 10.0);
+
+        let ended = detector.sample(148, 150);
+        assert_eq!(ended.len(), 2);
+        assert!(ended.iter().all(|change| matches!(change, DriftChange::Ended(_))));
+        assert!(detector.drifts().is_empty());
+    }
+
+    #[test]
+    fn minutes_without_blocks_only_sample_the_rate() {
+        let mut detector = FilterDriftDetector::new(&config());
+        for transactions in steady(150.0, 20) {
+            detector.sample(transactions as u64, 150);
+        }
+        // blocks stopped too, only the rate can drift
+        let changes = detector.sample(0, 0);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], DriftChange::Started(drift) if drift.metric == DriftMetric::TransactionRate));
+
+        // blocks carry fewer of our votes than usual while the rate holds
+        let mut detector = FilterDriftDetector::new(&config());
+        for _ in 0..20 {
+            detector.sample(150, 150);
+        }
+        let changes = detector.sample(150, 1_500);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], DriftChange::Started(drift) if drift.metric == DriftMetric::TransactionsPerBlock));
+    }
+}
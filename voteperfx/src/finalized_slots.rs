@@ -0,0 +1,196 @@
+use std::collections::BTreeSet;
+use std::mem::size_of;
+
+use crate::performance::Slot;
+
+/// finalized slots older than this behind the newest one are rejected unless configured
+pub const DEFAULT_BLOCK_HORIZON_SLOTS: u64 = 512;
+
+/// outcome of offering a finalized slot to `FinalizedSlots`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotAdmission {
+    /// newest finalized slot so far
+    New,
+    /// behind the newest slot but within the horizon and not seen before
+    OutOfOrder { behind: u64 },
+    /// already processed
+    Duplicate,
+    /// older than the horizon, usually history replayed after a reconnect
+    Stale { behind: u64 },
+}
+
+impl SlotAdmission {
+    /// whether the block should be processed
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Self::New | Self::OutOfOrder { .. })
+    }
+}
+
+/// dedup of finalized slots across both the block stream and the slot-status fallback
+///
+/// remembers every processed slot within `horizon` of the newest one, so a
+/// replayed block is caught no matter how far apart the duplicates arrive.
+/// anything older than the horizon can no longer be told apart from a
+/// duplicate and is rejected.
+#[derive(Debug, Clone)]
+pub struct FinalizedSlots {
+    highest: Option<Slot>,
+    recent: BTreeSet<Slot>,
+    horizon: u64,
+    out_of_order: u64,
+    duplicates: u64,
+    stale: u64,
+}
+
+impl Default for FinalizedSlots {
+    fn default() -> Self {
+        Self::new(DEFAULT_BLOCK_HORIZON_SLOTS)
+    }
+}
+
+impl FinalizedSlots {
+    pub fn new(horizon: u64) -> Self {
+        Self {
+            highest: None,
+            recent: BTreeSet::new(),
+            horizon,
+            out_of_order: 0,
+            duplicates: 0,
+            stale: 0,
+        }
+    }
+
+    /// record `slot` as processed if it is accepted
+    pub fn admit(&mut self, slot: Slot) -> SlotAdmission {
+        let Some(highest) = self.highest else {
+            self.highest = Some(slot);
+            self.recent.insert(slot);
+            return SlotAdmission::New;
+        };
+
+        if slot > highest {
+            self.highest = Some(slot);
+            self.recent.insert(slot);
+            let floor = slot.saturating_sub(self.horizon);
+            self.recent = self.recent.split_off(&floor);
+            return SlotAdmission::New;
+        }
+
+        let behind = highest - slot;
+        if behind > self.horizon {
+            self.stale += 1;
+            SlotAdmission::Stale { behind }
+        } else if !self.recent.insert(slot) {
+            self.duplicates += 1;
+            SlotAdmission::Duplicate
+        } else {
+            self.out_of_order += 1;
+            SlotAdmission::OutOfOrder { behind }
+        }
+    }
+
+    /// newest finalized slot processed
+    pub fn highest(&self) -> Option<Slot> {
+        self.highest
+    }
+
+    pub fn horizon(&self) -> u64 {
+        self.horizon
+    }
+
+    pub fn len(&self) -> usize {
+        self.recent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
+    }
+
+    /// accepted slots that arrived behind the newest one
+    pub fn out_of_order(&self) -> u64 {
+        self.out_of_order
+    }
+
+    /// rejected slots that were already processed
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// rejected slots older than the horizon
+    pub fn stale(&self) -> u64 {
+        self.stale
+    }
+
+    /// approximate bytes held by the set
+    pub fn allocated_bytes(&self) -> usize {
+        self.recent.len() * size_of::<Slot>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replayed_and_reordered_slots() {
+        let mut slots = FinalizedSlots::new(50);
+        let outcomes: Vec<_> = [100, 99, 100, 160, 100].into_iter().map(|slot| slots.admit(slot)).collect();
+        assert_eq!(
+            outcomes,
+            [
+                SlotAdmission::New,
+                SlotAdmission::OutOfOrder { behind: 1 },
+                SlotAdmission::Duplicate,
+                SlotAdmission::New,
+                SlotAdmission::Stale { behind: 60 },
+            ]
+        );
+        assert_eq!((slots.out_of_order(), slots.duplicates(), slots.stale()), (1, 1, 1));
+        assert_eq!(slots.highest(), Some(160));
+        let accepted = outcomes.iter().filter(|outcome| outcome.is_accepted()).count();
+        assert_eq!(accepted, 3);
+    }
+
+    #[test]
+    fn duplicates_are_caught_anywhere_within_the_horizon() {
+        let mut slots = FinalizedSlots::new(50);
+        for slot in [110, 130, 160] {
+            assert_eq!(slots.admit(slot), SlotAdmission::New);
+        }
+        // exactly the horizon behind is still remembered
+        assert_eq!(slots.admit(110), SlotAdmission::Duplicate);
+        assert_eq!(slots.admit(130), SlotAdmission::Duplicate);
+        assert_eq!(slots.admit(120), SlotAdmission::OutOfOrder { behind: 40 });
+        assert_eq!(slots.admit(120), SlotAdmission::Duplicate);
+        assert_eq!(slots.admit(109), SlotAdmission::Stale { behind: 51 });
+        assert_eq!(slots.duplicates(), 3);
+    }
+
+    #[test]
+    fn slots_behind_the_horizon_are_pruned() {
+        let mut slots = FinalizedSlots::new(50);
+        for slot in 100..150 {
+            slots.admit(slot);
+        }
+        assert_eq!(slots.len(), 50);
+        slots.admit(400);
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots.allocated_bytes(), size_of::<Slot>());
+        for slot in 351..400 {
+            assert!(slots.admit(slot).is_accepted());
+        }
+        // the window never holds more than horizon + 1 slots
+        slots.admit(401);
+        assert_eq!(slots.len(), 51);
+        assert_eq!(slots.admit(350), SlotAdmission::Stale { behind: 51 });
+    }
+
+    #[test]
+    fn first_slot_is_new_whatever_its_value() {
+        let mut slots = FinalizedSlots::default();
+        assert!(slots.is_empty());
+        assert_eq!(slots.admit(0), SlotAdmission::New);
+        assert_eq!(slots.admit(0), SlotAdmission::Duplicate);
+        assert_eq!(slots.horizon(), DEFAULT_BLOCK_HORIZON_SLOTS);
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::config::{parse_grpc_url, Config};
+use crate::error::{Result, VoteMonitorError};
+
+/// exit code of the binary when there is no config file to start from and
+/// no terminal to ask on, EX_CONFIG from sysexits.h
+pub const MISSING_CONFIG_EXIT_CODE: i32 = 78;
+
+/// the commented config.toml of the repository, written by --write-default-config
+pub const DEFAULT_CONFIG: &str = include_str!("../../config.toml");
+
+/// the settings needed to start, printed when the config file is missing
+pub const SAMPLE_CONFIG: &str = "\
+# yellowstone grpc endpoint, an http:// or https:// url with the port
+grpc_url = \"https://your-grpc-endpoint:443\"
+# vote account to monitor
+vote_account = \"<vote account pubkey>\"
+# x-token header, when the endpoint requires one
+# grpc_x_token = \"your-token\"";
+
+/// write the commented default config to `path`, an existing file is kept
+pub async fn write_default_config(path: &Path) -> Result<()> {
+    if tokio::fs::try_exists(path).await? {
+        return Err(VoteMonitorError::Config(format!("{} already exists, not overwriting it", path.display())));
+    }
+    tokio::fs::write(path, DEFAULT_CONFIG).await?;
+    Ok(())
+}
+
+/// ask on the terminal for what `config` is missing of grpc_url and
+/// vote_account, then offer to save the result to `path`
+///
+/// returns whether the file was written. values already set by the
+/// environment or the command line are not asked for.
+pub async fn bootstrap_config(config: &mut Config, path: &Path) -> Result<bool> {
+    eprintln!("{} not found, enter the settings to start with (ctrl-c to quit)", path.display());
+    let answers = {
+        let needs_grpc_url = !config.has_source();
+        let needs_vote_account = config.vote_account.is_empty();
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let mut input = io::stdin().lock();
+            let mut output = io::stderr();
+            let grpc_url = needs_grpc_url
+                .then(|| prompt(&mut input, &mut output, "grpc_url (e.g. https://host:443)", validate_grpc_url))
+                .transpose()?;
+            let vote_account = needs_vote_account
+                .then(|| prompt(&mut input, &mut output, "vote_account", validate_vote_account))
+                .transpose()?;
+            let save = confirm(&mut input, &mut output, &format!("write {}?", path.display()))?;
+            io::Result::Ok((grpc_url, vote_account, save))
+        })
+        .await
+        .map_err(|e| VoteMonitorError::Config(format!("config prompt failed: {}", e)))?
+    };
+    let (grpc_url, vote_account, save) = answers?;
+
+    if let Some(grpc_url) = grpc_url {
+        config.grpc_url = grpc_url;
+        config.grpc_urls.clear();
+    }
+    if let Some(vote_account) = vote_account {
+        config.vote_account = vote_account;
+    }
+    if !save {
+        return Ok(false);
+    }
+    // only the endpoint and the account, a token from VOTEPERFX_X_TOKEN and
+    // the other flags stay out of the file
+    let saved = Config {
+        grpc_url: config.grpc_url.clone(),
+        grpc_urls: config.grpc_urls.clone(),
+        vote_account: config.vote_account.clone(),
+        ..Config::default()
+    };
+    saved.save_to_file(path).await?;
+    eprintln!("wrote {}, --write-default-config <PATH> writes a commented one with every setting", path.display());
+    Ok(true)
+}
+
+fn validate_grpc_url(value: &str) -> std::result::Result<(), String> {
+    parse_grpc_url(value).map(|_| ()).map_err(|e| e.to_string())
+}
+
+fn validate_vote_account(value: &str) -> std::result::Result<(), String> {
+    Pubkey::from_str(value).map(|_| ()).map_err(|e| format!("not a valid base58 pubkey: {}", e))
+}
+
+/// ask until `validate` accepts the trimmed answer, fails on end of input
+fn prompt(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    name: &str,
+    validate: fn(&str) -> std::result::Result<(), String>,
+) -> io::Result<String> {
+    loop {
+        write!(output, "{}: ", name)?;
+        output.flush()?;
+        let answer = read_answer(input)?;
+        match validate(&answer) {
+            Ok(()) => return Ok(answer),
+            Err(e) => writeln!(output, "  {}", e)?,
+        }
+    }
+}
+
+/// yes unless the answer starts with n
+fn confirm(input: &mut impl BufRead, output: &mut impl Write, question: &str) -> io::Result<bool> {
+    write!(output, "{} [Y/n] ", question)?;
+    output.flush()?;
+    let answer = read_answer(input)?;
+    Ok(!answer.to_ascii_lowercase().starts_with('n'))
+}
+
+fn read_answer(input: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no answer, input closed"));
+    }
+    Ok(line.trim().to_string())
+}
@@ -0,0 +1,129 @@
+use std::error::Error as StdError;
+use std::fmt::Display;
+use std::time::Duration;
+
+use futures_util::{Sink, Stream};
+use grpc_client::{ConnectError, GeyserGrpcClientError, ProxyUrl, YellowstoneGrpc};
+use yellowstone_grpc_proto::geyser::{SubscribeRequest, SubscribeUpdate};
+use yellowstone_grpc_proto::tonic::{Code, Status};
+
+use crate::config::Config;
+use crate::error::{Result, VoteMonitorError};
+
+/// connect timeout when grpc_connect_timeout_secs is unset
+pub const DEFAULT_GRPC_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const PEM_CERTIFICATE: &[u8] = b"-----BEGIN CERTIFICATE-----";
+
+/// connect to `endpoint` and open the subscription
+///
+/// a rejected token, a failed tls handshake and an unreachable endpoint come
+/// back as `GrpcAuth`, `GrpcTls` and `GrpcTransport`, other rejections are
+/// classified by their status code, see `VoteMonitorError::from_grpc_status`.
+/// the x-token never appears in an error.
+pub async fn subscribe(
+    config: &Config,
+    endpoint: &str,
+    request: SubscribeRequest,
+) -> Result<(
+    impl Sink<SubscribeRequest, Error = impl Display> + Unpin + Send + 'static,
+    impl Stream<Item = std::result::Result<SubscribeUpdate, Status>> + Unpin + Send + 'static,
+)> {
+    let connect_timeout = config.grpc_connect_timeout_secs.map(Duration::from_secs).unwrap_or(DEFAULT_GRPC_CONNECT_TIMEOUT);
+    let mut grpc = YellowstoneGrpc::new(endpoint.to_string(), config.grpc_x_token.clone())
+        .with_connect_timeout(connect_timeout)
+        .with_ip_version(config.grpc_ip_version.into());
+    if let Some(ref url) = config.grpc_proxy_url {
+        let proxy = ProxyUrl::parse(url).map_err(|e| VoteMonitorError::Config(format!("grpc_proxy_url: {}", e)))?;
+        grpc = grpc.with_proxy(proxy);
+    }
+    if let Some(ref path) = config.grpc_tls_ca_cert_path {
+        let pem = tokio::fs::read(path).await.map_err(|e| {
+            VoteMonitorError::GrpcTls(format!("cannot read grpc_tls_ca_cert_path {}: {}", path.display(), e))
+        })?;
+        // tonic skips anything that is not a pem certificate without an error
+        if !pem.windows(PEM_CERTIFICATE.len()).any(|window| window == PEM_CERTIFICATE) {
+            return Err(VoteMonitorError::GrpcTls(format!(
+                "grpc_tls_ca_cert_path {} contains no pem certificate", path.display()
+            )));
+        }
+        grpc = grpc.with_ca_cert(pem);
+    }
+    if let Some(limit) = config.grpc_max_message_size {
+        grpc = grpc.with_max_message_size(limit);
+    }
+
+    let client = grpc.build_client().await.map_err(|e| connect_error(e, config, endpoint, connect_timeout))?;
+    let subscription = client.lock().await.subscribe_with_request(Some(request)).await;
+    subscription.map_err(|e| subscribe_error(e, config, endpoint))
+}
+
+fn connect_error(error: ConnectError, config: &Config, endpoint: &str, connect_timeout: Duration) -> VoteMonitorError {
+    match error {
+        ConnectError::Endpoint(e) => {
+            VoteMonitorError::Config(format!("{} is not a valid grpc endpoint: {}", endpoint, error_chain(&e)))
+        }
+        ConnectError::InvalidXToken => {
+            VoteMonitorError::GrpcAuth("grpc_x_token is not a valid header value".to_string())
+        }
+        ConnectError::Tls(e) => VoteMonitorError::GrpcTls(format!(
+            "invalid tls settings, check grpc_tls_ca_cert_path is a pem certificate: {}",
+            error_chain(&e)
+        )),
+        ConnectError::Connect { route, source: e } => {
+            let chain = error_chain(&e);
+            let lower = chain.to_lowercase();
+            if ["certificate", "handshake", "tls", "alert", "corrupt message"].iter().any(|hint| lower.contains(hint)) {
+                let hint = if config.grpc_tls_ca_cert_path.is_some() {
+                    "check that grpc_tls_ca_cert_path signed the server certificate"
+                } else {
+                    "set grpc_tls_ca_cert_path if the endpoint uses a private ca"
+                };
+                VoteMonitorError::GrpcTls(format!("tls handshake with {} {} failed ({}), {}", endpoint, route, chain, hint))
+            } else if lower.contains("timed out") {
+                VoteMonitorError::GrpcTransport(format!(
+                    "timed out connecting to {} {} after {}s, see grpc_connect_timeout_secs",
+                    endpoint,
+                    route,
+                    connect_timeout.as_secs()
+                ))
+            } else {
+                VoteMonitorError::GrpcTransport(format!("cannot connect to {} {}: {}", endpoint, route, chain))
+            }
+        }
+    }
+}
+
+fn subscribe_error(error: GeyserGrpcClientError, config: &Config, endpoint: &str) -> VoteMonitorError {
+    match error {
+        GeyserGrpcClientError::TonicStatus(status)
+            if matches!(status.code(), Code::Unauthenticated | Code::PermissionDenied) =>
+        {
+            let hint = if config.grpc_x_token.is_some() {
+                "check grpc_x_token"
+            } else {
+                "the endpoint needs a token, set grpc_x_token"
+            };
+            VoteMonitorError::GrpcAuth(format!("{} rejected the subscription ({}), {}", endpoint, status.message(), hint))
+        }
+        GeyserGrpcClientError::TonicStatus(status) => {
+            VoteMonitorError::from_grpc_status(&status, &format!("subscribe to {} failed", endpoint))
+        }
+        e => VoteMonitorError::GrpcTransport(format!("subscribe to {} failed: {}", endpoint, e)),
+    }
+}
+
+/// an error and its sources, tonic's own message alone is just "transport error"
+fn error_chain(error: &(dyn StdError + 'static)) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let cause_text = cause.to_string();
+        if !chain.contains(&cause_text) {
+            chain.push_str(": ");
+            chain.push_str(&cause_text);
+        }
+        source = cause.source();
+    }
+    chain
+}
@@ -0,0 +1,367 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use log::{info, warn};
+use tokio::io::AsyncWriteExt;
+use tokio::net::UdpSocket;
+use tokio::sync::broadcast::{self, error::{RecvError, TryRecvError}};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::config::{InfluxConfig, TvcConfig};
+use crate::error::{Result, VoteMonitorError};
+use crate::message::SystemEvent;
+use crate::performance::ConfirmedVote;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// first retry after a failed write, doubled up to `MAX_RETRY_BACKOFF`
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// payload of one udp datagram, below the usual mtu
+const MAX_DATAGRAM: usize = 1400;
+
+/// escape a measurement name, tag key or tag value for line protocol
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// turns pipeline events into influxdb line protocol points
+///
+/// one `vote` point per confirmed vote and a `session` point with the
+/// aggregate gauges on demand. the aggregates count from when the sink
+/// started, restored session state is not included.
+#[derive(Debug)]
+pub struct InfluxSink {
+    // escaped once, every point carries it
+    vote_account_tag: String,
+    tvc: TvcConfig,
+    started: Instant,
+    votes: u64,
+    poor_votes: u64,
+    missed_votes: u64,
+    failed_votes: u64,
+    tvc_earned: u64,
+    latency_sum: u64,
+    stream_connected: bool,
+}
+
+impl InfluxSink {
+    pub fn new(vote_account: &str, tvc: TvcConfig) -> Self {
+        Self {
+            vote_account_tag: escape(vote_account),
+            tvc,
+            started: Instant::now(),
+            votes: 0,
+            poor_votes: 0,
+            missed_votes: 0,
+            failed_votes: 0,
+            tvc_earned: 0,
+            latency_sum: 0,
+            stream_connected: false,
+        }
+    }
+
+    /// the `vote` point of a confirmed vote, none for other events
+    pub fn handle(&mut self, event: &SystemEvent) -> Option<String> {
+        match event {
+            SystemEvent::VoteConfirmed(vote) => {
+                self.votes += 1;
+                self.tvc_earned += vote.tvc_credits;
+                self.latency_sum += vote.latency;
+                if vote.tvc_credits < self.tvc.good_threshold() {
+                    self.poor_votes += 1;
+                }
+                return Some(self.vote_point(vote));
+            }
+            SystemEvent::VoteMissed { .. } => self.missed_votes += 1,
+            SystemEvent::VoteFailed(_) => self.failed_votes += 1,
+            SystemEvent::StreamConnected { .. } => self.stream_connected = true,
+            SystemEvent::StreamDisconnected { .. } => self.stream_connected = false,
+            _ => {}
+        }
+        None
+    }
+
+    fn vote_point(&self, vote: &ConfirmedVote) -> String {
+        format!(
+            "vote,vote_account={} latency={}i,tvc={}i,slot={}i {}",
+            self.vote_account_tag,
+            vote.latency,
+            vote.tvc_credits,
+            vote.voted_slot,
+            nanos(vote.timestamp.timestamp_millis())
+        )
+    }
+
+    /// aggregate gauges since the sink started, as a `session` point
+    pub fn session_point(&self) -> String {
+        let possible = self.votes * self.tvc.max_credits_per_slot;
+        let efficiency = if possible == 0 { 100.0 } else { self.tvc_earned as f64 / possible as f64 * 100.0 };
+        let avg_latency = if self.votes == 0 { 0.0 } else { self.latency_sum as f64 / self.votes as f64 };
+        format!(
+            "session,vote_account={} votes={}i,poor_votes={}i,missed_votes={}i,failed_votes={}i,\
+             tvc_earned={}i,tvc_possible={}i,efficiency={:.2},avg_latency={:.3},stream_connected={},uptime_secs={}i {}",
+            self.vote_account_tag,
+            self.votes,
+            self.poor_votes,
+            self.missed_votes,
+            self.failed_votes,
+            self.tvc_earned,
+            possible,
+            efficiency,
+            avg_latency,
+            self.stream_connected,
+            self.started.elapsed().as_secs(),
+            nanos(Utc::now().timestamp_millis())
+        )
+    }
+}
+
+/// line protocol timestamps default to nanoseconds
+fn nanos(millis: i64) -> i64 {
+    millis.saturating_mul(1_000_000)
+}
+
+/// where points are written
+#[derive(Debug)]
+enum InfluxTarget {
+    /// influxdb v2 write api
+    Http {
+        http: reqwest::Client,
+        url: String,
+        token: Option<String>,
+        org: String,
+        bucket: String,
+    },
+    /// line protocol socket (influxdb udp listener, telegraf socket_listener),
+    /// bound on the first write
+    Udp {
+        address: String,
+        socket: Option<UdpSocket>,
+    },
+    Stdout,
+}
+
+impl InfluxTarget {
+    fn from_config(config: &InfluxConfig) -> Result<Self> {
+        if config.stdout {
+            return Ok(Self::Stdout);
+        }
+        let url = config.url.as_deref().unwrap_or_default();
+        if let Some(address) = url.strip_prefix("udp://") {
+            return Ok(Self::Udp { address: address.trim_end_matches('/').to_string(), socket: None });
+        }
+        Ok(Self::Http {
+            http: reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?,
+            url: format!("{}/api/v2/write", url.trim_end_matches('/')),
+            token: config.token.clone(),
+            org: config.org.clone().unwrap_or_default(),
+            bucket: config.bucket.clone().unwrap_or_default(),
+        })
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Http { url, bucket, .. } => format!("{} (bucket {})", url, bucket),
+            Self::Udp { address, .. } => format!("udp://{}", address),
+            Self::Stdout => "stdout".to_string(),
+        }
+    }
+
+    async fn write(&mut self, lines: &[String]) -> Result<()> {
+        match self {
+            Self::Http { http, url, token, org, bucket } => {
+                let mut request = http
+                    .post(url.as_str())
+                    .query(&[("org", org.as_str()), ("bucket", bucket.as_str())])
+                    .body(lines.join("\n"));
+                if let Some(token) = token {
+                    request = request.header("Authorization", format!("Token {}", token));
+                }
+                let response = request.send().await.map_err(metrics_error)?;
+                if let Err(e) = response.error_for_status_ref() {
+                    // influx explains a rejected write in the body
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(VoteMonitorError::Metrics(format!("{}: {}", metrics_error(e), body.trim())));
+                }
+            }
+            Self::Udp { address, socket } => {
+                if socket.is_none() {
+                    let peer = tokio::net::lookup_host(address.as_str()).await?.next().ok_or_else(|| {
+                        VoteMonitorError::Metrics(format!("udp://{} does not resolve to an address", address))
+                    })?;
+                    let bound = UdpSocket::bind(if peer.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }).await?;
+                    bound.connect(peer).await?;
+                    *socket = Some(bound);
+                }
+                let Some(socket) = socket.as_ref() else { return Ok(()) };
+                let mut datagram = String::with_capacity(MAX_DATAGRAM);
+                for line in lines {
+                    if !datagram.is_empty() && datagram.len() + line.len() >= MAX_DATAGRAM {
+                        socket.send(datagram.as_bytes()).await?;
+                        datagram.clear();
+                    }
+                    datagram.push_str(line);
+                    datagram.push('\n');
+                }
+                socket.send(datagram.as_bytes()).await?;
+            }
+            Self::Stdout => {
+                let mut stdout = tokio::io::stdout();
+                let mut text = lines.join("\n");
+                text.push('\n');
+                stdout.write_all(text.as_bytes()).await?;
+                stdout.flush().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the request error with its cause, reqwest alone says "error sending request"
+pub(crate) fn metrics_error(error: reqwest::Error) -> VoteMonitorError {
+    let error = error.without_url();
+    match std::error::Error::source(&error) {
+        Some(source) => VoteMonitorError::Metrics(format!("{}: {}", error, source)),
+        None => VoteMonitorError::Metrics(error.to_string()),
+    }
+}
+
+/// spawn the influxdb sink, points are encoded from the event bus and
+/// written in batches from a separate task so a slow or unreachable influx
+/// never holds up the encoder or vote processing
+///
+/// a failed write is retried with backoff while new points keep queueing.
+/// at most `queue_capacity` points wait for the writer, beyond that the
+/// oldest are dropped. on shutdown a last session point is queued and one
+/// final write attempted before the returned task completes.
+pub fn spawn_influx_sink(
+    config: InfluxConfig,
+    tvc: TvcConfig,
+    vote_account: &str,
+    mut events: broadcast::Receiver<SystemEvent>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<JoinHandle<()>> {
+    let mut target = InfluxTarget::from_config(&config)?;
+    info!("influx metrics enabled: {}", target.describe());
+    let mut sink = InfluxSink::new(vote_account, tvc);
+
+    let (queue_tx, mut queue_rx) = broadcast::channel::<String>(config.queue_capacity);
+    let batch_size = config.batch_size;
+    let queue_capacity = config.queue_capacity;
+    let flush_interval = Duration::from_secs(config.flush_interval_secs);
+    let writer = tokio::spawn(async move {
+        let mut batch: VecDeque<String> = VecDeque::with_capacity(batch_size);
+        let mut flush = tokio::time::interval(flush_interval);
+        let mut retry_at = Instant::now();
+        let mut backoff = RETRY_BACKOFF;
+        let mut failures = 0u64;
+        let mut dropped = 0usize;
+
+        loop {
+            let flush_due = tokio::select! {
+                line = queue_rx.recv() => match line {
+                    Ok(line) => {
+                        batch.push_back(line);
+                        batch.len() >= batch_size
+                    }
+                    Err(RecvError::Lagged(dropped)) => {
+                        warn!("influx queue full, dropped {} oldest points", dropped);
+                        false
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = flush.tick() => true,
+            };
+            // points that failed to write wait here, bounded like the queue
+            if batch.len() > queue_capacity {
+                let excess = batch.len() - queue_capacity;
+                batch.drain(..excess);
+                dropped += excess;
+            }
+            if !flush_due || batch.is_empty() || Instant::now() < retry_at {
+                continue;
+            }
+
+            match target.write(batch.make_contiguous()).await {
+                Ok(()) => {
+                    if failures > 0 {
+                        info!(
+                            "influx writes resumed after {} failed attempts, {} oldest points dropped meanwhile",
+                            failures, dropped
+                        );
+                    }
+                    batch.clear();
+                    failures = 0;
+                    dropped = 0;
+                    backoff = RETRY_BACKOFF;
+                }
+                Err(e) => {
+                    failures += 1;
+                    warn!(
+                        "influx write of {} points failed, retrying in {}s: {}",
+                        batch.len(), backoff.as_secs(), e
+                    );
+                    retry_at = Instant::now() + backoff;
+                    backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+                }
+            }
+        }
+
+        if !batch.is_empty() {
+            if let Err(e) = target.write(batch.make_contiguous()).await {
+                warn!("final influx write failed, {} points lost: {}", batch.len(), e);
+            }
+        }
+    });
+
+    let session_interval = Duration::from_secs(config.session_interval_secs);
+    Ok(tokio::spawn(async move {
+        let mut session = tokio::time::interval_at(tokio::time::Instant::now() + session_interval, session_interval);
+        let dispatch = |sink: &mut InfluxSink, event: &SystemEvent| {
+            if let Some(point) = sink.handle(event) {
+                let _ = queue_tx.send(point);
+            }
+        };
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => dispatch(&mut sink, &event),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("influx sink fell behind, skipped {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = session.tick() => {
+                    let _ = queue_tx.send(sink.session_point());
+                }
+                _ = shutdown.changed() => {
+                    loop {
+                        match events.try_recv() {
+                            Ok(event) => dispatch(&mut sink, &event),
+                            Err(TryRecvError::Lagged(_)) => continue,
+                            Err(_) => break,
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        let _ = queue_tx.send(sink.session_point());
+
+        // closing the queue lets the writer flush what is left
+        drop(queue_tx);
+        let _ = writer.await;
+        info!("influx sink stopped");
+    }))
+}
@@ -0,0 +1,255 @@
+use std::time::{Duration, Instant};
+
+use chrono::{SecondsFormat, Utc};
+use log::{info, warn};
+use serde_json::{json, Map, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast::{self, error::{RecvError, TryRecvError}};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::config::{JsonStreamConfig, TvcConfig};
+use crate::message::SystemEvent;
+
+/// schema version in the "v" field of every record, bumped when a field
+/// changes meaning or goes away. new fields don't bump it.
+pub const JSON_STREAM_VERSION: u32 = 1;
+
+/// turns pipeline events into ndjson records for --json-stream
+///
+/// a `vote_confirmed`, `vote_missed` or `poor_vote` record per event and a
+/// `stats` record on demand. like the influx sink, the stats count from
+/// when the stream started, restored session state is not included and a
+/// stats reset only writes a `stats_reset` record with the session before it.
+#[derive(Debug)]
+pub struct JsonStream {
+    vote_account: String,
+    tvc: TvcConfig,
+    started: Instant,
+    votes: u64,
+    poor_votes: u64,
+    missed_votes: u64,
+    failed_votes: u64,
+    tvc_earned: u64,
+    latency_sum: u64,
+    stream_connected: bool,
+}
+
+impl JsonStream {
+    pub fn new(vote_account: &str, tvc: TvcConfig) -> Self {
+        Self {
+            vote_account: vote_account.to_string(),
+            tvc,
+            started: Instant::now(),
+            votes: 0,
+            poor_votes: 0,
+            missed_votes: 0,
+            failed_votes: 0,
+            tvc_earned: 0,
+            latency_sum: 0,
+            stream_connected: false,
+        }
+    }
+
+    /// the record of an event, none for events that are not streamed
+    pub fn handle(&mut self, event: &SystemEvent) -> Option<Value> {
+        match event {
+            SystemEvent::VoteConfirmed(vote) => {
+                self.votes += 1;
+                self.tvc_earned += vote.tvc_credits;
+                self.latency_sum += vote.latency;
+                if vote.tvc_credits < self.tvc.good_threshold() {
+                    self.poor_votes += 1;
+                }
+                Some(self.record("vote_confirmed", json!({
+                    "signature": vote.signature,
+                    "voted_slot": vote.voted_slot,
+                    "finalized_slot": vote.finalized_slot,
+                    "landed_slot": vote.landed_slot,
+                    "latency": vote.latency,
+                    "tvc_credits": vote.tvc_credits,
+                    "estimated": vote.kind.is_estimated(),
+                    "kind": vote.kind,
+                    "source": vote.source,
+                    "confirmation_ms": vote.confirmation_ms,
+                    "confirmed_latency": vote.confirmed_latency,
+                    "finalization_delay_ms": vote.finalization_delay_ms,
+                    "loss_cause": vote.loss_cause,
+                    "blockhash": vote.blockhash,
+                    "parent_slot": vote.parent_slot,
+                    "block_height": vote.block_height,
+                    "before_voter_change": vote.before_voter_change,
+                })))
+            }
+            SystemEvent::VoteMissed { signature, reason } => {
+                self.missed_votes += 1;
+                Some(self.record("vote_missed", json!({
+                    "signature": signature.as_str(),
+                    "reason": reason,
+                })))
+            }
+            SystemEvent::PerformanceEvent(event) => Some(self.record("poor_vote", json!({
+                "signature": event.transaction_signature,
+                "voted_slot": event.voted_slot,
+                "landed_slot": event.landed_slot,
+                "latency": event.latency,
+                "tvc_credits": event.tvc_credits,
+                "tvc_multiplier": event.tvc_multiplier,
+                "leader": event.leader,
+                "loss_cause": event.loss_cause,
+                "blockhash": event.blockhash,
+                "parent_slot": event.parent_slot,
+                "block_height": event.block_height,
+            }))),
+            SystemEvent::LeaderSlotSkipped { slot, epoch } => Some(self.record("leader_slot_skipped", json!({
+                "slot": slot,
+                "epoch": epoch,
+            }))),
+            SystemEvent::FilterDrift(drift) => Some(self.record("filter_drift", json!({
+                "metric": drift.metric,
+                "current": drift.current,
+                "baseline": drift.baseline,
+            }))),
+            SystemEvent::FilterDriftEnded { metric } => Some(self.record("filter_drift_ended", json!({
+                "metric": metric,
+            }))),
+            SystemEvent::StatsReset(reset) => Some(self.record("stats_reset", json!({
+                "session_secs": reset.session_secs,
+                "votes": reset.votes,
+                "tvc_earned": reset.tvc_earned,
+                "tvc_possible": reset.tvc_possible,
+                "efficiency": reset.efficiency,
+                "avg_latency": reset.avg_latency,
+                "missed_votes": reset.missed_votes,
+                "failed_votes": reset.failed_votes,
+                "kept_epoch_history": reset.kept_epoch_history,
+            }))),
+            SystemEvent::OptimalStreakBroken(broken) => Some(self.record("optimal_streak_broken", json!({
+                "length": broken.length,
+                "voted_slot": broken.voted_slot,
+                "tvc_credits": broken.tvc_credits,
+                "signature": broken.signature,
+            }))),
+            SystemEvent::VoteFailed(_) => {
+                self.failed_votes += 1;
+                None
+            }
+            SystemEvent::StreamConnected { .. } => {
+                self.stream_connected = true;
+                None
+            }
+            SystemEvent::StreamDisconnected { .. } => {
+                self.stream_connected = false;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// aggregates since the stream started, as a `stats` record
+    pub fn snapshot(&self) -> Value {
+        let possible = self.votes * self.tvc.max_credits_per_slot;
+        let efficiency = if possible == 0 { 100.0 } else { self.tvc_earned as f64 / possible as f64 * 100.0 };
+        let avg_latency = if self.votes == 0 { 0.0 } else { self.latency_sum as f64 / self.votes as f64 };
+        self.record("stats", json!({
+            "votes": self.votes,
+            "poor_votes": self.poor_votes,
+            "missed_votes": self.missed_votes,
+            "failed_votes": self.failed_votes,
+            "tvc_earned": self.tvc_earned,
+            "tvc_possible": possible,
+            "efficiency": efficiency,
+            "avg_latency": avg_latency,
+            "stream_connected": self.stream_connected,
+            "uptime_secs": self.started.elapsed().as_secs(),
+        }))
+    }
+
+    /// the fields of the record type with the common ones, v, type, timestamp and vote_account
+    fn record(&self, kind: &str, fields: Value) -> Value {
+        let mut record = Map::new();
+        record.insert("v".to_string(), JSON_STREAM_VERSION.into());
+        record.insert("type".to_string(), kind.into());
+        record.insert("timestamp".to_string(), Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true).into());
+        record.insert("vote_account".to_string(), self.vote_account.as_str().into());
+        if let Value::Object(fields) = fields {
+            record.extend(fields);
+        }
+        Value::Object(record)
+    }
+}
+
+/// spawn the ndjson writer, one record per line on stdout, flushed per line
+/// so a reader sees each record as it happens
+///
+/// a `stats` record is written every `snapshot_interval_secs` and once more
+/// on shutdown. once stdout is closed records are dropped, the monitor keeps
+/// running.
+pub fn spawn_json_stream(
+    config: JsonStreamConfig,
+    tvc: TvcConfig,
+    vote_account: &str,
+    mut events: broadcast::Receiver<SystemEvent>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    info!("json stream enabled: ndjson v{} records on stdout", JSON_STREAM_VERSION);
+    let mut stream = JsonStream::new(vote_account, tvc);
+    let snapshot_interval = Duration::from_secs(config.snapshot_interval_secs);
+
+    tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        let mut snapshot = tokio::time::interval_at(tokio::time::Instant::now() + snapshot_interval, snapshot_interval);
+        let mut open = true;
+        loop {
+            let record = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => stream.handle(&event),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("json stream fell behind, skipped {} events", skipped);
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = snapshot.tick() => Some(stream.snapshot()),
+                _ = shutdown.changed() => {
+                    loop {
+                        match events.try_recv() {
+                            Ok(event) => {
+                                if let Some(record) = stream.handle(&event) {
+                                    open = open && write_record(&mut stdout, &record).await;
+                                }
+                            }
+                            Err(TryRecvError::Lagged(_)) => continue,
+                            Err(_) => break,
+                        }
+                    }
+                    break;
+                }
+            };
+            if let Some(record) = record {
+                open = open && write_record(&mut stdout, &record).await;
+            }
+        }
+        if open {
+            write_record(&mut stdout, &stream.snapshot()).await;
+        }
+        info!("json stream stopped");
+    })
+}
+
+/// write one line, false once stdout is gone
+async fn write_record(stdout: &mut tokio::io::Stdout, record: &Value) -> bool {
+    let mut line = record.to_string();
+    line.push('\n');
+    let written = async {
+        stdout.write_all(line.as_bytes()).await?;
+        stdout.flush().await
+    };
+    match written.await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("json stream stopped writing, stdout is unavailable: {}", e);
+            false
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::mem::size_of;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::error::{Result, VoteMonitorError};
+use crate::performance::{PerformanceStats, Slot};
+use crate::rpc::{PollBackoff, RpcClient};
+
+/// how often the current epoch is checked for a schedule that is not cached yet
+const EPOCH_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// epochs kept, the previous one still resolves votes finalized after the boundary
+const CACHED_EPOCHS: usize = 2;
+
+/// how far behind the newest finalized slot a leader slot is judged, a
+/// finalized status that arrives late still counts as produced
+pub const LEADER_SLOT_SETTLE_SLOTS: u64 = 32;
+
+/// a jump in the finalized slots larger than this means missed slot statuses,
+/// such as a reconnect, the leader slots in between are not judged
+const FINALIZED_GAP_SLOTS: u64 = 64;
+
+/// leader identity of every slot in one epoch
+#[derive(Debug)]
+pub struct LeaderSchedule {
+    pub epoch: u64,
+    pub first_slot: Slot,
+    leaders: Vec<Arc<str>>,
+    /// index into `leaders` per slot of the epoch
+    slots: Vec<u32>,
+}
+
+impl LeaderSchedule {
+    /// build from getLeaderSchedule's leader -> slot indices map
+    pub fn from_rpc(epoch: u64, first_slot: Slot, schedule: HashMap<String, Vec<usize>>) -> Self {
+        let slot_count = schedule.values().flatten().max().map_or(0, |&index| index + 1);
+        let mut leaders = Vec::with_capacity(schedule.len());
+        let mut slots = vec![u32::MAX; slot_count];
+
+        for (leader, indices) in schedule {
+            let leader_index = leaders.len() as u32;
+            leaders.push(Arc::from(leader));
+            for index in indices {
+                slots[index] = leader_index;
+            }
+        }
+
+        Self { epoch, first_slot, leaders, slots }
+    }
+
+    /// leader of `slot`, none outside this epoch
+    pub fn leader(&self, slot: Slot) -> Option<&Arc<str>> {
+        let index = slot.checked_sub(self.first_slot)? as usize;
+        self.leaders.get(*self.slots.get(index)? as usize)
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.slots.capacity() * size_of::<u32>()
+            + self.leaders.iter().map(|leader| size_of::<Arc<str>>() + leader.len()).sum::<usize>()
+    }
+}
+
+/// poor votes of one leader, see `LeaderAttribution::worst_leaders`
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderPoorVotes {
+    pub leader: String,
+    pub poor_votes: u64,
+}
+
+/// votes for slots the monitored validator was leader of
+///
+/// the validator can't vote the usual way in its own leader window, so these
+/// votes land late and skew the latency averages.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LeaderSlotVotes {
+    pub votes: u64,
+    pub latency_sum: u64,
+}
+
+impl LeaderSlotVotes {
+    pub fn avg_latency(&self) -> Option<f64> {
+        (self.votes > 0).then(|| self.latency_sum as f64 / self.votes as f64)
+    }
+}
+
+/// produced and skipped leader slots of the monitored validator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LeaderSlotCounts {
+    pub produced: u64,
+    pub skipped: u64,
+}
+
+impl LeaderSlotCounts {
+    pub fn scheduled(&self) -> u64 {
+        self.produced + self.skipped
+    }
+
+    /// skipped share of the judged leader slots, none before the first one
+    pub fn skip_rate(&self) -> Option<f64> {
+        (self.scheduled() > 0).then(|| self.skipped as f64 / self.scheduled() as f64 * 100.0)
+    }
+
+    fn record(&mut self, produced: bool) {
+        if produced {
+            self.produced += 1;
+        } else {
+            self.skipped += 1;
+        }
+    }
+}
+
+/// block production of the monitored validator this session
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct BlockProduction {
+    pub session: LeaderSlotCounts,
+    /// epoch of the newest judged leader slot
+    pub epoch: Option<u64>,
+    pub epoch_slots: LeaderSlotCounts,
+    pub last_skipped: Option<Slot>,
+}
+
+/// a leader slot of the monitored validator once it settled, see
+/// `LeaderAttribution::record_finalized_slot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JudgedLeaderSlot {
+    pub slot: Slot,
+    pub epoch: u64,
+    pub produced: bool,
+}
+
+/// poor votes attributed to the leader of the voted slot
+///
+/// only present when rpc_url is set, schedules are filled in by
+/// `spawn_leader_schedule_fetcher`. with the validator identity known,
+/// votes for its own leader slots are counted separately.
+#[derive(Debug, Clone, Default)]
+pub struct LeaderAttribution {
+    schedules: VecDeque<Arc<LeaderSchedule>>,
+    poor_votes: FxHashMap<Arc<str>, u64>,
+    /// poor votes whose epoch schedule was not loaded
+    pub unattributed: u64,
+    identity: Option<Arc<str>>,
+    leader_slot_votes: LeaderSlotVotes,
+    block_production: BlockProduction,
+    // finalized slots after `judged_through`, the produced ones among our leader slots
+    finalized: BTreeSet<Slot>,
+    highest_finalized: Option<Slot>,
+    judged_through: Option<Slot>,
+}
+
+impl LeaderAttribution {
+    /// the monitored validator's identity, for `is_own_slot`
+    pub fn with_identity(mut self, identity: &str) -> Self {
+        self.identity = Some(Arc::from(identity));
+        self
+    }
+
+    /// same schedules and identity, no attributed votes, for a session reset
+    pub fn fresh(&self) -> Self {
+        Self {
+            schedules: self.schedules.clone(),
+            identity: self.identity.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn identity(&self) -> Option<&str> {
+        self.identity.as_deref()
+    }
+
+    pub fn schedule_count(&self) -> usize {
+        self.schedules.len()
+    }
+
+    pub fn has_epoch(&self, epoch: u64) -> bool {
+        self.schedules.iter().any(|schedule| schedule.epoch == epoch)
+    }
+
+    pub fn insert_schedule(&mut self, schedule: LeaderSchedule) {
+        if self.has_epoch(schedule.epoch) {
+            return;
+        }
+        self.schedules.push_back(Arc::new(schedule));
+        self.schedules.make_contiguous().sort_unstable_by_key(|schedule| schedule.epoch);
+        while self.schedules.len() > CACHED_EPOCHS {
+            self.schedules.pop_front();
+        }
+    }
+
+    pub fn leader(&self, slot: Slot) -> Option<&Arc<str>> {
+        self.schedules.iter().find_map(|schedule| schedule.leader(slot))
+    }
+
+    /// epoch and leader of `slot`
+    fn scheduled(&self, slot: Slot) -> Option<(u64, &Arc<str>)> {
+        self.schedules.iter().find_map(|schedule| Some((schedule.epoch, schedule.leader(slot)?)))
+    }
+
+    /// count a poor vote against the leader of `voted_slot`
+    pub fn record_poor_vote(&mut self, voted_slot: Slot) {
+        match self.leader(voted_slot).cloned() {
+            Some(leader) => *self.poor_votes.entry(leader).or_default() += 1,
+            None => self.unattributed += 1,
+        }
+    }
+
+    /// the monitored validator is the leader of `slot`, false without an
+    /// identity or before the epoch's schedule is loaded
+    pub fn is_own_slot(&self, slot: Slot) -> bool {
+        match (&self.identity, self.leader(slot)) {
+            (Some(identity), Some(leader)) => identity == leader,
+            _ => false,
+        }
+    }
+
+    /// one of our own leader slots is within `distance` slots of `slot`,
+    /// false without an identity or a loaded schedule
+    pub fn near_own_slot(&self, slot: Slot, distance: u64) -> bool {
+        self.identity.is_some()
+            && (slot.saturating_sub(distance)..=slot.saturating_add(distance)).any(|near| self.is_own_slot(near))
+    }
+
+    /// count a confirmed vote, true when it was for one of our own leader slots
+    pub fn record_vote(&mut self, voted_slot: Slot, latency: u64) -> bool {
+        let own_slot = self.is_own_slot(voted_slot);
+        if own_slot {
+            self.leader_slot_votes.votes += 1;
+            self.leader_slot_votes.latency_sum += latency;
+        }
+        own_slot
+    }
+
+    /// votes for our own leader slots this session, none without an identity
+    pub fn leader_slot_votes(&self) -> Option<LeaderSlotVotes> {
+        self.identity.as_ref().map(|_| self.leader_slot_votes)
+    }
+
+    /// feed a finalized slot, returns our leader slots that settled with it
+    ///
+    /// a skipped slot never finalizes, so one of our leader slots missing
+    /// from the finalized slots once it is `LEADER_SLOT_SETTLE_SLOTS` behind
+    /// the newest is counted skipped. slots before the first finalized slot,
+    /// across a gap in the stream or without a loaded schedule are not judged.
+    /// nothing is tracked without an identity.
+    pub fn record_finalized_slot(&mut self, slot: Slot) -> Vec<JudgedLeaderSlot> {
+        if self.identity.is_none() {
+            return Vec::new();
+        }
+        match self.highest_finalized {
+            Some(highest) if slot > highest.saturating_add(FINALIZED_GAP_SLOTS) => {
+                log::debug!("finalized slots jumped from {} to {}, leader slots in between not judged", highest, slot);
+                self.finalized.clear();
+                self.judged_through = Some(slot - 1);
+            }
+            None => self.judged_through = Some(slot.saturating_sub(1)),
+            _ => {}
+        }
+        // settled already, too late to change the outcome
+        if self.judged_through.is_some_and(|judged| slot <= judged) {
+            return Vec::new();
+        }
+        self.finalized.insert(slot);
+        let highest = self.highest_finalized.map_or(slot, |highest| highest.max(slot));
+        self.highest_finalized = Some(highest);
+
+        let (Some(judged_through), Some(cutoff)) = (self.judged_through, highest.checked_sub(LEADER_SLOT_SETTLE_SLOTS)) else {
+            return Vec::new();
+        };
+        if cutoff <= judged_through {
+            return Vec::new();
+        }
+
+        let mut judged = Vec::new();
+        for slot in judged_through + 1..=cutoff {
+            let Some((epoch, leader)) = self.scheduled(slot) else { continue };
+            if Some(leader) != self.identity.as_ref() {
+                continue;
+            }
+            judged.push(JudgedLeaderSlot { slot, epoch, produced: self.finalized.contains(&slot) });
+        }
+        self.finalized = self.finalized.split_off(&(cutoff + 1));
+        self.judged_through = Some(cutoff);
+
+        for judged_slot in &judged {
+            let production = &mut self.block_production;
+            if production.epoch != Some(judged_slot.epoch) {
+                production.epoch = Some(judged_slot.epoch);
+                production.epoch_slots = LeaderSlotCounts::default();
+            }
+            production.session.record(judged_slot.produced);
+            production.epoch_slots.record(judged_slot.produced);
+            if !judged_slot.produced {
+                production.last_skipped = Some(judged_slot.slot);
+            }
+        }
+        judged
+    }
+
+    /// produced and skipped leader slots this session, none without an identity
+    pub fn block_production(&self) -> Option<BlockProduction> {
+        self.identity.as_ref().map(|_| self.block_production)
+    }
+
+    /// leaders with the most poor votes, most first
+    pub fn worst_leaders(&self, n: usize) -> Vec<LeaderPoorVotes> {
+        let mut leaders: Vec<LeaderPoorVotes> = self.poor_votes
+            .iter()
+            .map(|(leader, &poor_votes)| LeaderPoorVotes { leader: leader.to_string(), poor_votes })
+            .collect();
+        leaders.sort_unstable_by(|a, b| b.poor_votes.cmp(&a.poor_votes).then_with(|| a.leader.cmp(&b.leader)));
+        leaders.truncate(n);
+        leaders
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.schedules.iter().map(|schedule| schedule.allocated_bytes()).sum::<usize>()
+            + self.poor_votes.capacity() * size_of::<(Arc<str>, u64)>()
+            + self.finalized.len() * size_of::<Slot>()
+    }
+}
+
+/// leader schedules over json-rpc
+#[derive(Debug, Clone)]
+pub struct LeaderScheduleClient {
+    rpc: RpcClient,
+}
+
+impl LeaderScheduleClient {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        Ok(Self { rpc: RpcClient::new(url)? })
+    }
+
+    /// current epoch and its first slot at finalized commitment
+    pub async fn fetch_epoch(&self) -> Result<(u64, Slot)> {
+        let info = self.rpc.epoch_info().await?;
+        Ok((info.epoch, info.first_slot()))
+    }
+
+    pub async fn fetch_schedule(&self, epoch: u64, first_slot: Slot) -> Result<LeaderSchedule> {
+        let schedule: Option<HashMap<String, Vec<usize>>> = self.rpc
+            .call("getLeaderSchedule", json!([first_slot, { "commitment": "finalized" }]))
+            .await?;
+        let schedule = schedule
+            .ok_or_else(|| VoteMonitorError::Rpc(format!("no leader schedule for epoch {}", epoch)))?;
+        Ok(LeaderSchedule::from_rpc(epoch, first_slot, schedule))
+    }
+}
+
+/// load the leader schedule once per epoch into the session's attribution
+///
+/// the current epoch is checked every minute and its schedule fetched
+/// when missing. failed polls back off like the credits check.
+pub fn spawn_leader_schedule_fetcher(
+    client: LeaderScheduleClient,
+    stats: Arc<RwLock<PerformanceStats>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut backoff = PollBackoff::new(EPOCH_POLL_INTERVAL);
+
+        loop {
+            backoff.wait().await;
+
+            match fetch_missing_schedule(&client, &stats).await {
+                Ok(()) => backoff.succeeded(),
+                Err(e) => log::warn!("leader schedule fetch failed ({} in a row): {}", backoff.failed(), e),
+            }
+        }
+    })
+}
+
+async fn fetch_missing_schedule(client: &LeaderScheduleClient, stats: &RwLock<PerformanceStats>) -> Result<()> {
+    let (epoch, first_slot) = client.fetch_epoch().await?;
+    let cached = stats.read().await
+        .leader_attribution
+        .as_ref()
+        .is_some_and(|attribution| attribution.has_epoch(epoch));
+    if cached {
+        return Ok(());
+    }
+
+    let schedule = client.fetch_schedule(epoch, first_slot).await?;
+    log::info!("loaded leader schedule for epoch {} ({} leaders)", epoch, schedule.leaders.len());
+
+    if let Some(attribution) = stats.write().await.leader_attribution.as_mut() {
+        attribution.insert_schedule(schedule);
+    }
+    Ok(())
+}
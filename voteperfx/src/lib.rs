@@ -1,81 +1,193 @@
 // mimalloc
+#[cfg(not(test))]
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
+// mimalloc counting the allocations of each thread, see `test_support::allocations`
+#[cfg(test)]
+#[global_allocator]
+static GLOBAL: test_support::CountingAllocator = test_support::CountingAllocator;
+
+pub mod account_info;
+pub mod account_switch;
+pub mod analyze;
+pub mod channel_metrics;
+pub mod cli;
+pub mod cluster;
+pub mod cluster_tip;
+pub mod commitment;
+pub mod compare;
 pub mod config;
+pub mod config_reload;
+pub mod coverage;
+pub mod credits_check;
 pub mod dashboard;
+pub mod efficiency_trend;
+pub mod endpoints;
+pub mod epoch;
 pub mod error;
+pub mod events;
+pub mod filter_drift;
+pub mod finalized_slots;
+pub mod first_run;
+pub mod grpc;
+pub mod influx;
+pub mod json_stream;
+pub mod leader_schedule;
+pub mod liveness;
+pub mod logging;
+pub mod loss_cause;
+pub mod memory;
 pub mod message;
+pub mod monitor;
+pub mod network;
+pub mod notify;
 pub mod performance;
+pub mod performance_log;
+pub mod pipeline;
+pub mod process_metrics;
+pub mod prometheus;
+pub mod ring_buffer;
+pub mod rolling_windows;
+pub mod rpc;
+pub mod rpc_source;
+pub mod signature_display;
+pub mod state;
+pub mod stats_dump;
+pub mod status_api;
+pub mod storage;
+pub mod streaks;
+pub mod stream_health;
+pub mod tasks;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod theme;
+pub mod time_window;
+pub mod tower_root;
+#[cfg(feature = "tracing")]
+pub mod tracing_logging;
+pub mod vote_account;
+pub mod vote_log;
+pub mod vote_parse;
+pub mod vote_source;
 pub mod vote_tracker;
+pub mod vote_tracker_actor;
+pub mod votes_sent;
 //pub mod simd_utils;
 
-pub use config::{Config, PerformanceFilterConfig};
-pub use dashboard::DashboardRenderer;
+pub use account_info::{AccountInfo, AccountInfoClient, spawn_account_info_monitor};
+pub use account_switch::{AccountArchive, AccountSwitch, SwitchRefused, append_account_archive, request_account_switch};
+pub use analyze::{AnalysisReport, analyze_votes, print_report, read_votes, resolve_inputs};
+pub use compare::{CompareRange, ComparisonReport, RangeVotes, compare_votes, print_comparison};
+pub use channel_metrics::{ChannelMetrics, ChannelSnapshot, PipelineMetrics, PipelineSnapshot};
+pub use cli::{AnalyzeArgs, Cli, Command, SwitchAccountArgs};
+pub use cluster::{ClusterClient, ClusterSnapshot, VoteAccountEntry, spawn_cluster_comparison, MIN_EPOCH_SLOTS};
+pub use cluster_tip::{ClusterTip, LagAlert, SlotCommitment};
+pub use commitment::{Commitment, ConfirmedObservations, HeldBlocks, CONFIRMED_OBSERVATION_WINDOW_SLOTS, HELD_BLOCK_WINDOW_SLOTS};
+pub use config::{
+    AccountInfoConfig, ClusterConfig, Config, ConfirmationMode, CoverageConfig, CreditsDriftConfig, DashboardConfig, EpochConfig, FilterDriftConfig, HealthzConfig, RpcSourceConfig, VoteSourceKind, InfluxConfig, JsonStreamConfig, LivenessConfig, LogFormat, LossCausesConfig, NotificationsConfig, TelegramConfig, PerformanceFilterConfig, PipelineConfig, PrometheusConfig, ReloadConfig, StalenessConfig, StateConfig, StatsConfig, StorageBackend, StorageConfig, ThresholdsConfig, TrackerConfig, TvcConfig,
+    VoteLogConfig, VoteLogFormat, VoteLogRotation, ENV_GRPC_URL, ENV_VOTE_ACCOUNT, ENV_X_TOKEN, env_overrides,
+};
+pub use config_reload::{ConfigOverrides, ConfigSource, SharedConfig, reload_config, spawn_config_watcher};
+pub use coverage::{CoverageSummary, VoteCoverage, VoteGap};
+pub use credits_check::{CreditsDrift, RpcCreditsClient, spawn_credits_monitor};
+pub use dashboard::{compose_panels, DashboardAction, DashboardRenderer, Panel, PoorEventFilter};
+pub use efficiency_trend::{EfficiencyBucket, EfficiencyTrend, DEFAULT_TREND_BUCKETS, DEFAULT_TREND_BUCKET_SECS};
+pub use endpoints::{EndpointPool, EndpointStatus, FAILOVER_PENALTY, MAX_FAILOVER_PENALTY, STABLE_CONNECTION};
+pub use epoch::{
+    EpochReport, EpochStats, EpochTracker, LatencyPercentiles, OnChainCredits, spawn_epoch_report, write_epoch_report,
+    DEFAULT_SLOTS_PER_EPOCH, EPOCH_REPORT_WORST_VOTES,
+};
 pub use error::{Result, VoteMonitorError};
+pub use events::{EventBus, SimpleLogger, DEFAULT_EVENT_BUFFER};
+pub use influx::{InfluxSink, spawn_influx_sink};
+pub use json_stream::{JsonStream, spawn_json_stream, JSON_STREAM_VERSION};
+pub use finalized_slots::{FinalizedSlots, SlotAdmission, DEFAULT_BLOCK_HORIZON_SLOTS};
+pub use first_run::{bootstrap_config, write_default_config, DEFAULT_CONFIG, MISSING_CONFIG_EXIT_CODE, SAMPLE_CONFIG};
+pub use leader_schedule::{
+    BlockProduction, JudgedLeaderSlot, LeaderAttribution, LeaderPoorVotes, LeaderSchedule, LeaderSlotCounts, LeaderSlotVotes, LeaderScheduleClient, spawn_leader_schedule_fetcher,
+};
+pub use filter_drift::{
+    Deviation, DriftChange, DriftMetric, EwmaBaseline, FilterDrift, FilterDriftDetector, spawn_filter_drift_watchdog,
+};
+pub use liveness::{VoteLiveness, spawn_liveness_watchdog, LIVENESS_EXIT_CODE};
+pub use logging::{init_logging, set_log_filters, set_log_mode, set_log_vote_account, VOTE_RECORD_TARGET};
+pub use loss_cause::{CauseTally, LossCause, LostCredits, classify_loss};
+pub use memory::{MemoryUsage, MemoryComponent};
 pub use performance::{
-    ConfirmedVote, PerformanceStats, TvcPerformanceLevel, PoorPerformanceEvent,
+    BatchedEventWriter, ConfirmationKind, ConfirmationSource, ConfirmedVote, ConfirmedVoteFollowUp, EventWriterMetrics, EventWriterSnapshot, LandingOffsets, PerformanceStats, TvcPerformanceLevel, PoorPerformanceEvent,
+    SeverityHistogram, LatencyDistribution, DEFAULT_POOR_VOTES_WINDOW, LATENCY_BUCKETS, TvcHistogram, HistogramScale, TVC_BUCKETS,
+    StatusSegment, StatusTimeline, performance_status_for, save_performance_events,
     calculate_tvc_credits_from_latency, calculate_tvc_credits, categorize_tvc_performance,
-    format_duration, format_number, Slot,
-    VOTE_CREDITS_GRACE_SLOTS, VOTE_CREDITS_MAXIMUM_PER_SLOT,
+    format_duration, format_number, sorted_percentile, Slot, RECENT_FAILED_VOTES, RecentWindowSummary, DEFAULT_RECENT_VOTES_WINDOW, DEFAULT_LATENCY_WINDOW_SECS,
+    VOTE_CREDITS_GRACE_SLOTS, VOTE_CREDITS_MAXIMUM_PER_SLOT, LANDING_OFFSET_BUCKETS,
+};
+pub use monitor::{Monitor, MonitorBuilder};
+pub use network::{expected_vote_rate, Explorer, Network, NetworkProfile, DEFAULT_SLOT_DURATION_MS, SIGNATURE_PLACEHOLDER};
+pub use notify::{AlertDetector, Notification, NotificationKind, Severity, spawn_notifier};
+pub use performance_log::{
+    PruneSummary, performance_log_file, prune_performance_logs, spawn_performance_event_writer, spawn_performance_log_pruner,
+    PERFORMANCE_EVENT_CHANNEL_CAPACITY,
+};
+pub use pipeline::{
+    Pipeline, UpdateReceivers, UpdateSenders, create_confirmed_blocks_request, create_subscription_request, slot_commitment, spawn_block_task,
+    spawn_stream_task, spawn_transaction_task, update_channels, forward_updates, spawn_resubscribing_stream_task,
+    StreamEnd, UPDATE_CHANNEL_CAPACITY, RESUBSCRIBE_BACKOFF, MAX_RESUBSCRIBE_BACKOFF,
+};
+pub use process_metrics::{ProcessMetrics, ProcessSampler};
+pub use prometheus::{spawn_pushgateway, MetricsRegistry, PUSH_JOB, TEXT_FORMAT};
+pub use ring_buffer::RingBuffer;
+pub use rolling_windows::{RollingWindows, ROLLING_BUCKET, ROLLING_BUCKETS, ROLLING_WINDOWS};
+pub use rpc::{EpochInfo, RpcClient};
+pub use rpc_source::{RpcVoteSource, VoteStateVotes, vote_state_tower};
+pub use signature_display::{SignatureDisplay, osc52_copy, truncate_visible, visible_width, SHORT_SIGNATURE_LEN};
+pub use state::{SessionState, STATE_VERSION};
+pub use stats_dump::{StatsCommand, StatsDump, spawn_stats_commands, write_stats_dump, DEFAULT_SNAPSHOT_DIR};
+#[cfg(unix)]
+pub use stats_dump::spawn_signal_listener;
+pub use status_api::{StatsSnapshot, StatusApiConfig, spawn_status_api, STATUS_DASHBOARD_SIZE};
+pub use stream_health::{StreamHealth, StreamStaleness, UpdateKind, spawn_stream_watchdog, STALE_MIN_SLOTS};
+pub use theme::{DashboardTheme, Theme, MARKER_WIDTH};
+pub use time_window::TimeWindow;
+pub use tower_root::{TowerRoot, TowerRootSnapshot, TowerUpdate, DEFAULT_ROOT_LAG_ALERT_SLOTS};
+pub use storage::{
+    SqliteStore, SqliteWriter, StorageBatch, StorageRecord, StoredVotes, is_sqlite_file, read_database,
+    spawn_sqlite_writer, SCHEMA_VERSION, STORAGE_CHANNEL_CAPACITY,
+};
+pub use streaks::{OptimalStreaks, StreakBreak, StreakSnapshot, DEFAULT_STREAK_ALERT_VOTES};
+pub use vote_account::{AuthorizedVoterChange, AuthorizedVoterTracker, VoteAccountInfo, parse_vote_state, verify_vote_account};
+pub use vote_log::{VoteLogRecord, VoteLogWriter, spawn_vote_log_writer};
+pub use vote_parse::{
+    ParsedVote, ParsedVoteTx, VoteInstructionKind, VoteSlotInfo, decode_vote_transaction, decode_vote_transaction_bytes,
+    parse_vote_instruction_data, VOTE_PROGRAM_ID,
 };
 pub use vote_tracker::{
-    VoteTracker, VoteSlotInfo, PendingVote, VoteTrackerStats,
-    parse_vote_instruction_data, process_vote_transaction, process_finalized_block,
-    VOTE_PROGRAM_ID,
+    VoteTracker, PendingVote, VoteTrackerStats, MissedVote, FailedVote, ProcessedVoteTransaction, ConfirmedSlotSet,
+    process_vote_transaction, process_finalized_block, process_finalized_slot,
+    UNMATCHED_CONFIRMATION_WINDOW_SLOTS, DEFAULT_MISSED_VOTE_CUTOFF_SLOTS,
+    DEFAULT_CONFIRMED_CAPACITY, DEFAULT_DUPLICATE_WINDOW_SLOTS, DEFAULT_PENDING_CLEANUP_INTERVAL_SLOTS,
+    DEFAULT_PENDING_MAX_AGE_SLOTS, DEFAULT_MAX_PENDING_VOTES,
 };
+pub use vote_tracker_actor::{VoteTrackerHandle, vote_tracker_actor};
+pub use votes_sent::{SilenceChange, VotesSent, VotesSentSnapshot, VOTES_SENT_BUCKET, VOTES_SENT_BUCKETS, VOTES_SENT_SILENCE};
+pub use vote_source::{GrpcSource, RunningSource, SourceContext, VoteSource, vote_source};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub async fn log_simple_transaction(stats: &PerformanceStats, confirmed_vote: &ConfirmedVote) {
-    let efficiency = stats.calculate_efficiency();
-    
-    log::info!(
-        "vote confirmed: slot {} → latency {} → {} TVC | TX: https://solscan.io/tx/{}", 
-        confirmed_vote.voted_slot, 
-        confirmed_vote.latency, 
-        confirmed_vote.tvc_credits,
-        confirmed_vote.signature
-    );
-    log::info!(
-        "session stats: {} votes, {:.1}% efficiency, {} total tvc earned",
-        stats.total_transactions(), 
-        efficiency, 
-        stats.total_tvc_earned()
-    );
-    log::info!("---");
-}
-
-pub fn print_banner() {
-    println!("solana monitor v{}", VERSION);
-    println!();
-}
+/// short commit hash of the build, "unknown" outside a git checkout
+pub const GIT_HASH: &str = env!("VOTEPERFX_GIT_HASH");
 
-pub fn print_help(program_name: &str) {
-    print_banner();
-    println!("usage:");
-    println!("    {} [options]", program_name);
-    println!();
-    println!("options:");
-    println!("    --dashboard    interactive dashboard with real-time metrics (default)");
-    println!("    --simple       simple cli logging mode");
-    println!("    --help, -h     show this help message");
-    println!();
-    println!("configuration:");
-    println!("    config.toml    all configuration including:");
-    println!("                   - grpc_url: yellowstone grpc endpoint");
-    println!("                   - vote_account: vote account to monitor");
-    println!("                   - performance_logging: logging filters");
-    println!();
-    println!("for more information, see: https://github.com/1000xsh/voteperfx");
-}
+/// rfc3339 utc build time, SOURCE_DATE_EPOCH when set
+pub const BUILD_TIME: &str = env!("VOTEPERFX_BUILD_TIME");
 
+/// version, commit and build time as printed by --version
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"), " (", env!("VOTEPERFX_GIT_HASH"), " ", env!("VOTEPERFX_BUILD_TIME"), ")"
+);
 
-pub fn init_logging(simple_mode: bool) {
-    if simple_mode {
-        std::env::set_var("RUST_LOG", "info");
-    } else {
-        std::env::set_var("RUST_LOG", "warn");
-    }
-    pretty_env_logger::init();
-}
\ No newline at end of file
+/// startup banner with the commit, on stderr so --json-stream and
+/// --metrics-stdout keep stdout to themselves
+pub fn print_banner() {
+    eprintln!("solana monitor v{} ({})", VERSION, GIT_HASH);
+    eprintln!();
+}
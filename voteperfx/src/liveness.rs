@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::config::LivenessConfig;
+use crate::performance::PerformanceStats;
+use crate::stream_health::StreamHealth;
+
+/// exit code of the binary when no vote was confirmed for `liveness.exit_secs`
+pub const LIVENESS_EXIT_CODE: i32 = 3;
+
+/// how often the liveness watchdog looks at the vote count
+const LIVENESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// what the liveness watchdog did, shared with the monitor
+#[derive(Debug, Default)]
+pub struct VoteLiveness {
+    resubscribes: AtomicU64,
+    // connected time without a confirmed vote when the monitor gave up, 0 while it runs
+    gave_up_after_ms: AtomicU64,
+}
+
+impl VoteLiveness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// resubscriptions because no vote was confirmed
+    pub fn resubscribes(&self) -> u64 {
+        self.resubscribes.load(Ordering::Relaxed)
+    }
+
+    /// connected time without a confirmed vote that stopped the monitor,
+    /// none while it runs
+    pub fn gave_up_after(&self) -> Option<Duration> {
+        match self.gave_up_after_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+}
+
+/// resubscribe, and eventually stop the monitor, when no vote is confirmed
+///
+/// counts the time the stream is connected since the last confirmed vote,
+/// pings and other updates do not reset it. every `resubscribe_secs` of it
+/// the subscription is re-established, after `exit_secs` `stop` closes the
+/// pipeline and `VoteLiveness::gave_up_after` tells why. a validator that
+/// stopped voting looks the same as a blind monitor.
+pub fn spawn_liveness_watchdog(
+    config: LivenessConfig,
+    stats: Arc<RwLock<PerformanceStats>>,
+    health: Arc<StreamHealth>,
+    liveness: Arc<VoteLiveness>,
+    stop: watch::Sender<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let resubscribe_after = (config.resubscribe_secs > 0).then(|| Duration::from_secs(config.resubscribe_secs));
+        let exit_after = (config.exit_secs > 0).then(|| Duration::from_secs(config.exit_secs));
+        let mut interval = tokio::time::interval(LIVENESS_INTERVAL);
+        let mut votes = stats.read().await.total_transactions();
+        let mut silent = Duration::ZERO;
+        let mut resubscribes = 0;
+        let mut last_tick = Instant::now();
+
+        loop {
+            interval.tick().await;
+            let elapsed = last_tick.elapsed();
+            last_tick = Instant::now();
+
+            let current = stats.read().await.total_transactions();
+            if current != votes {
+                if resubscribes > 0 {
+                    info!("votes confirmed again after {}s without one", silent.as_secs());
+                }
+                votes = current;
+                silent = Duration::ZERO;
+                resubscribes = 0;
+                continue;
+            }
+            // a dropped stream is the resubscribing stream task's to replace
+            if !health.is_connected() {
+                continue;
+            }
+            silent += elapsed;
+
+            if exit_after.is_some_and(|after| silent >= after) {
+                error!(
+                    "no confirmed vote for {}s while connected, stopping (liveness.exit_secs)",
+                    silent.as_secs()
+                );
+                liveness.gave_up_after_ms.store((silent.as_millis() as u64).max(1), Ordering::Relaxed);
+                stop.send_replace(true);
+                break;
+            }
+            if let Some(after) = resubscribe_after {
+                if silent >= after * (resubscribes + 1) {
+                    resubscribes += 1;
+                    liveness.resubscribes.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        "no confirmed vote for {}s while connected, re-establishing the subscription",
+                        silent.as_secs()
+                    );
+                    health.request_reconnect();
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,198 @@
+// builds with the tracing feature log through tracing_logging instead
+#![cfg_attr(feature = "tracing", allow(dead_code, unused_imports))]
+
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use pretty_env_logger::env_logger::fmt::{Color, Formatter};
+use serde_json::{Map, Value};
+
+use crate::config::LogFormat;
+use crate::signature_display::truncate_visible;
+
+/// target of the one-record-per-vote lines, their message is a json object
+/// whose fields are merged into the record in json mode
+pub const VOTE_RECORD_TARGET: &str = "voteperfx::vote";
+
+/// builds the logger for a filter string, kept so the filter can be swapped later
+type LoggerFactory = Box<dyn Fn(&str) -> (Box<dyn Log>, LevelFilter) + Send + Sync>;
+
+/// forwards to an env_logger built from the current filter
+struct ReloadableLogger {
+    inner: RwLock<Box<dyn Log>>,
+    build: LoggerFactory,
+    /// mode default used when the config sets no log_level, see `set_log_mode`
+    default_filters: RwLock<String>,
+    /// the config's log_level, none for the mode default
+    filters: RwLock<Option<String>>,
+}
+
+impl ReloadableLogger {
+    fn rebuild(&self) {
+        let filters = self.filters.read().unwrap_or_else(PoisonError::into_inner).clone();
+        let default_filters = self.default_filters.read().unwrap_or_else(PoisonError::into_inner).clone();
+        let (inner, max_level) = (self.build)(filters.as_deref().unwrap_or(&default_filters));
+        *self.inner.write().unwrap_or_else(PoisonError::into_inner) = inner;
+        log::set_max_level(max_level);
+    }
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).flush();
+    }
+}
+
+static LOGGER: OnceLock<ReloadableLogger> = OnceLock::new();
+
+/// vote account of json records, see `set_log_vote_account`
+static VOTE_ACCOUNT: RwLock<String> = RwLock::new(String::new());
+
+/// widest target logged so far, targets are padded to it like pretty_env_logger does
+static TARGET_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// install the logger, `log_level` uses RUST_LOG syntax and defaults to info
+/// in simple mode and warn in dashboard mode
+///
+/// records go to stderr, stdout is left to --metrics-stdout and --json-stream.
+/// text lines are cut to `line_width` cells when set, json records never are.
+/// builds with the tracing feature install `tracing_logging::init` instead.
+#[cfg(feature = "tracing")]
+pub fn init_logging(
+    simple_mode: bool,
+    log_level: Option<&str>,
+    format: LogFormat,
+    _vote_account: &str,
+    _line_width: Option<usize>,
+) {
+    crate::tracing_logging::init(simple_mode, log_level, format);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub fn init_logging(
+    simple_mode: bool,
+    log_level: Option<&str>,
+    format: LogFormat,
+    vote_account: &str,
+    line_width: Option<usize>,
+) {
+    let default_filters = mode_filters(simple_mode).to_string();
+    set_log_vote_account(vote_account);
+    let build: LoggerFactory = Box::new(move |filters| {
+        let mut builder = pretty_env_logger::formatted_builder();
+        builder.parse_filters(filters);
+        builder.target(pretty_env_logger::env_logger::Target::Stderr);
+        if format == LogFormat::Json {
+            builder.format(move |buf, record| {
+                let line = json_record(record, &VOTE_ACCOUNT.read().unwrap_or_else(PoisonError::into_inner));
+                writeln!(buf, "{}", line)
+            });
+        } else if let Some(width) = line_width {
+            builder.format(move |buf, record| {
+                let line = text_record(buf, record);
+                writeln!(buf, "{}", truncate_visible(&line, width, "…"))
+            });
+        }
+        let logger = builder.build();
+        let max_level = logger.filter();
+        (Box::new(logger), max_level)
+    });
+
+    let (inner, max_level) = build(log_level.unwrap_or(&default_filters));
+    let logger = LOGGER.get_or_init(|| ReloadableLogger {
+        inner: RwLock::new(inner),
+        build,
+        default_filters: RwLock::new(default_filters),
+        filters: RwLock::new(log_level.map(str::to_string)),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// the vote account json records carry from now on, after an account switch
+pub fn set_log_vote_account(vote_account: &str) {
+    *VOTE_ACCOUNT.write().unwrap_or_else(PoisonError::into_inner) = vote_account.to_string();
+}
+
+/// swap the log filter of the logger installed by `init_logging`, none
+/// restores the mode default. no-op before `init_logging`.
+pub fn set_log_filters(log_level: Option<&str>) {
+    #[cfg(feature = "tracing")]
+    crate::tracing_logging::set_log_filters(log_level);
+    let Some(logger) = LOGGER.get() else { return };
+    *logger.filters.write().unwrap_or_else(PoisonError::into_inner) = log_level.map(str::to_string);
+    logger.rebuild();
+}
+
+/// switch the mode default between simple mode and the dashboard, a
+/// configured log_level stays. no-op before `init_logging`.
+pub fn set_log_mode(simple_mode: bool) {
+    #[cfg(feature = "tracing")]
+    crate::tracing_logging::set_log_mode(simple_mode);
+    let Some(logger) = LOGGER.get() else { return };
+    *logger.default_filters.write().unwrap_or_else(PoisonError::into_inner) = mode_filters(simple_mode).to_string();
+    logger.rebuild();
+}
+
+/// info in simple mode, warn under the dashboard
+pub(crate) fn mode_filters(simple_mode: bool) -> &'static str {
+    if simple_mode { "info" } else { "warn" }
+}
+
+/// the pretty_env_logger line, ` INFO  voteperfx::events > message`, as a string
+/// so it can be cut before it is written
+fn text_record(buf: &mut Formatter, record: &Record) -> String {
+    let (color, label) = match record.level() {
+        Level::Trace => (Color::Magenta, "TRACE"),
+        Level::Debug => (Color::Blue, "DEBUG"),
+        Level::Info => (Color::Green, "INFO "),
+        Level::Warn => (Color::Yellow, "WARN "),
+        Level::Error => (Color::Red, "ERROR"),
+    };
+    let target = record.target();
+    let width = TARGET_WIDTH.fetch_max(target.len(), Ordering::Relaxed).max(target.len());
+
+    let mut level_style = buf.style();
+    level_style.set_color(color);
+    let mut target_style = buf.style();
+    target_style.set_bold(true);
+    format!(
+        " {} {} > {}",
+        level_style.value(label),
+        target_style.value(format!("{:<width$}", target, width = width)),
+        record.args()
+    )
+}
+
+/// one json object per record with rfc3339 utc timestamps
+fn json_record(record: &log::Record, vote_account: &str) -> Value {
+    let mut fields = Map::new();
+    fields.insert("timestamp".to_string(), Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true).into());
+    fields.insert("level".to_string(), record.level().as_str().to_lowercase().into());
+    fields.insert("target".to_string(), record.target().into());
+    fields.insert("vote_account".to_string(), vote_account.into());
+
+    let message = record.args().to_string();
+    match serde_json::from_str::<Value>(&message) {
+        Ok(Value::Object(record_fields)) if record.target() == VOTE_RECORD_TARGET => {
+            fields.extend(record_fields);
+        }
+        _ => {
+            fields.insert("message".to_string(), message.into());
+        }
+    }
+
+    Value::Object(fields)
+}
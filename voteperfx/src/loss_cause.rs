@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::LossCausesConfig;
+use crate::performance::ConfirmedVote;
+
+/// heuristic reason a vote earned less than the maximum credits, see `classify_loss`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LossCause {
+    /// the vote transaction landed many slots after the voted slot
+    LandingDelay,
+    /// the block the vote was confirmed from came long after it landed
+    FinalizationStall,
+    /// the voted slot is next to a leader window of the monitored validator
+    LeaderAdjacency,
+    Unknown,
+}
+
+impl LossCause {
+    pub const ALL: [Self; 4] = [Self::LandingDelay, Self::FinalizationStall, Self::LeaderAdjacency, Self::Unknown];
+
+    /// csv and database spelling, the same as in json
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LandingDelay => "landing_delay",
+            Self::FinalizationStall => "finalization_stall",
+            Self::LeaderAdjacency => "leader_adjacency",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|cause| cause.name() == name)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::LandingDelay => "high landing delay",
+            Self::FinalizationStall => "finalization stall",
+            Self::LeaderAdjacency => "leader-slot adjacency",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// cause of a vote below max credits
+///
+/// leader adjacency goes first, our own leader window explains a late
+/// landing. a finalization stall goes before the landing delay for the
+/// same reason. votes without a landed slot can only be blamed on the
+/// leader window or nothing.
+pub fn classify_loss(vote: &ConfirmedVote, near_own_leader_slot: bool, config: &LossCausesConfig) -> LossCause {
+    if near_own_leader_slot {
+        return LossCause::LeaderAdjacency;
+    }
+    let Some(landed_slot) = vote.landed_slot else {
+        return LossCause::Unknown;
+    };
+    let stall = vote.finalized_slot.saturating_sub(landed_slot);
+    if config.finalization_stall_slots > 0 && stall > config.finalization_stall_slots {
+        return LossCause::FinalizationStall;
+    }
+    let delay = landed_slot.saturating_sub(vote.voted_slot);
+    if config.landing_delay_slots > 0 && delay > config.landing_delay_slots {
+        return LossCause::LandingDelay;
+    }
+    LossCause::Unknown
+}
+
+/// votes and credits lost for one cause
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CauseTally {
+    pub votes: u64,
+    pub credits: u64,
+}
+
+/// credits lost below max per vote, by cause, this session
+///
+/// missed votes are counted on their own, only confirmed votes are here.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LostCredits {
+    pub landing_delay: CauseTally,
+    pub finalization_stall: CauseTally,
+    pub leader_adjacency: CauseTally,
+    pub unknown: CauseTally,
+}
+
+impl LostCredits {
+    pub fn record(&mut self, cause: LossCause, credits: u64) {
+        let tally = self.tally_mut(cause);
+        tally.votes += 1;
+        tally.credits += credits;
+    }
+
+    pub fn get(&self, cause: LossCause) -> CauseTally {
+        match cause {
+            LossCause::LandingDelay => self.landing_delay,
+            LossCause::FinalizationStall => self.finalization_stall,
+            LossCause::LeaderAdjacency => self.leader_adjacency,
+            LossCause::Unknown => self.unknown,
+        }
+    }
+
+    fn tally_mut(&mut self, cause: LossCause) -> &mut CauseTally {
+        match cause {
+            LossCause::LandingDelay => &mut self.landing_delay,
+            LossCause::FinalizationStall => &mut self.finalization_stall,
+            LossCause::LeaderAdjacency => &mut self.leader_adjacency,
+            LossCause::Unknown => &mut self.unknown,
+        }
+    }
+
+    pub fn total_credits(&self) -> u64 {
+        LossCause::ALL.iter().map(|&cause| self.get(cause).credits).sum()
+    }
+
+    /// causes with lost credits, most credits first
+    pub fn ranked(&self) -> Vec<(LossCause, CauseTally)> {
+        let mut ranked: Vec<(LossCause, CauseTally)> = LossCause::ALL
+            .into_iter()
+            .map(|cause| (cause, self.get(cause)))
+            .filter(|(_, tally)| tally.votes > 0)
+            .collect();
+        ranked.sort_by_key(|(_, tally)| std::cmp::Reverse(tally.credits));
+        ranked
+    }
+
+    /// `high landing delay: 120 (75.0%) | unknown: 40 (25.0%)`, none before
+    /// the first vote below max credits
+    pub fn summary(&self) -> Option<String> {
+        let total = self.total_credits();
+        let ranked = self.ranked();
+        if ranked.is_empty() {
+            return None;
+        }
+        let parts: Vec<String> = ranked
+            .into_iter()
+            .map(|(cause, tally)| {
+                let share = if total == 0 { 0.0 } else { tally.credits as f64 / total as f64 * 100.0 };
+                format!("{}: {} ({:.1}%)", cause.label(), tally.credits, share)
+            })
+            .collect();
+        Some(parts.join(" | "))
+    }
+}
@@ -1,53 +1,130 @@
-use std::env;
-use std::sync::Arc;
-// use std::sync::atomic::{AtomicU64, Ordering};
+use std::io::{self, IsTerminal};
 use std::time::Duration;
 
-use futures_util::SinkExt;
-use grpc_client::YellowstoneGrpc;
-use log::{error, info, warn};
-use tokio::sync::{mpsc, RwLock};
+use clap::Parser;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
+use log::{debug, error, info, warn};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, watch};
 use tokio_stream::StreamExt;
-use yellowstone_grpc_proto::geyser::{
-    CommitmentLevel, SubscribeRequest, 
-    SubscribeRequestFilterTransactions, SubscribeRequestFilterBlocks,
-    SubscribeRequestPing, subscribe_update::UpdateOneof,
-};
 
+use voteperfx::message::SystemEvent;
 use voteperfx::{
-    Config, DashboardRenderer, PerformanceStats, VoteTracker,
-    log_simple_transaction, print_help, init_logging,
-    process_vote_transaction, process_finalized_block,
-    Result, VoteMonitorError,
+    AnalyzeArgs, Cli, Command, Config, ConfigSource, DashboardAction, DashboardRenderer, Monitor, SimpleLogger, SwitchAccountArgs,
+    request_account_switch, set_log_mode, set_log_vote_account,
+    CompareRange, RangeVotes, analyze_votes, compare_votes, init_logging, print_comparison, print_report, read_votes,
+    print_banner, resolve_inputs,
+    Result, SignatureDisplay, StorageBackend, Theme, VoteMonitorError, VoteSourceKind, LIVENESS_EXIT_CODE, LONG_VERSION,
+    MISSING_CONFIG_EXIT_CODE, SAMPLE_CONFIG, bootstrap_config, env_overrides, vote_source, write_default_config,
 };
 
+/// how often simple mode logs the process metrics, at debug
+const PROCESS_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let simple_mode = args.contains(&"--simple".to_string());
-    
-    if args.contains(&"--help".to_string()) || args.contains(&"-h".to_string()) {
-        print_help(&args[0]);
+    let cli = Cli::parse();
+    // without a tty (systemd, docker without -t) the dashboard would write
+    // escape codes into the log, log votes instead
+    let no_terminal = !cli.simple_mode() && !io::stdout().is_terminal();
+    let simple_mode = cli.simple_mode() || no_terminal;
+
+    if cli.check_config {
+        return check_config(&cli).await;
+    }
+    if let Some(ref path) = cli.write_default_config {
+        write_default_config(path).await?;
+        println!("wrote the default config to {}", path.display());
         return Ok(());
     }
+    match cli.command {
+        Some(Command::Analyze(ref args)) => return analyze(&cli, args).await,
+        Some(Command::SwitchAccount(ref args)) => return switch_account(&cli, args).await,
+        None => {}
+    }
 
-    init_logging(simple_mode);
+    print_banner();
 
-    let config = Config::load_or_default("config.toml").await;
+    // the log format and vote account come from the config, so load it first
+    // and report the outcome once logging is up
+    let loaded = Config::load_from_file(&cli.config).await;
+    let config_missing = loaded.is_err() && !cli.config.exists();
+    let mut config = loaded.as_ref().cloned().unwrap_or_default();
+    // a no-op after a successful load, the defaults still take the environment
+    config.apply_env_overrides();
+    cli.apply_overrides(&mut config);
+
+    // first run: ask for what the environment and flags left out, or say
+    // what to put in the file instead of starting with nothing to monitor
+    let mut config_written = false;
+    if config_missing && (!config.has_source() || config.vote_account.is_empty()) {
+        if io::stdin().is_terminal() {
+            config_written = bootstrap_config(&mut config, &cli.config).await?;
+        } else {
+            eprintln!("{} not found, create it with at least:\n\n{}\n", cli.config.display(), SAMPLE_CONFIG);
+            eprintln!("or write a commented one with every setting: voteperfx --write-default-config {}", cli.config.display());
+            std::process::exit(MISSING_CONFIG_EXIT_CODE);
+        }
+    }
+
+    // url mode cuts simple mode lines to the terminal so links don't wrap, the
+    // dashboard can switch to simple mode at runtime
+    let signature_display = config.dashboard.signature_display;
+    let line_width = (signature_display == SignatureDisplay::Url && io::stderr().is_terminal())
+        .then(|| crossterm::terminal::size().ok())
+        .flatten()
+        .map(|(columns, _)| usize::from(columns));
+    init_logging(simple_mode, config.log_level.as_deref(), config.log_format, &config.vote_account, line_width);
+    let config_loaded = match loaded {
+        Ok(_) => {
+            info!("configuration loaded from {}", cli.config.display());
+            true
+        }
+        Err(_) if config_written => {
+            info!("configuration written to {}", cli.config.display());
+            true
+        }
+        Err(_) if config_missing => {
+            warn!("{} not found, using defaults", cli.config.display());
+            false
+        }
+        Err(e) => {
+            warn!("failed to load {} ({}), using defaults", cli.config.display(), e);
+            false
+        }
+    };
+    let from_env = env_overrides();
+    if !from_env.is_empty() {
+        info!("config overridden from the environment: {}", from_env.join(", "));
+    }
+    
+    if no_terminal {
+        info!("stdout is not a terminal, falling back to simple mode (--headless skips this check)");
+    }
     
-    let grpc_url = config.grpc_url.clone();
-    let vote_account = config.vote_account.clone();
+    for warning in config.warnings() {
+        warn!("config: {}", warning);
+    }
     
-    if grpc_url.is_empty() || vote_account.is_empty() {
-        error!("missing required configuration in config.toml");
-        error!("please ensure grpc_url and vote_account are set");
+    if !config.has_source() || config.vote_account.is_empty() {
+        error!("missing required configuration in {}", cli.config.display());
+        let setting = match config.source {
+            VoteSourceKind::Grpc => {
+                error!("please ensure grpc_url (or grpc_urls) and vote_account are set, or pass --grpc-url and --vote-account");
+                "grpc_url"
+            }
+            VoteSourceKind::Rpc => {
+                error!("source = \"rpc\" needs rpc_url and vote_account");
+                "rpc_url"
+            }
+        };
         return Err(VoteMonitorError::Config(
-            "missing grpc_url or vote_account in config.toml".to_string()
+            format!("missing {} or vote_account in {}", setting, cli.config.display())
         ));
     }
 
-    info!("vote monitor starting...");
-    info!("monitoring vote account: {}", vote_account);
+    info!("vote monitor v{} starting...", LONG_VERSION);
+    info!("monitoring vote account: {}", config.vote_account);
     
     if config.performance_logging.enabled {
         info!("performance logging enabled: {}", config.performance_logging.describe_filters());
@@ -58,225 +135,393 @@ async fn main() -> Result<()> {
     if simple_mode {
         info!("simple cli logging mode");
     } else {
-        info!("interactive dashboard mode (press ctrl+c to quit)");
+        info!("interactive dashboard mode (press q to quit, l or SIGHUP to switch to simple mode logging)");
     }
 
-    let grpc = YellowstoneGrpc::new(grpc_url, None);
-    let client = grpc.build_client().await
-        .map_err(|e| VoteMonitorError::GrpcConnection(format!("{:?}", e)))?;
+    let explorer = config.network_profile().explorer;
+    let refresh = Duration::from_millis(config.dashboard.refresh_ms);
+    let theme = Theme::new(config.dashboard.theme);
+    let show_process = config.dashboard.show_process;
+    let histogram_scale = config.dashboard.histogram_scale;
+    // the account a switch-account request moved to, it outlasts config reloads
+    let mut switched: Option<String> = None;
 
-    let subscribe_request = create_subscription_request(&vote_account);
+    // a switch-account request stops the monitor, the next one starts on the
+    // new account with a fresh session
+    loop {
+        let simple_logger = SimpleLogger::new()
+            .with_tvc_config(config.tvc)
+            .with_log_format(config.log_format)
+            .with_signature_display(signature_display)
+            .with_explorer(explorer.clone());
 
-    let (mut subscribe_tx, mut stream) = client
-        .lock()
-        .await
-        .subscribe_with_request(Some(subscribe_request))
-        .await
-        .map_err(|e| VoteMonitorError::GrpcConnection(format!("{:?}", e)))?;
+        // grpc, or json-rpc polling on machines without geyser access
+        let source = vote_source(&config)?;
+        let source_kind = config.source;
 
-    info!("connected to gRPC stream, processing votes...");
-
-    // create shared state with arc<rwlock<>> for better async performance
-    // rwlock allows multiple concurrent readers
-    let vote_tracker = Arc::new(RwLock::new(VoteTracker::new()));
-    let stats = Arc::new(RwLock::new(PerformanceStats::new()));
-    let config = Arc::new(config);
+        // overridden values are validated here, the file was validated while loading
+        let mut builder = Monitor::builder().source(source).config(config.clone());
+        if config_loaded {
+            // reloads keep the command line flags on top of the file
+            let overrides = cli.clone();
+            let switched = switched.clone();
+            builder = builder.watch_config(ConfigSource::new(&cli.config, move |config: &mut Config| {
+                overrides.apply_overrides(config);
+                if let Some(ref vote_account) = switched {
+                    config.vote_account = vote_account.clone();
+                }
+            }));
+        }
+        let mut monitor = builder.build().await?;
+        // the dashboard turns it on while it is switched to simple mode
+        let (simple_output, simple_output_rx) = watch::channel(simple_mode);
+        simple_logger.with_active(simple_output_rx).spawn(monitor.subscribe_events());
+        if let Err(e) = monitor.start().await {
+            // tells a supervisor's restart loop apart from a config to fix
+            if e.is_retryable() {
+                error!("{} source unavailable, a restart may succeed: {}", source_kind.as_str(), e);
+            } else {
+                error!("cannot start, fix the configuration before restarting: {}", e);
+            }
+            monitor.shutdown().await;
+            return Err(e);
+        }
 
-    // bounded channels for async communication with backpressure
-    let (tx_sender, mut tx_receiver) = mpsc::channel(1000);
-    let (block_sender, mut block_receiver) = mpsc::channel(1000);
-    
-    // channel for dashboard cleanup signal
-    let (cleanup_tx, mut cleanup_rx) = mpsc::channel::<()>(1);
-
-    // clone references for tasks (more efficient than cloning arcs repeatedly)
-    let vote_tracker_tx = vote_tracker.clone();
-    let vote_tracker_block = vote_tracker.clone();
-    let stats_block = stats.clone();
-    let stats_dashboard = stats.clone();
-    let config_block = config.clone();
-    let vote_account_tx = vote_account.clone();
-    let vote_account_block = vote_account.clone();
-    let vote_account_dashboard = vote_account.clone();
-
-    let mut dashboard_renderer = if !simple_mode {
-        Some(DashboardRenderer::new())
-    } else {
-        None
-    };
+        let mut dashboard_renderer = if !simple_mode {
+            match DashboardRenderer::new() {
+                Ok(renderer) => Some(renderer
+                    .with_theme(theme)
+                    .with_process_panel(show_process)
+                    .with_histogram_scale(histogram_scale)
+                    .with_signature_display(signature_display)
+                    .with_explorer(explorer.clone())),
+                Err(e) => {
+                    monitor.shutdown().await;
+                    return Err(e);
+                }
+            }
+        } else {
+            None
+        };
 
-    // get updates and routes them to appropriate channels
-    let stream_task = tokio::spawn(async move {
-        while let Some(message) = stream.next().await {
-            match message {
-                Ok(msg) => {
-                    match msg.update_oneof {
-                        Some(UpdateOneof::Transaction(sut)) => {
-                            if let Err(e) = tx_sender.send(sut).await {
-                                warn!("transaction channel closed: {}, stopping stream", e);
-                                break;
-                            }
-                        }
-                        Some(UpdateOneof::Block(sub)) => {
-                            if let Err(e) = block_sender.send(sub).await {
-                                warn!("block channel closed: {}, stopping stream", e);
-                                break;
-                            }
-                        }
-                        Some(UpdateOneof::Ping(_ping)) => {
-                            // respond to ping to keep connection alive
-                            let ping_response = SubscribeRequest {
-                                ping: Some(SubscribeRequestPing { id: 1 }),
-                                ..Default::default()
-                            };
-                            if let Err(e) = subscribe_tx.send(ping_response).await {
-                                error!("failed to send ping response: {}", e);
-                                break;
-                            }
-                            log::debug!("responded to ping");
+        let shutdown_requested = match dashboard_renderer {
+            Some(ref mut renderer) => {
+                let quit = run_interactive(&monitor, renderer, refresh, &simple_output).await;
+                if let Err(e) = renderer.cleanup() {
+                    error!("failed to cleanup dashboard: {}", e);
+                }
+                quit
+            }
+            None => {
+                let mut process_interval = tokio::time::interval(PROCESS_SAMPLE_INTERVAL);
+                loop {
+                    tokio::select! {
+                        _ = monitor.closed() => break false,
+                        _ = tokio::signal::ctrl_c() => break true,
+                        _ = process_interval.tick() => {
+                            debug!("process: {}", monitor.sample_process().await.summary());
                         }
-                        _ => {} // ignore other update types
                     }
                 }
-                Err(error) => {
-                    error!("grpc stream error: {:?}", error);
-                    break;
-                }
             }
+        };
+
+        if shutdown_requested {
+            info!("shutdown signal received, generating final statistics...");
+        }
+        let liveness_failure = monitor.liveness_failure();
+        let next_account = monitor.switched_to().filter(|_| !shutdown_requested);
+        monitor.shutdown().await;
+        if let Some(next_account) = next_account {
+            info!("monitoring vote account: {}", next_account);
+            set_log_vote_account(&next_account);
+            config.vote_account = next_account.clone();
+            switched = Some(next_account);
+            continue;
+        }
+        if shutdown_requested {
+            info!("shutdown complete");
+        }
+        // a distinct code so a supervisor restarts a monitor that went blind
+        if let Some(silent) = liveness_failure {
+            error!(
+                "exiting with code {}: no vote confirmed in {}s connected to the grpc stream",
+                LIVENESS_EXIT_CODE, silent.as_secs()
+            );
+            std::process::exit(LIVENESS_EXIT_CODE);
         }
-        info!("gRPC stream task completed");
-    });
 
-    // processes incoming vote transactions and adds them as pending votes
-    let tx_task = tokio::spawn(async move {
-        while let Some(tx_update) = tx_receiver.recv().await {
-            let mut tracker = vote_tracker_tx.write().await;
-            if let Err(e) = process_vote_transaction(tx_update, &vote_account_tx, &mut tracker).await {
-                error!("error processing vote transaction: {}", e);
+        return Ok(());
+    }
+}
+
+/// why the dashboard or the simple mode logging in its place stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViewExit {
+    /// the monitor stopped on its own
+    Closed,
+    /// shutdown was requested
+    Quit,
+    /// `l` or SIGHUP, switch to the other view
+    Switch,
+}
+
+/// the dashboard, switched to simple mode logging and back with `l` or
+/// SIGHUP without losing the session. true when shutdown was requested
+async fn run_interactive(
+    monitor: &Monitor,
+    renderer: &mut DashboardRenderer,
+    refresh: Duration,
+    simple_output: &watch::Sender<bool>,
+) -> bool {
+    let mut terminal_events = EventStream::new();
+    let mut hangups = listen_for_hangups();
+
+    loop {
+        match run_dashboard(monitor, renderer, refresh, &mut terminal_events, &mut hangups).await {
+            ViewExit::Closed => return false,
+            ViewExit::Quit => return true,
+            ViewExit::Switch => {}
+        }
+
+        if let Err(e) = renderer.suspend() {
+            error!("failed to suspend dashboard: {}", e);
+        }
+        set_log_mode(true);
+        simple_output.send_replace(true);
+        info!("switched to simple mode logging, press l and enter or send SIGHUP to return to the dashboard");
+
+        match run_suspended(monitor, &mut terminal_events, &mut hangups).await {
+            ViewExit::Closed => return false,
+            ViewExit::Quit => return true,
+            ViewExit::Switch => {}
+        }
+
+        // quiet the vote lines first so nothing lands on the dashboard
+        simple_output.send_replace(false);
+        set_log_mode(false);
+        if let Err(e) = renderer.resume() {
+            error!("failed to resume dashboard: {}", e);
+        }
+    }
+}
+
+/// SIGHUP switches between the dashboard and simple mode logging
+#[cfg(unix)]
+fn listen_for_hangups() -> mpsc::Receiver<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (sender, receiver) = mpsc::channel(1);
+    tokio::spawn(async move {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                warn!("cannot listen for SIGHUP, switching views on signal disabled: {}", e);
+                return;
             }
+        };
+        while hangup.recv().await.is_some() {
+            // a switch still pending absorbs the signal
+            let _ = sender.try_send(());
         }
-        info!("transaction processing task completed");
     });
+    receiver
+}
 
-    // processes finalized blocks and handles dashboard updates
-    let dashboard_task = tokio::spawn(async move {
-        let mut render_interval = tokio::time::interval(Duration::from_millis(500));
-        
-        loop {
-            tokio::select! {
-                // handle cleanup signal
-                _ = cleanup_rx.recv() => {
-                    if let Some(ref renderer) = dashboard_renderer {
-                        if let Err(e) = renderer.cleanup_without_clear() {
-                            error!("failed to cleanup dashboard: {}", e);
-                        }
+#[cfg(not(unix))]
+fn listen_for_hangups() -> mpsc::Receiver<()> {
+    mpsc::channel(1).1
+}
+
+/// simple mode logging while the dashboard is suspended
+///
+/// the terminal is back in line mode, so `l` arrives with enter and ctrl+c
+/// as a signal.
+async fn run_suspended(
+    monitor: &Monitor,
+    terminal_events: &mut EventStream,
+    hangups: &mut mpsc::Receiver<()>,
+) -> ViewExit {
+    let mut process_interval = tokio::time::interval(PROCESS_SAMPLE_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = monitor.closed() => return ViewExit::Closed,
+            _ = tokio::signal::ctrl_c() => return ViewExit::Quit,
+            Some(()) = hangups.recv() => return ViewExit::Switch,
+            Some(event) = next_terminal_event(terminal_events) => {
+                if let Event::Key(key) = event {
+                    if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('l') {
+                        return ViewExit::Switch;
                     }
-                    break;
                 }
+            }
+            _ = process_interval.tick() => {
+                debug!("process: {}", monitor.sample_process().await.summary());
+            }
+        }
+    }
+}
+
+/// draw the dashboard until the monitor stops, the user quits or switches to
+/// simple mode
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "dashboard", skip_all))]
+async fn run_dashboard(
+    monitor: &Monitor,
+    renderer: &mut DashboardRenderer,
+    refresh: Duration,
+    terminal_events: &mut EventStream,
+    hangups: &mut mpsc::Receiver<()>,
+) -> ViewExit {
+    // the first tick draws right away, also after a switch back from simple mode
+    let mut render_interval = frame_interval(refresh);
+    let mut events = monitor.subscribe_events();
+
+    loop {
+        tokio::select! {
+            _ = monitor.closed() => return ViewExit::Closed,
+            // raw mode swallows ctrl+c, the dashboard's quit key covers it
+            _ = tokio::signal::ctrl_c() => return ViewExit::Quit,
+            Some(()) = hangups.recv() => return ViewExit::Switch,
+
+            Some(event) = next_terminal_event(terminal_events) => {
+                let action = match event {
+                    Event::Key(key) => renderer.handle_key(key),
+                    // draw the full frame at the new size right away
+                    Event::Resize(..) => {
+                        renderer.request_redraw();
+                        DashboardAction::Redraw
+                    }
+                    _ => continue,
+                };
                 
-                Some(block_update) = block_receiver.recv() => {
-                    let confirmed_votes = {
-                        let mut tracker = vote_tracker_block.write().await;
-                        match process_finalized_block(block_update, &vote_account_block, &mut tracker).await {
-                            Ok(votes) => votes,
-                            Err(e) => {
-                                error!("error processing finalized block: {}", e);
-                                continue;
-                            }
-                        }
-                    };
-                    
-                    // update performance stats
-                    if !confirmed_votes.is_empty() {
-                        let mut stats_guard = stats_block.write().await;
-                        for confirmed_vote in confirmed_votes {
-                            if simple_mode {
-                                log_simple_transaction(&stats_guard, &confirmed_vote).await;
-                            }
-                            
-                            if let Err(e) = stats_guard.add_confirmed_vote_with_config(
-                                confirmed_vote, 
-                                &vote_account_block, 
-                                &config_block.performance_logging
-                            ).await {
-                                error!("error saving performance event: {}", e);
-                            }
+                match action {
+                    DashboardAction::None => continue,
+                    DashboardAction::Quit => return ViewExit::Quit,
+                    DashboardAction::SimpleMode => return ViewExit::Switch,
+                    DashboardAction::ResetStats => {
+                        info!("stats reset requested from the dashboard");
+                        monitor.reset_stats().await;
+                    }
+                    DashboardAction::Redraw => {
+                        if renderer.shows_debug() {
+                            monitor.sample_tracker().await;
                         }
                     }
                 }
                 
-                // only in dashboard mode
-                _ = render_interval.tick() => {
-                    if let Some(ref mut renderer) = dashboard_renderer {
-                        let stats_guard = stats_dashboard.read().await;
-                        if let Err(e) = renderer.render(&stats_guard, &vote_account_dashboard).await {
-                            error!("dashboard render error: {}", e);
-                        }
+                // redraw right away instead of waiting for the next tick
+                render(monitor, renderer).await;
+            }
+            
+            event = events.recv() => match event {
+                Ok(SystemEvent::ConfigReloaded(config)) => {
+                    let refresh_ms = config.dashboard.refresh_ms;
+                    if render_interval.period() != Duration::from_millis(refresh_ms) {
+                        render_interval = frame_interval(Duration::from_millis(refresh_ms));
                     }
+                    renderer.notice("config reloaded");
+                    render(monitor, renderer).await;
                 }
-            }
-        }
-    });
+                // also resets from SIGUSR2 and POST /reset
+                Ok(SystemEvent::StatsReset(_)) => {
+                    renderer.notice("stats reset");
+                    render(monitor, renderer).await;
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return ViewExit::Closed,
+            },
 
-    info!("all processing tasks started - monitoring vote performance...");
-
-    tokio::select! {
-        _ = stream_task => {
-            info!("stream task completed");
-        },
-        _ = tx_task => {
-            info!("transaction processing task completed");
-        },
-        _ = dashboard_task => {
-            info!("dashboard task completed");
-        },
-        _ = tokio::signal::ctrl_c() => {
-            info!("shutdown signal received, generating final statistics...");
-            
-            // send cleanup signal to dashboard task
-            if cleanup_tx.send(()).await.is_err() {
-                error!("failed to send cleanup signal to dashboard task");
+            _ = render_interval.tick() => {
+                monitor.sample_process().await;
+                if renderer.shows_debug() {
+                    monitor.sample_tracker().await;
+                }
+                render(monitor, renderer).await;
             }
-            
-            // give dashboard task a moment to cleanup
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            // fix me
-            // print_final_statistics(&stats, &vote_account).await;
-            
-            info!("shutdown complete");
         }
     }
+}
+
+fn frame_interval(refresh: Duration) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(refresh);
+    // a slow terminal delays ticks, don't follow up with a burst of frames
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    interval
+}
+
+async fn render(monitor: &Monitor, renderer: &mut DashboardRenderer) {
+    let memory = monitor.memory_usage().await;
+    let stats_guard = monitor.read_stats().await;
+    if let Err(e) = renderer.render(&stats_guard, monitor.vote_account(), &memory).await {
+        error!("dashboard render error: {}", e);
+    }
+}
+
+/// next terminal event, none when the terminal stream failed
+async fn next_terminal_event(events: &mut EventStream) -> Option<Event> {
+    events.next().await.and_then(|event| event.ok())
+}
+
+/// validate the config file and report warnings without connecting
+async fn check_config(cli: &Cli) -> Result<()> {
+    let path = cli.config.as_path();
+    let mut config = Config::load_from_file(path).await?;
+    cli.apply_overrides(&mut config);
+    config.validate()?;
     
+    let warnings = config.warnings();
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    
+    println!("{}: ok ({} warnings)", path.display(), warnings.len());
     Ok(())
 }
 
-/// create the grpc subscription request for vote transactions and finalized blocks
-fn create_subscription_request(vote_account: &str) -> SubscribeRequest {
-    SubscribeRequest {
-        transactions: std::collections::HashMap::from([(
-            "vote_transactions".to_string(),
-            SubscribeRequestFilterTransactions {
-                vote: Some(true),
-                failed: Some(true),
-                signature: None,
-                account_include: vec![vote_account.to_string()],
-                account_exclude: vec![],
-                account_required: vec![],
-            },
-        )]),
-        blocks: std::collections::HashMap::from([(
-            "finalized_blocks".to_string(),
-            SubscribeRequestFilterBlocks {
-                account_include: vec![vote_account.to_string()],
-                include_transactions: Some(true),
-                include_accounts: Some(false),
-                include_entries: Some(false),
-            },
-        )]),
-        // fix me
-        commitment: Some(CommitmentLevel::Finalized.into()),
-        ..Default::default()
+/// send a switch-account request to the monitor serving http_listen
+async fn switch_account(cli: &Cli, args: &SwitchAccountArgs) -> Result<()> {
+    let config = Config::load_from_file(&cli.config).await?;
+    let Some(http_listen) = config.http_listen else {
+        return Err(VoteMonitorError::Config(format!("http_listen is not set in {}", cli.config.display())));
+    };
+    println!("{}", request_account_switch(http_listen, &args.vote_account).await?);
+    Ok(())
+}
+
+/// recompute stats from recorded logs and print the report
+async fn analyze(cli: &Cli, args: &AnalyzeArgs) -> Result<()> {
+    // tvc, epoch, log dir and storage settings come from the config, defaults when there is none
+    let config = Config::load_from_file(&cli.config).await.unwrap_or_default();
+
+    let paths = if !args.paths.is_empty() {
+        args.paths.clone()
+    } else if config.storage.backend == StorageBackend::Sqlite {
+        vec![config.storage.path.display().to_string()]
+    } else {
+        vec![config.performance_logging.performance_log_dir.display().to_string()]
+    };
+    if let [a, b] = args.compare.as_slice() {
+        let a = RangeVotes::load(CompareRange::parse(a)?, &paths).await?;
+        let b = RangeVotes::load(CompareRange::parse(b)?, &paths).await?;
+        let report = compare_votes(a, b, &config);
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print_comparison(&report);
+        }
+        return Ok(());
     }
+
+    let files = resolve_inputs(&paths)?;
+    let (votes, inputs) = read_votes(&files).await?;
+    let report = analyze_votes(votes, inputs, &config);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
 }
 
 // async fn print_final_statistics(stats: &Arc<RwLock<PerformanceStats>>, vote_account: &str) {
@@ -0,0 +1,57 @@
+use std::mem::size_of;
+
+/// approximate heap usage of one owner, derived from counts and element sizes
+#[derive(Debug, Clone)]
+pub struct MemoryComponent {
+    pub name: &'static str,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// memory accounting for the major owners
+///
+/// computed from counts and element sizes rather than allocator introspection,
+/// so the numbers are estimates that move in the right direction.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryUsage {
+    pub components: Vec<MemoryComponent>,
+}
+
+impl MemoryUsage {
+    pub fn new() -> Self {
+        Self { components: Vec::with_capacity(8) }
+    }
+
+    pub fn add(&mut self, name: &'static str, entries: usize, bytes: usize) {
+        self.components.push(MemoryComponent { name, entries, bytes });
+    }
+
+    pub fn extend(&mut self, other: MemoryUsage) {
+        self.components.extend(other.components);
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.components.iter().map(|c| c.bytes).sum()
+    }
+
+    /// biggest consumer, used when the soft budget is exceeded
+    pub fn largest(&self) -> Option<&MemoryComponent> {
+        self.components.iter().max_by_key(|c| c.bytes)
+    }
+}
+
+/// estimated heap size of a base58 signature string
+#[inline]
+pub fn signature_bytes() -> usize {
+    size_of::<String>() + 88
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
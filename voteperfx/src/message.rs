@@ -1,31 +1,83 @@
-use crate::performance::{ConfirmedVote, PoorPerformanceEvent, Slot};
-use crate::vote_tracker::{PendingVote, VoteTrackerStats};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::coverage::VoteGap;
+use crate::credits_check::CreditsDrift;
+use crate::filter_drift::{DriftMetric, FilterDrift};
+use crate::finalized_slots::SlotAdmission;
+use crate::memory::MemoryUsage;
+use crate::performance::{ConfirmedVote, PoorPerformanceEvent, Slot, StatsReset};
+use crate::streaks::StreakBreak;
+use crate::vote_tracker::{FailedVote, MissedVote, PendingVote, VoteTrackerStats};
 use tokio::sync::oneshot;
 
+/// requests handled by the vote tracker actor
 #[derive(Debug)]
 pub enum VoteCommand {
-    AddPending(PendingVote),
+    /// responds with votes matched against already seen block confirmations
+    AddPending {
+        vote: PendingVote,
+        respond_to: oneshot::Sender<Vec<ConfirmedVote>>,
+    },
     ConfirmVote {
-        signature: String,
+        signature: Arc<String>,
         voted_slot: Slot,
         finalized_slot: Slot,
+        /// unix seconds of the confirming block
+        block_time: Option<i64>,
         respond_to: oneshot::Sender<Option<ConfirmedVote>>,
     },
-    MarkSlotProcessed(Slot),
-    HasProcessedSlot {
+    CacheSignature {
+        signature_bytes: Vec<u8>,
+        /// none for a signature that is not 64 bytes
+        respond_to: oneshot::Sender<Option<Arc<String>>>,
+    },
+    /// dedup a finalized slot and mark it processed when accepted
+    AdmitFinalizedSlot {
+        slot: Slot,
+        block_height: Option<u64>,
+        respond_to: oneshot::Sender<SlotAdmission>,
+    },
+    /// expire unmatched confirmations and missed votes once a block is done
+    FinishBlock {
+        finalized_slot: Slot,
+        respond_to: oneshot::Sender<Vec<ConfirmedVote>>,
+    },
+    /// slot-status fallback: confirm votes whose transaction slot is now finalized
+    ConfirmFinalizedSlot {
+        finalized_slot: Slot,
+        respond_to: oneshot::Sender<Vec<ConfirmedVote>>,
+    },
+    /// confirm votes that landed in a finalized block the stream dropped
+    ConfirmDroppedBlock {
         slot: Slot,
-        respond_to: oneshot::Sender<bool>,
+        respond_to: oneshot::Sender<Vec<ConfirmedVote>>,
+    },
+    /// a vote program instruction failed to deserialize, `data` is its start
+    RecordParseFailure {
+        data: Vec<u8>,
+        error: String,
+    },
+    TakeMissedVotes {
+        respond_to: oneshot::Sender<Vec<MissedVote>>,
+    },
+    /// remove every pending vote, see `VoteTracker::drain_pending`
+    DrainPending {
+        respond_to: oneshot::Sender<Vec<MissedVote>>,
     },
     GetStats {
         respond_to: oneshot::Sender<VoteTrackerStats>,
     },
+    GetMemoryUsage {
+        respond_to: oneshot::Sender<MemoryUsage>,
+    },
     Cleanup,
 }
 
 #[derive(Debug)]
 pub enum StatsCommand {
     AddConfirmedVote {
-        vote: ConfirmedVote,
+        vote: Box<ConfirmedVote>,
         vote_account: String,
     },
     GetEfficiency {
@@ -60,19 +112,62 @@ pub enum StatsCommand {
     },
 }
 
+/// pipeline events published on the event bus
+///
+/// payloads are shared, so cloning an event per subscriber is cheap and never
+/// borrows tracker internals.
 #[derive(Debug, Clone)]
 pub enum SystemEvent {
-    VoteAdded(PendingVote),
-    VoteConfirmed(ConfirmedVote),
+    VoteAdded(Arc<PendingVote>),
+    VoteConfirmed(Arc<ConfirmedVote>),
     VoteMissed {
-        signature: String,
+        signature: Arc<String>,
         reason: String,
     },
-    PerformanceEvent(PoorPerformanceEvent),
+    /// vote transaction landed with an error and earns no credits
+    VoteFailed(Arc<FailedVote>),
+    PerformanceEvent(Arc<PoorPerformanceEvent>),
     SlotProcessed(Slot),
     CleanupCompleted {
         remaining_votes: usize,
     },
+    /// local and on-chain credits disagree by more than the configured threshold
+    CreditsMismatch(Arc<CreditsDrift>),
+    /// finalized slots without a vote from the monitored account crossed the alert threshold
+    VoteGap(VoteGap),
+    /// subscribed to the grpc stream
+    StreamConnected {
+        endpoint: String,
+    },
+    /// grpc stream failed or was closed by the server
+    StreamDisconnected {
+        reason: String,
+    },
+    /// live settings were reloaded from config.toml, see `spawn_config_watcher`
+    ConfigReloaded(Arc<Config>),
+    /// one of the validator's leader slots settled without a finalized block
+    /// (rpc_url and identity)
+    LeaderSlotSkipped {
+        slot: Slot,
+        epoch: u64,
+    },
+    /// the vote account's commission changed during the session (rpc_url)
+    CommissionChanged {
+        previous: u8,
+        current: u8,
+    },
+    /// a grpc update rate left its baseline, the subscription filters may
+    /// have changed on the provider's side
+    FilterDrift(Arc<FilterDrift>),
+    /// the rate is back within its baseline
+    FilterDriftEnded {
+        metric: DriftMetric,
+    },
+    /// the session stats were zeroed, with the numbers before, see
+    /// `PerformanceStats::reset`
+    StatsReset(Arc<StatsReset>),
+    /// a run of at least `stats.streak_alert_votes` max credit votes ended
+    OptimalStreakBroken(Arc<StreakBreak>),
 }
 
 #[derive(Debug)]
@@ -81,4 +176,16 @@ pub enum Message {
     StatsCommand(StatsCommand),
     Event(SystemEvent),
     Shutdown,
-}
\ No newline at end of file
+}
+
+impl From<&MissedVote> for SystemEvent {
+    fn from(missed: &MissedVote) -> Self {
+        SystemEvent::VoteMissed {
+            signature: missed.signature.clone(),
+            reason: format!(
+                "slots {:?} from tx slot {} not finalized after {} slots",
+                missed.voted_slots, missed.transaction_slot, missed.age_slots
+            ),
+        }
+    }
+}
@@ -0,0 +1,959 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Local;
+use log::{debug, error, info, warn};
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::{broadcast, mpsc, watch, RwLock, RwLockReadGuard};
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio::time::Instant;
+
+use crate::account_info::{spawn_account_info_monitor, AccountInfoClient};
+use crate::account_switch::{append_account_archive, AccountArchive, AccountSwitch, SwitchRefused};
+use crate::cluster::{spawn_cluster_comparison, ClusterClient};
+use crate::config::{Config, StorageBackend, VoteSourceKind};
+use crate::config_reload::{spawn_config_watcher, ConfigSource, SharedConfig};
+use crate::credits_check::{spawn_credits_monitor, RpcCreditsClient};
+use crate::error::{Result, VoteMonitorError};
+use crate::events::EventBus;
+use crate::influx::spawn_influx_sink;
+use crate::prometheus::{spawn_pushgateway, MetricsRegistry};
+use crate::json_stream::spawn_json_stream;
+use crate::filter_drift::spawn_filter_drift_watchdog;
+use crate::liveness::{spawn_liveness_watchdog, VoteLiveness};
+use crate::leader_schedule::{spawn_leader_schedule_fetcher, LeaderScheduleClient};
+use crate::memory::{format_bytes, MemoryUsage};
+use crate::process_metrics::{ProcessMetrics, ProcessSampler};
+use crate::message::SystemEvent;
+use crate::network::Network;
+use crate::notify::spawn_notifier;
+use crate::performance::{
+    format_duration, format_number, ConfirmationSource, ConfirmedVote, EventWriterMetrics, PerformanceStats, PoorPerformanceEvent,
+};
+use crate::performance_log::{
+    spawn_performance_event_writer, spawn_performance_log_pruner, PERFORMANCE_EVENT_CHANNEL_CAPACITY,
+};
+use crate::pipeline::{spawn_block_task, spawn_transaction_task, update_channels, Pipeline};
+use crate::state::SessionState;
+use crate::rpc::RpcClient;
+use crate::status_api::{spawn_status_api, EventReplay, StatsSnapshot, StatusApiConfig};
+use crate::stream_health::{spawn_stream_watchdog, StreamHealth};
+use crate::storage::{spawn_sqlite_writer, SqliteStore, StorageRecord, STORAGE_CHANNEL_CAPACITY};
+use crate::vote_log::spawn_vote_log_writer;
+use crate::vote_source::{vote_source, SourceContext, VoteSource};
+use crate::vote_account::verify_vote_account;
+use crate::vote_tracker::VoteTracker;
+use crate::vote_tracker_actor::VoteTrackerHandle;
+
+/// time the whole shutdown sequence gets unless shutdown_timeout_secs is set
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// task that drains and flushes once the monitor's shutdown signal is sent
+#[derive(Debug)]
+struct FlushingTask {
+    name: &'static str,
+    task: JoinHandle<()>,
+}
+
+impl FlushingTask {
+    /// wait for the final flush until `deadline`, then abandon it
+    async fn finish(self, deadline: Instant) {
+        let abort = self.task.abort_handle();
+        if tokio::time::timeout_at(deadline, self.task).await.is_err() {
+            warn!("{} did not finish before the shutdown timeout, abandoning its remaining work", self.name);
+            abort.abort();
+        }
+    }
+}
+
+/// stream, transaction and block tasks once started
+#[derive(Debug)]
+struct RunningPipeline {
+    /// closes the stream, the processing tasks then drain their channels
+    stop: watch::Sender<bool>,
+    stream: AbortHandle,
+    supervisor: JoinHandle<()>,
+}
+
+#[derive(Debug, Default)]
+pub struct MonitorBuilder {
+    config: Option<Config>,
+    events: Option<EventBus>,
+    config_source: Option<ConfigSource>,
+    source: Option<Box<dyn VoteSource>>,
+}
+
+impl MonitorBuilder {
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// publish pipeline events on an existing bus instead of a new one
+    pub fn event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// reload live settings from this file while running, see `spawn_config_watcher`
+    pub fn watch_config(mut self, source: ConfigSource) -> Self {
+        self.config_source = Some(source);
+        self
+    }
+
+    /// read votes from `source` instead of the one `config.source` selects
+    pub fn source(mut self, source: Box<dyn VoteSource>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// validate the config, restore session state and spawn the writers,
+    /// nothing connects until `start`
+    pub async fn build(self) -> Result<Monitor> {
+        let config = self.config.unwrap_or_default();
+        if !config.has_source() || config.vote_account.is_empty() {
+            let setting = match config.source {
+                VoteSourceKind::Grpc => "grpc_url",
+                VoteSourceKind::Rpc => "rpc_url",
+            };
+            return Err(VoteMonitorError::Config(format!("missing {} or vote_account", setting)));
+        }
+        config.validate()?;
+        let source = match self.source {
+            Some(source) => source,
+            None => vote_source(&config)?,
+        };
+
+        let vote_account = config.vote_account.clone();
+        let vote_account_key = Pubkey::from_str(&vote_account)
+            .map_err(|e| VoteMonitorError::Config(format!("vote_account is not a valid pubkey: {}", e)))?
+            .to_bytes();
+        let events = self.events.unwrap_or_default();
+        // writers and sinks drain and flush once this is sent, see `Monitor::shutdown`
+        let (shutdown, _) = watch::channel(false);
+
+        // an account that doesn't exist would connect and never show a vote
+        let vote_account_info = match config.rpc_url {
+            Some(ref rpc_url) => match verify_vote_account(&RpcClient::new(rpc_url.clone())?, &vote_account).await {
+                Ok(info) => {
+                    info!("vote account {}: identity {}, commission {}%", vote_account, info.identity, info.commission);
+                    Some(info)
+                }
+                Err(VoteMonitorError::Rpc(e)) => {
+                    warn!("could not verify vote account {} over rpc, continuing: {}", vote_account, e);
+                    None
+                }
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+
+        let network = config.network_profile();
+        if network.network != Network::Mainnet {
+            info!(
+                "network {}: {} slots per epoch, {}ms slots",
+                network.network.as_str(), network.slots_per_epoch, network.slot_duration.as_millis()
+            );
+        }
+
+        let notifier = if config.notifications.enabled() {
+            let task = spawn_notifier(
+                config.notifications.clone(),
+                config.tvc,
+                &vote_account,
+                network.explorer.clone(),
+                events.subscribe(),
+                shutdown.subscribe(),
+            )?;
+            info!("notifications enabled");
+            Some(FlushingTask { name: "notifier", task })
+        } else {
+            None
+        };
+
+        // metrics are encoded from the event bus, like the notifier
+        let mut metrics = if config.influx.enabled() {
+            let task = spawn_influx_sink(
+                config.influx.clone(),
+                config.tvc,
+                &vote_account,
+                events.subscribe(),
+                shutdown.subscribe(),
+            )?;
+            vec![FlushingTask { name: "influx sink", task }]
+        } else {
+            Vec::new()
+        };
+
+        // the vote tracker is owned by an actor task, processing tasks talk to it through handles
+        let (vote_tracker, _vote_tracker_task) = VoteTrackerHandle::spawn(
+            VoteTracker::new()
+                .with_missed_vote_cutoff(config.tracker.missed_vote_cutoff_slots)
+                .with_confirmed_capacity(config.tracker.confirmed_capacity)
+                .with_block_horizon(config.tracker.block_horizon_slots)
+                .with_duplicate_window(config.tracker.duplicate_window_slots)
+                .with_pending_cleanup(config.tracker.pending_cleanup_interval_slots, config.tracker.pending_max_age_slots)
+                .with_max_pending_votes(config.tracker.max_pending_votes)
+                .with_tvc_config(config.tvc),
+            events.clone(),
+        );
+        let mut initial_stats = PerformanceStats::new()
+            .with_slots_per_epoch(network.slots_per_epoch)
+            .with_network(network.network, network.slot_duration)
+            .with_recent_votes_window(config.dashboard.recent_votes_window)
+            .with_latency_window(Duration::from_secs(config.stats.recent_window_secs))
+            .with_poor_votes_window(config.stats.poor_votes_window)
+            .with_efficiency_trend(Duration::from_secs(config.stats.trend_bucket_secs), config.stats.trend_buckets)
+            .with_finalized_lag_alert(config.tracker.finalized_lag_alert_slots)
+            .with_root_lag_alert(config.tracker.root_lag_alert_slots)
+            .with_coverage_config(&config.coverage)
+            .with_tvc_config(config.tvc)
+            .with_commitment(config.commitment)
+            .with_thresholds(config.thresholds)
+            .with_loss_causes(config.loss_causes)
+            .with_leader_slot_latency_excluded(config.stats.exclude_leader_slots)
+            .with_streak_alert(config.stats.streak_alert_votes)
+            .with_estimates_in_percentiles(config.stats.percentiles_include_estimates)
+            .with_event_bus(events.clone());
+        if config.rpc_url.is_some() {
+            let identity = config.identity.as_deref().or(vote_account_info.as_ref().map(|info| info.identity.as_str()));
+            if let (Some(configured), Some(info)) = (config.identity.as_deref(), vote_account_info.as_ref()) {
+                if configured != info.identity {
+                    warn!("identity {} is not the identity of the vote account ({})", configured, info.identity);
+                }
+            }
+            if identity.is_none() {
+                warn!("validator identity unknown, votes for its own leader slots are not told apart");
+            }
+            initial_stats = initial_stats.with_leader_attribution(identity);
+        }
+        initial_stats.vote_account_info = vote_account_info;
+        initial_stats.confirmation_source = match source.kind() {
+            VoteSourceKind::Grpc => config.confirmation_source.initial_source(),
+            VoteSourceKind::Rpc => ConfirmationSource::VoteState,
+        };
+        if source.kind() == VoteSourceKind::Grpc && !config.confirmation_source.subscribes_blocks() {
+            info!("confirmation_source = \"tx-status\": no block subscription, latencies are estimated from the transaction slot");
+        }
+
+        let mut writers = Vec::new();
+
+        // ndjson on stdout, from the event bus like the influx sink
+        if config.json_stream.enabled {
+            let task = spawn_json_stream(
+                config.json_stream.clone(),
+                config.tvc,
+                &vote_account,
+                events.subscribe(),
+                shutdown.subscribe(),
+            );
+            writers.push(FlushingTask { name: "json stream", task });
+        }
+
+        let sqlite = config.storage.backend == StorageBackend::Sqlite;
+
+        // poor performance events are written in batches by their own task
+        if config.performance_logging.enabled && !sqlite {
+            let (event_tx, event_rx) = mpsc::channel::<PoorPerformanceEvent>(PERFORMANCE_EVENT_CHANNEL_CAPACITY);
+            let metrics = Arc::new(EventWriterMetrics::new());
+            let task = spawn_performance_event_writer(
+                &config.performance_logging,
+                metrics.clone(),
+                event_rx,
+                shutdown.subscribe(),
+            );
+            initial_stats = initial_stats.with_event_sender(event_tx);
+            initial_stats.event_writer = Some(metrics);
+            writers.push(FlushingTask { name: "performance event writer", task });
+        }
+
+        // the sqlite backend keeps votes, poor performance events and epoch
+        // summaries in one database, written in batches by one task
+        let storage = if sqlite {
+            let store = SqliteStore::open(&config.storage.path).await?;
+            info!("sqlite storage enabled: {}", store.path().display());
+            let (record_tx, record_rx) = mpsc::channel::<StorageRecord>(STORAGE_CHANNEL_CAPACITY);
+            let (event_tx, event_rx) = mpsc::channel::<PoorPerformanceEvent>(PERFORMANCE_EVENT_CHANNEL_CAPACITY);
+            if config.performance_logging.enabled {
+                initial_stats = initial_stats.with_event_sender(event_tx);
+            }
+            let task = spawn_sqlite_writer(store, &config.storage, record_rx, event_rx, shutdown.subscribe());
+            writers.push(FlushingTask { name: "sqlite writer", task });
+            Some(record_tx)
+        } else {
+            None
+        };
+
+        // optional export of every confirmed vote, batched in its own task
+        let vote_log = if config.vote_log.enabled {
+            info!("vote log enabled: {:?} files in {}", config.vote_log.format, config.vote_log.path.display());
+            let (vote_log_tx, vote_log_rx) = mpsc::channel::<ConfirmedVote>(1000);
+            let task = spawn_vote_log_writer(config.vote_log.clone(), vote_log_rx, shutdown.subscribe());
+            writers.push(FlushingTask { name: "vote log writer", task });
+            Some(vote_log_tx)
+        } else {
+            None
+        };
+
+        if let Some(ref state_file) = config.state.file {
+            let max_age = Duration::from_secs(config.state.max_age_secs);
+            if let Some(state) = SessionState::load(state_file, max_age, &vote_account).await {
+                info!(
+                    "resuming session started {} ({} votes) from {}",
+                    state.session_started_at.format("%Y-%m-%d %H:%M:%S"), state.total_transactions, state_file.display()
+                );
+                state.apply(&mut initial_stats);
+            }
+        }
+
+        let stats = Arc::new(RwLock::new(initial_stats));
+
+        // scraped and pushed prometheus metrics read the stats through one registry
+        let prometheus = if config.prometheus.enabled() {
+            let registry = Arc::new(MetricsRegistry::new(stats.clone()));
+            if config.prometheus.pushgateway_url.is_some() {
+                let task = spawn_pushgateway(&config.prometheus, registry.clone(), &vote_account, shutdown.subscribe())?;
+                metrics.push(FlushingTask { name: "prometheus pushgateway", task });
+            }
+            Some(registry)
+        } else {
+            None
+        };
+
+        let (running, _) = watch::channel(false);
+        let (switch_target, _) = watch::channel(None);
+        Ok(Monitor {
+            pipeline: Pipeline {
+                vote_account,
+                vote_account_key,
+                config: SharedConfig::new(config),
+                stats,
+                vote_tracker,
+                events,
+                vote_log,
+                storage,
+            },
+            stream_health: Arc::new(StreamHealth::new()),
+            config_source: self.config_source,
+            source,
+            notifier,
+            metrics,
+            prometheus,
+            writers,
+            shutdown,
+            background: Vec::new(),
+            running,
+            switch_target,
+            account_switch: None,
+            liveness: Arc::new(VoteLiveness::new()),
+            tasks: None,
+            process: Mutex::new(ProcessSampler::new()),
+        })
+    }
+}
+
+/// the vote monitor without a user interface
+///
+/// `start` connects to the grpc endpoint and spawns the processing tasks.
+/// consumers read the stats through `stats` and follow the pipeline through
+/// `subscribe_events`; subscribe before `start` to see the connection event.
+/// `shutdown` flushes the writers and saves the session state.
+#[derive(Debug)]
+pub struct Monitor {
+    pipeline: Pipeline,
+    stream_health: Arc<StreamHealth>,
+    config_source: Option<ConfigSource>,
+    source: Box<dyn VoteSource>,
+    notifier: Option<FlushingTask>,
+    metrics: Vec<FlushingTask>,
+    // shared by /metrics and the pushgateway task, see `PrometheusConfig`
+    prometheus: Option<Arc<MetricsRegistry>>,
+    writers: Vec<FlushingTask>,
+    shutdown: watch::Sender<bool>,
+    // aborted on shutdown, nothing to flush
+    background: Vec<JoinHandle<()>>,
+    running: watch::Sender<bool>,
+    // next vote account once a switch was requested, see `AccountSwitch`
+    switch_target: watch::Sender<Option<String>>,
+    // stops the running pipeline for a switch, set by `start`
+    account_switch: Option<AccountSwitch>,
+    liveness: Arc<VoteLiveness>,
+    tasks: Option<RunningPipeline>,
+    process: Mutex<ProcessSampler>,
+}
+
+impl Monitor {
+    pub fn builder() -> MonitorBuilder {
+        MonitorBuilder::default()
+    }
+
+    /// connect and spawn the processing and background tasks
+    ///
+    /// the vote source connects first, see `GrpcSource` and `RpcVoteSource`.
+    /// when it cannot `StreamDisconnected` is published, call `shutdown`
+    /// afterwards to deliver it.
+    pub async fn start(&mut self) -> Result<()> {
+        if self.tasks.is_some() {
+            return Err(VoteMonitorError::Config("monitor already started".to_string()));
+        }
+        let config = self.pipeline.config.current();
+        let events = self.pipeline.events.clone();
+
+        let (senders, receivers) = update_channels(&config.pipeline);
+        {
+            let mut stats = self.pipeline.stats.write().await;
+            stats.pipeline = Some(senders.metrics.clone());
+            stats.stream_health = Some(self.stream_health.clone());
+        }
+        let (stop, stop_rx) = watch::channel(false);
+        let source = self.source
+            .start(SourceContext {
+                config: config.clone(),
+                senders,
+                stats: self.pipeline.stats.clone(),
+                health: self.stream_health.clone(),
+                events,
+                stop: stop_rx,
+            })
+            .await?;
+        self.background.extend(source.background);
+
+        self.account_switch = Some(AccountSwitch::new(&config.vote_account, self.switch_target.clone(), stop.clone()));
+        self.spawn_background_tasks();
+
+        if config.liveness.resubscribe_secs > 0 || config.liveness.exit_secs > 0 {
+            self.background.push(spawn_liveness_watchdog(
+                config.liveness.clone(),
+                self.pipeline.stats.clone(),
+                self.stream_health.clone(),
+                self.liveness.clone(),
+                stop.clone(),
+            ));
+        }
+        let stream = source.stream;
+        let transactions = spawn_transaction_task(receivers.transactions, self.pipeline.clone());
+        let blocks = spawn_block_task(
+            receivers.blocks,
+            source.confirmed_blocks,
+            receivers.slots,
+            receivers.accounts,
+            receivers.dropped_blocks,
+            self.pipeline.clone(),
+        );
+
+        let stream_abort = stream.abort_handle();
+        self.running.send_replace(true);
+        let running = self.running.clone();
+        let supervisor = tokio::spawn(async move {
+            let mut tasks = [stream, transactions, blocks];
+            let [stream, transactions, blocks] = &mut tasks;
+            tokio::select! {
+                _ = stream => info!("stream task completed"),
+                _ = transactions => info!("transaction processing task completed"),
+                _ = blocks => info!("block processing task completed"),
+            }
+            running.send_replace(false);
+            // the others wind down once the stream's channels close
+            for task in tasks {
+                if !task.is_finished() {
+                    let _ = task.await;
+                }
+            }
+        });
+        self.tasks = Some(RunningPipeline { stop, stream: stream_abort, supervisor });
+
+        info!("all processing tasks started - monitoring vote performance...");
+        Ok(())
+    }
+
+    fn spawn_background_tasks(&mut self) {
+        let config = self.pipeline.config.current();
+        let stats = &self.pipeline.stats;
+
+        // optional cross-check of session credits against the chain
+        if let Some(ref rpc_url) = config.rpc_url {
+            match RpcCreditsClient::new(rpc_url.clone(), config.vote_account.clone()) {
+                Ok(client) => {
+                    info!(
+                        "credits check enabled: polling {} every {}s",
+                        rpc_url, config.credits_drift.poll_interval_secs
+                    );
+                    self.background.push(spawn_credits_monitor(
+                        client,
+                        config.credits_drift.clone(),
+                        stats.clone(),
+                        self.pipeline.events.clone(),
+                    ));
+                }
+                Err(e) => error!("failed to create rpc client, credits check disabled: {}", e),
+            }
+
+            // leader schedule once per epoch for the worst leaders panel
+            match LeaderScheduleClient::new(rpc_url.clone()) {
+                Ok(client) => {
+                    info!("leader attribution enabled: fetching leader schedules from {}", rpc_url);
+                    self.background.push(spawn_leader_schedule_fetcher(client, stats.clone()));
+                }
+                Err(e) => error!("failed to create rpc client, leader attribution disabled: {}", e),
+            }
+
+            // identity, commission and stake for the dashboard header
+            match AccountInfoClient::new(rpc_url.clone(), config.vote_account.clone()) {
+                Ok(client) => {
+                    self.background.push(spawn_account_info_monitor(
+                        client,
+                        Duration::from_secs(config.account_info.poll_interval_secs),
+                        stats.clone(),
+                        self.pipeline.events.clone(),
+                    ));
+                }
+                Err(e) => error!("failed to create rpc client, account info disabled: {}", e),
+            }
+
+            // cluster efficiency distribution for the comparison panel
+            match ClusterClient::new(rpc_url.clone()) {
+                Ok(client) => {
+                    info!("cluster comparison enabled: refreshing every {}s", config.cluster.poll_interval_secs);
+                    self.background.push(spawn_cluster_comparison(
+                        client,
+                        Duration::from_secs(config.cluster.poll_interval_secs),
+                        stats.clone(),
+                    ));
+                }
+                Err(e) => error!("failed to create rpc client, cluster comparison disabled: {}", e),
+            }
+        }
+
+        // the rpc source has no stream to go stale or filters to drift
+        let grpc = self.source.kind() == VoteSourceKind::Grpc;
+        // warn about and replace a stream that stops delivering without closing
+        if grpc {
+            self.background.push(spawn_stream_watchdog(
+                self.stream_health.clone(),
+                config.staleness.clone(),
+                config.confirmation_source.watched_updates(),
+                stats.clone(),
+            ));
+        }
+        // a stream that stays up while the provider narrowed its filters
+        if grpc && config.filter_drift.factor > 0.0 {
+            self.background.push(spawn_filter_drift_watchdog(
+                config.filter_drift.clone(),
+                self.stream_health.clone(),
+                stats.clone(),
+                self.pipeline.events.clone(),
+            ));
+        }
+
+        // optional json status api, /healthz tracks grpc stream freshness
+        if let Some(addr) = config.http_listen {
+            self.background.push(spawn_status_api(
+                addr,
+                StatusApiConfig {
+                    stats: stats.clone(),
+                    vote_account: config.vote_account.clone(),
+                    health: self.stream_health.clone(),
+                    max_stale: Duration::from_secs(config.healthz.max_stale_secs),
+                    explorer: config.network_profile().explorer,
+                    reset: config.stats.reset_over_http.then_some(config.stats.reset_keeps_epochs),
+                    metrics: self.prometheus.clone().filter(|_| config.prometheus.scrape),
+                    switch_account: self.account_switch.clone().filter(|_| config.stats.switch_account_over_http),
+                    events: self.pipeline.events.clone(),
+                    event_replay: EventReplay {
+                        votes: config.stats.event_replay_votes,
+                        window: Duration::from_secs(config.stats.event_replay_minutes.saturating_mul(60)),
+                    },
+                    shutdown: self.shutdown.subscribe(),
+                },
+            ));
+        }
+
+        // retention and compression of the daily performance_issues files
+        let perf = &config.performance_logging;
+        if perf.retention_days.is_some() || perf.compress {
+            info!(
+                "performance log retention: {} in {}{}",
+                perf.retention_days.map(|days| format!("{} days", days)).unwrap_or_else(|| "unlimited".to_string()),
+                perf.performance_log_dir.display(),
+                if perf.compress { ", compressing finished days" } else { "" }
+            );
+            self.background.push(spawn_performance_log_pruner(perf.clone()));
+        }
+
+        // live settings from config.toml without a restart
+        if let Some(source) = self.config_source.clone().filter(|_| config.reload.enabled) {
+            info!("watching {} for config changes every {}s", source.path.display(), config.reload.poll_secs);
+            self.background.push(spawn_config_watcher(
+                source,
+                self.pipeline.config.clone(),
+                self.pipeline.events.clone(),
+                Duration::from_secs(config.reload.poll_secs),
+            ));
+        }
+
+        // stats snapshot on SIGUSR1, session reset on SIGUSR2 (every counter and
+        // window, the epoch breakdown too unless stats.reset_keeps_epochs)
+        #[cfg(unix)]
+        {
+            use crate::stats_dump::{spawn_signal_listener, spawn_stats_commands, DEFAULT_SNAPSHOT_DIR};
+
+            let (commands, commands_rx) = mpsc::channel(1);
+            let dir = config.snapshot_dir.clone().unwrap_or_else(|| DEFAULT_SNAPSHOT_DIR.into());
+            self.background.push(spawn_stats_commands(commands_rx, self.pipeline.clone(), dir));
+            self.background.push(spawn_signal_listener(commands));
+        }
+
+        // periodic state snapshots so a crash loses at most one interval
+        if let Some(state_file) = config.state.file.clone() {
+            let stats = stats.clone();
+            let vote_account = self.pipeline.vote_account.clone();
+            let save_interval = Duration::from_secs(config.state.save_interval_secs);
+            self.background.push(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(save_interval);
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    let state = SessionState::capture(&*stats.read().await, &vote_account);
+                    if let Err(e) = state.save(&state_file).await {
+                        error!("error saving session state: {}", e);
+                    }
+                }
+            }));
+        }
+    }
+
+    /// resolves once the stream or a processing task has stopped, right away
+    /// when the monitor is not running
+    pub async fn closed(&self) {
+        let mut running = self.running.subscribe();
+        let _ = running.wait_for(|running| !running).await;
+    }
+
+    /// connected time without a confirmed vote after which the liveness
+    /// watchdog stopped the monitor, see `LivenessConfig::exit_secs`
+    pub fn liveness_failure(&self) -> Option<Duration> {
+        self.liveness.gave_up_after()
+    }
+
+    pub fn is_running(&self) -> bool {
+        *self.running.borrow()
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SystemEvent> {
+        self.pipeline.events.subscribe()
+    }
+
+    pub fn event_bus(&self) -> &EventBus {
+        &self.pipeline.events
+    }
+
+    /// the running config, reflects reloaded live settings
+    pub fn config(&self) -> Arc<Config> {
+        self.pipeline.config.current()
+    }
+
+    pub fn vote_account(&self) -> &str {
+        &self.pipeline.vote_account
+    }
+
+    /// stop and archive the session on shutdown to continue with
+    /// `vote_account`, POST /switch-account with stats.switch_account_over_http
+    pub fn switch_account(&self, vote_account: &str) -> std::result::Result<(), SwitchRefused> {
+        match self.account_switch {
+            Some(ref switch) if self.is_running() => switch.request(vote_account),
+            _ => Err(SwitchRefused::NotRunning),
+        }
+    }
+
+    /// the vote account a switch was requested to, the monitor stops once it is
+    /// set and `shutdown` archives the session, see `AccountSwitch`
+    pub fn switched_to(&self) -> Option<String> {
+        self.switch_target.borrow().clone()
+    }
+
+    /// owned copy of everything the dashboard shows
+    pub async fn stats(&self) -> StatsSnapshot {
+        StatsSnapshot::capture(&*self.pipeline.stats.read().await, &self.pipeline.vote_account)
+    }
+
+    /// read access for renderers that need more than the snapshot, hold it briefly
+    /// since vote processing waits on it
+    pub async fn read_stats(&self) -> RwLockReadGuard<'_, PerformanceStats> {
+        self.pipeline.stats.read().await
+    }
+
+    /// zero the session stats, see `PerformanceStats::reset`. the per-epoch
+    /// breakdown is kept unless stats.reset_keeps_epochs is off
+    pub async fn reset_stats(&self) {
+        let keep_epochs = self.pipeline.config.current().stats.reset_keeps_epochs;
+        self.pipeline.stats.write().await.reset(keep_epochs);
+    }
+
+    /// approximate memory usage across the tracker and the stats windows
+    pub async fn memory_usage(&self) -> MemoryUsage {
+        self.pipeline.memory_usage(&*self.pipeline.stats.read().await).await
+    }
+
+    /// sample the process metrics into the stats, cpu% covers the time since
+    /// the previous call
+    pub async fn sample_process(&self) -> ProcessMetrics {
+        let metrics = self.process
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .sample();
+        self.pipeline.stats.write().await.process = Some(metrics.clone());
+        metrics
+    }
+
+    /// copy the vote tracker counters into the stats for the dashboard debug
+    /// line, kept when the tracker does not answer
+    pub async fn sample_tracker(&self) {
+        match self.pipeline.vote_tracker.get_stats().await {
+            Ok(tracker) => self.pipeline.stats.write().await.tracker = Some(tracker),
+            Err(e) => debug!("vote tracker stats unavailable: {}", e),
+        }
+    }
+
+    /// close the stream, let the processing tasks drain their channels, then
+    /// flush the writers and pending notifications and save the session state
+    ///
+    /// the whole sequence gets `shutdown_timeout_secs`, work still left after
+    /// that is abandoned with a warning.
+    pub async fn shutdown(mut self) {
+        let config = self.pipeline.config.current();
+        let deadline = Instant::now() + config.shutdown_timeout_secs.map_or(DEFAULT_SHUTDOWN_TIMEOUT, Duration::from_secs);
+
+        if let Some(tasks) = self.tasks.take() {
+            tasks.stop.send_replace(true);
+            if tokio::time::timeout_at(deadline, tasks.supervisor).await.is_err() {
+                warn!("processing tasks did not drain before the shutdown timeout, abandoning queued updates");
+                tasks.stream.abort();
+            }
+        }
+        self.running.send_replace(false);
+
+        // votes of an account switched away from would never be finalized
+        // here, they are archived instead of expiring as missed
+        let switched_to = self.switched_to();
+        let in_flight_votes = match switched_to {
+            Some(_) => self.pipeline.vote_tracker.drain_pending().await.unwrap_or_else(|e| {
+                warn!("in-flight votes unavailable for the account archive: {}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+
+        // every vote and event is queued by now, writers drain and flush them
+        // and the notifier delivers queued alerts, a disconnect included
+        self.shutdown.send_replace(true);
+        for task in self.writers.drain(..).chain(self.notifier.take()).chain(self.metrics.drain(..)) {
+            task.finish(deadline).await;
+        }
+        for task in self.background.drain(..) {
+            task.abort();
+        }
+
+        let stats_guard = self.pipeline.stats.read().await;
+        log_status_timeline(&stats_guard);
+
+        if let Some(ref state_file) = config.state.file {
+            match SessionState::capture(&stats_guard, &self.pipeline.vote_account).save(state_file).await {
+                Ok(()) => info!("session state saved to {}", state_file.display()),
+                Err(e) => error!("error saving session state: {}", e),
+            }
+        }
+
+        if let Some(switched_to) = switched_to {
+            let vote_account = &self.pipeline.vote_account;
+            if !in_flight_votes.is_empty() {
+                warn!(
+                    "{} votes of {} were still in flight at the switch, archived without counting as missed",
+                    in_flight_votes.len(), vote_account
+                );
+            }
+            let archive = AccountArchive {
+                archived_at: Local::now(),
+                switched_to,
+                stats: StatsSnapshot::capture(&stats_guard, vote_account),
+                in_flight_votes,
+            };
+            let path = &config.stats.account_archive_file;
+            match append_account_archive(&archive, path).await {
+                Ok(()) => info!("session of {} archived to {}", vote_account, path.display()),
+                Err(e) => error!("failed to archive the session of {} to {}: {}", vote_account, path.display(), e),
+            }
+        }
+
+        let memory = self.pipeline.memory_usage(&stats_guard).await;
+        info!("approx memory usage at shutdown: {}", format_bytes(memory.total_bytes()));
+    }
+}
+
+/// log time spent per windowed status and each non-optimal period
+fn log_status_timeline(stats: &PerformanceStats) {
+    let severity = stats.severity_histogram();
+    info!(
+        "votes below max credits: {} (good {} | fair {} | poor {} | critical {})",
+        format_number(severity.total()), severity.good, severity.fair, severity.poor, severity.critical
+    );
+    if let Some(summary) = stats.lost_credits().summary() {
+        info!("credits lost by cause: {}", summary);
+    }
+    if let Some(summary) = stats.tvc_histogram().summary() {
+        info!("tvc distribution: {}", summary);
+    }
+    let latency = stats.latency_distribution();
+    if let Some(summary) = latency.summary() {
+        info!(
+            "latency distribution: {} | grace-dependent {:.1}% (latency {})",
+            summary, latency.grace_dependent_pct(), stats.tvc.grace_slots
+        );
+    }
+    if stats.commitment.observes_confirmed() {
+        let observations = &stats.confirmed_observations;
+        info!(
+            "confirmed sightings: {} duplicate | {} after finalization | {} expired | {} waiting",
+            observations.duplicates(), observations.late(), observations.expired(), observations.len()
+        );
+    }
+
+    info!("optimal vote streaks: {}", stats.streaks.summary());
+    if let Some(broken) = stats.streaks.last_break() {
+        info!("   last streak of {} ended by {}", broken.length, broken.signature);
+    }
+
+    let timeline = &stats.status_timeline;
+    info!(
+        "time in status: optimal {} | good {} | poor {}",
+        format_duration(timeline.time_in("optimal")),
+        format_duration(timeline.time_in("good")),
+        format_duration(timeline.time_in("poor"))
+    );
+
+    for segment in timeline.segments().filter(|segment| segment.status != "optimal") {
+        info!(
+            "   {} for {} starting {}",
+            segment.status,
+            format_duration(segment.duration()),
+            segment.start.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::BoxFuture;
+    use solana_sdk::pubkey::Pubkey;
+
+    use crate::state::SessionState;
+    use crate::test_support::{make_block, make_vote_tx, signature, temp_dir, VOTE_ACCOUNT};
+    use crate::vote_source::{RunningSource, SourceContext};
+
+    /// two votes and the block of the first, then waits to be stopped
+    #[derive(Debug)]
+    struct ScriptedSource;
+
+    impl VoteSource for ScriptedSource {
+        fn kind(&self) -> VoteSourceKind {
+            VoteSourceKind::Grpc
+        }
+
+        fn start(&self, context: SourceContext) -> BoxFuture<'static, Result<RunningSource>> {
+            Box::pin(async move {
+                let SourceContext { senders, mut stop, .. } = context;
+                let stream = tokio::spawn(async move {
+                    let landed = make_vote_tx(signature(1), 101, &[(100, 1)]);
+                    let in_flight = make_vote_tx(signature(2), 103, &[(102, 1)]);
+                    senders.transactions.send(landed.clone()).await.unwrap();
+                    senders.transactions.send(in_flight).await.unwrap();
+                    senders.blocks.send(make_block(101, &[landed])).await.unwrap();
+                    let _ = stop.wait_for(|stop| *stop).await;
+                });
+                Ok(RunningSource { stream, confirmed_blocks: None, background: Vec::new() })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn switch_archives_the_session_and_its_votes_in_flight() {
+        let dir = temp_dir("account-switch");
+        let mut config = Config {
+            grpc_url: "http://127.0.0.1:10000".to_string(),
+            vote_account: VOTE_ACCOUNT.to_string(),
+            ..Config::default()
+        };
+        config.performance_logging.enabled = false;
+        config.state.file = Some(dir.join("state.json"));
+        config.stats.account_archive_file = dir.join("sessions.jsonl");
+
+        let mut monitor = Monitor::builder().source(Box::new(ScriptedSource)).config(config.clone()).build().await.unwrap();
+        let next = Pubkey::new_unique().to_string();
+        assert_eq!(monitor.switch_account(&next), Err(SwitchRefused::NotRunning));
+        monitor.start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while monitor.read_stats().await.total_transactions() == 0 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("first vote confirmed");
+
+        monitor.switch_account(&next).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), monitor.closed()).await.expect("monitor stopped");
+        assert_eq!(monitor.switched_to(), Some(next.clone()));
+        monitor.shutdown().await;
+
+        let archive = std::fs::read_to_string(&config.stats.account_archive_file).unwrap();
+        let lines: Vec<serde_json::Value> = archive.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["vote_account"], VOTE_ACCOUNT.to_string());
+        assert_eq!(lines[0]["switched_to"], next);
+        assert_eq!(lines[0]["totals"]["votes"], 1);
+        // the vote still in flight is reported, not counted as missed
+        assert_eq!(lines[0]["totals"]["missed_votes"], 0);
+        assert_eq!(lines[0]["in_flight_votes"][0]["voted_slots"], serde_json::json!([102]));
+
+        // the next account starts a fresh session
+        let state_file = config.state.file.as_deref().unwrap();
+        let max_age = Duration::from_secs(config.state.max_age_secs);
+        assert!(SessionState::load(state_file, max_age, &next).await.is_none());
+        assert!(SessionState::load(state_file, max_age, &VOTE_ACCOUNT.to_string()).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn shutdown_ends_the_event_streams() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut config = Config {
+            grpc_url: "http://127.0.0.1:10000".to_string(),
+            vote_account: VOTE_ACCOUNT.to_string(),
+            http_listen: Some(addr),
+            ..Config::default()
+        };
+        config.performance_logging.enabled = false;
+
+        let mut monitor = Monitor::builder().source(Box::new(ScriptedSource)).config(config).build().await.unwrap();
+        monitor.start().await.unwrap();
+        let mut response = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match reqwest::get(format!("http://{}/events", addr)).await {
+                    Ok(response) => break response,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            }
+        })
+        .await
+        .expect("status api listening");
+        let first = tokio::time::timeout(Duration::from_secs(5), response.chunk()).await.unwrap().unwrap();
+        assert!(first.is_some());
+
+        monitor.shutdown().await;
+        // the connection task outlives the server, the stream still ends
+        let rest = tokio::time::timeout(Duration::from_secs(5), async {
+            while response.chunk().await.unwrap().is_some() {}
+        })
+        .await;
+        assert!(rest.is_ok(), "GET /events still open after shutdown");
+    }
+}
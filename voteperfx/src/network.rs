@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::epoch::DEFAULT_SLOTS_PER_EPOCH;
+
+/// stands for the signature in an explorer url template
+pub const SIGNATURE_PLACEHOLDER: &str = "{signature}";
+
+/// target slot time of the public clusters
+pub const DEFAULT_SLOT_DURATION_MS: u64 = 400;
+
+/// cluster the vote account is on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    Devnet,
+    /// a private cluster, described by `[custom_network]`
+    Custom,
+}
+
+impl Network {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Devnet => "devnet",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+/// builds the transaction links of the dashboard, simple mode lines and
+/// notifications
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explorer {
+    tx_url: String,
+}
+
+impl Default for Explorer {
+    fn default() -> Self {
+        Self::for_network(Network::Mainnet)
+    }
+}
+
+impl Explorer {
+    /// links from a template with `{signature}` in place of the signature
+    pub fn new(tx_url: impl Into<String>) -> Self {
+        Self { tx_url: tx_url.into() }
+    }
+
+    /// solscan on mainnet, the solana explorer for the test clusters. a
+    /// custom network has no explorer of its own, it gets solscan's until
+    /// `CustomNetworkConfig::explorer_tx_url` replaces it
+    pub fn for_network(network: Network) -> Self {
+        Self::new(match network {
+            Network::Mainnet | Network::Custom => "https://solscan.io/tx/{signature}",
+            Network::Testnet => "https://explorer.solana.com/tx/{signature}?cluster=testnet",
+            Network::Devnet => "https://explorer.solana.com/tx/{signature}?cluster=devnet",
+        })
+    }
+
+    /// link to the transaction with `signature`
+    pub fn tx_url(&self, signature: &str) -> String {
+        self.tx_url.replace(SIGNATURE_PLACEHOLDER, signature)
+    }
+
+    /// the template, for links built elsewhere such as the web dashboard
+    pub fn tx_url_template(&self) -> &str {
+        &self.tx_url
+    }
+}
+
+/// explorer and timing of the monitored cluster, see `Config::network_profile`
+#[derive(Debug, Clone)]
+pub struct NetworkProfile {
+    pub network: Network,
+    pub explorer: Explorer,
+    pub slots_per_epoch: u64,
+    /// expected time per slot, what the vote rate and staleness thresholds
+    /// are measured against
+    pub slot_duration: Duration,
+}
+
+impl Default for NetworkProfile {
+    fn default() -> Self {
+        Self::preset(Network::Mainnet)
+    }
+}
+
+impl NetworkProfile {
+    /// the public cluster defaults, a custom network starts from mainnet's
+    pub fn preset(network: Network) -> Self {
+        Self {
+            network,
+            explorer: Explorer::for_network(network),
+            slots_per_epoch: DEFAULT_SLOTS_PER_EPOCH,
+            slot_duration: Duration::from_millis(DEFAULT_SLOT_DURATION_MS),
+        }
+    }
+
+    /// wall-clock time of `slots` slots
+    pub fn slots_duration(&self, slots: u64) -> Duration {
+        self.slot_duration.saturating_mul(slots.min(u32::MAX as u64) as u32)
+    }
+}
+
+/// votes per second of a validator voting on every slot
+pub fn expected_vote_rate(slot_duration: Duration) -> f64 {
+    match slot_duration.as_secs_f64() {
+        secs if secs > 0.0 => 1.0 / secs,
+        _ => 0.0,
+    }
+}
@@ -0,0 +1,515 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rustc_hash::FxHashMap;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{self, error::{RecvError, TryRecvError}};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::config::{NotificationsConfig, TelegramConfig, TvcConfig};
+use crate::error::Result;
+use crate::message::SystemEvent;
+use crate::performance::ConfirmedVote;
+use crate::network::Explorer;
+
+/// notifications waiting for delivery, the oldest are dropped beyond this
+const NOTIFICATION_QUEUE: usize = 32;
+
+/// signatures linked in a single notification
+const MAX_LINKED_SIGNATURES: usize = 5;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    StreamConnected,
+    StreamDisconnected,
+    PoorVoteStreak,
+    LowEfficiency,
+    MissedVoteStreak,
+    CommissionChanged,
+    FilterDrift,
+}
+
+impl NotificationKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            NotificationKind::StreamConnected => "grpc stream connected",
+            NotificationKind::StreamDisconnected => "grpc stream disconnected",
+            NotificationKind::PoorVoteStreak => "poor vote streak",
+            NotificationKind::LowEfficiency => "low vote efficiency",
+            NotificationKind::MissedVoteStreak => "missed vote streak",
+            NotificationKind::CommissionChanged => "commission changed",
+            NotificationKind::FilterDrift => "grpc filter drift",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    fn discord_color(&self) -> u32 {
+        match self {
+            Severity::Info => 0x2ecc71,
+            Severity::Warning => 0xf1c40f,
+            Severity::Critical => 0xe74c3c,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub severity: Severity,
+    pub summary: String,
+    /// the numbers behind the alert
+    pub fields: Vec<(&'static str, String)>,
+    /// offending vote transactions, linked on the explorer
+    pub signatures: Vec<String>,
+    /// alerts of the same kind held back by the rate limit since the last one
+    pub suppressed: u64,
+}
+
+impl Notification {
+    fn new(kind: NotificationKind, severity: Severity, summary: String) -> Self {
+        Self {
+            kind,
+            severity,
+            summary,
+            fields: Vec::new(),
+            signatures: Vec::new(),
+            suppressed: 0,
+        }
+    }
+
+    fn with_field(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.fields.push((name, value.to_string()));
+        self
+    }
+
+    fn with_signatures(mut self, signatures: impl IntoIterator<Item = String>) -> Self {
+        self.signatures = signatures.into_iter().take(MAX_LINKED_SIGNATURES).collect();
+        self
+    }
+}
+
+/// first and last characters of a signature for link text
+fn short_signature(signature: &str) -> String {
+    if signature.len() <= 16 {
+        return signature.to_string();
+    }
+    format!("{}…{}", &signature[..8], &signature[signature.len() - 8..])
+}
+
+/// newest signatures of a streak, capped
+fn push_signature(signatures: &mut VecDeque<String>, signature: &str) {
+    signatures.push_back(signature.to_string());
+    if signatures.len() > MAX_LINKED_SIGNATURES {
+        signatures.pop_front();
+    }
+}
+
+/// turns pipeline events into alerts: poor vote and missed vote streaks,
+/// efficiency over a rolling window and stream connection changes
+#[derive(Debug)]
+pub struct AlertDetector {
+    config: NotificationsConfig,
+    tvc: TvcConfig,
+    poor_streak: u64,
+    poor_signatures: VecDeque<String>,
+    missed_streak: u64,
+    missed_signatures: VecDeque<String>,
+    // (credits, signature) of the newest votes
+    window: VecDeque<(u64, Arc<String>)>,
+    window_credits: u64,
+    efficiency_alerted: bool,
+}
+
+impl AlertDetector {
+    pub fn new(config: NotificationsConfig, tvc: TvcConfig) -> Self {
+        Self {
+            window: VecDeque::with_capacity(config.efficiency_window_votes),
+            config,
+            tvc,
+            poor_streak: 0,
+            poor_signatures: VecDeque::new(),
+            missed_streak: 0,
+            missed_signatures: VecDeque::new(),
+            window_credits: 0,
+            efficiency_alerted: false,
+        }
+    }
+
+    pub fn handle(&mut self, event: &SystemEvent) -> Vec<Notification> {
+        let mut notifications = Vec::new();
+        match event {
+            SystemEvent::VoteConfirmed(vote) => self.record_vote(vote, &mut notifications),
+            SystemEvent::VoteMissed { signature, reason } => {
+                self.missed_streak += 1;
+                push_signature(&mut self.missed_signatures, signature);
+                if self.missed_streak == self.config.missed_vote_streak {
+                    notifications.push(
+                        Notification::new(
+                            NotificationKind::MissedVoteStreak,
+                            Severity::Critical,
+                            format!("{} vote transactions in a row never landed", self.missed_streak),
+                        )
+                        .with_field("missed in a row", self.missed_streak)
+                        .with_field("last reason", reason)
+                        .with_signatures(self.missed_signatures.iter().rev().cloned()),
+                    );
+                }
+            }
+            SystemEvent::StreamConnected { endpoint } => {
+                notifications.push(
+                    Notification::new(
+                        NotificationKind::StreamConnected,
+                        Severity::Info,
+                        "connected to the grpc stream, monitoring votes".to_string(),
+                    )
+                    .with_field("endpoint", endpoint),
+                );
+            }
+            SystemEvent::StreamDisconnected { reason } => {
+                notifications.push(
+                    Notification::new(
+                        NotificationKind::StreamDisconnected,
+                        Severity::Critical,
+                        "grpc stream lost, votes are no longer monitored".to_string(),
+                    )
+                    .with_field("reason", reason),
+                );
+            }
+            SystemEvent::CommissionChanged { previous, current } => {
+                // a raise costs delegators, a cut is only worth a note
+                let severity = if current > previous { Severity::Critical } else { Severity::Info };
+                notifications.push(
+                    Notification::new(
+                        NotificationKind::CommissionChanged,
+                        severity,
+                        format!("commission changed from {}% to {}%", previous, current),
+                    )
+                    .with_field("previous", format!("{}%", previous))
+                    .with_field("current", format!("{}%", current)),
+                );
+            }
+            SystemEvent::FilterDrift(drift) => {
+                notifications.push(
+                    Notification::new(
+                        NotificationKind::FilterDrift,
+                        Severity::Warning,
+                        format!("grpc {} left its baseline, the provider may have narrowed the subscription filters", drift.metric.as_str()),
+                    )
+                    .with_field("current", format!("{:.1}", drift.current))
+                    .with_field("baseline", format!("{:.1}", drift.baseline)),
+                );
+            }
+            SystemEvent::FilterDriftEnded { metric } => {
+                notifications.push(Notification::new(
+                    NotificationKind::FilterDrift,
+                    Severity::Info,
+                    format!("grpc {} is back within its baseline", metric.as_str()),
+                ));
+            }
+            // destinations and rate limit need a restart, only thresholds change
+            SystemEvent::ConfigReloaded(config) => {
+                let reloaded = &config.notifications;
+                self.config.poor_vote_streak = reloaded.poor_vote_streak;
+                self.config.missed_vote_streak = reloaded.missed_vote_streak;
+                self.config.efficiency_threshold_pct = reloaded.efficiency_threshold_pct;
+                self.config.efficiency_window_votes = reloaded.efficiency_window_votes;
+            }
+            _ => {}
+        }
+        notifications
+    }
+
+    fn record_vote(&mut self, vote: &ConfirmedVote, notifications: &mut Vec<Notification>) {
+        // a landed vote ends a missed streak
+        self.missed_streak = 0;
+        self.missed_signatures.clear();
+
+        if vote.tvc_credits < self.tvc.good_threshold() {
+            self.poor_streak += 1;
+            push_signature(&mut self.poor_signatures, &vote.signature);
+            if self.poor_streak == self.config.poor_vote_streak {
+                notifications.push(
+                    Notification::new(
+                        NotificationKind::PoorVoteStreak,
+                        Severity::Warning,
+                        format!(
+                            "{} votes in a row earned less than {} credits",
+                            self.poor_streak, self.tvc.good_threshold()
+                        ),
+                    )
+                    .with_field("poor votes in a row", self.poor_streak)
+                    .with_field("last latency", format!("{} slots", vote.latency))
+                    .with_field("last credits", format!("{}/{}", vote.tvc_credits, self.tvc.max_credits_per_slot))
+                    .with_signatures(self.poor_signatures.iter().rev().cloned()),
+                );
+            }
+        } else {
+            self.poor_streak = 0;
+            self.poor_signatures.clear();
+        }
+
+        self.window.push_back((vote.tvc_credits, vote.signature.clone()));
+        self.window_credits += vote.tvc_credits;
+        // more than one after a reload shrank the window
+        while self.window.len() > self.config.efficiency_window_votes {
+            if let Some((credits, _)) = self.window.pop_front() {
+                self.window_credits -= credits;
+            }
+        }
+        if self.window.len() < self.config.efficiency_window_votes {
+            return;
+        }
+
+        let possible = self.window.len() as u64 * self.tvc.max_credits_per_slot;
+        let efficiency = self.window_credits as f64 / possible as f64 * 100.0;
+        if efficiency >= self.config.efficiency_threshold_pct {
+            self.efficiency_alerted = false;
+            return;
+        }
+        if self.efficiency_alerted {
+            return;
+        }
+        self.efficiency_alerted = true;
+
+        let mut worst: Vec<&(u64, Arc<String>)> = self.window.iter().collect();
+        worst.sort_by_key(|(credits, _)| *credits);
+        notifications.push(
+            Notification::new(
+                NotificationKind::LowEfficiency,
+                Severity::Warning,
+                format!(
+                    "efficiency over the last {} votes dropped to {:.1}% (threshold {:.1}%)",
+                    self.window.len(), efficiency, self.config.efficiency_threshold_pct
+                ),
+            )
+            .with_field("efficiency", format!("{:.1}%", efficiency))
+            .with_field("credits", format!("{}/{}", self.window_credits, possible))
+            .with_signatures(worst.into_iter().map(|(_, signature)| signature.to_string())),
+        );
+    }
+}
+
+/// at most one alert per kind and interval, the rest are counted
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    last_sent: FxHashMap<NotificationKind, Instant>,
+    suppressed: FxHashMap<NotificationKind, u64>,
+}
+
+impl RateLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: FxHashMap::default(),
+            suppressed: FxHashMap::default(),
+        }
+    }
+
+    fn admit(&mut self, notification: &mut Notification) -> bool {
+        let kind = notification.kind;
+        if self.last_sent.get(&kind).is_some_and(|sent| sent.elapsed() < self.interval) {
+            *self.suppressed.entry(kind).or_default() += 1;
+            return false;
+        }
+        self.last_sent.insert(kind, Instant::now());
+        notification.suppressed = self.suppressed.remove(&kind).unwrap_or(0);
+        true
+    }
+}
+
+fn discord_payload(notification: &Notification, vote_account: &str, explorer: &Explorer) -> Value {
+    let mut description = notification.summary.clone();
+    for signature in &notification.signatures {
+        description.push_str(&format!("\n[{}]({})", short_signature(signature), explorer.tx_url(signature)));
+    }
+
+    let mut fields = vec![json!({ "name": "vote account", "value": vote_account, "inline": false })];
+    fields.extend(notification.fields.iter().map(|(name, value)| {
+        json!({ "name": name, "value": value, "inline": true })
+    }));
+    if notification.suppressed > 0 {
+        fields.push(json!({ "name": "suppressed", "value": notification.suppressed.to_string(), "inline": true }));
+    }
+
+    json!({
+        "username": "voteperfx",
+        "embeds": [{
+            "title": notification.kind.title(),
+            "description": description,
+            "color": notification.severity.discord_color(),
+            "fields": fields,
+            "timestamp": Utc::now().to_rfc3339(),
+        }],
+    })
+}
+
+/// escape text for telegram markdownv2
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn telegram_text(notification: &Notification, vote_account: &str, explorer: &Explorer) -> String {
+    let mut text = format!(
+        "*{}* \\({}\\)\n{}\n\nvote account: `{}`",
+        escape_markdown(notification.kind.title()),
+        notification.severity.as_str(),
+        escape_markdown(&notification.summary),
+        vote_account
+    );
+    for (name, value) in &notification.fields {
+        text.push_str(&format!("\n{}: {}", escape_markdown(name), escape_markdown(value)));
+    }
+    if notification.suppressed > 0 {
+        text.push_str(&format!("\nsuppressed: {}", notification.suppressed));
+    }
+    for signature in &notification.signatures {
+        // inside the parentheses of an inline link only ) and \ need escaping
+        let url = explorer.tx_url(signature).replace('\\', "\\\\").replace(')', "\\)");
+        text.push_str(&format!("\n[{}]({})", escape_markdown(&short_signature(signature)), url));
+    }
+    text
+}
+
+#[derive(Debug, Clone)]
+struct Destinations {
+    http: reqwest::Client,
+    discord_webhook_url: Option<String>,
+    telegram: Option<TelegramConfig>,
+    vote_account: String,
+    explorer: Explorer,
+}
+
+impl Destinations {
+    async fn deliver(&self, notification: &Notification) {
+        if let Some(ref url) = self.discord_webhook_url {
+            let payload = discord_payload(notification, &self.vote_account, &self.explorer);
+            if let Err(e) = self.post(url, &payload).await {
+                log::warn!("discord notification failed: {}", e);
+            }
+        }
+
+        if let Some(ref telegram) = self.telegram {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", telegram.bot_token);
+            let payload = json!({
+                "chat_id": telegram.chat_id,
+                "text": telegram_text(notification, &self.vote_account, &self.explorer),
+                "parse_mode": "MarkdownV2",
+                "disable_web_page_preview": true,
+            });
+            // the url carries the bot token, keep it out of the log
+            if let Err(e) = self.post(&url, &payload).await {
+                log::warn!("telegram notification failed: {}", e.without_url());
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, payload: &Value) -> std::result::Result<(), reqwest::Error> {
+        self.http.post(url).json(payload).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// spawn the notifier, alerts are detected from the event bus and delivered
+/// from a separate task so slow webhooks never hold up detection
+///
+/// publishing on the bus never waits, and a backlog of undelivered alerts drops
+/// the oldest. on shutdown, queued events and alerts are flushed before the
+/// returned task completes.
+pub fn spawn_notifier(
+    config: NotificationsConfig,
+    tvc: TvcConfig,
+    vote_account: &str,
+    explorer: Explorer,
+    mut events: broadcast::Receiver<SystemEvent>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<JoinHandle<()>> {
+    let destinations = Destinations {
+        http: reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?,
+        discord_webhook_url: config.discord_webhook_url.clone(),
+        telegram: config.telegram.clone(),
+        vote_account: vote_account.to_string(),
+        explorer,
+    };
+    let mut rate_limiter = RateLimiter::new(Duration::from_secs(config.rate_limit_secs));
+    let mut detector = AlertDetector::new(config, tvc);
+
+    let (queue_tx, mut queue_rx) = broadcast::channel::<Notification>(NOTIFICATION_QUEUE);
+    let delivery = tokio::spawn(async move {
+        loop {
+            match queue_rx.recv().await {
+                Ok(notification) => destinations.deliver(&notification).await,
+                Err(RecvError::Lagged(dropped)) => {
+                    log::warn!("notification backlog full, dropped {} oldest notifications", dropped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let mut dispatch = move |event: &SystemEvent| {
+        for mut notification in detector.handle(event) {
+            if rate_limiter.admit(&mut notification) {
+                log::info!("notification: {} - {}", notification.kind.title(), notification.summary);
+                let _ = queue_tx.send(notification);
+            }
+        }
+    };
+
+    Ok(tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => dispatch(&event),
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("notifier fell behind, skipped {} events, streaks may be off", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown.changed() => {
+                    loop {
+                        match events.try_recv() {
+                            Ok(event) => dispatch(&event),
+                            Err(TryRecvError::Lagged(_)) => continue,
+                            Err(_) => break,
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        // closing the queue lets delivery finish what is left
+        drop(dispatch);
+        let _ = delivery.await;
+        log::info!("notifier stopped");
+    }))
+}
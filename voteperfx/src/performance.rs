@@ -1,36 +1,244 @@
 use std::collections::VecDeque;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Local, Utc};
 use crossterm::style::Color;
 use serde::{Deserialize, Serialize};
-// use tokio::sync::mpsc;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
 
-use crate::config::PerformanceFilterConfig;
+use crate::account_info::AccountInfo;
+use crate::channel_metrics::PipelineMetrics;
+use crate::stream_health::StreamHealth;
+use crate::tower_root::{TowerRoot, TowerUpdate};
+use crate::cluster::ClusterSnapshot;
+use crate::cluster_tip::{ClusterTip, LagAlert, SlotCommitment};
+use crate::commitment::{Commitment, ConfirmedObservations};
+use crate::network::{expected_vote_rate, Network, DEFAULT_SLOT_DURATION_MS};
+use crate::config::{CoverageConfig, LossCausesConfig, PerformanceFilterConfig, ThresholdsConfig, TvcConfig};
+use crate::coverage::VoteCoverage;
+use crate::endpoints::EndpointPool;
+use crate::credits_check::CreditsDrift;
 use crate::error::Result;
+use crate::memory::{MemoryUsage, signature_bytes};
+use crate::process_metrics::ProcessMetrics;
+use crate::efficiency_trend::EfficiencyTrend;
+use crate::rolling_windows::RollingWindows;
+use crate::epoch::{EpochStats, EpochTracker, DEFAULT_SLOTS_PER_EPOCH};
+use crate::events::EventBus;
+use crate::filter_drift::FilterDrift;
+use crate::leader_schedule::{BlockProduction, LeaderAttribution, LeaderSlotVotes};
+use crate::loss_cause::{classify_loss, LossCause, LostCredits};
+use crate::message::SystemEvent;
+use crate::performance_log::{current_performance_log_file, performance_log_file};
+use crate::ring_buffer::RingBuffer;
+use crate::time_window::TimeWindow;
+use crate::vote_account::{AuthorizedVoterTracker, VoteAccountInfo};
+use crate::vote_tracker::{FailedVote, MissedVote, VoteTrackerStats};
+use crate::streaks::{OptimalStreaks, DEFAULT_STREAK_ALERT_VOTES};
+use crate::votes_sent::{SilenceChange, VotesSent};
 
 pub type Slot = u64;
 
 pub const VOTE_CREDITS_GRACE_SLOTS: u8 = 2;
 pub const VOTE_CREDITS_MAXIMUM_PER_SLOT: u8 = 16;
 
-#[derive(Debug, Clone)]
+/// confirmed votes kept for the dashboard and status api unless configured
+pub const DEFAULT_RECENT_VOTES_WINDOW: usize = 100;
+
+/// failed vote transactions kept for the dashboard and status api
+pub const RECENT_FAILED_VOTES: usize = 10;
+
+/// seconds of confirmed votes in the rolling average latency unless configured
+pub const DEFAULT_LATENCY_WINDOW_SECS: u64 = 60;
+
+/// poor votes kept for the dashboard and status api unless configured
+pub const DEFAULT_POOR_VOTES_WINDOW: usize = 50;
+
+/// most recent votes behind the window efficiency and status timeline
+const EFFICIENCY_WINDOW: usize = 20;
+
+/// a vote confirmed for the monitored account, as exported and published
+///
+/// the serialized field names are the schema of the jsonl vote log, the
+/// vote_confirmed events and the state file, fields are only ever added.
+/// `kind` says how the latency was established, `source` where the
+/// confirmation was read from; consumers branch on `kind`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfirmedVote {
-    pub signature: String,
+    /// base58 signature, shared with the tracker's signature cache and every
+    /// window holding the vote, serialized as a plain string
+    pub signature: Arc<String>,
     pub voted_slot: Slot,
     pub finalized_slot: Slot,
     pub latency: u64,
     pub tvc_credits: u64,
     pub timestamp: DateTime<Local>,
+    pub kind: ConfirmationKind,
+    pub source: ConfirmationSource,
+    /// wall-clock milliseconds from seeing the vote transaction to its
+    /// confirmation, none when the transaction was not seen first
+    #[serde(default)]
+    pub confirmation_ms: Option<u64>,
+    /// block time of the confirming block minus when the vote transaction was
+    /// seen, second resolution, none without a block time
+    #[serde(default)]
+    pub block_time_delta_ms: Option<i64>,
+    /// slot the vote transaction landed in, none without a matching pending
+    /// vote. `finalized_slot` is the block the confirmation was read from.
+    #[serde(default)]
+    pub landed_slot: Option<Slot>,
+    /// slot of the confirmed block carrying the vote minus the voted slot,
+    /// informational, credits come from the finalized block. only with
+    /// commitment "confirmed" or "both"
+    #[serde(default)]
+    pub confirmed_latency: Option<u64>,
+    /// wall-clock milliseconds from the confirmed block to finalization,
+    /// only with commitment "confirmed" or "both"
+    #[serde(default)]
+    pub finalization_delay_ms: Option<u64>,
+    /// why the vote earned less than max credits, set by the stats when
+    /// it is counted. none for optimal votes
+    #[serde(default)]
+    pub loss_cause: Option<LossCause>,
+    /// hash of the finalized block the vote was confirmed from, shared by
+    /// the votes of that block. none without a block (tx-status, rpc polling)
+    #[serde(default)]
+    pub blockhash: Option<Arc<str>>,
+    /// parent slot of that block, tells the fork it built on
+    #[serde(default)]
+    pub parent_slot: Option<Slot>,
+    /// block height of that block, none when the stream leaves it out
+    #[serde(default)]
+    pub block_height: Option<u64>,
+    /// voted before the last authorized voter change, cast by the previous
+    /// voter. set by the stats when it is counted
+    #[serde(default)]
+    pub before_voter_change: bool,
+}
+
+/// how the latency of a confirmed vote was established, set where it is
+/// confirmed
+///
+/// serialized in snake_case (`matched`, `direct_block_only`,
+/// `estimated_tx_slot`, `tower_latency`, `duplicate_suppressed`), the same
+/// spelling as the csv and database column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationKind {
+    /// block confirmation matched to the vote transaction, latency to the
+    /// block it was confirmed in or the slot it landed in
+    #[default]
+    Matched,
+    /// block confirmation whose transaction never showed up, latency to the
+    /// finalized block, pessimistic
+    DirectBlockOnly,
+    /// transaction slot reached finalized status (confirmation_source =
+    /// "tx-status"), the transaction slot stands in for the block
+    EstimatedTxSlot,
+    /// latency the vote program recorded in the tower, read over rpc
+    /// (source = "rpc") without seeing the transaction
+    TowerLatency,
+    /// voted slot already confirmed by another signature, e.g. a vote resent
+    /// after a failover. counted apart, never in the stats or exports
+    DuplicateSuppressed,
+}
+
+impl ConfirmationKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Matched => "matched",
+            Self::DirectBlockOnly => "direct_block_only",
+            Self::EstimatedTxSlot => "estimated_tx_slot",
+            Self::TowerLatency => "tower_latency",
+            Self::DuplicateSuppressed => "duplicate_suppressed",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "matched" => Some(Self::Matched),
+            "direct_block_only" => Some(Self::DirectBlockOnly),
+            "estimated_tx_slot" => Some(Self::EstimatedTxSlot),
+            "tower_latency" => Some(Self::TowerLatency),
+            "duplicate_suppressed" => Some(Self::DuplicateSuppressed),
+            _ => None,
+        }
+    }
+
+    /// the kind of a vote recorded before it was exported, from its
+    /// estimated flag and source
+    pub fn infer(estimated: bool, source: ConfirmationSource) -> Self {
+        match source {
+            ConfirmationSource::Block if estimated => Self::DirectBlockOnly,
+            ConfirmationSource::Block => Self::Matched,
+            ConfirmationSource::SlotStatus => Self::EstimatedTxSlot,
+            ConfirmationSource::VoteState => Self::TowerLatency,
+        }
+    }
+
+    /// latency not measured against the block that confirmed the vote,
+    /// left out of latency percentiles unless stats.percentiles_include_estimates
+    pub fn is_estimated(&self) -> bool {
+        matches!(self, Self::DirectBlockOnly | Self::EstimatedTxSlot | Self::TowerLatency)
+    }
+
+    /// counted in the session stats and exported
+    pub fn is_counted(&self) -> bool {
+        *self != Self::DuplicateSuppressed
+    }
+
+    /// suffix of the latency on the dashboard, empty for measured latencies
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Self::Matched | Self::DuplicateSuppressed => "",
+            Self::DirectBlockOnly => "*",
+            Self::EstimatedTxSlot => "~",
+            Self::TowerLatency => "^",
+        }
+    }
+}
+
+/// how a vote was confirmed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationSource {
+    /// matched against the vote transaction in a finalized block
+    #[default]
+    Block,
+    /// transaction slot reached finalized status, used when blocks are
+    /// unavailable or not subscribed (confirmation_source = "tx-status")
+    SlotStatus,
+    /// new entry in the vote account's tower read over json-rpc
+    /// (source = "rpc"), no transaction and no signature
+    VoteState,
+}
+
+impl ConfirmationSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfirmationSource::Block => "blocks",
+            ConfirmationSource::SlotStatus => "slot status (estimated)",
+            ConfirmationSource::VoteState => "rpc polling (approximate)",
+        }
+    }
+
+    /// latencies are estimated from the transaction slot, or read from the
+    /// vote account without seeing the vote
+    pub fn is_estimate(&self) -> bool {
+        *self != ConfirmationSource::Block
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TvcPerformanceLevel {
-    Optimal,   // 16 TVC
-    Good,      // 12-15 TVC  
-    Fair,      // 8-11 TVC
-    Poor,      // 4-7 TVC
+    Optimal,   // 16 TVC (max credits)
+    Good,      // 12-15 TVC (>= 3/4 of max)
+    Fair,      // 8-11 TVC (>= 1/2 of max)
+    Poor,      // 4-7 TVC (>= 1/4 of max)
     Critical,  // 1-3 TVC
 }
 
@@ -56,6 +264,181 @@ impl TvcPerformanceLevel {
     }
 }
 
+/// non-optimal votes per severity this session, never truncated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityHistogram {
+    pub good: u64,
+    pub fair: u64,
+    pub poor: u64,
+    pub critical: u64,
+}
+
+impl SeverityHistogram {
+    pub fn record(&mut self, level: TvcPerformanceLevel) {
+        match level {
+            TvcPerformanceLevel::Optimal => {}
+            TvcPerformanceLevel::Good => self.good += 1,
+            TvcPerformanceLevel::Fair => self.fair += 1,
+            TvcPerformanceLevel::Poor => self.poor += 1,
+            TvcPerformanceLevel::Critical => self.critical += 1,
+        }
+    }
+
+    /// every vote below max credits
+    pub fn total(&self) -> u64 {
+        self.good + self.fair + self.poor + self.critical
+    }
+
+    /// votes at `level` or worse, every vote below max credits for optimal
+    pub fn at_or_below(&self, level: TvcPerformanceLevel) -> u64 {
+        match level {
+            TvcPerformanceLevel::Optimal | TvcPerformanceLevel::Good => self.total(),
+            TvcPerformanceLevel::Fair => self.fair + self.poor + self.critical,
+            TvcPerformanceLevel::Poor => self.poor + self.critical,
+            TvcPerformanceLevel::Critical => self.critical,
+        }
+    }
+}
+
+/// latencies counted exactly by `LatencyDistribution`, higher ones share the last bucket
+pub const LATENCY_BUCKETS: usize = 5;
+
+/// votes per exact latency this session, 0, 1, 2, 3 and 4+ slots
+///
+/// optimal votes at the grace limit earn full credits but are a slot away
+/// from losing one, `grace_dependent_pct` tells them apart from the rest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyDistribution {
+    pub counts: [u64; LATENCY_BUCKETS],
+    /// votes at exactly tvc.grace_slots
+    pub at_grace_limit: u64,
+}
+
+impl LatencyDistribution {
+    pub fn record(&mut self, latency: u64, grace_slots: u64) {
+        let bucket = (latency as usize).min(LATENCY_BUCKETS - 1);
+        self.counts[bucket] += 1;
+        if latency == grace_slots {
+            self.at_grace_limit += 1;
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// share of each bucket in percent, none before the first vote
+    pub fn percentages(&self) -> Option<[f64; LATENCY_BUCKETS]> {
+        let total = self.total();
+        (total > 0).then(|| self.counts.map(|count| count as f64 / total as f64 * 100.0))
+    }
+
+    /// votes that only kept full credits thanks to the last grace slot, in percent
+    pub fn grace_dependent_pct(&self) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        self.at_grace_limit as f64 / total as f64 * 100.0
+    }
+
+    /// `0: 2% | 1: 81% | 2: 15% | 3: 1% | 4+: 1%`
+    pub fn summary(&self) -> Option<String> {
+        let percentages = self.percentages()?;
+        let parts: Vec<String> = percentages
+            .iter()
+            .enumerate()
+            .map(|(latency, pct)| {
+                let label = if latency == LATENCY_BUCKETS - 1 { format!("{}+", latency) } else { latency.to_string() };
+                format!("{}: {:.0}%", label, pct)
+            })
+            .collect();
+        Some(parts.join(" | "))
+    }
+}
+
+/// buckets of `TvcHistogram`, one per credit value from 0 to the maximum
+pub const TVC_BUCKETS: usize = VOTE_CREDITS_MAXIMUM_PER_SLOT as usize + 1;
+
+/// votes per earned credits, 0 to 16 tvc, serialized as the plain counts
+///
+/// with a tvc.max_credits_per_slot above 16 the higher credits share the
+/// last bucket.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TvcHistogram {
+    pub counts: [u64; TVC_BUCKETS],
+}
+
+impl TvcHistogram {
+    pub fn record(&mut self, tvc_credits: u64) {
+        let bucket = tvc_credits.min(TVC_BUCKETS as u64 - 1) as usize;
+        self.counts[bucket] += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// (credits, votes) of the buckets with votes, most credits first
+    pub fn nonzero(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, &count)| count > 0)
+            .map(|(credits, &count)| (credits as u64, count))
+    }
+
+    /// `16: 96.1% | 15: 3.0% | 9: 0.2%`, none before the first vote
+    pub fn summary(&self) -> Option<String> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let parts: Vec<String> = self
+            .nonzero()
+            .map(|(credits, count)| format!("{}: {:.1}%", credits, count as f64 / total as f64 * 100.0))
+            .collect();
+        Some(parts.join(" | "))
+    }
+}
+
+/// how histogram bars grow with their count
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistogramScale {
+    #[default]
+    Linear,
+    /// by log(1 + count), a bucket with a handful of votes stays visible
+    /// next to one with thousands
+    Log,
+}
+
+impl HistogramScale {
+    /// columns of the bar for `count` when `max` fills `width`, at least
+    /// one for any vote
+    pub fn bar_len(self, count: u64, max: u64, width: usize) -> usize {
+        if count == 0 || max == 0 || width == 0 {
+            return 0;
+        }
+        let fraction = match self {
+            Self::Linear => count as f64 / max as f64,
+            Self::Log => (count as f64).ln_1p() / (max as f64).ln_1p(),
+        };
+        ((fraction * width as f64).round() as usize).clamp(1, width)
+    }
+}
+
+/// what `add_confirmed_vote_with_config` leaves to do after the stats lock is released
+#[derive(Debug, Default)]
+pub struct ConfirmedVoteFollowUp {
+    /// the epoch the vote closed
+    pub completed_epoch: Option<EpochStats>,
+    /// a poor vote for the performance log, set without a writer task to queue it on
+    pub unqueued_event: Option<PoorPerformanceEvent>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PoorPerformanceEvent {
     pub timestamp: DateTime<Utc>,
@@ -63,81 +446,259 @@ pub struct PoorPerformanceEvent {
     pub voted_slot: Slot,
     pub latency: u64,
     pub tvc_credits: u64,
-    pub transaction_signature: String,
+    pub transaction_signature: Arc<String>,
     pub vote_account: String,
     pub total_tvc_credits: u64,
     pub total_voted_slots: usize,
     pub tvc_multiplier: f64,
+    /// leader of the voted slot, only with rpc_url
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leader: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loss_cause: Option<LossCause>,
+    /// block the vote was confirmed from, see `ConfirmedVote::blockhash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockhash: Option<Arc<str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_slot: Option<Slot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u64>,
+    /// see `ConfirmedVote::kind`, matched in events written before it was added
+    #[serde(default)]
+    pub kind: ConfirmationKind,
+}
+
+impl From<PoorPerformanceEvent> for ConfirmedVote {
+    fn from(event: PoorPerformanceEvent) -> Self {
+        Self {
+            signature: event.transaction_signature,
+            voted_slot: event.voted_slot,
+            finalized_slot: event.landed_slot,
+            latency: event.latency,
+            tvc_credits: event.tvc_credits,
+            timestamp: event.timestamp.with_timezone(&Local),
+            kind: event.kind,
+            source: ConfirmationSource::Block,
+            confirmation_ms: None,
+            block_time_delta_ms: None,
+            landed_slot: None,
+            confirmed_latency: None,
+            finalization_delay_ms: None,
+            loss_cause: event.loss_cause,
+            blockhash: event.blockhash,
+            parent_slot: event.parent_slot,
+            block_height: event.block_height,
+            before_voter_change: false,
+        }
+    }
 }
 
-/// circular buffer for recent votes - more efficient than vecdeque
-pub struct CircularVoteBuffer {
-    votes: Vec<Option<ConfirmedVote>>,
-    head: usize,
-    tail: usize,
-    size: usize,
-    capacity: usize,
+/// landing offset buckets: +1, +2 and +3 or more slots after the voted slot
+pub const LANDING_OFFSET_BUCKETS: usize = 3;
+
+/// how many slots after the voted slot vote transactions landed, separate
+/// from finalization latency
+#[derive(Debug, Clone, Default)]
+pub struct LandingOffsets {
+    counts: [u64; LANDING_OFFSET_BUCKETS],
 }
 
-impl CircularVoteBuffer {
-    pub fn new(capacity: usize) -> Self {
+impl LandingOffsets {
+    /// a vote can't land before the slot after the one it votes on, lower
+    /// offsets count as +1
+    pub fn record(&mut self, voted_slot: Slot, landed_slot: Slot) {
+        let offset = landed_slot.saturating_sub(voted_slot).max(1) as usize;
+        self.counts[offset.min(LANDING_OFFSET_BUCKETS) - 1] += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// (offset, percentage of votes) per bucket, the last covers every larger offset,
+    /// none before any vote with a landed slot
+    pub fn distribution(&self) -> Option<[(u64, f64); LANDING_OFFSET_BUCKETS]> {
+        let total = self.total();
+        (total > 0).then(|| {
+            std::array::from_fn(|bucket| (bucket as u64 + 1, self.counts[bucket] as f64 / total as f64 * 100.0))
+        })
+    }
+}
+
+/// session numbers right before `PerformanceStats::reset`
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsReset {
+    pub session_started_at: DateTime<Local>,
+    pub session_secs: u64,
+    pub votes: u64,
+    pub tvc_earned: u64,
+    pub tvc_possible: u64,
+    pub efficiency: f64,
+    pub avg_latency: f64,
+    pub missed_votes: u64,
+    pub failed_votes: u64,
+    /// the per-epoch breakdown was kept
+    pub kept_epoch_history: bool,
+}
+
+impl StatsReset {
+    /// `12,345 votes over 1h 2m 5s | efficiency 97.2% (191,234/196,736) | avg latency 1.6 slots | 3 missed | 0 failed`
+    pub fn summary(&self) -> String {
+        format!(
+            "{} votes over {} | efficiency {:.1}% ({}/{}) | avg latency {:.1} slots | {} missed | {} failed",
+            format_number(self.votes),
+            format_duration(Duration::from_secs(self.session_secs)),
+            self.efficiency,
+            format_number(self.tvc_earned),
+            format_number(self.tvc_possible),
+            self.avg_latency,
+            format_number(self.missed_votes),
+            format_number(self.failed_votes),
+        )
+    }
+}
+
+/// contiguous period during which the windowed status stayed the same
+#[derive(Debug, Clone)]
+pub struct StatusSegment {
+    pub status: &'static str,
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    started_at: Instant,
+}
+
+impl StatusSegment {
+    pub fn duration(&self) -> std::time::Duration {
+        match self.end {
+            Some(end) => (end - self.start).to_std().unwrap_or_default(),
+            None => self.started_at.elapsed(),
+        }
+    }
+}
+
+/// bounded timeline of windowed performance status changes
+#[derive(Debug, Clone)]
+pub struct StatusTimeline {
+    segments: VecDeque<StatusSegment>,
+    max_segments: usize,
+    // closed time per status: optimal, good, poor
+    closed_totals: [std::time::Duration; 3],
+}
+
+impl StatusTimeline {
+    pub const STATUSES: [&'static str; 3] = ["optimal", "good", "poor"];
+    
+    pub fn new(max_segments: usize) -> Self {
         Self {
-            votes: vec![None; capacity],
-            head: 0,
-            tail: 0,
-            size: 0,
-            capacity,
+            segments: VecDeque::with_capacity(max_segments),
+            max_segments,
+            closed_totals: [std::time::Duration::ZERO; 3],
         }
     }
     
-    pub fn push(&mut self, vote: ConfirmedVote) {
-        self.votes[self.tail] = Some(vote);
-        self.tail = (self.tail + 1) % self.capacity;
+    /// record the current status, opening a new segment on change
+    pub fn update(&mut self, status: &'static str) {
+        if let Some(current) = self.segments.back_mut() {
+            if current.status == status {
+                return;
+            }
+            let elapsed = current.started_at.elapsed();
+            current.end = Some(current.start + chrono::Duration::from_std(elapsed).unwrap_or_default());
+            if let Some(idx) = Self::index_of(current.status) {
+                self.closed_totals[idx] += elapsed;
+            }
+            
+            log::info!(
+                "performance status changed: {} -> {} (after {})",
+                current.status, status, format_duration(elapsed)
+            );
+        }
         
-        if self.size < self.capacity {
-            self.size += 1;
-        } else {
-            self.head = (self.head + 1) % self.capacity;
+        self.segments.push_back(StatusSegment {
+            status,
+            start: Local::now(),
+            end: None,
+            started_at: Instant::now(),
+        });
+        if self.segments.len() > self.max_segments {
+            self.segments.pop_front();
         }
     }
     
-    pub fn len(&self) -> usize {
-        self.size
+    /// total time spent in a status this session, including the open segment
+    pub fn time_in(&self, status: &str) -> std::time::Duration {
+        let closed = Self::index_of(status)
+            .map(|idx| self.closed_totals[idx])
+            .unwrap_or_default();
+        match self.segments.back() {
+            Some(current) if current.status == status => closed + current.started_at.elapsed(),
+            _ => closed,
+        }
     }
     
-    pub fn iter(&self) -> impl Iterator<Item = &ConfirmedVote> {
-        let mut idx = self.head;
-        let mut count = 0;
-        std::iter::from_fn(move || {
-            if count >= self.size {
-                return None;
-            }
-            let item = self.votes[idx].as_ref();
-            idx = (idx + 1) % self.capacity;
-            count += 1;
-            item
-        })
+    /// current status and how long it has lasted
+    pub fn current_streak(&self) -> Option<(&'static str, std::time::Duration)> {
+        self.segments.back().map(|current| (current.status, current.duration()))
+    }
+    
+    pub fn segments(&self) -> impl Iterator<Item = &StatusSegment> {
+        self.segments.iter()
+    }
+    
+    fn index_of(status: &str) -> Option<usize> {
+        Self::STATUSES.iter().position(|s| *s == status)
     }
 }
 
+/// aggregates over the recent votes window
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct RecentWindowSummary {
+    pub votes: u64,
+    pub avg_latency: f64,
+    pub tvc_lost: u64,
+    pub optimal_pct: f64,
+    /// mean time from confirmed block to finalized vote, none without
+    /// confirmed sightings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_finalization_delay_ms: Option<f64>,
+}
+
 #[derive(Debug)]
 pub struct PerformanceStats {
+    // may predate this process when restored from a state file
     pub session_start: Instant,
+    pub session_started_at: DateTime<Local>,
+    pub process_start: Instant,
     pub total_transactions: AtomicU64,
     
     pub total_tvc_earned: AtomicU64,
     pub total_tvc_possible: AtomicU64,
     
-    pub optimal_votes: AtomicU64,    // 16 TVC
-    pub good_votes: AtomicU64,       // 12-15 TVC  
-    pub poor_votes: AtomicU64,       // <12 TVC
-    pub low_latency_votes: AtomicU64, // latency <= 2 slots
+    pub optimal_votes: AtomicU64,    // max credits (16 TVC)
+    pub good_votes: AtomicU64,       // >= 3/4 of max (12-15 TVC)
+    pub poor_votes: AtomicU64,       // below that (<12 TVC)
+    pub low_latency_votes: AtomicU64, // latency within thresholds.low_latency_slots
+    pub missed_votes: AtomicU64,      // voted slots never finalized
+    pub failed_votes: AtomicU64,      // vote transactions that landed with an error
+    pub foreign_votes_ignored: AtomicU64, // vote transactions for another vote account
+    pub duplicate_confirmations: AtomicU64, // voted slots confirmed again by another signature
     
     // memory usage with circular buffers
-    pub recent_confirmed_votes: VecDeque<ConfirmedVote>, // kept for compatibility
+    pub recent_confirmed_votes: RingBuffer<ConfirmedVote>,
+    /// newest `poor_votes_window` votes below max credits
     pub session_poor_votes: VecDeque<ConfirmedVote>,
-    pub avg_latency_window: VecDeque<u64>,
-    pub avg_latency_window_sum: AtomicU64,
+    pub poor_votes_window: usize,
+    /// every vote below max credits by severity, unlike session_poor_votes
+    pub severity: SeverityHistogram,
+    /// every confirmed vote by exact latency
+    pub latency_distribution: LatencyDistribution,
+    /// every confirmed vote by earned credits
+    pub tvc_histogram: TvcHistogram,
+    /// credits lost by every vote below max credits, per cause
+    pub lost_credits: LostCredits,
+    pub recent_failed_votes: VecDeque<FailedVote>,
+    /// latencies of the votes confirmed in the last `stats.recent_window_secs`
+    pub avg_latency_window: TimeWindow,
     
     // current state
     pub current_finalized_slot: AtomicU64,
@@ -146,14 +707,123 @@ pub struct PerformanceStats {
     // session-wide
     pub total_latency_sum: AtomicU64,
     
-    // implement batched event writer channel?
-    // event_sender: Option<mpsc::Sender<PoorPerformanceEvent>>,
+    // windowed status over time
+    pub status_timeline: StatusTimeline,
+    
+    // efficiency per fixed-duration bucket, rolled by the block task's maintenance tick
+    pub efficiency_trend: EfficiencyTrend,
+    
+    // efficiency and latency over the last minute, 15 minutes and hour,
+    // rolled with the efficiency trend
+    pub rolling: RollingWindows,
+    
+    // vote transactions of the monitored account per minute, from the
+    // transaction path before confirmation, rolled with the efficiency trend
+    pub votes_sent: VotesSent,
+    
+    // consecutive max credit votes, broken streaks of at least
+    // streak_alert_votes are published (0 never)
+    pub streaks: OptimalStreaks,
+    pub streak_alert_votes: u64,
+    
+    // landed slot minus voted slot of votes matched to their transaction
+    pub landing_offsets: LandingOffsets,
+    
+    // per-epoch breakdown
+    pub epochs: EpochTracker,
+    
+    // authorized voter of the monitored account (follow_authorized_voter)
+    pub authorized_voter: AuthorizedVoterTracker,
+    pub pre_voter_change_votes: AtomicU64,
+    
+    // where confirmations currently come from, shown on the dashboard
+    pub confirmation_source: ConfirmationSource,
+    
+    // commitment the vote blocks are read at, and the confirmed sightings
+    // waiting for their finalized vote with "confirmed" or "both"
+    pub commitment: Commitment,
+    pub confirmed_observations: ConfirmedObservations,
+    
+    // monitored cluster and its slot time, the vote rate is held against it
+    pub network: Network,
+    pub slot_duration: Duration,
+    
+    // credit parameters used for bucketing and possible credits
+    pub tvc: TvcConfig,
+    
+    // status boundaries and the low latency cutoff
+    pub thresholds: ThresholdsConfig,
+    
+    // what votes below max credits are blamed on
+    pub loss_causes: LossCausesConfig,
+    
+    // finalized slots with and without a vote from the monitored account
+    pub coverage: VoteCoverage,
+    
+    // newest processed, confirmed and finalized slots on the slot status stream
+    pub cluster_tip: ClusterTip,
+    
+    // root progression of the monitored tower this session
+    pub tower_root: TowerRoot,
+    
+    // identity and commission of the vote account, read at startup (rpc_url)
+    pub vote_account_info: Option<VoteAccountInfo>,
+    // stake, commission and delinquency, refreshed over getVoteAccounts (rpc_url)
+    pub account_info: Option<AccountInfo>,
+    
+    // backpressure on the update channels, set once the stream starts
+    pub pipeline: Option<Arc<PipelineMetrics>>,
+    /// retry queue of the poor performance event writer, when it runs
+    pub event_writer: Option<Arc<EventWriterMetrics>>,
+    // age of the last grpc updates, set once the stream starts
+    pub stream_health: Option<Arc<StreamHealth>>,
+    // active grpc endpoint and failovers, set once the stream starts
+    pub endpoints: Option<Arc<EndpointPool>>,
+    // cpu, rss and allocator usage of this process, see Monitor::sample_process
+    pub process: Option<ProcessMetrics>,
+    // vote tracker counters for the debug line, see Monitor::sample_tracker
+    pub tracker: Option<VoteTrackerStats>,
+    // grpc update rates off their baseline, see spawn_filter_drift_watchdog
+    pub filter_drift: Vec<FilterDrift>,
+    
+    // latest on-chain credits cross-check (rpc_url)
+    pub credits_drift: Option<CreditsDrift>,
+    
+    // stake-weighted cluster efficiency, kept across failed refreshes (rpc_url)
+    pub cluster: Option<ClusterSnapshot>,
+    
+    // poor votes per leader of the voted slot, only with rpc_url
+    pub leader_attribution: Option<LeaderAttribution>,
+    // votes for our own leader slots stay out of the latency averages
+    pub exclude_leader_slot_latency: bool,
+    // estimated latencies count in the latency percentiles too
+    pub percentiles_include_estimates: bool,
+    
+    // confirmed votes and poor performance events are published here
+    events: Option<EventBus>,
+    
+    // poor performance events go to the batched writer task when set,
+    // otherwise each event is appended to the daily file directly
+    event_sender: Option<mpsc::Sender<PoorPerformanceEvent>>,
+    // events the writer channel had no room for
+    pub dropped_performance_events: AtomicU64,
+    
+    // bumped on every change the dashboard shows, lets it skip idle frames
+    generation: AtomicU64,
+}
+
+impl Default for PerformanceStats {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PerformanceStats {
     pub fn new() -> Self {
         Self {
             session_start: Instant::now(),
+            session_started_at: Local::now(),
+            process_start: Instant::now(),
             total_transactions: AtomicU64::new(0),
             total_tvc_earned: AtomicU64::new(0),
             total_tvc_possible: AtomicU64::new(0),
@@ -161,69 +831,555 @@ impl PerformanceStats {
             good_votes: AtomicU64::new(0),
             poor_votes: AtomicU64::new(0),
             low_latency_votes: AtomicU64::new(0),
-            recent_confirmed_votes: VecDeque::with_capacity(20),
-            session_poor_votes: VecDeque::with_capacity(50),
-            avg_latency_window: VecDeque::with_capacity(20),
-            avg_latency_window_sum: AtomicU64::new(0),
+            missed_votes: AtomicU64::new(0),
+            failed_votes: AtomicU64::new(0),
+            foreign_votes_ignored: AtomicU64::new(0),
+            duplicate_confirmations: AtomicU64::new(0),
+            recent_confirmed_votes: RingBuffer::new(DEFAULT_RECENT_VOTES_WINDOW),
+            session_poor_votes: VecDeque::with_capacity(DEFAULT_POOR_VOTES_WINDOW),
+            poor_votes_window: DEFAULT_POOR_VOTES_WINDOW,
+            severity: SeverityHistogram::default(),
+            latency_distribution: LatencyDistribution::default(),
+            tvc_histogram: TvcHistogram::default(),
+            lost_credits: LostCredits::default(),
+            recent_failed_votes: VecDeque::with_capacity(RECENT_FAILED_VOTES),
+            avg_latency_window: TimeWindow::new(Duration::from_secs(DEFAULT_LATENCY_WINDOW_SECS)),
             current_finalized_slot: AtomicU64::new(0),
             last_confirmed_vote: None,
             total_latency_sum: AtomicU64::new(0),
-            // event_sender: None,
+            status_timeline: StatusTimeline::new(100),
+            efficiency_trend: EfficiencyTrend::default(),
+            rolling: RollingWindows::new(),
+            votes_sent: VotesSent::new(),
+            streaks: OptimalStreaks::default(),
+            streak_alert_votes: DEFAULT_STREAK_ALERT_VOTES,
+            landing_offsets: LandingOffsets::default(),
+            epochs: EpochTracker::new(DEFAULT_SLOTS_PER_EPOCH),
+            authorized_voter: AuthorizedVoterTracker::new(),
+            pre_voter_change_votes: AtomicU64::new(0),
+            confirmation_source: ConfirmationSource::Block,
+            commitment: Commitment::default(),
+            confirmed_observations: ConfirmedObservations::default(),
+            network: Network::default(),
+            slot_duration: Duration::from_millis(DEFAULT_SLOT_DURATION_MS),
+            tvc: TvcConfig::default(),
+            thresholds: ThresholdsConfig::default(),
+            loss_causes: LossCausesConfig::default(),
+            coverage: VoteCoverage::default(),
+            cluster_tip: ClusterTip::default(),
+            tower_root: TowerRoot::default(),
+            vote_account_info: None,
+            account_info: None,
+            pipeline: None,
+            event_writer: None,
+            stream_health: None,
+            endpoints: None,
+            process: None,
+            tracker: None,
+            filter_drift: Vec::new(),
+            credits_drift: None,
+            cluster: None,
+            leader_attribution: None,
+            exclude_leader_slot_latency: false,
+            percentiles_include_estimates: false,
+            events: None,
+            event_sender: None,
+            dropped_performance_events: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
         }
     }
     
+    /// epoch length used for the per-epoch breakdown (testnets differ)
+    pub fn with_slots_per_epoch(mut self, slots_per_epoch: u64) -> Self {
+        self.epochs = EpochTracker::new(slots_per_epoch).with_estimates_in_percentiles(self.percentiles_include_estimates);
+        self
+    }
+
+    /// number of confirmed votes retained for the recent votes view
+    pub fn with_recent_votes_window(mut self, window: usize) -> Self {
+        self.recent_confirmed_votes = RingBuffer::new(window.max(1));
+        self
+    }
+
+    /// number of poor votes retained for the poor performance events view
+    pub fn with_poor_votes_window(mut self, window: usize) -> Self {
+        self.poor_votes_window = window.max(1);
+        self.session_poor_votes = VecDeque::with_capacity(self.poor_votes_window);
+        self
+    }
+
+    /// age of the oldest confirmed vote in the rolling average latency
+    pub fn with_latency_window(mut self, window: Duration) -> Self {
+        self.avg_latency_window = TimeWindow::new(window.max(Duration::from_secs(1)));
+        self
+    }
+
+    /// bucket length and count of the efficiency trend
+    pub fn with_efficiency_trend(mut self, bucket_duration: Duration, buckets: usize) -> Self {
+        self.efficiency_trend = EfficiencyTrend::new(bucket_duration, buckets);
+        self
+    }
+
+    /// finalized lag behind the processed tip that logs a warning, 0 disables it
+    pub fn with_finalized_lag_alert(mut self, slots: u64) -> Self {
+        self.cluster_tip = ClusterTip::new(slots);
+        self
+    }
+
+    /// warn when the tower root trails the newest voted slot by more than `slots`, 0 disables it
+    pub fn with_root_lag_alert(mut self, slots: u64) -> Self {
+        self.tower_root = TowerRoot::new(slots);
+        self
+    }
+
+    /// attribute poor votes to leaders once schedules arrive, see spawn_leader_schedule_fetcher.
+    /// with the validator identity, votes for its own leader slots are counted separately
+    pub fn with_leader_attribution(mut self, identity: Option<&str>) -> Self {
+        let attribution = LeaderAttribution::default();
+        self.leader_attribution = Some(match identity {
+            Some(identity) => attribution.with_identity(identity),
+            None => attribution,
+        });
+        self
+    }
+
+    /// broken optimal streaks of at least `votes` are published, 0 never
+    pub fn with_streak_alert(mut self, votes: u64) -> Self {
+        self.streak_alert_votes = votes;
+        self
+    }
+    
+    /// leave votes for our own leader slots out of the session and window latency averages
+    pub fn with_leader_slot_latency_excluded(mut self, exclude: bool) -> Self {
+        self.exclude_leader_slot_latency = exclude;
+        self
+    }
+    
+    /// count estimated latencies in the latency percentiles, see `ConfirmationKind::is_estimated`
+    pub fn with_estimates_in_percentiles(mut self, include: bool) -> Self {
+        self.percentiles_include_estimates = include;
+        self.epochs = self.epochs.with_estimates_in_percentiles(include);
+        self
+    }
+
+    pub fn with_coverage_config(mut self, config: &CoverageConfig) -> Self {
+        self.coverage = VoteCoverage::new(config);
+        self
+    }
+
+    /// credit parameters for networks whose feature gates differ from mainnet
+    pub fn with_tvc_config(mut self, tvc: TvcConfig) -> Self {
+        self.tvc = tvc;
+        self
+    }
+
+    /// votes carry confirmed figures unless `commitment` is finalized
+    pub fn with_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// cluster and expected slot time, see `NetworkProfile`
+    pub fn with_network(mut self, network: Network, slot_duration: Duration) -> Self {
+        self.network = network;
+        self.slot_duration = slot_duration;
+        self
+    }
+
+    pub fn with_thresholds(mut self, thresholds: ThresholdsConfig) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    pub fn with_loss_causes(mut self, loss_causes: LossCausesConfig) -> Self {
+        self.loss_causes = loss_causes;
+        self
+    }
+
+    /// publish confirmed votes and poor performance events on `events`
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// queue poor performance events for the writer task instead of writing them inline
+    pub fn with_event_sender(mut self, sender: mpsc::Sender<PoorPerformanceEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+
+    /// zero the session counters, empty the windows and restart the session
+    /// clock, see `reset_session` for what is kept. with `keep_epoch_history`
+    /// the per-epoch breakdown carries on, otherwise it starts over too.
+    ///
+    /// the numbers before the reset are logged, published as `StatsReset`
+    /// and returned. it runs under the stats write lock like every vote, so
+    /// a vote is counted either entirely before the reset or entirely after
+    /// it. a vote cast before the reset but confirmed after it counts in the
+    /// new session, as do missed votes detected after it.
+    pub fn reset(&mut self, keep_epoch_history: bool) -> StatsReset {
+        let reset = StatsReset {
+            session_started_at: self.session_started_at,
+            session_secs: self.session_start.elapsed().as_secs(),
+            votes: self.total_transactions(),
+            tvc_earned: self.total_tvc_earned(),
+            tvc_possible: self.total_tvc_possible(),
+            efficiency: self.calculate_efficiency(),
+            avg_latency: self.calculate_session_avg_latency(),
+            missed_votes: self.missed_votes(),
+            failed_votes: self.failed_votes(),
+            kept_epoch_history: keep_epoch_history,
+        };
+
+        let slots_per_epoch = self.epochs.slots_per_epoch();
+        let epochs = keep_epoch_history.then(|| std::mem::replace(&mut self.epochs, EpochTracker::new(slots_per_epoch)));
+        self.reset_session();
+        if let Some(epochs) = epochs {
+            self.epochs = epochs;
+        }
+
+        log::info!("stats reset, before: {}", reset.summary());
+        if let Some(ref events) = self.events {
+            events.publish(SystemEvent::StatsReset(Arc::new(reset.clone())));
+        }
+        reset
+    }
+
+    /// start a fresh session, zeroing the counters and emptying the windows in
+    /// place. the settings from the `with_` builders stay as they are, as do
+    /// the chain position, authorized voter, vote account info, confirmation
+    /// source, pending confirmed sightings, attached metrics, event bus and
+    /// event writer
+    pub fn reset_session(&mut self) {
+        self.session_start = Instant::now();
+        self.session_started_at = Local::now();
+        for counter in [
+            &self.total_transactions,
+            &self.total_tvc_earned,
+            &self.total_tvc_possible,
+            &self.optimal_votes,
+            &self.good_votes,
+            &self.poor_votes,
+            &self.low_latency_votes,
+            &self.missed_votes,
+            &self.failed_votes,
+            &self.foreign_votes_ignored,
+            &self.duplicate_confirmations,
+            &self.total_latency_sum,
+            &self.pre_voter_change_votes,
+            &self.dropped_performance_events,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.recent_confirmed_votes.clear();
+        self.session_poor_votes.clear();
+        self.severity = SeverityHistogram::default();
+        self.latency_distribution = LatencyDistribution::default();
+        self.tvc_histogram = TvcHistogram::default();
+        self.lost_credits = LostCredits::default();
+        self.recent_failed_votes.clear();
+        self.avg_latency_window.clear();
+        self.last_confirmed_vote = None;
+        self.status_timeline = StatusTimeline::new(self.status_timeline.max_segments);
+        self.efficiency_trend = EfficiencyTrend::new(self.efficiency_trend.bucket_duration(), self.efficiency_trend.capacity());
+        self.rolling = RollingWindows::new();
+        self.votes_sent = VotesSent::new();
+        self.streaks = OptimalStreaks::default();
+        self.landing_offsets = LandingOffsets::default();
+        self.epochs = EpochTracker::new(self.epochs.slots_per_epoch())
+            .with_estimates_in_percentiles(self.percentiles_include_estimates);
+        self.coverage = self.coverage.fresh();
+        self.tower_root = self.tower_root.fresh();
+        self.leader_attribution = self.leader_attribution.as_ref().map(LeaderAttribution::fresh);
+        self.credits_drift = None;
+        self.mark_changed();
+    }
+
+    /// returns the previous epoch's summary when this vote rolls over into a new epoch
     #[inline]
-    pub fn add_confirmed_vote(&mut self, confirmed: ConfirmedVote) {
+    /// flag a vote cast before the last authorized voter change, a replayed
+    /// vote keeps the flag it was recorded with
+    pub fn mark_voter_change(&self, confirmed: &mut ConfirmedVote) {
+        if self.authorized_voter.is_before_change(confirmed.voted_slot) {
+            confirmed.before_voter_change = true;
+        }
+    }
+
+    pub fn add_confirmed_vote(&mut self, mut confirmed: ConfirmedVote) -> Option<EpochStats> {
+        // the slot already earned its credits with the first signature
+        if !confirmed.kind.is_counted() {
+            self.duplicate_confirmations.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        // recorded votes keep the cause they were exported with
+        if confirmed.loss_cause.is_none() {
+            confirmed.loss_cause = self.loss_cause(&confirmed);
+        }
         // atomic operations for lock-free updates
         self.total_transactions.fetch_add(1, Ordering::Relaxed);
         self.total_tvc_earned.fetch_add(confirmed.tvc_credits, Ordering::Relaxed);
-        self.total_tvc_possible.fetch_add(VOTE_CREDITS_MAXIMUM_PER_SLOT as u64, Ordering::Relaxed);
+        self.total_tvc_possible.fetch_add(self.tvc.max_credits_per_slot, Ordering::Relaxed);
         self.current_finalized_slot.store(confirmed.finalized_slot, Ordering::Relaxed);
         self.total_latency_sum.fetch_add(confirmed.latency, Ordering::Relaxed);
         
-        match confirmed.tvc_credits {
-            16 => { self.optimal_votes.fetch_add(1, Ordering::Relaxed); },
-            12..=15 => { self.good_votes.fetch_add(1, Ordering::Relaxed); },
-            _ => { self.poor_votes.fetch_add(1, Ordering::Relaxed); },
+        if confirmed.tvc_credits >= self.tvc.max_credits_per_slot {
+            self.optimal_votes.fetch_add(1, Ordering::Relaxed);
+        } else if confirmed.tvc_credits >= self.tvc.good_threshold() {
+            self.good_votes.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.poor_votes.fetch_add(1, Ordering::Relaxed);
         }
         
-        if confirmed.latency <= 2 {
+        if confirmed.latency <= self.thresholds.low_latency_slots {
             self.low_latency_votes.fetch_add(1, Ordering::Relaxed);
         }
+        if let Some(broken) = self.streaks.record(&confirmed, self.tvc.max_credits_per_slot) {
+            if self.streak_alert_votes > 0 && broken.length >= self.streak_alert_votes {
+                if let Some(ref events) = self.events {
+                    events.publish(SystemEvent::OptimalStreakBroken(broken));
+                }
+            }
+        }
+        self.latency_distribution.record(confirmed.latency, self.tvc.grace_slots);
+        self.tvc_histogram.record(confirmed.tvc_credits);
+        
+        // in-flight votes cast by the previous authorized voter
+        self.mark_voter_change(&mut confirmed);
+        if confirmed.before_voter_change {
+            self.pre_voter_change_votes.fetch_add(1, Ordering::Relaxed);
+        }
         
-        self.recent_confirmed_votes.push_back(confirmed.clone());
-        if self.recent_confirmed_votes.len() > 20 {
-            self.recent_confirmed_votes.pop_front();
+        self.coverage.record_vote(confirmed.voted_slot);
+        if let Some(landed_slot) = confirmed.landed_slot {
+            self.landing_offsets.record(confirmed.voted_slot, landed_slot);
         }
+        self.efficiency_trend.record(confirmed.tvc_credits, self.tvc.max_credits_per_slot);
+        self.rolling.record_vote(confirmed.tvc_credits, self.tvc.max_credits_per_slot, confirmed.latency);
+        self.mark_changed();
+        
+        self.recent_confirmed_votes.push(confirmed.clone());
         
-        self.avg_latency_window.push_back(confirmed.latency);
-        self.avg_latency_window_sum.fetch_add(confirmed.latency, Ordering::Relaxed);
-        if self.avg_latency_window.len() > 20 {
-            let removed = self.avg_latency_window.pop_front().unwrap();
-            self.avg_latency_window_sum.fetch_sub(removed, Ordering::Relaxed);
+        let leader_slot = match self.leader_attribution {
+            Some(ref mut attribution) => attribution.record_vote(confirmed.voted_slot, confirmed.latency),
+            None => false,
+        };
+        if !(leader_slot && self.exclude_leader_slot_latency) {
+            self.avg_latency_window.push(confirmed.latency);
         }
         
         // track poor performance for analysis
-        if confirmed.tvc_credits < VOTE_CREDITS_MAXIMUM_PER_SLOT as u64 {
+        if confirmed.tvc_credits < self.tvc.max_credits_per_slot {
+            self.severity.record(categorize_tvc_performance(confirmed.tvc_credits, &self.tvc));
+            self.lost_credits.record(
+                confirmed.loss_cause.unwrap_or(LossCause::Unknown),
+                self.tvc.max_credits_per_slot - confirmed.tvc_credits,
+            );
             self.session_poor_votes.push_back(confirmed.clone());
-            if self.session_poor_votes.len() > 50 {
+            if self.session_poor_votes.len() > self.poor_votes_window {
                 self.session_poor_votes.pop_front();
             }
+            if let Some(ref mut attribution) = self.leader_attribution {
+                attribution.record_poor_vote(confirmed.voted_slot);
+            }
         }
         
+        let completed_epoch = self.epochs.record(&confirmed, &self.tvc);
+        
         self.last_confirmed_vote = Some(confirmed);
+        
+        let (window_status, _) = self.get_window_performance_status();
+        self.status_timeline.update(window_status);
+        
+        completed_epoch
+    }
+
+    /// start new efficiency trend and rolling window buckets and expire the
+    /// latency window as time passes, also without votes
+    pub fn roll_efficiency_trend(&mut self) {
+        let trend_rolled = self.efficiency_trend.roll();
+        let latency_expired = self.avg_latency_window.expire(Instant::now()) > 0;
+        let sent_rolled = self.votes_sent.roll();
+        if self.rolling.roll() || trend_rolled || latency_expired || sent_rolled {
+            self.mark_changed();
+        }
+
+        match self.votes_sent.update_silence() {
+            Some(SilenceChange::Started(quiet)) => {
+                self.mark_changed();
+                log::warn!(
+                    "no vote transaction from the monitored account for {}s while finalized blocks arrive, \
+                     the validator may have stopped voting (tower file, voting thread)",
+                    quiet.as_secs()
+                );
+            }
+            Some(SilenceChange::Ended) => {
+                self.mark_changed();
+                log::info!("vote transaction silence ended");
+            }
+            None => {}
+        }
+    }
+
+    /// a vote transaction of the monitored account became a pending vote
+    pub fn record_vote_sent(&mut self) {
+        self.votes_sent.record_sent();
+    }
+
+    /// efficiency over the last `window`, up to an hour, none without votes
+    /// in it. see `RollingWindows` for how missed votes and gaps count.
+    pub fn efficiency_over(&self, window: Duration) -> Option<f64> {
+        self.rolling.efficiency_over(window)
+    }
+
+    /// average latency over the last `window`, up to an hour
+    pub fn avg_latency_over(&self, window: Duration) -> Option<f64> {
+        self.rolling.avg_latency_over(window)
     }
 
-    pub async fn add_confirmed_vote_with_config(
+    /// feed a finalized block slot into the vote coverage window
+    pub fn record_finalized_slot(&mut self, slot: Slot) {
+        self.votes_sent.record_block();
+        let was_alerted = self.coverage.gap_alerted();
+        let gap = self.coverage.record_finalized_slot(slot);
+        // an active gap grows with every slot, keep it current on screen
+        if was_alerted || self.coverage.gap_alerted() {
+            self.mark_changed();
+        }
+        
+        if let Some(gap) = gap {
+            log::warn!(
+                "no votes for {} finalized slots ({}-{}), the validator may have stopped voting",
+                gap.slots, gap.first_slot, gap.last_slot
+            );
+            if let Some(ref events) = self.events {
+                events.publish(SystemEvent::VoteGap(gap));
+            }
+        }
+    }
+
+    /// feed a slot status update into the cluster tip
+    pub fn record_tower_update(&mut self, update: TowerUpdate) {
+        match self.tower_root.record(update) {
+            Some(LagAlert::Raised(lag)) => {
+                self.mark_changed();
+                log::warn!(
+                    "tower root {} is {} slots behind the newest voted slot, the root may be stalled (lockout problems?)",
+                    update.root, lag
+                );
+            }
+            Some(LagAlert::Cleared(lag)) => {
+                self.mark_changed();
+                log::info!("tower root lag recovered to {} slots", lag);
+            }
+            None => {}
+        }
+    }
+
+    pub fn record_slot_status(&mut self, slot: Slot, commitment: SlotCommitment) {
+        if commitment == SlotCommitment::Finalized {
+            self.record_leader_slots(slot);
+        }
+        match self.cluster_tip.record(slot, commitment) {
+            Some(LagAlert::Raised(lag)) => {
+                self.mark_changed();
+                log::warn!(
+                    "finalized slot {} is {} slots behind the processed tip, the grpc endpoint may be unhealthy and latencies suspect",
+                    self.cluster_tip.finalized.unwrap_or_default(), lag
+                );
+            }
+            Some(LagAlert::Cleared(lag)) => {
+                self.mark_changed();
+                log::info!("finalized lag recovered to {} slots", lag);
+            }
+            None => {}
+        }
+    }
+
+    /// judge our leader slots against the finalized slots, skipped blocks
+    /// never finalize
+    fn record_leader_slots(&mut self, finalized_slot: Slot) {
+        let Some(attribution) = self.leader_attribution.as_mut() else { return };
+        let judged = attribution.record_finalized_slot(finalized_slot);
+        if judged.is_empty() {
+            return;
+        }
+        self.mark_changed();
+
+        for leader_slot in judged.iter().filter(|leader_slot| !leader_slot.produced) {
+            log::warn!("leader slot {} was skipped, no block was finalized for it", leader_slot.slot);
+            if let Some(ref events) = self.events {
+                events.publish(SystemEvent::LeaderSlotSkipped { slot: leader_slot.slot, epoch: leader_slot.epoch });
+            }
+        }
+    }
+
+    /// feed recorded votes through the live aggregation, returning every epoch
+    /// completed along the way
+    ///
+    /// votes should be in finalized slot order, the epoch breakdown only
+    /// backfills epochs still held in memory.
+    pub fn replay<I: IntoIterator<Item = ConfirmedVote>>(&mut self, votes: I) -> Vec<EpochStats> {
+        votes.into_iter().filter_map(|vote| self.add_confirmed_vote(vote)).collect()
+    }
+
+    /// count voted slots from a pending vote that never landed
+    pub fn add_missed_vote(&mut self, missed: &MissedVote) {
+        self.missed_votes.fetch_add(missed.voted_slots.len() as u64, Ordering::Relaxed);
+        self.rolling.record_missed(missed.voted_slots.len() as u64 * self.tvc.max_credits_per_slot);
+        self.epochs.record_missed(&missed.voted_slots);
+        self.mark_changed();
+    }
+
+    /// count a vote transaction that landed but failed and keep it for display
+    pub fn add_failed_vote(&mut self, failed: FailedVote) {
+        self.failed_votes.fetch_add(1, Ordering::Relaxed);
+        self.mark_changed();
+        
+        let failed = Arc::new(failed);
+        if let Some(ref events) = self.events {
+            events.publish(SystemEvent::VoteFailed(failed.clone()));
+        }
+        
+        self.recent_failed_votes.push_back(Arc::unwrap_or_clone(failed));
+        if self.recent_failed_votes.len() > RECENT_FAILED_VOTES {
+            self.recent_failed_votes.pop_front();
+        }
+    }
+
+    /// count a vote transaction for another vote account, see `process_vote_transaction`
+    pub fn add_foreign_vote(&self) {
+        self.foreign_votes_ignored.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// count a confirmed vote, publish it and pick out a poor vote for the
+    /// performance log. nothing here waits on the disk, the caller writes the
+    /// unqueued event once the stats are unlocked
+    pub fn add_confirmed_vote_with_config(
         &mut self, 
-        confirmed: ConfirmedVote, 
+        mut confirmed: ConfirmedVote, 
         vote_account: &str,
         filter_config: &PerformanceFilterConfig,
-    ) -> Result<()> {
-        self.add_confirmed_vote(confirmed.clone());
+    ) -> ConfirmedVoteFollowUp {
+        if !confirmed.kind.is_counted() {
+            return ConfirmedVoteFollowUp { completed_epoch: self.add_confirmed_vote(confirmed), unqueued_event: None };
+        }
+        // the published vote and event carry the cause too, a replayed vote
+        // keeps the one it was recorded with
+        if confirmed.loss_cause.is_none() {
+            confirmed.loss_cause = self.loss_cause(&confirmed);
+        }
+        self.mark_voter_change(&mut confirmed);
+        let completed_epoch = self.add_confirmed_vote(confirmed.clone());
+        let mut unqueued_event = None;
+        
+        // the event takes the last copy, the signature is shared by all of them
+        let confirmed = Arc::new(confirmed);
+        if let Some(ref events) = self.events {
+            events.publish(SystemEvent::VoteConfirmed(confirmed.clone()));
+        }
         
         if filter_config.enabled {
-            let performance_level = categorize_tvc_performance(confirmed.tvc_credits);
+            let performance_level = categorize_tvc_performance(confirmed.tvc_credits, &self.tvc);
             
             if filter_config.should_save_vote(confirmed.latency, confirmed.tvc_credits, performance_level) {
                 let event = PoorPerformanceEvent {
@@ -236,14 +1392,30 @@ impl PerformanceStats {
                     vote_account: vote_account.to_string(),
                     total_tvc_credits: confirmed.tvc_credits,
                     total_voted_slots: 1,
-                    tvc_multiplier: confirmed.tvc_credits as f64 / VOTE_CREDITS_MAXIMUM_PER_SLOT as f64,
+                    tvc_multiplier: confirmed.tvc_credits as f64 / self.tvc.max_credits_per_slot as f64,
+                    leader: self.leader_attribution
+                        .as_ref()
+                        .and_then(|attribution| attribution.leader(confirmed.voted_slot))
+                        .map(|leader| leader.to_string()),
+                    loss_cause: confirmed.loss_cause,
+                    blockhash: confirmed.blockhash.clone(),
+                    parent_slot: confirmed.parent_slot,
+                    block_height: confirmed.block_height,
+                    kind: confirmed.kind,
                 };
                 
-                save_performance_event(event, filter_config).await?;
+                if let Some(ref events) = self.events {
+                    events.publish(SystemEvent::PerformanceEvent(Arc::new(event.clone())));
+                }
+                
+                match self.event_sender {
+                    Some(ref sender) => self.queue_performance_event(sender, event),
+                    None => unqueued_event = Some(event),
+                }
             }
         }
         
-        Ok(())
+        ConfirmedVoteFollowUp { completed_epoch, unqueued_event }
     }
     
     #[inline]
@@ -268,12 +1440,21 @@ impl PerformanceStats {
         let total_tx = self.total_transactions.load(Ordering::Relaxed);
         total_tx as f64 / elapsed
     }
+
+    /// votes per second when every slot is voted on
+    pub fn expected_vote_rate(&self) -> f64 {
+        expected_vote_rate(self.slot_duration)
+    }
     
+    /// average latency over the last `latency_window()`, 0 without votes in it
     #[inline]
     pub fn calculate_avg_latency(&self) -> f64 {
-        if self.avg_latency_window.is_empty() { return 0.0; }
-        let sum = self.avg_latency_window_sum.load(Ordering::Relaxed);
-        sum as f64 / self.avg_latency_window.len() as f64
+        self.avg_latency_window.mean().unwrap_or(0.0)
+    }
+
+    /// span of the rolling average latency
+    pub fn latency_window(&self) -> Duration {
+        self.avg_latency_window.span()
     }
     
     #[inline]
@@ -284,26 +1465,301 @@ impl PerformanceStats {
         (low_latency as f64 / total_tx as f64) * 100.0
     }
     
+    /// session average, without our own leader slots when they are excluded
     #[inline]
     pub fn calculate_session_avg_latency(&self) -> f64 {
+        match self.non_leader_slot_avg_latency() {
+            Some(avg) if self.exclude_leader_slot_latency => avg,
+            _ => self.overall_session_avg_latency(),
+        }
+    }
+    
+    /// session average over every vote, our own leader slots included
+    #[inline]
+    pub fn overall_session_avg_latency(&self) -> f64 {
         let total_tx = self.total_transactions.load(Ordering::Relaxed);
         if total_tx == 0 { return 0.0; }
         let latency_sum = self.total_latency_sum.load(Ordering::Relaxed);
         latency_sum as f64 / total_tx as f64
     }
     
+    /// votes for our own leader slots this session, none without the
+    /// validator identity and rpc_url
+    pub fn leader_slot_votes(&self) -> Option<LeaderSlotVotes> {
+        self.leader_attribution.as_ref()?.leader_slot_votes()
+    }
+    
+    /// produced and skipped leader slots this session, none without the
+    /// validator identity and rpc_url
+    pub fn block_production(&self) -> Option<BlockProduction> {
+        self.leader_attribution.as_ref()?.block_production()
+    }
+    
+    /// session average outside our own leader slots, none without the
+    /// validator identity and rpc_url
+    pub fn non_leader_slot_avg_latency(&self) -> Option<f64> {
+        let leader_slots = self.leader_slot_votes()?;
+        let votes = self.total_transactions.load(Ordering::Relaxed).saturating_sub(leader_slots.votes);
+        if votes == 0 { return Some(0.0); }
+        let latency_sum = self.total_latency_sum.load(Ordering::Relaxed).saturating_sub(leader_slots.latency_sum);
+        Some(latency_sum as f64 / votes as f64)
+    }
+    
+    /// efficiency over the recent votes window
+    #[inline]
+    pub fn calculate_window_efficiency(&self) -> f64 {
+        if self.recent_confirmed_votes.is_empty() { return 100.0; }
+        let window = self.recent_confirmed_votes.latest(EFFICIENCY_WINDOW);
+        let votes = window.len() as u64;
+        let earned: u64 = window.map(|v| v.tvc_credits).sum();
+        let possible = votes * self.tvc.max_credits_per_slot;
+        (earned as f64 / possible as f64) * 100.0
+    }
+    
+    /// every vote below max credits this session by severity
+    pub fn severity_histogram(&self) -> SeverityHistogram {
+        self.severity
+    }
+
+    pub fn latency_distribution(&self) -> LatencyDistribution {
+        self.latency_distribution
+    }
+
+    /// every vote this session by earned credits
+    pub fn tvc_histogram(&self) -> TvcHistogram {
+        self.tvc_histogram
+    }
+
+    pub fn lost_credits(&self) -> LostCredits {
+        self.lost_credits
+    }
+
+    /// heuristic cause of a vote below max credits, none for optimal votes.
+    /// leader adjacency needs a loaded schedule and the validator identity
+    pub fn loss_cause(&self, vote: &ConfirmedVote) -> Option<LossCause> {
+        if vote.tvc_credits >= self.tvc.max_credits_per_slot {
+            return None;
+        }
+        let distance = self.loss_causes.leader_adjacency_slots;
+        let near_own_leader_slot = distance > 0
+            && self.leader_attribution
+                .as_ref()
+                .is_some_and(|attribution| attribution.near_own_slot(vote.voted_slot, distance));
+        Some(classify_loss(vote, near_own_leader_slot, &self.loss_causes))
+    }
+
+    /// summary over the whole retained recent votes window
+    pub fn recent_window_summary(&self) -> RecentWindowSummary {
+        let votes = self.recent_confirmed_votes.len() as u64;
+        if votes == 0 {
+            return RecentWindowSummary::default();
+        }
+        
+        let max_credits = self.tvc.max_credits_per_slot;
+        let latency_sum: u64 = self.recent_confirmed_votes.iter().map(|v| v.latency).sum();
+        let tvc_lost: u64 = self.recent_confirmed_votes.iter().map(|v| max_credits.saturating_sub(v.tvc_credits)).sum();
+        let optimal = self.recent_confirmed_votes.iter().filter(|v| v.tvc_credits >= max_credits).count() as u64;
+        let delays: Vec<u64> = self.recent_confirmed_votes.iter().filter_map(|v| v.finalization_delay_ms).collect();
+        
+        RecentWindowSummary {
+            votes,
+            avg_latency: latency_sum as f64 / votes as f64,
+            tvc_lost,
+            optimal_pct: optimal as f64 / votes as f64 * 100.0,
+            avg_finalization_delay_ms: (!delays.is_empty())
+                .then(|| delays.iter().sum::<u64>() as f64 / delays.len() as f64),
+        }
+    }
+    
+    /// latency percentile (0-100) over the recent votes, none before the first
+    /// vote. estimated latencies only with `percentiles_include_estimates`
+    pub fn recent_latency_percentile(&self, percentile: f64) -> Option<u64> {
+        let mut latencies: Vec<u64> = self.recent_confirmed_votes
+            .iter()
+            .filter(|v| self.percentiles_include_estimates || !v.kind.is_estimated())
+            .map(|v| v.latency)
+            .collect();
+        latencies.sort_unstable();
+        sorted_percentile(&latencies, percentile)
+    }
+    
+    /// average wall-clock confirmation time over the recent votes that have one
+    pub fn recent_confirmation_ms_avg(&self) -> Option<f64> {
+        let (sum, count) = self.recent_confirmed_votes
+            .iter()
+            .filter_map(|v| v.confirmation_ms)
+            .fold((0u64, 0u64), |(sum, count), ms| (sum + ms, count + 1));
+        (count > 0).then(|| sum as f64 / count as f64)
+    }
+    
+    /// wall-clock confirmation time percentile (0-100) over the recent votes that have one
+    pub fn recent_confirmation_ms_percentile(&self, percentile: f64) -> Option<u64> {
+        let mut confirmation_ms: Vec<u64> = self.recent_confirmed_votes.iter().filter_map(|v| v.confirmation_ms).collect();
+        confirmation_ms.sort_unstable();
+        sorted_percentile(&confirmation_ms, percentile)
+    }
+    
     #[inline]
     pub fn get_performance_status(&self) -> (&'static str, Color) {
-        let efficiency = self.calculate_efficiency();
-        if efficiency >= 95.0 {
-            ("optimal", Color::Green)
-        } else if efficiency >= 85.0 {
-            ("good", Color::Yellow)
-        } else {
-            ("poor", Color::Red)
+        performance_status_for(self.calculate_efficiency(), &self.thresholds)
+    }
+    
+    /// status of the recent votes window, drives the status timeline
+    #[inline]
+    pub fn get_window_performance_status(&self) -> (&'static str, Color) {
+        performance_status_for(self.calculate_window_efficiency(), &self.thresholds)
+    }
+    
+    /// approximate memory held by the retained vote windows
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage::new();
+        
+        usage.add(
+            "recent votes window",
+            self.recent_confirmed_votes.len(),
+            self.recent_confirmed_votes.allocated_bytes()
+                + self.recent_confirmed_votes.len() * signature_bytes(),
+        );
+        usage.add(
+            "session poor votes",
+            self.session_poor_votes.len(),
+            self.session_poor_votes.capacity() * std::mem::size_of::<ConfirmedVote>()
+                + self.session_poor_votes.len() * signature_bytes(),
+        );
+        usage.add(
+            "failed votes",
+            self.recent_failed_votes.len(),
+            self.recent_failed_votes.capacity() * std::mem::size_of::<FailedVote>()
+                + self.recent_failed_votes.iter().map(|failed| failed.signature.len() + failed.error.len()).sum::<usize>(),
+        );
+        usage.add(
+            "latency window",
+            self.avg_latency_window.len(),
+            self.avg_latency_window.allocated_bytes(),
+        );
+        usage.add(
+            "efficiency trend",
+            self.efficiency_trend.buckets().len(),
+            self.efficiency_trend.allocated_bytes(),
+        );
+        usage.add("rolling windows", self.rolling.bucket_count(), self.rolling.allocated_bytes());
+        if self.commitment.observes_confirmed() {
+            usage.add("confirmed sightings", self.confirmed_observations.len(), self.confirmed_observations.allocated_bytes());
+        }
+        if let Some(ref attribution) = self.leader_attribution {
+            usage.add("leader schedule", attribution.schedule_count(), attribution.allocated_bytes());
+        }
+        if let Some(ref cluster) = self.cluster {
+            usage.add("cluster snapshot", cluster.validators, cluster.allocated_bytes());
+        }
+        
+        usage
+    }
+    
+    /// hand an event to the writer task, never waits on a full channel
+    fn queue_performance_event(&self, sender: &mpsc::Sender<PoorPerformanceEvent>, event: PoorPerformanceEvent) {
+        let reason = match sender.try_send(event) {
+            Ok(()) => return,
+            Err(TrySendError::Full(_)) => "channel full",
+            Err(TrySendError::Closed(_)) => "writer stopped",
+        };
+        let dropped = self.dropped_performance_events.fetch_add(1, Ordering::Relaxed) + 1;
+        if dropped == 1 || dropped % 100 == 0 {
+            log::warn!("performance event writer {}, {} events dropped", reason, dropped);
         }
     }
     
+    /// note a change the dashboard should draw on its next tick
+    #[inline]
+    pub fn mark_changed(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// changes so far, equal values mean nothing new to draw
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// a detached copy for a paused dashboard to keep drawing
+    ///
+    /// the copy publishes nothing: it has no event bus and no event writer,
+    /// and updates to this instance no longer reach it.
+    pub fn frozen_copy(&self) -> Self {
+        let copy = |counter: &AtomicU64| AtomicU64::new(counter.load(Ordering::Relaxed));
+        Self {
+            session_start: self.session_start,
+            session_started_at: self.session_started_at,
+            process_start: self.process_start,
+            total_transactions: copy(&self.total_transactions),
+            total_tvc_earned: copy(&self.total_tvc_earned),
+            total_tvc_possible: copy(&self.total_tvc_possible),
+            optimal_votes: copy(&self.optimal_votes),
+            good_votes: copy(&self.good_votes),
+            poor_votes: copy(&self.poor_votes),
+            low_latency_votes: copy(&self.low_latency_votes),
+            missed_votes: copy(&self.missed_votes),
+            failed_votes: copy(&self.failed_votes),
+            foreign_votes_ignored: copy(&self.foreign_votes_ignored),
+            duplicate_confirmations: copy(&self.duplicate_confirmations),
+            recent_confirmed_votes: self.recent_confirmed_votes.clone(),
+            session_poor_votes: self.session_poor_votes.clone(),
+            poor_votes_window: self.poor_votes_window,
+            severity: self.severity,
+            latency_distribution: self.latency_distribution,
+            tvc_histogram: self.tvc_histogram,
+            lost_credits: self.lost_credits,
+            recent_failed_votes: self.recent_failed_votes.clone(),
+            avg_latency_window: self.avg_latency_window.clone(),
+            current_finalized_slot: copy(&self.current_finalized_slot),
+            last_confirmed_vote: self.last_confirmed_vote.clone(),
+            total_latency_sum: copy(&self.total_latency_sum),
+            status_timeline: self.status_timeline.clone(),
+            efficiency_trend: self.efficiency_trend.clone(),
+            rolling: self.rolling.clone(),
+            votes_sent: self.votes_sent.clone(),
+            streaks: self.streaks.clone(),
+            streak_alert_votes: self.streak_alert_votes,
+            landing_offsets: self.landing_offsets.clone(),
+            epochs: self.epochs.clone(),
+            authorized_voter: self.authorized_voter.clone(),
+            pre_voter_change_votes: copy(&self.pre_voter_change_votes),
+            confirmation_source: self.confirmation_source,
+            commitment: self.commitment,
+            confirmed_observations: self.confirmed_observations.clone(),
+            network: self.network,
+            slot_duration: self.slot_duration,
+            tvc: self.tvc,
+            thresholds: self.thresholds,
+            loss_causes: self.loss_causes,
+            coverage: self.coverage.clone(),
+            cluster_tip: self.cluster_tip,
+            tower_root: self.tower_root.clone(),
+            vote_account_info: self.vote_account_info.clone(),
+            account_info: self.account_info.clone(),
+            pipeline: self.pipeline.clone(),
+            event_writer: self.event_writer.clone(),
+            stream_health: self.stream_health.clone(),
+            endpoints: self.endpoints.clone(),
+            process: self.process.clone(),
+            tracker: self.tracker.clone(),
+            filter_drift: self.filter_drift.clone(),
+            credits_drift: self.credits_drift.clone(),
+            cluster: self.cluster.clone(),
+            leader_attribution: self.leader_attribution.clone(),
+            exclude_leader_slot_latency: self.exclude_leader_slot_latency,
+            percentiles_include_estimates: self.percentiles_include_estimates,
+            events: None,
+            event_sender: None,
+            dropped_performance_events: copy(&self.dropped_performance_events),
+            generation: copy(&self.generation),
+        }
+    }
+
+    /// votes confirmed this session, estimated ones included
+    pub fn confirmed_votes(&self) -> u64 {
+        self.optimal_votes() + self.good_votes() + self.poor_votes()
+    }
+
     // getters for atomic fields
     pub fn total_transactions(&self) -> u64 {
         self.total_transactions.load(Ordering::Relaxed)
@@ -325,6 +1781,10 @@ impl PerformanceStats {
         self.good_votes.load(Ordering::Relaxed)
     }
     
+    pub fn dropped_performance_events(&self) -> u64 {
+        self.dropped_performance_events.load(Ordering::Relaxed)
+    }
+    
     pub fn poor_votes(&self) -> u64 {
         self.poor_votes.load(Ordering::Relaxed)
     }
@@ -333,18 +1793,53 @@ impl PerformanceStats {
         self.low_latency_votes.load(Ordering::Relaxed)
     }
     
+    pub fn missed_votes(&self) -> u64 {
+        self.missed_votes.load(Ordering::Relaxed)
+    }
+    
+    pub fn failed_votes(&self) -> u64 {
+        self.failed_votes.load(Ordering::Relaxed)
+    }
+    
+    pub fn foreign_votes_ignored(&self) -> u64 {
+        self.foreign_votes_ignored.load(Ordering::Relaxed)
+    }
+    
+    pub fn duplicate_confirmations(&self) -> u64 {
+        self.duplicate_confirmations.load(Ordering::Relaxed)
+    }
+    
     pub fn current_finalized_slot(&self) -> u64 {
         self.current_finalized_slot.load(Ordering::Relaxed)
     }
 }
 
+/// nearest-rank percentile (0-100) of an ascending slice
+pub fn sorted_percentile(sorted: &[u64], percentile: f64) -> Option<u64> {
+    if sorted.is_empty() { return None; }
+    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+    Some(sorted[rank])
+}
+
+/// status of `efficiency` against the configured boundaries
+#[inline]
+pub fn performance_status_for(efficiency: f64, thresholds: &ThresholdsConfig) -> (&'static str, Color) {
+    if efficiency >= thresholds.optimal_efficiency_pct {
+        ("optimal", Color::Green)
+    } else if efficiency >= thresholds.good_efficiency_pct {
+        ("good", Color::Yellow)
+    } else {
+        ("poor", Color::Red)
+    }
+}
+
 #[inline]
-pub fn calculate_tvc_credits_from_latency(latency: u64) -> u64 {
-    if latency <= VOTE_CREDITS_GRACE_SLOTS as u64 {
-        VOTE_CREDITS_MAXIMUM_PER_SLOT as u64
+pub fn calculate_tvc_credits_from_latency(latency: u64, tvc: &TvcConfig) -> u64 {
+    if latency <= tvc.grace_slots {
+        tvc.max_credits_per_slot
     } else {
-        let penalty = latency - (VOTE_CREDITS_GRACE_SLOTS as u64);
-        match (VOTE_CREDITS_MAXIMUM_PER_SLOT as u64).checked_sub(penalty) {
+        let penalty = latency - tvc.grace_slots;
+        match tvc.max_credits_per_slot.checked_sub(penalty) {
             Some(credits) if credits > 0 => credits,
             _ => 1, // minimum 1 credit
         }
@@ -352,29 +1847,51 @@ pub fn calculate_tvc_credits_from_latency(latency: u64) -> u64 {
 }
 
 #[inline]
-pub fn calculate_tvc_credits(voted_slot: Slot, finalized_slot: Slot) -> (u64, u64) {
+pub fn calculate_tvc_credits(voted_slot: Slot, finalized_slot: Slot, tvc: &TvcConfig) -> (u64, u64) {
     let latency = finalized_slot.saturating_sub(voted_slot);
-    let credits = calculate_tvc_credits_from_latency(latency);
+    let credits = calculate_tvc_credits_from_latency(latency, tvc);
     (latency, credits)
 }
 
 #[inline]
-pub fn categorize_tvc_performance(tvc_credits: u64) -> TvcPerformanceLevel {
-    match tvc_credits {
-        16 => TvcPerformanceLevel::Optimal,
-        12..=15 => TvcPerformanceLevel::Good,
-        8..=11 => TvcPerformanceLevel::Fair,
-        4..=7 => TvcPerformanceLevel::Poor,
-        _ => TvcPerformanceLevel::Critical,
+pub fn categorize_tvc_performance(tvc_credits: u64, tvc: &TvcConfig) -> TvcPerformanceLevel {
+    let max = tvc.max_credits_per_slot;
+    if tvc_credits >= max {
+        TvcPerformanceLevel::Optimal
+    } else if tvc_credits >= tvc.good_threshold() {
+        TvcPerformanceLevel::Good
+    } else if tvc_credits >= max / 2 {
+        TvcPerformanceLevel::Fair
+    } else if tvc_credits >= max / 4 {
+        TvcPerformanceLevel::Poor
+    } else {
+        TvcPerformanceLevel::Critical
     }
 }
 
+/// first retry after a failed event write, doubled up to `MAX_EVENT_RETRY_BACKOFF`
+const EVENT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_EVENT_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
 /// batched event writer
+///
+/// events of a write that failed with a transient error, such as a full
+/// disk or a read-only remount, stay queued and are retried with backoff.
+/// the queue keeps at most `max_retry_events` of them for `max_retry_age`,
+/// the oldest are dropped beyond that. a permanent error drops the batch.
 pub struct BatchedEventWriter {
     buffer: Vec<PoorPerformanceEvent>,
     buffer_capacity: usize,
     flush_interval: std::time::Duration,
     last_flush: Instant,
+    log_dir: PathBuf,
+    max_retry_events: usize,
+    max_retry_age: Option<Duration>,
+    // set while the last write failed
+    retry_at: Option<Instant>,
+    backoff: Duration,
+    failed_writes: u64,
+    metrics: Arc<EventWriterMetrics>,
 }
 
 impl BatchedEventWriter {
@@ -384,92 +1901,223 @@ impl BatchedEventWriter {
             buffer_capacity,
             flush_interval: std::time::Duration::from_secs(flush_interval_secs),
             last_flush: Instant::now(),
+            log_dir: PerformanceFilterConfig::default().performance_log_dir,
+            max_retry_events: PerformanceFilterConfig::default().retry_queue_events,
+            max_retry_age: None,
+            retry_at: None,
+            backoff: EVENT_RETRY_BACKOFF,
+            failed_writes: 0,
+            metrics: Arc::default(),
         }
     }
     
+    /// directory for the daily files (performance_log_dir)
+    pub fn with_log_dir(mut self, log_dir: impl Into<PathBuf>) -> Self {
+        self.log_dir = log_dir.into();
+        self
+    }
+    
+    /// bounds of the retry queue, a zero `max_age` keeps events until the
+    /// queue overflows
+    pub fn with_retry_queue(mut self, max_events: usize, max_age: Duration) -> Self {
+        self.max_retry_events = max_events;
+        self.max_retry_age = (!max_age.is_zero()).then_some(max_age);
+        self
+    }
+    
+    /// queued and dropped events, for the dashboard and status api
+    pub fn with_metrics(mut self, metrics: Arc<EventWriterMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+    
     pub async fn add_event(&mut self, event: PoorPerformanceEvent) -> Result<()> {
         self.buffer.push(event);
+        self.trim_retry_queue();
         
         // flush if buffer is full or interval elapsed
         if self.buffer.len() >= self.buffer_capacity || 
            self.last_flush.elapsed() >= self.flush_interval {
-            self.flush().await?;
+            self.flush_if_due().await?;
         }
         
         Ok(())
     }
     
+    /// flush unless a failed write is still backing off
+    pub async fn flush_if_due(&mut self) -> Result<()> {
+        if self.retry_at.is_some_and(|retry_at| Instant::now() < retry_at) {
+            return Ok(());
+        }
+        self.flush().await
+    }
+    
+    /// write the buffered events now, also while backing off
     pub async fn flush(&mut self) -> Result<()> {
         if self.buffer.is_empty() {
             return Ok(());
         }
         
+        match self.write_buffer().await {
+            Ok(()) => {
+                if self.failed_writes > 0 {
+                    log::info!("performance events written again after {} failed attempts", self.failed_writes);
+                }
+                self.retry_at = None;
+                self.backoff = EVENT_RETRY_BACKOFF;
+                self.failed_writes = 0;
+                self.last_flush = Instant::now();
+                self.metrics.set_queued(0, false);
+                Ok(())
+            }
+            Err(e) if e.is_transient_io() => {
+                self.failed_writes += 1;
+                self.retry_at = Some(Instant::now() + self.backoff);
+                self.backoff = (self.backoff * 2).min(MAX_EVENT_RETRY_BACKOFF);
+                self.trim_retry_queue();
+                Err(e)
+            }
+            Err(e) => {
+                // retrying won't help, keep the writer going for the next events
+                self.drop_events(self.buffer.len(), "the write failed permanently");
+                self.metrics.set_queued(0, false);
+                Err(e)
+            }
+        }
+    }
+    
+    async fn write_buffer(&mut self) -> Result<()> {
         // create directory if needed
-        tokio::fs::create_dir_all("./performance_issues").await?;
-        
-        let today = Utc::now().format("%Y-%m-%d").to_string();
-        let filename = format!("./performance_issues/performance_issues_{}.json", today);
+        tokio::fs::create_dir_all(&self.log_dir).await?;
         
-        // batch serialize all events
-        let mut batch_json = String::with_capacity(self.buffer.len() * 256);
-        for event in &self.buffer {
-            batch_json.push_str(&serde_json::to_string(event)?);
-            batch_json.push('\n');
+        // events are in arrival order, a batch spanning midnight goes to two files.
+        // days already written are dropped on error so a retry can't duplicate them
+        let mut written = 0;
+        while written < self.buffer.len() {
+            let date = self.buffer[written].timestamp.date_naive();
+            let day_len = self.buffer[written..]
+                .iter()
+                .take_while(|event| event.timestamp.date_naive() == date)
+                .count();
+            let filename = performance_log_file(&self.log_dir, date);
+            
+            if let Err(e) = append_events(&filename, &self.buffer[written..written + day_len]).await {
+                self.buffer.drain(..written);
+                return Err(e);
+            }
+            written += day_len;
         }
         
-        // single atomic write
-        use tokio::fs::OpenOptions;
-        use tokio::io::AsyncWriteExt;
-        
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&filename)
-            .await?;
-        
-        file.write_all(batch_json.as_bytes()).await?;
-        file.flush().await?;
-        
         self.buffer.clear();
-        self.last_flush = Instant::now();
-        
         Ok(())
     }
-}
-
-async fn save_performance_event(
-    event: PoorPerformanceEvent,
-    filter_config: &PerformanceFilterConfig,
-) -> Result<()> {
-    let performance_level = categorize_tvc_performance(event.tvc_credits);
     
-    if !filter_config.should_save_vote(event.latency, event.tvc_credits, performance_level) {
-        return Ok(());
+    /// drop the oldest events beyond the retry queue bounds while writes fail
+    fn trim_retry_queue(&mut self) {
+        if self.retry_at.is_none() {
+            return;
+        }
+        let mut expired = self.buffer.len().saturating_sub(self.max_retry_events);
+        let cutoff = self.max_retry_age
+            .and_then(|max_age| chrono::Duration::from_std(max_age).ok())
+            .and_then(|max_age| Utc::now().checked_sub_signed(max_age));
+        if let Some(cutoff) = cutoff {
+            // events are in arrival order
+            expired += self.buffer[expired..].iter().take_while(|event| event.timestamp < cutoff).count();
+        }
+        if expired > 0 {
+            self.drop_events(expired, "the retry queue is full or they waited too long");
+        }
+        self.metrics.set_queued(self.buffer.len() as u64, true);
     }
     
-    // for now, still do immediate write
-    tokio::fs::create_dir_all("./performance_issues").await?;
-    
-    let today = Utc::now().format("%Y-%m-%d").to_string();
-    let filename = format!("./performance_issues/performance_issues_{}.json", today);
+    fn drop_events(&mut self, count: usize, reason: &str) {
+        self.buffer.drain(..count);
+        let previous = self.metrics.dropped.fetch_add(count as u64, Ordering::Relaxed);
+        let dropped = previous + count as u64;
+        // a full queue drops one event per new one, log every hundredth
+        if previous == 0 || dropped / 100 > previous / 100 || count > 1 {
+            log::warn!("dropped {} performance events, {} ({} so far)", count, reason, dropped);
+        }
+    }
+}
+
+/// retry queue of the `BatchedEventWriter`, shared with the dashboard and status api
+#[derive(Debug, Default)]
+pub struct EventWriterMetrics {
+    queued: AtomicU64,
+    dropped: AtomicU64,
+    retrying: AtomicBool,
+}
+
+impl EventWriterMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
     
-    let json_line = format!("{}\n", serde_json::to_string(&event)?);
+    fn set_queued(&self, queued: u64, retrying: bool) {
+        self.queued.store(queued, Ordering::Relaxed);
+        self.retrying.store(retrying, Ordering::Relaxed);
+    }
     
+    pub fn snapshot(&self) -> EventWriterSnapshot {
+        EventWriterSnapshot {
+            retrying: self.retrying.load(Ordering::Relaxed),
+            queued_events: self.queued.load(Ordering::Relaxed),
+            dropped_events: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// see `EventWriterMetrics`
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EventWriterSnapshot {
+    /// the last write failed, `queued_events` wait for a retry
+    pub retrying: bool,
+    pub queued_events: u64,
+    /// events dropped by the retry queue or a permanent write error
+    pub dropped_events: u64,
+}
+
+impl EventWriterSnapshot {
+    pub fn is_degraded(&self) -> bool {
+        self.retrying || self.dropped_events > 0
+    }
+}
+
+/// append events as json lines in a single write
+async fn append_events(filename: &Path, events: &[PoorPerformanceEvent]) -> Result<()> {
     use tokio::fs::OpenOptions;
     use tokio::io::AsyncWriteExt;
     
+    // batch serialize all events
+    let mut batch_json = String::with_capacity(events.len() * 256);
+    for event in events {
+        batch_json.push_str(&serde_json::to_string(event)?);
+        batch_json.push('\n');
+    }
+    
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&filename)
+        .open(filename)
         .await?;
     
-    file.write_all(json_line.as_bytes()).await?;
+    file.write_all(batch_json.as_bytes()).await?;
     file.flush().await?;
     
     Ok(())
 }
 
+/// append events straight to today's file, for stats without a writer task
+pub async fn save_performance_events(events: &[PoorPerformanceEvent], log_dir: &Path) -> Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    tokio::fs::create_dir_all(log_dir).await?;
+    append_events(&current_performance_log_file(log_dir), events).await
+}
+
 pub fn format_number(n: u64) -> String {
     if n >= 1_000_000 {
         format!("{:.1}M", n as f64 / 1_000_000.0)
@@ -493,4 +2141,315 @@ pub fn format_duration(duration: std::time::Duration) -> String {
     } else {
         format!("{}s", seconds)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tvc_credits_by_latency() {
+        let default = TvcConfig::default();
+        let strict = TvcConfig { grace_slots: 0, max_credits_per_slot: 8 };
+        // (config, latency, credits)
+        let cases = [
+            (&default, 0, 16),
+            (&default, 1, 16),
+            // the last slot of the grace period still earns the maximum
+            (&default, 2, 16),
+            (&default, 3, 15),
+            (&default, 4, 14),
+            (&default, 10, 8),
+            (&default, 16, 2),
+            (&default, 17, 1),
+            // never below one credit, however late
+            (&default, 18, 1),
+            (&default, 150, 1),
+            (&default, u64::MAX, 1),
+            (&strict, 0, 8),
+            (&strict, 1, 7),
+            (&strict, 7, 1),
+            (&strict, 8, 1),
+        ];
+        for (tvc, latency, credits) in cases {
+            assert_eq!(calculate_tvc_credits_from_latency(latency, tvc), credits, "latency {} with {:?}", latency, tvc);
+        }
+        assert_eq!(default.saturation_latency(), 18);
+        assert_eq!(calculate_tvc_credits_from_latency(default.saturation_latency(), &default), 1);
+        assert!(calculate_tvc_credits_from_latency(default.saturation_latency() - 2, &default) > 1);
+    }
+
+    #[test]
+    fn tvc_credits_from_slots() {
+        let tvc = TvcConfig::default();
+        assert_eq!(calculate_tvc_credits(100, 101, &tvc), (1, 16));
+        assert_eq!(calculate_tvc_credits(100, 105, &tvc), (5, 13));
+        // a finalized slot behind the voted slot is treated as no latency
+        assert_eq!(calculate_tvc_credits(100, 99, &tvc), (0, 16));
+    }
+
+    #[test]
+    fn tvc_performance_levels_at_their_boundaries() {
+        let tvc = TvcConfig::default();
+        let cases = [
+            (16, TvcPerformanceLevel::Optimal),
+            (15, TvcPerformanceLevel::Good),
+            (12, TvcPerformanceLevel::Good),
+            (11, TvcPerformanceLevel::Fair),
+            (8, TvcPerformanceLevel::Fair),
+            (7, TvcPerformanceLevel::Poor),
+            (4, TvcPerformanceLevel::Poor),
+            (3, TvcPerformanceLevel::Critical),
+            (1, TvcPerformanceLevel::Critical),
+        ];
+        for (credits, level) in cases {
+            assert_eq!(categorize_tvc_performance(credits, &tvc), level, "{} credits", credits);
+        }
+    }
+
+    #[test]
+    fn performance_status_at_the_threshold_boundaries() {
+        let default = ThresholdsConfig::default();
+        let strict = ThresholdsConfig { optimal_efficiency_pct: 98.0, good_efficiency_pct: 92.0, ..Default::default() };
+        let cases = [
+            (&default, 100.0, "optimal"),
+            (&default, 95.0, "optimal"),
+            (&default, 94.99, "good"),
+            (&default, 85.0, "good"),
+            (&default, 84.99, "poor"),
+            (&default, 0.0, "poor"),
+            (&strict, 98.0, "optimal"),
+            (&strict, 97.99, "good"),
+            (&strict, 95.0, "good"),
+            (&strict, 92.0, "good"),
+            (&strict, 91.99, "poor"),
+        ];
+        for (thresholds, efficiency, status) in cases {
+            assert_eq!(performance_status_for(efficiency, thresholds).0, status, "{}% with {:?}", efficiency, thresholds);
+        }
+    }
+
+    #[test]
+    fn low_latency_votes_follow_the_configured_cutoff() {
+        for (low_latency_slots, expected) in [(1, 1), (2, 2), (3, 3), (10, 4)] {
+            let thresholds = ThresholdsConfig { low_latency_slots, ..Default::default() };
+            let mut stats = PerformanceStats::new().with_thresholds(thresholds);
+            for (seed, latency) in [(1, 1), (2, 2), (3, 3), (4, 4)] {
+                stats.add_confirmed_vote(crate::test_support::make_confirmed_vote(seed, 100 + seed, latency));
+            }
+            assert_eq!(stats.low_latency_votes(), expected, "low_latency_slots = {}", low_latency_slots);
+        }
+    }
+
+    #[test]
+    fn vote_burst_shares_the_signatures() {
+        use crate::test_support::{allocations, make_confirmed_vote};
+
+        const BURST_VOTES: u64 = 10_000;
+        let votes: Vec<_> = (0..BURST_VOTES)
+            .map(|n| make_confirmed_vote(n, 300_000_000 + n, [1, 2, 1, 3, 6][n as usize % 5]))
+            .collect();
+        // a copy of every signature alone would be one allocation per vote
+        let (copies, copied) = allocations(|| votes.iter().map(|vote| (*vote.signature).clone()).collect::<Vec<_>>());
+        assert!(copied >= BURST_VOTES);
+        drop(copies);
+
+        let mut stats = PerformanceStats::new();
+        let ((), allocated) = allocations(|| {
+            for vote in votes.iter().cloned() {
+                stats.add_confirmed_vote(vote);
+            }
+        });
+        assert!(allocated < BURST_VOTES / 2, "{} allocations for {} votes", allocated, BURST_VOTES);
+
+        // the windows hold the strings the votes came with
+        let last = votes.last().unwrap();
+        assert!(Arc::ptr_eq(&stats.last_confirmed_vote.as_ref().unwrap().signature, &last.signature));
+        assert!(stats.recent_confirmed_votes.iter().all(|vote| Arc::strong_count(&vote.signature) > 1));
+    }
+
+    #[test]
+    fn estimated_kinds_stay_out_of_the_percentiles() {
+        use crate::test_support::make_confirmed_vote;
+
+        let vote = |seed: u64, latency: u64, kind: ConfirmationKind| {
+            ConfirmedVote { kind, ..make_confirmed_vote(seed, 100 + seed, latency) }
+        };
+        let votes = [
+            vote(1, 1, ConfirmationKind::Matched),
+            vote(2, 2, ConfirmationKind::Matched),
+            vote(3, 30, ConfirmationKind::DirectBlockOnly),
+            vote(4, 40, ConfirmationKind::EstimatedTxSlot),
+            vote(5, 50, ConfirmationKind::TowerLatency),
+            vote(6, 60, ConfirmationKind::DuplicateSuppressed),
+        ];
+
+        let mut measured = PerformanceStats::new();
+        let mut everything = PerformanceStats::new().with_estimates_in_percentiles(true);
+        for vote in &votes {
+            measured.add_confirmed_vote(vote.clone());
+            everything.add_confirmed_vote(vote.clone());
+        }
+        // the duplicate is never counted, the estimates are by default
+        assert_eq!(measured.total_transactions(), 5);
+        assert_eq!(measured.recent_latency_percentile(100.0), Some(2));
+        assert_eq!(everything.recent_latency_percentile(100.0), Some(50));
+    }
+
+    #[test]
+    fn kind_names_are_stable() {
+        let kinds = [
+            (ConfirmationKind::Matched, "matched"),
+            (ConfirmationKind::DirectBlockOnly, "direct_block_only"),
+            (ConfirmationKind::EstimatedTxSlot, "estimated_tx_slot"),
+            (ConfirmationKind::TowerLatency, "tower_latency"),
+            (ConfirmationKind::DuplicateSuppressed, "duplicate_suppressed"),
+        ];
+        for (kind, name) in kinds {
+            assert_eq!(kind.name(), name);
+            assert_eq!(ConfirmationKind::parse(name), Some(kind));
+            assert_eq!(serde_json::to_string(&kind).unwrap(), format!("\"{}\"", name));
+        }
+        assert_eq!(ConfirmationKind::parse("estimated"), None);
+    }
+
+    #[test]
+    fn loss_causes_attributed_once() {
+        use crate::loss_cause::CauseTally;
+        use crate::test_support::make_confirmed_vote;
+
+        let (sender, mut events) = mpsc::channel(8);
+        let mut stats = PerformanceStats::new().with_event_sender(sender);
+        let every_vote = PerformanceFilterConfig {
+            min_latency_threshold: None,
+            max_tvc_threshold: None,
+            performance_levels: Vec::new(),
+            ..Default::default()
+        };
+        // landed one slot after the vote, confirmed four slots later
+        let stalled = make_confirmed_vote(1, 100, 5);
+        // landed ten slots after the vote
+        let delayed = ConfirmedVote { landed_slot: Some(210), ..make_confirmed_vote(2, 200, 11) };
+        // a stall by its slots, recorded as leader adjacency
+        let replayed = ConfirmedVote { loss_cause: Some(LossCause::LeaderAdjacency), ..make_confirmed_vote(3, 300, 5) };
+        let optimal = make_confirmed_vote(4, 400, 1);
+
+        let cases = [
+            (stalled, Some(LossCause::FinalizationStall)),
+            (delayed, Some(LossCause::LandingDelay)),
+            (replayed, Some(LossCause::LeaderAdjacency)),
+            (optimal, None),
+        ];
+        for (vote, cause) in cases {
+            let voted_slot = vote.voted_slot;
+            let follow_up = stats.add_confirmed_vote_with_config(vote, "vote-account", &every_vote);
+            assert!(follow_up.unqueued_event.is_none());
+            let event = events.try_recv().unwrap();
+            assert_eq!((event.voted_slot, event.loss_cause), (voted_slot, cause));
+        }
+
+        let lost = stats.lost_credits();
+        let max = stats.tvc.max_credits_per_slot;
+        let credits = |latency| max - calculate_tvc_credits_from_latency(latency, &stats.tvc);
+        assert_eq!(lost.get(LossCause::FinalizationStall), CauseTally { votes: 1, credits: credits(5) });
+        assert_eq!(lost.get(LossCause::LandingDelay), CauseTally { votes: 1, credits: credits(11) });
+        assert_eq!(lost.get(LossCause::LeaderAdjacency), CauseTally { votes: 1, credits: credits(5) });
+        assert_eq!(lost.get(LossCause::Unknown), CauseTally::default());
+    }
+
+    #[test]
+    fn votes_before_a_voter_change_are_flagged() {
+        use crate::test_support::make_confirmed_vote;
+
+        let events = EventBus::new(16);
+        let mut published = events.subscribe();
+        let mut stats = PerformanceStats::new().with_event_bus(events);
+        stats.authorized_voter.changed_at_slot = Some(500);
+        let unlogged = PerformanceFilterConfig { enabled: false, ..Default::default() };
+
+        stats.add_confirmed_vote_with_config(make_confirmed_vote(1, 499, 1), "vote-account", &unlogged);
+        stats.add_confirmed_vote_with_config(make_confirmed_vote(2, 500, 1), "vote-account", &unlogged);
+        // a replayed vote keeps its flag
+        let replayed = ConfirmedVote { before_voter_change: true, ..make_confirmed_vote(3, 400, 1) };
+        stats.authorized_voter.changed_at_slot = None;
+        stats.add_confirmed_vote(replayed);
+
+        let flags: Vec<_> = stats.recent_confirmed_votes.iter().map(|v| (v.voted_slot, v.before_voter_change)).collect();
+        assert_eq!(flags, [(499, true), (500, false), (400, true)]);
+        assert_eq!(stats.pre_voter_change_votes.load(Ordering::Relaxed), 2);
+        let published: Vec<_> = std::iter::from_fn(|| published.try_recv().ok())
+            .filter_map(|event| match event {
+                SystemEvent::VoteConfirmed(vote) => Some(vote.before_voter_change),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(published, [true, false]);
+    }
+
+    #[test]
+    fn reset_splits_the_votes_at_the_reset() {
+        use crate::test_support::make_confirmed_vote;
+
+        let events = EventBus::new(16);
+        let mut published = events.subscribe();
+        let tvc = TvcConfig { grace_slots: 1, max_credits_per_slot: 8 };
+        let mut stats = PerformanceStats::new()
+            .with_slots_per_epoch(1_000)
+            .with_recent_votes_window(7)
+            .with_latency_window(Duration::from_secs(90))
+            .with_tvc_config(tvc)
+            .with_streak_alert(3)
+            .with_estimates_in_percentiles(true)
+            .with_event_bus(events);
+        let vote = |seed, slot, latency| ConfirmedVote {
+            tvc_credits: calculate_tvc_credits_from_latency(latency, &tvc),
+            ..make_confirmed_vote(seed, slot, latency)
+        };
+
+        let unlogged = PerformanceFilterConfig { enabled: false, ..Default::default() };
+
+        stats.add_confirmed_vote_with_config(vote(1, 100, 1), "vote-account", &unlogged);
+        stats.add_confirmed_vote_with_config(vote(2, 101, 4), "vote-account", &unlogged);
+        stats.add_missed_vote(&MissedVote {
+            signature: Arc::new(String::new()),
+            voted_slots: vec![102],
+            transaction_slot: 102,
+            age_slots: 150,
+        });
+        let reset = stats.reset(true);
+        stats.add_confirmed_vote_with_config(vote(3, 103, 2), "vote-account", &unlogged);
+
+        // the reset reports the votes before it, the session holds the ones after it
+        assert_eq!((reset.votes, reset.missed_votes, reset.tvc_earned), (2, 1, 8 + 5));
+        assert!(reset.kept_epoch_history);
+        assert_eq!((stats.total_transactions(), stats.missed_votes(), stats.total_tvc_earned()), (1, 0, 7));
+        assert_eq!(stats.recent_confirmed_votes.iter().map(|v| v.voted_slot).collect::<Vec<_>>(), [103]);
+        assert_eq!(stats.calculate_avg_latency(), 2.0);
+        assert_eq!(stats.current_finalized_slot(), 105);
+        // kept epoch history counts on across the reset
+        assert_eq!(stats.epochs.current().map(|epoch| epoch.votes), Some(3));
+
+        // the settings survive it
+        assert_eq!(stats.tvc, tvc);
+        assert_eq!(stats.recent_confirmed_votes.capacity(), 7);
+        assert_eq!(stats.avg_latency_window.span(), Duration::from_secs(90));
+        assert_eq!(stats.epochs.slots_per_epoch(), 1_000);
+        assert_eq!(stats.streak_alert_votes, 3);
+        assert!(stats.percentiles_include_estimates);
+
+        // and the bus sees it between the votes
+        let order: Vec<_> = std::iter::from_fn(|| published.try_recv().ok())
+            .filter_map(|event| match event {
+                SystemEvent::VoteConfirmed(vote) => Some(format!("vote {}", vote.voted_slot)),
+                SystemEvent::StatsReset(reset) => Some(format!("reset after {}", reset.votes)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(order, ["vote 100", "vote 101", "reset after 2", "vote 103"]);
+
+        // without the epoch history it starts over too
+        stats.reset(false);
+        assert_eq!(stats.epochs.current().map(|epoch| epoch.votes), None);
+        assert_eq!(stats.epochs.slots_per_epoch(), 1_000);
+    }
+}
@@ -0,0 +1,363 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{NaiveDate, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+use crate::config::PerformanceFilterConfig;
+use crate::error::Result;
+use crate::performance::{BatchedEventWriter, EventWriterMetrics, PoorPerformanceEvent};
+
+const FILE_PREFIX: &str = "performance_issues_";
+const FILE_EXTENSION: &str = ".json";
+const COMPRESSED_EXTENSION: &str = ".json.gz";
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// queued poor performance events before new ones are dropped
+pub const PERFORMANCE_EVENT_CHANNEL_CAPACITY: usize = 1000;
+
+const EVENT_BATCH_SIZE: usize = 100;
+const EVENT_FLUSH_INTERVAL_SECS: u64 = 5;
+
+/// daily performance_issues file for `date`
+pub fn performance_log_file(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!("{}{}{}", FILE_PREFIX, date.format("%Y-%m-%d"), FILE_EXTENSION))
+}
+
+/// today's file, days roll over in utc
+pub fn current_performance_log_file(dir: &Path) -> PathBuf {
+    performance_log_file(dir, Utc::now().date_naive())
+}
+
+/// date and compression of a `performance_issues_YYYY-MM-DD.json[.gz]` file name,
+/// none for anything else
+fn parse_file_name(name: &str) -> Option<(NaiveDate, bool)> {
+    let rest = name.strip_prefix(FILE_PREFIX)?;
+    let (date, compressed) = match rest.strip_suffix(COMPRESSED_EXTENSION) {
+        Some(date) => (date, true),
+        None => (rest.strip_suffix(FILE_EXTENSION)?, false),
+    };
+    // parse_from_str accepts unpadded fields, insist on the exact layout
+    if date.len() != 10 {
+        return None;
+    }
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some((date, compressed))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneSummary {
+    pub deleted: usize,
+    pub compressed: usize,
+}
+
+/// delete daily files past retention and gzip finished days
+///
+/// only `performance_issues_YYYY-MM-DD.json[.gz]` files are touched, anything
+/// else in the directory (epoch summaries, vote logs) is left alone.
+pub async fn prune_performance_logs(config: &PerformanceFilterConfig) -> Result<PruneSummary> {
+    let mut summary = PruneSummary::default();
+    let dir = &config.performance_log_dir;
+    if !tokio::fs::try_exists(dir).await.unwrap_or(false) {
+        return Ok(summary);
+    }
+
+    let today = Utc::now().date_naive();
+    let cutoff = config.retention_days.and_then(|days| today.checked_sub_days(chrono::Days::new(days)));
+
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if !entry.file_type().await?.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name();
+        let Some((date, compressed)) = file_name.to_str().and_then(parse_file_name) else {
+            continue;
+        };
+        let path = entry.path();
+
+        if cutoff.is_some_and(|cutoff| date < cutoff) {
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => summary.deleted += 1,
+                Err(e) => log::warn!("failed to delete {}: {}", path.display(), e),
+            }
+            continue;
+        }
+
+        if config.compress && !compressed && date < today {
+            let source = path.clone();
+            match tokio::task::spawn_blocking(move || compress_file(&source)).await {
+                Ok(Ok(())) => summary.compressed += 1,
+                Ok(Err(e)) => log::warn!("failed to compress {}: {}", path.display(), e),
+                Err(e) => log::warn!("compression task for {} failed: {}", path.display(), e),
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// gzip `path` next to itself and remove the original
+///
+/// a day that already has a .gz (written to after it was compressed) gets the
+/// late lines appended as another gzip member.
+fn compress_file(path: &Path) -> std::io::Result<()> {
+    let mut target = path.as_os_str().to_owned();
+    target.push(".gz");
+    let target = PathBuf::from(target);
+    let mut staging = target.as_os_str().to_owned();
+    staging.push(".tmp");
+    let staging = PathBuf::from(staging);
+
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(&staging)?), Compression::default());
+    std::io::copy(&mut BufReader::new(File::open(path)?), &mut encoder)?;
+    encoder.finish()?.flush()?;
+
+    if target.exists() {
+        let mut existing = OpenOptions::new().append(true).open(&target)?;
+        std::io::copy(&mut File::open(&staging)?, &mut existing)?;
+        std::fs::remove_file(&staging)?;
+    } else {
+        std::fs::rename(&staging, &target)?;
+    }
+
+    std::fs::remove_file(path)
+}
+
+/// prune on startup and then once a day
+pub fn spawn_performance_log_pruner(config: PerformanceFilterConfig) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match prune_performance_logs(&config).await {
+                Ok(summary) if summary.deleted > 0 || summary.compressed > 0 => log::info!(
+                    "performance logs in {}: {} deleted, {} compressed",
+                    config.performance_log_dir.display(), summary.deleted, summary.compressed
+                ),
+                Ok(_) => {}
+                Err(e) => log::warn!("failed to prune {}: {}", config.performance_log_dir.display(), e),
+            }
+        }
+    })
+}
+
+/// spawn the poor performance event writer task
+///
+/// flushes on batch size, on interval, and once more when a shutdown signal
+/// arrives or every sender is dropped. failed writes are retried from the
+/// writer's bounded queue, see `BatchedEventWriter`.
+pub fn spawn_performance_event_writer(
+    config: &PerformanceFilterConfig,
+    metrics: Arc<EventWriterMetrics>,
+    mut events: mpsc::Receiver<PoorPerformanceEvent>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    let writer = BatchedEventWriter::new(EVENT_BATCH_SIZE, EVENT_FLUSH_INTERVAL_SECS)
+        .with_log_dir(config.performance_log_dir.clone())
+        .with_retry_queue(config.retry_queue_events, Duration::from_secs(config.retry_max_age_secs))
+        .with_metrics(metrics);
+    tokio::spawn(async move {
+        let mut flush_interval = tokio::time::interval(Duration::from_secs(EVENT_FLUSH_INTERVAL_SECS));
+        let mut writer = writer;
+
+        loop {
+            tokio::select! {
+                event = events.recv() => {
+                    match event {
+                        Some(event) => {
+                            if let Err(e) = writer.add_event(event).await {
+                                log::error!("error writing performance events: {}", e);
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                _ = flush_interval.tick() => {
+                    if let Err(e) = writer.flush_if_due().await {
+                        log::error!("error flushing performance events: {}", e);
+                    }
+                }
+                _ = shutdown.changed() => {
+                    // drain whatever is already queued before the final flush
+                    while let Ok(event) = events.try_recv() {
+                        if let Err(e) = writer.add_event(event).await {
+                            log::error!("error writing performance events: {}", e);
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        if let Err(e) = writer.flush().await {
+            log::error!("error flushing performance events on shutdown: {}", e);
+        }
+        log::info!("performance event writer stopped");
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::ConfirmationKind;
+    use crate::test_support::{signature_base58, temp_dir};
+
+    fn event(seed: u64) -> PoorPerformanceEvent {
+        PoorPerformanceEvent {
+            timestamp: Utc::now(),
+            landed_slot: 1_000 + seed + 6,
+            voted_slot: 1_000 + seed,
+            latency: 6,
+            tvc_credits: 12,
+            transaction_signature: Arc::new(signature_base58(seed)),
+            vote_account: "Vote111111111111111111111111111111111111111".to_string(),
+            total_tvc_credits: 12,
+            total_voted_slots: 1,
+            tvc_multiplier: 0.75,
+            leader: None,
+            loss_cause: None,
+            blockhash: None,
+            parent_slot: None,
+            block_height: None,
+            kind: ConfirmationKind::Matched,
+        }
+    }
+
+    /// voted slots of every event written to `dir`, in file order
+    fn written(dir: &Path) -> Vec<u64> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        // a run across midnight writes two days
+        files.sort();
+        files
+            .iter()
+            .flat_map(|file| std::fs::read_to_string(file).unwrap().lines().map(str::to_string).collect::<Vec<_>>())
+            .map(|line| serde_json::from_str::<PoorPerformanceEvent>(&line).unwrap().voted_slot)
+            .collect()
+    }
+
+    /// send `count` events, then stop the writer by a shutdown signal or,
+    /// with `close_channel`, by dropping the sender
+    async fn run_writer(name: &str, count: u64, close_channel: bool) -> Vec<u64> {
+        let dir = temp_dir(name);
+        let config = PerformanceFilterConfig { performance_log_dir: dir.clone(), ..Default::default() };
+        let (sender, events) = mpsc::channel(count as usize);
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let writer = spawn_performance_event_writer(&config, Arc::new(EventWriterMetrics::new()), events, shutdown_rx);
+
+        for seed in 0..count {
+            sender.send(event(seed)).await.unwrap();
+        }
+        // otherwise the sender outlives the signal, as in the pipeline
+        let sender = (!close_channel).then_some(sender);
+        if !close_channel {
+            shutdown.send(true).unwrap();
+        }
+        tokio::time::timeout(Duration::from_secs(5), writer).await.unwrap().unwrap();
+        drop(sender);
+
+        let slots = written(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        slots
+    }
+
+    #[tokio::test]
+    async fn shutdown_drains_every_queued_event() {
+        // more than a batch, with most still queued when the signal arrives
+        let slots = run_writer("shutdown-drain", 350, false).await;
+        assert_eq!(slots, (1_000..1_350).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn closed_channel_flushes_the_rest() {
+        let slots = run_writer("closed-channel", 7, true).await;
+        assert_eq!(slots, (1_000..1_007).collect::<Vec<_>>());
+    }
+
+    /// make writes to `dir` fail: read-only, and since root writes to
+    /// read-only directories anyway, today's file a link to /dev/full too
+    fn block_writes(dir: &Path) {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o555)).unwrap();
+        let probe = dir.join("probe");
+        if std::fs::write(&probe, b"").is_ok() {
+            std::fs::remove_file(&probe).unwrap();
+            std::os::unix::fs::symlink("/dev/full", current_performance_log_file(dir)).unwrap();
+        }
+    }
+
+    fn unblock_writes(dir: &Path) {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let _ = std::fs::remove_file(current_performance_log_file(dir));
+    }
+
+    #[tokio::test]
+    async fn failed_writes_are_retried_once_writable() {
+        let dir = temp_dir("read-only");
+        let metrics = Arc::new(EventWriterMetrics::new());
+        let mut writer = BatchedEventWriter::new(100, 3_600)
+            .with_log_dir(&dir)
+            .with_retry_queue(5, Duration::ZERO)
+            .with_metrics(metrics.clone());
+
+        block_writes(&dir);
+        for seed in 0..3 {
+            writer.add_event(event(seed)).await.unwrap();
+        }
+        let error = writer.flush().await.unwrap_err();
+        assert!(error.is_transient_io(), "{}", error);
+        let snapshot = metrics.snapshot();
+        assert!(snapshot.retrying);
+        assert_eq!((snapshot.queued_events, snapshot.dropped_events), (3, 0));
+
+        // the queue keeps the newest 5
+        for seed in 3..7 {
+            writer.add_event(event(seed)).await.unwrap();
+        }
+        let snapshot = metrics.snapshot();
+        assert_eq!((snapshot.queued_events, snapshot.dropped_events), (5, 2));
+        // still backing off, nothing is attempted
+        writer.flush_if_due().await.unwrap();
+        assert_eq!(metrics.snapshot().queued_events, 5);
+
+        unblock_writes(&dir);
+        writer.flush().await.unwrap();
+        let snapshot = metrics.snapshot();
+        assert!(!snapshot.retrying);
+        assert_eq!((snapshot.queued_events, snapshot.dropped_events), (0, 2));
+
+        writer.add_event(event(7)).await.unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(written(&dir), (1_002..1_008).collect::<Vec<_>>());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn permanent_write_errors_drop_the_batch() {
+        let dir = temp_dir("not-a-dir");
+        // a file where the log directory should be
+        let log_dir = dir.join("performance_issues");
+        std::fs::write(&log_dir, b"").unwrap();
+        let metrics = Arc::new(EventWriterMetrics::new());
+        let mut writer = BatchedEventWriter::new(100, 3_600).with_log_dir(&log_dir).with_metrics(metrics.clone());
+
+        for seed in 0..3 {
+            writer.add_event(event(seed)).await.unwrap();
+        }
+        assert!(!writer.flush().await.unwrap_err().is_transient_io());
+        let snapshot = metrics.snapshot();
+        assert!(!snapshot.retrying);
+        assert_eq!((snapshot.queued_events, snapshot.dropped_events), (0, 3));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1013 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use log::{debug, error, info, warn};
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+use yellowstone_grpc_proto::geyser::{
+    CommitmentLevel, SlotStatus, SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterBlocks,
+    SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions, SubscribeRequestPing, SubscribeUpdate,
+    SubscribeUpdateAccount, SubscribeUpdateBlock, SubscribeUpdateSlot, SubscribeUpdateTransaction,
+    subscribe_update::UpdateOneof,
+};
+
+use crate::channel_metrics::{ChannelMetrics, PipelineMetrics};
+use crate::cluster_tip::SlotCommitment;
+use crate::commitment::{Commitment, HeldBlocks};
+use crate::config::{Config, ConfirmationMode, PipelineConfig};
+use crate::config_reload::SharedConfig;
+use crate::credits_check::RpcCreditsClient;
+use crate::epoch::{append_epoch_summary, spawn_epoch_report, EpochReport, EpochStats};
+use crate::error::VoteMonitorError;
+use crate::events::EventBus;
+use crate::memory::{format_bytes, MemoryUsage};
+use crate::message::SystemEvent;
+use crate::performance::{save_performance_events, ConfirmationSource, ConfirmedVote, PerformanceStats, Slot};
+use crate::rpc_source::{vote_state_tower, VoteStateVotes};
+use crate::stream_health::{StreamHealth, UpdateKind};
+use crate::storage::StorageRecord;
+use crate::tasks::spawn_named;
+use crate::vote_account::parse_vote_state;
+use crate::vote_tracker::{block_votes, process_finalized_block, process_finalized_slot, process_vote_transaction};
+use crate::vote_tracker_actor::VoteTrackerHandle;
+
+/// default updates buffered per kind between the stream task and the processing tasks
+pub const UPDATE_CHANNEL_CAPACITY: usize = 1000;
+
+const ACCOUNT_CHANNEL_CAPACITY: usize = 16;
+
+/// fallback and memory budget checks in the block task
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// first wait after a failed resubscription, doubled up to `MAX_RESUBSCRIBE_BACKOFF`
+pub const RESUBSCRIBE_BACKOFF: Duration = Duration::from_secs(5);
+pub const MAX_RESUBSCRIBE_BACKOFF: Duration = Duration::from_secs(60);
+
+/// where the stream task routes each kind of update
+#[derive(Debug, Clone)]
+pub struct UpdateSenders {
+    pub transactions: mpsc::Sender<SubscribeUpdateTransaction>,
+    pub blocks: mpsc::Sender<SubscribeUpdateBlock>,
+    pub slots: mpsc::Sender<SubscribeUpdateSlot>,
+    pub accounts: mpsc::Sender<SubscribeUpdateAccount>,
+    /// slots of the blocks the drop policy discarded, their votes are
+    /// confirmed from the transaction slot instead
+    pub dropped_blocks: mpsc::Sender<Slot>,
+    /// fill level and backpressure of the transaction and block channels
+    pub metrics: Arc<PipelineMetrics>,
+    /// block channel depth from which blocks behind the newest finalized slot
+    /// are dropped, none queues every block
+    pub stale_block_drop_depth: Option<usize>,
+}
+
+impl UpdateSenders {
+    /// whether the drop policy discards the block for `slot`
+    fn drops_block(&self, slot: Slot, newest_finalized: Slot) -> bool {
+        match self.stale_block_drop_depth {
+            Some(threshold) => slot < newest_finalized && self.metrics.blocks.sample(&self.blocks) >= threshold,
+            None => false,
+        }
+    }
+
+    fn sample_depths(&self) {
+        self.metrics.transactions.sample(&self.transactions);
+        self.metrics.blocks.sample(&self.blocks);
+    }
+}
+
+#[derive(Debug)]
+pub struct UpdateReceivers {
+    pub transactions: mpsc::Receiver<SubscribeUpdateTransaction>,
+    pub blocks: mpsc::Receiver<SubscribeUpdateBlock>,
+    pub slots: mpsc::Receiver<SubscribeUpdateSlot>,
+    pub accounts: mpsc::Receiver<SubscribeUpdateAccount>,
+    pub dropped_blocks: mpsc::Receiver<Slot>,
+}
+
+/// bounded channels for every update kind, with backpressure on the stream
+pub fn update_channels(config: &PipelineConfig) -> (UpdateSenders, UpdateReceivers) {
+    let capacity = config.channel_capacity;
+    let (transactions_tx, transactions_rx) = mpsc::channel(capacity);
+    let (blocks_tx, blocks_rx) = mpsc::channel(capacity);
+    let (slots_tx, slots_rx) = mpsc::channel(capacity);
+    let (accounts_tx, accounts_rx) = mpsc::channel(ACCOUNT_CHANNEL_CAPACITY);
+    let (dropped_blocks_tx, dropped_blocks_rx) = mpsc::channel(capacity);
+    let stale_block_drop_depth = config.drop_stale_blocks
+        .then(|| (capacity * config.drop_threshold_pct as usize).div_ceil(100).max(1));
+    (
+        UpdateSenders {
+            transactions: transactions_tx,
+            blocks: blocks_tx,
+            slots: slots_tx,
+            accounts: accounts_tx,
+            dropped_blocks: dropped_blocks_tx,
+            metrics: Arc::new(PipelineMetrics::new(capacity)),
+            stale_block_drop_depth,
+        },
+        UpdateReceivers {
+            transactions: transactions_rx,
+            blocks: blocks_rx,
+            slots: slots_rx,
+            accounts: accounts_rx,
+            dropped_blocks: dropped_blocks_rx,
+        },
+    )
+}
+
+/// send without waiting when there is room, otherwise count the wait and block
+async fn send_counted<T>(
+    sender: &mpsc::Sender<T>,
+    metrics: &ChannelMetrics,
+    value: T,
+) -> Result<(), mpsc::error::SendError<T>> {
+    match sender.try_send(value) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(value)) => {
+            metrics.record_blocked();
+            sender.send(value).await
+        }
+        Err(TrySendError::Closed(value)) => Err(mpsc::error::SendError(value)),
+    }
+}
+
+/// state shared by the processing tasks
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    pub vote_account: String,
+    /// `vote_account` decoded, vote instructions are matched against it
+    pub vote_account_key: [u8; 32],
+    /// reloaded in place, take a snapshot with `current`
+    pub config: SharedConfig,
+    pub stats: Arc<RwLock<PerformanceStats>>,
+    pub vote_tracker: VoteTrackerHandle,
+    pub events: EventBus,
+    /// export of every confirmed vote, never waited on
+    pub vote_log: Option<mpsc::Sender<ConfirmedVote>>,
+    /// confirmed votes and epoch summaries for the sqlite backend, never waited on
+    pub storage: Option<mpsc::Sender<StorageRecord>>,
+}
+
+impl Pipeline {
+    /// approximate memory usage across the tracker and the stats windows
+    pub async fn memory_usage(&self, stats: &PerformanceStats) -> MemoryUsage {
+        let mut memory = self.vote_tracker.memory_usage().await.unwrap_or_default();
+        memory.extend(stats.memory_usage());
+        memory
+    }
+
+    /// count votes the tracker declared missed and publish them
+    async fn record_missed_votes(&self) {
+        let missed_votes = self.vote_tracker.take_missed_votes().await.unwrap_or_default();
+        if missed_votes.is_empty() {
+            return;
+        }
+
+        let mut stats_guard = self.stats.write().await;
+        for missed in &missed_votes {
+            stats_guard.add_missed_vote(missed);
+            self.events.publish(SystemEvent::from(missed));
+        }
+    }
+
+    /// confirm the pending votes in a finalized block
+    async fn confirm_block(&self, block: SubscribeUpdateBlock) {
+        let finalized_slot = block.slot;
+        let confirmed_votes = match process_finalized_block(block, &self.vote_account_key, &self.vote_tracker).await {
+            Ok(votes) => votes,
+            Err(e) => {
+                error!("error processing finalized block: {}", e);
+                return;
+            }
+        };
+        self.record_missed_votes().await;
+
+        // update performance stats
+        self.record_confirmed_votes(confirmed_votes).await;
+        self.stats.write().await.record_finalized_slot(finalized_slot);
+    }
+
+    /// note the first sighting of each vote in a confirmed block
+    async fn observe_confirmed_block(&self, block: &SubscribeUpdateBlock) {
+        let voted_slots = block_votes(block, &self.vote_account_key);
+        if voted_slots.is_empty() {
+            return;
+        }
+        let seen = Instant::now();
+        let mut stats_guard = self.stats.write().await;
+        for voted_slot in voted_slots {
+            stats_guard.confirmed_observations.observe(voted_slot, block.slot, seen);
+        }
+    }
+
+    /// add confirmed votes to the shared performance stats
+    async fn record_confirmed_votes(&self, confirmed_votes: Vec<ConfirmedVote>) {
+        if confirmed_votes.is_empty() {
+            return;
+        }
+
+        let config = self.config.current();
+        let mut unqueued_events = Vec::new();
+        let mut completed_epochs = Vec::new();
+        let mut stats_guard = self.stats.write().await;
+        for mut confirmed_vote in confirmed_votes {
+            if stats_guard.commitment.observes_confirmed() {
+                stats_guard.confirmed_observations.attach(&mut confirmed_vote);
+            }
+            // the exports below carry the flag too
+            stats_guard.mark_voter_change(&mut confirmed_vote);
+            // duplicates are only counted, the slot was exported with its first signature
+            if !confirmed_vote.kind.is_counted() {
+                stats_guard.add_confirmed_vote(confirmed_vote);
+                continue;
+            }
+            // never block vote processing on the export
+            if let Some(ref vote_log) = self.vote_log {
+                if vote_log.try_send(confirmed_vote.clone()).is_err() {
+                    warn!("vote log channel full, dropping vote {}", confirmed_vote.voted_slot);
+                }
+            }
+            if let Some(ref storage) = self.storage {
+                if storage.try_send(StorageRecord::Vote(confirmed_vote.clone())).is_err() {
+                    warn!("storage channel full, dropping vote {}", confirmed_vote.voted_slot);
+                }
+            }
+
+            let follow_up = stats_guard.add_confirmed_vote_with_config(
+                confirmed_vote,
+                &self.vote_account,
+                &config.performance_logging
+            );
+            unqueued_events.extend(follow_up.unqueued_event);
+            if let Some(completed_epoch) = follow_up.completed_epoch {
+                let report = stats_guard.epochs.report(&completed_epoch, &self.vote_account);
+                completed_epochs.push((completed_epoch, report));
+            }
+        }
+        // the files below wait on the disk, the dashboard and the other tasks
+        // must not wait on them for the stats
+        drop(stats_guard);
+
+        if let Err(e) = save_performance_events(&unqueued_events, &config.performance_logging.performance_log_dir).await {
+            error!("error saving {} performance events: {}", unqueued_events.len(), e);
+        }
+        for (completed_epoch, report) in completed_epochs {
+            self.finish_epoch(&config, completed_epoch, report).await;
+        }
+    }
+
+    /// log, report and store an epoch the votes closed
+    async fn finish_epoch(&self, config: &Config, completed_epoch: EpochStats, report: EpochReport) {
+        info!("{}", report.summary_line());
+        if config.epoch.reports {
+            let rpc = config.rpc_url.as_ref().and_then(|rpc_url| {
+                RpcCreditsClient::new(rpc_url.clone(), self.vote_account.clone())
+                    .inspect_err(|e| error!("failed to create rpc client for the epoch report: {}", e))
+                    .ok()
+            });
+            spawn_epoch_report(report, config.epoch.report_dir.clone(), rpc);
+        }
+        if let Some(ref storage) = self.storage {
+            let epoch = completed_epoch.epoch;
+            if storage.try_send(StorageRecord::EpochSummary(completed_epoch)).is_err() {
+                error!("storage channel full, dropping epoch {} summary", epoch);
+            }
+        } else if let Err(e) = append_epoch_summary(&completed_epoch, &config.epoch.summary_file).await {
+            error!("error saving epoch summary: {}", e);
+        }
+    }
+}
+
+/// create the grpc subscription request for vote transactions, blocks and slot statuses
+///
+/// `commitment` applies to the transactions and blocks, slot statuses come at
+/// every commitment. the tx-status mode leaves the blocks out, its votes are
+/// confirmed from the finalized slot statuses.
+pub fn create_subscription_request(
+    vote_account: &str,
+    follow_authorized_voter: bool,
+    commitment: CommitmentLevel,
+    confirmation: ConfirmationMode,
+) -> SubscribeRequest {
+    let mut accounts = HashMap::new();
+    if follow_authorized_voter {
+        accounts.insert(
+            "vote_account".to_string(),
+            SubscribeRequestFilterAccounts {
+                account: vec![vote_account.to_string()],
+                owner: vec![],
+                filters: vec![],
+                nonempty_txn_signature: None,
+            },
+        );
+    }
+
+    let mut blocks = HashMap::new();
+    if confirmation.subscribes_blocks() {
+        blocks.insert("vote_blocks".to_string(), vote_blocks_filter(vote_account));
+    }
+
+    SubscribeRequest {
+        accounts,
+        transactions: HashMap::from([(
+            "vote_transactions".to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(true),
+                // unset streams both outcomes, Some(true) would deliver only failed transactions
+                failed: None,
+                signature: None,
+                account_include: vec![vote_account.to_string()],
+                account_exclude: vec![],
+                account_required: vec![],
+            },
+        )]),
+        blocks,
+        // every slot status: processed and confirmed track the cluster tip,
+        // finalized also confirms votes when blocks are unavailable or not
+        // subscribed
+        slots: HashMap::from([(
+            "slots".to_string(),
+            SubscribeRequestFilterSlots {
+                filter_by_commitment: Some(false),
+                interslot_updates: Some(false),
+            },
+        )]),
+        commitment: Some(commitment.into()),
+        ..Default::default()
+    }
+}
+
+/// create the request of the second subscription with `commitment = "both"`,
+/// confirmed blocks only
+pub fn create_confirmed_blocks_request(vote_account: &str) -> SubscribeRequest {
+    SubscribeRequest {
+        blocks: HashMap::from([("confirmed_blocks".to_string(), vote_blocks_filter(vote_account))]),
+        commitment: Some(CommitmentLevel::Confirmed.into()),
+        ..Default::default()
+    }
+}
+
+/// blocks with a transaction touching the vote account, transactions included
+fn vote_blocks_filter(vote_account: &str) -> SubscribeRequestFilterBlocks {
+    SubscribeRequestFilterBlocks {
+        account_include: vec![vote_account.to_string()],
+        include_transactions: Some(true),
+        include_accounts: Some(false),
+        include_entries: Some(false),
+    }
+}
+
+/// commitment of a slot status update, none for the intermediate statuses
+pub fn slot_commitment(status: i32) -> Option<SlotCommitment> {
+    match SlotStatus::try_from(status).ok()? {
+        SlotStatus::SlotProcessed => Some(SlotCommitment::Processed),
+        SlotStatus::SlotConfirmed => Some(SlotCommitment::Confirmed),
+        SlotStatus::SlotFinalized => Some(SlotCommitment::Finalized),
+        _ => None,
+    }
+}
+
+/// why `forward_updates` returned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// the update channels closed, the stream was stopped from our side
+    Closed,
+    /// a stream error or a server side close
+    Disconnected(String),
+    /// the watchdog asked for a resubscription, see `StreamHealth::request_reconnect`
+    Stale,
+}
+
+/// read the grpc stream, answer pings and route updates until it ends
+pub async fn forward_updates<S, E, K>(
+    stream: &mut S,
+    subscribe_tx: &mut K,
+    senders: &UpdateSenders,
+    health: &StreamHealth,
+) -> StreamEnd
+where
+    S: Stream<Item = Result<SubscribeUpdate, E>> + Unpin,
+    E: Display,
+    K: Sink<SubscribeRequest> + Unpin,
+    K::Error: Display,
+{
+    // newest finalized slot seen on the stream, for the block drop policy
+    let mut newest_finalized: Slot = 0;
+    loop {
+        let message = tokio::select! {
+            message = stream.next() => message,
+            _ = health.reconnect_requested() => return StreamEnd::Stale,
+        };
+        let Some(message) = message else {
+            return StreamEnd::Disconnected("stream closed by the server".to_string());
+        };
+        match message {
+            Ok(msg) => {
+                match msg.update_oneof {
+                    Some(UpdateOneof::Transaction(sut)) => {
+                        health.record(UpdateKind::Transaction);
+                        if let Err(e) = send_counted(&senders.transactions, &senders.metrics.transactions, sut).await {
+                            warn!("transaction channel closed: {}, stopping stream", e);
+                            return StreamEnd::Closed;
+                        }
+                    }
+                    Some(UpdateOneof::Block(sub)) => {
+                        health.record(UpdateKind::Block);
+                        let slot = sub.slot;
+                        if senders.drops_block(slot, newest_finalized) {
+                            senders.metrics.blocks.record_dropped();
+                            log::debug!("block channel backed up, dropped stale block {} (finalized {})", slot, newest_finalized);
+                            // its votes still count, without them they would expire as missed
+                            if senders.dropped_blocks.try_send(slot).is_err() {
+                                warn!("dropped block channel full, votes in block {} may be reported as missed", slot);
+                            }
+                        } else if let Err(e) = send_counted(&senders.blocks, &senders.metrics.blocks, sub).await {
+                            warn!("block channel closed: {}, stopping stream", e);
+                            return StreamEnd::Closed;
+                        }
+                        newest_finalized = newest_finalized.max(slot);
+                    }
+                    Some(UpdateOneof::Slot(slot)) => {
+                        health.record_update();
+                        if slot_commitment(slot.status) == Some(SlotCommitment::Finalized) {
+                            newest_finalized = newest_finalized.max(slot.slot);
+                        }
+                        if let Err(e) = senders.slots.send(slot).await {
+                            warn!("slot channel closed: {}, stopping stream", e);
+                            return StreamEnd::Closed;
+                        }
+                    }
+                    Some(UpdateOneof::Account(account)) => {
+                        health.record_update();
+                        if let Err(e) = senders.accounts.send(account).await {
+                            warn!("account channel closed: {}, stopping stream", e);
+                            return StreamEnd::Closed;
+                        }
+                    }
+                    Some(UpdateOneof::Ping(_ping)) => {
+                        health.record(UpdateKind::Ping);
+                        // respond to ping to keep connection alive
+                        let ping_response = SubscribeRequest {
+                            ping: Some(SubscribeRequestPing { id: 1 }),
+                            ..Default::default()
+                        };
+                        if let Err(e) = subscribe_tx.send(ping_response).await {
+                            error!("failed to send ping response: {}", e);
+                            return StreamEnd::Disconnected(format!("failed to send ping response: {}", e));
+                        }
+                        log::debug!("responded to ping");
+                    }
+                    _ => health.record_update(), // ignore other update types
+                }
+                senders.sample_depths();
+            }
+            Err(error) => {
+                error!("grpc stream error: {}", error);
+                return StreamEnd::Disconnected(format!("grpc stream error: {}", error));
+            }
+        }
+    }
+}
+
+/// spawn the task that reads the grpc stream, answers pings and routes updates
+///
+/// a stream error, a server side close or a stale stream publishes
+/// `StreamDisconnected`. closing the update channels, or aborting the task,
+/// stops it quietly.
+pub fn spawn_stream_task<S, E, K>(
+    mut stream: S,
+    mut subscribe_tx: K,
+    senders: UpdateSenders,
+    health: Arc<StreamHealth>,
+    events: EventBus,
+) -> JoinHandle<()>
+where
+    S: Stream<Item = Result<SubscribeUpdate, E>> + Unpin + Send + 'static,
+    E: Display + Send,
+    K: Sink<SubscribeRequest> + Unpin + Send + 'static,
+    K::Error: Display,
+{
+    spawn_named("grpc_stream", async move {
+        health.mark_connected();
+        let end = forward_updates(&mut stream, &mut subscribe_tx, &senders, &health).await;
+        health.mark_disconnected();
+        match end {
+            StreamEnd::Closed => {}
+            StreamEnd::Disconnected(reason) => events.publish(SystemEvent::StreamDisconnected { reason }),
+            StreamEnd::Stale => events.publish(SystemEvent::StreamDisconnected {
+                reason: "no updates from the grpc stream".to_string(),
+            }),
+        }
+        info!("gRPC stream task completed");
+    })
+}
+
+/// `spawn_stream_task` that replaces a stale subscription through
+/// `resubscribe` instead of stopping
+///
+/// with `failover` a failed stream is replaced as well, `resubscribe` then
+/// moves on to the next endpoint. failed resubscriptions are retried with a backoff up to
+/// `MAX_RESUBSCRIBE_BACKOFF` until the update channels close, a rate limited
+/// one waits longer. without `failover` an error that is not retryable, a
+/// rejected token for one, stops the stream instead.
+///
+/// `shutdown` closes the stream and drops the update senders, the processing
+/// tasks then drain what is queued and finish.
+pub fn spawn_resubscribing_stream_task<S, E, K, F, Fut>(
+    connection: (K, S),
+    mut resubscribe: F,
+    failover: bool,
+    senders: UpdateSenders,
+    health: Arc<StreamHealth>,
+    events: EventBus,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()>
+where
+    S: Stream<Item = Result<SubscribeUpdate, E>> + Unpin + Send + 'static,
+    E: Display + Send,
+    K: Sink<SubscribeRequest> + Unpin + Send + 'static,
+    K::Error: Display,
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = crate::error::Result<(K, S)>> + Send,
+{
+    spawn_named("grpc_stream", async move {
+        let (mut subscribe_tx, mut stream) = connection;
+        health.mark_connected();
+        loop {
+            let end = tokio::select! {
+                end = forward_updates(&mut stream, &mut subscribe_tx, &senders, &health) => end,
+                _ = shutdown.changed() => StreamEnd::Closed,
+            };
+            health.mark_disconnected();
+            match end {
+                StreamEnd::Closed => break,
+                StreamEnd::Disconnected(reason) if failover => {
+                    events.publish(SystemEvent::StreamDisconnected { reason: format!("{}, failing over", reason) });
+                }
+                StreamEnd::Disconnected(reason) => {
+                    events.publish(SystemEvent::StreamDisconnected { reason });
+                    break;
+                }
+                StreamEnd::Stale => {
+                    events.publish(SystemEvent::StreamDisconnected {
+                        reason: "no updates from the grpc stream, resubscribing".to_string(),
+                    });
+                }
+            }
+
+            let mut backoff = RESUBSCRIBE_BACKOFF;
+            let resubscribed = async {
+                loop {
+                    match resubscribe().await {
+                        Ok(connection) => break Some(connection),
+                        Err(e) if senders.transactions.is_closed() => {
+                            warn!("resubscribe failed while stopping: {}", e);
+                            break None;
+                        }
+                        Err(e) if !failover && !e.is_retryable() => {
+                            error!("resubscribe failed and retrying won't help, stopping: {}", e);
+                            events.publish(SystemEvent::StreamDisconnected { reason: e.to_string() });
+                            break None;
+                        }
+                        Err(e) => {
+                            if matches!(e, VoteMonitorError::GrpcRateLimited(_)) {
+                                backoff = (backoff * 4).min(MAX_RESUBSCRIBE_BACKOFF);
+                            }
+                            error!("resubscribe failed, retrying in {}s: {}", backoff.as_secs(), e);
+                            tokio::time::sleep(backoff).await;
+                            backoff = (backoff * 2).min(MAX_RESUBSCRIBE_BACKOFF);
+                        }
+                    }
+                }
+            };
+            let connection = tokio::select! {
+                connection = resubscribed => connection,
+                _ = shutdown.changed() => None,
+            };
+            let Some((new_tx, new_stream)) = connection else { break };
+            subscribe_tx = new_tx;
+            stream = new_stream;
+            health.mark_connected();
+            info!("grpc subscription re-established");
+        }
+        info!("gRPC stream task completed");
+    })
+}
+
+/// spawn the task that turns vote transactions into pending votes
+///
+/// runs until the transaction channel closes.
+pub fn spawn_transaction_task(
+    mut transactions: mpsc::Receiver<SubscribeUpdateTransaction>,
+    pipeline: Pipeline,
+) -> JoinHandle<()> {
+    spawn_named("transactions", async move {
+        while let Some(tx_update) = transactions.recv().await {
+            let processed = match process_vote_transaction(tx_update, &pipeline.vote_account_key, &pipeline.vote_tracker).await {
+                Ok(processed) => processed,
+                Err(e) => {
+                    error!("error processing vote transaction: {}", e);
+                    continue;
+                }
+            };
+
+            if processed.foreign {
+                debug!("ignoring a vote transaction for another vote account");
+                pipeline.stats.read().await.add_foreign_vote();
+            }
+            if let Some(failed) = processed.failed {
+                pipeline.stats.write().await.add_failed_vote(failed);
+            }
+            if processed.pending_added || processed.tower.is_some() {
+                let mut stats = pipeline.stats.write().await;
+                if processed.pending_added {
+                    stats.record_vote_sent();
+                }
+                if let Some(tower) = processed.tower {
+                    stats.record_tower_update(tower);
+                }
+            }
+
+            // votes whose finalized block arrived before the transaction
+            pipeline.record_confirmed_votes(processed.confirmed).await;
+        }
+        info!("transaction processing task completed");
+    })
+}
+
+/// spawn the task that confirms votes from finalized blocks (or slot statuses
+/// in the fallback), follows the cluster tip and the authorized voter
+///
+/// with `commitment = "confirmed"` the blocks are confirmed ones, each held
+/// until its slot is finalized. `confirmed_blocks` is the second subscription
+/// of `commitment = "both"`, its blocks are only observed.
+///
+/// `dropped_blocks` are the slots of finalized blocks the stream task dropped
+/// while the block channel was backed up, see `UpdateSenders::drops_block`.
+///
+/// runs until the block, slot, account and dropped block channels are closed
+/// and drained.
+pub fn spawn_block_task(
+    mut blocks: mpsc::Receiver<SubscribeUpdateBlock>,
+    mut confirmed_blocks: Option<mpsc::Receiver<SubscribeUpdateBlock>>,
+    mut slots: mpsc::Receiver<SubscribeUpdateSlot>,
+    mut accounts: mpsc::Receiver<SubscribeUpdateAccount>,
+    mut dropped_blocks: mpsc::Receiver<Slot>,
+    pipeline: Pipeline,
+) -> JoinHandle<()> {
+    spawn_named("blocks", async move {
+        let stats = &pipeline.stats;
+        // tracker and memory settings need a restart, the startup values stay
+        let config = pipeline.config.current();
+        let mut maintenance = tokio::time::interval(MAINTENANCE_INTERVAL);
+        let mut over_memory_budget = false;
+        let mut blocks_seen = false;
+        let block_timeout = Duration::from_secs(config.tracker.block_timeout_secs);
+        let stream_start = Instant::now();
+        let commitment = config.commitment;
+        let mut held_blocks = HeldBlocks::default();
+        let mut vote_state_votes = VoteStateVotes::new();
+        let mut confirmed_open = confirmed_blocks.is_some();
+        // first block after the slot-status fallback, finalized slot statuses
+        // before it still confirm the votes that landed before the blocks resumed
+        let mut handover_slot: Option<Slot> = None;
+        // the stream drops every sender at once, each channel is drained to the end
+        let (mut blocks_open, mut slots_open, mut accounts_open, mut dropped_open) = (true, true, true, true);
+
+        while blocks_open || slots_open || accounts_open || dropped_open {
+            tokio::select! {
+                block_update = blocks.recv(), if blocks_open => {
+                    let Some(block_update) = block_update else {
+                        blocks_open = false;
+                        continue;
+                    };
+                    if !blocks_seen {
+                        blocks_seen = true;
+                        let mut stats_guard = stats.write().await;
+                        if stats_guard.confirmation_source == ConfirmationSource::SlotStatus {
+                            stats_guard.confirmation_source = ConfirmationSource::Block;
+                            stats_guard.mark_changed();
+                            handover_slot = Some(block_update.slot);
+                            warn!(
+                                "block updates resumed at slot {}, leaving the slot-status fallback and \
+                                 confirming votes from blocks again",
+                                block_update.slot
+                            );
+                        }
+                    }
+                    // both paths consume the same pending votes, only one may be active
+                    if stats.read().await.confirmation_source != ConfirmationSource::Block {
+                        continue;
+                    }
+
+                    if commitment == Commitment::Confirmed {
+                        pipeline.observe_confirmed_block(&block_update).await;
+                        if let Some(finalized) = held_blocks.hold(block_update) {
+                            pipeline.confirm_block(finalized).await;
+                        }
+                    } else {
+                        pipeline.confirm_block(block_update).await;
+                    }
+                }
+
+                block_update = recv_optional(&mut confirmed_blocks), if confirmed_open => {
+                    let Some(block_update) = block_update else {
+                        confirmed_open = false;
+                        continue;
+                    };
+                    pipeline.observe_confirmed_block(&block_update).await;
+                }
+
+                dropped_slot = dropped_blocks.recv(), if dropped_open => {
+                    let Some(dropped_slot) = dropped_slot else {
+                        dropped_open = false;
+                        continue;
+                    };
+                    if stats.read().await.confirmation_source != ConfirmationSource::Block {
+                        continue;
+                    }
+                    match pipeline.vote_tracker.confirm_dropped_block(dropped_slot).await {
+                        Ok(confirmed_votes) => pipeline.record_confirmed_votes(confirmed_votes).await,
+                        Err(e) => error!("error confirming the votes of dropped block {}: {}", dropped_slot, e),
+                    }
+                }
+
+                slot_update = slots.recv(), if slots_open => {
+                    let Some(slot_update) = slot_update else {
+                        slots_open = false;
+                        continue;
+                    };
+                    let status = slot_commitment(slot_update.status);
+                    let finalized = status == Some(SlotCommitment::Finalized);
+                    let handing_over = finalized && handover_slot.is_some_and(|first_block| slot_update.slot < first_block);
+                    // the slot before the first block is the last one the fallback confirms
+                    if finalized && handover_slot.is_some_and(|first_block| slot_update.slot + 1 >= first_block) {
+                        handover_slot = None;
+                    }
+                    let on_fallback = {
+                        let mut stats_guard = stats.write().await;
+                        if let Some(status) = status {
+                            stats_guard.record_slot_status(slot_update.slot, status);
+                        }
+                        stats_guard.confirmation_source == ConfirmationSource::SlotStatus
+                    } || handing_over;
+                    if !on_fallback {
+                        // a held confirmed block is final now
+                        if commitment == Commitment::Confirmed && finalized {
+                            if let Some(finalized) = held_blocks.finalize(slot_update.slot) {
+                                pipeline.confirm_block(finalized).await;
+                            }
+                        }
+                        continue;
+                    }
+
+                    let confirmed_votes = match process_finalized_slot(slot_update, &pipeline.vote_tracker).await {
+                        Ok(votes) => votes,
+                        Err(e) => {
+                            error!("error processing slot update: {}", e);
+                            continue;
+                        }
+                    };
+                    pipeline.record_missed_votes().await;
+                    pipeline.record_confirmed_votes(confirmed_votes).await;
+                }
+
+                account_update = accounts.recv(), if accounts_open => {
+                    let Some(account_update) = account_update else {
+                        accounts_open = false;
+                        continue;
+                    };
+                    let Some(account) = account_update.account else { continue };
+                    // parsed once for the voter and, with source = "rpc", the tower
+                    let state = match parse_vote_state(&account.data) {
+                        Ok(state) => state,
+                        Err(e) => {
+                            error!("error processing vote account update: {}", e);
+                            continue;
+                        }
+                    };
+                    let (from_vote_state, tvc) = {
+                        let mut stats_guard = stats.write().await;
+                        let epoch = stats_guard.epochs.epoch_of(account_update.slot);
+                        if let Some(change) = stats_guard.authorized_voter.update(&state, account_update.slot, epoch) {
+                            stats_guard.mark_changed();
+                            warn!(
+                                "authorized voter changed at slot {}: {} -> {}",
+                                change.slot,
+                                change.previous.map(|p| p.to_string()).unwrap_or_default(),
+                                change.current
+                            );
+                        }
+                        (stats_guard.confirmation_source == ConfirmationSource::VoteState, stats_guard.tvc)
+                    };
+                    if !from_vote_state {
+                        continue;
+                    }
+
+                    // source = "rpc": the tower itself confirms the votes
+                    let confirmed_votes = vote_state_votes.new_votes(&state, account_update.slot, &tvc);
+                    if let Some(tower) = vote_state_tower(&state) {
+                        stats.write().await.record_tower_update(tower);
+                    }
+                    pipeline.record_confirmed_votes(confirmed_votes).await;
+                }
+
+                _ = maintenance.tick() => {
+                    stats.write().await.roll_efficiency_trend();
+                    
+                    // vote transactions but no blocks: the endpoint likely rejects block subscriptions
+                    if !blocks_seen && !block_timeout.is_zero() && stream_start.elapsed() >= block_timeout {
+                        let pending_votes = pipeline.vote_tracker.get_stats().await.map(|s| s.pending_votes).unwrap_or(0);
+                        let mut stats_guard = stats.write().await;
+                        if pending_votes > 0 && stats_guard.confirmation_source == ConfirmationSource::Block {
+                            stats_guard.confirmation_source = ConfirmationSource::SlotStatus;
+                            stats_guard.mark_changed();
+                            warn!(
+                                "no block updates after {}s while {} vote transactions are pending: \
+                                 the endpoint may not allow block subscriptions. switching to the \
+                                 slot-status fallback, latencies are estimated from the transaction slot",
+                                block_timeout.as_secs(), pending_votes
+                            );
+                        }
+                    }
+
+                    // warn once per budget crossing, not on every tick
+                    if let Some(budget_mb) = config.memory_soft_budget_mb {
+                        let memory = pipeline.memory_usage(&*stats.read().await).await;
+                        let over_budget = memory.total_bytes() as u64 > budget_mb * 1024 * 1024;
+                        if over_budget && !over_memory_budget {
+                            if let Some(largest) = memory.largest() {
+                                warn!(
+                                    "approx memory usage {} exceeds soft budget of {} MiB (largest: {} with {} entries, {})",
+                                    format_bytes(memory.total_bytes()), budget_mb,
+                                    largest.name, largest.entries, format_bytes(largest.bytes)
+                                );
+                            }
+                        }
+                        over_memory_budget = over_budget;
+                    }
+                }
+            }
+        }
+        if held_blocks.dropped() > 0 || !held_blocks.is_empty() {
+            info!(
+                "{} confirmed blocks never finalized, {} still held at shutdown",
+                held_blocks.dropped(), held_blocks.len()
+            );
+        }
+        info!("block processing task completed");
+    })
+}
+
+/// the next update of an optional channel, none right away without one
+async fn recv_optional<T>(receiver: &mut Option<mpsc::Receiver<T>>) -> Option<T> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PerformanceFilterConfig;
+    use crate::performance::{ConfirmationKind, PoorPerformanceEvent};
+    use crate::performance_log::current_performance_log_file;
+    use crate::test_support::{
+        make_block, make_confirmed_vote, make_slot, make_vote_tx, signature, temp_dir, vote_account_key, VOTE_ACCOUNT,
+    };
+    use crate::vote_tracker::VoteTracker;
+
+    fn pipeline(config: Config) -> Pipeline {
+        let events = EventBus::default();
+        Pipeline {
+            vote_account: VOTE_ACCOUNT.to_string(),
+            vote_account_key: vote_account_key(),
+            config: SharedConfig::new(config),
+            stats: Arc::new(RwLock::new(PerformanceStats::new())),
+            vote_tracker: VoteTrackerHandle::spawn(VoteTracker::new(), events.clone()).0,
+            events,
+            vote_log: None,
+            storage: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn poor_votes_are_written_once_without_a_writer_task() {
+        const POOR_VOTES: u64 = 300;
+        let dir = temp_dir("pipeline-unqueued-events");
+        let pipeline = pipeline(Config {
+            performance_logging: PerformanceFilterConfig {
+                enabled: true,
+                min_latency_threshold: Some(5),
+                max_tvc_threshold: None,
+                performance_levels: Vec::new(),
+                performance_log_dir: dir.clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        // every other vote is late enough for the log, in batches the size of a few blocks
+        let votes: Vec<_> = (0..POOR_VOTES * 2)
+            .map(|i| make_confirmed_vote(i, 1_000 + i, if i % 2 == 0 { 8 } else { 1 }))
+            .collect();
+        for batch in votes.chunks(37) {
+            pipeline.record_confirmed_votes(batch.to_vec()).await;
+        }
+
+        let stats = pipeline.stats.try_read().expect("stats unlocked after recording");
+        assert_eq!(stats.confirmed_votes(), POOR_VOTES * 2);
+        drop(stats);
+        let mut written: Vec<_> = std::fs::read_to_string(current_performance_log_file(&dir))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<PoorPerformanceEvent>(line).unwrap().voted_slot)
+            .collect();
+        written.sort_unstable();
+        let expected: Vec<_> = (0..POOR_VOTES * 2).step_by(2).map(|i| 1_000 + i).collect();
+        assert_eq!(written, expected);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn blocks_resuming_end_the_slot_status_fallback() {
+        let pipeline = pipeline(Config::default());
+        pipeline.stats.write().await.confirmation_source = ConfirmationSource::SlotStatus;
+        let (blocks_tx, blocks) = mpsc::channel(8);
+        let (slots_tx, slots) = mpsc::channel(8);
+        let (accounts_tx, accounts) = mpsc::channel(1);
+        let (dropped_tx, dropped_blocks) = mpsc::channel(1);
+        let task = spawn_block_task(blocks, None, slots, accounts, dropped_blocks, pipeline.clone());
+
+        // one vote landed before the blocks resume, one in the first block
+        let early = make_vote_tx(signature(1), 100, &[(99, 1)]);
+        let late = make_vote_tx(signature(2), 102, &[(101, 1)]);
+        for tx in [&early, &late] {
+            process_vote_transaction(tx.clone(), &vote_account_key(), &pipeline.vote_tracker).await.unwrap();
+        }
+
+        blocks_tx.send(make_block(102, &[late])).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while pipeline.stats.read().await.confirmation_source != ConfirmationSource::Block {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("back on blocks");
+        // the fallback still confirms the earlier transaction slot once it is final
+        for slot in [100, 101, 102] {
+            slots_tx.send(make_slot(slot, SlotStatus::SlotFinalized)).await.unwrap();
+        }
+        drop((blocks_tx, slots_tx, accounts_tx, dropped_tx));
+        task.await.unwrap();
+
+        let stats = pipeline.stats.read().await;
+        let votes: Vec<_> = stats.recent_confirmed_votes.iter().map(|vote| (vote.voted_slot, vote.kind)).collect();
+        assert_eq!(votes, [(101, ConfirmationKind::Matched), (99, ConfirmationKind::EstimatedTxSlot)]);
+        assert_eq!((stats.missed_votes(), stats.duplicate_confirmations()), (0, 0));
+        assert_eq!(pipeline.vote_tracker.get_stats().await.unwrap().pending_votes, 0);
+    }
+
+    #[tokio::test]
+    async fn votes_in_dropped_blocks_are_not_missed() {
+        let pipeline = pipeline(Config::default());
+        let (blocks_tx, blocks) = mpsc::channel(8);
+        let (slots_tx, slots) = mpsc::channel(8);
+        let (accounts_tx, accounts) = mpsc::channel(1);
+        let (dropped_tx, dropped_blocks) = mpsc::channel(8);
+        let task = spawn_block_task(blocks, None, slots, accounts, dropped_blocks, pipeline.clone());
+
+        let queued = make_vote_tx(signature(1), 100, &[(99, 1)]);
+        let dropped = make_vote_tx(signature(2), 102, &[(101, 1)]);
+        for tx in [&queued, &dropped] {
+            process_vote_transaction(tx.clone(), &vote_account_key(), &pipeline.vote_tracker).await.unwrap();
+        }
+
+        // block 102 dropped while block 100 was still queued
+        dropped_tx.send(102).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while pipeline.vote_tracker.get_stats().await.unwrap().pending_votes > 1 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("dropped block confirmed");
+        blocks_tx.send(make_block(100, &[queued])).await.unwrap();
+        // a replay of the dropped block is a duplicate, and far later blocks
+        // expire whatever is still pending
+        blocks_tx.send(make_block(102, &[dropped])).await.unwrap();
+        blocks_tx.send(make_block(400, &[])).await.unwrap();
+        drop((blocks_tx, slots_tx, accounts_tx, dropped_tx));
+        task.await.unwrap();
+
+        let stats = pipeline.stats.read().await;
+        let votes: Vec<_> = stats.recent_confirmed_votes.iter().map(|vote| (vote.voted_slot, vote.kind)).collect();
+        assert_eq!(votes, [(101, ConfirmationKind::EstimatedTxSlot), (99, ConfirmationKind::Matched)]);
+        assert_eq!((stats.missed_votes(), stats.duplicate_confirmations()), (0, 0));
+    }
+}
@@ -0,0 +1,307 @@
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::config::PrometheusConfig;
+use crate::error::{Result, VoteMonitorError};
+use crate::influx::metrics_error;
+use crate::performance::{PerformanceStats, LATENCY_BUCKETS};
+
+/// job label of the pushed group
+pub const PUSH_JOB: &str = "voteperfx";
+
+/// content type of the text exposition format
+pub const TEXT_FORMAT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// first retry after a failed push, doubled up to `MAX_RETRY_BACKOFF`
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// the metric set served on /metrics and pushed to the pushgateway
+///
+/// encoded from the session stats on demand, the counters follow the
+/// dashboard and reset with it. one registry is shared by both modes so a
+/// scrape also sees the failed pushes.
+#[derive(Debug)]
+pub struct MetricsRegistry {
+    stats: Arc<RwLock<PerformanceStats>>,
+    push_failures: AtomicU64,
+}
+
+impl MetricsRegistry {
+    pub fn new(stats: Arc<RwLock<PerformanceStats>>) -> Self {
+        Self { stats, push_failures: AtomicU64::new(0) }
+    }
+
+    /// count a failed push, returns the failures so far
+    pub fn record_push_failure(&self) -> u64 {
+        self.push_failures.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub fn push_failures(&self) -> u64 {
+        self.push_failures.load(Ordering::Relaxed)
+    }
+
+    /// the metrics in the text exposition format
+    pub async fn gather(&self) -> String {
+        let stats = self.stats.read().await;
+        self.encode(&stats)
+    }
+
+    pub fn encode(&self, stats: &PerformanceStats) -> String {
+        let mut out = String::with_capacity(4096);
+        let counters = [
+            ("voteperfx_votes_total", "confirmed votes", stats.total_transactions()),
+            ("voteperfx_optimal_votes_total", "votes earning max credits", stats.optimal_votes()),
+            ("voteperfx_good_votes_total", "votes earning at least 3/4 of max credits", stats.good_votes()),
+            ("voteperfx_poor_votes_total", "votes earning less than 3/4 of max credits", stats.poor_votes()),
+            ("voteperfx_missed_votes_total", "voted slots never finalized", stats.missed_votes()),
+            ("voteperfx_failed_votes_total", "vote transactions that landed with an error", stats.failed_votes()),
+            ("voteperfx_low_latency_votes_total", "votes within thresholds.low_latency_slots", stats.low_latency_votes()),
+            ("voteperfx_tvc_earned_total", "timely vote credits earned", stats.total_tvc_earned()),
+            ("voteperfx_tvc_possible_total", "timely vote credits possible", stats.total_tvc_possible()),
+            ("voteperfx_foreign_votes_ignored_total", "vote transactions for another vote account", stats.foreign_votes_ignored()),
+            ("voteperfx_duplicate_confirmations_total", "voted slots confirmed again by another signature", stats.duplicate_confirmations()),
+            ("voteperfx_dropped_performance_events_total", "poor performance events the writer had no room for", stats.dropped_performance_events()),
+            ("voteperfx_push_failures_total", "failed pushes to the pushgateway", self.push_failures()),
+        ];
+        for (name, help, value) in counters {
+            sample(&mut out, name, "counter", help, value);
+        }
+
+        sample(&mut out, "voteperfx_efficiency_percent", "gauge", "credits earned of possible this session", stats.calculate_efficiency());
+        sample(
+            &mut out,
+            "voteperfx_window_efficiency_percent",
+            "gauge",
+            "credits earned of possible over the recent votes",
+            stats.calculate_window_efficiency(),
+        );
+        sample(
+            &mut out,
+            "voteperfx_avg_latency_slots",
+            "gauge",
+            "average vote latency over stats.recent_window_secs",
+            stats.calculate_avg_latency(),
+        );
+        sample(&mut out, "voteperfx_finalized_slot", "gauge", "newest finalized slot with a vote", stats.current_finalized_slot());
+        sample(&mut out, "voteperfx_session_uptime_seconds", "gauge", "age of the session", stats.session_start.elapsed().as_secs());
+        sample(
+            &mut out,
+            "voteperfx_vote_silence_seconds",
+            "gauge",
+            "time without a vote transaction while blocks arrive, 0 while voting",
+            stats.votes_sent.silence().map_or(0, |quiet| quiet.as_secs()),
+        );
+        if let Some(age) = stats.stream_health.as_ref().and_then(|health| health.since_last_update()) {
+            sample(&mut out, "voteperfx_grpc_update_age_seconds", "gauge", "time since the last grpc update", age.as_secs_f64());
+        }
+
+        // the last latency bucket collects everything above, it becomes +Inf
+        let latency = stats.latency_distribution();
+        let name = "voteperfx_vote_latency_slots";
+        let _ = writeln!(out, "# HELP {} slots from the voted slot to the slot the vote landed in", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let mut cumulative = 0;
+        for (bucket, count) in latency.counts.iter().enumerate() {
+            cumulative += count;
+            let le = if bucket + 1 == LATENCY_BUCKETS { "+Inf".to_string() } else { bucket.to_string() };
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, cumulative);
+        }
+        let _ = writeln!(out, "{}_sum {}", name, stats.total_latency_sum.load(Ordering::Relaxed));
+        let _ = writeln!(out, "{}_count {}", name, latency.total());
+        out
+    }
+}
+
+fn sample(out: &mut String, name: &str, kind: &str, help: &str, value: impl Display) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// replace the pushed group with the current metrics
+async fn push(http: &reqwest::Client, url: &str, registry: &MetricsRegistry) -> Result<()> {
+    let response = http
+        .put(url)
+        .header(reqwest::header::CONTENT_TYPE, TEXT_FORMAT)
+        .body(registry.gather().await)
+        .send()
+        .await
+        .map_err(metrics_error)?;
+    if let Err(e) = response.error_for_status_ref() {
+        // the pushgateway explains a rejected push in the body
+        let body = response.text().await.unwrap_or_default();
+        return Err(VoteMonitorError::Metrics(format!("{}: {}", metrics_error(e), body.trim())));
+    }
+    Ok(())
+}
+
+/// push the registry to `prometheus.pushgateway_url` every
+/// `push_interval_secs`, grouped as job="voteperfx", instance=`vote_account`
+///
+/// each push replaces the group. a failed push is counted and retried with
+/// backoff, ticks in between are skipped; pushes read the stats like a
+/// scrape and never wait on the processing tasks. on shutdown one last push
+/// is attempted before the returned task completes.
+pub fn spawn_pushgateway(
+    config: &PrometheusConfig,
+    registry: Arc<MetricsRegistry>,
+    vote_account: &str,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<JoinHandle<()>> {
+    let base = config.pushgateway_url.as_deref().ok_or_else(|| {
+        VoteMonitorError::Config("prometheus.pushgateway_url is not set".to_string())
+    })?;
+    let url = format!("{}/metrics/job/{}/instance/{}", base.trim_end_matches('/'), PUSH_JOB, vote_account);
+    let http = reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?;
+    let push_interval = Duration::from_secs(config.push_interval_secs);
+    info!("prometheus pushgateway enabled: {} every {}s", url, push_interval.as_secs());
+
+    Ok(tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(push_interval);
+        let mut retry_at = Instant::now();
+        let mut backoff = RETRY_BACKOFF;
+        let mut failures = 0u64;
+        loop {
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            if Instant::now() < retry_at {
+                continue;
+            }
+            match push(&http, &url, &registry).await {
+                Ok(()) => {
+                    if failures > 0 {
+                        info!("pushgateway pushes resumed after {} failed attempts", failures);
+                    }
+                    failures = 0;
+                    backoff = RETRY_BACKOFF;
+                }
+                Err(e) => {
+                    failures += 1;
+                    registry.record_push_failure();
+                    warn!("pushgateway push failed, retrying in {}s: {}", backoff.as_secs(), e);
+                    retry_at = Instant::now() + backoff;
+                    backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+                }
+            }
+        }
+
+        // the stats are final once the shutdown signal is sent
+        if let Err(e) = push(&http, &url, &registry).await {
+            registry.record_push_failure();
+            warn!("final pushgateway push failed: {}", e);
+        }
+        info!("prometheus pushgateway stopped");
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::make_confirmed_vote;
+    use axum::extract::{Path, State};
+    use axum::http::StatusCode;
+    use axum::routing::put;
+    use axum::Router;
+
+    type Pushes = Arc<std::sync::Mutex<Vec<(String, String)>>>;
+
+    /// a pushgateway answering every push with `status`
+    async fn pushgateway(status: StatusCode) -> (String, Pushes) {
+        let pushes = Pushes::default();
+        let app = Router::new()
+            .route(
+                "/metrics/*group",
+                put(move |State(pushes): State<Pushes>, Path(group): Path<String>, body: String| async move {
+                    pushes.lock().unwrap().push((group, body));
+                    status
+                }),
+            )
+            .with_state(pushes.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{}/", addr), pushes)
+    }
+
+    #[test]
+    fn latency_histogram_is_cumulative() {
+        let stats = Arc::new(RwLock::new(PerformanceStats::new()));
+        let registry = MetricsRegistry::new(stats);
+        let mut session = PerformanceStats::new();
+        session.add_confirmed_vote(make_confirmed_vote(1, 100, 1));
+        session.add_confirmed_vote(make_confirmed_vote(2, 101, 1));
+        session.add_confirmed_vote(make_confirmed_vote(3, 102, 4));
+
+        let text = registry.encode(&session);
+        assert!(text.contains("voteperfx_votes_total 3\n"), "{}", text);
+        assert!(text.contains("voteperfx_vote_latency_slots_bucket{le=\"1\"} 2\n"), "{}", text);
+        assert!(text.contains("voteperfx_vote_latency_slots_bucket{le=\"3\"} 2\n"), "{}", text);
+        assert!(text.contains("voteperfx_vote_latency_slots_bucket{le=\"+Inf\"} 3\n"), "{}", text);
+        assert!(text.contains("voteperfx_vote_latency_slots_sum 6\n"), "{}", text);
+        assert!(text.contains("voteperfx_vote_latency_slots_count 3\n"), "{}", text);
+        // every sample is announced with its type
+        for line in text.lines().filter(|line| !line.starts_with('#')) {
+            let name = line.split(['{', ' ']).next().unwrap();
+            let family = name.trim_end_matches("_bucket").trim_end_matches("_sum").trim_end_matches("_count");
+            assert!(text.contains(&format!("# TYPE {} ", family)), "{} has no TYPE", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn final_push_replaces_the_group_on_shutdown() {
+        let (url, pushes) = pushgateway(StatusCode::OK).await;
+        let stats = Arc::new(RwLock::new(PerformanceStats::new()));
+        stats.write().await.add_confirmed_vote(make_confirmed_vote(1, 100, 1));
+        let registry = Arc::new(MetricsRegistry::new(stats));
+        let config = PrometheusConfig { pushgateway_url: Some(url), push_interval_secs: 3600, ..Default::default() };
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let task = spawn_pushgateway(&config, registry.clone(), "VoteAccount", shutdown_rx).unwrap();
+        shutdown.send(true).unwrap();
+        task.await.unwrap();
+
+        let pushes = pushes.lock().unwrap();
+        // the first tick pushes right away, the shutdown pushes again
+        assert!(!pushes.is_empty());
+        for (group, body) in pushes.iter() {
+            assert_eq!(group, "job/voteperfx/instance/VoteAccount");
+            assert!(body.contains("voteperfx_votes_total 1\n"), "{}", body);
+        }
+        assert_eq!(registry.push_failures(), 0);
+    }
+
+    #[tokio::test]
+    async fn rejected_pushes_are_counted() {
+        let (url, pushes) = pushgateway(StatusCode::BAD_REQUEST).await;
+        let registry = Arc::new(MetricsRegistry::new(Arc::new(RwLock::new(PerformanceStats::new()))));
+        let config = PrometheusConfig { pushgateway_url: Some(url), ..Default::default() };
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let task = spawn_pushgateway(&config, registry.clone(), "VoteAccount", shutdown_rx).unwrap();
+        shutdown.send(true).unwrap();
+        task.await.unwrap();
+
+        assert_eq!(registry.push_failures() as usize, pushes.lock().unwrap().len());
+        assert!(registry.push_failures() >= 1);
+        assert!(registry.encode(&PerformanceStats::new()).contains(&format!("voteperfx_push_failures_total {}\n", registry.push_failures())));
+    }
+
+    #[test]
+    fn pushgateway_needs_a_url() {
+        let registry = Arc::new(MetricsRegistry::new(Arc::new(RwLock::new(PerformanceStats::new()))));
+        let (_shutdown, shutdown_rx) = watch::channel(false);
+        let result = spawn_pushgateway(&PrometheusConfig::default(), registry, "VoteAccount", shutdown_rx);
+        assert!(matches!(result, Err(VoteMonitorError::Config(_))));
+    }
+}
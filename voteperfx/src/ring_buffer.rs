@@ -0,0 +1,168 @@
+use std::collections::vec_deque::{self, VecDeque};
+
+/// fixed capacity buffer that overwrites its oldest item when full
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// panics on a zero capacity, config validation rejects those first
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "ring buffer capacity must be at least 1");
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// append `item`, returning the oldest item when it was overwritten
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.is_full() { self.items.pop_front() } else { None };
+        self.items.push_back(item);
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.capacity
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// oldest first
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// the newest `n` items, newest first
+    pub fn latest(&self, n: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter().rev().take(n)
+    }
+
+    /// newest item
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        self.items.back_mut()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// bytes held by the slots, not counting heap data owned by the items
+    pub fn allocated_bytes(&self) -> usize {
+        self.items.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T> {
+    /// only the newest `capacity` items are kept
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(buffer: &RingBuffer<u32>) -> Vec<u32> {
+        buffer.iter().copied().collect()
+    }
+
+    #[test]
+    fn fills_up_to_capacity() {
+        let mut buffer = RingBuffer::new(3);
+        assert!(buffer.is_empty() && !buffer.is_full());
+        assert_eq!(buffer.push(1), None);
+        assert_eq!(buffer.push(2), None);
+        assert!(!buffer.is_full());
+        assert_eq!(buffer.push(3), None);
+        assert!(buffer.is_full());
+        assert_eq!((buffer.len(), buffer.capacity()), (3, 3));
+        assert_eq!(contents(&buffer), [1, 2, 3]);
+    }
+
+    #[test]
+    fn wraps_around_oldest_first() {
+        let mut buffer = RingBuffer::new(3);
+        buffer.extend([1, 2, 3]);
+        assert_eq!(buffer.push(4), Some(1));
+        assert_eq!(buffer.push(5), Some(2));
+        assert_eq!(contents(&buffer), [3, 4, 5]);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.last(), Some(&5));
+
+        // many laps over the same slots keep the order
+        buffer.extend(6..=100);
+        assert_eq!(contents(&buffer), [98, 99, 100]);
+        assert_eq!(buffer.len(), buffer.capacity());
+        assert_eq!((&buffer).into_iter().count(), 3);
+    }
+
+    #[test]
+    fn latest_is_newest_first() {
+        let mut buffer = RingBuffer::new(4);
+        buffer.extend(1..=6);
+        assert_eq!(buffer.latest(2).copied().collect::<Vec<_>>(), [6, 5]);
+        assert_eq!(buffer.latest(0).count(), 0);
+        // capped at what is held
+        assert_eq!(buffer.latest(10).copied().collect::<Vec<_>>(), [6, 5, 4, 3]);
+        assert_eq!(buffer.latest(10).len(), 4);
+        assert_eq!(buffer.latest(3).rev().copied().collect::<Vec<_>>(), [4, 5, 6]);
+    }
+
+    #[test]
+    fn capacity_of_one_keeps_the_newest() {
+        let mut buffer = RingBuffer::new(1);
+        assert_eq!(buffer.push(1), None);
+        assert_eq!(buffer.push(2), Some(1));
+        assert_eq!(contents(&buffer), [2]);
+        *buffer.last_mut().unwrap() = 7;
+        assert_eq!(buffer.last(), Some(&7));
+    }
+
+    #[test]
+    fn clear_keeps_the_capacity() {
+        let mut buffer = RingBuffer::new(2);
+        buffer.extend([1, 2, 3]);
+        buffer.clear();
+        assert!(buffer.is_empty() && buffer.last().is_none());
+        assert_eq!(buffer.capacity(), 2);
+        assert_eq!(buffer.push(4), None);
+        assert_eq!(buffer.push(5), None);
+        assert_eq!(buffer.push(6), Some(4));
+        assert!(buffer.allocated_bytes() >= 2 * std::mem::size_of::<u32>());
+    }
+
+    #[test]
+    #[should_panic(expected = "ring buffer capacity must be at least 1")]
+    fn rejects_zero_capacity() {
+        RingBuffer::<u32>::new(0);
+    }
+}
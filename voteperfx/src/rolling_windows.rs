@@ -0,0 +1,148 @@
+use std::time::{Duration, Instant};
+
+use crate::ring_buffer::RingBuffer;
+
+/// length of one rolling window bucket
+pub const ROLLING_BUCKET: Duration = Duration::from_secs(60);
+
+/// buckets kept, enough for the longest window
+pub const ROLLING_BUCKETS: usize = 60;
+
+/// windows shown on the dashboard and served by the status api, shortest first
+pub const ROLLING_WINDOWS: [(&str, Duration); 3] = [
+    ("1m", Duration::from_secs(60)),
+    ("15m", Duration::from_secs(15 * 60)),
+    ("1h", Duration::from_secs(60 * 60)),
+];
+
+#[derive(Debug, Clone, Copy, Default)]
+struct WindowBucket {
+    tvc_earned: u64,
+    tvc_possible: u64,
+    latency_sum: u64,
+    votes: u64,
+}
+
+/// load-average style efficiency and latency over the last minute to hour
+///
+/// votes land in per-minute buckets that expire with time, not with vote
+/// arrival. a missed vote adds its possible credits with nothing earned, so
+/// votes that never land drag the short windows down. time without any votes,
+/// a stalled stream or a validator that stopped voting, adds nothing: such a
+/// window reports none rather than 0% or its last value, the coverage gap
+/// warning covers the validator side. the oldest bucket a window reaches into
+/// counts pro rata.
+#[derive(Debug, Clone)]
+pub struct RollingWindows {
+    buckets: RingBuffer<WindowBucket>,
+    current_started: Instant,
+}
+
+impl Default for RollingWindows {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RollingWindows {
+    pub fn new() -> Self {
+        let mut windows = Self {
+            buckets: RingBuffer::new(ROLLING_BUCKETS),
+            current_started: Instant::now(),
+        };
+        windows.buckets.push(WindowBucket::default());
+        windows
+    }
+
+    fn current(&mut self) -> Option<&mut WindowBucket> {
+        self.roll();
+        self.buckets.last_mut()
+    }
+
+    pub fn record_vote(&mut self, tvc_earned: u64, tvc_possible: u64, latency: u64) {
+        if let Some(current) = self.current() {
+            current.tvc_earned += tvc_earned;
+            current.tvc_possible += tvc_possible;
+            current.latency_sum += latency;
+            current.votes += 1;
+        }
+    }
+
+    /// credits a missed vote could have earned, nothing earned and no latency
+    pub fn record_missed(&mut self, tvc_possible: u64) {
+        if let Some(current) = self.current() {
+            current.tvc_possible += tvc_possible;
+        }
+    }
+
+    /// start an empty bucket for every minute elapsed, returns whether any started
+    pub fn roll(&mut self) -> bool {
+        let elapsed = self.current_started.elapsed();
+        if elapsed < ROLLING_BUCKET {
+            return false;
+        }
+
+        let elapsed_buckets = (elapsed.as_nanos() / ROLLING_BUCKET.as_nanos()) as u32;
+        for _ in 0..elapsed_buckets.min(ROLLING_BUCKETS as u32) {
+            self.buckets.push(WindowBucket::default());
+        }
+        self.current_started += ROLLING_BUCKET * elapsed_buckets;
+        true
+    }
+
+    /// bucket totals over the last `window`, the oldest bucket pro rata
+    fn totals_over(&self, window: Duration) -> WindowTotals {
+        let mut totals = WindowTotals::default();
+        // age of the newest and oldest edge of each bucket
+        let mut newest = Duration::ZERO;
+        let mut oldest = self.current_started.elapsed();
+
+        for bucket in self.buckets.iter().rev() {
+            if newest >= window {
+                break;
+            }
+            let fraction = if oldest <= window {
+                1.0
+            } else {
+                (window - newest).as_secs_f64() / (oldest - newest).as_secs_f64()
+            };
+            totals.tvc_earned += bucket.tvc_earned as f64 * fraction;
+            totals.tvc_possible += bucket.tvc_possible as f64 * fraction;
+            totals.latency_sum += bucket.latency_sum as f64 * fraction;
+            totals.votes += bucket.votes as f64 * fraction;
+
+            newest = oldest;
+            oldest += ROLLING_BUCKET;
+        }
+        totals
+    }
+
+    /// efficiency over the last `window`, at most an hour, none without votes in it
+    pub fn efficiency_over(&self, window: Duration) -> Option<f64> {
+        let totals = self.totals_over(window);
+        (totals.tvc_possible > 0.0).then(|| totals.tvc_earned / totals.tvc_possible * 100.0)
+    }
+
+    /// average latency of the votes confirmed in the last `window`
+    pub fn avg_latency_over(&self, window: Duration) -> Option<f64> {
+        let totals = self.totals_over(window);
+        (totals.votes > 0.0).then(|| totals.latency_sum / totals.votes)
+    }
+
+    /// buckets filled so far, at most `ROLLING_BUCKETS`
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.buckets.allocated_bytes()
+    }
+}
+
+#[derive(Debug, Default)]
+struct WindowTotals {
+    tvc_earned: f64,
+    tvc_possible: f64,
+    latency_sum: f64,
+    votes: f64,
+}
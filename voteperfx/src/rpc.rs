@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{Result, VoteMonitorError};
+use crate::performance::Slot;
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// getEpochInfo result
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochInfo {
+    pub epoch: u64,
+    pub absolute_slot: Slot,
+    /// slots elapsed in the epoch
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+}
+
+impl EpochInfo {
+    pub fn first_slot(&self) -> Slot {
+        self.absolute_slot.saturating_sub(self.slot_index)
+    }
+}
+
+/// minimal solana json-rpc client shared by the optional rpc_url features
+#[derive(Debug, Clone)]
+pub struct RpcClient {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        let http = reqwest::Client::builder().timeout(RPC_TIMEOUT).build()?;
+        Ok(Self { http, url: url.into() })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// call `method`, rate limiting and rpc errors surface as `VoteMonitorError::Rpc`
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response = self.http.post(&self.url).json(&request).send().await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(VoteMonitorError::Rpc("rate limited".to_string()));
+        }
+
+        let body: RpcResponse<T> = response.error_for_status()?.json().await?;
+        if let Some(error) = body.error {
+            return Err(VoteMonitorError::Rpc(format!("{} (code {})", error.message, error.code)));
+        }
+
+        body.result
+            .ok_or_else(|| VoteMonitorError::Rpc(format!("{} returned no result", method)))
+    }
+
+    /// current epoch at finalized commitment
+    pub async fn epoch_info(&self) -> Result<EpochInfo> {
+        self.call("getEpochInfo", json!([{ "commitment": "finalized" }])).await
+    }
+}
+
+/// longest wait after repeated rpc failures, in poll intervals
+const MAX_BACKOFF_INTERVALS: u32 = 8;
+
+/// pacing of the tasks polling `RpcClient`
+///
+/// the first poll runs right away, the next ones every `interval`. each
+/// failure in a row doubles the wait, up to `MAX_BACKOFF_INTERVALS`
+/// intervals, and a success restores it.
+#[derive(Debug, Clone)]
+pub struct PollBackoff {
+    interval: Duration,
+    failures: u32,
+    polled: bool,
+}
+
+impl PollBackoff {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, failures: 0, polled: false }
+    }
+
+    /// sleep until the next poll is due
+    pub async fn wait(&mut self) {
+        if self.polled {
+            tokio::time::sleep(self.delay()).await;
+        }
+        self.polled = true;
+    }
+
+    pub fn succeeded(&mut self) {
+        self.failures = 0;
+    }
+
+    /// record a failed poll, returns the failures in a row for the log
+    pub fn failed(&mut self) -> u32 {
+        self.failures = self.failures.saturating_add(1);
+        self.failures
+    }
+
+    fn delay(&self) -> Duration {
+        self.interval * 2u32.saturating_pow(self.failures).min(MAX_BACKOFF_INTERVALS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let mut backoff = PollBackoff::new(Duration::from_secs(10));
+        assert_eq!(backoff.delay(), Duration::from_secs(10));
+
+        let delays: Vec<u64> = (0..5).map(|_| {
+            backoff.failed();
+            backoff.delay().as_secs()
+        }).collect();
+        assert_eq!(delays, [20, 40, 80, 80, 80]);
+
+        backoff.succeeded();
+        assert_eq!(backoff.delay(), Duration::from_secs(10));
+    }
+}
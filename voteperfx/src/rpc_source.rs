@@ -0,0 +1,274 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use chrono::Local;
+use futures_util::future::BoxFuture;
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::vote::state::VoteState;
+use tokio::time::MissedTickBehavior;
+use yellowstone_grpc_proto::geyser::{SlotStatus, SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateSlot};
+
+use crate::config::{Config, TvcConfig, VoteSourceKind};
+use crate::error::{Result, VoteMonitorError};
+use crate::message::SystemEvent;
+use crate::performance::{calculate_tvc_credits_from_latency, ConfirmationKind, ConfirmationSource, ConfirmedVote, Slot};
+use crate::rpc::RpcClient;
+use crate::tasks::spawn_named;
+use crate::tower_root::TowerUpdate;
+use crate::vote_parse::VOTE_PROGRAM_ID;
+use crate::vote_source::{RunningSource, SourceContext, VoteSource};
+
+#[derive(Deserialize)]
+struct RpcContext {
+    slot: Slot,
+}
+
+#[derive(Deserialize)]
+struct AccountResponse {
+    context: RpcContext,
+    value: Option<EncodedAccount>,
+}
+
+#[derive(Deserialize)]
+struct EncodedAccount {
+    /// base64 data and its encoding
+    data: (String, String),
+}
+
+/// polls the vote account over json-rpc instead of subscribing to geyser
+/// (`source = "rpc"`)
+///
+/// each finalized read goes into the account and slot channels like a grpc
+/// account update, the block task then confirms the votes that are new in
+/// the tower with the latency recorded on-chain, see `VoteStateVotes`.
+/// without the transactions there are no signatures, no confirmation times
+/// and no missed votes, a vote that drops out of the tower between two
+/// reads is never seen.
+#[derive(Debug, Clone)]
+pub struct RpcVoteSource {
+    rpc: RpcClient,
+    vote_account: String,
+    vote_account_key: Pubkey,
+    poll_interval: Duration,
+}
+
+impl RpcVoteSource {
+    pub fn new(config: &Config) -> Result<Self> {
+        let rpc_url = config
+            .rpc_url
+            .clone()
+            .ok_or_else(|| VoteMonitorError::Config("source = \"rpc\" needs rpc_url".to_string()))?;
+        let vote_account_key = Pubkey::from_str(&config.vote_account)
+            .map_err(|e| VoteMonitorError::Config(format!("vote_account is not a valid pubkey: {}", e)))?;
+        Ok(Self {
+            rpc: RpcClient::new(rpc_url)?,
+            vote_account: config.vote_account.clone(),
+            vote_account_key,
+            poll_interval: Duration::from_secs(config.rpc_source.poll_secs),
+        })
+    }
+
+    /// the vote account at finalized commitment and the slot it was read at
+    async fn read_account(&self) -> Result<(Slot, Vec<u8>)> {
+        let response: AccountResponse = self
+            .rpc
+            .call("getAccountInfo", json!([self.vote_account, { "encoding": "base64", "commitment": "finalized" }]))
+            .await?;
+        let Some(account) = response.value else {
+            return Err(VoteMonitorError::Config(format!(
+                "vote account {} does not exist on {}", self.vote_account, self.rpc.url()
+            )));
+        };
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(account.data.0)
+            .map_err(|e| VoteMonitorError::Rpc(format!("getAccountInfo returned undecodable data: {}", e)))?;
+        Ok((response.context.slot, data))
+    }
+
+    /// the read as the grpc stream would deliver it
+    fn account_update(&self, slot: Slot, data: Vec<u8>) -> SubscribeUpdateAccount {
+        SubscribeUpdateAccount {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: self.vote_account_key.to_bytes().to_vec(),
+                owner: VOTE_PROGRAM_ID.to_vec(),
+                data,
+                ..Default::default()
+            }),
+            slot,
+            is_startup: false,
+        }
+    }
+}
+
+impl VoteSource for RpcVoteSource {
+    fn kind(&self) -> VoteSourceKind {
+        VoteSourceKind::Rpc
+    }
+
+    /// the first read has to succeed, later failures are retried on the next
+    /// poll and reported like a stream disconnect
+    fn start(&self, context: SourceContext) -> BoxFuture<'static, Result<RunningSource>> {
+        let source = self.clone();
+        Box::pin(async move {
+            let SourceContext { senders, health, events, mut stop, .. } = context;
+            let endpoint = source.rpc.url().to_string();
+            if let Err(e) = source.read_account().await {
+                events.publish(SystemEvent::StreamDisconnected { reason: e.to_string() });
+                return Err(e);
+            }
+            health.mark_connected();
+            warn!(
+                "no grpc stream: polling vote account {} over {} every {}s, latencies are approximate",
+                source.vote_account, endpoint, source.poll_interval.as_secs()
+            );
+            events.publish(SystemEvent::StreamConnected { endpoint: endpoint.clone() });
+
+            let stream = spawn_named("rpc_poll", async move {
+                // every channel stays open until the source ends, the
+                // transaction task would finish on a closed one
+                let senders = senders;
+                let mut interval = tokio::time::interval(source.poll_interval);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    tokio::select! {
+                        _ = stop.changed() => break,
+                        _ = interval.tick() => {}
+                    }
+                    let (slot, data) = match source.read_account().await {
+                        Ok(read) => read,
+                        Err(e) => {
+                            if health.is_connected() {
+                                warn!("cannot read the vote account over {}, retrying: {}", endpoint, e);
+                                health.mark_disconnected();
+                                events.publish(SystemEvent::StreamDisconnected { reason: e.to_string() });
+                            }
+                            continue;
+                        }
+                    };
+                    if !health.is_connected() {
+                        info!("reading the vote account over {} again", endpoint);
+                        health.mark_connected();
+                        events.publish(SystemEvent::StreamConnected { endpoint: endpoint.clone() });
+                    }
+                    health.record_update();
+
+                    let slot_update = SubscribeUpdateSlot {
+                        slot,
+                        status: SlotStatus::SlotFinalized as i32,
+                        ..Default::default()
+                    };
+                    // closed channels: the pipeline is shutting down
+                    if senders.slots.send(slot_update).await.is_err()
+                        || senders.accounts.send(source.account_update(slot, data)).await.is_err()
+                    {
+                        break;
+                    }
+                }
+                health.mark_disconnected();
+                info!("rpc polling task completed");
+            });
+            Ok(RunningSource { stream, confirmed_blocks: None, background: Vec::new() })
+        })
+    }
+}
+
+/// turns successive vote states of the monitored account into confirmed votes
+#[derive(Debug, Clone, Default)]
+pub struct VoteStateVotes {
+    /// newest voted slot seen in the tower
+    newest: Option<Slot>,
+}
+
+impl VoteStateVotes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// votes in the tower newer than any seen before, read at `slot`
+    ///
+    /// the first state only sets the baseline. the latency is the one the
+    /// vote program recorded when the vote landed, votes from before it
+    /// recorded latencies (0) are left out.
+    pub fn new_votes(&mut self, state: &VoteState, slot: Slot, tvc: &TvcConfig) -> Vec<ConfirmedVote> {
+        let Some(newest) = state.votes.back().map(|vote| vote.slot()) else {
+            return Vec::new();
+        };
+        let Some(previous) = self.newest.replace(self.newest.map_or(newest, |seen| seen.max(newest))) else {
+            return Vec::new();
+        };
+
+        state
+            .votes
+            .iter()
+            .filter(|vote| vote.slot() > previous && vote.latency > 0)
+            .map(|vote| {
+                let latency = u64::from(vote.latency);
+                ConfirmedVote {
+                    signature: Arc::default(),
+                    voted_slot: vote.slot(),
+                    finalized_slot: slot,
+                    latency,
+                    tvc_credits: calculate_tvc_credits_from_latency(latency, tvc),
+                    timestamp: Local::now(),
+                    kind: ConfirmationKind::TowerLatency,
+                    source: ConfirmationSource::VoteState,
+                    confirmation_ms: None,
+                    block_time_delta_ms: None,
+                    landed_slot: Some(vote.slot() + latency),
+                    confirmed_latency: None,
+                    finalization_delay_ms: None,
+                    loss_cause: None,
+                    blockhash: None,
+                    parent_slot: None,
+                    block_height: None,
+                    before_voter_change: false,
+                }
+            })
+            .collect()
+    }
+}
+
+/// root and newest voted slot of the tower, none before the first root
+pub fn vote_state_tower(state: &VoteState) -> Option<TowerUpdate> {
+    Some(TowerUpdate { root: state.root_slot?, latest_voted: state.last_voted_slot()? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::vote::state::{LandedVote, Lockout};
+
+    /// a tower of (slot, latency) votes
+    fn vote_state(votes: &[(Slot, u8)]) -> VoteState {
+        VoteState {
+            votes: votes.iter().map(|&(slot, latency)| LandedVote { latency, lockout: Lockout::new(slot) }).collect(),
+            ..VoteState::default()
+        }
+    }
+
+    #[test]
+    fn tower_latency_kind_from_the_vote_state() {
+        let tvc = TvcConfig::default();
+        let mut votes = VoteStateVotes::new();
+        // the first state is the baseline
+        assert!(votes.new_votes(&vote_state(&[(100, 1), (101, 2)]), 103, &tvc).is_empty());
+
+        // 103 predates recorded latencies
+        let state = vote_state(&[(100, 1), (101, 2), (102, 4), (103, 0)]);
+        let confirmed = votes.new_votes(&state, 108, &tvc);
+        assert_eq!(confirmed.len(), 1);
+        let vote = &confirmed[0];
+        assert_eq!((vote.kind, vote.source), (ConfirmationKind::TowerLatency, ConfirmationSource::VoteState));
+        assert_eq!((vote.voted_slot, vote.latency, vote.landed_slot), (102, 4, Some(106)));
+        assert_eq!(vote.tvc_credits, calculate_tvc_credits_from_latency(4, &tvc));
+        assert!(vote.kind.is_estimated() && vote.kind.is_counted());
+
+        // nothing newer, nothing again
+        assert!(votes.new_votes(&state, 109, &tvc).is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::network::Explorer;
+
+/// characters of a signature in `SignatureDisplay::Short`
+pub const SHORT_SIGNATURE_LEN: usize = 8;
+
+/// how vote signatures are shown on the dashboard and in simple mode lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureDisplay {
+    /// the whole signature on the dashboard, an explorer link in simple mode
+    #[default]
+    Full,
+    /// the first 8 characters, no link
+    Short,
+    /// an explorer link cut to the terminal width with an ellipsis
+    Url,
+}
+
+impl SignatureDisplay {
+    /// `signature` as shown in a column or line of `width` cells, none leaves it uncut
+    pub fn format(self, signature: &str, explorer: &Explorer, width: Option<usize>, ellipsis: &str) -> String {
+        // votes read from the vote account (source = "rpc") have none
+        if signature.is_empty() {
+            return "-".to_string();
+        }
+        match self {
+            Self::Full => signature.to_string(),
+            Self::Short => signature.chars().take(SHORT_SIGNATURE_LEN).collect(),
+            Self::Url => {
+                let url = explorer.tx_url(signature);
+                match width {
+                    Some(width) => truncate_visible(&url, width, ellipsis),
+                    None => url,
+                }
+            }
+        }
+    }
+
+    /// what `c` copies from the dashboard: the link in url mode, the whole signature otherwise
+    pub fn copy_text(self, signature: &str, explorer: &Explorer) -> String {
+        match self {
+            Self::Url => explorer.tx_url(signature),
+            Self::Full | Self::Short => signature.to_string(),
+        }
+    }
+}
+
+/// terminal cells `text` takes, ansi escape sequences take none
+///
+/// counts a cell per char, the signatures, urls and log lines this measures
+/// have no wide characters.
+pub fn visible_width(text: &str) -> usize {
+    segments(text).filter(|segment| !segment.is_escape).map(|segment| segment.text.chars().count()).sum()
+}
+
+/// cut `text` to `width` visible cells, ending in `ellipsis` when cut
+///
+/// escape sequences are kept, also those after the cut, so a color reset at
+/// the end of the line still applies.
+pub fn truncate_visible(text: &str, width: usize, ellipsis: &str) -> String {
+    if visible_width(text) <= width {
+        return text.to_string();
+    }
+    // narrower than the ellipsis itself, it gets cut as well
+    let ellipsis: String = ellipsis.chars().take(width).collect();
+    let mut budget = width - visible_width(&ellipsis);
+    let mut cut = false;
+    let mut truncated = String::with_capacity(text.len());
+    for segment in segments(text) {
+        if segment.is_escape {
+            truncated.push_str(segment.text);
+            continue;
+        }
+        for ch in segment.text.chars() {
+            if budget > 0 {
+                truncated.push(ch);
+                budget -= 1;
+            } else if !cut {
+                truncated.push_str(&ellipsis);
+                cut = true;
+            }
+        }
+    }
+    truncated
+}
+
+/// OSC 52 sequence that puts `text` on the system clipboard, terminals
+/// without support ignore it
+pub fn osc52_copy(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64::engine::general_purpose::STANDARD.encode(text))
+}
+
+struct Segment<'a> {
+    text: &'a str,
+    is_escape: bool,
+}
+
+/// `text` split into runs of visible text and single escape sequences
+///
+/// csi sequences end at their final byte, osc sequences at BEL or ST.
+fn segments(text: &str) -> impl Iterator<Item = Segment<'_>> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (len, is_escape) = match rest.find('\x1b') {
+            Some(0) => (escape_len(rest), true),
+            Some(start) => (start, false),
+            None => (rest.len(), false),
+        };
+        let (segment, tail) = rest.split_at(len);
+        rest = tail;
+        Some(Segment { text: segment, is_escape })
+    })
+}
+
+/// bytes of the escape sequence at the start of `text`
+fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|byte| (0x40..=0x7e).contains(byte))
+            .map_or(bytes.len(), |end| end + 3),
+        Some(b']') => {
+            let end = (2..bytes.len()).find(|&i| bytes[i] == 0x07 || (bytes[i] == b'\\' && bytes[i - 1] == 0x1b));
+            end.map_or(bytes.len(), |end| end + 1)
+        }
+        Some(byte) if byte.is_ascii() => 2,
+        _ => 1,
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::epoch::EpochStats;
+use crate::error::Result;
+use crate::loss_cause::LostCredits;
+use crate::performance::{ConfirmedVote, LatencyDistribution, PerformanceStats, SeverityHistogram, Slot, TvcHistogram};
+
+/// bumped whenever the persisted layout changes incompatibly
+pub const STATE_VERSION: u32 = 2;
+
+/// persisted session counters and windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    /// account the session monitored, none in files saved before it was recorded
+    #[serde(default)]
+    pub vote_account: Option<String>,
+    pub session_started_at: DateTime<Local>,
+
+    pub total_transactions: u64,
+    pub total_tvc_earned: u64,
+    pub total_tvc_possible: u64,
+    pub optimal_votes: u64,
+    pub good_votes: u64,
+    pub poor_votes: u64,
+    pub low_latency_votes: u64,
+    pub missed_votes: u64,
+    #[serde(default)]
+    pub failed_votes: u64,
+    pub total_latency_sum: u64,
+    pub pre_voter_change_votes: u64,
+    pub current_finalized_slot: Slot,
+
+    /// oldest first
+    pub epochs: Vec<EpochStats>,
+    pub recent_confirmed_votes: Vec<ConfirmedVote>,
+    pub session_poor_votes: Vec<ConfirmedVote>,
+    #[serde(default)]
+    pub severity: SeverityHistogram,
+    #[serde(default)]
+    pub latency_distribution: LatencyDistribution,
+    #[serde(default)]
+    pub tvc_histogram: TvcHistogram,
+    #[serde(default)]
+    pub lost_credits: LostCredits,
+    /// latencies in the rolling average, oldest first
+    pub avg_latency_window: Vec<u64>,
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+impl SessionState {
+    pub fn capture(stats: &PerformanceStats, vote_account: &str) -> Self {
+        let mut epochs: Vec<EpochStats> = stats.epochs.epochs().cloned().collect();
+        epochs.reverse();
+
+        Self {
+            version: STATE_VERSION,
+            saved_at: Utc::now(),
+            vote_account: Some(vote_account.to_string()),
+            session_started_at: stats.session_started_at,
+            total_transactions: stats.total_transactions(),
+            total_tvc_earned: stats.total_tvc_earned(),
+            total_tvc_possible: stats.total_tvc_possible(),
+            optimal_votes: stats.optimal_votes(),
+            good_votes: stats.good_votes(),
+            poor_votes: stats.poor_votes(),
+            low_latency_votes: stats.low_latency_votes(),
+            missed_votes: stats.missed_votes(),
+            failed_votes: stats.failed_votes(),
+            total_latency_sum: stats.total_latency_sum.load(Ordering::Relaxed),
+            pre_voter_change_votes: stats.pre_voter_change_votes.load(Ordering::Relaxed),
+            current_finalized_slot: stats.current_finalized_slot(),
+            epochs,
+            recent_confirmed_votes: stats.recent_confirmed_votes.iter().cloned().collect(),
+            session_poor_votes: stats.session_poor_votes.iter().cloned().collect(),
+            severity: stats.severity_histogram(),
+            latency_distribution: stats.latency_distribution(),
+            tvc_histogram: stats.tvc_histogram(),
+            lost_credits: stats.lost_credits(),
+            avg_latency_window: stats.avg_latency_window.iter().map(|&(_, latency)| latency).collect(),
+        }
+    }
+
+    /// merge into freshly created stats, keeping the original session start
+    pub fn apply(self, stats: &mut PerformanceStats) {
+        let session_age = (Local::now() - self.session_started_at).to_std().unwrap_or_default();
+        stats.session_start = Instant::now().checked_sub(session_age).unwrap_or(stats.process_start);
+        stats.session_started_at = self.session_started_at;
+
+        stats.total_transactions.store(self.total_transactions, Ordering::Relaxed);
+        stats.total_tvc_earned.store(self.total_tvc_earned, Ordering::Relaxed);
+        stats.total_tvc_possible.store(self.total_tvc_possible, Ordering::Relaxed);
+        stats.optimal_votes.store(self.optimal_votes, Ordering::Relaxed);
+        stats.good_votes.store(self.good_votes, Ordering::Relaxed);
+        stats.poor_votes.store(self.poor_votes, Ordering::Relaxed);
+        stats.low_latency_votes.store(self.low_latency_votes, Ordering::Relaxed);
+        stats.missed_votes.store(self.missed_votes, Ordering::Relaxed);
+        stats.failed_votes.store(self.failed_votes, Ordering::Relaxed);
+        stats.total_latency_sum.store(self.total_latency_sum, Ordering::Relaxed);
+        stats.pre_voter_change_votes.store(self.pre_voter_change_votes, Ordering::Relaxed);
+        stats.current_finalized_slot.store(self.current_finalized_slot, Ordering::Relaxed);
+
+        stats.epochs.restore(self.epochs);
+        stats.last_confirmed_vote = self.recent_confirmed_votes.last().cloned();
+        // the configured windows may have shrunk since the state was saved,
+        // extending keeps the newest
+        stats.recent_confirmed_votes.clear();
+        stats.recent_confirmed_votes.extend(self.recent_confirmed_votes);
+        let skip = self.session_poor_votes.len().saturating_sub(stats.poor_votes_window);
+        stats.session_poor_votes = self.session_poor_votes.into_iter().skip(skip).collect();
+        stats.severity = self.severity;
+        stats.latency_distribution = self.latency_distribution;
+        stats.tvc_histogram = self.tvc_histogram;
+        stats.lost_credits = self.lost_credits;
+        // entry times are not persisted, the latencies count as confirmed
+        // when the state was saved and expire one window after that
+        stats.avg_latency_window.clear();
+        let saved_age = (Utc::now() - self.saved_at).to_std().unwrap_or_default();
+        let saved = Instant::now().checked_sub(saved_age).unwrap_or(stats.process_start);
+        for latency in self.avg_latency_window {
+            stats.avg_latency_window.push_at(saved, latency);
+        }
+        stats.avg_latency_window.expire(Instant::now());
+    }
+
+    /// write atomically through a temporary file next to `path`
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_vec(self)?).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+
+        Ok(())
+    }
+
+    /// load a state file, ignoring missing, stale, corrupt or incompatible files
+    /// and one saved while monitoring another account than `vote_account`
+    pub async fn load(path: &Path, max_age: Duration, vote_account: &str) -> Option<Self> {
+        let content = match tokio::fs::read(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                log::warn!("failed to read state file {} ({}), starting a new session", path.display(), e);
+                return None;
+            }
+        };
+
+        match serde_json::from_slice::<VersionProbe>(&content) {
+            Ok(probe) if probe.version == STATE_VERSION => {}
+            Ok(probe) => {
+                log::warn!(
+                    "ignoring state file {}: version {} is not supported (expected {})",
+                    path.display(), probe.version, STATE_VERSION
+                );
+                return None;
+            }
+            Err(e) => {
+                log::warn!("ignoring corrupt state file {}: {}", path.display(), e);
+                return None;
+            }
+        }
+
+        let state: Self = match serde_json::from_slice(&content) {
+            Ok(state) => state,
+            Err(e) => {
+                log::warn!("ignoring corrupt state file {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        if let Some(saved_for) = state.vote_account.as_deref().filter(|&saved_for| saved_for != vote_account) {
+            log::info!(
+                "state file {} is for vote account {}, starting a new session for {}",
+                path.display(), saved_for, vote_account
+            );
+            return None;
+        }
+
+        let age = (Utc::now() - state.saved_at).to_std().unwrap_or_default();
+        if age > max_age {
+            log::info!(
+                "state file {} is {}s old (max {}s), starting a new session",
+                path.display(), age.as_secs(), max_age.as_secs()
+            );
+            return None;
+        }
+
+        Some(state)
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use log::{error, info, warn};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::epoch::EpochStats;
+use crate::error::Result;
+use crate::pipeline::Pipeline;
+use crate::status_api::StatsSnapshot;
+use crate::vote_tracker::VoteTrackerStats;
+
+/// stats snapshots go here when snapshot_dir is unset
+pub const DEFAULT_SNAPSHOT_DIR: &str = "./snapshots";
+
+/// what SIGUSR1 and SIGUSR2 ask of the stats task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsCommand {
+    /// write a `StatsDump` to the snapshot directory
+    Dump,
+    /// zero the session stats, see `PerformanceStats::reset`
+    Reset,
+}
+
+/// everything `/status` serves plus the whole recent votes window, every
+/// epoch of the session and the vote tracker counters
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsDump {
+    pub taken_at: DateTime<Local>,
+    #[serde(flatten)]
+    pub stats: StatsSnapshot,
+    /// newest first
+    pub epochs: Vec<EpochStats>,
+    /// none when the tracker did not answer
+    pub tracker: Option<VoteTrackerStats>,
+}
+
+impl StatsDump {
+    pub async fn capture(pipeline: &Pipeline) -> Self {
+        let tracker = match pipeline.vote_tracker.get_stats().await {
+            Ok(tracker) => Some(tracker),
+            Err(e) => {
+                warn!("vote tracker stats unavailable for the snapshot: {}", e);
+                None
+            }
+        };
+        let stats = pipeline.stats.read().await;
+        let mut snapshot = StatsSnapshot::capture(&stats, &pipeline.vote_account);
+        snapshot.recent_votes = stats.recent_confirmed_votes.iter().rev().cloned().collect();
+        Self {
+            taken_at: Local::now(),
+            stats: snapshot,
+            epochs: stats.epochs.epochs().cloned().collect(),
+            tracker,
+        }
+    }
+
+    /// `stats_20250301_142501.json`
+    pub fn file_name(&self) -> String {
+        format!("stats_{}.json", self.taken_at.format("%Y%m%d_%H%M%S"))
+    }
+}
+
+/// write the dump as pretty json through a temporary file, returns its path
+pub async fn write_stats_dump(dump: &StatsDump, dir: &Path) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let path = dir.join(dump.file_name());
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, serde_json::to_vec_pretty(dump)?).await?;
+    tokio::fs::rename(&tmp_path, &path).await?;
+
+    Ok(path)
+}
+
+/// run `StatsCommand`s until every sender is gone
+pub fn spawn_stats_commands(mut commands: mpsc::Receiver<StatsCommand>, pipeline: Pipeline, dir: PathBuf) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(command) = commands.recv().await {
+            match command {
+                StatsCommand::Dump => {
+                    let dump = StatsDump::capture(&pipeline).await;
+                    match write_stats_dump(&dump, &dir).await {
+                        Ok(path) => info!("stats snapshot written to {}", path.display()),
+                        Err(e) => error!("failed to write stats snapshot to {}: {}", dir.display(), e),
+                    }
+                }
+                StatsCommand::Reset => {
+                    info!("stats reset requested by SIGUSR2");
+                    let keep_epochs = pipeline.config.current().stats.reset_keeps_epochs;
+                    pipeline.stats.write().await.reset(keep_epochs);
+                }
+            }
+        }
+    })
+}
+
+/// SIGUSR1 sends `Dump` and SIGUSR2 `Reset` to the stats task
+///
+/// the signal handler only queues the command, a signal arriving while the
+/// previous one is still queued is dropped.
+#[cfg(unix)]
+pub fn spawn_signal_listener(commands: mpsc::Sender<StatsCommand>) -> JoinHandle<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tokio::spawn(async move {
+        let (mut usr1, mut usr2) = match (signal(SignalKind::user_defined1()), signal(SignalKind::user_defined2())) {
+            (Ok(usr1), Ok(usr2)) => (usr1, usr2),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("cannot listen for SIGUSR1/SIGUSR2, stats snapshots and resets on signal disabled: {}", e);
+                return;
+            }
+        };
+        loop {
+            let command = tokio::select! {
+                Some(()) = usr1.recv() => StatsCommand::Dump,
+                Some(()) = usr2.recv() => StatsCommand::Reset,
+                else => break,
+            };
+            if commands.try_send(command).is_err() {
+                warn!("{:?} already queued, ignoring the signal", command);
+            }
+        }
+    })
+}
@@ -0,0 +1,843 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::{RawQuery, State};
+use axum::http::{header, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::Html;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Local};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::account_info::AccountInfo;
+use crate::account_switch::{AccountSwitch, SwitchRefused};
+use crate::channel_metrics::PipelineSnapshot;
+use crate::cluster_tip::ClusterTip;
+use crate::coverage::{CoverageSummary, VoteGap};
+use crate::credits_check::CreditsDrift;
+use crate::dashboard::{compose_panels, DashboardRenderer, Panel};
+use crate::endpoints::EndpointStatus;
+use crate::epoch::EpochStats;
+use crate::events::EventBus;
+use crate::filter_drift::FilterDrift;
+use crate::leader_schedule::{BlockProduction, LeaderPoorVotes, LeaderSlotVotes};
+use crate::loss_cause::LostCredits;
+use crate::message::SystemEvent;
+use crate::network::Explorer;
+use crate::performance::{
+    ConfirmedVote, EventWriterSnapshot, PerformanceStats, RecentWindowSummary, SeverityHistogram, Slot, StatsReset, TvcHistogram,
+    LATENCY_BUCKETS,
+};
+use crate::process_metrics::ProcessMetrics;
+use crate::prometheus::{MetricsRegistry, TEXT_FORMAT};
+use crate::rolling_windows::ROLLING_WINDOWS;
+use crate::stream_health::{StreamHealth, StreamStaleness};
+use crate::theme::{DashboardTheme, Theme};
+use crate::tower_root::TowerRootSnapshot;
+use crate::vote_tracker::FailedVote;
+use crate::streaks::StreakSnapshot;
+use crate::votes_sent::VotesSentSnapshot;
+
+/// confirmed votes included in /status
+pub const STATUS_RECENT_VOTES: usize = 30;
+
+/// poor performance events included in /status
+pub const STATUS_POOR_EVENTS: usize = 20;
+
+/// leaders included in /status when leader attribution is active
+pub const STATUS_WORST_LEADERS: usize = 10;
+
+/// confirmed votes /events replays to a new client
+pub const DEFAULT_EVENT_REPLAY_VOTES: usize = 100;
+
+/// minutes back the /events replay reaches
+pub const DEFAULT_EVENT_REPLAY_MINUTES: u64 = 10;
+
+/// columns and rows /dashboard is laid out for, tall enough for every panel
+pub const STATUS_DASHBOARD_SIZE: (u16, u16) = (160, 80);
+
+/// the browser dashboard served on /, it polls /status
+const WEB_DASHBOARD: &str = include_str!("../assets/dashboard.html");
+
+/// stands for the explorer link template in `WEB_DASHBOARD`
+const WEB_EXPLORER_PLACEHOLDER: &str = "__EXPLORER_TX_URL__";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusTotals {
+    pub votes: u64,
+    pub missed_votes: u64,
+    pub failed_votes: u64,
+    pub tvc_earned: u64,
+    pub tvc_possible: u64,
+    pub missed_credits: u64,
+    pub low_latency_votes: u64,
+    /// poor performance events the writer had no room for
+    pub dropped_performance_events: u64,
+    /// vote transactions for another vote account, a loosely filtered stream
+    pub foreign_votes_ignored: u64,
+    /// voted slots confirmed again by another signature, not counted as votes
+    pub duplicate_confirmations: u64,
+    /// confirmed votes per second this session
+    pub vote_rate: f64,
+    /// votes per second when every slot of the network is voted on
+    pub expected_vote_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusEfficiency {
+    pub session_pct: f64,
+    pub window_pct: f64,
+    pub status: &'static str,
+    /// efficiency per time bucket, oldest first, the last one still filling
+    pub trend: Vec<StatusTrendBucket>,
+    /// 1m, 15m and 1h windows, see `RollingWindows`
+    pub windows: Vec<StatusWindow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusWindow {
+    pub window: &'static str,
+    /// none for a window without votes
+    pub efficiency_pct: Option<f64>,
+    pub avg_latency: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusTrendBucket {
+    pub start: DateTime<Local>,
+    pub votes: u64,
+    /// none for a bucket without votes
+    pub efficiency_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusLatency {
+    pub session_avg: f64,
+    /// average over the votes confirmed in the last `window_secs`
+    pub window_avg: f64,
+    pub window_secs: u64,
+    /// percentiles over the recent votes, estimated latencies only with
+    /// stats.percentiles_include_estimates
+    pub p50: Option<u64>,
+    pub p90: Option<u64>,
+    pub p99: Option<u64>,
+    pub low_latency_pct: f64,
+    /// session votes at latency 0, 1, 2, 3 and 4+ slots
+    pub distribution: [u64; LATENCY_BUCKETS],
+    /// votes at exactly the grace limit, full credits one slot from losing one
+    pub grace_dependent_pct: f64,
+    /// wall-clock milliseconds from transaction to confirmation over the recent votes
+    pub confirmation_ms_avg: Option<f64>,
+    pub confirmation_ms_p95: Option<u64>,
+    /// session average over every vote, session_avg leaves our own leader
+    /// slots out with stats.exclude_leader_slots
+    pub overall_avg: f64,
+    /// outside and in our own leader slots, none without the validator identity
+    pub non_leader_slot_avg: Option<f64>,
+    pub leader_slots: Option<LeaderSlotVotes>,
+}
+
+/// the monitored account against the cluster's epoch-to-date efficiency
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusCluster {
+    pub epoch: u64,
+    pub validators: usize,
+    pub median_pct: f64,
+    pub top_decile_pct: f64,
+    /// percent of stake with a lower efficiency than this session
+    pub percentile: f64,
+    pub fetched_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusBreakdown {
+    pub optimal: u64,
+    pub good: u64,
+    pub poor: u64,
+}
+
+/// everything the dashboard shows, served on /status and by `Monitor::stats`
+///
+/// captured under the stats lock and serialized after it is released
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsSnapshot {
+    pub vote_account: String,
+    pub authorized_voter: Option<String>,
+    /// identity, commission, stake and delinquency, only with rpc_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountInfo>,
+    pub session_started_at: DateTime<Local>,
+    pub session_uptime_secs: u64,
+    pub process_uptime_secs: u64,
+    pub current_finalized_slot: Slot,
+    pub confirmation_source: &'static str,
+    /// commitment the vote blocks are read at
+    pub commitment: &'static str,
+    /// mainnet, testnet, devnet or custom
+    pub network: &'static str,
+    pub totals: StatusTotals,
+    pub efficiency: StatusEfficiency,
+    pub latency: StatusLatency,
+    pub breakdown: StatusBreakdown,
+    /// every vote below max credits by severity, `poor_events` lists the newest only
+    pub severity: SeverityHistogram,
+    /// session votes at 0 to 16 tvc, index = credits
+    pub tvc_histogram: TvcHistogram,
+    /// votes and credits lost below max credits per heuristic cause
+    pub lost_credits: LostCredits,
+    /// over the configured recent votes window
+    pub recent_window: RecentWindowSummary,
+    pub current_epoch: Option<EpochStats>,
+    pub coverage: CoverageSummary,
+    pub coverage_pct: f64,
+    pub vote_gap: Option<VoteGap>,
+    /// vote transactions of the monitored account per minute over the last
+    /// hour, counted before confirmation
+    pub votes_sent: VotesSentSnapshot,
+    /// consecutive max credit votes, estimated ones left out
+    pub optimal_streak: StreakSnapshot,
+    pub cluster_tip: ClusterTip,
+    pub finalized_lag: Option<u64>,
+    /// tower root progression, once a tower update landed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tower_root: Option<TowerRootSnapshot>,
+    /// transaction and block channel backpressure, once the stream started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<PipelineSnapshot>,
+    /// age of the last grpc update of each kind, once the stream started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamStaleness>,
+    /// grpc update rates off their baseline, empty while the filters look intact
+    pub filter_drift: Vec<FilterDrift>,
+    /// active grpc endpoint and failovers, once the stream started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<EndpointStatus>,
+    pub credits_drift: Option<CreditsDrift>,
+    /// rss, cpu, tokio tasks and allocator usage of the monitor, once sampled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process: Option<ProcessMetrics>,
+    /// leaders with the most poor votes, only with rpc_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worst_leaders: Option<Vec<LeaderPoorVotes>>,
+    /// retry queue of the poor performance event writer, when it runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_writer: Option<EventWriterSnapshot>,
+    /// produced and skipped leader slots, only with rpc_url and a known identity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_production: Option<BlockProduction>,
+    /// stake-weighted cluster comparison, only with rpc_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<StatusCluster>,
+    /// newest first
+    pub recent_votes: Vec<ConfirmedVote>,
+    /// newest first
+    pub poor_events: Vec<ConfirmedVote>,
+    /// newest first
+    pub failed_votes: Vec<FailedVote>,
+}
+
+impl StatsSnapshot {
+    pub fn capture(stats: &PerformanceStats, vote_account: &str) -> Self {
+        let (status, _) = stats.get_performance_status();
+        let coverage = stats.coverage.summary();
+
+        Self {
+            vote_account: vote_account.to_string(),
+            authorized_voter: stats.authorized_voter.current.map(|voter| voter.to_string()),
+            account: stats.account_info.clone(),
+            session_started_at: stats.session_started_at,
+            session_uptime_secs: stats.session_start.elapsed().as_secs(),
+            process_uptime_secs: stats.process_start.elapsed().as_secs(),
+            current_finalized_slot: stats.current_finalized_slot(),
+            confirmation_source: stats.confirmation_source.as_str(),
+            commitment: stats.commitment.as_str(),
+            network: stats.network.as_str(),
+            totals: StatusTotals {
+                votes: stats.total_transactions(),
+                missed_votes: stats.missed_votes(),
+                failed_votes: stats.failed_votes(),
+                tvc_earned: stats.total_tvc_earned(),
+                tvc_possible: stats.total_tvc_possible(),
+                missed_credits: stats.calculate_missed_credits(),
+                low_latency_votes: stats.low_latency_votes(),
+                dropped_performance_events: stats.dropped_performance_events(),
+                foreign_votes_ignored: stats.foreign_votes_ignored(),
+                duplicate_confirmations: stats.duplicate_confirmations(),
+                vote_rate: stats.calculate_vote_rate(),
+                expected_vote_rate: stats.expected_vote_rate(),
+            },
+            efficiency: StatusEfficiency {
+                session_pct: stats.calculate_efficiency(),
+                window_pct: stats.calculate_window_efficiency(),
+                status,
+                trend: stats.efficiency_trend
+                    .buckets()
+                    .map(|bucket| StatusTrendBucket {
+                        start: bucket.start,
+                        votes: bucket.votes,
+                        efficiency_pct: bucket.efficiency(),
+                    })
+                    .collect(),
+                windows: ROLLING_WINDOWS
+                    .iter()
+                    .map(|&(window, duration)| StatusWindow {
+                        window,
+                        efficiency_pct: stats.efficiency_over(duration),
+                        avg_latency: stats.avg_latency_over(duration),
+                    })
+                    .collect(),
+            },
+            latency: StatusLatency {
+                session_avg: stats.calculate_session_avg_latency(),
+                window_avg: stats.calculate_avg_latency(),
+                window_secs: stats.latency_window().as_secs(),
+                p50: stats.recent_latency_percentile(50.0),
+                p90: stats.recent_latency_percentile(90.0),
+                p99: stats.recent_latency_percentile(99.0),
+                low_latency_pct: stats.calculate_low_latency_percentage(),
+                distribution: stats.latency_distribution().counts,
+                grace_dependent_pct: stats.latency_distribution().grace_dependent_pct(),
+                confirmation_ms_avg: stats.recent_confirmation_ms_avg(),
+                confirmation_ms_p95: stats.recent_confirmation_ms_percentile(95.0),
+                overall_avg: stats.overall_session_avg_latency(),
+                non_leader_slot_avg: stats.non_leader_slot_avg_latency(),
+                leader_slots: stats.leader_slot_votes(),
+            },
+            breakdown: StatusBreakdown {
+                optimal: stats.optimal_votes(),
+                good: stats.good_votes(),
+                poor: stats.poor_votes(),
+            },
+            severity: stats.severity_histogram(),
+            tvc_histogram: stats.tvc_histogram(),
+            lost_credits: stats.lost_credits(),
+            recent_window: stats.recent_window_summary(),
+            current_epoch: stats.epochs.current().cloned(),
+            coverage,
+            coverage_pct: coverage.percentage(),
+            vote_gap: stats.coverage.current_gap(),
+            votes_sent: stats.votes_sent.snapshot(),
+            optimal_streak: stats.streaks.snapshot(),
+            cluster_tip: stats.cluster_tip,
+            finalized_lag: stats.cluster_tip.finalized_lag(),
+            tower_root: stats.tower_root.snapshot(),
+            pipeline: stats.pipeline.as_ref().map(|metrics| metrics.snapshot()),
+            stream: stats.stream_health.as_ref().map(|health| health.staleness()),
+            filter_drift: stats.filter_drift.clone(),
+            endpoint: stats.endpoints.as_ref().map(|endpoints| endpoints.status()),
+            credits_drift: stats.credits_drift.clone(),
+            process: stats.process.clone(),
+            worst_leaders: stats.leader_attribution
+                .as_ref()
+                .map(|attribution| attribution.worst_leaders(STATUS_WORST_LEADERS)),
+            block_production: stats.block_production(),
+            event_writer: stats.event_writer.as_ref().map(|metrics| metrics.snapshot()),
+            cluster: stats.cluster.as_ref().map(|cluster| StatusCluster {
+                epoch: cluster.epoch,
+                validators: cluster.validators,
+                median_pct: cluster.median(),
+                top_decile_pct: cluster.top_decile(),
+                percentile: cluster.rank_of(stats.calculate_efficiency()),
+                fetched_at: cluster.fetched_at,
+            }),
+            recent_votes: stats.recent_confirmed_votes.latest(STATUS_RECENT_VOTES).cloned().collect(),
+            poor_events: stats.session_poor_votes.iter().rev().take(STATUS_POOR_EVENTS).cloned().collect(),
+            failed_votes: stats.recent_failed_votes.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    stats: Arc<RwLock<PerformanceStats>>,
+    vote_account: Arc<str>,
+    health: Arc<StreamHealth>,
+    max_stale: Duration,
+    web_dashboard: Bytes,
+    /// default of POST /reset's keep_epochs
+    reset_keeps_epochs: bool,
+    /// served on /metrics with prometheus.scrape
+    metrics: Option<Arc<MetricsRegistry>>,
+    /// served on POST /switch-account with stats.switch_account_over_http
+    switch_account: Option<AccountSwitch>,
+    /// live events of GET /events
+    events: EventBus,
+    event_replay: EventReplay,
+    /// ends the /events streams, connections outlive the aborted server task
+    shutdown: watch::Receiver<bool>,
+}
+
+/// `WEB_DASHBOARD` linking signatures with `explorer`
+fn web_dashboard(explorer: &Explorer) -> Bytes {
+    // a json string is a javascript string, `<` is escaped so a template
+    // cannot close the script element
+    let template = serde_json::Value::from(explorer.tx_url_template()).to_string().replace('<', "\\u003c");
+    Bytes::from(WEB_DASHBOARD.replace(WEB_EXPLORER_PLACEHOLDER, &template))
+}
+
+async fn index(State(state): State<ApiState>) -> Html<Bytes> {
+    Html(state.web_dashboard)
+}
+
+async fn status(State(state): State<ApiState>) -> Json<StatsSnapshot> {
+    let snapshot = StatsSnapshot::capture(&*state.stats.read().await, &state.vote_account);
+    Json(snapshot)
+}
+
+/// the dashboard panels as laid out on a `STATUS_DASHBOARD_SIZE` terminal
+async fn dashboard_panels(state: &ApiState) -> Vec<Panel> {
+    let stats = state.stats.read().await;
+    DashboardRenderer::build_dashboard_panels(
+        &stats,
+        &state.vote_account,
+        &stats.memory_usage(),
+        &Theme::new(DashboardTheme::NoColor),
+        STATUS_DASHBOARD_SIZE,
+    )
+}
+
+async fn dashboard(State(state): State<ApiState>) -> Json<Vec<Panel>> {
+    Json(dashboard_panels(&state).await)
+}
+
+async fn dashboard_text(State(state): State<ApiState>) -> String {
+    compose_panels(&dashboard_panels(&state).await)
+}
+
+/// one json message of GET /events
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum FeedMessage {
+    /// `replay` for the recent votes sent on connect, false once live
+    VoteConfirmed { replay: bool, vote: Arc<ConfirmedVote> },
+    /// the replay is complete, live events follow
+    ReplayEnd { votes: usize },
+    VoteMissed { signature: Arc<String>, reason: String },
+    VoteFailed { vote: Arc<FailedVote> },
+    /// live events dropped because this client read too slowly
+    Lagged { skipped: u64 },
+}
+
+/// the newest `replay.votes` recent votes inside `replay.window`, oldest first
+fn replay_votes(stats: &PerformanceStats, replay: EventReplay) -> Vec<Arc<ConfirmedVote>> {
+    let since = chrono::Duration::from_std(replay.window).ok().and_then(|window| Local::now().checked_sub_signed(window));
+    let mut votes: Vec<Arc<ConfirmedVote>> = stats.recent_confirmed_votes
+        .latest(replay.votes)
+        .take_while(|vote| since.map_or(true, |since| vote.timestamp >= since))
+        .map(|vote| Arc::new(vote.clone()))
+        .collect();
+    votes.reverse();
+    votes
+}
+
+/// the replay, its end marker, then live events until the bus closes.
+/// votes already replayed are not sent again live
+fn event_feed(replay: Vec<Arc<ConfirmedVote>>, live: broadcast::Receiver<SystemEvent>) -> impl Stream<Item = FeedMessage> {
+    let replayed: HashSet<Arc<String>> = replay.iter().map(|vote| vote.signature.clone()).collect();
+    let end = FeedMessage::ReplayEnd { votes: replay.len() };
+    let replay = replay.into_iter().map(|vote| FeedMessage::VoteConfirmed { replay: true, vote });
+
+    let live = stream::unfold((live, replayed), |(mut live, replayed)| async move {
+        let message = loop {
+            match live.recv().await {
+                Ok(SystemEvent::VoteConfirmed(vote)) if !replayed.contains(&vote.signature) => {
+                    break FeedMessage::VoteConfirmed { replay: false, vote };
+                }
+                Ok(SystemEvent::VoteMissed { signature, reason }) => break FeedMessage::VoteMissed { signature, reason },
+                Ok(SystemEvent::VoteFailed(vote)) => break FeedMessage::VoteFailed { vote },
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => break FeedMessage::Lagged { skipped },
+                Err(RecvError::Closed) => return None,
+            }
+        };
+        Some((message, (live, replayed)))
+    });
+    stream::iter(replay.chain(std::iter::once(end))).chain(live)
+}
+
+/// GET /events, server-sent events with one json `FeedMessage` each
+///
+/// a client first gets the recent votes, bounded by `EventReplay` and
+/// flagged `"replay": true`, then `replay_end` and the live vote events.
+/// the replay is copied out under a short read lock and each client reads
+/// its own copy; live events come from the event bus, which never waits on
+/// a slow client, it gets `lagged` with the events it missed instead. the
+/// stream ends with the monitor's shutdown
+async fn events(State(state): State<ApiState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // subscribed before the copy, a vote confirmed in between is not lost
+    let live = state.events.subscribe();
+    let replay = replay_votes(&*state.stats.read().await, state.event_replay);
+    let mut shutdown = state.shutdown.clone();
+    // a dropped sender ends the stream as well
+    let stopped = async move {
+        let _ = shutdown.wait_for(|stop| *stop).await;
+    };
+    let feed = event_feed(replay, live).take_until(stopped).map(|message| {
+        Ok(Event::default()
+            .json_data(&message)
+            .unwrap_or_else(|e| Event::default().comment(format!("unserializable event: {}", e))))
+    });
+    Sse::new(feed).keep_alive(KeepAlive::default())
+}
+
+/// POST /reset, `?keep_epochs=true|false` overrides stats.reset_keeps_epochs.
+/// returns the numbers before the reset
+async fn reset_stats(
+    State(state): State<ApiState>,
+    RawQuery(query): RawQuery,
+) -> Result<Json<StatsReset>, (StatusCode, String)> {
+    let mut keep_epochs = state.reset_keeps_epochs;
+    let pairs = query.iter().flat_map(|query| query.split('&')).filter_map(|pair| pair.split_once('='));
+    for (key, value) in pairs {
+        match (key, value) {
+            ("keep_epochs", "true") => keep_epochs = true,
+            ("keep_epochs", "false") => keep_epochs = false,
+            ("keep_epochs", _) => return Err((StatusCode::BAD_REQUEST, "keep_epochs must be true or false\n".to_string())),
+            _ => {}
+        }
+    }
+    log::info!("stats reset requested over http");
+    Ok(Json(state.stats.write().await.reset(keep_epochs)))
+}
+
+/// POST /switch-account?vote_account=<pubkey>, the monitor stops and the old
+/// account's session is archived, see `AccountSwitch`
+async fn switch_account(State(state): State<ApiState>, RawQuery(query): RawQuery) -> (StatusCode, String) {
+    let Some(ref switch) = state.switch_account else {
+        return (StatusCode::NOT_FOUND, String::new());
+    };
+    let vote_account = query
+        .iter()
+        .flat_map(|query| query.split('&'))
+        .filter_map(|pair| pair.split_once('='))
+        .find_map(|(key, value)| (key == "vote_account").then_some(value));
+    let Some(vote_account) = vote_account else {
+        return (StatusCode::BAD_REQUEST, "vote_account is required\n".to_string());
+    };
+
+    log::info!("vote account switch to {} requested over http", vote_account);
+    match switch.request(vote_account) {
+        Ok(()) => (StatusCode::ACCEPTED, format!("switching from {} to {}\n", state.vote_account, vote_account)),
+        Err(refused @ SwitchRefused::Pending(_)) => (StatusCode::CONFLICT, format!("{}\n", refused)),
+        Err(refused) => (StatusCode::BAD_REQUEST, format!("{}\n", refused)),
+    }
+}
+
+/// GET /metrics in the prometheus text format
+async fn metrics(State(state): State<ApiState>) -> ([(header::HeaderName, &'static str); 1], String) {
+    let body = match state.metrics {
+        Some(ref registry) => registry.gather().await,
+        None => String::new(),
+    };
+    ([(header::CONTENT_TYPE, TEXT_FORMAT)], body)
+}
+
+async fn healthz(State(state): State<ApiState>) -> (StatusCode, String) {
+    match state.health.since_last_update() {
+        Some(age) if age <= state.max_stale => (StatusCode::OK, "ok\n".to_string()),
+        Some(age) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("stale: no grpc update for {}s\n", age.as_secs()),
+        ),
+        None => (StatusCode::SERVICE_UNAVAILABLE, "no grpc updates yet\n".to_string()),
+    }
+}
+
+/// what the status api serves, see `spawn_status_api`
+pub struct StatusApiConfig {
+    pub stats: Arc<RwLock<PerformanceStats>>,
+    pub vote_account: String,
+    pub health: Arc<StreamHealth>,
+    /// /healthz fails once the grpc stream is silent for longer
+    pub max_stale: Duration,
+    /// signatures of the browser dashboard link here
+    pub explorer: Explorer,
+    /// serve POST /reset, keeping the per-epoch breakdown by default when true
+    pub reset: Option<bool>,
+    /// serve GET /metrics from this registry
+    pub metrics: Option<Arc<MetricsRegistry>>,
+    /// serve POST /switch-account
+    pub switch_account: Option<AccountSwitch>,
+    /// streamed on GET /events after the replay
+    pub events: EventBus,
+    pub event_replay: EventReplay,
+    /// the monitor's shutdown signal, GET /events streams end when it is sent
+    pub shutdown: watch::Receiver<bool>,
+}
+
+/// the recent votes GET /events sends a client when it connects
+#[derive(Debug, Clone, Copy)]
+pub struct EventReplay {
+    /// newest confirmed votes replayed, 0 replays nothing
+    pub votes: usize,
+    /// votes confirmed longer ago are left out
+    pub window: Duration,
+}
+
+/// serve GET /, GET /status, GET /dashboard, GET /dashboard.txt, GET /events
+/// and GET /healthz on `addr`
+///
+/// / is a self-contained browser dashboard drawn from /status, with
+/// signatures linked on the configured explorer. /dashboard returns the
+/// dashboard panels as json, /dashboard.txt as plain text. /events streams
+/// vote events, see `events`. /healthz returns 200 only while the grpc
+/// stream delivered an update within `max_stale`.
+/// POST /reset, POST /switch-account and GET /metrics are only served when
+/// configured.
+pub fn spawn_status_api(addr: SocketAddr, config: StatusApiConfig) -> JoinHandle<()> {
+    let app = router(config);
+
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("failed to bind status api on {}: {}", addr, e);
+                return;
+            }
+        };
+
+        log::info!("status api listening on http://{}", addr);
+        if let Err(e) = axum::serve(listener, app).await {
+            log::error!("status api stopped: {}", e);
+        }
+    })
+}
+
+fn router(config: StatusApiConfig) -> Router {
+    let StatusApiConfig {
+        stats, vote_account, health, max_stale, explorer, reset, metrics, switch_account, events, event_replay, shutdown,
+    } = config;
+    let state = ApiState {
+        stats,
+        vote_account: Arc::from(vote_account),
+        health,
+        max_stale,
+        web_dashboard: web_dashboard(&explorer),
+        reset_keeps_epochs: reset.unwrap_or_default(),
+        metrics: metrics.clone(),
+        switch_account: switch_account.clone(),
+        events,
+        event_replay,
+        shutdown,
+    };
+    let mut app = Router::new()
+        .route("/", get(index))
+        .route("/status", get(status))
+        .route("/dashboard", get(dashboard))
+        .route("/dashboard.txt", get(dashboard_text))
+        .route("/events", get(self::events))
+        .route("/healthz", get(healthz));
+    if reset.is_some() {
+        app = app.route("/reset", post(reset_stats));
+    }
+    if metrics.is_some() {
+        app = app.route("/metrics", get(self::metrics));
+    }
+    if switch_account.is_some() {
+        app = app.route("/switch-account", post(self::switch_account));
+    }
+    app.with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{Network, NetworkProfile};
+    use crate::test_support::{make_confirmed_vote, signature_base58};
+
+    fn config(stats: Arc<RwLock<PerformanceStats>>) -> StatusApiConfig {
+        StatusApiConfig {
+            stats,
+            vote_account: "Vote111111111111111111111111111111111111111".to_string(),
+            health: Arc::new(StreamHealth::new()),
+            max_stale: Duration::from_secs(30),
+            explorer: NetworkProfile::preset(Network::Mainnet).explorer,
+            reset: None,
+            metrics: None,
+            switch_account: None,
+            events: EventBus::default(),
+            event_replay: EventReplay { votes: DEFAULT_EVENT_REPLAY_VOTES, window: Duration::from_secs(600) },
+            // the sender is dropped, GET /events tests set their own
+            shutdown: watch::channel(false).1,
+        }
+    }
+
+    async fn serve(config: StatusApiConfig) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(config);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn metrics_are_only_served_when_configured() {
+        let stats = Arc::new(RwLock::new(PerformanceStats::new()));
+        stats.write().await.add_confirmed_vote(make_confirmed_vote(1, 100, 1));
+
+        let plain = serve(config(stats.clone())).await;
+        let response = reqwest::get(format!("{}/metrics", plain)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let mut scraped = config(stats.clone());
+        scraped.metrics = Some(Arc::new(MetricsRegistry::new(stats)));
+        let base = serve(scraped).await;
+        let response = reqwest::get(format!("{}/metrics", base)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], TEXT_FORMAT);
+        let body = response.text().await.unwrap();
+        assert!(body.contains("\nvoteperfx_votes_total 1\n"), "{}", body);
+        assert!(body.contains("voteperfx_vote_latency_slots_count 1"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn reset_is_only_served_when_configured() {
+        let stats = Arc::new(RwLock::new(PerformanceStats::new()));
+        stats.write().await.add_confirmed_vote(make_confirmed_vote(1, 100, 1));
+        let http = reqwest::Client::new();
+
+        let base = serve(config(stats.clone())).await;
+        let response = http.post(format!("{}/reset", base)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(stats.read().await.total_transactions(), 1);
+
+        let mut resettable = config(stats.clone());
+        resettable.reset = Some(false);
+        let base = serve(resettable).await;
+        let response = http.post(format!("{}/reset", base)).send().await.unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(stats.read().await.total_transactions(), 0);
+    }
+
+    /// the json of every `data:` line of a server-sent events body
+    fn sse_data(body: &str) -> Vec<serde_json::Value> {
+        body.lines().filter_map(|line| line.strip_prefix("data:")).map(|data| serde_json::from_str(data.trim()).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn events_replay_recent_votes_before_live_ones() {
+        let stats = Arc::new(RwLock::new(PerformanceStats::new()));
+        {
+            let mut stats = stats.write().await;
+            let mut old = make_confirmed_vote(1, 100, 1);
+            old.timestamp = Local::now() - chrono::Duration::minutes(20);
+            stats.add_confirmed_vote(old);
+            for seed in 2..=4 {
+                stats.add_confirmed_vote(make_confirmed_vote(seed, 100 + seed, 1));
+            }
+        }
+        let mut config = config(stats);
+        config.event_replay = EventReplay { votes: 2, window: Duration::from_secs(600) };
+        let (_stop, shutdown) = watch::channel(false);
+        config.shutdown = shutdown;
+        let events = config.events.clone();
+        let base = serve(config).await;
+
+        // subscribed once the response starts
+        let mut response = reqwest::get(format!("{}/events", base)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+        events.publish(SystemEvent::VoteConfirmed(Arc::new(make_confirmed_vote(4, 104, 1))));
+        events.publish(SystemEvent::VoteConfirmed(Arc::new(make_confirmed_vote(5, 105, 1))));
+
+        let mut body = String::new();
+        while !body.contains(&signature_base58(5)) {
+            let chunk = tokio::time::timeout(Duration::from_secs(5), response.chunk()).await.unwrap().unwrap().unwrap();
+            body.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        let messages = sse_data(&body);
+        let summary: Vec<(&str, Option<bool>, Option<u64>)> = messages
+            .iter()
+            .map(|message| (message["event"].as_str().unwrap(), message["replay"].as_bool(), message["vote"]["voted_slot"].as_u64()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("vote_confirmed", Some(true), Some(103)),
+                ("vote_confirmed", Some(true), Some(104)),
+                ("replay_end", None, None),
+                ("vote_confirmed", Some(false), Some(105)),
+            ]
+        );
+        assert_eq!(messages[2]["votes"], 2);
+    }
+
+    #[tokio::test]
+    async fn event_feed_reports_lagged_events() {
+        let bus = EventBus::new(2);
+        let live = bus.subscribe();
+        for seed in 1..=4 {
+            bus.publish(SystemEvent::VoteConfirmed(Arc::new(make_confirmed_vote(seed, 100 + seed, 1))));
+        }
+        drop(bus);
+
+        let messages: Vec<FeedMessage> = event_feed(Vec::new(), live).collect().await;
+        let summary: Vec<String> = messages
+            .iter()
+            .map(|message| match message {
+                FeedMessage::VoteConfirmed { replay, vote } => format!("vote {} {}", vote.voted_slot, replay),
+                FeedMessage::ReplayEnd { votes } => format!("end {}", votes),
+                FeedMessage::Lagged { skipped } => format!("lagged {}", skipped),
+                other => format!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(summary, ["end 0", "lagged 2", "vote 103 false", "vote 104 false"]);
+    }
+
+    #[tokio::test]
+    async fn healthz_fails_before_the_first_update() {
+        let stats = Arc::new(RwLock::new(PerformanceStats::new()));
+        let config = config(stats);
+        let health = config.health.clone();
+        let base = serve(config).await;
+
+        let response = reqwest::get(format!("{}/healthz", base)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        health.record_update();
+        let response = reqwest::get(format!("{}/healthz", base)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn switch_account_stops_the_monitor_once() {
+        let stats = Arc::new(RwLock::new(PerformanceStats::new()));
+        let http = reqwest::Client::new();
+        let base = serve(config(stats.clone())).await;
+        let response = http.post(format!("{}/switch-account", base)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let (target, target_rx) = tokio::sync::watch::channel(None);
+        let (stop, stop_rx) = tokio::sync::watch::channel(false);
+        let mut switchable = config(stats);
+        switchable.switch_account = Some(AccountSwitch::new(&switchable.vote_account, target, stop));
+        let base = serve(switchable).await;
+        let url = format!("{}/switch-account", base);
+
+        let response = http.post(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = http.post(&url).query(&[("vote_account", "nope")]).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(!*stop_rx.borrow());
+
+        let next = solana_sdk::pubkey::Pubkey::new_unique().to_string();
+        let response = http.post(&url).query(&[("vote_account", next.as_str())]).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(target_rx.borrow().as_deref(), Some(next.as_str()));
+        assert!(*stop_rx.borrow());
+
+        let other = solana_sdk::pubkey::Pubkey::new_unique().to_string();
+        let response = http.post(&url).query(&[("vote_account", other.as_str())]).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(target_rx.borrow().as_deref(), Some(next.as_str()));
+    }
+}
@@ -0,0 +1,537 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Row};
+use tokio::io::AsyncReadExt;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+use crate::config::StorageConfig;
+use crate::epoch::EpochStats;
+use crate::error::{Result, VoteMonitorError};
+use crate::loss_cause::LossCause;
+use crate::performance::{ConfirmationKind, ConfirmedVote, PoorPerformanceEvent};
+use crate::vote_log::{parse_source, source_name};
+
+/// first bytes of every sqlite database file
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// how long a write waits on a lock held by an external connection
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// queued votes and epoch summaries before new ones are dropped
+pub const STORAGE_CHANNEL_CAPACITY: usize = 1000;
+
+/// schema changes in order, `schema_version` records how many are applied.
+/// append new ones, never edit a released migration.
+const MIGRATIONS: &[&str] = &[
+    // 1: timestamps are rfc3339 utc with milliseconds, so they compare as text
+    "CREATE TABLE confirmed_votes (
+        id INTEGER PRIMARY KEY,
+        timestamp TEXT NOT NULL,
+        voted_slot INTEGER NOT NULL,
+        finalized_slot INTEGER NOT NULL,
+        latency INTEGER NOT NULL,
+        tvc_credits INTEGER NOT NULL,
+        signature TEXT NOT NULL,
+        estimated INTEGER NOT NULL,
+        source TEXT NOT NULL,
+        landed_slot INTEGER,
+        confirmation_ms INTEGER,
+        UNIQUE (signature, voted_slot)
+    );
+    CREATE INDEX confirmed_votes_timestamp ON confirmed_votes (timestamp);
+    CREATE INDEX confirmed_votes_voted_slot ON confirmed_votes (voted_slot);
+
+    CREATE TABLE poor_events (
+        id INTEGER PRIMARY KEY,
+        timestamp TEXT NOT NULL,
+        landed_slot INTEGER NOT NULL,
+        voted_slot INTEGER NOT NULL,
+        latency INTEGER NOT NULL,
+        tvc_credits INTEGER NOT NULL,
+        transaction_signature TEXT NOT NULL,
+        vote_account TEXT NOT NULL,
+        total_tvc_credits INTEGER NOT NULL,
+        total_voted_slots INTEGER NOT NULL,
+        tvc_multiplier REAL NOT NULL,
+        leader TEXT,
+        UNIQUE (transaction_signature, voted_slot)
+    );
+    CREATE INDEX poor_events_timestamp ON poor_events (timestamp);
+
+    CREATE TABLE epoch_summaries (
+        epoch INTEGER PRIMARY KEY,
+        first_slot INTEGER NOT NULL,
+        last_slot INTEGER NOT NULL,
+        votes INTEGER NOT NULL,
+        tvc_earned INTEGER NOT NULL,
+        tvc_possible INTEGER NOT NULL,
+        latency_sum INTEGER NOT NULL,
+        optimal_votes INTEGER NOT NULL,
+        good_votes INTEGER NOT NULL,
+        poor_votes INTEGER NOT NULL
+    );",
+    // 2: confirmed figures with commitment "confirmed" or "both"
+    "ALTER TABLE confirmed_votes ADD COLUMN confirmed_latency INTEGER;
+    ALTER TABLE confirmed_votes ADD COLUMN finalization_delay_ms INTEGER;",
+    // 3: heuristic cause of votes below max credits
+    "ALTER TABLE confirmed_votes ADD COLUMN loss_cause TEXT;
+    ALTER TABLE poor_events ADD COLUMN loss_cause TEXT;",
+    // 4: how the latency was established, see `ConfirmationKind`
+    "ALTER TABLE confirmed_votes ADD COLUMN kind TEXT;",
+    // 5: cast by the previous authorized voter
+    "ALTER TABLE confirmed_votes ADD COLUMN before_voter_change INTEGER NOT NULL DEFAULT 0;",
+];
+
+/// schema version written by this build
+pub const SCHEMA_VERSION: usize = MIGRATIONS.len();
+
+/// a vote or epoch summary for the database writer, poor performance events
+/// arrive on their own channel
+#[derive(Debug, Clone)]
+pub enum StorageRecord {
+    Vote(ConfirmedVote),
+    EpochSummary(EpochStats),
+}
+
+/// rows written in one transaction
+#[derive(Debug, Default)]
+pub struct StorageBatch {
+    pub votes: Vec<ConfirmedVote>,
+    pub events: Vec<PoorPerformanceEvent>,
+    pub epochs: Vec<EpochStats>,
+}
+
+impl StorageBatch {
+    pub fn len(&self) -> usize {
+        self.votes.len() + self.events.len() + self.epochs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// whether `path` starts with the sqlite file header
+pub async fn is_sqlite_file(path: &Path) -> bool {
+    let mut header = [0u8; SQLITE_HEADER.len()];
+    match tokio::fs::File::open(path).await {
+        Ok(mut file) => file.read_exact(&mut header).await.is_ok() && &header == SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+/// run blocking database work off the async runtime
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| VoteMonitorError::Channel(format!("database task failed: {}", e)))?
+}
+
+/// applied migrations, 0 for a database without a schema_version table
+fn schema_version(conn: &Connection) -> Result<usize> {
+    let has_table = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'", [], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if !has_table {
+        return Ok(0);
+    }
+    Ok(conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?)
+}
+
+fn newer_schema_error(path: &Path, version: usize) -> VoteMonitorError {
+    VoteMonitorError::Config(format!(
+        "{} has schema version {}, this build supports up to {}",
+        path.display(), version, SCHEMA_VERSION
+    ))
+}
+
+/// apply the migrations past the database's version, each in its own transaction
+fn migrate(conn: &mut Connection, path: &Path) -> Result<()> {
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)")?;
+    let version = schema_version(conn)?;
+    if version > SCHEMA_VERSION {
+        return Err(newer_schema_error(path, version));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.execute("INSERT INTO schema_version (version) VALUES (?1)", [index + 1])?;
+        tx.commit()?;
+        log::info!("{} migrated to schema version {}", path.display(), index + 1);
+    }
+    Ok(())
+}
+
+/// open or create the database in wal mode and bring the schema up to date
+fn open_for_writing(path: &Path) -> Result<Connection> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // external readers don't block the writer and the writer doesn't block them
+    let journal_mode: String = conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+    if !journal_mode.eq_ignore_ascii_case("wal") {
+        log::warn!("{} is in {} journal mode, readers may block writes", path.display(), journal_mode);
+    }
+    // durable across process crashes in wal mode, fewer fsyncs than full
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+
+    migrate(&mut conn, path)?;
+    Ok(conn)
+}
+
+/// utc with milliseconds and a z suffix, sorts and compares as text
+fn format_timestamp<Tz: chrono::TimeZone>(timestamp: &DateTime<Tz>) -> String {
+    timestamp.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+/// insert a batch in one transaction, rows already stored are skipped
+fn insert_batch(conn: &mut Connection, batch: &StorageBatch) -> Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut insert_vote = tx.prepare_cached(
+            "INSERT OR IGNORE INTO confirmed_votes (
+                timestamp, voted_slot, finalized_slot, latency, tvc_credits, signature,
+                estimated, source, landed_slot, confirmation_ms, confirmed_latency, finalization_delay_ms,
+                loss_cause, kind, before_voter_change
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        )?;
+        for vote in &batch.votes {
+            insert_vote.execute(params![
+                format_timestamp(&vote.timestamp), vote.voted_slot, vote.finalized_slot, vote.latency,
+                vote.tvc_credits, vote.signature.as_str(), vote.kind.is_estimated(), source_name(vote.source),
+                vote.landed_slot, vote.confirmation_ms, vote.confirmed_latency, vote.finalization_delay_ms,
+                vote.loss_cause.map(LossCause::name), vote.kind.name(), vote.before_voter_change,
+            ])?;
+        }
+
+        let mut insert_event = tx.prepare_cached(
+            "INSERT OR IGNORE INTO poor_events (
+                timestamp, landed_slot, voted_slot, latency, tvc_credits, transaction_signature,
+                vote_account, total_tvc_credits, total_voted_slots, tvc_multiplier, leader, loss_cause
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        )?;
+        for event in &batch.events {
+            insert_event.execute(params![
+                format_timestamp(&event.timestamp), event.landed_slot, event.voted_slot, event.latency,
+                event.tvc_credits, event.transaction_signature.as_str(), event.vote_account, event.total_tvc_credits,
+                event.total_voted_slots, event.tvc_multiplier, event.leader, event.loss_cause.map(LossCause::name),
+            ])?;
+        }
+
+        // a late vote can update an epoch summary that was already written
+        let mut upsert_epoch = tx.prepare_cached(
+            "INSERT OR REPLACE INTO epoch_summaries (
+                epoch, first_slot, last_slot, votes, tvc_earned, tvc_possible,
+                latency_sum, optimal_votes, good_votes, poor_votes
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        for epoch in &batch.epochs {
+            upsert_epoch.execute(params![
+                epoch.epoch, epoch.first_slot, epoch.last_slot, epoch.votes, epoch.tvc_earned,
+                epoch.tvc_possible, epoch.latency_sum, epoch.optimal_votes, epoch.good_votes, epoch.poor_votes,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// the database written by the monitor, opened in wal mode and migrated
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    path: PathBuf,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// open or create the database, fails on a schema newer than this build
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let conn = {
+            let path = path.clone();
+            blocking(move || open_for_writing(&path)).await?
+        };
+        Ok(Self { path, conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// write the batch in one transaction, handing it back on failure so it
+    /// can be retried
+    pub async fn write_batch(&self, batch: StorageBatch) -> std::result::Result<(), (VoteMonitorError, StorageBatch)> {
+        let conn = self.conn.clone();
+        let written = tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap_or_else(PoisonError::into_inner);
+            let result = insert_batch(&mut conn, &batch);
+            (result, batch)
+        })
+        .await;
+
+        match written {
+            Ok((Ok(()), _)) => Ok(()),
+            Ok((Err(e), batch)) => Err((e, batch)),
+            Err(e) => Err((
+                VoteMonitorError::Channel(format!("database task failed: {}", e)),
+                StorageBatch::default(),
+            )),
+        }
+    }
+}
+
+/// batched writer for the sqlite backend, the database counterpart of
+/// `VoteLogWriter` and `BatchedEventWriter`
+pub struct SqliteWriter {
+    store: SqliteStore,
+    buffer: StorageBatch,
+    batch_size: usize,
+    flush_interval: Duration,
+    last_flush: Instant,
+}
+
+impl SqliteWriter {
+    pub fn new(store: SqliteStore, batch_size: usize, flush_interval_secs: u64) -> Self {
+        Self {
+            store,
+            buffer: StorageBatch::default(),
+            batch_size,
+            flush_interval: Duration::from_secs(flush_interval_secs),
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub async fn add_vote(&mut self, vote: ConfirmedVote) -> Result<()> {
+        self.buffer.votes.push(vote);
+        self.flush_if_due().await
+    }
+
+    pub async fn add_event(&mut self, event: PoorPerformanceEvent) -> Result<()> {
+        self.buffer.events.push(event);
+        self.flush_if_due().await
+    }
+
+    pub async fn add_epoch_summary(&mut self, summary: EpochStats) -> Result<()> {
+        self.buffer.epochs.push(summary);
+        self.flush_if_due().await
+    }
+
+    async fn flush_if_due(&mut self) -> Result<()> {
+        if self.buffer.len() >= self.batch_size || self.last_flush.elapsed() >= self.flush_interval {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// failed batches stay buffered, inserts skip rows that made it anyway
+    pub async fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let batch = std::mem::take(&mut self.buffer);
+        if let Err((e, batch)) = self.store.write_batch(batch).await {
+            self.buffer = batch;
+            return Err(e);
+        }
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+/// spawn the sqlite writer task
+///
+/// votes and epoch summaries come from the pipeline, poor performance events
+/// from the stats. flushes on batch size, on interval, and once more when a
+/// shutdown signal arrives or the pipeline's sender is dropped.
+pub fn spawn_sqlite_writer(
+    store: SqliteStore,
+    config: &StorageConfig,
+    mut records: mpsc::Receiver<StorageRecord>,
+    mut events: mpsc::Receiver<PoorPerformanceEvent>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    let flush_every = Duration::from_secs(config.flush_interval_secs.max(1));
+    let mut writer = SqliteWriter::new(store, config.batch_size, config.flush_interval_secs);
+
+    tokio::spawn(async move {
+        let mut flush_interval = tokio::time::interval(flush_every);
+        // no event sender when performance logging is disabled
+        let mut events_open = true;
+
+        loop {
+            let result = tokio::select! {
+                record = records.recv() => match record {
+                    Some(StorageRecord::Vote(vote)) => writer.add_vote(vote).await,
+                    Some(StorageRecord::EpochSummary(summary)) => writer.add_epoch_summary(summary).await,
+                    None => break,
+                },
+                event = events.recv(), if events_open => match event {
+                    Some(event) => writer.add_event(event).await,
+                    None => {
+                        events_open = false;
+                        Ok(())
+                    }
+                },
+                _ = flush_interval.tick() => writer.flush().await,
+                _ = shutdown.changed() => {
+                    // drain whatever is already queued before the final flush
+                    while let Ok(record) = records.try_recv() {
+                        match record {
+                            StorageRecord::Vote(vote) => writer.buffer.votes.push(vote),
+                            StorageRecord::EpochSummary(summary) => writer.buffer.epochs.push(summary),
+                        }
+                    }
+                    while let Ok(event) = events.try_recv() {
+                        writer.buffer.events.push(event);
+                    }
+                    break;
+                }
+            };
+            if let Err(e) = result {
+                log::error!("error writing to {}: {}", writer.store.path().display(), e);
+            }
+        }
+
+        if let Err(e) = writer.flush().await {
+            log::error!("error flushing {} on shutdown: {}", writer.store.path().display(), e);
+        }
+        log::info!("sqlite writer stopped");
+    })
+}
+
+/// votes and poor performance events read back from a database
+#[derive(Debug, Default)]
+pub struct StoredVotes {
+    pub votes: Vec<ConfirmedVote>,
+    pub events: Vec<PoorPerformanceEvent>,
+    /// vote rows with an unknown confirmation source
+    pub malformed: u64,
+}
+
+fn vote_from_row(row: &Row<'_>) -> rusqlite::Result<Option<ConfirmedVote>> {
+    let timestamp: String = row.get(0)?;
+    let source: String = row.get(7)?;
+    let Some(source) = parse_source(&source) else {
+        return Ok(None);
+    };
+    // rows from before the kind column, or an unknown kind from a newer build
+    let kind: Option<String> = row.get(13)?;
+    let kind = match kind.as_deref().and_then(ConfirmationKind::parse) {
+        Some(kind) => kind,
+        None => ConfirmationKind::infer(row.get(6)?, source),
+    };
+    Ok(Some(ConfirmedVote {
+        timestamp: parse_timestamp(&timestamp).with_timezone(&Local),
+        voted_slot: row.get(1)?,
+        finalized_slot: row.get(2)?,
+        latency: row.get(3)?,
+        tvc_credits: row.get(4)?,
+        signature: Arc::new(row.get(5)?),
+        kind,
+        source,
+        landed_slot: row.get(8)?,
+        confirmation_ms: row.get(9)?,
+        block_time_delta_ms: None,
+        confirmed_latency: row.get(10)?,
+        finalization_delay_ms: row.get(11)?,
+        loss_cause: loss_cause_from_row(row, 12)?,
+        // the block context is not stored
+        blockhash: None,
+        parent_slot: None,
+        block_height: None,
+        before_voter_change: row.get(14)?,
+    }))
+}
+
+/// an unknown name, from a newer build, reads as none
+fn loss_cause_from_row(row: &Row<'_>, index: usize) -> rusqlite::Result<Option<LossCause>> {
+    let name: Option<String> = row.get(index)?;
+    Ok(name.as_deref().and_then(LossCause::parse))
+}
+
+fn event_from_row(row: &Row<'_>) -> rusqlite::Result<PoorPerformanceEvent> {
+    let timestamp: String = row.get(0)?;
+    Ok(PoorPerformanceEvent {
+        timestamp: parse_timestamp(&timestamp),
+        landed_slot: row.get(1)?,
+        voted_slot: row.get(2)?,
+        latency: row.get(3)?,
+        tvc_credits: row.get(4)?,
+        transaction_signature: Arc::new(row.get(5)?),
+        vote_account: row.get(6)?,
+        total_tvc_credits: row.get(7)?,
+        total_voted_slots: row.get(8)?,
+        tvc_multiplier: row.get(9)?,
+        leader: row.get(10)?,
+        loss_cause: loss_cause_from_row(row, 11)?,
+        blockhash: None,
+        parent_slot: None,
+        block_height: None,
+        // not stored for events, the vote row has it
+        kind: ConfirmationKind::Matched,
+    })
+}
+
+fn read_stored_votes(path: &Path) -> Result<StoredVotes> {
+    // read only, the monitor may be writing to it at the same time
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    let version = match schema_version(&conn)? {
+        0 => return Err(VoteMonitorError::Config(format!("{} is not a voteperfx database", path.display()))),
+        version if version > SCHEMA_VERSION => return Err(newer_schema_error(path, version)),
+        version => version,
+    };
+    // read only, an older database is read as it is
+    let confirmed_columns = if version >= 2 { "confirmed_latency, finalization_delay_ms" } else { "NULL, NULL" };
+    let loss_cause_column = if version >= 3 { "loss_cause" } else { "NULL" };
+    let kind_column = if version >= 4 { "kind" } else { "NULL" };
+    let voter_change_column = if version >= 5 { "before_voter_change" } else { "0" };
+
+    let mut stored = StoredVotes::default();
+    let mut votes = conn.prepare(&format!(
+        "SELECT timestamp, voted_slot, finalized_slot, latency, tvc_credits, signature,
+                estimated, source, landed_slot, confirmation_ms, {}, {}, {}, {}
+         FROM confirmed_votes ORDER BY id",
+        confirmed_columns, loss_cause_column, kind_column, voter_change_column
+    ))?;
+    for vote in votes.query_map([], vote_from_row)? {
+        match vote? {
+            Some(vote) => stored.votes.push(vote),
+            None => stored.malformed += 1,
+        }
+    }
+
+    let mut events = conn.prepare(&format!(
+        "SELECT timestamp, landed_slot, voted_slot, latency, tvc_credits, transaction_signature,
+                vote_account, total_tvc_credits, total_voted_slots, tvc_multiplier, leader, {}
+         FROM poor_events ORDER BY id",
+        loss_cause_column
+    ))?;
+    for event in events.query_map([], event_from_row)? {
+        stored.events.push(event?);
+    }
+
+    Ok(stored)
+}
+
+/// every confirmed vote and poor performance event in the database at `path`
+pub async fn read_database(path: &Path) -> Result<StoredVotes> {
+    let path = path.to_path_buf();
+    blocking(move || read_stored_votes(&path)).await
+}
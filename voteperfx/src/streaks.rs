@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::performance::{ConfirmedVote, Slot};
+
+/// broken streaks at least this long are published, see `stats.streak_alert_votes`
+pub const DEFAULT_STREAK_ALERT_VOTES: u64 = 100;
+
+/// consecutive votes earning max credits
+///
+/// votes with an estimated latency neither extend nor break a streak, their
+/// credits may be off by the estimate.
+#[derive(Debug, Clone, Default)]
+pub struct OptimalStreaks {
+    current: u64,
+    best: u64,
+    last_break: Option<Arc<StreakBreak>>,
+}
+
+/// the vote that ended a streak
+#[derive(Debug, Clone, Serialize)]
+pub struct StreakBreak {
+    /// optimal votes in a row before it
+    pub length: u64,
+    pub voted_slot: Slot,
+    pub tvc_credits: u64,
+    pub timestamp: DateTime<Local>,
+    pub signature: Arc<String>,
+}
+
+/// streaks as served on /status
+#[derive(Debug, Clone, Serialize)]
+pub struct StreakSnapshot {
+    pub current: u64,
+    pub best: u64,
+    pub last_break: Option<Arc<StreakBreak>>,
+}
+
+impl OptimalStreaks {
+    /// count a confirmed vote, returns the break when it ended a streak
+    pub fn record(&mut self, vote: &ConfirmedVote, max_credits: u64) -> Option<Arc<StreakBreak>> {
+        if vote.kind.is_estimated() || !vote.kind.is_counted() {
+            return None;
+        }
+        if vote.tvc_credits >= max_credits {
+            self.current += 1;
+            self.best = self.best.max(self.current);
+            return None;
+        }
+        if self.current == 0 {
+            return None;
+        }
+
+        let broken = Arc::new(StreakBreak {
+            length: self.current,
+            voted_slot: vote.voted_slot,
+            tvc_credits: vote.tvc_credits,
+            timestamp: vote.timestamp,
+            signature: vote.signature.clone(),
+        });
+        self.current = 0;
+        self.last_break = Some(broken.clone());
+        Some(broken)
+    }
+
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    pub fn best(&self) -> u64 {
+        self.best
+    }
+
+    pub fn last_break(&self) -> Option<&StreakBreak> {
+        self.last_break.as_deref()
+    }
+
+    pub fn snapshot(&self) -> StreakSnapshot {
+        StreakSnapshot {
+            current: self.current,
+            best: self.best,
+            last_break: self.last_break.clone(),
+        }
+    }
+
+    /// `streak 42, best 317, last broken at slot 123456 (14:32:07, 14 TVC)`
+    pub fn summary(&self) -> String {
+        let mut summary = format!("streak {}, best {}", self.current, self.best);
+        if let Some(broken) = self.last_break() {
+            summary.push_str(&format!(
+                ", last broken at slot {} ({}, {} TVC)",
+                broken.voted_slot, broken.timestamp.format("%H:%M:%S"), broken.tvc_credits
+            ));
+        }
+        summary
+    }
+}
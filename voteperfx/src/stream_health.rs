@@ -0,0 +1,265 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use serde::Serialize;
+use tokio::sync::{Notify, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::config::StalenessConfig;
+use crate::performance::PerformanceStats;
+
+/// how often the watchdog checks the update ages
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// slots `staleness.warn_secs` should span at least, shorter gaps between
+/// updates are normal
+pub const STALE_MIN_SLOTS: u64 = 10;
+
+/// grpc update kinds with their own freshness
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateKind {
+    Transaction,
+    Block,
+    Ping,
+}
+
+impl UpdateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Transaction => "transaction",
+            Self::Block => "block",
+            Self::Ping => "ping",
+        }
+    }
+}
+
+/// time of the last grpc update of each kind, shared between the stream
+/// task, the watchdog, the dashboard and /healthz
+#[derive(Debug)]
+pub struct StreamHealth {
+    started: Instant,
+    // millis since `started`, 0 until the first update
+    last_update_ms: AtomicU64,
+    last_transaction_ms: AtomicU64,
+    last_block_ms: AtomicU64,
+    last_ping_ms: AtomicU64,
+    // when the current subscription was established
+    connected_ms: AtomicU64,
+    connected: AtomicBool,
+    stale: AtomicBool,
+    reconnect_pending: AtomicBool,
+    reconnects: AtomicU64,
+    reconnect: Notify,
+    // updates received, sampled per minute by the filter drift watchdog
+    transactions: AtomicU64,
+    blocks: AtomicU64,
+}
+
+impl Default for StreamHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamHealth {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_update_ms: AtomicU64::new(0),
+            last_transaction_ms: AtomicU64::new(0),
+            last_block_ms: AtomicU64::new(0),
+            last_ping_ms: AtomicU64::new(0),
+            connected_ms: AtomicU64::new(0),
+            connected: AtomicBool::new(false),
+            stale: AtomicBool::new(false),
+            reconnect_pending: AtomicBool::new(false),
+            reconnects: AtomicU64::new(0),
+            reconnect: Notify::new(),
+            transactions: AtomicU64::new(0),
+            blocks: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    fn now_ms(&self) -> u64 {
+        (self.started.elapsed().as_millis() as u64).max(1)
+    }
+
+    fn age(&self, last_ms: &AtomicU64) -> Option<Duration> {
+        match last_ms.load(Ordering::Relaxed) {
+            0 => None,
+            last_ms => Some(self.started.elapsed().saturating_sub(Duration::from_millis(last_ms))),
+        }
+    }
+
+    #[inline]
+    pub fn record_update(&self) {
+        self.last_update_ms.store(self.now_ms(), Ordering::Relaxed);
+    }
+
+    /// an update of `kind`, also counts as any update
+    #[inline]
+    pub fn record(&self, kind: UpdateKind) {
+        let now_ms = self.now_ms();
+        self.last_update_ms.store(now_ms, Ordering::Relaxed);
+        let last_ms = match kind {
+            UpdateKind::Transaction => {
+                self.transactions.fetch_add(1, Ordering::Relaxed);
+                &self.last_transaction_ms
+            }
+            UpdateKind::Block => {
+                self.blocks.fetch_add(1, Ordering::Relaxed);
+                &self.last_block_ms
+            }
+            UpdateKind::Ping => &self.last_ping_ms,
+        };
+        last_ms.store(now_ms, Ordering::Relaxed);
+    }
+
+    /// transaction and block updates received since startup
+    pub fn update_counts(&self) -> (u64, u64) {
+        (self.transactions.load(Ordering::Relaxed), self.blocks.load(Ordering::Relaxed))
+    }
+
+    /// time since the last update, none before the first one
+    pub fn since_last_update(&self) -> Option<Duration> {
+        self.age(&self.last_update_ms)
+    }
+
+    /// time since the last update of `kind`, none before the first one
+    pub fn since(&self, kind: UpdateKind) -> Option<Duration> {
+        match kind {
+            UpdateKind::Transaction => self.age(&self.last_transaction_ms),
+            UpdateKind::Block => self.age(&self.last_block_ms),
+            UpdateKind::Ping => self.age(&self.last_ping_ms),
+        }
+    }
+
+    /// a subscription was (re-)established
+    pub fn mark_connected(&self) {
+        self.connected_ms.store(self.now_ms(), Ordering::Relaxed);
+        self.connected.store(true, Ordering::Relaxed);
+        self.reconnect_pending.store(false, Ordering::Relaxed);
+    }
+
+    /// the subscription ended, until the next `mark_connected`
+    pub fn mark_disconnected(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+    }
+
+    /// a subscription is established and has not ended
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// age of the current subscription, none while disconnected
+    pub fn connected_for(&self) -> Option<Duration> {
+        self.age(&self.connected_ms).filter(|_| self.is_connected())
+    }
+
+    /// how long `kind` has been missing on the current subscription, none
+    /// before the first subscription
+    pub fn stale_for(&self, kind: UpdateKind) -> Option<Duration> {
+        let connected = self.age(&self.connected_ms)?;
+        Some(self.since(kind).map_or(connected, |age| age.min(connected)))
+    }
+
+    /// a transaction or block update is overdue, see `StalenessConfig::warn_secs`
+    pub fn is_stale(&self) -> bool {
+        self.stale.load(Ordering::Relaxed)
+    }
+
+    /// ask the stream task to tear the subscription down and resubscribe
+    pub fn request_reconnect(&self) {
+        if !self.reconnect_pending.swap(true, Ordering::Relaxed) {
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+            self.reconnect.notify_one();
+        }
+    }
+
+    /// resolves once a reconnect was requested
+    pub async fn reconnect_requested(&self) {
+        self.reconnect.notified().await
+    }
+
+    /// resubscriptions requested because the stream went stale
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    pub fn staleness(&self) -> StreamStaleness {
+        let secs = |kind| self.since(kind).map(|age: Duration| age.as_secs());
+        StreamStaleness {
+            last_update_secs: self.since_last_update().map(|age| age.as_secs()),
+            last_transaction_secs: secs(UpdateKind::Transaction),
+            last_block_secs: secs(UpdateKind::Block),
+            last_ping_secs: secs(UpdateKind::Ping),
+            stale: self.is_stale(),
+            stale_reconnects: self.reconnects(),
+        }
+    }
+}
+
+/// seconds since the last grpc update of each kind, none before the first one
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StreamStaleness {
+    pub last_update_secs: Option<u64>,
+    pub last_transaction_secs: Option<u64>,
+    pub last_block_secs: Option<u64>,
+    pub last_ping_secs: Option<u64>,
+    /// a transaction or block update is overdue
+    pub stale: bool,
+    /// resubscriptions because the stream went stale
+    pub stale_reconnects: u64,
+}
+
+/// warn when transaction or block updates stop arriving and request a
+/// resubscription once they have been missing for `reconnect_secs`
+///
+/// only the `watched` kinds count, blocks are not subscribed in the
+/// tx-status mode. also keeps the dashboard redrawing while stale, nothing else changes the
+/// stats then.
+pub fn spawn_stream_watchdog(
+    health: Arc<StreamHealth>,
+    config: StalenessConfig,
+    watched: &'static [UpdateKind],
+    stats: Arc<RwLock<PerformanceStats>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let warn_after = Duration::from_secs(config.warn_secs);
+        let reconnect_after = (config.reconnect_secs > 0).then(|| Duration::from_secs(config.reconnect_secs));
+        let mut interval = tokio::time::interval(WATCHDOG_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            let overdue = watched
+                .iter()
+                .copied()
+                .filter_map(|kind| health.stale_for(kind).map(|age| (kind, age)))
+                .max_by_key(|&(_, age)| age);
+            let Some((kind, age)) = overdue else { continue };
+
+            let stale = age >= warn_after;
+            if stale != health.stale.swap(stale, Ordering::Relaxed) {
+                if stale {
+                    warn!("no grpc {} update for {}s, the stream may be stalled", kind.as_str(), age.as_secs());
+                } else {
+                    info!("grpc stream no longer stale");
+                }
+            }
+            if stale {
+                stats.read().await.mark_changed();
+            }
+
+            if reconnect_after.is_some_and(|after| age >= after) && !health.reconnect_pending.load(Ordering::Relaxed) {
+                warn!(
+                    "no grpc {} update for {}s, re-establishing the subscription",
+                    kind.as_str(), age.as_secs()
+                );
+                health.request_reconnect();
+            }
+        }
+    })
+}
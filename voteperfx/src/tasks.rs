@@ -0,0 +1,29 @@
+use std::future::Future;
+
+use tokio::task::JoinHandle;
+
+/// spawn a long-running pipeline task under `name`
+///
+/// with the tracing feature the task runs in a `task` span carrying the
+/// name, and builds with `--cfg tokio_unstable` name the tokio task for
+/// tokio-console. otherwise this is `tokio::spawn`.
+pub fn spawn_named<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(feature = "tracing")]
+    let future = tracing::Instrument::instrument(future, tracing::info_span!("task", name));
+
+    #[cfg(all(feature = "tracing", tokio_unstable))]
+    return tokio::task::Builder::new()
+        .name(name)
+        .spawn(future)
+        .unwrap_or_else(|e| panic!("cannot spawn the {} task: {}", name, e));
+
+    #[cfg(not(all(feature = "tracing", tokio_unstable)))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
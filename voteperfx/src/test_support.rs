@@ -0,0 +1,304 @@
+//! builders for realistic grpc updates, for tests and anyone extending the parser
+//!
+//! vote transactions carry a real bincode serialized `TowerSync` instruction,
+//! so they go through the same parsing as updates from a live stream.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use solana_sdk::hash::Hash;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::transaction::TransactionError;
+use solana_sdk::vote::instruction as vote_instruction;
+use solana_sdk::vote::state::{Lockout, TowerSync};
+use yellowstone_grpc_proto::convert_to::create_transaction_error;
+use yellowstone_grpc_proto::geyser::{
+    SlotStatus, SubscribeUpdateBlock, SubscribeUpdateSlot, SubscribeUpdateTransaction,
+    SubscribeUpdateTransactionInfo,
+};
+use yellowstone_grpc_proto::prelude::{
+    CompiledInstruction, Message as ProtoMessage, MessageHeader, Transaction, TransactionStatusMeta,
+};
+
+use chrono::Local;
+use rustc_hash::FxHashSet;
+
+use crate::config::TvcConfig;
+use crate::performance::{calculate_tvc_credits_from_latency, ConfirmationKind, ConfirmationSource, ConfirmedVote, Slot};
+use crate::vote_tracker::PendingVote;
+
+/// vote account the `make_vote_tx` builders vote for
+pub const VOTE_ACCOUNT: Pubkey = Pubkey::new_from_array([7; 32]);
+
+/// `VOTE_ACCOUNT` as the tracker functions take it
+pub fn vote_account_key() -> [u8; 32] {
+    VOTE_ACCOUNT.to_bytes()
+}
+
+/// deterministic 64 byte signature, distinct per `seed`
+pub fn signature(seed: u64) -> Vec<u8> {
+    signature_bytes(seed).to_vec()
+}
+
+fn signature_bytes(seed: u64) -> [u8; 64] {
+    let mut signature = [0u8; 64];
+    signature[..8].copy_from_slice(&seed.to_le_bytes());
+    // keep it away from the all zero signature
+    signature[63] = 1;
+    signature
+}
+
+/// base58 form of `signature(seed)`, as the tracker reports it
+pub fn signature_base58(seed: u64) -> String {
+    fd_bs58::encode_64(signature_bytes(seed))
+}
+
+/// serialized `TowerSync` instruction data for `(slot, confirmation_count)` lockouts,
+/// a count of 1 marks a new vote
+pub fn tower_sync_data(lockouts: &[(Slot, u32)]) -> Vec<u8> {
+    tower_sync_root_data(lockouts, None)
+}
+
+/// `tower_sync_data` with a tower root
+pub fn tower_sync_root_data(lockouts: &[(Slot, u32)], root: Option<Slot>) -> Vec<u8> {
+    tower_sync_message(&Pubkey::new_unique(), lockouts, root).instructions.remove(0).data
+}
+
+fn tower_sync_message(vote_account: &Pubkey, lockouts: &[(Slot, u32)], root: Option<Slot>) -> Message {
+    let tower_sync = TowerSync {
+        lockouts: lockouts
+            .iter()
+            .map(|&(slot, confirmation_count)| Lockout::new_with_confirmation_count(slot, confirmation_count))
+            .collect::<VecDeque<_>>(),
+        root,
+        hash: Hash::new_unique(),
+        timestamp: None,
+        block_id: Hash::new_unique(),
+    };
+    let authority = Pubkey::new_unique();
+    let instruction = vote_instruction::tower_sync(vote_account, &authority, tower_sync);
+    Message::new(&[instruction], Some(&authority))
+}
+
+fn proto_message(message: &Message) -> ProtoMessage {
+    ProtoMessage {
+        header: Some(MessageHeader {
+            num_required_signatures: message.header.num_required_signatures as u32,
+            num_readonly_signed_accounts: message.header.num_readonly_signed_accounts as u32,
+            num_readonly_unsigned_accounts: message.header.num_readonly_unsigned_accounts as u32,
+        }),
+        account_keys: message.account_keys.iter().map(|key| key.to_bytes().to_vec()).collect(),
+        recent_blockhash: message.recent_blockhash.to_bytes().to_vec(),
+        instructions: message
+            .instructions
+            .iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: instruction.program_id_index as u32,
+                accounts: instruction.accounts.clone(),
+                data: instruction.data.clone(),
+            })
+            .collect(),
+        versioned: false,
+        address_table_lookups: Vec::new(),
+    }
+}
+
+fn transaction_update(
+    signature: Vec<u8>,
+    slot: Slot,
+    message: &Message,
+    is_vote: bool,
+    status: Result<(), TransactionError>,
+) -> SubscribeUpdateTransaction {
+    SubscribeUpdateTransaction {
+        transaction: Some(SubscribeUpdateTransactionInfo {
+            signature: signature.clone(),
+            is_vote,
+            transaction: Some(Transaction {
+                signatures: vec![signature],
+                message: Some(proto_message(message)),
+            }),
+            meta: Some(TransactionStatusMeta {
+                err: create_transaction_error(&status),
+                fee: 5000,
+                ..Default::default()
+            }),
+            index: 0,
+        }),
+        slot,
+    }
+}
+
+/// vote transaction of `VOTE_ACCOUNT` landed at `slot` voting on `(slot, confirmation_count)` lockouts
+pub fn make_vote_tx(signature: Vec<u8>, slot: Slot, lockouts: &[(Slot, u32)]) -> SubscribeUpdateTransaction {
+    make_vote_tx_for(&VOTE_ACCOUNT, signature, slot, lockouts)
+}
+
+/// `make_vote_tx` for another vote account
+pub fn make_vote_tx_for(
+    vote_account: &Pubkey,
+    signature: Vec<u8>,
+    slot: Slot,
+    lockouts: &[(Slot, u32)],
+) -> SubscribeUpdateTransaction {
+    let message = tower_sync_message(vote_account, lockouts, None);
+    transaction_update(signature, slot, &message, true, Ok(()))
+}
+
+/// vote transaction that landed with `error`
+pub fn make_failed_vote_tx(
+    signature: Vec<u8>,
+    slot: Slot,
+    lockouts: &[(Slot, u32)],
+    error: TransactionError,
+) -> SubscribeUpdateTransaction {
+    let message = tower_sync_message(&VOTE_ACCOUNT, lockouts, None);
+    transaction_update(signature, slot, &message, true, Err(error))
+}
+
+/// vote program transaction of `VOTE_ACCOUNT` whose instructions carry
+/// `instructions_data`, e.g. an authorize or a variant this build does not know
+pub fn make_vote_program_tx(signature: Vec<u8>, slot: Slot, instructions_data: &[Vec<u8>]) -> SubscribeUpdateTransaction {
+    let authority = Pubkey::new_unique();
+    let instructions: Vec<Instruction> = instructions_data
+        .iter()
+        .map(|data| {
+            Instruction::new_with_bytes(
+                solana_sdk::vote::program::id(),
+                data,
+                vec![AccountMeta::new(VOTE_ACCOUNT, false), AccountMeta::new_readonly(authority, true)],
+            )
+        })
+        .collect();
+    let message = Message::new(&instructions, Some(&authority));
+    transaction_update(signature, slot, &message, true, Ok(()))
+}
+
+/// transaction calling some other program, not a vote
+pub fn make_non_vote_tx(signature: Vec<u8>, slot: Slot) -> SubscribeUpdateTransaction {
+    let payer = Pubkey::new_unique();
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0],
+        vec![AccountMeta::new(payer, true), AccountMeta::new(Pubkey::new_unique(), false)],
+    );
+    let message = Message::new(&[instruction], Some(&payer));
+    transaction_update(signature, slot, &message, false, Ok(()))
+}
+
+/// finalized block at `slot` containing `transactions`
+pub fn make_block(slot: Slot, transactions: &[SubscribeUpdateTransaction]) -> SubscribeUpdateBlock {
+    let transactions: Vec<SubscribeUpdateTransactionInfo> = transactions
+        .iter()
+        .filter_map(|update| update.transaction.clone())
+        .enumerate()
+        .map(|(index, mut info)| {
+            info.index = index as u64;
+            info
+        })
+        .collect();
+    SubscribeUpdateBlock {
+        slot,
+        blockhash: Hash::new_unique().to_string(),
+        parent_slot: slot.saturating_sub(1),
+        parent_blockhash: Hash::new_unique().to_string(),
+        executed_transaction_count: transactions.len() as u64,
+        transactions,
+        ..Default::default()
+    }
+}
+
+/// pending vote as the transaction path creates it, new votes on `voted_slots`
+/// from a transaction that landed at `transaction_slot`
+pub fn make_pending_vote(seed: u64, transaction_slot: Slot, voted_slots: &[Slot]) -> PendingVote {
+    let lockouts: Vec<(Slot, u32)> = voted_slots.iter().map(|&slot| (slot, 1)).collect();
+    PendingVote {
+        signature: Arc::new(signature_base58(seed)),
+        voted_slots: voted_slots.iter().copied().collect(),
+        transaction_slot,
+        timestamp: Local::now(),
+        instruction_data: tower_sync_data(&lockouts),
+        confirmed_slots: FxHashSet::default(),
+    }
+}
+
+/// confirmed vote on `voted_slot` after `latency` slots, credited with the
+/// default tvc parameters
+pub fn make_confirmed_vote(seed: u64, voted_slot: Slot, latency: u64) -> ConfirmedVote {
+    ConfirmedVote {
+        signature: Arc::new(signature_base58(seed)),
+        voted_slot,
+        finalized_slot: voted_slot + latency,
+        latency,
+        tvc_credits: calculate_tvc_credits_from_latency(latency, &TvcConfig::default()),
+        timestamp: Local::now(),
+        kind: ConfirmationKind::Matched,
+        source: ConfirmationSource::Block,
+        confirmation_ms: Some(latency * 400),
+        block_time_delta_ms: None,
+        landed_slot: Some(voted_slot + 1),
+        confirmed_latency: None,
+        finalization_delay_ms: None,
+        loss_cause: None,
+        blockhash: None,
+        parent_slot: None,
+        block_height: None,
+        before_voter_change: false,
+    }
+}
+
+/// slot status update
+pub fn make_slot(slot: Slot, status: SlotStatus) -> SubscribeUpdateSlot {
+    SubscribeUpdateSlot {
+        slot,
+        parent: Some(slot.saturating_sub(1)),
+        status: status as i32,
+        dead_error: None,
+    }
+}
+
+/// empty directory under the system temp dir, unique to `name` and this process
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("voteperfx-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+/// `mimalloc` that counts the allocations made on each thread, the global
+/// allocator of the unit tests
+#[cfg(test)]
+pub struct CountingAllocator;
+
+#[cfg(test)]
+thread_local! {
+    static ALLOCATIONS: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(test)]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        mimalloc::MiMalloc.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        mimalloc::MiMalloc.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        mimalloc::MiMalloc.realloc(ptr, layout, new_size)
+    }
+}
+
+/// what `f` returns and the allocations it made on this thread
+#[cfg(test)]
+pub fn allocations<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let before = ALLOCATIONS.with(std::cell::Cell::get);
+    let value = f();
+    (value, ALLOCATIONS.with(std::cell::Cell::get) - before)
+}
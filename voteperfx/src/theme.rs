@@ -0,0 +1,169 @@
+use ratatui::style::{Color, Style};
+use ratatui::symbols::{bar, border};
+use ratatui::widgets::{Block, Borders};
+use serde::{Deserialize, Serialize};
+
+use crate::performance::TvcPerformanceLevel;
+
+/// dashboard look, see `Theme`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DashboardTheme {
+    /// picked from NO_COLOR and TERM, see `detect`
+    #[default]
+    Auto,
+    /// unicode box drawing, block characters and emoji severity markers
+    Emoji,
+    /// plain ascii borders and markers, still colored
+    Ascii,
+    /// ascii without any color escapes
+    NoColor,
+}
+
+impl DashboardTheme {
+    /// the configured theme, `Auto` resolved from the environment
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto => Self::detect(
+                std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+                std::env::var("TERM").ok().as_deref(),
+            ),
+            theme => theme,
+        }
+    }
+
+    /// NO_COLOR (https://no-color.org) or a dumb terminal get no colors, the
+    /// linux console and vt terminals lack the unicode glyphs
+    pub fn detect(no_color: bool, term: Option<&str>) -> Self {
+        let term = term.unwrap_or_default();
+        if no_color || term == "dumb" {
+            Self::NoColor
+        } else if term == "linux" || term == "ansi" || term.starts_with("vt") || term.starts_with("cons") {
+            Self::Ascii
+        } else {
+            Self::Emoji
+        }
+    }
+}
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+const ASCII_BARS: bar::Set = bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "#",
+    five_eighths: "=",
+    half: "=",
+    three_eighths: "-",
+    one_quarter: "-",
+    one_eighth: "_",
+    empty: " ",
+};
+
+/// terminal columns taken by a severity marker
+pub const MARKER_WIDTH: u16 = 4;
+
+/// every glyph and color the dashboard draws with
+///
+/// markers are padded to `MARKER_WIDTH`, so columns line up whichever
+/// marker a row gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    kind: DashboardTheme,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(DashboardTheme::Auto)
+    }
+}
+
+impl Theme {
+    pub fn new(kind: DashboardTheme) -> Self {
+        Self { kind: kind.resolve() }
+    }
+
+    pub fn kind(&self) -> DashboardTheme {
+        self.kind
+    }
+
+    /// ascii glyphs only, the emoji theme draws unicode
+    pub fn is_ascii(&self) -> bool {
+        self.kind != DashboardTheme::Emoji
+    }
+
+    /// `color` as foreground, the terminal default without colors
+    pub fn fg(&self, color: Color) -> Style {
+        if self.kind == DashboardTheme::NoColor {
+            Style::default()
+        } else {
+            Style::default().fg(color)
+        }
+    }
+
+    /// bordered panel
+    pub fn block(&self) -> Block<'static> {
+        let block = Block::default().borders(Borders::ALL);
+        if self.is_ascii() {
+            block.border_set(ASCII_BORDER)
+        } else {
+            block
+        }
+    }
+
+    pub fn bar_set(&self) -> bar::Set {
+        if self.is_ascii() {
+            ASCII_BARS
+        } else {
+            bar::NINE_LEVELS
+        }
+    }
+
+    /// trend levels from lowest to highest
+    pub fn levels(&self) -> [char; 8] {
+        if self.is_ascii() {
+            ['_', '.', ',', '-', '~', '=', '+', '#']
+        } else {
+            ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█']
+        }
+    }
+
+    /// one column for a trend bucket without votes
+    pub fn empty_level(&self) -> &'static str {
+        if self.is_ascii() { " " } else { "·" }
+    }
+
+    /// severity marker of a vote
+    pub fn marker(&self, level: TvcPerformanceLevel) -> &'static str {
+        match (self.is_ascii(), level) {
+            (false, TvcPerformanceLevel::Optimal) => "🟩",
+            (false, TvcPerformanceLevel::Good | TvcPerformanceLevel::Fair) => "🟨",
+            (false, TvcPerformanceLevel::Poor) => "🟥",
+            (false, TvcPerformanceLevel::Critical) => "💀",
+            (true, TvcPerformanceLevel::Optimal) => "[OK]",
+            (true, TvcPerformanceLevel::Good | TvcPerformanceLevel::Fair) => "[!]",
+            (true, TvcPerformanceLevel::Poor | TvcPerformanceLevel::Critical) => "[X]",
+        }
+    }
+
+    /// the marker padded to `MARKER_WIDTH` terminal columns, emoji are two wide
+    pub fn marker_cell(&self, level: TvcPerformanceLevel) -> String {
+        let marker = self.marker(level);
+        let width = if self.is_ascii() { marker.len() } else { 2 };
+        format!("{}{}", marker, " ".repeat(MARKER_WIDTH as usize - width))
+    }
+
+    /// `unicode` or its ascii stand-in, for arrows, ellipses and comparison signs
+    pub fn text(&self, unicode: &'static str, ascii: &'static str) -> &'static str {
+        if self.is_ascii() { ascii } else { unicode }
+    }
+}
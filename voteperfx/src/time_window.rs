@@ -0,0 +1,167 @@
+use std::collections::vec_deque::{self, VecDeque};
+use std::time::{Duration, Instant};
+
+/// values from the last `span`, with a running sum for the mean
+///
+/// entries expire on every push and on `expire`, which the maintenance tick
+/// calls so the window also drains while no values arrive.
+#[derive(Debug, Clone)]
+pub struct TimeWindow {
+    span: Duration,
+    /// oldest first
+    entries: VecDeque<(Instant, u64)>,
+    sum: u64,
+}
+
+impl TimeWindow {
+    pub fn new(span: Duration) -> Self {
+        Self { span, entries: VecDeque::new(), sum: 0 }
+    }
+
+    pub fn span(&self) -> Duration {
+        self.span
+    }
+
+    pub fn push(&mut self, value: u64) {
+        self.push_at(Instant::now(), value);
+    }
+
+    /// `at` must not be older than the newest entry
+    pub fn push_at(&mut self, at: Instant, value: u64) {
+        self.expire(at);
+        self.entries.push_back((at, value));
+        self.sum += value;
+    }
+
+    /// drop the entries older than `span` at `now`, any number at once
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let mut expired = 0;
+        while let Some(&(at, value)) = self.entries.front() {
+            if now.saturating_duration_since(at) <= self.span {
+                break;
+            }
+            self.entries.pop_front();
+            self.sum -= value;
+            expired += 1;
+        }
+        expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// none for an empty window
+    pub fn mean(&self) -> Option<f64> {
+        (!self.entries.is_empty()).then(|| self.sum as f64 / self.entries.len() as f64)
+    }
+
+    /// oldest first
+    pub fn iter(&self) -> vec_deque::Iter<'_, (Instant, u64)> {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.sum = 0;
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.entries.capacity() * std::mem::size_of::<(Instant, u64)>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPAN: Duration = Duration::from_secs(60);
+
+    /// the running sum against the entries it stands for
+    fn assert_consistent(window: &TimeWindow) {
+        assert_eq!(window.sum, window.iter().map(|&(_, value)| value).sum::<u64>());
+        match window.mean() {
+            Some(mean) => assert_eq!(mean, window.sum as f64 / window.len() as f64),
+            None => assert_eq!(window.sum, 0),
+        }
+    }
+
+    #[test]
+    fn burst_expires_at_once() {
+        let start = Instant::now();
+        let mut window = TimeWindow::new(SPAN);
+        for i in 0..1_000 {
+            window.push_at(start + Duration::from_millis(i), 1 + i % 7);
+        }
+        assert_consistent(&window);
+
+        // a gap past the whole burst
+        assert_eq!(window.expire(start + Duration::from_secs(120)), 1_000);
+        assert!(window.is_empty());
+        assert_eq!((window.sum, window.mean()), (0, None));
+
+        window.push_at(start + Duration::from_secs(121), 4);
+        assert_eq!(window.mean(), Some(4.0));
+    }
+
+    #[test]
+    fn entries_at_the_span_are_kept() {
+        let start = Instant::now();
+        let mut window = TimeWindow::new(SPAN);
+        window.push_at(start, 2);
+        window.push_at(start + Duration::from_secs(30), 4);
+        assert_eq!(window.expire(start + SPAN), 0);
+        assert_eq!(window.mean(), Some(3.0));
+        assert_eq!(window.expire(start + SPAN + Duration::from_millis(1)), 1);
+        assert_eq!(window.mean(), Some(4.0));
+        // an earlier `now` expires nothing
+        assert_eq!(window.expire(start), 0);
+        assert_consistent(&window);
+    }
+
+    #[test]
+    fn bursts_and_gaps_never_drift() {
+        let start = Instant::now();
+        let mut window = TimeWindow::new(SPAN);
+        let mut now = Duration::ZERO;
+        let mut pushed = Vec::new();
+        // bursts of up to 50 votes, quiet spells up to three spans long
+        for round in 0u64..500 {
+            let burst = (round * 37) % 50;
+            for i in 0..burst {
+                now += Duration::from_millis(400);
+                let value = (round + i) % 32;
+                window.push_at(start + now, value);
+                pushed.push((now, value));
+            }
+            now += Duration::from_secs((round * 13) % 180);
+            // the maintenance tick during the gap
+            window.expire(start + now);
+            assert_consistent(&window);
+
+            let expected: Vec<u64> = pushed
+                .iter()
+                .filter(|(at, _)| now.saturating_sub(*at) <= SPAN)
+                .map(|&(_, value)| value)
+                .collect();
+            assert_eq!(window.len(), expected.len(), "round {}", round);
+            assert_eq!(window.sum, expected.iter().sum::<u64>(), "round {}", round);
+        }
+    }
+
+    #[test]
+    fn clear_resets_the_sum() {
+        let mut window = TimeWindow::new(SPAN);
+        window.push(5);
+        window.push(7);
+        window.clear();
+        assert_consistent(&window);
+        assert_eq!(window.mean(), None);
+        window.push(3);
+        assert_eq!(window.mean(), Some(3.0));
+    }
+}
@@ -0,0 +1,213 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::cluster_tip::LagAlert;
+use crate::performance::Slot;
+
+/// root lag that raises a warning unless configured
+///
+/// a full tower keeps the root 31 votes behind the newest one, skipped slots
+/// push the slot distance past that.
+pub const DEFAULT_ROOT_LAG_ALERT_SLOTS: u64 = 128;
+
+/// root and newest voted slot of a landed tower update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TowerUpdate {
+    pub root: Slot,
+    pub latest_voted: Slot,
+}
+
+/// root progression of the monitored tower over the session
+///
+/// the root advances as votes pop off the bottom of a full tower, so a root
+/// that stays put while votes keep landing points at lockout problems.
+#[derive(Debug, Clone)]
+pub struct TowerRoot {
+    root: Option<Slot>,
+    latest_voted: Slot,
+    // first root of the session and when it was seen
+    first: Option<(Slot, Instant)>,
+    advanced_at: Option<Instant>,
+    /// root lag that raises an alert, 0 disables it
+    lag_alert_slots: u64,
+    lagging: bool,
+}
+
+impl Default for TowerRoot {
+    fn default() -> Self {
+        Self::new(DEFAULT_ROOT_LAG_ALERT_SLOTS)
+    }
+}
+
+/// root progression for /status
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TowerRootSnapshot {
+    pub root: Slot,
+    pub latest_voted: Slot,
+    /// slots between the root and the newest voted slot
+    pub lag: u64,
+    /// root slots advanced per minute over the session
+    pub advance_per_min: Option<f64>,
+    pub secs_since_advance: Option<u64>,
+    pub lagging: bool,
+}
+
+impl TowerRoot {
+    pub fn new(lag_alert_slots: u64) -> Self {
+        Self {
+            root: None,
+            latest_voted: 0,
+            first: None,
+            advanced_at: None,
+            lag_alert_slots,
+            lagging: false,
+        }
+    }
+
+    /// same threshold, no progress, for a session reset
+    pub fn fresh(&self) -> Self {
+        Self::new(self.lag_alert_slots)
+    }
+
+    /// record a landed tower update, returning a change of the lag alert
+    ///
+    /// updates that arrive out of order never move the root backwards.
+    pub fn record(&mut self, update: TowerUpdate) -> Option<LagAlert> {
+        let now = Instant::now();
+        self.latest_voted = self.latest_voted.max(update.latest_voted);
+        match self.root {
+            None => {
+                self.root = Some(update.root);
+                self.first = Some((update.root, now));
+                self.advanced_at = Some(now);
+            }
+            Some(root) if update.root > root => {
+                self.root = Some(update.root);
+                self.advanced_at = Some(now);
+            }
+            Some(_) => {}
+        }
+
+        let lag = self.lag()?;
+        if self.lag_alert_slots == 0 {
+            return None;
+        }
+        match (self.lagging, lag > self.lag_alert_slots) {
+            (false, true) => {
+                self.lagging = true;
+                Some(LagAlert::Raised(lag))
+            }
+            (true, false) => {
+                self.lagging = false;
+                Some(LagAlert::Cleared(lag))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn root(&self) -> Option<Slot> {
+        self.root
+    }
+
+    /// slots between the root and the newest voted slot, none before the first root
+    pub fn lag(&self) -> Option<u64> {
+        Some(self.latest_voted.saturating_sub(self.root?))
+    }
+
+    /// the lag is above the alert threshold
+    pub fn is_lagging(&self) -> bool {
+        self.lagging
+    }
+
+    pub fn lag_alert_slots(&self) -> u64 {
+        self.lag_alert_slots
+    }
+
+    /// root slots advanced per minute since the first root of the session
+    pub fn advance_per_min(&self) -> Option<f64> {
+        let (first_root, first_at) = self.first?;
+        let elapsed = first_at.elapsed();
+        if elapsed < Duration::from_secs(1) {
+            return None;
+        }
+        Some((self.root? - first_root) as f64 / elapsed.as_secs_f64() * 60.0)
+    }
+
+    /// time since the root last moved
+    pub fn since_advance(&self) -> Option<Duration> {
+        self.advanced_at.map(|at| at.elapsed())
+    }
+
+    pub fn snapshot(&self) -> Option<TowerRootSnapshot> {
+        Some(TowerRootSnapshot {
+            root: self.root?,
+            latest_voted: self.latest_voted,
+            lag: self.lag()?,
+            advance_per_min: self.advance_per_min(),
+            secs_since_advance: self.since_advance().map(|age| age.as_secs()),
+            lagging: self.lagging,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{tower_sync_data, tower_sync_root_data};
+    use crate::vote_parse::parse_vote_instruction_data;
+
+    /// tower update of a full tower with `root` whose newest vote is `newest`
+    fn tower_sync(root: Option<Slot>, newest: Slot) -> Option<TowerUpdate> {
+        let lockouts: Vec<(Slot, u32)> = (0..31u32).rev().map(|depth| (newest - depth as Slot, depth + 1)).collect();
+        parse_vote_instruction_data(&tower_sync_root_data(&lockouts, root)).unwrap().tower_update()
+    }
+
+    #[test]
+    fn root_is_read_from_the_tower_sync() {
+        assert_eq!(tower_sync(Some(69), 100), Some(TowerUpdate { root: 69, latest_voted: 100 }));
+        // a tower without a root, right after a restart
+        assert_eq!(tower_sync(None, 100), None);
+        assert!(parse_vote_instruction_data(&tower_sync_data(&[(100, 1)])).unwrap().root.is_none());
+    }
+
+    #[test]
+    fn advancing_root_keeps_the_lag() {
+        let mut tower = TowerRoot::new(64);
+        for newest in 100..140 {
+            assert_eq!(tower.record(tower_sync(Some(newest - 31), newest).unwrap()), None);
+        }
+        assert_eq!(tower.root(), Some(108));
+        assert_eq!(tower.lag(), Some(31));
+        assert!(!tower.is_lagging());
+        let snapshot = tower.snapshot().unwrap();
+        assert_eq!((snapshot.root, snapshot.latest_voted, snapshot.lag), (108, 139, 31));
+    }
+
+    #[test]
+    fn stalled_root_raises_and_clears_the_alert() {
+        let mut tower = TowerRoot::new(64);
+        assert_eq!(tower.record(tower_sync(Some(69), 100).unwrap()), None);
+        // votes keep landing, the root stays put
+        let alerts: Vec<_> = (101..=140).filter_map(|newest| tower.record(tower_sync(Some(69), newest).unwrap())).collect();
+        assert_eq!(alerts, [LagAlert::Raised(65)]);
+        assert_eq!(tower.root(), Some(69));
+        assert!(tower.is_lagging());
+
+        // the root catches up
+        assert_eq!(tower.record(tower_sync(Some(110), 141).unwrap()), Some(LagAlert::Cleared(31)));
+        assert!(!tower.is_lagging());
+    }
+
+    #[test]
+    fn late_updates_never_move_the_root_back() {
+        let mut tower = TowerRoot::new(0);
+        tower.record(TowerUpdate { root: 200, latest_voted: 231 });
+        tower.record(TowerUpdate { root: 150, latest_voted: 181 });
+        assert_eq!((tower.root(), tower.lag()), (Some(200), Some(31)));
+        // 0 disables the alert
+        assert_eq!(tower.record(TowerUpdate { root: 200, latest_voted: 10_000 }), None);
+        assert!(!tower.is_lagging());
+        assert_eq!(tower.fresh().root(), None);
+    }
+}
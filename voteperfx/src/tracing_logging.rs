@@ -0,0 +1,92 @@
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use tracing_subscriber::filter::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+use crate::config::LogFormat;
+
+/// set to 1 to serve tokio-console on its default port, 6669
+pub const TOKIO_CONSOLE_ENV: &str = "VOTEPERFX_TOKIO_CONSOLE";
+
+/// what tokio-console needs from the runtime, whatever the log filter
+const CONSOLE_FILTER: &str = "tokio=trace,runtime=trace";
+
+/// the log filter of the installed subscriber, swapped like the env_logger one
+struct Filters {
+    handle: reload::Handle<EnvFilter, Registry>,
+    /// mode default, RUST_LOG when set, see `set_log_mode`
+    default_filters: RwLock<String>,
+    /// the config's log_level, none for the mode default
+    filters: RwLock<Option<String>>,
+}
+
+impl Filters {
+    fn rebuild(&self) {
+        let filters = self.filters.read().unwrap_or_else(PoisonError::into_inner).clone();
+        let default_filters = self.default_filters.read().unwrap_or_else(PoisonError::into_inner).clone();
+        let _ = self.handle.reload(env_filter(filters.as_deref().unwrap_or(&default_filters)));
+    }
+}
+
+static FILTERS: OnceLock<Filters> = OnceLock::new();
+
+/// an unparsable filter logs everything at info, like env_logger skips
+/// what it cannot parse
+fn env_filter(filters: &str) -> EnvFilter {
+    EnvFilter::try_new(filters).unwrap_or_else(|_| EnvFilter::new("info"))
+}
+
+/// RUST_LOG, or the mode default without it
+fn default_filters(simple_mode: bool) -> String {
+    std::env::var("RUST_LOG").unwrap_or_else(|_| crate::logging::mode_filters(simple_mode).to_string())
+}
+
+/// install a tracing-subscriber in place of the env_logger of `init_logging`
+///
+/// log records come in through the tracing-log bridge and are filtered with
+/// the spans, so `log_level` takes span filters such as
+/// `voteperfx[process_finalized_block{slot=301234567}]=trace`. without a
+/// log_level RUST_LOG replaces the mode default. lines are not cut to the
+/// terminal width and json records don't merge the vote record fields.
+pub fn init(simple_mode: bool, log_level: Option<&str>, format: LogFormat) {
+    let default_filters = default_filters(simple_mode);
+    let (filter, handle) = reload::Layer::new(env_filter(log_level.unwrap_or(&default_filters)));
+    let output = match format {
+        LogFormat::Json => fmt::layer().json().with_writer(std::io::stderr).boxed(),
+        LogFormat::Text => fmt::layer().with_writer(std::io::stderr).boxed(),
+    };
+    let console_enabled = std::env::var(TOKIO_CONSOLE_ENV).is_ok_and(|value| value == "1");
+    let console = console_enabled.then(|| console_subscriber::spawn().with_filter(EnvFilter::new(CONSOLE_FILTER)));
+
+    let installed = tracing_subscriber::registry()
+        .with(output.with_filter(filter))
+        .with(console)
+        .try_init()
+        .is_ok();
+    if installed {
+        let _ = FILTERS.set(Filters {
+            handle,
+            default_filters: RwLock::new(default_filters),
+            filters: RwLock::new(log_level.map(str::to_string)),
+        });
+        if console_enabled {
+            log::info!("tokio-console server listening on 127.0.0.1:6669");
+        }
+    }
+}
+
+/// see `logging::set_log_filters`
+pub fn set_log_filters(log_level: Option<&str>) {
+    let Some(filters) = FILTERS.get() else { return };
+    *filters.filters.write().unwrap_or_else(PoisonError::into_inner) = log_level.map(str::to_string);
+    filters.rebuild();
+}
+
+/// see `logging::set_log_mode`
+pub fn set_log_mode(simple_mode: bool) {
+    let Some(filters) = FILTERS.get() else { return };
+    *filters.default_filters.write().unwrap_or_else(PoisonError::into_inner) = default_filters(simple_mode);
+    filters.rebuild();
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, vote::state::VoteState};
+
+use crate::error::{Result, VoteMonitorError};
+use crate::performance::Slot;
+use crate::rpc::RpcClient;
+use crate::vote_parse::VOTE_PROGRAM_ID;
+
+/// decode the on-chain vote state from raw vote account data
+pub fn parse_vote_state(data: &[u8]) -> Result<VoteState> {
+    VoteState::deserialize(data)
+        .map_err(|e| VoteMonitorError::VoteParsing(format!("failed to deserialize vote account: {}", e)))
+}
+
+/// authorized voter change observed on the monitored vote account
+#[derive(Debug, Clone)]
+pub struct AuthorizedVoterChange {
+    pub previous: Option<Pubkey>,
+    pub current: Pubkey,
+    pub slot: Slot,
+}
+
+/// follows the authorized voter of the monitored vote account
+///
+/// votes confirmed at or after `changed_at_slot` were cast by the current voter.
+#[derive(Debug, Default, Clone)]
+pub struct AuthorizedVoterTracker {
+    pub current: Option<Pubkey>,
+    pub changed_at_slot: Option<Slot>,
+    pub changes: u64,
+}
+
+impl AuthorizedVoterTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// update from a vote account snapshot, returning the change if the voter moved
+    ///
+    /// the voter is the one authorized for `epoch`; a voter queued for a later
+    /// epoch only counts once that epoch starts.
+    pub fn update(&mut self, vote_state: &VoteState, slot: Slot, epoch: u64) -> Option<AuthorizedVoterChange> {
+        let voter = vote_state.authorized_voters().get_authorized_voter(epoch)?;
+        if self.current == Some(voter) {
+            return None;
+        }
+
+        let previous = self.current.replace(voter);
+        // the first snapshot only establishes the baseline
+        previous?;
+
+        self.changed_at_slot = Some(slot);
+        self.changes += 1;
+
+        Some(AuthorizedVoterChange { previous, current: voter, slot })
+    }
+
+    /// whether a vote for `slot` was cast before the last voter change
+    pub fn is_before_change(&self, slot: Slot) -> bool {
+        self.changed_at_slot.is_some_and(|changed_at| slot < changed_at)
+    }
+}
+
+/// validator identity and commission of the monitored vote account (rpc_url)
+#[derive(Debug, Clone, Serialize)]
+pub struct VoteAccountInfo {
+    pub identity: String,
+    pub commission: u8,
+}
+
+#[derive(Deserialize)]
+struct AccountInfo {
+    value: Option<RpcAccount>,
+}
+
+#[derive(Deserialize)]
+struct RpcAccount {
+    owner: String,
+    data: Value,
+}
+
+/// check over json-rpc that `vote_account` exists and is a vote account
+///
+/// a missing account or one owned by another program is a config error,
+/// rpc failures come back as `VoteMonitorError::Rpc` so callers can carry on
+/// without the check.
+pub async fn verify_vote_account(rpc: &RpcClient, vote_account: &str) -> Result<VoteAccountInfo> {
+    let account: AccountInfo = rpc
+        .call("getAccountInfo", json!([vote_account, { "encoding": "jsonParsed", "commitment": "finalized" }]))
+        .await?;
+
+    let Some(account) = account.value else {
+        return Err(VoteMonitorError::Config(format!(
+            "vote account {} does not exist on {}, check vote_account and that rpc_url is on the same cluster",
+            vote_account, rpc.url()
+        )));
+    };
+
+    let vote_program = Pubkey::new_from_array(VOTE_PROGRAM_ID).to_string();
+    if account.owner != vote_program {
+        return Err(VoteMonitorError::Config(format!(
+            "{} is owned by {}, not the vote program; vote_account must be the vote account, not the validator identity",
+            vote_account, account.owner
+        )));
+    }
+
+    let info = account.data.pointer("/parsed/info");
+    let identity = info.and_then(|info| info.get("nodePubkey")).and_then(Value::as_str);
+    let commission = info
+        .and_then(|info| info.get("commission"))
+        .and_then(Value::as_u64)
+        .and_then(|commission| u8::try_from(commission).ok());
+    match (identity, commission) {
+        (Some(identity), Some(commission)) => Ok(VoteAccountInfo { identity: identity.to_string(), commission }),
+        _ => Err(VoteMonitorError::Rpc(format!("getAccountInfo returned no parsed vote state for {}", vote_account))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        clock::Clock,
+        vote::state::{VoteInit, VoteStateVersions},
+    };
+
+    /// round-trips the state through the account data the stream delivers
+    fn vote_account_state(vote_state: VoteState) -> VoteState {
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data).unwrap();
+        parse_vote_state(&data).unwrap()
+    }
+
+    #[test]
+    fn queued_voter_counts_from_its_epoch() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let init = VoteInit { node_pubkey: Pubkey::new_unique(), authorized_voter: old, ..VoteInit::default() };
+        let mut vote_state = VoteState::new(&init, &Clock::default());
+        vote_state.set_new_authorized_voter(&new, 0, 2, |_| Ok(())).unwrap();
+        let vote_state = vote_account_state(vote_state);
+
+        let mut tracker = AuthorizedVoterTracker::new();
+        assert!(tracker.update(&vote_state, 100, 1).is_none());
+        assert_eq!(tracker.current, Some(old));
+
+        // the same snapshot once the queued epoch starts
+        let change = tracker.update(&vote_state, 900, 2).unwrap();
+        assert_eq!(change.previous, Some(old));
+        assert_eq!(change.current, new);
+        assert_eq!(tracker.changed_at_slot, Some(900));
+    }
+}
@@ -0,0 +1,327 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+use crate::config::{VoteLogConfig, VoteLogFormat, VoteLogRotation};
+use crate::error::Result;
+use crate::loss_cause::LossCause;
+use crate::performance::{ConfirmationKind, ConfirmationSource, ConfirmedVote};
+
+/// first column of the header, identifies header lines of any column set
+pub const CSV_HEADER_PREFIX: &str = "timestamp,";
+
+pub const CSV_HEADER: &str = "timestamp,voted_slot,finalized_slot,latency,tvc_credits,signature,estimated,source,landed_slot,confirmed_latency,finalization_delay_ms,loss_cause,blockhash,parent_slot,block_height,kind,before_voter_change\n";
+
+/// csv and database spelling of a confirmation source, the same as in json
+pub(crate) fn source_name(source: ConfirmationSource) -> &'static str {
+    match source {
+        ConfirmationSource::Block => "block",
+        ConfirmationSource::SlotStatus => "slot_status",
+        ConfirmationSource::VoteState => "vote_state",
+    }
+}
+
+pub(crate) fn parse_source(name: &str) -> Option<ConfirmationSource> {
+    match name {
+        "block" => Some(ConfirmationSource::Block),
+        "slot_status" => Some(ConfirmationSource::SlotStatus),
+        "vote_state" => Some(ConfirmationSource::VoteState),
+        _ => None,
+    }
+}
+
+/// one exported row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteLogRecord {
+    pub timestamp: String,
+    pub voted_slot: u64,
+    pub finalized_slot: u64,
+    pub latency: u64,
+    pub tvc_credits: u64,
+    pub signature: Arc<String>,
+    /// `kind.is_estimated()`, kept for readers of the older column set
+    pub estimated: bool,
+    pub source: ConfirmationSource,
+    /// empty in csv and null in jsonl when the transaction was never matched,
+    /// missing in files written before it was added
+    #[serde(default)]
+    pub landed_slot: Option<u64>,
+    /// set with commitment "confirmed" or "both" when the vote was seen in a
+    /// confirmed block, empty or null otherwise
+    #[serde(default)]
+    pub confirmed_latency: Option<u64>,
+    #[serde(default)]
+    pub finalization_delay_ms: Option<u64>,
+    /// empty or null for optimal votes, see `LossCause`
+    #[serde(default)]
+    pub loss_cause: Option<LossCause>,
+    /// the finalized block the vote was confirmed from, empty or null
+    /// without one
+    #[serde(default)]
+    pub blockhash: Option<Arc<str>>,
+    #[serde(default)]
+    pub parent_slot: Option<u64>,
+    #[serde(default)]
+    pub block_height: Option<u64>,
+    /// see `ConfirmationKind`, missing in files written before it was added;
+    /// those read as inferred from estimated and source
+    #[serde(default)]
+    pub kind: Option<ConfirmationKind>,
+    /// cast by the previous authorized voter, false in files written before
+    /// it was added
+    #[serde(default)]
+    pub before_voter_change: bool,
+}
+
+/// an optional trailing csv column, missing and empty are both none
+fn optional_column(field: Option<&str>) -> Option<Option<u64>> {
+    match field {
+        None | Some("") => Some(None),
+        Some(value) => value.parse().ok().map(Some),
+    }
+}
+
+/// the trailing loss cause column, missing and empty are both none
+fn optional_cause(field: Option<&str>) -> Option<Option<LossCause>> {
+    match field {
+        None | Some("") => Some(None),
+        Some(name) => LossCause::parse(name).map(Some),
+    }
+}
+
+fn optional_cell(value: Option<u64>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+impl From<&ConfirmedVote> for VoteLogRecord {
+    fn from(vote: &ConfirmedVote) -> Self {
+        Self {
+            timestamp: vote.timestamp.with_timezone(&Utc).to_rfc3339(),
+            voted_slot: vote.voted_slot,
+            finalized_slot: vote.finalized_slot,
+            latency: vote.latency,
+            tvc_credits: vote.tvc_credits,
+            signature: vote.signature.clone(),
+            estimated: vote.kind.is_estimated(),
+            source: vote.source,
+            landed_slot: vote.landed_slot,
+            confirmed_latency: vote.confirmed_latency,
+            finalization_delay_ms: vote.finalization_delay_ms,
+            loss_cause: vote.loss_cause,
+            blockhash: vote.blockhash.clone(),
+            parent_slot: vote.parent_slot,
+            block_height: vote.block_height,
+            kind: Some(vote.kind),
+            before_voter_change: vote.before_voter_change,
+        }
+    }
+}
+
+impl From<VoteLogRecord> for ConfirmedVote {
+    fn from(record: VoteLogRecord) -> Self {
+        Self {
+            timestamp: DateTime::parse_from_rfc3339(&record.timestamp)
+                .map(|timestamp| timestamp.with_timezone(&Local))
+                .unwrap_or_default(),
+            signature: record.signature,
+            voted_slot: record.voted_slot,
+            finalized_slot: record.finalized_slot,
+            latency: record.latency,
+            tvc_credits: record.tvc_credits,
+            kind: record.kind.unwrap_or_else(|| ConfirmationKind::infer(record.estimated, record.source)),
+            source: record.source,
+            confirmation_ms: None,
+            block_time_delta_ms: None,
+            landed_slot: record.landed_slot,
+            confirmed_latency: record.confirmed_latency,
+            finalization_delay_ms: record.finalization_delay_ms,
+            loss_cause: record.loss_cause,
+            blockhash: record.blockhash,
+            parent_slot: record.parent_slot,
+            block_height: record.block_height,
+            before_voter_change: record.before_voter_change,
+        }
+    }
+}
+
+impl VoteLogRecord {
+    /// parse a row written by `to_csv_line`, none for the header or malformed rows.
+    /// rows without the trailing landed_slot, confirmed, loss cause, block, kind or voter change columns are accepted.
+    pub fn from_csv_line(line: &str) -> Option<Self> {
+        let mut fields = line.trim_end().split(',');
+        let record = Self {
+            timestamp: fields.next()?.to_string(),
+            voted_slot: fields.next()?.parse().ok()?,
+            finalized_slot: fields.next()?.parse().ok()?,
+            latency: fields.next()?.parse().ok()?,
+            tvc_credits: fields.next()?.parse().ok()?,
+            signature: Arc::new(fields.next()?.to_string()),
+            estimated: fields.next()?.parse().ok()?,
+            source: parse_source(fields.next()?)?,
+            landed_slot: optional_column(fields.next())?,
+            confirmed_latency: optional_column(fields.next())?,
+            finalization_delay_ms: optional_column(fields.next())?,
+            loss_cause: optional_cause(fields.next())?,
+            blockhash: fields.next().filter(|hash| !hash.is_empty()).map(Arc::from),
+            parent_slot: optional_column(fields.next())?,
+            block_height: optional_column(fields.next())?,
+            kind: match fields.next() {
+                None | Some("") => None,
+                Some(name) => Some(ConfirmationKind::parse(name)?),
+            },
+            before_voter_change: match fields.next() {
+                None | Some("") => false,
+                Some(flag) => flag.parse().ok()?,
+            },
+        };
+        fields.next().is_none().then_some(record)
+    }
+
+    fn to_csv_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            self.timestamp, self.voted_slot, self.finalized_slot, self.latency,
+            self.tvc_credits, self.signature, self.estimated, source_name(self.source),
+            optional_cell(self.landed_slot), optional_cell(self.confirmed_latency),
+            optional_cell(self.finalization_delay_ms),
+            self.loss_cause.map_or("", LossCause::name),
+            self.blockhash.as_deref().unwrap_or_default(), optional_cell(self.parent_slot),
+            optional_cell(self.block_height), self.kind.map_or("", |kind| kind.name()),
+            self.before_voter_change
+        )
+    }
+}
+
+/// batched writer appending confirmed votes to daily rotated files
+pub struct VoteLogWriter {
+    config: VoteLogConfig,
+    buffer: Vec<VoteLogRecord>,
+    last_flush: Instant,
+}
+
+impl VoteLogWriter {
+    pub fn new(config: VoteLogConfig) -> Self {
+        Self {
+            buffer: Vec::with_capacity(config.batch_size),
+            config,
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub async fn add_vote(&mut self, vote: &ConfirmedVote) -> Result<()> {
+        self.buffer.push(VoteLogRecord::from(vote));
+
+        if self.buffer.len() >= self.config.batch_size ||
+           self.last_flush.elapsed() >= Duration::from_secs(self.config.flush_interval_secs) {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn current_file(&self) -> PathBuf {
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        let filename = match self.config.rotate {
+            VoteLogRotation::Daily => format!("votes_{}.{}", today, self.config.format.extension()),
+        };
+        self.config.path.join(filename)
+    }
+
+    pub async fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        tokio::fs::create_dir_all(&self.config.path).await?;
+        let filename = self.current_file();
+        let is_new_file = !tokio::fs::try_exists(&filename).await.unwrap_or(false);
+
+        let mut batch = String::with_capacity(self.buffer.len() * 192);
+        match self.config.format {
+            VoteLogFormat::Csv => {
+                if is_new_file {
+                    batch.push_str(CSV_HEADER);
+                }
+                for record in &self.buffer {
+                    batch.push_str(&record.to_csv_line());
+                }
+            }
+            VoteLogFormat::Jsonl => {
+                for record in &self.buffer {
+                    batch.push_str(&serde_json::to_string(record)?);
+                    batch.push('\n');
+                }
+            }
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&filename)
+            .await?;
+
+        file.write_all(batch.as_bytes()).await?;
+        file.flush().await?;
+
+        self.buffer.clear();
+        self.last_flush = Instant::now();
+
+        Ok(())
+    }
+}
+
+/// spawn the vote log writer task
+///
+/// flushes on batch size, on interval, and once more when a shutdown signal
+/// arrives or every sender is dropped.
+pub fn spawn_vote_log_writer(
+    config: VoteLogConfig,
+    mut votes: mpsc::Receiver<ConfirmedVote>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut flush_interval = tokio::time::interval(Duration::from_secs(config.flush_interval_secs.max(1)));
+        let mut writer = VoteLogWriter::new(config);
+
+        loop {
+            tokio::select! {
+                vote = votes.recv() => {
+                    match vote {
+                        Some(vote) => {
+                            if let Err(e) = writer.add_vote(&vote).await {
+                                log::error!("error writing vote log: {}", e);
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                _ = flush_interval.tick() => {
+                    if let Err(e) = writer.flush().await {
+                        log::error!("error flushing vote log: {}", e);
+                    }
+                }
+                _ = shutdown.changed() => {
+                    // drain whatever is already queued before the final flush
+                    while let Ok(vote) = votes.try_recv() {
+                        if let Err(e) = writer.add_vote(&vote).await {
+                            log::error!("error writing vote log: {}", e);
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        if let Err(e) = writer.flush().await {
+            log::error!("error flushing vote log on shutdown: {}", e);
+        }
+        log::info!("vote log writer stopped");
+    })
+}
@@ -0,0 +1,301 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_sdk::{program_utils::limited_deserialize, vote::instruction::VoteInstruction};
+use yellowstone_grpc_proto::prelude::{CompiledInstruction, Transaction};
+
+use crate::error::{Result, VoteMonitorError};
+use crate::performance::Slot;
+use crate::tower_root::TowerUpdate;
+
+// for verification
+pub const VOTE_PROGRAM_ID: [u8; 32] = [
+    7, 97, 72, 29, 53, 116, 116, 187, 124, 77, 118, 36, 235, 211, 189, 179,
+    216, 53, 94, 115, 209, 16, 67, 252, 13, 163, 83, 128, 0, 0, 0, 0
+];
+
+/// vote instruction a voted slot came from, the switch variants included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteInstructionKind {
+    Vote,
+    VoteStateUpdate,
+    CompactVoteStateUpdate,
+    TowerSync,
+}
+
+impl VoteInstructionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Vote => "vote",
+            Self::VoteStateUpdate => "vote_state_update",
+            Self::CompactVoteStateUpdate => "compact_vote_state_update",
+            Self::TowerSync => "tower_sync",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteSlotInfo {
+    pub slot: Slot,
+    pub confirmation_count: Option<u32>,
+    pub kind: VoteInstructionKind,
+}
+
+impl VoteSlotInfo {
+    pub fn new(slot: Slot, confirmation_count: Option<u32>, kind: VoteInstructionKind) -> Self {
+        Self { slot, confirmation_count, kind }
+    }
+
+    /// check if this is a new vote (confirmation_count == 1)
+    pub fn is_new_vote(&self) -> bool {
+        self.confirmation_count == Some(1)
+    }
+
+    /// check if this is an existing vote (confirmation_count > 1)
+    pub fn is_existing_vote(&self) -> bool {
+        self.confirmation_count.is_some_and(|count| count > 1)
+    }
+}
+
+/// voted slots and tower root of one vote instruction
+#[derive(Debug, Clone, Default)]
+pub struct ParsedVote {
+    pub slots: Vec<VoteSlotInfo>,
+    /// root of a vote state update or tower sync, none for a plain vote or an
+    /// empty root
+    pub root: Option<Slot>,
+    /// none for the instructions that vote on nothing
+    pub kind: Option<VoteInstructionKind>,
+}
+
+impl ParsedVote {
+    /// root and newest voted slot, none without both
+    pub fn tower_update(&self) -> Option<TowerUpdate> {
+        Some(TowerUpdate {
+            root: self.root?,
+            latest_voted: self.slots.iter().map(|vote| vote.slot).max()?,
+        })
+    }
+}
+
+/// parse vote instruction data to extract vote slot information
+///
+/// extract the slots being voted on along with their confirmation counts.
+/// instructions that carry no vote (authorize, withdraw, commission updates)
+/// yield no slots; only data that fails to deserialize, such as a variant
+/// added after this build, is an error.
+pub fn parse_vote_instruction_data(data: &[u8]) -> Result<ParsedVote> {
+    match limited_deserialize::<VoteInstruction>(data) {
+        Ok(vote_instruction) => {
+            let parsed = match vote_instruction {
+                VoteInstruction::Vote(vote) | VoteInstruction::VoteSwitch(vote, _) => ParsedVote {
+                    slots: vote.slots.into_iter()
+                        .map(|slot| VoteSlotInfo::new(slot, Some(1), VoteInstructionKind::Vote))
+                        .collect(),
+                    root: None,
+                    kind: Some(VoteInstructionKind::Vote),
+                },
+                VoteInstruction::UpdateVoteState(vote_state_update)
+                | VoteInstruction::UpdateVoteStateSwitch(vote_state_update, _) => {
+                    lockouts_vote(vote_state_update.lockouts, vote_state_update.root, VoteInstructionKind::VoteStateUpdate)
+                }
+                VoteInstruction::CompactUpdateVoteState(vote_state_update)
+                | VoteInstruction::CompactUpdateVoteStateSwitch(vote_state_update, _) => {
+                    lockouts_vote(vote_state_update.lockouts, vote_state_update.root, VoteInstructionKind::CompactVoteStateUpdate)
+                }
+                VoteInstruction::TowerSync(tower_sync)
+                | VoteInstruction::TowerSyncSwitch(tower_sync, _) => {
+                    lockouts_vote(tower_sync.lockouts, tower_sync.root, VoteInstructionKind::TowerSync)
+                }
+                // account management, nothing voted
+                _ => ParsedVote::default(),
+            };
+
+            Ok(parsed)
+        }
+        Err(e) => Err(VoteMonitorError::VoteParsing(format!("failed to deserialize vote instruction: {}", e))),
+    }
+}
+
+fn lockouts_vote(
+    lockouts: impl IntoIterator<Item = solana_sdk::vote::state::Lockout>,
+    root: Option<Slot>,
+    kind: VoteInstructionKind,
+) -> ParsedVote {
+    ParsedVote {
+        slots: lockouts.into_iter()
+            .map(|lockout| VoteSlotInfo::new(lockout.slot(), Some(lockout.confirmation_count()), kind))
+            .collect(),
+        root,
+        kind: Some(kind),
+    }
+}
+
+/// the instruction votes with `vote_account`, which every vote instruction
+/// takes as its first account
+pub(crate) fn votes_for(instruction: &CompiledInstruction, account_keys: &[Vec<u8>], vote_account: &[u8; 32]) -> bool {
+    instruction.accounts
+        .first()
+        .and_then(|&index| account_keys.get(index as usize))
+        .is_some_and(|key| key.as_slice() == vote_account)
+}
+
+/// the votes of one transaction, see `decode_vote_transaction`
+#[derive(Debug, Clone)]
+pub struct ParsedVoteTx {
+    /// first signature, base58
+    pub signature: String,
+    /// vote account of the first instruction that votes
+    pub vote_account: Option<Pubkey>,
+    /// every slot of every vote instruction, in instruction order
+    pub voted_slots: Vec<VoteSlotInfo>,
+    /// highest tower root among the instructions
+    pub root: Option<Slot>,
+    /// kind of the first instruction that votes, none when the vote
+    /// instructions only manage the account
+    pub instruction_kind: Option<VoteInstructionKind>,
+}
+
+impl ParsedVoteTx {
+    /// the new votes, confirmation count 1
+    pub fn new_votes(&self) -> impl Iterator<Item = Slot> + '_ {
+        self.voted_slots.iter().filter(|vote| vote.is_new_vote()).map(|vote| vote.slot)
+    }
+
+    /// whether the transaction votes with `vote_account`
+    pub fn votes_for(&self, vote_account: &Pubkey) -> bool {
+        self.vote_account.as_ref() == Some(vote_account)
+    }
+}
+
+/// decode the vote instructions of a transaction from a yellowstone update
+///
+/// needs neither a tracker nor a runtime. a transaction without a vote
+/// program instruction, without a 64 byte first signature or with a vote
+/// instruction that does not deserialize is an error.
+pub fn decode_vote_transaction(transaction: &Transaction) -> Result<ParsedVoteTx> {
+    let message = transaction.message.as_ref()
+        .ok_or_else(|| VoteMonitorError::VoteParsing("transaction without a message".to_string()))?;
+    let account_keys: Vec<&[u8]> = message.account_keys.iter().map(Vec::as_slice).collect();
+    let instructions = message.instructions.iter().map(|instruction| InstructionRef {
+        program_id_index: instruction.program_id_index as usize,
+        accounts: &instruction.accounts,
+        data: &instruction.data,
+    });
+    decode(transaction.signatures.first().map(Vec::as_slice), &account_keys, instructions)
+}
+
+/// `decode_vote_transaction` for a transaction in wire format, as sent to
+/// the leader and stored in packet captures
+///
+/// legacy and v0 transactions are read, keys from address lookup tables are
+/// not resolved. vote instructions only use static keys.
+pub fn decode_vote_transaction_bytes(bytes: &[u8]) -> Result<ParsedVoteTx> {
+    let transaction = limited_deserialize::<VersionedTransaction>(bytes)
+        .map_err(|e| VoteMonitorError::VoteParsing(format!("failed to deserialize transaction: {}", e)))?;
+    let account_keys: Vec<&[u8]> = transaction.message.static_account_keys().iter().map(|key| key.as_ref()).collect();
+    let instructions = transaction.message.instructions().iter().map(|instruction| InstructionRef {
+        program_id_index: instruction.program_id_index as usize,
+        accounts: &instruction.accounts,
+        data: &instruction.data,
+    });
+    decode(transaction.signatures.first().map(|signature| signature.as_ref()), &account_keys, instructions)
+}
+
+/// a compiled instruction of either transaction representation
+struct InstructionRef<'a> {
+    program_id_index: usize,
+    accounts: &'a [u8],
+    data: &'a [u8],
+}
+
+fn decode<'a>(
+    signature: Option<&[u8]>,
+    account_keys: &[&[u8]],
+    instructions: impl Iterator<Item = InstructionRef<'a>>,
+) -> Result<ParsedVoteTx> {
+    let signature: [u8; 64] = signature
+        .and_then(|signature| signature.try_into().ok())
+        .ok_or_else(|| VoteMonitorError::VoteParsing("transaction without a valid signature".to_string()))?;
+    let mut parsed = ParsedVoteTx {
+        signature: fd_bs58::encode_64(signature),
+        vote_account: None,
+        voted_slots: Vec::new(),
+        root: None,
+        instruction_kind: None,
+    };
+
+    let mut vote_instructions = 0;
+    for instruction in instructions {
+        if account_keys.get(instruction.program_id_index) != Some(&VOTE_PROGRAM_ID.as_slice()) {
+            continue;
+        }
+        vote_instructions += 1;
+        let vote = parse_vote_instruction_data(instruction.data)?;
+        let Some(kind) = vote.kind else { continue };
+        if parsed.instruction_kind.is_none() {
+            parsed.instruction_kind = Some(kind);
+            parsed.vote_account = instruction.accounts
+                .first()
+                .and_then(|&index| account_keys.get(index as usize))
+                .and_then(|key| Pubkey::try_from(*key).ok());
+        }
+        parsed.root = parsed.root.max(vote.root);
+        parsed.voted_slots.extend(vote.slots);
+    }
+
+    if vote_instructions == 0 {
+        return Err(VoteMonitorError::VoteParsing("no vote program instruction".to_string()));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::vote::instruction as vote_instruction;
+    use solana_sdk::vote::state::VoteAuthorize;
+
+    use crate::test_support::tower_sync_data;
+
+    #[test]
+    fn account_management_instructions_vote_on_nothing() {
+        let vote_account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let instructions = [
+            ("authorize voter", vote_instruction::authorize(&vote_account, &authority, &Pubkey::new_unique(), VoteAuthorize::Voter)),
+            ("authorize withdrawer", vote_instruction::authorize(&vote_account, &authority, &Pubkey::new_unique(), VoteAuthorize::Withdrawer)),
+            ("withdraw", vote_instruction::withdraw(&vote_account, &authority, 1_000, &Pubkey::new_unique())),
+            ("update commission", vote_instruction::update_commission(&vote_account, &authority, 5)),
+            ("update identity", vote_instruction::update_validator_identity(&vote_account, &authority, &Pubkey::new_unique())),
+        ];
+        for (name, instruction) in instructions {
+            let parsed = parse_vote_instruction_data(&instruction.data).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert!(parsed.slots.is_empty(), "{}", name);
+            assert_eq!((parsed.kind, parsed.root), (None, None), "{}", name);
+            assert!(parsed.tower_update().is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn unknown_discriminant_is_an_error() {
+        // a variant added after this build, followed by some payload
+        let mut data = 99u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(matches!(parse_vote_instruction_data(&data), Err(VoteMonitorError::VoteParsing(_))));
+        assert!(parse_vote_instruction_data(&[]).is_err());
+        // a known discriminant with a truncated payload
+        let truncated = tower_sync_data(&[(100, 1)]);
+        assert!(parse_vote_instruction_data(&truncated[..8]).is_err());
+    }
+
+    #[test]
+    fn tower_sync_new_and_existing_votes() {
+        let parsed = parse_vote_instruction_data(&tower_sync_data(&[(98, 3), (99, 2), (100, 1)])).unwrap();
+        assert_eq!(parsed.kind, Some(VoteInstructionKind::TowerSync));
+        let new: Vec<Slot> = parsed.slots.iter().filter(|vote| vote.is_new_vote()).map(|vote| vote.slot).collect();
+        let existing = parsed.slots.iter().filter(|vote| vote.is_existing_vote()).count();
+        assert_eq!((new, existing), (vec![100], 2));
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use log::{info, warn};
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::task::JoinHandle;
+use yellowstone_grpc_proto::geyser::SubscribeUpdateBlock;
+
+use crate::channel_metrics::PipelineMetrics;
+use crate::commitment::Commitment;
+use crate::config::{Config, VoteSourceKind};
+use crate::endpoints::EndpointPool;
+use crate::error::Result;
+use crate::events::EventBus;
+use crate::grpc;
+use crate::message::SystemEvent;
+use crate::performance::PerformanceStats;
+use crate::pipeline::{
+    create_confirmed_blocks_request, create_subscription_request, spawn_resubscribing_stream_task, UpdateSenders,
+};
+use crate::rpc_source::RpcVoteSource;
+use crate::stream_health::StreamHealth;
+
+/// what a source is started with
+#[derive(Debug)]
+pub struct SourceContext {
+    pub config: Arc<Config>,
+    /// the channels the transaction and block tasks read
+    pub senders: UpdateSenders,
+    pub stats: Arc<RwLock<PerformanceStats>>,
+    pub health: Arc<StreamHealth>,
+    pub events: EventBus,
+    /// closes the source, which drops the senders
+    pub stop: watch::Receiver<bool>,
+}
+
+/// tasks of a started source
+#[derive(Debug)]
+pub struct RunningSource {
+    /// feeds the update channels, the pipeline winds down once it ends
+    pub stream: JoinHandle<()>,
+    /// confirmed blocks of `commitment = "both"`, observed by the block task
+    pub confirmed_blocks: Option<mpsc::Receiver<SubscribeUpdateBlock>>,
+    /// helper tasks, aborted on shutdown
+    pub background: Vec<JoinHandle<()>>,
+}
+
+/// where the updates of the processing tasks come from
+///
+/// a source sends the same geyser updates into `SourceContext::senders` as
+/// the grpc stream task, the transaction and block tasks don't know which
+/// one runs. `Monitor::start` starts it.
+pub trait VoteSource: Debug + Send + Sync {
+    fn kind(&self) -> VoteSourceKind;
+
+    /// connect and spawn the task feeding the channels, an error when the
+    /// first connection fails
+    fn start(&self, context: SourceContext) -> BoxFuture<'static, Result<RunningSource>>;
+}
+
+/// the source `config.source` selects
+pub fn vote_source(config: &Config) -> Result<Box<dyn VoteSource>> {
+    Ok(match config.source {
+        VoteSourceKind::Grpc => Box::new(GrpcSource),
+        VoteSourceKind::Rpc => Box::new(RpcVoteSource::new(config)?),
+    })
+}
+
+/// the geyser subscription, with failover across the grpc endpoints
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrpcSource;
+
+impl VoteSource for GrpcSource {
+    fn kind(&self) -> VoteSourceKind {
+        VoteSourceKind::Grpc
+    }
+
+    /// the grpc endpoints are tried in order. when none accepts the
+    /// subscription `StreamDisconnected` is published.
+    fn start(&self, context: SourceContext) -> BoxFuture<'static, Result<RunningSource>> {
+        Box::pin(async move {
+            let SourceContext { config, senders, stats, health, events, stop } = context;
+
+            // the first endpoint that accepts the subscription, in failover order
+            let endpoints = Arc::new(EndpointPool::new(config.grpc_endpoints()));
+            let mut attempts = endpoints.len();
+            let (subscribe_tx, stream) = loop {
+                let endpoint = endpoints.active();
+                let request = create_subscription_request(
+                    &config.vote_account,
+                    config.follow_authorized_voter,
+                    config.commitment.subscription_level(),
+                    config.confirmation_source,
+                );
+                match grpc::subscribe(&config, &endpoint, request).await {
+                    Ok(connection) => break connection,
+                    // the next endpoint may accept what this one rejected, even a token
+                    Err(e) if attempts > 1 => {
+                        attempts -= 1;
+                        warn!("cannot subscribe to {}: {}", endpoint, e);
+                        endpoints.fail_over();
+                    }
+                    Err(e) => {
+                        events.publish(SystemEvent::StreamDisconnected { reason: e.to_string() });
+                        return Err(e);
+                    }
+                }
+            };
+            endpoints.mark_connected();
+
+            info!("connected to gRPC stream at {}, processing votes...", endpoints.active());
+            events.publish(SystemEvent::StreamConnected { endpoint: endpoints.active() });
+            stats.write().await.endpoints = Some(endpoints.clone());
+
+            let mut background = Vec::new();
+            let confirmed_blocks = match config.commitment {
+                Commitment::Both => {
+                    spawn_confirmed_stream(&config, &senders, &endpoints, stop.clone()).await.map(|(blocks, task)| {
+                        background.push(task);
+                        blocks
+                    })
+                }
+                Commitment::Finalized | Commitment::Confirmed => None,
+            };
+
+            // a stale subscription is replaced (see spawn_stream_watchdog), with
+            // several endpoints a failed one too, on the next endpoint
+            let failover = endpoints.len() > 1;
+            let resubscribe = {
+                let config = config.clone();
+                let events = events.clone();
+                move || {
+                    let config = config.clone();
+                    let events = events.clone();
+                    let endpoints = endpoints.clone();
+                    async move {
+                        let (endpoint, penalized_for) = endpoints.fail_over();
+                        if !penalized_for.is_zero() {
+                            info!("every grpc endpoint failed recently, waiting {}s for {}", penalized_for.as_secs(), endpoint);
+                            tokio::time::sleep(penalized_for).await;
+                        }
+                        let request = create_subscription_request(
+                            &config.vote_account,
+                            config.follow_authorized_voter,
+                            config.commitment.subscription_level(),
+                            config.confirmation_source,
+                        );
+                        let connection = grpc::subscribe(&config, &endpoint, request).await?;
+                        endpoints.mark_connected();
+                        events.publish(SystemEvent::StreamConnected { endpoint });
+                        Ok(connection)
+                    }
+                }
+            };
+            let stream = spawn_resubscribing_stream_task((subscribe_tx, stream), resubscribe, failover, senders, health, events, stop);
+            Ok(RunningSource { stream, confirmed_blocks, background })
+        })
+    }
+}
+
+/// the second subscription of `commitment = "both"`, confirmed blocks
+/// from the active endpoint
+///
+/// it follows the main stream: it stops with it and reconnects to
+/// whichever endpoint is active. its disconnects are only logged, none
+/// when it cannot subscribe and votes go without confirmed figures.
+async fn spawn_confirmed_stream(
+    config: &Arc<Config>,
+    senders: &UpdateSenders,
+    endpoints: &Arc<EndpointPool>,
+    stop: watch::Receiver<bool>,
+) -> Option<(mpsc::Receiver<SubscribeUpdateBlock>, JoinHandle<()>)> {
+    let connect = {
+        let config = config.clone();
+        let endpoints = endpoints.clone();
+        move || {
+            let config = config.clone();
+            let endpoint = endpoints.active();
+            async move {
+                let connection = grpc::subscribe(&config, &endpoint, create_confirmed_blocks_request(&config.vote_account)).await?;
+                info!("subscribed to confirmed blocks at {}", endpoint);
+                Ok(connection)
+            }
+        }
+    };
+    let connection = match connect().await {
+        Ok(connection) => connection,
+        Err(e) => {
+            warn!("cannot subscribe to confirmed blocks, votes carry no confirmed latency: {}", e);
+            return None;
+        }
+    };
+
+    let capacity = senders.metrics.blocks.capacity();
+    let (blocks_tx, blocks_rx) = mpsc::channel(capacity);
+    // shares the main transaction sender, whose closing stops the retries
+    let confirmed_senders = UpdateSenders {
+        blocks: blocks_tx,
+        metrics: Arc::new(PipelineMetrics::new(capacity)),
+        stale_block_drop_depth: None,
+        ..senders.clone()
+    };
+    let task = spawn_resubscribing_stream_task(
+        connection,
+        connect,
+        true,
+        confirmed_senders,
+        Arc::new(StreamHealth::new()),
+        // disconnects of the second stream stay off the main event bus
+        EventBus::new(1),
+        stop,
+    );
+    Some((blocks_rx, task))
+}
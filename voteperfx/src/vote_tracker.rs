@@ -1,40 +1,28 @@
+use std::collections::BTreeSet;
+use std::mem::size_of;
 use std::sync::Arc;
 use std::time::Instant;
 
 use chrono::{DateTime, Local};
 use rustc_hash::{FxHashMap, FxHashSet};
-use solana_sdk::{program_utils::limited_deserialize, vote::instruction::VoteInstruction};
+use serde::Serialize;
+use yellowstone_grpc_proto::convert_from::create_tx_error;
+use yellowstone_grpc_proto::geyser::SlotStatus;
+use yellowstone_grpc_proto::prelude::TransactionStatusMeta;
 
-use crate::performance::{ConfirmedVote, Slot, calculate_tvc_credits};
+use crate::config::TvcConfig;
+use crate::performance::{
+    ConfirmationKind, ConfirmationSource, ConfirmedVote, Slot, calculate_tvc_credits, calculate_tvc_credits_from_latency,
+};
 use crate::error::{Result, VoteMonitorError};
-
-// for verification
-pub const VOTE_PROGRAM_ID: [u8; 32] = [
-    7, 97, 72, 29, 53, 116, 116, 187, 124, 77, 118, 36, 235, 211, 189, 179, 
-    216, 53, 94, 115, 209, 16, 67, 252, 13, 163, 83, 128, 0, 0, 0, 0
-];
-
-#[derive(Debug, Clone)]
-pub struct VoteSlotInfo {
-    pub slot: Slot,
-    pub confirmation_count: Option<u32>,
-}
-
-impl VoteSlotInfo {
-    pub fn new(slot: Slot, confirmation_count: Option<u32>) -> Self {
-        Self { slot, confirmation_count }
-    }
-    
-    /// check if this is a new vote (confirmation_count == 1)
-    pub fn is_new_vote(&self) -> bool {
-        self.confirmation_count == Some(1)
-    }
-    
-    /// check if this is an existing vote (confirmation_count > 1)
-    pub fn is_existing_vote(&self) -> bool {
-        self.confirmation_count.is_some_and(|count| count > 1)
-    }
-}
+use crate::finalized_slots::{FinalizedSlots, SlotAdmission};
+use crate::memory::{MemoryUsage, signature_bytes};
+use crate::ring_buffer::RingBuffer;
+use crate::tower_root::TowerUpdate;
+use crate::vote_parse::votes_for;
+// parsing moved to vote_parse, the old paths keep working
+pub use crate::vote_parse::{parse_vote_instruction_data, ParsedVote, VoteSlotInfo, VOTE_PROGRAM_ID};
+use crate::vote_tracker_actor::VoteTrackerHandle;
 
 /// pending vote awaiting confirmation in a finalized block
 #[derive(Debug, Clone)]
@@ -44,6 +32,25 @@ pub struct PendingVote {
     pub transaction_slot: Slot,
     pub timestamp: DateTime<Local>,
     pub instruction_data: Vec<u8>,
+    /// voted slots already matched in a finalized block
+    pub confirmed_slots: FxHashSet<Slot>,
+}
+
+impl PendingVote {
+    /// wall-clock milliseconds since the transaction was seen
+    pub fn elapsed_ms(&self) -> u64 {
+        (Local::now() - self.timestamp).num_milliseconds().max(0) as u64
+    }
+    
+    /// `block_time` in unix seconds minus when the transaction was seen
+    pub fn block_time_delta_ms(&self, block_time: Option<i64>) -> Option<i64> {
+        Some(block_time?.saturating_mul(1000) - self.timestamp.timestamp_millis())
+    }
+    
+    /// all voted slots have been matched
+    pub fn is_fully_confirmed(&self) -> bool {
+        self.confirmed_slots.len() >= self.voted_slots.len()
+    }
 }
 
 /// signature cache - avoid encoding
@@ -61,13 +68,12 @@ impl SignatureCache {
         }
     }
     
-    pub fn get_or_insert(&mut self, signature_bytes: &[u8]) -> Arc<String> {
-        // ensure we have exactly 64 bytes
-        let mut key = [0u8; 64];
-        key.copy_from_slice(&signature_bytes[..64.min(signature_bytes.len())]);
+    /// base58 of a 64 byte signature, none for any other length
+    pub fn get_or_insert(&mut self, signature_bytes: &[u8]) -> Option<Arc<String>> {
+        let key: [u8; 64] = signature_bytes.try_into().ok()?;
         
         if let Some(cached) = self.cache.get(&key) {
-            return cached.clone();
+            return Some(cached.clone());
         }
         
         // lru eviction if needed
@@ -78,62 +84,156 @@ impl SignatureCache {
             }
         }
         
-        let signature = Arc::new(fd_bs58::encode_64(&key));
+        let signature = Arc::new(fd_bs58::encode_64(key));
         self.cache.insert(key, signature.clone());
-        signature
+        Some(signature)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
     }
 }
 
-/// circular buffer for confirmed votes
-#[derive(Debug)]
-pub struct CircularBuffer<T> {
-    data: Vec<Option<T>>,
-    head: usize,
-    tail: usize,
-    size: usize,
-    capacity: usize,
+/// how long a block confirmation without a matching pending vote is kept
+/// before falling back to the pessimistic direct calculation
+pub const UNMATCHED_CONFIRMATION_WINDOW_SLOTS: u64 = 150;
+
+/// block confirmation seen before its vote transaction
+#[derive(Debug, Clone)]
+pub struct UnmatchedConfirmation {
+    pub voted_slots: Vec<Slot>,
+    pub finalized_slot: Slot,
+    /// unix seconds of the confirming block, when the endpoint sends it
+    pub block_time: Option<i64>,
+}
+
+/// default number of slots behind the latest finalized slot before an
+/// unconfirmed pending vote is declared missed
+pub const DEFAULT_MISSED_VOTE_CUTOFF_SLOTS: u64 = 150;
+
+/// confirmed votes retained by the tracker unless configured
+pub const DEFAULT_CONFIRMED_CAPACITY: usize = 100;
+
+/// voted slots remembered behind the newest confirmed one to catch a second
+/// confirmation, unless configured
+pub const DEFAULT_DUPLICATE_WINDOW_SLOTS: u64 = 512;
+
+/// finalized slots between two sweeps of the pending votes, unless configured
+pub const DEFAULT_PENDING_CLEANUP_INTERVAL_SLOTS: u64 = 32;
+
+/// slots behind the newest finalized slot before a pending vote is dropped by
+/// the sweep, unless configured
+pub const DEFAULT_PENDING_MAX_AGE_SLOTS: u64 = 300;
+
+/// pending votes held before the oldest are evicted, unless configured
+pub const DEFAULT_MAX_PENDING_VOTES: usize = 10_000;
+
+/// voted slots confirmed recently, across signatures
+///
+/// overlapping tower updates or a resent vote can land the same voted slot in
+/// two transactions. the vote program credits a slot once, so only the first
+/// confirmation counts. slots more than `window` behind the newest confirmed
+/// one are forgotten.
+#[derive(Debug, Clone)]
+pub struct ConfirmedSlotSet {
+    recent: BTreeSet<Slot>,
+    highest: Slot,
+    window: u64,
+    duplicates: u64,
+}
+
+impl Default for ConfirmedSlotSet {
+    fn default() -> Self {
+        Self::new(DEFAULT_DUPLICATE_WINDOW_SLOTS)
+    }
 }
 
-impl<T: Clone> CircularBuffer<T> {
-    pub fn new(capacity: usize) -> Self {
+impl ConfirmedSlotSet {
+    pub fn new(window: u64) -> Self {
         Self {
-            data: vec![None; capacity],
-            head: 0,
-            tail: 0,
-            size: 0,
-            capacity,
+            recent: BTreeSet::new(),
+            highest: 0,
+            window,
+            duplicates: 0,
         }
     }
-    
-    pub fn push(&mut self, item: T) {
-        self.data[self.tail] = Some(item);
-        self.tail = (self.tail + 1) % self.capacity;
-        
-        if self.size < self.capacity {
-            self.size += 1;
-        } else {
-            // overwrite oldest
-            self.head = (self.head + 1) % self.capacity;
+
+    /// record a confirmation of `slot`, false when it was already confirmed
+    pub fn first_confirmation(&mut self, slot: Slot) -> bool {
+        if !self.recent.insert(slot) {
+            self.duplicates += 1;
+            return false;
+        }
+        if slot > self.highest {
+            self.highest = slot;
+            let floor = slot.saturating_sub(self.window);
+            if self.recent.first().is_some_and(|&oldest| oldest < floor) {
+                self.recent = self.recent.split_off(&floor);
+            }
         }
+        true
     }
-    
+
+    /// whether `slot` was confirmed within the window
+    pub fn contains(&self, slot: Slot) -> bool {
+        self.recent.contains(&slot)
+    }
+
+    /// second and later confirmations of a voted slot, not counted as votes
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
     pub fn len(&self) -> usize {
-        self.size
+        self.recent.len()
     }
-    
-    pub fn iter(&self) -> impl Iterator<Item = &T> {
-        let mut idx = self.head;
-        let mut count = 0;
-        std::iter::from_fn(move || {
-            if count >= self.size {
-                return None;
-            }
-            let item = self.data[idx].as_ref();
-            idx = (idx + 1) % self.capacity;
-            count += 1;
-            item
-        })
+
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
     }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.recent.len() * size_of::<Slot>()
+    }
+}
+
+
+/// pending vote that never appeared in a finalized block
+#[derive(Debug, Clone, Serialize)]
+pub struct MissedVote {
+    pub signature: Arc<String>,
+    pub voted_slots: Vec<Slot>,
+    pub transaction_slot: Slot,
+    pub age_slots: u64,
+}
+
+/// vote transaction that landed in a block but failed, it earns no credits
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedVote {
+    pub signature: String,
+    pub transaction_slot: Slot,
+    pub voted_slots: Vec<Slot>,
+    pub error: String,
+    pub timestamp: DateTime<Local>,
+}
+
+/// outcome of a vote transaction from the grpc stream
+#[derive(Debug, Default)]
+pub struct ProcessedVoteTransaction {
+    /// votes confirmed immediately because their block was already seen
+    pub confirmed: Vec<ConfirmedVote>,
+    pub failed: Option<FailedVote>,
+    /// root and newest voted slot of the landed tower, see `TowerRoot`
+    pub tower: Option<TowerUpdate>,
+    /// a vote transaction for another vote account, ignored
+    pub foreign: bool,
+    /// a vote transaction of the monitored account added a pending vote,
+    /// counted as sent, see `VotesSent`
+    pub pending_added: bool,
 }
 
 /// vote correlation tracker
@@ -143,211 +243,790 @@ pub struct VoteTracker {
     // awaiting confirmation (signature -> pendingvote)
     pending_votes: FxHashMap<Arc<String>, PendingVote>,
     
-    // recently confirmed votes for analysis (circular buffer)
-    confirmed_votes: CircularBuffer<ConfirmedVote>,
+    // recently confirmed votes for analysis
+    confirmed_votes: RingBuffer<ConfirmedVote>,
+    
+    // finalized slots already processed, see FinalizedSlots
+    processed_slots: FinalizedSlots,
+    
+    // voted slots already confirmed by any signature, see ConfirmedSlotSet
+    confirmed_slots: ConfirmedSlotSet,
     
-    // track processed slots
-    processed_slots: CircularBuffer<Slot>,
+    // block confirmations that arrived before their transaction (signature -> confirmation)
+    unmatched_confirmations: FxHashMap<Arc<String>, UnmatchedConfirmation>,
     
     // signature cache
     signature_cache: SignatureCache,
     
-    // state for cleanup
-    last_cleanup_slot: Slot,
-    last_cleanup_time: Instant,
-    pending_count: usize,
+    // expired pending votes awaiting collection (see take_missed_votes)
+    missed_votes: Vec<MissedVote>,
+    missed_vote_cutoff_slots: u64,
+    tvc: TvcConfig,
+    
+    // vote program instructions that failed to deserialize, logged once per
+    // discriminant (none when shorter than one)
+    parse_failures: u64,
+    unknown_discriminants: FxHashSet<Option<u32>>,
+    
+    // transactions skipped for a signature that is not 64 bytes
+    malformed_signatures: u64,
+    
+    // vote transactions whose slots were all confirmed already
+    replayed_transactions: u64,
+    
+    // sweep of old pending votes, driven by finalized slots, see cleanup_pending_if_due
+    cleanup_interval_slots: u64,
+    pending_max_age_slots: u64,
+    last_cleanup_slot: Option<Slot>,
+    last_cleanup: Option<Instant>,
+    
+    // hard cap on pending votes, 0 disables it
+    max_pending_votes: usize,
+    evicted_pending_votes: u64,
+    
+    // block height of the newest finalized block, see record_block_height
+    newest_block_height: Option<u64>,
+    blocks_without_vote: u64,
+}
+
+impl Default for VoteTracker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VoteTracker {
     pub fn new() -> Self {
         Self {
             pending_votes: FxHashMap::with_capacity_and_hasher(1024, Default::default()),
-            confirmed_votes: CircularBuffer::new(100),
-            processed_slots: CircularBuffer::new(50),
+            confirmed_votes: RingBuffer::new(DEFAULT_CONFIRMED_CAPACITY),
+            processed_slots: FinalizedSlots::default(),
+            confirmed_slots: ConfirmedSlotSet::default(),
+            unmatched_confirmations: FxHashMap::with_capacity_and_hasher(256, Default::default()),
             signature_cache: SignatureCache::new(2048),
-            last_cleanup_slot: 0,
-            last_cleanup_time: Instant::now(),
-            pending_count: 0,
+            missed_votes: Vec::new(),
+            missed_vote_cutoff_slots: DEFAULT_MISSED_VOTE_CUTOFF_SLOTS,
+            tvc: TvcConfig::default(),
+            parse_failures: 0,
+            unknown_discriminants: FxHashSet::default(),
+            malformed_signatures: 0,
+            replayed_transactions: 0,
+            cleanup_interval_slots: DEFAULT_PENDING_CLEANUP_INTERVAL_SLOTS,
+            pending_max_age_slots: DEFAULT_PENDING_MAX_AGE_SLOTS,
+            last_cleanup_slot: None,
+            last_cleanup: None,
+            max_pending_votes: DEFAULT_MAX_PENDING_VOTES,
+            evicted_pending_votes: 0,
+            newest_block_height: None,
+            blocks_without_vote: 0,
         }
     }
     
+    /// slots behind the latest finalized slot before a pending vote is declared missed
+    pub fn with_missed_vote_cutoff(mut self, cutoff_slots: u64) -> Self {
+        self.missed_vote_cutoff_slots = cutoff_slots;
+        self
+    }
+    
+    /// confirmed votes retained by the tracker
+    pub fn with_confirmed_capacity(mut self, capacity: usize) -> Self {
+        self.confirmed_votes = RingBuffer::new(capacity.max(1));
+        self
+    }
+    
+    /// slots behind the newest finalized slot before a block is rejected as stale
+    pub fn with_block_horizon(mut self, horizon_slots: u64) -> Self {
+        self.processed_slots = FinalizedSlots::new(horizon_slots);
+        self
+    }
+    
+    /// slots behind the newest confirmed voted slot a second confirmation is still caught
+    pub fn with_duplicate_window(mut self, window_slots: u64) -> Self {
+        self.confirmed_slots = ConfirmedSlotSet::new(window_slots);
+        self
+    }
+    
+    /// credit parameters used when converting latency to tvc
+    pub fn with_tvc_config(mut self, tvc: TvcConfig) -> Self {
+        self.tvc = tvc;
+        self
+    }
+    
+    /// sweep the pending votes every `interval_slots` finalized slots, dropping
+    /// those more than `max_age_slots` behind
+    pub fn with_pending_cleanup(mut self, interval_slots: u64, max_age_slots: u64) -> Self {
+        self.cleanup_interval_slots = interval_slots.max(1);
+        self.pending_max_age_slots = max_age_slots;
+        self
+    }
+    
+    /// pending votes held before the oldest are evicted, 0 disables the cap
+    pub fn with_max_pending_votes(mut self, max_pending_votes: usize) -> Self {
+        self.max_pending_votes = max_pending_votes;
+        self
+    }
+    
     /// awaiting confirmation
+    /// 
+    /// if the finalized block carrying this vote was already seen, the buffered
+    /// confirmations are matched immediately and returned.
     #[inline]
-    pub fn add_pending_vote(&mut self, pending: PendingVote) {
-        self.pending_votes.insert(pending.signature.clone(), pending);
-        self.pending_count += 1;
+    pub fn add_pending_vote(&mut self, mut pending: PendingVote) -> Vec<ConfirmedVote> {
+        // resent by another endpoint after a failover, its block is rejected
+        // as a duplicate so it would only ever expire as a missed vote
+        if !pending.voted_slots.is_empty() && pending.voted_slots.iter().all(|&slot| self.confirmed_slots.contains(slot)) {
+            self.replayed_transactions += 1;
+            log::debug!("skipping vote transaction for already confirmed slots (sig: {})", &pending.signature[..8]);
+            return Vec::new();
+        }
         
-        // time-based cleanup to prevent memory growth (every 60 seconds)
-        if self.last_cleanup_time.elapsed().as_secs() >= 60 {
-            self.cleanup_old_pending();
+        let mut confirmed_votes = Vec::new();
+        
+        if let Some(unmatched) = self.unmatched_confirmations.remove(&pending.signature) {
+            for voted_slot in unmatched.voted_slots {
+                if !pending.voted_slots.contains(&voted_slot) || !pending.confirmed_slots.insert(voted_slot) {
+                    continue;
+                }
+                let kind = confirmation_kind(&mut self.confirmed_slots, voted_slot, ConfirmationKind::Matched, &pending.signature);
+                
+                // the vote landed in the transaction slot, not whenever the block showed up
+                let latency = pending.transaction_slot.saturating_sub(voted_slot);
+                let tvc_credits = calculate_tvc_credits_from_latency(latency, &self.tvc);
+                
+                log::debug!(
+                    "late vote match: slot {} -> landed {} -> latency {} -> {} tvc (sig: {})",
+                    voted_slot, pending.transaction_slot, latency, tvc_credits,
+                    &pending.signature[..8]
+                );
+                
+                let confirmed = ConfirmedVote {
+                    signature: pending.signature.clone(),
+                    voted_slot,
+                    finalized_slot: unmatched.finalized_slot,
+                    latency,
+                    tvc_credits,
+                    timestamp: Local::now(),
+                    kind,
+                    source: ConfirmationSource::Block,
+                    // the block was seen before the transaction, there is no wall-clock delta
+                    confirmation_ms: None,
+                    block_time_delta_ms: pending.block_time_delta_ms(unmatched.block_time),
+                    landed_slot: Some(pending.transaction_slot),
+                    confirmed_latency: None,
+                    finalization_delay_ms: None,
+                    loss_cause: None,
+                    blockhash: None,
+                    parent_slot: None,
+                    block_height: None,
+                    before_voter_change: false,
+                };
+                if kind.is_counted() {
+                    self.confirmed_votes.push(confirmed.clone());
+                }
+                confirmed_votes.push(confirmed);
+            }
+        }
+        
+        if !pending.is_fully_confirmed() {
+            self.pending_votes.insert(pending.signature.clone(), pending);
+            self.enforce_pending_cap();
         }
+        
+        confirmed_votes
+    }
+    
+    /// evict the oldest pending votes by transaction slot beyond `max_pending_votes`
+    /// 
+    /// without finalized slots, e.g. a stalled block stream, nothing else
+    /// bounds the pending votes.
+    fn enforce_pending_cap(&mut self) {
+        if self.max_pending_votes == 0 || self.pending_votes.len() <= self.max_pending_votes {
+            return;
+        }
+        let excess = self.pending_votes.len() - self.max_pending_votes;
+        let mut oldest: Vec<(Slot, Arc<String>)> = self.pending_votes.iter()
+            .map(|(signature, pending)| (pending.transaction_slot, signature.clone()))
+            .collect();
+        oldest.select_nth_unstable(excess - 1);
+        for (_, signature) in oldest.into_iter().take(excess) {
+            self.pending_votes.remove(&signature);
+        }
+        
+        if self.evicted_pending_votes == 0 {
+            log::warn!(
+                "more than {} pending votes, evicting the oldest (tracker.max_pending_votes); later evictions are only counted",
+                self.max_pending_votes
+            );
+        }
+        self.evicted_pending_votes += excess as u64;
     }
     
     /// attempt to confirm a vote from a finalized block
     /// 
-    /// returns Some(ConfirmedVote) if the vote was successfully confirmed,
-    /// none if no matching pending vote was found.
+    /// returns Some(ConfirmedVote) if the vote was successfully confirmed, of
+    /// kind duplicate_suppressed when another signature confirmed the slot
+    /// first; none if no matching pending vote was found. unmatched confirmations are
+    /// buffered until the transaction arrives or the window expires.
+    /// the pending vote is removed once all of its voted slots are confirmed.
     #[inline]
-    pub fn confirm_vote(&mut self, signature: Arc<String>, voted_slot: Slot, finalized_slot: Slot) -> Option<ConfirmedVote> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "debug", skip(self, signature, block_time), fields(signature = %signature)
+    ))]
+    pub fn confirm_vote(
+        &mut self,
+        signature: Arc<String>,
+        voted_slot: Slot,
+        finalized_slot: Slot,
+        block_time: Option<i64>,
+    ) -> Option<ConfirmedVote> {
         // validate slot ordering
         if finalized_slot < voted_slot {
             log::warn!("invalid slot order: finalized_slot {} < voted_slot {}", finalized_slot, voted_slot);
             return None;
         }
         
-        if let Some(pending) = self.pending_votes.get(&signature) {
+        if let Some(pending) = self.pending_votes.get_mut(&signature) {
             // verify this voted_slot was actually in the original pending vote
-            if pending.voted_slots.contains(&voted_slot) {
-                // remove the pending vote and create confirmed vote
-                self.pending_votes.remove(&signature);
-                self.pending_count -= 1;
-                
-                // calculate vote latency: finalized_slot - voted_slot
-                let latency = finalized_slot.saturating_sub(voted_slot);
-                let tvc_credits = crate::performance::calculate_tvc_credits_from_latency(latency);
-                
-                let confirmed = ConfirmedVote {
-                    signature: (*signature).clone(),
-                    voted_slot,
-                    finalized_slot,
-                    latency,
-                    tvc_credits,
-                    timestamp: Local::now(),
-                };
-                
-                // use circular buffer for o(1) operations
-                self.confirmed_votes.push(confirmed.clone());
-                
-                Some(confirmed)
-            } else {
+            if !pending.voted_slots.contains(&voted_slot) {
                 // voted_slot not in original pending vote - no confirmation
                 log::debug!("voted slot {} not found in pending slots {:?} for signature {}", 
                            voted_slot, pending.voted_slots, &signature[..8]);
-                None
+                return None;
             }
-        } else {
-            // no pending vote found - create direct confirmation
-            // this happens when we see the confirmation before the transaction. fix me.
-            let (latency, tvc_credits) = calculate_tvc_credits(voted_slot, finalized_slot);
             
-            log::debug!(
-                "direct vote confirmation: slot {} → block {} → latency {} → {} tvc (no pending)",
-                voted_slot, finalized_slot, latency, tvc_credits
-            );
+            if !pending.confirmed_slots.insert(voted_slot) {
+                log::debug!("voted slot {} already confirmed for signature {}", voted_slot, &signature[..8]);
+                return None;
+            }
+            
+            let confirmation_ms = pending.elapsed_ms();
+            let block_time_delta_ms = pending.block_time_delta_ms(block_time);
+            let landed_slot = pending.transaction_slot;
+            
+            // drop the pending vote once every voted slot has been matched
+            if pending.is_fully_confirmed() {
+                self.pending_votes.remove(&signature);
+            }
+            
+            let kind = confirmation_kind(&mut self.confirmed_slots, voted_slot, ConfirmationKind::Matched, &signature);
             
-            // create confirmed vote even without pending match
-            Some(ConfirmedVote {
-                signature: (*signature).clone(),
+            // calculate vote latency: finalized_slot - voted_slot
+            let latency = finalized_slot.saturating_sub(voted_slot);
+            let tvc_credits = calculate_tvc_credits_from_latency(latency, &self.tvc);
+            
+            let confirmed = ConfirmedVote {
+                signature,
                 voted_slot,
                 finalized_slot,
                 latency,
                 tvc_credits,
                 timestamp: Local::now(),
-            })
+                kind,
+                source: ConfirmationSource::Block,
+                confirmation_ms: Some(confirmation_ms),
+                block_time_delta_ms,
+                landed_slot: Some(landed_slot),
+                confirmed_latency: None,
+                finalization_delay_ms: None,
+                loss_cause: None,
+                blockhash: None,
+                parent_slot: None,
+                block_height: None,
+                before_voter_change: false,
+            };
+            
+            // use circular buffer for o(1) operations
+            if kind.is_counted() {
+                self.confirmed_votes.push(confirmed.clone());
+            }
+            
+            Some(confirmed)
+        } else {
+            // no pending vote found - the block outran the transaction stream.
+            // keep the voted slots per signature until the transaction shows up.
+            let unmatched = self.unmatched_confirmations
+                .entry(signature)
+                .or_insert_with(|| UnmatchedConfirmation { voted_slots: Vec::new(), finalized_slot, block_time });
+            if !unmatched.voted_slots.contains(&voted_slot) {
+                unmatched.voted_slots.push(voted_slot);
+            }
+            None
         }
     }
     
-    #[inline]
-    pub fn has_processed_slot(&self, slot: Slot) -> bool {
-        self.processed_slots.iter().any(|&s| s == slot)
+    /// expire buffered block confirmations older than the matching window
+    /// 
+    /// falls back to the pessimistic direct calculation (finalized_slot - voted_slot)
+    /// and flags the resulting votes as direct_block_only.
+    pub fn expire_unmatched_confirmations(&mut self, current_slot: Slot) -> Vec<ConfirmedVote> {
+        let cutoff_slot = current_slot.saturating_sub(UNMATCHED_CONFIRMATION_WINDOW_SLOTS);
+        let mut expired = Vec::new();
+        let confirmed_slots = &mut self.confirmed_slots;
+        
+        self.unmatched_confirmations.retain(|signature, unmatched| {
+            if unmatched.finalized_slot > cutoff_slot {
+                return true;
+            }
+            
+            for &voted_slot in &unmatched.voted_slots {
+                let kind = confirmation_kind(confirmed_slots, voted_slot, ConfirmationKind::DirectBlockOnly, signature);
+                let (latency, tvc_credits) = calculate_tvc_credits(voted_slot, unmatched.finalized_slot, &self.tvc);
+                
+                log::debug!(
+                    "direct vote confirmation: slot {} → block {} → latency {} → {} tvc (no pending, estimated)",
+                    voted_slot, unmatched.finalized_slot, latency, tvc_credits
+                );
+                
+                expired.push(ConfirmedVote {
+                    signature: signature.clone(),
+                    voted_slot,
+                    finalized_slot: unmatched.finalized_slot,
+                    latency,
+                    tvc_credits,
+                    timestamp: Local::now(),
+                    kind,
+                    source: ConfirmationSource::Block,
+                    // direct path, the transaction was never seen
+                    confirmation_ms: None,
+                    block_time_delta_ms: None,
+                    landed_slot: None,
+                    confirmed_latency: None,
+                    finalization_delay_ms: None,
+                    loss_cause: None,
+                    blockhash: None,
+                    parent_slot: None,
+                    block_height: None,
+                    before_voter_change: false,
+                });
+            }
+            false
+        });
+        
+        for confirmed in expired.iter().filter(|vote| vote.kind.is_counted()) {
+            self.confirmed_votes.push(confirmed.clone());
+        }
+        
+        expired
     }
     
-    #[inline]
-    pub fn mark_slot_processed(&mut self, slot: Slot) {
-        self.processed_slots.push(slot);
+    /// confirm pending votes whose transaction slot reached finalized status
+    /// 
+    /// fallback for endpoints without block subscriptions. the transaction slot
+    /// stands in for the block slot, so latency matches the block path as long
+    /// as the transaction was not on a dropped fork.
+    pub fn confirm_by_finalized_slot(&mut self, finalized_slot: Slot) -> Vec<ConfirmedVote> {
+        self.confirm_by_transaction_slot(|slot| slot <= finalized_slot, ConfirmationSource::SlotStatus)
+    }
+
+    /// confirm pending votes that landed in a finalized block the stream dropped
+    ///
+    /// estimated from the transaction slot like `confirm_by_finalized_slot`,
+    /// but only for that slot: older blocks may still be queued and confirm
+    /// their own votes.
+    pub fn confirm_dropped_block(&mut self, slot: Slot) -> Vec<ConfirmedVote> {
+        self.confirm_by_transaction_slot(|transaction_slot| transaction_slot == slot, ConfirmationSource::Block)
+    }
+
+    /// estimated confirmations of the pending votes whose transaction slot is `final_slot`
+    fn confirm_by_transaction_slot(
+        &mut self,
+        final_slot: impl Fn(Slot) -> bool,
+        source: ConfirmationSource,
+    ) -> Vec<ConfirmedVote> {
+        let mut confirmed_votes = Vec::new();
+        let confirmed_slots = &mut self.confirmed_slots;
+        
+        self.pending_votes.retain(|signature, pending| {
+            if !final_slot(pending.transaction_slot) {
+                return true;
+            }
+            
+            let mut voted_slots: Vec<Slot> = pending.voted_slots
+                .difference(&pending.confirmed_slots)
+                .copied()
+                .collect();
+            voted_slots.sort_unstable();
+            
+            for voted_slot in voted_slots {
+                let kind = confirmation_kind(confirmed_slots, voted_slot, ConfirmationKind::EstimatedTxSlot, signature);
+                let (latency, tvc_credits) = calculate_tvc_credits(voted_slot, pending.transaction_slot, &self.tvc);
+                
+                log::debug!(
+                    "tx slot confirmation: slot {} -> tx slot {} -> latency {} -> {} tvc (sig: {})",
+                    voted_slot, pending.transaction_slot, latency, tvc_credits, &signature[..8]
+                );
+                
+                confirmed_votes.push(ConfirmedVote {
+                    signature: signature.clone(),
+                    voted_slot,
+                    finalized_slot: pending.transaction_slot,
+                    latency,
+                    tvc_credits,
+                    timestamp: Local::now(),
+                    kind,
+                    source,
+                    confirmation_ms: Some(pending.elapsed_ms()),
+                    block_time_delta_ms: None,
+                    landed_slot: Some(pending.transaction_slot),
+                    confirmed_latency: None,
+                    finalization_delay_ms: None,
+                    loss_cause: None,
+                    blockhash: None,
+                    parent_slot: None,
+                    block_height: None,
+                    before_voter_change: false,
+                });
+            }
+            false
+        });
+        
+        for confirmed in confirmed_votes.iter().filter(|vote| vote.kind.is_counted()) {
+            self.confirmed_votes.push(confirmed.clone());
+        }
+        
+        confirmed_votes
+    }
+    
+    /// record a finalized slot, returning whether its block should be processed
+    ///
+    /// `block_height` comes with blocks, not with slot statuses, see
+    /// `record_block_height`.
+    pub fn admit_finalized_slot(&mut self, slot: Slot, block_height: Option<u64>) -> SlotAdmission {
+        let admission = self.processed_slots.admit(slot);
+        if let Some(height) = block_height.filter(|_| admission.is_accepted()) {
+            self.record_block_height(height);
+        }
+        match admission {
+            SlotAdmission::New => {}
+            SlotAdmission::OutOfOrder { behind } => {
+                log::debug!("finalized slot {} arrived {} slots behind the newest", slot, behind);
+            }
+            SlotAdmission::Duplicate => {
+                log::debug!("skipping duplicate finalized slot {}", slot);
+            }
+            SlotAdmission::Stale { behind } => {
+                log::warn!(
+                    "finalized slots went backwards: skipping slot {}, {} slots behind the newest ({} slot horizon), stream replay after reconnect?",
+                    slot, behind, self.processed_slots.horizon()
+                );
+            }
+        }
+        admission
+    }
+    
+    /// count the finalized blocks below `height` that carried none of our votes
+    ///
+    /// block heights of finalized blocks have no gaps, skipped slots don't
+    /// take one. the subscription only delivers blocks with a vote of the
+    /// monitored account, so a gap is mostly blocks without one; blocks
+    /// dropped by the pipeline or lost in a reconnect cannot be told apart
+    /// and count too. a late block fills the gap it was counted in.
+    fn record_block_height(&mut self, height: u64) {
+        match self.newest_block_height {
+            Some(newest) if height > newest + 1 => {
+                let missing = height - newest - 1;
+                log::debug!("block height jumped from {} to {}, {} finalized blocks without our vote", newest, height, missing);
+                self.blocks_without_vote += missing;
+                self.newest_block_height = Some(height);
+            }
+            Some(newest) if height < newest => {
+                self.blocks_without_vote = self.blocks_without_vote.saturating_sub(1);
+            }
+            _ => self.newest_block_height = Some(height),
+        }
     }
     
     pub fn get_stats(&self) -> VoteTrackerStats {
         VoteTrackerStats {
-            pending_votes: self.pending_count,
+            pending_votes: self.pending_votes.len(),
+            unmatched_confirmations: self.unmatched_confirmations.len(),
             confirmed_votes: self.confirmed_votes.len(),
             processed_slots: self.processed_slots.len(),
+            out_of_order_blocks: self.processed_slots.out_of_order(),
+            duplicate_blocks: self.processed_slots.duplicates(),
+            stale_blocks: self.processed_slots.stale(),
+            duplicate_confirmations: self.confirmed_slots.duplicates(),
+            parse_failures: self.parse_failures,
+            malformed_signatures: self.malformed_signatures,
+            replayed_transactions: self.replayed_transactions,
+            evicted_pending_votes: self.evicted_pending_votes,
+            blocks_without_vote: self.blocks_without_vote,
+            pending_max_age_slots: self.pending_max_age_slots.max(self.missed_vote_cutoff_slots),
+            last_cleanup_slot: self.last_cleanup_slot,
+            last_cleanup_secs: self.last_cleanup.map(|at| at.elapsed().as_secs()),
+        }
+    }
+    
+    /// count a vote program instruction that failed to deserialize, most
+    /// likely a variant newer than this build
+    ///
+    /// `data` is the start of the instruction, logged at debug level the first
+    /// time its discriminant shows up.
+    pub fn record_parse_failure(&mut self, data: &[u8], error: &str) {
+        self.parse_failures += 1;
+        let discriminant = data.get(..4).map(|tag| u32::from_le_bytes([tag[0], tag[1], tag[2], tag[3]]));
+        if self.unknown_discriminants.insert(discriminant) {
+            let hex: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+            log::debug!(
+                "skipping unparsable vote instruction (discriminant {}, starts {}): {}; later ones with this discriminant are only counted",
+                discriminant.map_or("none".to_string(), |tag| tag.to_string()), hex, error
+            );
+        }
+    }
+    
+    /// declare pending votes that fell behind the missed-vote cutoff as missed
+    /// 
+    /// only voted slots that were never confirmed are reported.
+    pub fn expire_missed_votes(&mut self, finalized_slot: Slot) {
+        let cutoff_slot = finalized_slot.saturating_sub(self.missed_vote_cutoff_slots);
+        let missed_votes = &mut self.missed_votes;
+        
+        self.pending_votes.retain(|signature, pending| {
+            if pending.transaction_slot > cutoff_slot {
+                return true;
+            }
+            
+            let mut voted_slots: Vec<Slot> = pending.voted_slots
+                .difference(&pending.confirmed_slots)
+                .copied()
+                .collect();
+            voted_slots.sort_unstable();
+            
+            let age_slots = finalized_slot.saturating_sub(pending.transaction_slot);
+            log::debug!(
+                "missed vote: slots {:?} from tx slot {} not finalized after {} slots (sig: {})",
+                voted_slots, pending.transaction_slot, age_slots, &signature[..8]
+            );
+            
+            missed_votes.push(MissedVote {
+                signature: signature.clone(),
+                voted_slots,
+                transaction_slot: pending.transaction_slot,
+                age_slots,
+            });
+            false
+        });
+    }
+    
+    /// drain missed votes recorded since the last call
+    pub fn take_missed_votes(&mut self) -> Vec<MissedVote> {
+        std::mem::take(&mut self.missed_votes)
+    }
+    
+    /// remove every pending vote, for an account switch that will never see
+    /// them finalized. the voted slots not confirmed yet are reported with
+    /// their age behind the newest finalized slot, oldest transaction first
+    pub fn drain_pending(&mut self) -> Vec<MissedVote> {
+        let newest_slot = self.processed_slots.highest().unwrap_or_default();
+        let mut drained: Vec<MissedVote> = self.pending_votes
+            .drain()
+            .map(|(signature, pending)| {
+                let mut voted_slots: Vec<Slot> = pending.voted_slots
+                    .difference(&pending.confirmed_slots)
+                    .copied()
+                    .collect();
+                voted_slots.sort_unstable();
+                MissedVote {
+                    signature,
+                    voted_slots,
+                    transaction_slot: pending.transaction_slot,
+                    age_slots: newest_slot.saturating_sub(pending.transaction_slot),
+                }
+            })
+            .collect();
+        drained.sort_unstable_by_key(|vote| vote.transaction_slot);
+        drained
+    }
+    
+    /// sweep the pending votes once `cleanup_interval_slots` finalized slots
+    /// passed since the last sweep
+    pub fn cleanup_pending_if_due(&mut self, finalized_slot: Slot) {
+        let due = self.last_cleanup_slot
+            .map_or(true, |last| finalized_slot >= last.saturating_add(self.cleanup_interval_slots));
+        if due {
+            self.cleanup_old_pending();
         }
     }
     
-    fn cleanup_old_pending(&mut self) {
-        let current_slot = self.processed_slots.iter().last().cloned().unwrap_or(0);
-        let cutoff_slot = current_slot.saturating_sub(100);
+    /// drop pending votes more than `pending_max_age_slots` behind the newest
+    /// finalized slot, nothing before the first one
+    pub fn cleanup_old_pending(&mut self) {
+        // the newest admitted slot, an out of order one does not move the cutoff back
+        let Some(current_slot) = self.processed_slots.highest() else {
+            return;
+        };
+        // never drop votes before they had a chance to be declared missed
+        let cutoff_slot = current_slot.saturating_sub(self.pending_max_age_slots.max(self.missed_vote_cutoff_slots));
         
+        let before = self.pending_votes.len();
         self.pending_votes.retain(|_, pending| {
             pending.transaction_slot > cutoff_slot
         });
         
-        self.pending_count = self.pending_votes.len();
-        self.last_cleanup_slot = current_slot;
-        self.last_cleanup_time = Instant::now();
+        self.last_cleanup_slot = Some(current_slot);
+        self.last_cleanup = Some(Instant::now());
+        
+        log::debug!(
+            "cleaned up {} old pending votes at slot {}, {} remaining",
+            before - self.pending_votes.len(), current_slot, self.pending_votes.len()
+        );
+    }
+    
+    /// approximate memory held by the tracker, per owner
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage::new();
+        
+        // signature strings are shared with the cache, so only count them there
+        let pending_bytes: usize = self.pending_votes.values()
+            .map(|pending| {
+                size_of::<(Arc<String>, PendingVote)>()
+                    + (pending.voted_slots.capacity() + pending.confirmed_slots.capacity()) * size_of::<Slot>()
+                    + pending.instruction_data.capacity()
+            })
+            .sum();
+        usage.add("pending votes", self.pending_votes.len(), pending_bytes);
+        
+        let unmatched_bytes: usize = self.unmatched_confirmations.values()
+            .map(|unmatched| {
+                size_of::<(Arc<String>, UnmatchedConfirmation)>()
+                    + unmatched.voted_slots.capacity() * size_of::<Slot>()
+            })
+            .sum();
+        usage.add("unmatched confirmations", self.unmatched_confirmations.len(), unmatched_bytes);
+        
+        usage.add(
+            "signature cache",
+            self.signature_cache.len(),
+            self.signature_cache.len() * (64 + size_of::<Arc<String>>() + signature_bytes()),
+        );
+        
+        usage.add(
+            "confirmed ring",
+            self.confirmed_votes.len(),
+            self.confirmed_votes.allocated_bytes()
+                + self.confirmed_votes.len() * signature_bytes(),
+        );
+        
+        usage.add(
+            "confirmed slots",
+            self.confirmed_slots.len(),
+            self.confirmed_slots.allocated_bytes(),
+        );
         
-        log::debug!("cleaned up old pending votes, {} remaining", self.pending_count);
+        usage.add(
+            "processed slots",
+            self.processed_slots.len(),
+            self.processed_slots.allocated_bytes(),
+        );
+        
+        usage
     }
     
-    /// get cached signature or create new one
-    pub fn get_or_cache_signature(&mut self, signature_bytes: &[u8]) -> Arc<String> {
-        self.signature_cache.get_or_insert(signature_bytes)
+    /// get cached signature or create new one, none for a malformed
+    /// signature, whose transaction is to be skipped
+    pub fn get_or_cache_signature(&mut self, signature_bytes: &[u8]) -> Option<Arc<String>> {
+        let signature = self.signature_cache.get_or_insert(signature_bytes);
+        if signature.is_none() {
+            self.malformed_signatures += 1;
+            log::warn!(
+                "skipping transaction with a {} byte signature, expected 64 ({} so far)",
+                signature_bytes.len(), self.malformed_signatures
+            );
+        }
+        signature
     }
 }
 
-#[derive(Debug, Clone)]
+/// `kind` for the first confirmation of `voted_slot`, a duplicate when
+/// another signature carrying it was confirmed first
+fn confirmation_kind(
+    confirmed_slots: &mut ConfirmedSlotSet,
+    voted_slot: Slot,
+    kind: ConfirmationKind,
+    signature: &str,
+) -> ConfirmationKind {
+    if confirmed_slots.first_confirmation(voted_slot) {
+        return kind;
+    }
+    log::debug!("voted slot {} already confirmed by another signature (sig: {})", voted_slot, &signature[..8]);
+    ConfirmationKind::DuplicateSuppressed
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct VoteTrackerStats {
     pub pending_votes: usize,
+    pub unmatched_confirmations: usize,
     pub confirmed_votes: usize,
     pub processed_slots: usize,
+    /// finalized blocks accepted behind the newest one
+    pub out_of_order_blocks: u64,
+    /// finalized blocks skipped as already processed
+    pub duplicate_blocks: u64,
+    /// finalized blocks skipped as older than the horizon
+    pub stale_blocks: u64,
+    /// voted slots confirmed again under another signature, not counted as votes
+    pub duplicate_confirmations: u64,
+    /// vote program instructions skipped because they failed to deserialize
+    pub parse_failures: u64,
+    /// transactions skipped because their signature is not 64 bytes
+    pub malformed_signatures: u64,
+    /// vote transactions skipped because every voted slot was already confirmed
+    pub replayed_transactions: u64,
+    /// pending votes evicted over `tracker.max_pending_votes`
+    pub evicted_pending_votes: u64,
+    /// finalized blocks without our vote, from gaps in the block height of
+    /// the vote-filtered block stream, see `VoteTracker::record_block_height`
+    pub blocks_without_vote: u64,
+    /// slots a pending vote is kept behind the newest finalized slot, never
+    /// less than the missed-vote cutoff
+    pub pending_max_age_slots: u64,
+    /// newest finalized slot at the last sweep of the pending votes, none before the first
+    pub last_cleanup_slot: Option<Slot>,
+    /// seconds since that sweep
+    pub last_cleanup_secs: Option<u64>,
 }
 
-/// parse vote instruction data to extract vote slot information
-/// 
-/// extract the slots being voted on along with their confirmation counts.
-pub fn parse_vote_instruction_data(data: &[u8]) -> Result<Vec<VoteSlotInfo>> {
-    match limited_deserialize::<VoteInstruction>(data) {
-        Ok(vote_instruction) => {
-            use solana_sdk::vote::instruction::VoteInstruction;
-            
-            let vote_slots = match vote_instruction {
-                VoteInstruction::Vote(vote) | VoteInstruction::VoteSwitch(vote, _) => {
-                    vote.slots.into_iter().map(|slot| VoteSlotInfo::new(slot, Some(1))).collect()
-                }
-                VoteInstruction::UpdateVoteState(vote_state_update)
-                | VoteInstruction::UpdateVoteStateSwitch(vote_state_update, _)
-                | VoteInstruction::CompactUpdateVoteState(vote_state_update)
-                | VoteInstruction::CompactUpdateVoteStateSwitch(vote_state_update, _) => {
-                    vote_state_update.lockouts.into_iter().map(|lockout| {
-                        VoteSlotInfo::new(lockout.slot(), Some(lockout.confirmation_count()))
-                    }).collect()
-                }
-                VoteInstruction::TowerSync(tower_sync)
-                | VoteInstruction::TowerSyncSwitch(tower_sync, _) => {
-                    tower_sync.lockouts.into_iter().map(|lockout| {
-                        VoteSlotInfo::new(lockout.slot(), Some(lockout.confirmation_count()))
-                    }).collect()
-                }
-                _ => return Err(VoteMonitorError::VoteParsing("unknown vote instruction type".to_string())),
-            };
-            
-            Ok(vote_slots)
-        }
-        Err(e) => Err(VoteMonitorError::VoteParsing(format!("failed to deserialize vote instruction: {}", e))),
-    }
+/// readable error of a failed transaction, none when it succeeded
+fn transaction_error(meta: Option<&TransactionStatusMeta>) -> Option<String> {
+    let err = meta?.err.as_ref()?;
+    Some(match create_tx_error(Some(err)) {
+        Ok(Some(error)) => error.to_string(),
+        _ => "undecodable transaction error".to_string(),
+    })
 }
 
 /// process a vote transaction from the grpc stream
 /// 
 /// extracts vote information from transactions and adds
 /// pending votes to the tracker for later confirmation.
+/// failed transactions are reported instead and never become pending votes.
+/// vote instructions for another vote account are skipped, a transaction
+/// with nothing but those comes back as `foreign`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    level = "debug", skip_all, fields(slot = tx_update.slot)
+))]
 pub async fn process_vote_transaction(
     tx_update: yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction,
-    _vote_account: &str,
-    vote_tracker: &mut VoteTracker,
-) -> Result<()> {
+    vote_account: &[u8; 32],
+    vote_tracker: &VoteTrackerHandle,
+) -> Result<ProcessedVoteTransaction> {
+    let mut processed = ProcessedVoteTransaction::default();
     let transaction_slot = tx_update.slot;
     
     let transaction = tx_update.transaction
         .ok_or_else(|| VoteMonitorError::VoteParsing("empty transaction".to_string()))?;
     
     if !transaction.is_vote {
-        return Ok(());
+        return Ok(processed);
     }
     
-    let signature_bytes = &transaction.signature;
-    let signature_base58 = vote_tracker.get_or_cache_signature(signature_bytes);
+    // a malformed signature would key the vote wrongly, see SignatureCache
+    let Some(signature_base58) = vote_tracker.cache_signature(&transaction.signature).await? else {
+        return Ok(processed);
+    };
+    let error = transaction_error(transaction.meta.as_ref());
+    let mut failed_slots = Vec::new();
+    let (mut ours, mut foreign) = (false, false);
     
     log::debug!("processing vote transaction at slot {} (sig: {})", 
                transaction_slot, &signature_base58[..8]);
@@ -357,15 +1036,42 @@ pub async fn process_vote_transaction(
             for instruction in &message.instructions {
                 if let Some(program_account) = message.account_keys.get(instruction.program_id_index as usize) {
                     if program_account == &VOTE_PROGRAM_ID {
-                        let vote_slots = parse_vote_instruction_data(&instruction.data)?;
+                        if !votes_for(instruction, &message.account_keys, vote_account) {
+                            foreign = true;
+                            continue;
+                        }
+                        ours = true;
+                        let parsed = match parse_vote_instruction_data(&instruction.data) {
+                            Ok(parsed) => parsed,
+                            // the other instructions and the failure itself still count
+                            Err(e) => {
+                                vote_tracker.record_parse_failure(&instruction.data, &e).await?;
+                                continue;
+                            }
+                        };
+                        
+                        // a failed transaction changed nothing on chain
+                        if error.is_none() {
+                            if let Some(update) = parsed.tower_update() {
+                                processed.tower = Some(processed.tower.map_or(update, |tower| TowerUpdate {
+                                    root: tower.root.max(update.root),
+                                    latest_voted: tower.latest_voted.max(update.latest_voted),
+                                }));
+                            }
+                        }
                         
                         // confirmation_count == 1
-                        let new_voted_slots: FxHashSet<Slot> = vote_slots
+                        let new_voted_slots: FxHashSet<Slot> = parsed.slots
                             .into_iter()
                             .filter(|vote_info| vote_info.is_new_vote())
                             .map(|vote_info| vote_info.slot)
                             .collect();
                         
+                        if error.is_some() {
+                            failed_slots.extend(new_voted_slots);
+                            continue;
+                        }
+                        
                         if !new_voted_slots.is_empty() {
                             // create pending vote for tracking
                             let pending_vote = PendingVote {
@@ -374,9 +1080,11 @@ pub async fn process_vote_transaction(
                                 transaction_slot,
                                 timestamp: Local::now(),
                                 instruction_data: instruction.data.clone(),
+                                confirmed_slots: FxHashSet::default(),
                             };
                             
-                            vote_tracker.add_pending_vote(pending_vote);
+                            processed.confirmed.extend(vote_tracker.add_pending(pending_vote).await?);
+                            processed.pending_added = true;
                             
                             log::debug!(
                                 "added pending vote: {} new votes at slot {} (sig: {})",
@@ -389,65 +1097,155 @@ pub async fn process_vote_transaction(
         }
     }
     
-    Ok(())
+    processed.foreign = foreign && !ours;
+    if let Some(error) = error.filter(|_| ours) {
+        failed_slots.sort_unstable();
+        processed.failed = Some(FailedVote {
+            signature: signature_base58.to_string(),
+            transaction_slot,
+            voted_slots: failed_slots,
+            error,
+            timestamp: Local::now(),
+        });
+    }
+    
+    Ok(processed)
 }
 
 /// process a finalized block to confirm pending votes
 /// 
 /// examines finalized blocks for vote confirmations and
-/// returns a list of confirmed votes.
+/// returns a list of confirmed votes. the votes of other validators in the
+/// block are skipped without asking the tracker.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    level = "debug", skip_all, fields(slot = block_update.slot)
+))]
 pub async fn process_finalized_block(
     block_update: yellowstone_grpc_proto::geyser::SubscribeUpdateBlock,
-    vote_account: &str,
-    vote_tracker: &mut VoteTracker,
+    vote_account: &[u8; 32],
+    vote_tracker: &VoteTrackerHandle,
 ) -> Result<Vec<ConfirmedVote>> {
     let mut confirmed_votes = Vec::new();
     let finalized_slot = block_update.slot;
+    let block_time = block_update.block_time.map(|block_time| block_time.timestamp);
+    let block_height = block_update.block_height.map(|height| height.block_height);
     
-    if vote_tracker.has_processed_slot(finalized_slot) {
+    if !vote_tracker.admit_finalized_slot(finalized_slot, block_height).await?.is_accepted() {
         return Ok(confirmed_votes);
     }
     
-    vote_tracker.mark_slot_processed(finalized_slot);
-    
     log::debug!("processing finalized block at slot {}", finalized_slot);
     
     for tx_info in block_update.transactions {
+        // failed vote transactions are reported from the transaction stream
+        if tx_info.meta.as_ref().is_some_and(|meta| meta.err.is_some()) {
+            continue;
+        }
         if let Some(transaction) = tx_info.transaction {
             if let Some(signature_bytes) = transaction.signatures.first() {
-                let signature_base58 = vote_tracker.get_or_cache_signature(signature_bytes);
+                let Some(signature_base58) = vote_tracker.cache_signature(signature_bytes).await? else {
+                    continue;
+                };
                 
-                if let Some(confirmed) = process_transaction_in_block(
+                confirmed_votes.extend(process_transaction_in_block(
                     &transaction,
                     signature_base58.clone(),
                     finalized_slot,
+                    block_time,
                     vote_account,
                     vote_tracker,
-                )? {
-                    confirmed_votes.push(confirmed);
-                }
+                ).await?);
             }
         }
     }
     
+    // estimated confirmations and missed votes, see VoteCommand::FinishBlock
+    confirmed_votes.extend(vote_tracker.finish_block(finalized_slot).await?);
+    
+    // expired confirmations can come from earlier blocks, their context is gone
+    let blockhash: Arc<str> = Arc::from(block_update.blockhash);
+    for vote in confirmed_votes.iter_mut().filter(|vote| vote.finalized_slot == finalized_slot) {
+        vote.blockhash = Some(blockhash.clone());
+        vote.parent_slot = Some(block_update.parent_slot);
+        vote.block_height = block_height;
+    }
+    
     log::debug!("confirmed {} votes in block {}", confirmed_votes.len(), finalized_slot);
     Ok(confirmed_votes)
 }
 
+/// new voted slots of `vote_account` in a block, without asking the tracker
+///
+/// reads the confirmed blocks for the confirmed latency, failed transactions
+/// and unparsable instructions are skipped.
+pub fn block_votes(block: &yellowstone_grpc_proto::geyser::SubscribeUpdateBlock, vote_account: &[u8; 32]) -> Vec<Slot> {
+    let mut voted_slots = Vec::new();
+    for tx_info in &block.transactions {
+        if tx_info.meta.as_ref().is_some_and(|meta| meta.err.is_some()) {
+            continue;
+        }
+        let Some(message) = tx_info.transaction.as_ref().and_then(|transaction| transaction.message.as_ref()) else {
+            continue;
+        };
+        for instruction in &message.instructions {
+            let is_vote = message.account_keys.get(instruction.program_id_index as usize)
+                .is_some_and(|program_account| program_account == &VOTE_PROGRAM_ID);
+            if !is_vote || !votes_for(instruction, &message.account_keys, vote_account) {
+                continue;
+            }
+            if let Ok(parsed) = parse_vote_instruction_data(&instruction.data) {
+                voted_slots.extend(parsed.slots.iter().filter(|vote_info| vote_info.is_new_vote()).map(|vote_info| vote_info.slot));
+            }
+        }
+    }
+    voted_slots
+}
+
+/// process a slot status update on the slot-status fallback path
+/// 
+/// only finalized statuses confirm votes, see VoteTracker::confirm_by_finalized_slot.
+pub async fn process_finalized_slot(
+    slot_update: yellowstone_grpc_proto::geyser::SubscribeUpdateSlot,
+    vote_tracker: &VoteTrackerHandle,
+) -> Result<Vec<ConfirmedVote>> {
+    let finalized_slot = slot_update.slot;
+    
+    if slot_update.status != SlotStatus::SlotFinalized as i32
+        || !vote_tracker.admit_finalized_slot(finalized_slot, None).await?.is_accepted()
+    {
+        return Ok(Vec::new());
+    }
+    
+    let confirmed_votes = vote_tracker.confirm_finalized_slot(finalized_slot).await?;
+    log::debug!("confirmed {} votes at finalized slot {}", confirmed_votes.len(), finalized_slot);
+    Ok(confirmed_votes)
+}
+
 /// process individual transaction within a finalized block
-fn process_transaction_in_block(
+/// 
+/// every new voted slot (confirmation_count == 1) gets its own confirmed vote.
+async fn process_transaction_in_block(
     transaction: &yellowstone_grpc_proto::prelude::Transaction,
     signature: Arc<String>,
     finalized_slot: Slot,
-    _vote_account: &str,
-    vote_tracker: &mut VoteTracker,
-) -> Result<Option<ConfirmedVote>> {
+    block_time: Option<i64>,
+    vote_account: &[u8; 32],
+    vote_tracker: &VoteTrackerHandle,
+) -> Result<Vec<ConfirmedVote>> {
+    let mut confirmed_votes = Vec::new();
+    
     // extract vote instruction data and verify it contains our vote account
     if let Some(message) = &transaction.message {
         for instruction in &message.instructions {
             if let Some(program_account) = message.account_keys.get(instruction.program_id_index as usize) {
-                if program_account == &VOTE_PROGRAM_ID {
-                    let vote_slots = parse_vote_instruction_data(&instruction.data)?;
+                if program_account == &VOTE_PROGRAM_ID && votes_for(instruction, &message.account_keys, vote_account) {
+                    let vote_slots = match parse_vote_instruction_data(&instruction.data) {
+                        Ok(parsed) => parsed.slots,
+                        Err(e) => {
+                            vote_tracker.record_parse_failure(&instruction.data, &e).await?;
+                            continue;
+                        }
+                    };
                     
                     log::debug!("found vote slots in block: {:?}", vote_slots);
 
@@ -461,13 +1259,14 @@ fn process_transaction_in_block(
                                 signature.clone(),
                                 voted_slot,
                                 finalized_slot,
-                            ) {
+                                block_time,
+                            ).await? {
                                 log::debug!(
                                     "confirmed vote: slot {} -> finalized {} -> latency {} -> {} tvc (sig: {})",
                                     voted_slot, finalized_slot, confirmed.latency, confirmed.tvc_credits,
                                     &signature[..8]
                                 );
-                                return Ok(Some(confirmed));
+                                confirmed_votes.push(confirmed);
                             }
                         }
                     }
@@ -476,5 +1275,286 @@ fn process_transaction_in_block(
         }
     }
     
-    Ok(None)
-}
\ No newline at end of file
+    Ok(confirmed_votes)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_pending_vote, signature_base58};
+
+    fn sig(seed: u64) -> Arc<String> {
+        Arc::new(signature_base58(seed))
+    }
+
+    #[test]
+    fn multi_slot_vote_stays_pending_until_every_slot_is_confirmed() {
+        let mut tracker = VoteTracker::new();
+        assert!(tracker.add_pending_vote(make_pending_vote(1, 104, &[100, 101, 102])).is_empty());
+
+        let first = tracker.confirm_vote(sig(1), 101, 104, None).unwrap();
+        assert_eq!((first.voted_slot, first.latency), (101, 3));
+        assert_eq!(tracker.pending_votes[&sig(1)].confirmed_slots.len(), 1);
+
+        // a slot is matched once per signature, one not voted on never
+        assert!(tracker.confirm_vote(sig(1), 101, 104, None).is_none());
+        assert!(tracker.confirm_vote(sig(1), 103, 104, None).is_none());
+        assert_eq!(tracker.get_stats().pending_votes, 1);
+
+        let second = tracker.confirm_vote(sig(1), 100, 104, None).unwrap();
+        assert_eq!(second.latency, 4);
+        assert_eq!(tracker.get_stats().pending_votes, 1);
+        let third = tracker.confirm_vote(sig(1), 102, 104, None).unwrap();
+        assert_eq!(third.latency, 2);
+        assert_eq!(tracker.get_stats().pending_votes, 0);
+        assert!([first, second, third].iter().all(|vote| vote.kind == ConfirmationKind::Matched));
+    }
+
+    #[test]
+    fn late_transaction_matches_only_its_buffered_slots() {
+        let mut tracker = VoteTracker::new();
+        // the block confirmed two of the three voted slots before the transaction arrived
+        assert!(tracker.confirm_vote(sig(2), 100, 106, None).is_none());
+        assert!(tracker.confirm_vote(sig(2), 101, 106, None).is_none());
+
+        let mut confirmed = tracker.add_pending_vote(make_pending_vote(2, 103, &[100, 101, 102]));
+        confirmed.sort_by_key(|vote| vote.voted_slot);
+        // latency to the transaction slot, not the block
+        let latencies: Vec<_> = confirmed.iter().map(|vote| (vote.voted_slot, vote.latency)).collect();
+        assert_eq!(latencies, [(100, 3), (101, 2)]);
+
+        let pending = &tracker.pending_votes[&sig(2)];
+        assert_eq!(pending.confirmed_slots, [100, 101].into_iter().collect());
+        assert!(!pending.is_fully_confirmed());
+        assert_eq!(tracker.confirm_vote(sig(2), 102, 104, None).unwrap().latency, 2);
+        assert!(tracker.pending_votes.is_empty());
+    }
+
+    #[test]
+    fn memory_usage_follows_the_load_and_the_cleanup() {
+        let mut tracker = VoteTracker::new();
+        let empty = tracker.memory_usage();
+
+        for n in 0..500 {
+            tracker.get_or_cache_signature(&crate::test_support::signature(n));
+            tracker.add_pending_vote(make_pending_vote(n, 1_001 + n, &[1_000 + n]));
+        }
+        let loaded = tracker.memory_usage();
+        assert!(loaded.total_bytes() > empty.total_bytes());
+        let pending = loaded.components.iter().find(|component| component.name == "pending votes").unwrap();
+        assert_eq!(pending.entries, 500);
+        assert_eq!(loaded.largest().unwrap().name, "pending votes");
+
+        // confirming half of them moves them to the bounded confirmed ring
+        for n in 0..250 {
+            tracker.confirm_vote(sig(n), 1_000 + n, 1_001 + n, None).unwrap();
+        }
+        let confirmed = tracker.memory_usage();
+        let pending_bytes = |usage: &MemoryUsage| {
+            usage.components.iter().find(|component| component.name == "pending votes").unwrap().bytes
+        };
+        assert!(pending_bytes(&confirmed) < pending_bytes(&loaded));
+
+        // the sweep drops the rest once they are far behind the finalized slot
+        assert!(tracker.admit_finalized_slot(5_000, None).is_accepted());
+        tracker.cleanup_pending_if_due(5_000);
+        let cleaned = tracker.memory_usage();
+        assert_eq!(pending_bytes(&cleaned), 0);
+        assert!(cleaned.total_bytes() < confirmed.total_bytes());
+    }
+
+    #[test]
+    fn confirmed_slot_set_counts_a_slot_once() {
+        let mut slots = ConfirmedSlotSet::new(100);
+        assert!(slots.first_confirmation(1_000));
+        assert!(slots.first_confirmation(999));
+        assert!(!slots.first_confirmation(1_000));
+        assert!(!slots.first_confirmation(999));
+        assert_eq!((slots.len(), slots.duplicates()), (2, 2));
+
+        // slots more than the window behind the newest are forgotten
+        assert!(slots.first_confirmation(1_100));
+        assert!(slots.contains(1_000) && !slots.contains(999));
+        assert!(slots.first_confirmation(999));
+        assert_eq!(slots.duplicates(), 2);
+    }
+
+    #[test]
+    fn overlapping_votes_yield_one_confirmed_vote_per_slot() {
+        let mut tracker = VoteTracker::new();
+        // a resent tower: both transactions carry 101
+        tracker.add_pending_vote(make_pending_vote(1, 102, &[100, 101]));
+        tracker.add_pending_vote(make_pending_vote(2, 103, &[101, 102]));
+
+        let mut confirmed: Vec<ConfirmedVote> = [(1, 100), (1, 101), (2, 101), (2, 102)]
+            .into_iter()
+            .filter_map(|(seed, slot)| tracker.confirm_vote(sig(seed), slot, 103, None))
+            .collect();
+        confirmed.retain(|vote| vote.kind.is_counted());
+        let slots: Vec<Slot> = confirmed.iter().map(|vote| vote.voted_slot).collect();
+        assert_eq!(slots, [100, 101, 102]);
+        // the first signature to confirm the slot keeps it
+        assert_eq!(*confirmed[1].signature, signature_base58(1));
+
+        let stats = tracker.get_stats();
+        assert_eq!((stats.confirmed_votes, stats.duplicate_confirmations, stats.pending_votes), (3, 1, 0));
+    }
+
+    #[test]
+    fn transaction_of_already_confirmed_slots_is_skipped() {
+        let mut tracker = VoteTracker::new();
+        tracker.add_pending_vote(make_pending_vote(1, 101, &[100]));
+        tracker.confirm_vote(sig(1), 100, 101, None).unwrap();
+
+        // the same vote resent by another endpoint after a failover
+        assert!(tracker.add_pending_vote(make_pending_vote(2, 102, &[100])).is_empty());
+        let stats = tracker.get_stats();
+        assert_eq!((stats.pending_votes, stats.replayed_transactions), (0, 1));
+    }
+
+    #[test]
+    fn signature_cache_hit_miss_and_eviction() {
+        let mut cache = SignatureCache::new(2);
+        let first = cache.get_or_insert(&[1; 64]).unwrap();
+        // a hit hands out the same string
+        assert!(Arc::ptr_eq(&first, &cache.get_or_insert(&[1; 64]).unwrap()));
+        assert_eq!(*first, fd_bs58::encode_64([1; 64]));
+
+        let second = cache.get_or_insert(&[2; 64]).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(cache.len(), 2);
+
+        // full, one of the two makes room
+        let third = cache.get_or_insert(&[3; 64]).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&third, &cache.get_or_insert(&[3; 64]).unwrap()));
+        // the evicted one is encoded again, to the same text
+        let again = cache.get_or_insert(&[1; 64]).unwrap();
+        assert_eq!(again, first);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn only_64_byte_signatures_are_keyed() {
+        let mut tracker = VoteTracker::new();
+        for len in [0, 32, 63, 64, 65] {
+            let signature = tracker.get_or_cache_signature(&vec![7; len]);
+            assert_eq!(signature.is_some(), len == 64, "{} bytes", len);
+        }
+        assert_eq!(tracker.get_stats().malformed_signatures, 4);
+        assert_eq!(tracker.signature_cache.len(), 1);
+    }
+
+    #[test]
+    fn confirmed_vote_shares_the_signature() {
+        let mut tracker = VoteTracker::new();
+        tracker.add_pending_vote(make_pending_vote(1, 101, &[100]));
+        let signature = sig(1);
+        let confirmed = tracker.confirm_vote(signature.clone(), 100, 101, None).unwrap();
+        assert!(Arc::ptr_eq(&confirmed.signature, &signature));
+    }
+
+    #[test]
+    fn matched_kind_comes_from_the_pending_vote() {
+        let mut tracker = VoteTracker::new();
+        tracker.add_pending_vote(make_pending_vote(1, 101, &[100]));
+        let vote = tracker.confirm_vote(sig(1), 100, 102, None).unwrap();
+        assert_eq!((vote.kind, vote.source), (ConfirmationKind::Matched, ConfirmationSource::Block));
+        // to the confirming block, with the landing slot and a wall-clock time
+        assert_eq!((vote.latency, vote.landed_slot), (2, Some(101)));
+        assert!(vote.confirmation_ms.is_some());
+        assert!(!vote.kind.is_estimated() && vote.kind.is_counted());
+    }
+
+    #[test]
+    fn direct_block_only_kind_when_the_transaction_never_shows_up() {
+        let mut tracker = VoteTracker::new();
+        assert!(tracker.confirm_vote(sig(2), 100, 104, None).is_none());
+        assert!(tracker.expire_unmatched_confirmations(103 + UNMATCHED_CONFIRMATION_WINDOW_SLOTS).is_empty());
+
+        let expired = tracker.expire_unmatched_confirmations(104 + UNMATCHED_CONFIRMATION_WINDOW_SLOTS);
+        assert_eq!(expired.len(), 1);
+        let vote = &expired[0];
+        assert_eq!((vote.kind, vote.source), (ConfirmationKind::DirectBlockOnly, ConfirmationSource::Block));
+        // pessimistic, to the finalized block
+        assert_eq!((vote.latency, vote.landed_slot, vote.confirmation_ms), (4, None, None));
+        assert!(vote.kind.is_estimated() && vote.kind.is_counted());
+        assert_eq!(tracker.get_stats().confirmed_votes, 1);
+    }
+
+    #[test]
+    fn estimated_tx_slot_kind_from_slot_statuses() {
+        let mut tracker = VoteTracker::new();
+        tracker.add_pending_vote(make_pending_vote(3, 103, &[100, 101]));
+        assert!(tracker.confirm_by_finalized_slot(102).is_empty());
+
+        let mut votes = tracker.confirm_by_finalized_slot(103);
+        votes.sort_by_key(|vote| vote.voted_slot);
+        let latencies: Vec<_> = votes.iter().map(|vote| (vote.voted_slot, vote.latency)).collect();
+        assert_eq!(latencies, [(100, 3), (101, 2)]);
+        for vote in &votes {
+            assert_eq!((vote.kind, vote.source), (ConfirmationKind::EstimatedTxSlot, ConfirmationSource::SlotStatus));
+            assert_eq!((vote.finalized_slot, vote.landed_slot), (103, Some(103)));
+            assert!(vote.kind.is_estimated() && vote.kind.is_counted());
+        }
+        assert_eq!(tracker.get_stats().pending_votes, 0);
+    }
+
+    #[test]
+    fn dropped_block_confirms_only_its_own_transactions() {
+        let mut tracker = VoteTracker::new();
+        // landed in the dropped block, and in older blocks still queued
+        tracker.add_pending_vote(make_pending_vote(6, 105, &[103, 104]));
+        tracker.add_pending_vote(make_pending_vote(7, 104, &[102]));
+        tracker.add_pending_vote(make_pending_vote(8, 106, &[105]));
+
+        let mut votes = tracker.confirm_dropped_block(105);
+        votes.sort_by_key(|vote| vote.voted_slot);
+        let latencies: Vec<_> = votes.iter().map(|vote| (vote.voted_slot, vote.latency)).collect();
+        assert_eq!(latencies, [(103, 2), (104, 1)]);
+        for vote in &votes {
+            assert_eq!((vote.kind, vote.source), (ConfirmationKind::EstimatedTxSlot, ConfirmationSource::Block));
+            assert_eq!(vote.landed_slot, Some(105));
+        }
+        assert_eq!(tracker.get_stats().pending_votes, 2);
+        assert_eq!(tracker.confirm_vote(sig(7), 102, 104, None).map(|vote| vote.kind), Some(ConfirmationKind::Matched));
+    }
+
+    #[test]
+    fn duplicate_suppressed_kind_for_a_second_signature() {
+        let mut tracker = VoteTracker::new();
+        // the same slot voted again after a failover
+        tracker.add_pending_vote(make_pending_vote(4, 101, &[100]));
+        tracker.add_pending_vote(make_pending_vote(5, 102, &[100]));
+
+        let first = tracker.confirm_vote(sig(4), 100, 102, None).unwrap();
+        let second = tracker.confirm_vote(sig(5), 100, 102, None).unwrap();
+        assert_eq!(first.kind, ConfirmationKind::Matched);
+        assert_eq!(second.kind, ConfirmationKind::DuplicateSuppressed);
+        assert!(!second.kind.is_counted());
+
+        let stats = tracker.get_stats();
+        assert_eq!((stats.confirmed_votes, stats.duplicate_confirmations), (1, 1));
+        // also on the direct path, a third signature whose transaction never came
+        assert!(tracker.confirm_vote(sig(6), 100, 103, None).is_none());
+        let expired = tracker.expire_unmatched_confirmations(103 + UNMATCHED_CONFIRMATION_WINDOW_SLOTS);
+        assert_eq!(expired.iter().map(|vote| vote.kind).collect::<Vec<_>>(), [ConfirmationKind::DuplicateSuppressed]);
+        assert_eq!(tracker.get_stats().confirmed_votes, 1);
+    }
+
+    #[test]
+    fn drained_pending_votes_report_their_unconfirmed_slots() {
+        let mut tracker = VoteTracker::new();
+        tracker.add_pending_vote(make_pending_vote(8, 106, &[104, 105]));
+        tracker.add_pending_vote(make_pending_vote(9, 103, &[102]));
+        tracker.confirm_vote(sig(8), 104, 107, None).unwrap();
+        tracker.admit_finalized_slot(110, None);
+
+        let drained = tracker.drain_pending();
+        let reported: Vec<_> = drained.iter().map(|vote| (vote.voted_slots.clone(), vote.age_slots)).collect();
+        assert_eq!(reported, [(vec![102], 7), (vec![105], 4)]);
+        assert_eq!(tracker.get_stats().pending_votes, 0);
+        // nothing is left to be declared missed later
+        tracker.expire_missed_votes(1_000);
+        assert!(tracker.take_missed_votes().is_empty());
+    }
+}
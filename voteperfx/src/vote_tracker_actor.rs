@@ -0,0 +1,324 @@
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::error::{Result, VoteMonitorError};
+use crate::events::EventBus;
+use crate::finalized_slots::SlotAdmission;
+use crate::memory::MemoryUsage;
+use crate::message::{SystemEvent, VoteCommand};
+use crate::performance::{ConfirmedVote, Slot};
+use crate::tasks::spawn_named;
+use crate::vote_tracker::{MissedVote, PendingVote, VoteTracker, VoteTrackerStats};
+
+/// pending commands buffered before senders wait
+pub const VOTE_COMMAND_BUFFER: usize = 1024;
+
+/// bytes of an unparsable instruction passed on for the debug log
+const PARSE_FAILURE_PREFIX: usize = 16;
+
+/// owns the vote tracker and serves commands one at a time
+///
+/// the tx and block tasks no longer contend on a lock; commands are applied in
+/// the order they arrive. stops once every handle is dropped.
+pub async fn vote_tracker_actor(
+    mut tracker: VoteTracker,
+    mut commands: mpsc::Receiver<VoteCommand>,
+    events: EventBus,
+) {
+    while let Some(command) = commands.recv().await {
+        // a dropped receiver only means the caller gave up waiting
+        match command {
+            VoteCommand::AddPending { vote, respond_to } => {
+                if events.subscriber_count() > 0 {
+                    events.publish(SystemEvent::VoteAdded(Arc::new(vote.clone())));
+                }
+                let _ = respond_to.send(tracker.add_pending_vote(vote));
+            }
+            VoteCommand::ConfirmVote { signature, voted_slot, finalized_slot, block_time, respond_to } => {
+                let _ = respond_to.send(tracker.confirm_vote(signature, voted_slot, finalized_slot, block_time));
+            }
+            VoteCommand::CacheSignature { signature_bytes, respond_to } => {
+                let _ = respond_to.send(tracker.get_or_cache_signature(&signature_bytes));
+            }
+            VoteCommand::AdmitFinalizedSlot { slot, block_height, respond_to } => {
+                let admission = tracker.admit_finalized_slot(slot, block_height);
+                if admission.is_accepted() {
+                    events.publish(SystemEvent::SlotProcessed(slot));
+                }
+                let _ = respond_to.send(admission);
+            }
+            VoteCommand::FinishBlock { finalized_slot, respond_to } => {
+                // fall back to direct confirmation for blocks whose transaction never showed up
+                let expired = tracker.expire_unmatched_confirmations(finalized_slot);
+                tracker.expire_missed_votes(finalized_slot);
+                tracker.cleanup_pending_if_due(finalized_slot);
+                let _ = respond_to.send(expired);
+            }
+            VoteCommand::ConfirmFinalizedSlot { finalized_slot, respond_to } => {
+                let confirmed = tracker.confirm_by_finalized_slot(finalized_slot);
+                tracker.expire_missed_votes(finalized_slot);
+                tracker.cleanup_pending_if_due(finalized_slot);
+                let _ = respond_to.send(confirmed);
+            }
+            VoteCommand::ConfirmDroppedBlock { slot, respond_to } => {
+                // a block redelivered later is a duplicate
+                let confirmed = if tracker.admit_finalized_slot(slot, None).is_accepted() {
+                    events.publish(SystemEvent::SlotProcessed(slot));
+                    tracker.confirm_dropped_block(slot)
+                } else {
+                    Vec::new()
+                };
+                let _ = respond_to.send(confirmed);
+            }
+            VoteCommand::RecordParseFailure { data, error } => {
+                tracker.record_parse_failure(&data, &error);
+            }
+            VoteCommand::TakeMissedVotes { respond_to } => {
+                let _ = respond_to.send(tracker.take_missed_votes());
+            }
+            VoteCommand::DrainPending { respond_to } => {
+                let _ = respond_to.send(tracker.drain_pending());
+            }
+            VoteCommand::GetStats { respond_to } => {
+                let _ = respond_to.send(tracker.get_stats());
+            }
+            VoteCommand::GetMemoryUsage { respond_to } => {
+                let _ = respond_to.send(tracker.memory_usage());
+            }
+            VoteCommand::Cleanup => {
+                tracker.cleanup_old_pending();
+                events.publish(SystemEvent::CleanupCompleted {
+                    remaining_votes: tracker.get_stats().pending_votes,
+                });
+            }
+        }
+    }
+
+    log::info!("vote tracker actor stopped");
+}
+
+/// cheap, cloneable handle to the vote tracker actor
+#[derive(Debug, Clone)]
+pub struct VoteTrackerHandle {
+    sender: mpsc::Sender<VoteCommand>,
+}
+
+impl VoteTrackerHandle {
+    /// spawn the actor owning `tracker`, publishing tracker events on `events`
+    pub fn spawn(tracker: VoteTracker, events: EventBus) -> (Self, JoinHandle<()>) {
+        let (sender, commands) = mpsc::channel(VOTE_COMMAND_BUFFER);
+        let task = spawn_named("vote_tracker", vote_tracker_actor(tracker, commands, events));
+        (Self { sender }, task)
+    }
+
+    async fn send(&self, command: VoteCommand) -> Result<()> {
+        self.sender
+            .send(command)
+            .await
+            .map_err(|_| VoteMonitorError::Channel("vote tracker actor stopped".to_string()))
+    }
+
+    async fn request<T>(&self, command: impl FnOnce(oneshot::Sender<T>) -> VoteCommand) -> Result<T> {
+        let (respond_to, response) = oneshot::channel();
+        self.send(command(respond_to)).await?;
+        response
+            .await
+            .map_err(|_| VoteMonitorError::Channel("vote tracker actor dropped the response".to_string()))
+    }
+
+    /// returns votes confirmed immediately because their block was already seen
+    pub async fn add_pending(&self, vote: PendingVote) -> Result<Vec<ConfirmedVote>> {
+        self.request(|respond_to| VoteCommand::AddPending { vote, respond_to }).await
+    }
+
+    pub async fn confirm_vote(
+        &self,
+        signature: Arc<String>,
+        voted_slot: Slot,
+        finalized_slot: Slot,
+        block_time: Option<i64>,
+    ) -> Result<Option<ConfirmedVote>> {
+        self.request(|respond_to| VoteCommand::ConfirmVote {
+            signature,
+            voted_slot,
+            finalized_slot,
+            block_time,
+            respond_to,
+        })
+        .await
+    }
+
+    /// none for a malformed signature, see `VoteTracker::get_or_cache_signature`
+    pub async fn cache_signature(&self, signature_bytes: &[u8]) -> Result<Option<Arc<String>>> {
+        let signature_bytes = signature_bytes.to_vec();
+        self.request(|respond_to| VoteCommand::CacheSignature { signature_bytes, respond_to }).await
+    }
+
+    /// marks the slot processed when accepted, see FinalizedSlots. the block
+    /// height of a block counts gaps as blocks without our vote
+    pub async fn admit_finalized_slot(&self, slot: Slot, block_height: Option<u64>) -> Result<SlotAdmission> {
+        self.request(|respond_to| VoteCommand::AdmitFinalizedSlot { slot, block_height, respond_to }).await
+    }
+
+    /// returns votes estimated from block confirmations whose transaction never arrived
+    pub async fn finish_block(&self, finalized_slot: Slot) -> Result<Vec<ConfirmedVote>> {
+        self.request(|respond_to| VoteCommand::FinishBlock { finalized_slot, respond_to }).await
+    }
+
+    /// slot-status fallback, returns votes whose transaction slot is now finalized
+    pub async fn confirm_finalized_slot(&self, finalized_slot: Slot) -> Result<Vec<ConfirmedVote>> {
+        self.request(|respond_to| VoteCommand::ConfirmFinalizedSlot { finalized_slot, respond_to }).await
+    }
+
+    /// returns the votes that landed in a finalized block the stream dropped
+    pub async fn confirm_dropped_block(&self, slot: Slot) -> Result<Vec<ConfirmedVote>> {
+        self.request(|respond_to| VoteCommand::ConfirmDroppedBlock { slot, respond_to }).await
+    }
+
+    /// count an instruction that failed to deserialize, see `VoteTracker::record_parse_failure`
+    pub async fn record_parse_failure(&self, data: &[u8], error: &VoteMonitorError) -> Result<()> {
+        let data = data[..data.len().min(PARSE_FAILURE_PREFIX)].to_vec();
+        self.send(VoteCommand::RecordParseFailure { data, error: error.to_string() }).await
+    }
+
+    pub async fn take_missed_votes(&self) -> Result<Vec<MissedVote>> {
+        self.request(|respond_to| VoteCommand::TakeMissedVotes { respond_to }).await
+    }
+
+    /// votes still in flight, removed from the tracker
+    pub async fn drain_pending(&self) -> Result<Vec<MissedVote>> {
+        self.request(|respond_to| VoteCommand::DrainPending { respond_to }).await
+    }
+
+    pub async fn get_stats(&self) -> Result<VoteTrackerStats> {
+        self.request(|respond_to| VoteCommand::GetStats { respond_to }).await
+    }
+
+    pub async fn memory_usage(&self) -> Result<MemoryUsage> {
+        self.request(|respond_to| VoteCommand::GetMemoryUsage { respond_to }).await
+    }
+
+    pub async fn cleanup(&self) -> Result<()> {
+        self.send(VoteCommand::Cleanup).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::ConfirmationKind;
+    use crate::test_support::{make_pending_vote, signature_base58};
+    use crate::vote_tracker::UNMATCHED_CONFIRMATION_WINDOW_SLOTS;
+
+    fn sig(seed: u64) -> Arc<String> {
+        Arc::new(signature_base58(seed))
+    }
+
+    #[tokio::test]
+    async fn interleaved_pending_and_confirm_commands() {
+        let events = EventBus::default();
+        let mut added = events.subscribe();
+        let (tracker, task) = VoteTrackerHandle::spawn(VoteTracker::new(), events);
+
+        assert!(tracker.add_pending(make_pending_vote(1, 101, &[100])).await.unwrap().is_empty());
+        // the block of the second vote outruns its transaction
+        assert!(tracker.confirm_vote(sig(2), 101, 104, None).await.unwrap().is_none());
+        assert!(tracker.add_pending(make_pending_vote(3, 103, &[101, 102])).await.unwrap().is_empty());
+
+        let late = tracker.add_pending(make_pending_vote(2, 102, &[101])).await.unwrap();
+        assert_eq!(late.len(), 1);
+        assert_eq!((late[0].voted_slot, late[0].latency, late[0].finalized_slot), (101, 1, 104));
+
+        let first = tracker.confirm_vote(sig(1), 100, 101, None).await.unwrap().unwrap();
+        assert_eq!((first.latency, first.kind), (1, ConfirmationKind::Matched));
+        // the vote is complete, a repeat is buffered like an unknown signature
+        assert!(tracker.confirm_vote(sig(1), 100, 101, None).await.unwrap().is_none());
+
+        // 101 was credited to the second vote, the third only earns 102
+        let duplicate = tracker.confirm_vote(sig(3), 101, 103, None).await.unwrap().unwrap();
+        assert_eq!(duplicate.kind, ConfirmationKind::DuplicateSuppressed);
+        let third = tracker.confirm_vote(sig(3), 102, 103, None).await.unwrap().unwrap();
+        assert_eq!((third.latency, third.kind), (1, ConfirmationKind::Matched));
+
+        let stats = tracker.get_stats().await.unwrap();
+        assert_eq!((stats.pending_votes, stats.unmatched_confirmations), (0, 1));
+        assert_eq!((stats.confirmed_votes, stats.duplicate_confirmations), (3, 1));
+
+        // and never credited twice once it expires
+        let expired = tracker.finish_block(101 + UNMATCHED_CONFIRMATION_WINDOW_SLOTS).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].kind, ConfirmationKind::DuplicateSuppressed);
+        let stats = tracker.get_stats().await.unwrap();
+        assert_eq!((stats.unmatched_confirmations, stats.confirmed_votes), (0, 3));
+
+        let mut signatures = Vec::new();
+        while let Ok(SystemEvent::VoteAdded(vote)) = added.try_recv() {
+            signatures.push(vote.signature.clone());
+        }
+        assert_eq!(signatures, [sig(1), sig(3), sig(2)]);
+
+        // the actor stops with its last handle
+        drop(tracker);
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn commands_after_the_actor_stopped_fail() {
+        let (tracker, task) = VoteTrackerHandle::spawn(VoteTracker::new(), EventBus::default());
+        task.abort();
+        let _ = task.await;
+        let error = tracker.add_pending(make_pending_vote(1, 101, &[100])).await.unwrap_err();
+        assert!(matches!(error, VoteMonitorError::Channel(_)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_adds_and_confirms_count_each_vote_once() {
+        const TASKS: u64 = 4;
+        const VOTES: u64 = 4_000;
+        let (tracker, task) = VoteTrackerHandle::spawn(VoteTracker::new(), EventBus::default());
+
+        // each pair of tasks adds its share of the votes in slot order and
+        // confirms them newest first, so blocks outrun some transactions
+        let mut tasks = Vec::new();
+        for share in 0..TASKS {
+            let votes: Vec<u64> = (share..VOTES).step_by(TASKS as usize).collect();
+            let adder = tracker.clone();
+            let added = votes.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut confirmed = Vec::new();
+                for n in added {
+                    let late = adder.add_pending(make_pending_vote(n, 1_001 + n, &[1_000 + n])).await.unwrap();
+                    confirmed.extend(late.into_iter().map(|vote| vote.signature));
+                }
+                confirmed
+            }));
+            let confirmer = tracker.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut confirmed = Vec::new();
+                for n in votes.into_iter().rev() {
+                    let vote = confirmer.confirm_vote(sig(n), 1_000 + n, 1_002 + n, None).await.unwrap();
+                    confirmed.extend(vote.map(|vote| vote.signature));
+                }
+                confirmed
+            }));
+        }
+
+        let mut confirmed = Vec::new();
+        for task in tasks {
+            confirmed.extend(task.await.unwrap());
+        }
+        confirmed.sort();
+        let mut expected: Vec<Arc<String>> = (0..VOTES).map(sig).collect();
+        expected.sort();
+        assert_eq!(confirmed, expected);
+
+        let stats = tracker.get_stats().await.unwrap();
+        assert_eq!((stats.pending_votes, stats.unmatched_confirmations), (0, 0));
+        assert_eq!(stats.duplicate_confirmations, 0);
+
+        drop(tracker);
+        task.await.unwrap();
+    }
+}
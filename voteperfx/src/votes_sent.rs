@@ -0,0 +1,148 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::ring_buffer::RingBuffer;
+
+/// length of one votes sent bucket
+pub const VOTES_SENT_BUCKET: Duration = Duration::from_secs(60);
+
+/// buckets kept, the last hour
+pub const VOTES_SENT_BUCKETS: usize = 60;
+
+/// time without a vote transaction of the monitored account, while finalized
+/// blocks keep arriving, before the validator counts as silent. a voting
+/// validator sends a few every second
+pub const VOTES_SENT_SILENCE: Duration = Duration::from_secs(10);
+
+/// vote transactions of the monitored account per minute, counted when
+/// they become pending votes, before any confirmation
+///
+/// confirmations trail the transactions by the finalization delay and a
+/// missed vote is only declared after `tracker.missed_vote_cutoff_slots`,
+/// this catches a validator that stopped sending votes (tower file trouble,
+/// a stuck voting thread) within seconds. finalized blocks tell a silent
+/// validator apart from a stalled stream.
+#[derive(Debug, Clone)]
+pub struct VotesSent {
+    buckets: RingBuffer<u64>,
+    current_started: Instant,
+    started: Instant,
+    last_sent: Option<Instant>,
+    last_block: Option<Instant>,
+    silence_alerted: bool,
+}
+
+/// see `VotesSent::update_silence`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SilenceChange {
+    /// no vote transaction for this long while blocks arrive
+    Started(Duration),
+    /// a vote was sent again, or the blocks stopped too
+    Ended,
+}
+
+/// votes sent per minute as served on /status
+#[derive(Debug, Clone, Serialize)]
+pub struct VotesSentSnapshot {
+    /// oldest first, the last minute still filling
+    pub per_minute: Vec<u64>,
+    /// seconds since the last vote transaction while blocks arrive, none
+    /// while the validator votes
+    pub silent_secs: Option<u64>,
+}
+
+impl Default for VotesSent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VotesSent {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        let mut sent = Self {
+            buckets: RingBuffer::new(VOTES_SENT_BUCKETS),
+            current_started: now,
+            started: now,
+            last_sent: None,
+            last_block: None,
+            silence_alerted: false,
+        };
+        sent.buckets.push(0);
+        sent
+    }
+
+    /// a vote transaction that added a pending vote
+    pub fn record_sent(&mut self) {
+        self.roll();
+        if let Some(current) = self.buckets.last_mut() {
+            *current += 1;
+        }
+        self.last_sent = Some(Instant::now());
+    }
+
+    /// a finalized block, the stream is delivering
+    pub fn record_block(&mut self) {
+        self.last_block = Some(Instant::now());
+    }
+
+    /// start an empty bucket for every minute elapsed, returns whether any started
+    pub fn roll(&mut self) -> bool {
+        let elapsed = self.current_started.elapsed();
+        if elapsed < VOTES_SENT_BUCKET {
+            return false;
+        }
+
+        let elapsed_buckets = (elapsed.as_nanos() / VOTES_SENT_BUCKET.as_nanos()) as u32;
+        for _ in 0..elapsed_buckets.min(VOTES_SENT_BUCKETS as u32) {
+            self.buckets.push(0);
+        }
+        self.current_started += VOTES_SENT_BUCKET * elapsed_buckets;
+        true
+    }
+
+    /// vote transactions per minute, oldest first, the last minute still filling
+    pub fn per_minute(&self) -> impl DoubleEndedIterator<Item = u64> + ExactSizeIterator + '_ {
+        self.buckets.iter().copied()
+    }
+
+    pub fn current_minute(&self) -> u64 {
+        self.buckets.last().copied().unwrap_or_default()
+    }
+
+    /// time since the last vote transaction, or since the start without one,
+    /// when it is `VOTES_SENT_SILENCE` or more and a finalized block came in
+    /// within that time
+    pub fn silence(&self) -> Option<Duration> {
+        let quiet = self.last_sent.unwrap_or(self.started).elapsed();
+        let blocks_arriving = self.last_block.is_some_and(|block| block.elapsed() < VOTES_SENT_SILENCE);
+        (quiet >= VOTES_SENT_SILENCE && blocks_arriving).then_some(quiet)
+    }
+
+    /// whether the silence started or ended since the last call, run on a timer
+    pub fn update_silence(&mut self) -> Option<SilenceChange> {
+        match (self.silence(), self.silence_alerted) {
+            (Some(quiet), false) => {
+                self.silence_alerted = true;
+                Some(SilenceChange::Started(quiet))
+            }
+            (None, true) => {
+                self.silence_alerted = false;
+                Some(SilenceChange::Ended)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn snapshot(&self) -> VotesSentSnapshot {
+        VotesSentSnapshot {
+            per_minute: self.per_minute().collect(),
+            silent_secs: self.silence().map(|quiet| quiet.as_secs()),
+        }
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.buckets.allocated_bytes()
+    }
+}
@@ -0,0 +1,95 @@
+//! `decode_vote_transaction` on a vote transaction in wire format, see
+//! `fixtures/README.md`
+
+use base64::Engine;
+use solana_sdk::program_utils::limited_deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use voteperfx::vote_parse::{decode_vote_transaction, decode_vote_transaction_bytes};
+use voteperfx::VoteInstructionKind;
+use yellowstone_grpc_proto::prelude::{CompiledInstruction, Message, MessageHeader, Transaction};
+
+const SIGNATURE: &str = "2FfYNX1NayCnPbddDYpUWEfk1aYaj2ft6N6YL5WXpVqqWbzRUbR49DmtsGd3jbXjAD561p39m6gPosHmCuT6nVEG";
+const VOTE_ACCOUNT: &str = "2KW2XRd9kwqet15Aha2oK3tYvd3nWbTFH1MBiRAv1BE1";
+const NEWEST_SLOT: u64 = 330_000_031;
+
+fn fixture() -> Vec<u8> {
+    let encoded = include_str!("fixtures/tower_sync_vote.b64");
+    base64::engine::general_purpose::STANDARD.decode(encoded.trim()).unwrap()
+}
+
+/// the fixture as a yellowstone update carries it
+fn proto_transaction(bytes: &[u8]) -> Transaction {
+    let transaction: VersionedTransaction = limited_deserialize(bytes).unwrap();
+    let message = &transaction.message;
+    let header = message.header();
+    Transaction {
+        signatures: transaction.signatures.iter().map(|signature| signature.as_ref().to_vec()).collect(),
+        message: Some(Message {
+            header: Some(MessageHeader {
+                num_required_signatures: header.num_required_signatures.into(),
+                num_readonly_signed_accounts: header.num_readonly_signed_accounts.into(),
+                num_readonly_unsigned_accounts: header.num_readonly_unsigned_accounts.into(),
+            }),
+            account_keys: message.static_account_keys().iter().map(|key| key.to_bytes().to_vec()).collect(),
+            recent_blockhash: message.recent_blockhash().to_bytes().to_vec(),
+            instructions: message
+                .instructions()
+                .iter()
+                .map(|instruction| CompiledInstruction {
+                    program_id_index: instruction.program_id_index.into(),
+                    accounts: instruction.accounts.clone(),
+                    data: instruction.data.clone(),
+                })
+                .collect(),
+            versioned: false,
+            address_table_lookups: vec![],
+        }),
+    }
+}
+
+#[test]
+fn fixture_is_a_signed_vote() {
+    let transaction: VersionedTransaction = limited_deserialize(&fixture()).unwrap();
+    assert!(transaction.verify_with_results().into_iter().all(|valid| valid));
+    assert_eq!(transaction.signatures[0].to_string(), SIGNATURE);
+}
+
+#[test]
+fn wire_format_vote_decodes() {
+    let parsed = decode_vote_transaction_bytes(&fixture()).unwrap();
+    let vote_account: Pubkey = VOTE_ACCOUNT.parse().unwrap();
+
+    assert_eq!(parsed.signature, SIGNATURE);
+    assert!(parsed.votes_for(&vote_account));
+    assert_eq!(parsed.instruction_kind, Some(VoteInstructionKind::TowerSync));
+    assert_eq!(parsed.root, Some(NEWEST_SLOT - 31));
+
+    // a full tower, oldest lockout first
+    assert_eq!(parsed.voted_slots.len(), 31);
+    let tower: Vec<_> = parsed.voted_slots.iter().map(|vote| (vote.slot, vote.confirmation_count)).collect();
+    assert_eq!(tower[0], (NEWEST_SLOT - 30, Some(31)));
+    assert_eq!(tower[30], (NEWEST_SLOT, Some(1)));
+    assert!(parsed.voted_slots.iter().all(|vote| vote.kind == VoteInstructionKind::TowerSync));
+    assert_eq!(parsed.new_votes().collect::<Vec<_>>(), [NEWEST_SLOT]);
+}
+
+#[test]
+fn both_representations_decode_the_same() {
+    let bytes = fixture();
+    let from_bytes = decode_vote_transaction_bytes(&bytes).unwrap();
+    let from_proto = decode_vote_transaction(&proto_transaction(&bytes)).unwrap();
+
+    assert_eq!(from_proto.signature, from_bytes.signature);
+    assert_eq!(from_proto.vote_account, from_bytes.vote_account);
+    assert_eq!(from_proto.root, from_bytes.root);
+    assert_eq!(from_proto.instruction_kind, from_bytes.instruction_kind);
+    assert_eq!(from_proto.voted_slots, from_bytes.voted_slots);
+}
+
+#[test]
+fn damaged_fixture_is_an_error() {
+    let bytes = fixture();
+    assert!(decode_vote_transaction_bytes(&bytes[..bytes.len() / 2]).is_err());
+    assert!(decode_vote_transaction_bytes(&[]).is_err());
+}
@@ -0,0 +1,219 @@
+//! grpc updates built with `test_support` through the transaction and block
+//! paths, the way the pipeline tasks drive the tracker
+
+use solana_sdk::pubkey::Pubkey;
+use voteperfx::events::EventBus;
+use voteperfx::test_support::{
+    make_block, make_non_vote_tx, make_vote_program_tx, make_vote_tx, make_vote_tx_for, signature, signature_base58, vote_account_key, VOTE_ACCOUNT,
+};
+use voteperfx::vote_tracker::block_votes;
+use voteperfx::{
+    process_finalized_block, process_vote_transaction, ConfirmationKind, ConfirmedVote, PerformanceStats, VoteTracker,
+    VoteTrackerHandle, VOTE_CREDITS_MAXIMUM_PER_SLOT,
+};
+
+/// credits of an optimal vote
+const MAX_CREDITS: u64 = VOTE_CREDITS_MAXIMUM_PER_SLOT as u64;
+
+fn tracker() -> VoteTrackerHandle {
+    VoteTrackerHandle::spawn(VoteTracker::new(), EventBus::default()).0
+}
+
+fn credited(votes: &[ConfirmedVote]) -> PerformanceStats {
+    let mut stats = PerformanceStats::new();
+    for vote in votes {
+        stats.add_confirmed_vote(vote.clone());
+    }
+    stats
+}
+
+#[tokio::test]
+async fn pending_vote_confirmed_by_its_block() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    let tx = make_vote_tx(signature(1), 101, &[(100, 1)]);
+
+    let processed = process_vote_transaction(tx.clone(), &account, &tracker).await.unwrap();
+    assert!(processed.pending_added);
+    assert!(processed.confirmed.is_empty());
+    assert_eq!(tracker.get_stats().await.unwrap().pending_votes, 1);
+
+    let confirmed = process_finalized_block(make_block(101, &[tx]), &account, &tracker).await.unwrap();
+    assert_eq!(confirmed.len(), 1);
+    let vote = &confirmed[0];
+    assert_eq!(*vote.signature, signature_base58(1));
+    assert_eq!((vote.voted_slot, vote.finalized_slot, vote.latency), (100, 101, 1));
+    assert_eq!(vote.kind, ConfirmationKind::Matched);
+    assert_eq!(vote.landed_slot, Some(101));
+    assert_eq!(vote.tvc_credits, MAX_CREDITS);
+    assert!(vote.blockhash.is_some());
+    assert_eq!(tracker.get_stats().await.unwrap().pending_votes, 0);
+
+    let stats = credited(&confirmed);
+    assert_eq!(stats.total_transactions(), 1);
+    assert_eq!(stats.total_tvc_earned(), MAX_CREDITS);
+    assert_eq!(stats.optimal_votes(), 1);
+}
+
+#[tokio::test]
+async fn block_seen_before_the_transaction() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    let tx = make_vote_tx(signature(2), 105, &[(100, 1)]);
+
+    // buffered until the transaction shows up
+    let confirmed = process_finalized_block(make_block(105, std::slice::from_ref(&tx)), &account, &tracker).await.unwrap();
+    assert!(confirmed.is_empty());
+    assert_eq!(tracker.get_stats().await.unwrap().unmatched_confirmations, 1);
+
+    let processed = process_vote_transaction(tx, &account, &tracker).await.unwrap();
+    assert_eq!(processed.confirmed.len(), 1);
+    let vote = &processed.confirmed[0];
+    assert_eq!((vote.voted_slot, vote.finalized_slot, vote.latency), (100, 105, 5));
+    assert_eq!(vote.kind, ConfirmationKind::Matched);
+    // no wall-clock confirmation time, the block came first
+    assert_eq!(vote.confirmation_ms, None);
+
+    let stats = tracker.get_stats().await.unwrap();
+    assert_eq!((stats.pending_votes, stats.unmatched_confirmations), (0, 0));
+    let stats = credited(&processed.confirmed);
+    assert!(stats.total_tvc_earned() < MAX_CREDITS);
+    assert_eq!(stats.total_tvc_possible(), MAX_CREDITS);
+}
+
+#[tokio::test]
+async fn every_new_slot_of_a_vote_is_confirmed() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    // 98 is an older lockout, only the count 1 slots are new votes
+    let tx = make_vote_tx(signature(3), 103, &[(98, 2), (100, 1), (101, 1)]);
+
+    process_vote_transaction(tx.clone(), &account, &tracker).await.unwrap();
+    let mut confirmed = process_finalized_block(make_block(103, &[tx]), &account, &tracker).await.unwrap();
+    confirmed.sort_by_key(|vote| vote.voted_slot);
+
+    let latencies: Vec<_> = confirmed.iter().map(|vote| (vote.voted_slot, vote.latency)).collect();
+    assert_eq!(latencies, [(100, 3), (101, 2)]);
+    assert!(confirmed.iter().all(|vote| *vote.signature == signature_base58(3)));
+    assert_eq!(tracker.get_stats().await.unwrap().pending_votes, 0);
+    assert_eq!(credited(&confirmed).total_transactions(), 2);
+}
+
+#[tokio::test]
+async fn non_vote_transactions_are_ignored() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    let tx = make_non_vote_tx(signature(4), 101);
+
+    let processed = process_vote_transaction(tx.clone(), &account, &tracker).await.unwrap();
+    assert!(!processed.pending_added && !processed.foreign);
+    assert!(processed.confirmed.is_empty() && processed.failed.is_none());
+
+    let confirmed = process_finalized_block(make_block(101, &[tx]), &account, &tracker).await.unwrap();
+    assert!(confirmed.is_empty());
+    let stats = tracker.get_stats().await.unwrap();
+    assert_eq!((stats.pending_votes, stats.unmatched_confirmations), (0, 0));
+}
+
+#[tokio::test]
+async fn duplicate_block_is_skipped() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    let tx = make_vote_tx(signature(5), 101, &[(100, 1)]);
+    let block = make_block(101, std::slice::from_ref(&tx));
+
+    process_vote_transaction(tx, &account, &tracker).await.unwrap();
+    let first = process_finalized_block(block.clone(), &account, &tracker).await.unwrap();
+    assert_eq!(first.len(), 1);
+
+    // a second endpoint delivering the same block
+    let second = process_finalized_block(block, &account, &tracker).await.unwrap();
+    assert!(second.is_empty());
+    let stats = tracker.get_stats().await.unwrap();
+    assert_eq!(stats.duplicate_blocks, 1);
+    assert_eq!(stats.unmatched_confirmations, 0);
+}
+
+#[tokio::test]
+async fn unparsable_vote_instructions_are_counted_and_skipped() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    let unknown = [99u32.to_le_bytes().as_slice(), &[1, 2, 3, 4]].concat();
+    let vote = voteperfx::test_support::tower_sync_data(&[(100, 1)]);
+    // an unknown variant next to a valid vote in the same transaction
+    let tx = make_vote_program_tx(signature(6), 101, &[unknown, vote]);
+
+    let processed = process_vote_transaction(tx.clone(), &account, &tracker).await.unwrap();
+    assert!(processed.pending_added);
+    let confirmed = process_finalized_block(make_block(101, &[tx]), &account, &tracker).await.unwrap();
+    assert_eq!(confirmed.len(), 1);
+
+    // once from the transaction, once from its block
+    assert_eq!(tracker.get_stats().await.unwrap().parse_failures, 2);
+
+    // and the pipeline carries on with the next vote
+    let next = make_vote_tx(signature(7), 102, &[(101, 1)]);
+    process_vote_transaction(next.clone(), &account, &tracker).await.unwrap();
+    let confirmed = process_finalized_block(make_block(102, &[next]), &account, &tracker).await.unwrap();
+    assert_eq!(confirmed.len(), 1);
+}
+
+#[tokio::test]
+async fn authorize_transactions_add_no_pending_votes() {
+    use solana_sdk::vote::instruction as vote_instruction;
+    use solana_sdk::vote::state::VoteAuthorize;
+
+    let tracker = tracker();
+    let account = vote_account_key();
+    let authorize = vote_instruction::authorize(&VOTE_ACCOUNT, &Pubkey::new_unique(), &Pubkey::new_unique(), VoteAuthorize::Voter);
+    let tx = make_vote_program_tx(signature(8), 101, &[authorize.data]);
+
+    let processed = process_vote_transaction(tx, &account, &tracker).await.unwrap();
+    assert!(!processed.pending_added && processed.failed.is_none());
+    let stats = tracker.get_stats().await.unwrap();
+    assert_eq!((stats.pending_votes, stats.parse_failures), (0, 0));
+}
+
+#[tokio::test]
+async fn votes_of_another_account_are_ignored() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    let other = Pubkey::new_unique();
+    let foreign = make_vote_tx_for(&other, signature(9), 101, &[(100, 1)]);
+
+    let processed = process_vote_transaction(foreign.clone(), &account, &tracker).await.unwrap();
+    assert!(processed.foreign);
+    assert!(!processed.pending_added && processed.confirmed.is_empty());
+
+    // a block with both votes on the same slot only confirms ours
+    let ours = make_vote_tx(signature(10), 101, &[(100, 1)]);
+    process_vote_transaction(ours.clone(), &account, &tracker).await.unwrap();
+    let block = make_block(101, &[foreign, ours]);
+    assert_eq!(block_votes(&block, &account), [100]);
+    assert_eq!(block_votes(&block, &other.to_bytes()), [100]);
+
+    let confirmed = process_finalized_block(block, &account, &tracker).await.unwrap();
+    assert_eq!(confirmed.len(), 1);
+    assert_eq!(*confirmed[0].signature, signature_base58(10));
+    let stats = tracker.get_stats().await.unwrap();
+    // the foreign signature was never buffered as an early confirmation
+    assert_eq!((stats.pending_votes, stats.unmatched_confirmations, stats.duplicate_confirmations), (0, 0, 0));
+}
+
+#[tokio::test]
+async fn malformed_signatures_are_skipped() {
+    let tracker = tracker();
+    let account = vote_account_key();
+    for (slot, len) in (101..).zip([0, 32, 63, 65]) {
+        let tx = make_vote_tx(vec![7; len], slot, &[(slot - 1, 1)]);
+        let processed = process_vote_transaction(tx.clone(), &account, &tracker).await.unwrap();
+        assert!(!processed.pending_added && processed.confirmed.is_empty(), "{} bytes", len);
+        let confirmed = process_finalized_block(make_block(slot, &[tx]), &account, &tracker).await.unwrap();
+        assert!(confirmed.is_empty(), "{} bytes", len);
+    }
+
+    let stats = tracker.get_stats().await.unwrap();
+    // once from each transaction, once from each block
+    assert_eq!(stats.malformed_signatures, 8);
+    assert_eq!((stats.pending_votes, stats.unmatched_confirmations), (0, 0));
+}